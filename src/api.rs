@@ -0,0 +1,234 @@
+//! Read and change annatomic corpora without the graphical user interface.
+//!
+//! The corpora of annatomic are graphANNIS corpora that are stored in their
+//! own directory. [`CorpusFile`] opens such a directory, applies changesets
+//! to it and persists the result, so that scripts and tests can work with
+//! the same corpora as the editor. The editor uses the same
+//! [`CorpusCache`] and applies its changesets in the same way.
+//!
+//! ```no_run
+//! use annatomic::api::CorpusFile;
+//! use graphannis::update::{GraphUpdate, UpdateEvent};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut corpus = CorpusFile::open("/path/to/corpus")?;
+//! let mut update = GraphUpdate::new();
+//! for document in corpus.documents()? {
+//!     update.add_event(UpdateEvent::AddNodeLabel {
+//!         node_name: document,
+//!         anno_ns: "default_ns".to_string(),
+//!         anno_name: "status".to_string(),
+//!         anno_value: "checked".to_string(),
+//!     })?;
+//! }
+//! corpus.apply(update)?;
+//! corpus.persist()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use egui::mutex::RwLock;
+use graphannis::{model::AnnotationComponentType, update::GraphUpdate, AnnotationGraph};
+use graphannis_core::graph::NODE_NAME_KEY;
+
+use crate::app::{
+    job_executor::FgJob,
+    util::{corpus_structure::ordered_documents, metadata_csv},
+};
+use token_helper::{TokenHelper, TOKEN_KEY};
+
+mod cache;
+pub(crate) mod changeset;
+pub(crate) mod export_verification;
+pub(crate) mod graphml_export;
+pub(crate) mod import_report;
+pub(crate) mod provenance;
+pub mod token_helper;
+
+pub use cache::{load_graph, CorpusCache};
+
+/// Read a graph from GraphML. Returns the graph and the ANNIS configuration
+/// embedded in the file, if there is one.
+pub fn read_graphml<R, F>(input: R, progress: F) -> Result<(AnnotationGraph, Option<String>)>
+where
+    R: Read,
+    F: Fn(&str),
+{
+    let result =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            input, false, progress,
+        )?;
+    Ok(result)
+}
+
+/// Write the graph as GraphML. Nodes and edges are written in a stable
+/// order, so exporting an unchanged corpus results in the same file.
+pub fn write_graphml<W, F>(graph: &AnnotationGraph, output: W, progress: F) -> Result<()>
+where
+    W: Write,
+    F: Fn(&str),
+{
+    graphannis_core::graph::serialization::graphml::export_stable_order(
+        graph, None, output, progress,
+    )?;
+    Ok(())
+}
+
+/// Create an empty corpus at the location.
+pub(crate) fn create_corpus(location: &Path) -> Result<()> {
+    let mut graph = AnnotationGraph::with_default_graphstorages(false)?;
+    graph.persist_to(location)?;
+    Ok(())
+}
+
+/// A corpus that is stored in a directory on disk. The graph is held in a
+/// [`CorpusCache`] and changed in the same way as by the editor.
+pub struct CorpusFile {
+    location: PathBuf,
+    cache: CorpusCache,
+}
+
+impl CorpusFile {
+    /// Open the corpus stored at the location.
+    pub fn open<P: Into<PathBuf>>(location: P) -> Result<Self> {
+        let location = location.into();
+        let cache = CorpusCache::default();
+        cache.get(&location)?;
+        Ok(Self { location, cache })
+    }
+
+    /// Create an empty corpus at the location.
+    pub fn create<P: Into<PathBuf>>(location: P) -> Result<Self> {
+        let location = location.into();
+        create_corpus(&location)?;
+        Self::open(location)
+    }
+
+    /// Import a GraphML file and store it as new corpus at the location.
+    pub fn import_graphml<P: Into<PathBuf>>(input: &Path, location: P) -> Result<Self> {
+        let location = location.into();
+        let corpus_name = input
+            .file_stem()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        import_report::import_corpus(
+            BufReader::new(File::open(input)?),
+            &corpus_name,
+            &location,
+            None,
+            &FgJob::default(),
+        )?;
+        Self::open(location)
+    }
+
+    /// The directory the corpus is stored in.
+    pub fn location(&self) -> &Path {
+        &self.location
+    }
+
+    /// The graph of the corpus. Components are loaded on demand, use
+    /// `ensure_loaded_all` before accessing them directly. Changes that are
+    /// not made with [`CorpusFile::apply`] are not checked for conflicts.
+    pub fn graph(&self) -> Result<Arc<RwLock<AnnotationGraph>>> {
+        self.cache.get(&self.location)
+    }
+
+    /// Apply a changeset to the corpus. Events that are already part of the
+    /// corpus are skipped. The changes are only written to disk with
+    /// [`CorpusFile::persist`].
+    pub fn apply(&mut self, update: GraphUpdate) -> Result<()> {
+        let graph = self.graph()?;
+        let result =
+            changeset::apply_changeset(&mut graph.write(), update, None, &FgJob::default());
+        self.cache.invalidate();
+        result?;
+        Ok(())
+    }
+
+    /// Write all changes to the location of the corpus.
+    pub fn persist(&mut self) -> Result<()> {
+        self.cache.persist()
+    }
+
+    /// Export the corpus as GraphML file, or as ZIP file containing it if
+    /// the output ends with `.zip`.
+    pub fn export_graphml(&mut self, output: &Path) -> Result<()> {
+        let corpus_name = self
+            .location
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let graph = self.graph()?;
+        let mut graph = graph.write();
+        graph.ensure_loaded_all()?;
+        graphml_export::export_graph(&graph, &corpus_name, output, &FgJob::default())
+    }
+
+    /// The node names of all documents in the order of the corpus tree.
+    pub fn documents(&mut self) -> Result<Vec<String>> {
+        let graph = self.graph()?;
+        let mut graph = graph.write();
+        graph.ensure_loaded_all()?;
+        let node_annos = graph.get_node_annos();
+        let mut result = Vec::new();
+        for doc in ordered_documents(&graph)? {
+            let node_name = node_annos
+                .get_value_for_item(&doc, &NODE_NAME_KEY)?
+                .context("Missing node name")?;
+            result.push(node_name.to_string());
+        }
+        Ok(result)
+    }
+
+    /// The values of the token of a document in their order. If no
+    /// segmentation is given, the base token are used.
+    pub fn token_values(
+        &mut self,
+        document: &str,
+        segmentation: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let graph = self.graph()?;
+        let mut graph = graph.write();
+        graph.ensure_loaded_all()?;
+        let tok_helper = TokenHelper::new(&graph)?;
+        let node_annos = graph.get_node_annos();
+        let mut result = Vec::new();
+        for t in tok_helper.get_ordered_token(document, segmentation)? {
+            let value = node_annos.get_value_for_item(&t, &TOKEN_KEY)?;
+            result.push(value.unwrap_or_default().to_string());
+        }
+        Ok(result)
+    }
+
+    /// Write the metadata of all documents as CSV. Returns the number of
+    /// documents.
+    pub fn export_metadata_csv<W: Write>(&mut self, output: W) -> Result<usize> {
+        let graph = self.graph()?;
+        let mut graph = graph.write();
+        graph.ensure_loaded_all()?;
+        metadata_csv::write_metadata_csv(&graph, output)
+    }
+
+    /// Change the metadata of the documents to the values of a CSV file in
+    /// the format of [`CorpusFile::export_metadata_csv`].
+    pub fn import_metadata_csv<R: Read>(&mut self, input: R) -> Result<()> {
+        let update = {
+            let graph = self.graph()?;
+            let mut graph = graph.write();
+            graph.ensure_loaded_all()?;
+            metadata_csv::metadata_updates(&graph, input)?
+        };
+        self.apply(update)
+    }
+}
+
+#[cfg(test)]
+mod tests;
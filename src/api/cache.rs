@@ -0,0 +1,154 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{self, AtomicU64},
+        Arc,
+    },
+};
+
+use anyhow::{Context, Result};
+use egui::mutex::{Mutex, RwLock};
+use graphannis::AnnotationGraph;
+
+use crate::app::util::text_index::TextIndex;
+
+struct InnerCorpusCache {
+    location: PathBuf,
+    graph: Arc<RwLock<AnnotationGraph>>,
+}
+
+/// Holds the graph of one corpus in memory, so it does not need to be
+/// loaded for each access. Clones share the same cached graph.
+#[derive(Clone, Default)]
+pub struct CorpusCache {
+    inner: Arc<RwLock<Option<InnerCorpusCache>>>,
+    /// Approximate number of bytes used by the cached graph. This is kept
+    /// outside of `inner`, so it can be queried while a corpus is loading.
+    approximate_size: Arc<Mutex<Option<usize>>>,
+    /// Incremented each time the cached graph is replaced or changed.
+    generation: Arc<AtomicU64>,
+    /// Index for the text search, which is created on the first search.
+    text_index: Arc<Mutex<Option<Arc<TextIndex>>>>,
+}
+
+impl CorpusCache {
+    /// Get the graph of the corpus at the location. If another corpus is
+    /// cached, it is replaced without persisting it.
+    pub fn get(&self, location: &Path) -> Result<Arc<RwLock<AnnotationGraph>>> {
+        {
+            let mut inner = self.inner.write();
+
+            // Check if a cached version exist
+            if let Some(existing) = inner.as_mut() {
+                if existing.location == location {
+                    return Ok(existing.graph.clone());
+                } else {
+                    // Drop the annotation graph in background thread, so we can return faster
+                    let old_graph = inner.take();
+                    *self.approximate_size.lock() = None;
+                    self.invalidate();
+                    std::thread::spawn(move || std::mem::drop(old_graph));
+                }
+            }
+        }
+
+        // Load and return the graph
+        self.load_from_disk(location)
+    }
+
+    /// Load the graph of the corpus at the location, even if it is already
+    /// cached. Changes of the cached graph that are not persisted are lost.
+    pub fn load_from_disk(&self, corpus_location: &Path) -> Result<Arc<RwLock<AnnotationGraph>>> {
+        let mut inner = self.inner.write();
+        self.invalidate();
+
+        // Load and return the graph. The increase of the used memory of the
+        // process is the best estimation of the graph size we have.
+        let memory_before = physical_memory();
+        let graph = load_graph(corpus_location)?;
+        *self.approximate_size.lock() = Some(physical_memory().saturating_sub(memory_before));
+
+        let graph = Arc::new(RwLock::new(graph));
+
+        *inner = Some(InnerCorpusCache {
+            graph: graph.clone(),
+            location: corpus_location.to_path_buf(),
+        });
+        Ok(graph)
+    }
+
+    /// Approximate number of bytes the cached graph used when it was loaded
+    /// or `None` if no graph is cached.
+    pub fn approximate_size(&self) -> Option<usize> {
+        *self.approximate_size.lock()
+    }
+
+    /// Write the cached graph to its location.
+    pub fn persist(&self) -> Result<()> {
+        if let Some(existing) = self.inner.read().as_ref() {
+            existing.graph.write().persist_to(&existing.location)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the cached graph from memory. If `persist` is true, the graph
+    /// is persisted to its location before.
+    pub fn unload(&self, persist: bool) -> Result<()> {
+        let mut inner = self.inner.write();
+        if let Some(existing) = inner.as_ref() {
+            if persist {
+                // Keep the graph cached if it could not be persisted
+                existing.graph.write().persist_to(&existing.location)?;
+            }
+        }
+        *inner = None;
+        *self.approximate_size.lock() = None;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Discard all data derived from the cached graph, because the graph
+    /// has been changed or replaced.
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, atomic::Ordering::Relaxed);
+        *self.text_index.lock() = None;
+    }
+
+    /// Changes each time the cached graph is replaced or changed, so data
+    /// derived from an older version of the graph can be discarded.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Get the text search index of the corpus at the location and create
+    /// it if necessary.
+    pub(crate) fn text_index(&self, location: &Path) -> Result<Arc<TextIndex>> {
+        if let Some(index) = self.text_index.lock().clone() {
+            return Ok(index);
+        }
+        let graph = self.get(location)?;
+        let index = {
+            let mut graph = graph.write();
+            graph.ensure_loaded_all()?;
+            Arc::new(TextIndex::build(&graph)?)
+        };
+        *self.text_index.lock() = Some(index.clone());
+        Ok(index)
+    }
+}
+
+/// Load the graph of the corpus stored at the location without caching it.
+pub fn load_graph(location: &Path) -> Result<AnnotationGraph> {
+    let mut graph = AnnotationGraph::new(false)?;
+    graph
+        .import(location)
+        .with_context(|| format!("Could not open corpus at {}", location.display()))?;
+    Ok(graph)
+}
+
+/// Physical memory used by the current process in bytes.
+fn physical_memory() -> usize {
+    memory_stats::memory_stats()
+        .map(|stats| stats.physical_mem)
+        .unwrap_or_default()
+}
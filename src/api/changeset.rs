@@ -0,0 +1,62 @@
+use anyhow::Result;
+use graphannis::{
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+
+use super::provenance;
+use crate::app::job_executor::FgJob;
+
+pub(crate) mod chunks;
+pub(crate) mod conflicts;
+
+/// Apply a changeset to the graph and return the events that have been
+/// applied. Events that are already part of the graph are skipped. If an
+/// annotator stamp (namespace and initials) is given, all added and changed
+/// nodes are annotated with it.
+///
+/// Large changesets are applied in chunks, which can be cancelled. If they
+/// are rolled back, deleted nodes are re-created with new IDs, so data
+/// derived from the graph is outdated even if this fails.
+pub(crate) fn apply_changeset(
+    graph: &mut AnnotationGraph,
+    mut update: GraphUpdate,
+    annotator_stamp: Option<&(String, String)>,
+    job: &FgJob,
+) -> Result<Vec<UpdateEvent>> {
+    job.update_message("Storing update events");
+    let mut events = Vec::with_capacity(update.len()?);
+    for event in update.iter()? {
+        let event = event?;
+        events.push(event.1);
+    }
+    job.update_message("Checking for conflicts");
+    let number_of_events = events.len();
+    let mut added_events = conflicts::rebase_events(graph, events)?;
+    if added_events.len() != number_of_events {
+        log::info!(
+            "Skipped {} update events that are already applied",
+            number_of_events - added_events.len()
+        );
+        update = GraphUpdate::new();
+        for event in added_events.iter() {
+            update.add_event(event.clone())?;
+        }
+    }
+    if let Some((ns, initials)) = annotator_stamp {
+        for stamp in provenance::annotator_stamps(&added_events, ns, initials) {
+            update.add_event(stamp.clone())?;
+            added_events.push(stamp);
+        }
+    }
+    job.update_message("Applying updates");
+    if added_events.len() > chunks::CHUNK_SIZE {
+        job.allow_cancel();
+        chunks::apply_in_chunks(graph, &added_events, chunks::CHUNK_SIZE, job)?;
+    } else {
+        graph.apply_update_keep_statistics(&mut update, |msg| {
+            job.update_message(format!("Applying updates: {msg}"))
+        })?;
+    }
+    Ok(added_events)
+}
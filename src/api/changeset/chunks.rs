@@ -0,0 +1,655 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use graphannis::{
+    graph::AnnoKey,
+    model::AnnotationComponent,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::{
+    graph::{storage::GraphStorage, NODE_NAME_KEY, NODE_TYPE_KEY},
+    types::{Edge, NodeID},
+};
+
+use crate::app::job_executor::FgJob;
+
+/// Changesets with more update events are applied in chunks of this size.
+pub(crate) const CHUNK_SIZE: usize = 50_000;
+
+/// Apply the events in chunks of `chunk_size` and report the progress after
+/// each chunk. Instead of recalculating all statistics of the graph, only
+/// the statistics of the components and annotations changed by a chunk are
+/// updated after applying it.
+///
+/// The job can be cancelled between two chunks. If it is cancelled or a
+/// chunk fails, the already applied chunks are rolled back by their inverse
+/// events and the graph has the same content as before.
+pub(crate) fn apply_in_chunks(
+    graph: &mut AnnotationGraph,
+    events: &[UpdateEvent],
+    chunk_size: usize,
+    job: &FgJob,
+) -> Result<()> {
+    // Deleting a node needs all of its edges to create the inverse events
+    graph.ensure_loaded_all()?;
+    let mut inverse_chunks = Vec::new();
+    if let Err(e) = apply_chunks(graph, events, chunk_size, job, &mut inverse_chunks) {
+        if !inverse_chunks.is_empty() {
+            job.update_message("Rolling back applied updates");
+            roll_back(graph, &inverse_chunks)?;
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Apply the inverse events of the applied chunks, the latest chunk first.
+fn roll_back(graph: &mut AnnotationGraph, inverse_chunks: &[Vec<UpdateEvent>]) -> Result<()> {
+    for inverse in inverse_chunks.iter().rev() {
+        apply_events(graph, inverse, |_| {}).context("Could not roll back the applied updates")?;
+        update_statistics(graph, inverse, &[])?;
+    }
+    Ok(())
+}
+
+fn apply_chunks(
+    graph: &mut AnnotationGraph,
+    events: &[UpdateEvent],
+    chunk_size: usize,
+    job: &FgJob,
+    inverse_chunks: &mut Vec<Vec<UpdateEvent>>,
+) -> Result<()> {
+    let number_of_chunks = events.len().div_ceil(chunk_size);
+    for (idx, chunk) in events.chunks(chunk_size).enumerate() {
+        job.check_cancelled()?;
+        job.update_message(format!(
+            "Applying updates {}/{}",
+            (idx * chunk_size) + chunk.len(),
+            events.len()
+        ));
+        // The inverse events restore the state before the chunk, even if
+        // applying it fails halfway
+        inverse_chunks.push(inverse_events(graph, chunk)?);
+        apply_events(graph, chunk, |msg| {
+            job.update_message(format!(
+                "Applying updates (chunk {}/{number_of_chunks}): {msg}",
+                idx + 1
+            ))
+        })?;
+        let inverse = inverse_chunks.last().map(Vec::as_slice).unwrap_or_default();
+        update_statistics(graph, chunk, inverse)?;
+    }
+    Ok(())
+}
+
+/// Recalculate the statistics changed by the applied events. The inverse
+/// events are needed, because deleting a node also deletes its edges, which
+/// are only part of the inverse events.
+fn update_statistics(
+    graph: &mut AnnotationGraph,
+    events: &[UpdateEvent],
+    inverse: &[UpdateEvent],
+) -> Result<()> {
+    let mut node_annos_changed = false;
+    let mut components = HashSet::new();
+    for event in events.iter().chain(inverse) {
+        match event {
+            UpdateEvent::AddNode { .. }
+            | UpdateEvent::DeleteNode { .. }
+            | UpdateEvent::AddNodeLabel { .. }
+            | UpdateEvent::DeleteNodeLabel { .. } => node_annos_changed = true,
+            UpdateEvent::AddEdge {
+                layer,
+                component_type,
+                component_name,
+                ..
+            }
+            | UpdateEvent::DeleteEdge {
+                layer,
+                component_type,
+                component_name,
+                ..
+            }
+            | UpdateEvent::AddEdgeLabel {
+                layer,
+                component_type,
+                component_name,
+                ..
+            }
+            | UpdateEvent::DeleteEdgeLabel {
+                layer,
+                component_type,
+                component_name,
+                ..
+            } => {
+                components.insert((
+                    layer.as_str(),
+                    component_type.as_str(),
+                    component_name.as_str(),
+                ));
+            }
+        }
+    }
+    if node_annos_changed {
+        graph.get_node_annos_mut().calculate_statistics()?;
+    }
+    for (layer, component_type, component_name) in components {
+        if let Some(component) = find_component(graph, layer, component_type, component_name) {
+            graph.calculate_component_statistics(&component)?;
+        }
+    }
+    Ok(())
+}
+
+fn apply_events<F>(graph: &mut AnnotationGraph, events: &[UpdateEvent], progress: F) -> Result<()>
+where
+    F: Fn(&str),
+{
+    let mut update = GraphUpdate::new();
+    for event in events {
+        update.add_event(event.clone())?;
+    }
+    graph.apply_update_keep_statistics(&mut update, progress)?;
+    Ok(())
+}
+
+/// Create the events that undo the given events. They must be computed
+/// before the events are applied to the graph and are in reverse order, so
+/// the earliest change is undone last.
+///
+/// Each inverse event is created for the state of the graph right before its
+/// event, including the changes of all earlier events, e.g. a node that is
+/// deleted and added again is deleted before the old node is restored.
+pub(crate) fn inverse_events(
+    graph: &AnnotationGraph,
+    events: &[UpdateEvent],
+) -> Result<Vec<UpdateEvent>> {
+    let mut state = ChunkState::new(graph);
+    let mut result = Vec::new();
+    for event in events {
+        // The inverse of a single event is reversed as a whole later, so
+        // create it in reverse order as well
+        let mut inverse = Vec::new();
+        match event {
+            UpdateEvent::AddNode {
+                node_name,
+                node_type,
+            } => {
+                if !state.node_exists(node_name)? {
+                    inverse.push(UpdateEvent::DeleteNode {
+                        node_name: node_name.clone(),
+                    });
+                    state
+                        .nodes
+                        .insert(node_name.clone(), Some(node_type.clone()));
+                }
+            }
+            UpdateEvent::DeleteNode { node_name } => match state.nodes.get(node_name) {
+                Some(Some(node_type)) => {
+                    // The node has been created by an earlier event, which
+                    // is undone by deleting it again
+                    inverse.push(UpdateEvent::AddNode {
+                        node_name: node_name.clone(),
+                        node_type: node_type.clone(),
+                    });
+                    state.delete_node(node_name);
+                }
+                Some(None) => {}
+                None => {
+                    if let Some(node) = node_id(graph, node_name)? {
+                        // Changes of earlier events are undone after the
+                        // node has been restored in its original state
+                        restore_node(graph, node, node_name, &mut inverse)?;
+                        inverse.reverse();
+                        state.delete_node(node_name);
+                    }
+                }
+            },
+            UpdateEvent::AddNodeLabel {
+                node_name,
+                anno_ns,
+                anno_name,
+                ..
+            }
+            | UpdateEvent::DeleteNodeLabel {
+                node_name,
+                anno_ns,
+                anno_name,
+            } => {
+                let key = AnnoKey {
+                    ns: anno_ns.as_str().into(),
+                    name: anno_name.as_str().into(),
+                };
+                if state.node_exists(node_name)? {
+                    inverse.push(match state.node_label(node_name, &key)? {
+                        Some(value) => UpdateEvent::AddNodeLabel {
+                            node_name: node_name.clone(),
+                            anno_ns: anno_ns.clone(),
+                            anno_name: anno_name.clone(),
+                            anno_value: value,
+                        },
+                        None => UpdateEvent::DeleteNodeLabel {
+                            node_name: node_name.clone(),
+                            anno_ns: anno_ns.clone(),
+                            anno_name: anno_name.clone(),
+                        },
+                    });
+                    let new_value = match event {
+                        UpdateEvent::AddNodeLabel { anno_value, .. } => Some(anno_value.clone()),
+                        _ => None,
+                    };
+                    state
+                        .node_labels
+                        .entry(node_name.clone())
+                        .or_default()
+                        .insert(key, new_value);
+                }
+            }
+            UpdateEvent::AddEdge {
+                source_node,
+                target_node,
+                layer,
+                component_type,
+                component_name,
+            } => {
+                let edge = EdgeKey {
+                    source_node: source_node.clone(),
+                    target_node: target_node.clone(),
+                    layer: layer.clone(),
+                    component_type: component_type.clone(),
+                    component_name: component_name.clone(),
+                };
+                if state.node_exists(source_node)?
+                    && state.node_exists(target_node)?
+                    && !state.edge_exists(&edge)?
+                {
+                    inverse.push(edge.delete_event());
+                    state.set_edge(edge, true);
+                }
+            }
+            UpdateEvent::DeleteEdge {
+                source_node,
+                target_node,
+                layer,
+                component_type,
+                component_name,
+            } => {
+                let edge = EdgeKey {
+                    source_node: source_node.clone(),
+                    target_node: target_node.clone(),
+                    layer: layer.clone(),
+                    component_type: component_type.clone(),
+                    component_name: component_name.clone(),
+                };
+                if state.edge_exists(&edge)? {
+                    match state.original_edge(&edge)? {
+                        Some((component, original)) => {
+                            restore_edge(graph, &component, original, &mut inverse)?;
+                            inverse.reverse();
+                        }
+                        None => inverse.push(edge.add_event()),
+                    }
+                    state.set_edge(edge, false);
+                }
+            }
+            UpdateEvent::AddEdgeLabel {
+                source_node,
+                target_node,
+                layer,
+                component_type,
+                component_name,
+                anno_ns,
+                anno_name,
+                ..
+            }
+            | UpdateEvent::DeleteEdgeLabel {
+                source_node,
+                target_node,
+                layer,
+                component_type,
+                component_name,
+                anno_ns,
+                anno_name,
+            } => {
+                let edge = EdgeKey {
+                    source_node: source_node.clone(),
+                    target_node: target_node.clone(),
+                    layer: layer.clone(),
+                    component_type: component_type.clone(),
+                    component_name: component_name.clone(),
+                };
+                let key = AnnoKey {
+                    ns: anno_ns.as_str().into(),
+                    name: anno_name.as_str().into(),
+                };
+                if state.edge_exists(&edge)? {
+                    inverse.push(match state.edge_label(&edge, &key)? {
+                        Some(anno_value) => UpdateEvent::AddEdgeLabel {
+                            source_node: source_node.clone(),
+                            target_node: target_node.clone(),
+                            layer: layer.clone(),
+                            component_type: component_type.clone(),
+                            component_name: component_name.clone(),
+                            anno_ns: anno_ns.clone(),
+                            anno_name: anno_name.clone(),
+                            anno_value,
+                        },
+                        None => UpdateEvent::DeleteEdgeLabel {
+                            source_node: source_node.clone(),
+                            target_node: target_node.clone(),
+                            layer: layer.clone(),
+                            component_type: component_type.clone(),
+                            component_name: component_name.clone(),
+                            anno_ns: anno_ns.clone(),
+                            anno_name: anno_name.clone(),
+                        },
+                    });
+                    let new_value = match event {
+                        UpdateEvent::AddEdgeLabel { anno_value, .. } => Some(anno_value.clone()),
+                        _ => None,
+                    };
+                    state
+                        .edge_labels
+                        .entry(edge)
+                        .or_default()
+                        .insert(key, new_value);
+                }
+            }
+        }
+        result.extend(inverse);
+    }
+    result.reverse();
+    Ok(result)
+}
+
+/// Identifies an edge by the fields of its update events.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct EdgeKey {
+    source_node: String,
+    target_node: String,
+    layer: String,
+    component_type: String,
+    component_name: String,
+}
+
+impl EdgeKey {
+    fn add_event(&self) -> UpdateEvent {
+        UpdateEvent::AddEdge {
+            source_node: self.source_node.clone(),
+            target_node: self.target_node.clone(),
+            layer: self.layer.clone(),
+            component_type: self.component_type.clone(),
+            component_name: self.component_name.clone(),
+        }
+    }
+
+    fn delete_event(&self) -> UpdateEvent {
+        UpdateEvent::DeleteEdge {
+            source_node: self.source_node.clone(),
+            target_node: self.target_node.clone(),
+            layer: self.layer.clone(),
+            component_type: self.component_type.clone(),
+            component_name: self.component_name.clone(),
+        }
+    }
+}
+
+/// The graph with the changes of the events of a chunk that have already
+/// been inverted. Only the changed nodes, edges and annotations are stored,
+/// everything else is looked up in the unchanged graph.
+struct ChunkState<'a> {
+    graph: &'a AnnotationGraph,
+    /// Nodes that have been added (with their type) or deleted (`None`).
+    /// None of the labels and edges of these nodes are part of the graph.
+    nodes: HashMap<String, Option<String>>,
+    /// Changed labels of existing nodes, `None` if the label is deleted.
+    node_labels: HashMap<String, HashMap<AnnoKey, Option<String>>>,
+    /// Edges that have been added (`true`) or deleted (`false`).
+    edges: HashMap<EdgeKey, bool>,
+    /// Changed labels of existing edges, `None` if the label is deleted.
+    edge_labels: HashMap<EdgeKey, HashMap<AnnoKey, Option<String>>>,
+    /// The changed edges by their source and target node, so they can be
+    /// removed when one of the nodes is deleted.
+    edges_by_node: HashMap<String, Vec<EdgeKey>>,
+}
+
+impl<'a> ChunkState<'a> {
+    fn new(graph: &'a AnnotationGraph) -> Self {
+        Self {
+            graph,
+            nodes: HashMap::new(),
+            node_labels: HashMap::new(),
+            edges: HashMap::new(),
+            edge_labels: HashMap::new(),
+            edges_by_node: HashMap::new(),
+        }
+    }
+
+    fn node_exists(&self, node_name: &str) -> Result<bool> {
+        match self.nodes.get(node_name) {
+            Some(node_type) => Ok(node_type.is_some()),
+            None => Ok(node_id(self.graph, node_name)?.is_some()),
+        }
+    }
+
+    fn node_label(&self, node_name: &str, key: &AnnoKey) -> Result<Option<String>> {
+        if let Some(value) = self.node_labels.get(node_name).and_then(|l| l.get(key)) {
+            return Ok(value.clone());
+        }
+        if self.nodes.contains_key(node_name) {
+            return Ok(None);
+        }
+        let value = match node_id(self.graph, node_name)? {
+            Some(node) => self
+                .graph
+                .get_node_annos()
+                .get_value_for_item(&node, key)?
+                .map(|v| v.to_string()),
+            None => None,
+        };
+        Ok(value)
+    }
+
+    /// The component and edge in the unchanged graph, if the edge has not
+    /// been added or deleted by an earlier event.
+    fn original_edge(&self, edge: &EdgeKey) -> Result<Option<(AnnotationComponent, Edge)>> {
+        if self.edges.contains_key(edge)
+            || self.nodes.contains_key(&edge.source_node)
+            || self.nodes.contains_key(&edge.target_node)
+        {
+            return Ok(None);
+        }
+        let Some(component) = find_component(
+            self.graph,
+            &edge.layer,
+            &edge.component_type,
+            &edge.component_name,
+        ) else {
+            return Ok(None);
+        };
+        let original = existing_edge(
+            self.graph,
+            Some(&component),
+            &edge.source_node,
+            &edge.target_node,
+        )?;
+        Ok(original.map(|original| (component, original)))
+    }
+
+    fn edge_exists(&self, edge: &EdgeKey) -> Result<bool> {
+        match self.edges.get(edge) {
+            Some(exists) => Ok(*exists),
+            None => Ok(self.original_edge(edge)?.is_some()),
+        }
+    }
+
+    fn edge_label(&self, edge: &EdgeKey, key: &AnnoKey) -> Result<Option<String>> {
+        if let Some(value) = self.edge_labels.get(edge).and_then(|l| l.get(key)) {
+            return Ok(value.clone());
+        }
+        let Some((component, original)) = self.original_edge(edge)? else {
+            return Ok(None);
+        };
+        let value = match self.graph.get_graphstorage_as_ref(&component) {
+            Some(gs) => gs
+                .get_anno_storage()
+                .get_value_for_item(&original, key)?
+                .map(|v| v.to_string()),
+            None => None,
+        };
+        Ok(value)
+    }
+
+    fn set_edge(&mut self, edge: EdgeKey, exists: bool) {
+        self.edge_labels.remove(&edge);
+        for node_name in [&edge.source_node, &edge.target_node] {
+            self.edges_by_node
+                .entry(node_name.clone())
+                .or_default()
+                .push(edge.clone());
+        }
+        self.edges.insert(edge, exists);
+    }
+
+    /// Remember that the node has been deleted together with its labels
+    /// and edges.
+    fn delete_node(&mut self, node_name: &str) {
+        self.nodes.insert(node_name.to_string(), None);
+        self.node_labels.remove(node_name);
+        for edge in self.edges_by_node.remove(node_name).unwrap_or_default() {
+            self.edge_labels.remove(&edge);
+            self.edges.remove(&edge);
+        }
+    }
+}
+
+fn node_id(graph: &AnnotationGraph, node_name: &str) -> Result<Option<NodeID>> {
+    let id = graph.get_node_annos().get_node_id_from_name(node_name)?;
+    Ok(id)
+}
+
+fn node_name(graph: &AnnotationGraph, node: NodeID) -> Result<String> {
+    let name = graph
+        .get_node_annos()
+        .get_value_for_item(&node, &NODE_NAME_KEY)?
+        .context("Missing node name")?;
+    Ok(name.to_string())
+}
+
+/// The existing component with the type, layer and name of an update event.
+fn find_component(
+    graph: &AnnotationGraph,
+    layer: &str,
+    component_type: &str,
+    component_name: &str,
+) -> Option<AnnotationComponent> {
+    graph.get_all_components(None, None).into_iter().find(|c| {
+        c.get_type().to_string() == component_type && c.layer == layer && c.name == component_name
+    })
+}
+
+fn existing_edge(
+    graph: &AnnotationGraph,
+    component: Option<&AnnotationComponent>,
+    source_node: &str,
+    target_node: &str,
+) -> Result<Option<Edge>> {
+    let (Some(source), Some(target), Some(gs)) = (
+        node_id(graph, source_node)?,
+        node_id(graph, target_node)?,
+        component.and_then(|c| graph.get_graphstorage_as_ref(c)),
+    ) else {
+        return Ok(None);
+    };
+    for t in gs.get_outgoing_edges(source) {
+        if t? == target {
+            return Ok(Some(Edge { source, target }));
+        }
+    }
+    Ok(None)
+}
+
+/// Add the events that create the node with all its annotations and edges.
+fn restore_node(
+    graph: &AnnotationGraph,
+    node: NodeID,
+    node_name: &str,
+    result: &mut Vec<UpdateEvent>,
+) -> Result<()> {
+    let node_annos = graph.get_node_annos();
+    let node_type = node_annos
+        .get_value_for_item(&node, &NODE_TYPE_KEY)?
+        .unwrap_or_default();
+    result.push(UpdateEvent::AddNode {
+        node_name: node_name.to_string(),
+        node_type: node_type.to_string(),
+    });
+    for anno in node_annos.get_annotations_for_item(&node)? {
+        if &anno.key == NODE_NAME_KEY.as_ref() || &anno.key == NODE_TYPE_KEY.as_ref() {
+            continue;
+        }
+        result.push(UpdateEvent::AddNodeLabel {
+            node_name: node_name.to_string(),
+            anno_ns: anno.key.ns.to_string(),
+            anno_name: anno.key.name.to_string(),
+            anno_value: anno.val.to_string(),
+        });
+    }
+    for component in graph.get_all_components(None, None) {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&component) {
+            let outgoing = gs.get_outgoing_edges(node).map(|target| {
+                target.map(|target| Edge {
+                    source: node,
+                    target,
+                })
+            });
+            let ingoing = gs.get_ingoing_edges(node).map(|source| {
+                source.map(|source| Edge {
+                    source,
+                    target: node,
+                })
+            });
+            for edge in outgoing.chain(ingoing) {
+                restore_edge(graph, &component, edge?, result)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Add the events that create the edge with all its annotations.
+fn restore_edge(
+    graph: &AnnotationGraph,
+    component: &AnnotationComponent,
+    edge: Edge,
+    result: &mut Vec<UpdateEvent>,
+) -> Result<()> {
+    let source_node = node_name(graph, edge.source)?;
+    let target_node = node_name(graph, edge.target)?;
+    result.push(UpdateEvent::AddEdge {
+        source_node: source_node.clone(),
+        target_node: target_node.clone(),
+        layer: component.layer.to_string(),
+        component_type: component.get_type().to_string(),
+        component_name: component.name.to_string(),
+    });
+    if let Some(gs) = graph.get_graphstorage_as_ref(component) {
+        for anno in gs.get_anno_storage().get_annotations_for_item(&edge)? {
+            result.push(UpdateEvent::AddEdgeLabel {
+                source_node: source_node.clone(),
+                target_node: target_node.clone(),
+                layer: component.layer.to_string(),
+                component_type: component.get_type().to_string(),
+                component_name: component.name.to_string(),
+                anno_ns: anno.key.ns.to_string(),
+                anno_name: anno.key.name.to_string(),
+                anno_value: anno.val.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
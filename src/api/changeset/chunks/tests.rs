@@ -0,0 +1,261 @@
+use graphannis::{
+    graph::AnnoKey,
+    model::{AnnotationComponent, AnnotationComponentType},
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::graph::ANNIS_NS;
+use pretty_assertions::assert_eq;
+
+use crate::{
+    api::export_verification::compare_graphs,
+    app::{
+        job_executor::{FgJob, JobCancelled},
+        util::example_generator,
+    },
+};
+
+use super::{apply_chunks, apply_in_chunks, inverse_events, roll_back};
+
+fn apply(graph: &mut AnnotationGraph, events: &[UpdateEvent]) {
+    let mut updates = GraphUpdate::new();
+    for e in events {
+        updates.add_event(e.clone()).unwrap();
+    }
+    graph.apply_update(&mut updates, |_msg| {}).unwrap();
+}
+
+fn token_value(graph: &AnnotationGraph, node_name: &str) -> Option<String> {
+    let node_annos = graph.get_node_annos();
+    let node = node_annos.get_node_id_from_name(node_name).unwrap()?;
+    node_annos
+        .get_value_for_item(
+            &node,
+            &AnnoKey {
+                ns: ANNIS_NS.into(),
+                name: "tok".into(),
+            },
+        )
+        .unwrap()
+        .map(|v| v.to_string())
+}
+
+fn changes() -> Vec<UpdateEvent> {
+    vec![
+        UpdateEvent::DeleteNode {
+            node_name: "root/doc1#tok1".to_string(),
+        },
+        UpdateEvent::AddNodeLabel {
+            node_name: "root/doc1#tok2".to_string(),
+            anno_ns: ANNIS_NS.to_string(),
+            anno_name: "tok".to_string(),
+            anno_value: "changed".to_string(),
+        },
+        UpdateEvent::AddNode {
+            node_name: "root/doc1#new".to_string(),
+            node_type: "node".to_string(),
+        },
+        UpdateEvent::AddEdge {
+            source_node: "root/doc1#new".to_string(),
+            target_node: "root/doc1#tok3".to_string(),
+            layer: "default_ns".to_string(),
+            component_type: "Pointing".to_string(),
+            component_name: "dep".to_string(),
+        },
+        UpdateEvent::AddEdgeLabel {
+            source_node: "root/doc1#new".to_string(),
+            target_node: "root/doc1#tok3".to_string(),
+            layer: "default_ns".to_string(),
+            component_type: "Pointing".to_string(),
+            component_name: "dep".to_string(),
+            anno_ns: "default_ns".to_string(),
+            anno_name: "func".to_string(),
+            anno_value: "obj".to_string(),
+        },
+        UpdateEvent::DeleteEdge {
+            source_node: "root/doc1#tok3".to_string(),
+            target_node: "root/doc1#tok4".to_string(),
+            layer: ANNIS_NS.to_string(),
+            component_type: "Ordering".to_string(),
+            component_name: "".to_string(),
+        },
+    ]
+}
+
+#[test]
+fn inverse_events_restore_graph() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let original = example_generator::create_graph(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+    let events = changes();
+
+    let inverse = inverse_events(&graph, &events).unwrap();
+    apply(&mut graph, &events);
+    assert_eq!(None, token_value(&graph, "root/doc1#tok1"));
+    assert_eq!(
+        Some("changed".to_string()),
+        token_value(&graph, "root/doc1#tok2")
+    );
+    assert!(!compare_graphs(&original, &graph).unwrap().is_empty());
+
+    apply(&mut graph, &inverse);
+    assert_eq!(
+        Some("this".to_string()),
+        token_value(&graph, "root/doc1#tok1")
+    );
+    assert_eq!(
+        Some("example".to_string()),
+        token_value(&graph, "root/doc1#tok2")
+    );
+    assert!(compare_graphs(&original, &graph).unwrap().is_empty());
+}
+
+#[test]
+fn apply_changes_in_chunks() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let mut expected = example_generator::create_graph(&mut updates);
+    apply(&mut expected, &changes());
+
+    let mut graph = example_generator::create_graph(&mut updates);
+    apply_in_chunks(&mut graph, &changes(), 2, &FgJob::default()).unwrap();
+    assert!(compare_graphs(&expected, &graph).unwrap().is_empty());
+
+    // The statistics of the new component have been calculated
+    let dep = AnnotationComponent::new(
+        AnnotationComponentType::Pointing,
+        "default_ns".into(),
+        "dep".into(),
+    );
+    let stats = graph
+        .get_graphstorage_as_ref(&dep)
+        .and_then(|gs| gs.get_statistics().cloned())
+        .unwrap();
+    assert_eq!(2, stats.nodes);
+}
+
+#[test]
+fn cancelled_chunks_are_not_applied() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let original = example_generator::create_graph(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+    let job = FgJob::default();
+    job.cancel();
+
+    let result = apply_in_chunks(&mut graph, &changes(), 2, &job);
+    assert!(result.unwrap_err().is::<JobCancelled>());
+    assert!(compare_graphs(&original, &graph).unwrap().is_empty());
+}
+
+/// Events that change the same nodes and labels several times.
+fn repeated_changes() -> Vec<UpdateEvent> {
+    let tok_label = |node_name: &str, value: Option<&str>| match value {
+        Some(value) => UpdateEvent::AddNodeLabel {
+            node_name: node_name.to_string(),
+            anno_ns: ANNIS_NS.to_string(),
+            anno_name: "tok".to_string(),
+            anno_value: value.to_string(),
+        },
+        None => UpdateEvent::DeleteNodeLabel {
+            node_name: node_name.to_string(),
+            anno_ns: ANNIS_NS.to_string(),
+            anno_name: "tok".to_string(),
+        },
+    };
+    let pointing_edge = UpdateEvent::AddEdge {
+        source_node: "root/doc1#tok1".to_string(),
+        target_node: "root/doc1#tok2".to_string(),
+        layer: "default_ns".to_string(),
+        component_type: "Pointing".to_string(),
+        component_name: "dep".to_string(),
+    };
+    vec![
+        // Replace a node by a new one with the same name
+        UpdateEvent::DeleteNode {
+            node_name: "root/doc1#tok1".to_string(),
+        },
+        UpdateEvent::AddNode {
+            node_name: "root/doc1#tok1".to_string(),
+            node_type: "node".to_string(),
+        },
+        tok_label("root/doc1#tok1", Some("new")),
+        UpdateEvent::AddNodeLabel {
+            node_name: "root/doc1#tok1".to_string(),
+            anno_ns: "default_ns".to_string(),
+            anno_name: "pos".to_string(),
+            anno_value: "NN".to_string(),
+        },
+        pointing_edge.clone(),
+        // Change the same label several times
+        tok_label("root/doc1#tok2", Some("a")),
+        tok_label("root/doc1#tok2", None),
+        tok_label("root/doc1#tok2", Some("b")),
+        // Add an edge that is deleted again
+        UpdateEvent::DeleteEdge {
+            source_node: "root/doc1#tok1".to_string(),
+            target_node: "root/doc1#tok2".to_string(),
+            layer: "default_ns".to_string(),
+            component_type: "Pointing".to_string(),
+            component_name: "dep".to_string(),
+        },
+        pointing_edge,
+        // Delete a node that has been added in the same chunk
+        UpdateEvent::AddNode {
+            node_name: "root/doc1#temp".to_string(),
+            node_type: "node".to_string(),
+        },
+        tok_label("root/doc1#temp", Some("temp")),
+        UpdateEvent::DeleteNode {
+            node_name: "root/doc1#temp".to_string(),
+        },
+    ]
+}
+
+#[test]
+fn roll_back_repeated_changes() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let original = example_generator::create_graph(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+    let events = repeated_changes();
+
+    let mut inverse_chunks = Vec::new();
+    apply_chunks(
+        &mut graph,
+        &events,
+        events.len(),
+        &FgJob::default(),
+        &mut inverse_chunks,
+    )
+    .unwrap();
+    assert_eq!(1, inverse_chunks.len());
+    assert_eq!(
+        Some("new".to_string()),
+        token_value(&graph, "root/doc1#tok1")
+    );
+    assert_eq!(Some("b".to_string()), token_value(&graph, "root/doc1#tok2"));
+
+    // Cancelling after the chunk restores the original graph, without any
+    // label of the replaced node
+    roll_back(&mut graph, &inverse_chunks).unwrap();
+    assert_eq!(
+        Some("this".to_string()),
+        token_value(&graph, "root/doc1#tok1")
+    );
+    assert_eq!(
+        Some("example".to_string()),
+        token_value(&graph, "root/doc1#tok2")
+    );
+    assert_eq!(None, token_value(&graph, "root/doc1#temp"));
+    assert!(compare_graphs(&original, &graph).unwrap().is_empty());
+}
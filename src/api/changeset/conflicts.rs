@@ -0,0 +1,184 @@
+use std::{collections::HashSet, fmt::Display};
+
+use anyhow::Result;
+use graphannis::{update::UpdateEvent, AnnotationGraph};
+
+/// Maximum number of conflicts listed in the error message.
+const MAX_REPORTED_CONFLICTS: usize = 5;
+
+/// An event of a changeset that refers to a node that does not exist
+/// (anymore), e.g. because an earlier changeset deleted it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Conflict {
+    pub(crate) node_name: String,
+    /// Description of the change that could not be applied.
+    pub(crate) change: String,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cannot {}: node {} does not exist anymore",
+            self.change, self.node_name
+        )
+    }
+}
+
+/// Error for a changeset that was not applied because of conflicts with the
+/// current graph.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChangesetConflicts(pub(crate) Vec<Conflict>);
+
+impl Display for ChangesetConflicts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The changes conflict with the current state of the corpus and have not been applied."
+        )?;
+        for c in self.0.iter().take(MAX_REPORTED_CONFLICTS) {
+            write!(f, "\n{c}")?;
+        }
+        if self.0.len() > MAX_REPORTED_CONFLICTS {
+            write!(f, "\n… and {} more", self.0.len() - MAX_REPORTED_CONFLICTS)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ChangesetConflicts {}
+
+/// Tracks which nodes exist while the events of a changeset are checked.
+struct NodeState<'a> {
+    graph: &'a AnnotationGraph,
+    added: HashSet<String>,
+    deleted: HashSet<String>,
+}
+
+impl NodeState<'_> {
+    fn exists(&self, node_name: &str) -> Result<bool> {
+        if self.deleted.contains(node_name) {
+            Ok(false)
+        } else if self.added.contains(node_name) {
+            Ok(true)
+        } else {
+            Ok(self
+                .graph
+                .get_node_annos()
+                .get_node_id_from_name(node_name)?
+                .is_some())
+        }
+    }
+
+    /// The first of the given nodes that does not exist.
+    fn first_missing<'b>(&self, node_names: &[&'b String]) -> Result<Option<&'b String>> {
+        for n in node_names {
+            if !self.exists(n)? {
+                return Ok(Some(n));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Validate the events of a changeset against the current graph before they
+/// are applied.
+///
+/// Events that only delete something that does not exist anymore are
+/// dropped, since the graph already is in the requested state. Events that
+/// add annotations or edges to missing nodes can not be re-based and are
+/// returned as [`ChangesetConflicts`] error.
+pub(crate) fn rebase_events(
+    graph: &AnnotationGraph,
+    events: Vec<UpdateEvent>,
+) -> Result<Vec<UpdateEvent>> {
+    let mut state = NodeState {
+        graph,
+        added: HashSet::new(),
+        deleted: HashSet::new(),
+    };
+    let mut conflicts = Vec::new();
+    let mut result = Vec::with_capacity(events.len());
+    for event in events {
+        let keep = match &event {
+            UpdateEvent::AddNode { node_name, .. } => {
+                state.deleted.remove(node_name);
+                state.added.insert(node_name.clone());
+                true
+            }
+            UpdateEvent::DeleteNode { node_name } => {
+                let exists = state.exists(node_name)?;
+                state.added.remove(node_name);
+                state.deleted.insert(node_name.clone());
+                exists
+            }
+            UpdateEvent::DeleteNodeLabel { node_name, .. } => state.exists(node_name)?,
+            UpdateEvent::DeleteEdge {
+                source_node,
+                target_node,
+                ..
+            }
+            | UpdateEvent::DeleteEdgeLabel {
+                source_node,
+                target_node,
+                ..
+            } => state.first_missing(&[source_node, target_node])?.is_none(),
+            UpdateEvent::AddNodeLabel {
+                node_name,
+                anno_ns,
+                anno_name,
+                ..
+            } => {
+                if !state.exists(node_name)? {
+                    conflicts.push(Conflict {
+                        node_name: node_name.clone(),
+                        change: format!("set annotation {anno_ns}::{anno_name}"),
+                    });
+                }
+                true
+            }
+            UpdateEvent::AddEdge {
+                source_node,
+                target_node,
+                component_type,
+                ..
+            } => {
+                if let Some(missing) = state.first_missing(&[source_node, target_node])? {
+                    conflicts.push(Conflict {
+                        node_name: missing.clone(),
+                        change: format!("add {component_type} edge {source_node} → {target_node}"),
+                    });
+                }
+                true
+            }
+            UpdateEvent::AddEdgeLabel {
+                source_node,
+                target_node,
+                anno_ns,
+                anno_name,
+                ..
+            } => {
+                if let Some(missing) = state.first_missing(&[source_node, target_node])? {
+                    conflicts.push(Conflict {
+                        node_name: missing.clone(),
+                        change: format!(
+                            "set annotation {anno_ns}::{anno_name} of edge {source_node} → {target_node}"
+                        ),
+                    });
+                }
+                true
+            }
+        };
+        if keep {
+            result.push(event);
+        }
+    }
+    if conflicts.is_empty() {
+        Ok(result)
+    } else {
+        Err(ChangesetConflicts(conflicts).into())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,89 @@
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{rebase_events, ChangesetConflicts, Conflict};
+
+fn add_label(node_name: &str) -> UpdateEvent {
+    UpdateEvent::AddNodeLabel {
+        node_name: node_name.to_string(),
+        anno_ns: "default_ns".to_string(),
+        anno_name: "pos".to_string(),
+        anno_value: "NN".to_string(),
+    }
+}
+
+#[test]
+fn drop_deletions_of_missing_nodes() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    let events = vec![
+        UpdateEvent::DeleteNode {
+            node_name: "root/doc1#tok1".to_string(),
+        },
+        // Already deleted by the event before
+        UpdateEvent::DeleteNode {
+            node_name: "root/doc1#tok1".to_string(),
+        },
+        UpdateEvent::DeleteNodeLabel {
+            node_name: "root/doc1#missing".to_string(),
+            anno_ns: "default_ns".to_string(),
+            anno_name: "pos".to_string(),
+        },
+        UpdateEvent::AddNode {
+            node_name: "root/doc1#new".to_string(),
+            node_type: "node".to_string(),
+        },
+        add_label("root/doc1#new"),
+        add_label("root/doc1#tok2"),
+    ];
+    let rebased = rebase_events(&graph, events.clone()).unwrap();
+    assert_eq!(
+        vec![
+            events[0].clone(),
+            events[3].clone(),
+            events[4].clone(),
+            events[5].clone()
+        ],
+        rebased
+    );
+}
+
+#[test]
+fn report_changes_of_deleted_nodes() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    let events = vec![
+        UpdateEvent::DeleteNode {
+            node_name: "root/doc1#tok1".to_string(),
+        },
+        add_label("root/doc1#tok1"),
+        UpdateEvent::AddEdge {
+            source_node: "root/doc1#tok0".to_string(),
+            target_node: "root/doc1#missing".to_string(),
+            layer: "annis".to_string(),
+            component_type: "Ordering".to_string(),
+            component_name: "".to_string(),
+        },
+    ];
+    let err = rebase_events(&graph, events).unwrap_err();
+    let conflicts = err.downcast_ref::<ChangesetConflicts>().unwrap();
+    assert_eq!(
+        &ChangesetConflicts(vec![
+            Conflict {
+                node_name: "root/doc1#tok1".to_string(),
+                change: "set annotation default_ns::pos".to_string(),
+            },
+            Conflict {
+                node_name: "root/doc1#missing".to_string(),
+                change: "add Ordering edge root/doc1#tok0 → root/doc1#missing".to_string(),
+            },
+        ]),
+        conflicts
+    );
+}
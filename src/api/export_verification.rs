@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use graphannis::{model::AnnotationComponentType, AnnotationGraph};
+use graphannis_core::types::Component;
+
+/// Compare an exported and re-imported graph with the original one and
+/// describe all differences that have been found.
+pub(crate) fn compare_graphs(
+    original: &AnnotationGraph,
+    exported: &AnnotationGraph,
+) -> Result<Vec<String>> {
+    compare_labeled_graphs(original, "corpus", exported, "exported file")
+}
+
+/// Compare the number of annotations and edges of two graphs. The labels
+/// describe the graphs in the listed differences.
+pub(crate) fn compare_labeled_graphs(
+    original: &AnnotationGraph,
+    original_label: &str,
+    exported: &AnnotationGraph,
+    exported_label: &str,
+) -> Result<Vec<String>> {
+    let mut differences = Vec::new();
+
+    // Compare the number of node annotations for each key
+    let original_annos = node_annotation_counts(original)?;
+    let exported_annos = node_annotation_counts(exported)?;
+    for (key, original_count) in original_annos.iter() {
+        let exported_count = exported_annos.get(key).copied().unwrap_or_default();
+        if exported_count != *original_count {
+            differences.push(format!(
+                "Annotation \"{key}\": {original_count} in {original_label}, {exported_count} in {exported_label}"
+            ));
+        }
+    }
+    for key in exported_annos.keys() {
+        if !original_annos.contains_key(key) {
+            differences.push(format!(
+                "Annotation \"{key}\" is only present in the {exported_label}"
+            ));
+        }
+    }
+
+    // Compare the components and their number of edges
+    let original_components = edge_counts(original)?;
+    let exported_components = edge_counts(exported)?;
+    for (component, original_count) in original_components.iter() {
+        match exported_components.get(component) {
+            Some(exported_count) if exported_count != original_count => {
+                differences.push(format!(
+                    "Component {component}: {original_count} edges in {original_label}, {exported_count} in {exported_label}"
+                ));
+            }
+            Some(_) => {}
+            None if *original_count > 0 => {
+                differences.push(format!(
+                    "Component {component} with {original_count} edges is missing in the {exported_label}"
+                ));
+            }
+            None => {}
+        }
+    }
+    for (component, exported_count) in exported_components.iter() {
+        if *exported_count > 0 && !original_components.contains_key(component) {
+            differences.push(format!(
+                "Component {component} is only present in the {exported_label}"
+            ));
+        }
+    }
+
+    Ok(differences)
+}
+
+fn node_annotation_counts(graph: &AnnotationGraph) -> Result<BTreeMap<String, usize>> {
+    let mut result = BTreeMap::new();
+    let node_annos = graph.get_node_annos();
+    for key in node_annos.annotation_keys()? {
+        let count = node_annos.number_of_annotations_by_name(Some(key.ns.as_str()), &key.name)?;
+        result.insert(format!("{}::{}", key.ns, key.name), count);
+    }
+    Ok(result)
+}
+
+fn edge_counts(graph: &AnnotationGraph) -> Result<BTreeMap<String, usize>> {
+    let mut result = BTreeMap::new();
+    for component in graph.get_all_components(None, None) {
+        let mut edges = 0;
+        if let Some(gs) = graph.get_graphstorage_as_ref(&component) {
+            for source in gs.source_nodes() {
+                let source = source?;
+                for target in gs.get_outgoing_edges(source) {
+                    target?;
+                    edges += 1;
+                }
+            }
+        }
+        result.insert(component_label(&component), edges);
+    }
+    Ok(result)
+}
+
+fn component_label(component: &Component<AnnotationComponentType>) -> String {
+    format!(
+        "{}/{}/{}",
+        component.get_type(),
+        component.layer,
+        component.name
+    )
+}
@@ -0,0 +1,237 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use egui::mutex::RwLock;
+use graphannis::AnnotationGraph;
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{ANNIS_NS, NODE_TYPE},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, DateTime, ZipArchive, ZipWriter};
+
+use super::{read_graphml, write_graphml};
+use crate::app::job_executor::FgJob;
+
+/// Whether the file is a ZIP file containing the GraphML file, as it is
+/// imported by ANNIS.
+pub(crate) fn is_zip_file(location: &Path) -> bool {
+    location
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// Number of nodes and edges that are written to the GraphML file.
+fn number_of_elements(graph: &AnnotationGraph) -> Result<usize> {
+    let mut result = graph
+        .get_node_annos()
+        .exact_anno_search(Some(ANNIS_NS), NODE_TYPE, ValueSearch::Any)
+        .count();
+    for component in graph.get_all_components(None, None) {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&component) {
+            for source in gs.source_nodes() {
+                result += gs.get_outgoing_edges(source?).count();
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Reports the percentage of the exported nodes and edges and aborts the
+/// export with an error when the job has been cancelled.
+struct ProgressWriter<'a, W> {
+    inner: W,
+    job: &'a FgJob,
+    /// Shown before the percentage in the job message.
+    label: String,
+    total: usize,
+    exported: usize,
+    percent: Option<usize>,
+    /// The end of the last written buffer, in case an element starts there.
+    tail: Vec<u8>,
+}
+
+impl<'a, W: Write> ProgressWriter<'a, W> {
+    const ELEMENTS: [&'static [u8]; 2] = [b"<node ", b"<edge "];
+
+    fn new(inner: W, job: &'a FgJob, label: String, total: usize) -> Self {
+        Self {
+            inner,
+            job,
+            label,
+            total,
+            exported: 0,
+            percent: None,
+            tail: Vec::new(),
+        }
+    }
+
+    fn count_elements(&mut self, written: &[u8]) {
+        let mut data = std::mem::take(&mut self.tail);
+        data.extend_from_slice(written);
+        for element in Self::ELEMENTS {
+            self.exported += data
+                .windows(element.len())
+                .filter(|w| w == &element)
+                .count();
+        }
+        // Elements are longer than the tail, so they are not counted twice
+        let tail_len = Self::ELEMENTS[0].len() - 1;
+        self.tail = data[data.len().saturating_sub(tail_len)..].to_vec();
+
+        let percent = (self.exported * 100)
+            .checked_div(self.total)
+            .unwrap_or(100)
+            .min(100);
+        if self.percent != Some(percent) {
+            self.percent = Some(percent);
+            self.job
+                .update_message(format!("{}: {percent}%", self.label));
+        }
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.job
+            .check_cancelled()
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let written = self.inner.write(buf)?;
+        self.count_elements(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Add the graph as GraphML file named after the corpus to the ZIP file.
+fn write_zip_entry<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    graph: &AnnotationGraph,
+    corpus_name: &str,
+    label: String,
+    job: &FgJob,
+) -> Result<()> {
+    job.update_message(format!("{label}: counting nodes and edges"));
+    let total = number_of_elements(graph)?;
+    // Use a fixed modification time, so the file only changes when the
+    // corpus changes
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(DateTime::default());
+    zip.start_file(format!("{corpus_name}.graphml"), options)?;
+    write_graphml(graph, ProgressWriter::new(zip, job, label, total), |_| {})?;
+    Ok(())
+}
+
+fn write_file(
+    graph: &AnnotationGraph,
+    corpus_name: &str,
+    location: &Path,
+    job: &FgJob,
+) -> Result<()> {
+    let outfile = File::create(location)?;
+    if is_zip_file(location) {
+        let mut zip = ZipWriter::new(outfile);
+        write_zip_entry(&mut zip, graph, corpus_name, "Exporting".to_string(), job)?;
+        zip.finish()?;
+    } else {
+        job.update_message("Counting nodes and edges");
+        let total = number_of_elements(graph)?;
+        let writer =
+            ProgressWriter::new(BufWriter::new(outfile), job, "Exporting".to_string(), total);
+        write_graphml(graph, writer, |_| {})?;
+    }
+    Ok(())
+}
+
+/// Remove the partially written file if the export failed.
+fn remove_incomplete<T>(result: Result<T>, location: &Path, job: &FgJob) -> Result<T> {
+    if result.is_err() {
+        if let Err(e) = std::fs::remove_file(location) {
+            log::warn!("Could not remove incomplete export: {e}");
+        }
+        // Report the cancellation instead of the resulting write error
+        job.check_cancelled()?;
+    }
+    result
+}
+
+/// Export the graph as GraphML file, or as ZIP file containing it if the
+/// location ends with `.zip`. If the export fails or is cancelled, the
+/// partially written file is removed.
+pub(crate) fn export_graph(
+    graph: &AnnotationGraph,
+    corpus_name: &str,
+    location: &Path,
+    job: &FgJob,
+) -> Result<()> {
+    remove_incomplete(write_file(graph, corpus_name, location, job), location, job)
+}
+
+/// Export several corpora as one ZIP file with a GraphML file for each
+/// corpus. The graphs are loaded one after another with `load_graph`, so
+/// only one of them needs to be held in memory.
+pub(crate) fn export_bundle<F>(
+    corpus_names: &[String],
+    location: &Path,
+    job: &FgJob,
+    load_graph: F,
+) -> Result<()>
+where
+    F: Fn(&str) -> Result<Arc<RwLock<AnnotationGraph>>>,
+{
+    let write_bundle = || -> Result<()> {
+        let mut zip = ZipWriter::new(File::create(location)?);
+        for (idx, corpus_name) in corpus_names.iter().enumerate() {
+            let label = format!(
+                "Exporting \"{corpus_name}\" ({}/{})",
+                idx + 1,
+                corpus_names.len()
+            );
+            job.update_message(format!("{label}: loading corpus"));
+            let graph = load_graph(corpus_name)?;
+            let graph = graph.read();
+            write_zip_entry(&mut zip, &graph, corpus_name, label, job)?;
+        }
+        zip.finish()?;
+        Ok(())
+    };
+    remove_incomplete(write_bundle(), location, job)
+}
+
+/// The names of the GraphML files in a ZIP file, in alphabetical order.
+pub(crate) fn graphml_entries<R: Read + Seek>(archive: &ZipArchive<R>) -> Vec<String> {
+    let mut result: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.to_lowercase().ends_with(".graphml"))
+        .map(|name| name.to_string())
+        .collect();
+    result.sort();
+    result
+}
+
+/// Read the graph of an exported GraphML or ZIP file.
+pub(crate) fn read_export<F>(location: &Path, progress: F) -> Result<AnnotationGraph>
+where
+    F: Fn(&str),
+{
+    let input = BufReader::new(File::open(location)?);
+    let (graph, _config) = if is_zip_file(location) {
+        let mut archive = ZipArchive::new(input)?;
+        let entry_name = graphml_entries(&archive)
+            .into_iter()
+            .next()
+            .context("Exported ZIP file contains no GraphML file")?;
+        read_graphml(BufReader::new(archive.by_name(&entry_name)?), progress)?
+    } else {
+        read_graphml(input, progress)?
+    };
+    Ok(graph)
+}
@@ -0,0 +1,381 @@
+use std::{
+    cell::Cell,
+    collections::{BTreeSet, HashMap, HashSet},
+    io::Read,
+    path::Path,
+};
+
+use anyhow::Result;
+use graphannis::{model::AnnotationComponentType, AnnotationGraph};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{ANNIS_NS, NODE_NAME_KEY},
+    types::Component,
+};
+
+use super::{read_graphml, token_helper::TokenHelper};
+use crate::app::{
+    job_executor::FgJob,
+    util::annis_config::{layer_settings, LayerSettings},
+};
+
+/// Summary of an imported corpus, including any non-fatal problems that
+/// have been found while checking the imported documents.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ImportReport {
+    pub(crate) corpus_name: String,
+    pub(crate) documents: usize,
+    pub(crate) token: usize,
+    pub(crate) issues: Vec<String>,
+}
+
+impl ImportReport {
+    /// Summarize the reports of several corpora that have been imported from
+    /// the same file.
+    pub(crate) fn combine(name: &str, reports: Vec<ImportReport>) -> Self {
+        let mut result = ImportReport {
+            corpus_name: name.to_string(),
+            ..Default::default()
+        };
+        for report in reports {
+            result.documents += report.documents;
+            result.token += report.token;
+            result.issues.extend(
+                report
+                    .issues
+                    .into_iter()
+                    .map(|issue| format!("{}: {issue}", report.corpus_name)),
+            );
+        }
+        result
+    }
+
+    /// Check each document of the imported graph and report the progress
+    /// per document to the job. The edges of the GraphML file are needed to
+    /// find edges that could not be imported.
+    pub(crate) fn create(
+        corpus_name: &str,
+        graph: &AnnotationGraph,
+        config: Option<&str>,
+        edges: &GraphmlEdges,
+        job: &FgJob,
+    ) -> Result<Self> {
+        let mut report = ImportReport {
+            corpus_name: corpus_name.to_string(),
+            ..Default::default()
+        };
+
+        // Only the layer settings of the configuration are used
+        if let Some(config) = config.filter(|c| !c.trim().is_empty()) {
+            if let Err(e) = layer_settings(config) {
+                report.issues.push(format!(
+                    "The ANNIS corpus configuration of the file can not be used: {e:#}"
+                ));
+            }
+        }
+
+        let part_of_component =
+            Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+        let part_of = graph.get_graphstorage(&part_of_component);
+        if part_of.is_none() {
+            report
+                .issues
+                .push("The corpus has no corpus structure (PartOf component).".to_string());
+        }
+        if graph
+            .get_all_components(Some(AnnotationComponentType::Ordering), Some(""))
+            .is_empty()
+        {
+            report
+                .issues
+                .push("The corpus has no token (Ordering component).".to_string());
+        }
+
+        // Check each document separately
+        let mut documents = Vec::new();
+        for m in graph
+            .get_node_annos()
+            .exact_anno_search(Some(ANNIS_NS), "doc", ValueSearch::Any)
+        {
+            let m = m?;
+            if let Some(name) = graph
+                .get_node_annos()
+                .get_value_for_item(&m.node, &NODE_NAME_KEY)?
+            {
+                documents.push(name.to_string());
+            }
+        }
+        documents.sort();
+        report.documents = documents.len();
+
+        if let (Some(_), Ok(tok_helper)) = (&part_of, TokenHelper::new(graph)) {
+            for (idx, document) in documents.iter().enumerate() {
+                job.update_message(format!(
+                    "Checking document {}/{}: {document}",
+                    idx + 1,
+                    documents.len()
+                ));
+                match tok_helper.get_ordered_token(document, None) {
+                    Ok(token) if token.is_empty() => report
+                        .issues
+                        .push(format!("Document \"{document}\" has no token.")),
+                    Ok(token) => report.token += token.len(),
+                    Err(e) => report.issues.push(format!(
+                        "Could not get token of document \"{document}\": {e}"
+                    )),
+                }
+            }
+        }
+
+        // Find token that are not connected to any document
+        if let Some(part_of) = &part_of {
+            job.update_message("Checking for token without a document");
+            let mut orphaned_token = 0;
+            for m in
+                graph
+                    .get_node_annos()
+                    .exact_anno_search(Some(ANNIS_NS), "tok", ValueSearch::Any)
+            {
+                let m = m?;
+                if !part_of.has_outgoing_edges(m.node)? {
+                    orphaned_token += 1;
+                }
+            }
+            if orphaned_token > 0 {
+                report.issues.push(format!(
+                    "{orphaned_token} token are not part of any document and will not be shown."
+                ));
+            }
+        }
+
+        job.update_message("Checking for edges that could not be imported");
+        report.check_edges(graph, edges)?;
+
+        Ok(report)
+    }
+
+    /// Edges are left out by the import if one of their nodes is not part of
+    /// the file or if their component is unknown.
+    fn check_edges(&mut self, graph: &AnnotationGraph, edges: &GraphmlEdges) -> Result<()> {
+        let mut missing_nodes = Vec::new();
+        let mut dangling_edges = 0;
+        for (node_name, number_of_edges) in &edges.nodes {
+            if graph
+                .get_node_annos()
+                .get_node_id_from_name(node_name)?
+                .is_none()
+            {
+                missing_nodes.push(node_name.as_str());
+                dangling_edges += number_of_edges;
+            }
+        }
+        if let Some(example) = missing_nodes.iter().min() {
+            self.issues.push(format!(
+                "{dangling_edges} edges refer to {} nodes that are not part of the file and have been left out, e.g. \"{example}\".",
+                missing_nodes.len()
+            ));
+        }
+
+        let components: HashSet<String> = graph
+            .get_all_components(None, None)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let unknown_components: Vec<_> = edges
+            .components
+            .iter()
+            .filter(|c| !components.contains(*c))
+            .map(|c| format!("\"{c}\""))
+            .collect();
+        if !unknown_components.is_empty() {
+            self.issues.push(format!(
+                "The edges of the unknown components {} have been left out.",
+                unknown_components.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Import a GraphML file as new corpus and store it at the location. Returns
+/// the report of the import and the layer settings of the ANNIS
+/// configuration embedded in the file, if they can be used. The progress
+/// messages are prefixed with the `label`, if one is given.
+pub(crate) fn import_corpus<R: Read>(
+    input: R,
+    corpus_name: &str,
+    location: &Path,
+    label: Option<&str>,
+    job: &FgJob,
+) -> Result<(ImportReport, Option<LayerSettings>)> {
+    let prefixed = |message: String| match label {
+        Some(label) => format!("{label}: {message}"),
+        None => message,
+    };
+    let input_read = Cell::new(false);
+    let mut input = GraphmlScanner::new(input, &input_read, |idx, document| {
+        let message = if label.is_some() {
+            format!("reading document {idx} \"{document}\"")
+        } else {
+            format!("Reading document {idx} \"{document}\"")
+        };
+        job.update_message(prefixed(message));
+    });
+    let (mut graph, config_str) = read_graphml(&mut input, |status| {
+        // Reading the file is reported per document
+        if input_read.get() {
+            job.update_message(prefixed(status.to_string()));
+        }
+    })?;
+    let edges = input.into_edges();
+
+    let report = ImportReport::create(corpus_name, &graph, config_str.as_deref(), &edges, job)?;
+    // Problems with the configuration are part of the report
+    let layers = config_str
+        .as_deref()
+        .and_then(|config| layer_settings(config).ok())
+        .filter(|layers| !layers.is_empty());
+
+    job.update_message("Persisting corpus");
+    graph.persist_to(location)?;
+
+    Ok((report, layers))
+}
+
+/// The edges of a GraphML file by the names of their nodes and their
+/// components, which are not part of the imported graph if they are invalid.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GraphmlEdges {
+    /// The number of edges for each node name used as source or target.
+    nodes: HashMap<String, usize>,
+    /// The component labels of the edges.
+    components: BTreeSet<String>,
+}
+
+/// Elements that are not complete after this many bytes are ignored.
+const MAX_ELEMENT_SIZE: usize = 64 * 1024;
+
+/// Reads a GraphML file, collects its edges and reports each document whose
+/// nodes are read. The import of graphANNIS only reports its progress for
+/// the whole file.
+pub(crate) struct GraphmlScanner<'a, R, F> {
+    inner: R,
+    on_document: F,
+    /// Set when the whole file has been read.
+    input_read: &'a Cell<bool>,
+    /// The end of the input that has been read but not scanned yet.
+    pending: Vec<u8>,
+    current_document: Option<String>,
+    documents: HashSet<String>,
+    edges: GraphmlEdges,
+}
+
+impl<'a, R, F> GraphmlScanner<'a, R, F>
+where
+    R: Read,
+    F: FnMut(usize, &str),
+{
+    /// Scan the input and call `on_document` with the number and the name
+    /// of each new document.
+    pub(crate) fn new(inner: R, input_read: &'a Cell<bool>, on_document: F) -> Self {
+        Self {
+            inner,
+            on_document,
+            input_read,
+            pending: Vec::new(),
+            current_document: None,
+            documents: HashSet::new(),
+            edges: GraphmlEdges::default(),
+        }
+    }
+
+    pub(crate) fn into_edges(self) -> GraphmlEdges {
+        self.edges
+    }
+
+    fn scan(&mut self) {
+        let mut processed = 0;
+        loop {
+            let Some(start) = self.pending[processed..].iter().position(|b| *b == b'<') else {
+                processed = self.pending.len();
+                break;
+            };
+            let start = processed + start;
+            let Some(length) = self.pending[start..].iter().position(|b| *b == b'>') else {
+                if self.pending.len() - start > MAX_ELEMENT_SIZE {
+                    processed = self.pending.len();
+                } else {
+                    // Wait for the rest of the element
+                    processed = start;
+                }
+                break;
+            };
+            let element = String::from_utf8_lossy(&self.pending[start..start + length]);
+            if let Some(attributes) = element.strip_prefix("<node ") {
+                if let Some(id) = attribute(attributes, "id") {
+                    self.node_read(&id);
+                }
+            } else if let Some(attributes) = element.strip_prefix("<edge ") {
+                for node in ["source", "target"] {
+                    if let Some(node_name) = attribute(attributes, node) {
+                        *self.edges.nodes.entry(node_name).or_default() += 1;
+                    }
+                }
+                if let Some(label) = attribute(attributes, "label") {
+                    self.edges.components.insert(label);
+                }
+            }
+            processed = start + length + 1;
+        }
+        self.pending.drain(..processed);
+    }
+
+    fn node_read(&mut self, node_name: &str) {
+        let Some((document, _)) = node_name.split_once('#') else {
+            return;
+        };
+        if self.current_document.as_deref() != Some(document) {
+            self.current_document = Some(document.to_string());
+            if self.documents.insert(document.to_string()) {
+                (self.on_document)(self.documents.len(), document);
+            }
+        }
+    }
+}
+
+impl<R, F> Read for GraphmlScanner<'_, R, F>
+where
+    R: Read,
+    F: FnMut(usize, &str),
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read == 0 && !buf.is_empty() {
+            self.input_read.set(true);
+        } else {
+            self.pending.extend_from_slice(&buf[..read]);
+            self.scan();
+        }
+        Ok(read)
+    }
+}
+
+/// The unescaped value of an attribute of an XML element.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some((attribute_name, value)) = rest.split_once("=\"") {
+        let (value, after) = value.split_once('"')?;
+        if attribute_name.trim() == name {
+            return Some(
+                value
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&amp;", "&"),
+            );
+        }
+        rest = after;
+    }
+    None
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashSet, fs::OpenOptions, io::ErrorKind, path::Path};
+
+use anyhow::Result;
+use graphannis::update::UpdateEvent;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::app::util::review::REVIEW_NS;
+
+#[cfg(test)]
+mod tests;
+
+pub(crate) const PROVENANCE_FILE_NAME: &str = "annatomic-provenance.csv";
+pub(crate) const ANNOTATOR_ANNO_NAME: &str = "annotator";
+
+/// A recorded change of a single node.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProvenanceEntry {
+    pub(crate) timestamp: String,
+    pub(crate) annotator: String,
+    pub(crate) node_name: String,
+    pub(crate) change: String,
+}
+
+/// Describe which nodes are affected by the event and how.
+fn describe(event: &UpdateEvent) -> Vec<(&str, String)> {
+    match event {
+        UpdateEvent::AddNode {
+            node_name,
+            node_type,
+        } => vec![(node_name.as_str(), format!("Added {node_type} node"))],
+        UpdateEvent::DeleteNode { node_name } => {
+            vec![(node_name.as_str(), "Deleted node".to_string())]
+        }
+        UpdateEvent::AddNodeLabel {
+            node_name,
+            anno_ns,
+            anno_name,
+            anno_value,
+        } => vec![(
+            node_name.as_str(),
+            format!("Set {anno_ns}::{anno_name} to \"{anno_value}\""),
+        )],
+        UpdateEvent::DeleteNodeLabel {
+            node_name,
+            anno_ns,
+            anno_name,
+        } => vec![(
+            node_name.as_str(),
+            format!("Deleted {anno_ns}::{anno_name}"),
+        )],
+        UpdateEvent::AddEdge {
+            source_node,
+            target_node,
+            component_type,
+            ..
+        } => vec![
+            (
+                source_node.as_str(),
+                format!("Added {component_type} edge to {target_node}"),
+            ),
+            (
+                target_node.as_str(),
+                format!("Added {component_type} edge from {source_node}"),
+            ),
+        ],
+        UpdateEvent::DeleteEdge {
+            source_node,
+            target_node,
+            component_type,
+            ..
+        } => vec![
+            (
+                source_node.as_str(),
+                format!("Deleted {component_type} edge to {target_node}"),
+            ),
+            (
+                target_node.as_str(),
+                format!("Deleted {component_type} edge from {source_node}"),
+            ),
+        ],
+        UpdateEvent::AddEdgeLabel {
+            source_node,
+            target_node,
+            component_type,
+            anno_ns,
+            anno_name,
+            anno_value,
+            ..
+        } => {
+            let change = format!("Set {anno_ns}::{anno_name} to \"{anno_value}\"");
+            vec![(
+                source_node.as_str(),
+                format!("{change} on {component_type} edge to {target_node}"),
+            )]
+        }
+        UpdateEvent::DeleteEdgeLabel {
+            source_node,
+            target_node,
+            component_type,
+            anno_ns,
+            anno_name,
+            ..
+        } => vec![(
+            source_node.as_str(),
+            format!("Deleted {anno_ns}::{anno_name} of {component_type} edge to {target_node}"),
+        )],
+    }
+}
+
+/// Create the events that stamp all nodes which are added or get a new
+/// annotation value by `events` with an `annotator` annotation in the
+/// namespace `ns`.
+pub(crate) fn annotator_stamps(
+    events: &[UpdateEvent],
+    ns: &str,
+    initials: &str,
+) -> Vec<UpdateEvent> {
+    let deleted: HashSet<&str> = events
+        .iter()
+        .filter_map(|e| match e {
+            UpdateEvent::DeleteNode { node_name } => Some(node_name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut stamped = HashSet::new();
+    let mut result = Vec::new();
+    for event in events {
+        let node_name = match event {
+            UpdateEvent::AddNode { node_name, .. } => node_name,
+            UpdateEvent::AddNodeLabel {
+                node_name,
+                anno_ns,
+                anno_name,
+                ..
+            } if (anno_ns != ns || anno_name != ANNOTATOR_ANNO_NAME) && anno_ns != REVIEW_NS => {
+                node_name
+            }
+            _ => continue,
+        };
+        if !deleted.contains(node_name.as_str()) && stamped.insert(node_name.as_str()) {
+            result.push(UpdateEvent::AddNodeLabel {
+                node_name: node_name.clone(),
+                anno_ns: ns.to_string(),
+                anno_name: ANNOTATOR_ANNO_NAME.to_string(),
+                anno_value: initials.to_string(),
+            });
+        }
+    }
+    result
+}
+
+/// Append the changes made by `annotator` to the provenance log of the
+/// corpus at the given location.
+pub(crate) fn record_changes(
+    corpus_location: &Path,
+    annotator: &str,
+    events: &[UpdateEvent],
+) -> Result<()> {
+    let timestamp = OffsetDateTime::now_utc().format(&Rfc3339)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(corpus_location.join(PROVENANCE_FILE_NAME))?;
+    let mut writer = csv::Writer::from_writer(file);
+    for event in events {
+        for (node_name, change) in describe(event) {
+            writer.write_record([timestamp.as_str(), annotator, node_name, change.as_str()])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Get all recorded changes of the node with the given name, oldest first.
+pub(crate) fn changes_of_node(
+    corpus_location: &Path,
+    node_name: &str,
+) -> Result<Vec<ProvenanceEntry>> {
+    let file = match std::fs::File::open(corpus_location.join(PROVENANCE_FILE_NAME)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(file);
+    let mut result = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        if record.get(2) == Some(node_name) {
+            result.push(ProvenanceEntry {
+                timestamp: record.get(0).unwrap_or_default().to_string(),
+                annotator: record.get(1).unwrap_or_default().to_string(),
+                node_name: node_name.to_string(),
+                change: record.get(3).unwrap_or_default().to_string(),
+            });
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,116 @@
+use graphannis::update::UpdateEvent;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+use super::{annotator_stamps, changes_of_node, record_changes};
+
+#[test]
+fn record_and_read_changes() {
+    let corpus_dir = TempDir::new().unwrap();
+    assert_eq!(
+        0,
+        changes_of_node(corpus_dir.path(), "doc#t1").unwrap().len()
+    );
+
+    record_changes(
+        corpus_dir.path(),
+        "Jane",
+        &[
+            UpdateEvent::AddNodeLabel {
+                node_name: "doc#t1".into(),
+                anno_ns: "default_ns".into(),
+                anno_name: "pos".into(),
+                anno_value: "ART".into(),
+            },
+            UpdateEvent::AddEdge {
+                source_node: "doc#t1".into(),
+                target_node: "doc#t2".into(),
+                layer: "annis".into(),
+                component_type: "Ordering".into(),
+                component_name: "".into(),
+            },
+        ],
+    )
+    .unwrap();
+    record_changes(
+        corpus_dir.path(),
+        "John",
+        &[UpdateEvent::DeleteNode {
+            node_name: "doc#t1".into(),
+        }],
+    )
+    .unwrap();
+
+    let changes = changes_of_node(corpus_dir.path(), "doc#t1").unwrap();
+    assert_eq!(
+        vec![
+            ("Jane", "Set default_ns::pos to \"ART\""),
+            ("Jane", "Added Ordering edge to doc#t2"),
+            ("John", "Deleted node"),
+        ],
+        changes
+            .iter()
+            .map(|c| (c.annotator.as_str(), c.change.as_str()))
+            .collect::<Vec<_>>()
+    );
+    assert!(changes.iter().all(|c| !c.timestamp.is_empty()));
+
+    let changes = changes_of_node(corpus_dir.path(), "doc#t2").unwrap();
+    assert_eq!(1, changes.len());
+    assert_eq!("Added Ordering edge from doc#t1", changes[0].change);
+}
+
+#[test]
+fn stamp_new_nodes_and_annotations() {
+    let events = vec![
+        UpdateEvent::AddNode {
+            node_name: "doc#span1".into(),
+            node_type: "node".into(),
+        },
+        UpdateEvent::AddNodeLabel {
+            node_name: "doc#span1".into(),
+            anno_ns: "default_ns".into(),
+            anno_name: "norm".into(),
+            anno_value: "Jugendliche".into(),
+        },
+        UpdateEvent::AddNodeLabel {
+            node_name: "doc#t2".into(),
+            anno_ns: "default_ns".into(),
+            anno_name: "pos".into(),
+            anno_value: "NN".into(),
+        },
+        UpdateEvent::DeleteNodeLabel {
+            node_name: "doc#t3".into(),
+            anno_ns: "default_ns".into(),
+            anno_name: "pos".into(),
+        },
+        UpdateEvent::AddNode {
+            node_name: "doc#span2".into(),
+            node_type: "node".into(),
+        },
+        UpdateEvent::DeleteNode {
+            node_name: "doc#span2".into(),
+        },
+    ];
+    let stamps = annotator_stamps(&events, "team", "JD");
+    assert_eq!(
+        vec![
+            UpdateEvent::AddNodeLabel {
+                node_name: "doc#span1".into(),
+                anno_ns: "team".into(),
+                anno_name: "annotator".into(),
+                anno_value: "JD".into(),
+            },
+            UpdateEvent::AddNodeLabel {
+                node_name: "doc#t2".into(),
+                anno_ns: "team".into(),
+                anno_name: "annotator".into(),
+                anno_value: "JD".into(),
+            },
+        ],
+        stamps
+    );
+
+    // Existing stamps are not stamped again
+    assert!(annotator_stamps(&stamps, "team", "JD").is_empty());
+}
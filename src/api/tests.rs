@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+use super::CorpusFile;
+
+fn import_example(tmp: &TempDir) -> CorpusFile {
+    CorpusFile::import_graphml(
+        Path::new("tests/data/single_sentence.graphml"),
+        tmp.path().join("corpus"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn read_documents_and_token() {
+    let tmp = TempDir::new().unwrap();
+    let mut corpus = import_example(&tmp);
+    assert_eq!(vec!["single_sentence/zossen"], corpus.documents().unwrap());
+    let token = corpus.token_values("single_sentence/zossen", None).unwrap();
+    assert_eq!(9, token.len());
+    assert_eq!("Die", token[0]);
+}
+
+#[test]
+fn apply_and_persist_changeset() {
+    let tmp = TempDir::new().unwrap();
+    let mut corpus = import_example(&tmp);
+    let mut update = GraphUpdate::new();
+    update
+        .add_event(UpdateEvent::AddNodeLabel {
+            node_name: "single_sentence/zossen#t1".to_string(),
+            anno_ns: "annis".to_string(),
+            anno_name: "tok".to_string(),
+            anno_value: "Der".to_string(),
+        })
+        .unwrap();
+    corpus.apply(update).unwrap();
+    corpus.persist().unwrap();
+
+    let mut reopened = CorpusFile::open(corpus.location()).unwrap();
+    let token = reopened
+        .token_values("single_sentence/zossen", None)
+        .unwrap();
+    assert_eq!("Der", token[0]);
+
+    let mut csv = Vec::new();
+    assert_eq!(1, reopened.export_metadata_csv(&mut csv).unwrap());
+}
+
+#[test]
+fn export_and_import_graphml() {
+    let tmp = TempDir::new().unwrap();
+    let mut corpus = import_example(&tmp);
+    let output = tmp.path().join("exported.graphml");
+    corpus.export_graphml(&output).unwrap();
+
+    let mut imported = CorpusFile::import_graphml(&output, tmp.path().join("imported")).unwrap();
+    assert_eq!(corpus.documents().unwrap(), imported.documents().unwrap());
+    assert_eq!(
+        corpus.token_values("single_sentence/zossen", None).unwrap(),
+        imported
+            .token_values("single_sentence/zossen", None)
+            .unwrap()
+    );
+}
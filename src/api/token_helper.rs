@@ -92,16 +92,18 @@ impl<'a> TokenHelper<'a> {
 
     pub fn is_segmentation_token(&self, id: NodeID, seg: &str) -> anyhow::Result<bool> {
         if self.node_annos.has_value_for_item(&id, &TOKEN_KEY)? {
+            let has_seg_label = !self
+                .node_annos
+                .get_all_keys_for_item(&id, None, Some(seg))?
+                .is_empty();
             if let Some(gs_ordering) = self.ordering_gs.get(seg) {
                 let part_of_seg_component =
                     gs_ordering.has_outgoing_edges(id)? || gs_ordering.has_ingoing_edges(id)?;
-                let has_seg_label = !self
-                    .node_annos
-                    .get_all_keys_for_item(&id, None, Some(seg))?
-                    .is_empty();
                 return Ok(part_of_seg_component
                     || (has_seg_label && gs_ordering.source_nodes().next().is_none()));
             }
+            // The first node of a new layer has no ordering component yet
+            return Ok(has_seg_label);
         }
         Ok(false)
     }
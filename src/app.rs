@@ -1,22 +1,56 @@
-use std::sync::{Arc, OnceLock};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
 use anyhow::Result;
 use clap::Parser;
 use editors::corpus_tree::CorpusTree;
 use editors::document_editor::DocumentEditor;
 use eframe::IntegrationInfo;
-use egui::{Button, Color32, FontData, Key, KeyboardShortcut, Modifiers, RichText, Theme};
-use graphannis::graph::NodeID;
+use egui::{Button, Color32, FontData, Key, KeyboardShortcut, Modifiers, RichText, TextEdit};
+use egui_notify::Toast;
+use graphannis::{graph::NodeID, model::AnnotationComponentType};
 use job_executor::JobExecutor;
 use messages::Notifier;
-use project::Project;
+use project::{configuration::ProjectConfiguration, history::UndoGroup, Project};
 use serde::{Deserialize, Serialize};
-use views::Editor;
+use settings::Settings;
+use util::{
+    annis_config::LayerSettings, corpus_groups::CorpusGrouping,
+    corpus_structure::neighbour_documents, language::document_tokenizer, tokenizer::TokenizerKind,
+};
+use views::{
+    component_browser::ComponentBrowserDialog,
+    detached::DetachedDocument,
+    diagnostics::DiagnosticsDialog,
+    frequency::FrequencyDialog,
+    job_queue::JobQueueDialog,
+    kwic_export::KwicExportDialog,
+    map_values::ValueMappingDialog,
+    new_document::NewDocumentDialog,
+    ngrams::NgramDialog,
+    ordering_chain::OrderingChainDialog,
+    provenance::ProvenanceDialog,
+    rename_annotation::RenameAnnotationDialog,
+    review::ReviewDialog,
+    start::{BundleExportDialog, CorpusRenameDialog},
+    text_search::TextSearchDialog,
+    virtual_collections::VirtualCollectionsDialog,
+    whitespace::WhitespaceDialog,
+    Editor, ShortcutEntry,
+};
 
+use crate::api::import_report::ImportReport;
+
+mod appearance;
 mod editors;
 pub(crate) mod job_executor;
 mod messages;
 mod project;
+mod settings;
 #[cfg(test)]
 mod tests;
 pub(crate) mod util;
@@ -24,10 +58,20 @@ mod views;
 pub(crate) mod widgets;
 
 pub(crate) const APP_ID: &str = "annatomic";
+/// Name of the profile used if no other profile has been selected.
+const DEFAULT_PROFILE: &str = "Default";
 pub const QUIT_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Q);
 pub const SAVE_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::S);
 pub const UNDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
 pub const REDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
+pub const DISCARD_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::D);
+pub const PREVIOUS_DOCUMENT_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND, Key::PageUp);
+pub const NEXT_DOCUMENT_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND, Key::PageDown);
+pub const SHORTCUT_HELP_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::NONE, Key::F1);
 
 pub const CHANGE_PENDING_COLOR_DARK: Color32 = Color32::from_rgb(160, 50, 50);
 pub const CHANGE_PENDING_COLOR_LIGHT: Color32 = Color32::from_rgb(255, 128, 128);
@@ -47,6 +91,32 @@ pub struct AnnatomicArgs {
     /// Start in development mode which displays additional information only relevant for developers.
     #[arg(long)]
     dev: bool,
+    /// Name of the profile to use, which is created if it does not exist yet.
+    #[arg(long)]
+    profile: Option<String>,
+    /// Start without restoring the previous state of the application, e.g.
+    /// the selected corpus, settings and layout. Only the list of corpora
+    /// and profiles is kept.
+    #[arg(long)]
+    safe_mode: bool,
+}
+
+impl AnnatomicArgs {
+    /// Whether the application should not restore its previous state.
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+}
+
+/// Another document or corpus the user wants to open while an editor still
+/// has pending changes.
+#[derive(Clone, PartialEq, Debug)]
+enum SwitchRequest {
+    Document {
+        node_id: NodeID,
+        token_index: Option<usize>,
+    },
+    Corpus(Option<String>),
 }
 
 #[derive(Default)]
@@ -62,12 +132,88 @@ enum ShutdownRequest {
 pub struct AnnatomicApp {
     main_view: MainView,
     new_corpus_name: String,
+    /// Name of the profile the project belongs to.
+    active_profile: String,
+    /// The configuration of all other profiles by their name.
+    profiles: BTreeMap<String, ProjectConfiguration>,
+    corpus_grouping: CorpusGrouping,
+    #[serde(skip)]
+    corpus_filter: String,
     project: Project,
+    settings: Settings,
+    #[serde(skip)]
+    settings_open: bool,
+    #[serde(skip)]
+    shortcuts_open: bool,
+    #[serde(skip)]
+    import_report: Option<ImportReport>,
+    #[serde(skip)]
+    export_differences: Option<(PathBuf, Vec<String>)>,
+    #[serde(skip)]
+    corpus_rename: Option<CorpusRenameDialog>,
+    #[serde(skip)]
+    bundle_export: Option<BundleExportDialog>,
+    #[serde(skip)]
+    rename_annotation: RenameAnnotationDialog,
+    #[serde(skip)]
+    value_mapping: ValueMappingDialog,
+    #[serde(skip)]
+    review: ReviewDialog,
+    #[serde(skip)]
+    whitespace: WhitespaceDialog,
+    #[serde(skip)]
+    diagnostics: DiagnosticsDialog,
+    #[serde(skip)]
+    job_queue: JobQueueDialog,
+    #[serde(skip)]
+    new_document: NewDocumentDialog,
+    #[serde(skip)]
+    provenance: ProvenanceDialog,
+    text_search: TextSearchDialog,
+    virtual_collections: VirtualCollectionsDialog,
+    #[serde(skip)]
+    kwic_export: KwicExportDialog,
+    #[serde(skip)]
+    frequency: FrequencyDialog,
+    #[serde(skip)]
+    component_browser: ComponentBrowserDialog,
+    #[serde(skip)]
+    ngrams: NgramDialog,
+    #[serde(skip)]
+    ordering_chain: OrderingChainDialog,
     #[serde(skip)]
     current_editor: OnceLock<Box<dyn Editor>>,
+    /// Documents that are edited in additional windows.
+    #[serde(skip)]
+    detached_documents: Vec<DetachedDocument>,
     #[serde(skip)]
     shutdown_request: ShutdownRequest,
     #[serde(skip)]
+    unload_requested: bool,
+    /// Profile to switch to once all pending changes have been applied.
+    #[serde(skip)]
+    requested_profile: Option<String>,
+    #[serde(skip)]
+    new_profile_name: String,
+    #[serde(skip)]
+    new_checkpoint_name: String,
+    /// Document to open once all pending changes have been applied.
+    #[serde(skip)]
+    requested_document: Option<NodeID>,
+    /// Corpus to select once all changesets of the currently selected corpus
+    /// have been applied.
+    #[serde(skip)]
+    requested_corpus: Option<Option<String>>,
+    /// Base token to scroll to once the requested document is shown.
+    #[serde(skip)]
+    requested_token: Option<usize>,
+    /// Empty segmentation layers to show once the requested document is shown.
+    #[serde(skip)]
+    requested_layers: Vec<String>,
+    /// Waits for the user to apply or discard the pending changes.
+    #[serde(skip)]
+    switch_request: Option<SwitchRequest>,
+    #[serde(skip)]
     jobs: JobExecutor,
     #[serde(skip)]
     notifier: Notifier,
@@ -81,16 +227,54 @@ impl Default for AnnatomicApp {
         let jobs = JobExecutor::default();
         let project = Project::new(notifier.clone(), jobs.clone());
 
-        Self {
+        let mut app = Self {
             main_view: MainView::Start,
             new_corpus_name: String::default(),
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profiles: BTreeMap::new(),
+            corpus_grouping: CorpusGrouping::default(),
+            corpus_filter: String::default(),
             project,
+            settings: Settings::default(),
+            settings_open: false,
+            shortcuts_open: false,
+            import_report: None,
+            export_differences: None,
+            corpus_rename: None,
+            bundle_export: None,
+            rename_annotation: RenameAnnotationDialog::default(),
+            value_mapping: ValueMappingDialog::default(),
+            review: ReviewDialog::default(),
+            whitespace: WhitespaceDialog::default(),
+            diagnostics: DiagnosticsDialog::default(),
+            job_queue: JobQueueDialog::default(),
+            new_document: NewDocumentDialog::default(),
+            provenance: ProvenanceDialog::default(),
+            text_search: TextSearchDialog::default(),
+            virtual_collections: VirtualCollectionsDialog::default(),
+            kwic_export: KwicExportDialog::default(),
+            frequency: FrequencyDialog::default(),
+            component_browser: ComponentBrowserDialog::default(),
+            ngrams: NgramDialog::default(),
+            ordering_chain: OrderingChainDialog::default(),
             jobs,
             notifier,
             args: AnnatomicArgs::default(),
             current_editor: OnceLock::new(),
+            detached_documents: Vec::new(),
             shutdown_request: ShutdownRequest::None,
-        }
+            unload_requested: false,
+            requested_profile: None,
+            new_profile_name: String::default(),
+            new_checkpoint_name: String::default(),
+            requested_document: None,
+            requested_corpus: None,
+            requested_token: None,
+            requested_layers: Vec::new(),
+            switch_request: None,
+        };
+        app.apply_settings();
+        app
     }
 }
 
@@ -159,8 +343,14 @@ impl AnnatomicApp {
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         let mut app = if let Some(storage) = cc.storage {
-            let mut app_from_storage: AnnatomicApp =
-                eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            let persisted = eframe::get_value(storage, eframe::APP_KEY);
+            let mut app_from_storage = if args.safe_mode {
+                // Also reset the layout of the windows and panels
+                cc.egui_ctx.memory_mut(|m| *m = egui::Memory::default());
+                AnnatomicApp::safe_mode_state(persisted)
+            } else {
+                persisted.unwrap_or_default()
+            };
             app_from_storage.args = args;
             app_from_storage
         } else {
@@ -174,9 +364,184 @@ impl AnnatomicApp {
         // Rebuild the state that is not persisted but calculated
         app.project
             .load_after_init(app.notifier.clone(), app.jobs.clone())?;
+        app.apply_settings();
+        if let Some(profile) = app.args.profile.clone() {
+            app.switch_profile(&profile);
+        }
+        if app.args.safe_mode {
+            app.notifier.add_toast(Toast::warning(
+                "Started in safe mode, the previous state of the application has been reset.",
+            ));
+        }
         Ok(app)
     }
 
+    /// The state used in safe mode. Only the corpora and profiles of the
+    /// persisted state are kept, everything else uses the default values.
+    fn safe_mode_state(persisted: Option<AnnatomicApp>) -> AnnatomicApp {
+        let mut app = AnnatomicApp::default();
+        if let Some(persisted) = persisted {
+            app.project.corpus_locations = persisted.project.corpus_locations;
+            app.project.corpus_tags = persisted.project.corpus_tags;
+            app.project.corpus_tokenizers = persisted.project.corpus_tokenizers;
+            app.project.corpus_layers = persisted.project.corpus_layers;
+            app.project.corpus_namespaces = persisted.project.corpus_namespaces;
+            app.project.pinned_corpora = persisted.project.pinned_corpora;
+            app.project.document_templates = persisted.project.document_templates;
+            app.project.portable = persisted.project.portable;
+            app.project.storage_profile = persisted.project.storage_profile;
+            app.active_profile = persisted.active_profile;
+            app.profiles = persisted.profiles;
+        }
+        app
+    }
+
+    /// Propagate the current settings to the parts of the application that use them.
+    pub(crate) fn apply_settings(&mut self) {
+        self.project
+            .set_undo_limits(self.settings.max_undos, self.settings.undo_memory_budget());
+        self.project.set_provenance_annotator(
+            self.settings
+                .record_provenance
+                .then(|| self.settings.annotator.clone()),
+        );
+        self.project
+            .set_integrity_check_interval(self.settings.integrity_check_interval());
+        let initials = self.settings.annotator_initials.trim();
+        self.project.set_annotator_stamp(
+            (self.settings.stamp_annotations && !initials.is_empty())
+                .then(|| (self.settings.stamp_namespace.clone(), initials.to_string())),
+        );
+        let corpus_tokenizer = self.tokenizer_of_selected_corpus();
+        let default_tokenizer = self.settings.tokenizer.clone();
+        let namespace = self.default_namespace_of_selected_corpus();
+        let Settings {
+            manual_commit,
+            confirm_deletion,
+            token_tooltips,
+            ..
+        } = self.settings;
+        for editor in self.editors_mut() {
+            editor.set_manual_commit(manual_commit);
+            editor.set_tokenizer(document_tokenizer(
+                corpus_tokenizer.as_ref(),
+                editor.language(),
+                &default_tokenizer,
+            ));
+            editor.set_default_namespace(namespace.clone());
+            editor.set_confirm_deletion(confirm_deletion);
+            editor.set_token_tooltips(token_tooltips);
+        }
+    }
+
+    /// The editor of the main view and the ones of all additional windows.
+    pub(crate) fn editors_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Editor>> {
+        self.current_editor.get_mut().into_iter().chain(
+            self.detached_documents
+                .iter_mut()
+                .filter_map(|d| d.editor.as_mut()),
+        )
+    }
+
+    /// The tokenizer configured for the selected corpus, if it does not use
+    /// the default one.
+    pub(crate) fn tokenizer_of_selected_corpus(&self) -> Option<TokenizerKind> {
+        self.project
+            .selected_corpus
+            .as_ref()
+            .and_then(|c| self.project.corpus_tokenizers.get(&c.name))
+            .cloned()
+    }
+
+    /// The namespace pre-filled for new annotations of the selected corpus,
+    /// which is empty if none has been configured.
+    pub(crate) fn default_namespace_of_selected_corpus(&self) -> String {
+        self.project
+            .selected_corpus
+            .as_ref()
+            .and_then(|c| self.project.corpus_namespaces.get(&c.name))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The layer settings of the selected corpus, e.g. from its ANNIS
+    /// configuration.
+    pub(crate) fn layer_settings_of_selected_corpus(&self) -> LayerSettings {
+        self.project
+            .selected_corpus
+            .as_ref()
+            .and_then(|c| self.project.corpus_layers.get(&c.name))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The corpus locations, tags, settings and saved queries that can be
+    /// shared with other annotators.
+    pub(crate) fn project_configuration(&self) -> ProjectConfiguration {
+        ProjectConfiguration {
+            settings: self.settings.clone(),
+            corpus_locations: self.project.corpus_locations.clone(),
+            portable: self.project.portable,
+            corpus_tags: self.project.corpus_tags.clone(),
+            corpus_tokenizers: self.project.corpus_tokenizers.clone(),
+            corpus_layers: self.project.corpus_layers.clone(),
+            corpus_namespaces: self.project.corpus_namespaces.clone(),
+            pinned_corpora: self.project.pinned_corpora.clone(),
+            document_templates: self.project.document_templates.clone(),
+            saved_queries: self.text_search.saved_queries(),
+            virtual_collections: self.virtual_collections.collections(),
+        }
+    }
+
+    /// Add the corpora and saved queries of the configuration to the project
+    /// and use its settings. The name of the local annotator is kept.
+    pub(crate) fn apply_project_configuration(&mut self, config: ProjectConfiguration) {
+        let annotator = std::mem::take(&mut self.settings.annotator);
+        let annotator_initials = std::mem::take(&mut self.settings.annotator_initials);
+        self.settings = Settings {
+            annotator,
+            annotator_initials,
+            ..config.settings
+        };
+        self.project
+            .corpus_locations
+            .extend(config.corpus_locations);
+        self.project.portable |= config.portable;
+        self.project.corpus_tags.extend(config.corpus_tags);
+        self.project
+            .corpus_tokenizers
+            .extend(config.corpus_tokenizers);
+        self.project.corpus_layers.extend(config.corpus_layers);
+        self.project
+            .corpus_namespaces
+            .extend(config.corpus_namespaces);
+        self.project
+            .document_templates
+            .extend(config.document_templates);
+        for corpus_name in config.pinned_corpora {
+            self.project.pin_corpus(&corpus_name);
+        }
+        for (corpus_name, queries) in config.saved_queries {
+            self.text_search.add_saved_queries(&corpus_name, queries);
+        }
+        for (corpus_name, collections) in config.virtual_collections {
+            self.virtual_collections
+                .add_collections(&corpus_name, collections);
+        }
+        self.project.check_corpus_locations();
+        self.apply_settings();
+    }
+
+    fn export_project_configuration(&self, path: &Path) -> Result<()> {
+        self.project_configuration().write(path)
+    }
+
+    fn import_project_configuration(&mut self, path: &Path) -> Result<()> {
+        let config = ProjectConfiguration::read(path)?;
+        self.apply_project_configuration(config);
+        Ok(())
+    }
+
     pub(crate) fn change_view(&mut self, new_view: MainView) {
         if self.main_view != new_view {
             self.main_view = new_view;
@@ -185,11 +550,18 @@ impl AnnatomicApp {
     }
 
     pub(crate) fn load_editor(&mut self, force_refresh: bool) {
+        if force_refresh {
+            views::detached::reload(self);
+        }
         let selected_corpus_node = {
             self.current_editor
                 .get()
                 .and_then(|editor| editor.get_selected_corpus_node())
         };
+        let view_state = self
+            .current_editor
+            .get()
+            .and_then(|editor| editor.view_state());
         match self.main_view {
             MainView::Start => {
                 if let Some(corpus) = &self.project.selected_corpus {
@@ -203,16 +575,18 @@ impl AnnatomicApp {
                         let jobs = self.jobs.clone();
                         let notifier = self.notifier.clone();
                         let location = corpus.location.clone();
+                        let namespace = self.default_namespace_of_selected_corpus();
                         self.jobs.add(
                             job_title,
                             move |_| {
                                 let graph = corpus_cache.get(&location)?;
-                                let corpus_tree = CorpusTree::create_from_graph(
+                                let mut corpus_tree = CorpusTree::create_from_graph(
                                     graph,
                                     selected_corpus_node,
                                     jobs,
                                     notifier,
                                 )?;
+                                corpus_tree.set_default_namespace(namespace);
                                 Ok(corpus_tree)
                             },
                             |corpus_tree, app| {
@@ -231,14 +605,60 @@ impl AnnatomicApp {
                     if needs_refresh && !self.jobs.has_active_job_with_title(job_title) {
                         self.current_editor = OnceLock::new();
                         let corpus_cache = self.project.corpus_cache.clone();
+                        let prefetch = self.project.prefetched_documents.clone();
                         let location = corpus.location.clone();
                         let jobs = self.jobs.clone();
+                        let manual_commit = self.settings.manual_commit;
+                        let confirm_deletion = self.settings.confirm_deletion;
+                        let token_tooltips = self.settings.token_tooltips;
+                        let corpus_tokenizer = self.tokenizer_of_selected_corpus();
+                        let default_tokenizer = self.settings.tokenizer.clone();
+                        let layer_settings = self.layer_settings_of_selected_corpus();
+                        let namespace = self.default_namespace_of_selected_corpus();
                         self.jobs.add(
                             job_title,
                             move |_| {
                                 let graph = corpus_cache.get(&location)?;
-                                let document_editor =
-                                    DocumentEditor::create_from_graph(node_id, graph, jobs)?;
+                                let neighbours = {
+                                    let mut graph = graph.write();
+                                    let part_of_components = graph.get_all_components(
+                                        Some(AnnotationComponentType::PartOf),
+                                        None,
+                                    );
+                                    graph.ensure_loaded_parallel(&part_of_components)?;
+                                    neighbour_documents(&graph, node_id)?
+                                };
+                                let mut document_editor = if let Some(content) =
+                                    prefetch.take(node_id, &corpus_cache)
+                                {
+                                    DocumentEditor::create_from_content(
+                                        content,
+                                        graph.clone(),
+                                        jobs,
+                                    )
+                                } else {
+                                    DocumentEditor::create_from_graph(node_id, graph.clone(), jobs)?
+                                };
+                                document_editor.set_neighbour_documents(neighbours);
+                                document_editor.apply_layer_settings(&layer_settings);
+                                document_editor.set_manual_commit(manual_commit);
+                                document_editor.set_tokenizer(document_tokenizer(
+                                    corpus_tokenizer.as_ref(),
+                                    document_editor.language(),
+                                    &default_tokenizer,
+                                ));
+                                document_editor.set_default_namespace(namespace);
+                                document_editor.set_confirm_deletion(confirm_deletion);
+                                document_editor.set_token_tooltips(token_tooltips);
+                                if let Some(view_state) = view_state {
+                                    document_editor.restore_view_state(view_state);
+                                }
+                                // Load the neighbouring documents in the background,
+                                // so switching to them is faster
+                                prefetch.prefetch_neighbours(node_id, graph, corpus_cache);
+                                if let Err(e) = document_editor.load_linked_media(&location) {
+                                    log::warn!("Could not load linked media: {e:?}");
+                                }
 
                                 Ok(document_editor)
                             },
@@ -287,23 +707,523 @@ impl AnnatomicApp {
         }
     }
 
+    fn handle_missing_corpus_dialog(&mut self, ctx: &egui::Context) {
+        if let Some(corpus_name) = self.project.missing_corpora.first().cloned() {
+            let location = self
+                .project
+                .corpus_location(&corpus_name)
+                .map(|l| l.to_string_lossy().to_string())
+                .unwrap_or_default();
+            egui::Modal::new("missing_corpus_dialog".into()).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(egui_phosphor::regular::WARNING)
+                            .color(Color32::ORANGE)
+                            .size(32.0),
+                    );
+                    ui.label(format!(
+                        "The corpus \"{corpus_name}\" could not be found at {location}. It might have been moved or deleted."
+                    ));
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Locate directory…").clicked() {
+                        if let Some(new_location) = rfd::FileDialog::new().pick_folder() {
+                            match self.project.relocate_corpus(&corpus_name, new_location) {
+                                Ok(_) => self.select_corpus(Some(corpus_name.clone())),
+                                Err(e) => self.notifier.report_error(e),
+                            }
+                        }
+                    }
+                    if ui.button("Restore from GraphML file…").clicked() {
+                        let dlg = rfd::FileDialog::new()
+                            .add_filter("GraphML (*.graphml)", &["graphml"])
+                            .add_filter("Compressed GraphML (*.zip)", &["zip"]);
+                        if let Some(path) = dlg.pick_file() {
+                            self.project.missing_corpora.retain(|c| c != &corpus_name);
+                            self.project
+                                .import_from_graphml(path, Some(corpus_name.clone()));
+                        }
+                    }
+                    if ui
+                        .button(RichText::new("Remove from list").color(Color32::RED))
+                        .clicked()
+                    {
+                        self.project.forget_corpus(&corpus_name);
+                        self.load_editor(true);
+                    }
+                    if ui.button("Ignore").clicked() {
+                        self.project.missing_corpora.retain(|c| c != &corpus_name);
+                    }
+                });
+            });
+        }
+    }
+
+    /// Select another corpus. If an editor has pending changes, the user is
+    /// asked whether to apply or discard them first.
     pub(crate) fn select_corpus(&mut self, selection: Option<String>) {
-        self.project.select_corpus(selection);
-        self.load_editor(true);
+        self.request_switch(SwitchRequest::Corpus(selection));
+    }
+
+    /// Switch to the document or corpus, or ask the user what to do with the
+    /// pending changes.
+    fn request_switch(&mut self, request: SwitchRequest) {
+        if self.has_pending_updates() {
+            self.switch_request = Some(request);
+        } else {
+            self.switch(request);
+        }
+    }
+
+    fn switch(&mut self, request: SwitchRequest) {
+        match request {
+            SwitchRequest::Document {
+                node_id,
+                token_index,
+            } => {
+                self.apply_pending_updates();
+                self.requested_document = Some(node_id);
+                self.requested_token = token_index;
+            }
+            SwitchRequest::Corpus(selection) => self.switch_corpus(selection),
+        }
+    }
+
+    /// Pending changes of the current editor are applied before and the
+    /// selection is only changed once all changesets of the currently
+    /// selected corpus have been applied to its graph.
+    fn switch_corpus(&mut self, selection: Option<String>) {
+        self.apply_pending_updates();
+        self.requested_corpus = Some(selection);
+        self.handle_corpus_request();
+        if self.requested_corpus.is_some() {
+            self.notifier.add_toast(Toast::info(
+                "Waiting for pending changes to be applied before switching the corpus.",
+            ));
+        }
+    }
+
+    fn has_changesets_in_flight(&self) -> bool {
+        self.project
+            .selected_corpus
+            .as_ref()
+            .is_some_and(|c| self.project.has_changesets_in_flight(&c.name))
     }
 
+    fn handle_corpus_request(&mut self) {
+        if self.requested_corpus.is_some()
+            && !self.has_pending_updates()
+            && !self.has_changesets_in_flight()
+        {
+            if let Some(selection) = self.requested_corpus.take() {
+                // The documents of the additional windows belong to the old corpus
+                self.detached_documents.clear();
+                self.project.select_corpus(selection);
+                self.load_editor(true);
+            }
+        }
+    }
+
+    /// Apply the pending updates of all editors, which are undone in a
+    /// single step.
     fn apply_pending_updates(&mut self) {
-        if let Some(editor) = self.current_editor.get_mut() {
-            editor.apply_pending_updates_for_editor();
+        let group = UndoGroup::new();
+        for editor in self.editors_mut() {
+            editor.apply_pending_updates_in_group(Some(group));
         }
     }
 
     fn has_pending_updates(&self) -> bool {
+        self.current_editor
+            .get()
+            .into_iter()
+            .chain(
+                self.detached_documents
+                    .iter()
+                    .filter_map(|d| d.editor.as_ref()),
+            )
+            .any(|editor| editor.has_pending_updates())
+    }
+
+    /// Forget the changes that have not been applied yet and load the
+    /// editors again to show the unchanged state of the corpus.
+    fn discard_pending_updates(&mut self) {
+        if !self.has_pending_updates() {
+            return;
+        }
+        for editor in self.editors_mut() {
+            editor.discard_pending_updates();
+        }
+        self.load_editor(true);
+    }
+
+    /// Unload the selected corpus once all pending changes have been applied.
+    fn handle_unload_request(&mut self) {
+        if self.unload_requested && !self.has_pending_updates() && !self.jobs.has_running_jobs() {
+            self.unload_requested = false;
+            self.main_view = MainView::Start;
+            self.detached_documents.clear();
+            self.project.unload_corpus();
+            self.load_editor(true);
+        }
+    }
+
+    /// Switch to another profile once all pending changes have been applied.
+    fn request_profile(&mut self, profile: String) {
+        self.apply_pending_updates();
+        self.requested_profile = Some(profile);
+    }
+
+    fn handle_profile_request(&mut self) {
+        if self.requested_profile.is_some()
+            && !self.has_pending_updates()
+            && !self.jobs.has_running_jobs()
+        {
+            if let Some(profile) = self.requested_profile.take() {
+                self.switch_profile(&profile);
+            }
+        }
+    }
+
+    /// Store the configuration of the active project and replace it with the
+    /// one of the given profile. Unknown profiles are created with an empty
+    /// list of corpora and default settings.
+    pub(crate) fn switch_profile(&mut self, profile: &str) {
+        let profile = profile.trim();
+        if profile.is_empty() || profile == self.active_profile {
+            return;
+        }
+        if profile.contains(['/', '\\']) || profile.starts_with('.') {
+            self.notifier.add_toast(Toast::error(format!(
+                "\"{profile}\" can not be used as profile name"
+            )));
+            return;
+        }
+        if self.project.selected_corpus.is_some() {
+            self.main_view = MainView::Start;
+            self.detached_documents.clear();
+            self.project.unload_corpus();
+            self.load_editor(true);
+        }
+        let current_config = self.project_configuration();
+        self.profiles
+            .insert(self.active_profile.clone(), current_config);
+        let config = self.profiles.remove(profile).unwrap_or_default();
+
+        self.project.corpus_locations.clear();
+        self.project.corpus_tags.clear();
+        self.project.corpus_tokenizers.clear();
+        self.project.corpus_layers.clear();
+        self.project.corpus_namespaces.clear();
+        self.project.pinned_corpora.clear();
+        self.project.document_templates.clear();
+        self.project.missing_corpora.clear();
+        self.project.portable = false;
+        self.text_search.clear_queries();
+        self.virtual_collections.clear_collections();
+        // Relative corpus locations are resolved against the storage directory of the profile
+        self.project.storage_profile = (profile != DEFAULT_PROFILE).then(|| profile.to_string());
+        self.apply_project_configuration(config);
+        self.active_profile = profile.to_string();
+        self.notifier
+            .add_toast(Toast::info(format!("Switched to profile \"{profile}\"")));
+    }
+
+    fn checkpoint_menu(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(self.project.selected_corpus.is_some(), |ui| {
+            ui.menu_button("Checkpoints", |ui| {
+                // Checkpoints must include all changes the user has made
+                let is_settled = !self.has_pending_updates()
+                    && !self
+                        .project
+                        .selected_corpus
+                        .as_ref()
+                        .is_some_and(|c| self.project.has_changesets_in_flight(&c.name));
+                let mut revert = None;
+                for (idx, checkpoint) in self.project.checkpoints().iter().enumerate() {
+                    let at_checkpoint = self.project.is_at_checkpoint(idx);
+                    if ui
+                        .add_enabled(
+                            is_settled && !at_checkpoint,
+                            Button::new(format!("Revert to \"{}\"", checkpoint.name)),
+                        )
+                        .on_hover_text(format!(
+                            "{} change(s) since the last save",
+                            checkpoint.number_of_changes()
+                        ))
+                        .on_disabled_hover_text(if at_checkpoint {
+                            "The corpus is in the state of this checkpoint"
+                        } else {
+                            "Apply or discard the pending changes first"
+                        })
+                        .clicked()
+                    {
+                        revert = Some(idx);
+                    }
+                }
+                if self.project.checkpoints().is_empty() {
+                    ui.label("No checkpoints since the last save");
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut self.new_checkpoint_name)
+                            .hint_text("Checkpoint name")
+                            .desired_width(160.0),
+                    );
+                    if ui
+                        .add_enabled(
+                            is_settled && !self.new_checkpoint_name.trim().is_empty(),
+                            Button::new("Create"),
+                        )
+                        .on_disabled_hover_text(
+                            "Enter a name and apply or discard the pending changes first",
+                        )
+                        .clicked()
+                    {
+                        let name = std::mem::take(&mut self.new_checkpoint_name);
+                        self.project.create_checkpoint(&name);
+                        ui.close_menu();
+                    }
+                });
+                if let Some(idx) = revert {
+                    self.project.revert_to_checkpoint(idx);
+                    ui.close_menu();
+                }
+            });
+        });
+    }
+
+    fn profile_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button(format!("Profile: {}", self.active_profile), |ui| {
+            let can_switch = self.requested_profile.is_none() && !self.jobs.has_running_jobs();
+            let mut selected = None;
+            let names = self
+                .profiles
+                .keys()
+                .chain(std::iter::once(&self.active_profile));
+            for name in names.collect::<std::collections::BTreeSet<_>>() {
+                if ui
+                    .add_enabled(
+                        can_switch,
+                        egui::SelectableLabel::new(*name == self.active_profile, name),
+                    )
+                    .clicked()
+                {
+                    selected = Some(name.clone());
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(&mut self.new_profile_name)
+                        .hint_text("Profile name")
+                        .desired_width(120.0),
+                );
+                if ui
+                    .add_enabled(
+                        can_switch && !self.new_profile_name.trim().is_empty(),
+                        Button::new("Create"),
+                    )
+                    .clicked()
+                {
+                    selected = Some(std::mem::take(&mut self.new_profile_name));
+                }
+            });
+            if let Some(profile) = selected {
+                self.request_profile(profile);
+                ui.close_menu();
+            }
+        });
+    }
+
+    /// Open another document in the editor. If an editor has pending
+    /// changes, the user is asked whether to apply or discard them first.
+    pub(crate) fn navigate_to_document(&mut self, node_id: NodeID) {
+        self.request_switch(SwitchRequest::Document {
+            node_id,
+            token_index: None,
+        });
+    }
+
+    /// Open the document and scroll to the base token with the given index.
+    pub(crate) fn navigate_to_token(&mut self, node_id: NodeID, token_index: usize) {
+        self.request_switch(SwitchRequest::Document {
+            node_id,
+            token_index: Some(token_index),
+        });
+    }
+
+    /// Ask whether the pending changes should be applied or discarded before
+    /// switching to another document or corpus, like it is done on
+    /// shutdown.
+    fn handle_switch_dialog(&mut self, ctx: &egui::Context) {
+        let Some(request) = self.switch_request.clone() else {
+            return;
+        };
+        if !self.has_pending_updates() {
+            // The changes have been applied in the meantime
+            self.switch_request = None;
+            self.switch(request);
+            return;
+        }
+        let target = match &request {
+            SwitchRequest::Document { .. } => "opening the other document".to_string(),
+            SwitchRequest::Corpus(Some(corpus_name)) => format!("selecting \"{corpus_name}\""),
+            SwitchRequest::Corpus(None) => "closing the corpus".to_string(),
+        };
+        let mut apply = None;
+        let response = egui::Modal::new("unsaved_changes_switch".into()).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(egui_phosphor::regular::WARNING)
+                        .color(Color32::ORANGE)
+                        .size(32.0),
+                );
+                ui.label(format!(
+                    "There are changes that have not been applied to the corpus yet. Do you want to apply them before {target}?"
+                ));
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Apply and continue").clicked() {
+                    apply = Some(true);
+                }
+                if ui
+                    .button(RichText::new("Discard and continue").color(Color32::RED))
+                    .clicked()
+                {
+                    apply = Some(false);
+                }
+                ui.add_space(5.0);
+                ui.button("Cancel").clicked()
+            })
+            .inner
+        });
+        if response.inner || response.should_close() {
+            self.switch_request = None;
+        } else if let Some(apply) = apply {
+            self.switch_request = None;
+            if !apply {
+                self.discard_pending_updates();
+            }
+            self.switch(request);
+        }
+    }
+
+    fn handle_document_request(&mut self) {
+        if self.requested_document.is_some()
+            && !self.has_pending_updates()
+            && !self.jobs.has_running_jobs()
+        {
+            if let Some(node_id) = self.requested_document.take() {
+                self.change_view(MainView::EditDocument { node_id });
+            }
+        }
+        if self.requested_document.is_none() {
+            if let Some(token_index) = self.requested_token {
+                let document_editor = self
+                    .current_editor
+                    .get_mut()
+                    .and_then(|editor| editor.any_mut().downcast_mut::<DocumentEditor>());
+                if let Some(document_editor) = document_editor {
+                    document_editor.scroll_to_token(token_index);
+                    self.requested_token = None;
+                }
+            }
+            if !self.requested_layers.is_empty() {
+                let document_editor = self
+                    .current_editor
+                    .get_mut()
+                    .and_then(|editor| editor.any_mut().downcast_mut::<DocumentEditor>());
+                if let Some(document_editor) = document_editor {
+                    document_editor.add_empty_layers(&std::mem::take(&mut self.requested_layers));
+                }
+            }
+        }
+    }
+
+    /// Open a document that has just been added to the selected corpus and
+    /// show the empty segmentation `layers` of its template.
+    pub(crate) fn open_created_document(&mut self, node_name: &str, layers: Vec<String>) {
+        let Some(corpus) = &self.project.selected_corpus else {
+            return;
+        };
+        let node_id = self
+            .project
+            .corpus_cache
+            .get(&corpus.location)
+            .and_then(|graph| {
+                let node_id = graph
+                    .read()
+                    .get_node_annos()
+                    .get_node_id_from_name(node_name)?;
+                Ok(node_id)
+            });
+        match node_id {
+            Ok(Some(node_id)) => {
+                self.notifier
+                    .add_toast(Toast::info(format!("Created document \"{node_name}\"")));
+                self.requested_layers = layers;
+                self.navigate_to_document(node_id);
+            }
+            Ok(None) => self
+                .notifier
+                .add_toast(Toast::error(format!("Missing document \"{node_name}\""))),
+            Err(e) => self.notifier.report_error(e),
+        }
+    }
+
+    /// The shortcuts handled by [`AnnatomicApp::consume_shortcuts`] itself and
+    /// the ones of the current editor.
+    pub(crate) fn active_shortcuts(&self) -> Vec<ShortcutEntry> {
+        let entry = |context, description: &str, shortcut| ShortcutEntry {
+            context,
+            description: description.to_string(),
+            shortcut,
+        };
+        let mut result = vec![
+            entry("General", "Apply pending changes", SAVE_SHORTCUT),
+            entry("General", "Discard pending changes", DISCARD_SHORTCUT),
+            entry("General", "Undo", UNDO_SHORTCUT),
+            entry("General", "Redo", REDO_SHORTCUT),
+            entry("General", "Show keyboard shortcuts", SHORTCUT_HELP_SHORTCUT),
+            entry("General", "Quit", QUIT_SHORTCUT),
+        ];
+        if let MainView::EditDocument { .. } = self.main_view {
+            result.push(entry(
+                "Document navigation",
+                "Previous document",
+                PREVIOUS_DOCUMENT_SHORTCUT,
+            ));
+            result.push(entry(
+                "Document navigation",
+                "Next document",
+                NEXT_DOCUMENT_SHORTCUT,
+            ));
+        }
         if let Some(editor) = self.current_editor.get() {
-            editor.has_pending_updates()
-        } else {
-            false
+            result.extend(editor.shortcuts());
         }
+        if self.review.is_reviewing() {
+            result.push(entry(
+                "Review",
+                "Accept annotation",
+                views::review::ACCEPT_SHORTCUT,
+            ));
+            result.push(entry(
+                "Review",
+                "Reject annotation",
+                views::review::REJECT_SHORTCUT,
+            ));
+            result.push(entry(
+                "Review",
+                "Skip annotation",
+                views::review::SKIP_SHORTCUT,
+            ));
+        }
+        result
     }
 
     fn consume_shortcuts(&mut self, ctx: &egui::Context) {
@@ -325,10 +1245,34 @@ impl AnnatomicApp {
         if ctx.input_mut(|i| i.consume_shortcut(&SAVE_SHORTCUT)) {
             self.apply_pending_updates();
         }
+        if ctx.input_mut(|i| i.consume_shortcut(&DISCARD_SHORTCUT)) {
+            self.discard_pending_updates();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&SHORTCUT_HELP_SHORTCUT)) {
+            self.shortcuts_open = !self.shortcuts_open;
+        }
+        if let MainView::EditDocument { .. } = self.main_view {
+            let (previous, next) = self
+                .current_editor
+                .get()
+                .map(|editor| editor.get_neighbour_documents())
+                .unwrap_or_default();
+            if let Some(previous) = previous {
+                if ctx.input_mut(|i| i.consume_shortcut(&PREVIOUS_DOCUMENT_SHORTCUT)) {
+                    self.navigate_to_document(previous);
+                }
+            }
+            if let Some(next) = next {
+                if ctx.input_mut(|i| i.consume_shortcut(&NEXT_DOCUMENT_SHORTCUT)) {
+                    self.navigate_to_document(next);
+                }
+            }
+        }
     }
 
     pub(crate) fn show(&mut self, ctx: &egui::Context, frame_info: &IntegrationInfo) {
         egui_extras::install_image_loaders(ctx);
+        self.settings.color_scheme.apply(ctx);
 
         // Check if we need to react to a closing event
         if let ShutdownRequest::None = self.shutdown_request {
@@ -368,7 +1312,32 @@ impl AnnatomicApp {
 
     fn show_view(&mut self, ctx: &egui::Context, frame_info: &IntegrationInfo) {
         self.consume_shortcuts(ctx);
+        self.handle_unload_request();
+        self.handle_profile_request();
+        self.handle_document_request();
+        self.handle_corpus_request();
         self.handle_corpus_confirmation_dialog(ctx);
+        self.handle_missing_corpus_dialog(ctx);
+        self.handle_switch_dialog(ctx);
+        self.project.check_integrity_if_due();
+        settings::show_window(ctx, self);
+        views::shortcuts::show_window(ctx, self);
+        views::rename_annotation::show_window(ctx, self);
+        views::map_values::show_window(ctx, self);
+        views::review::show_window(ctx, self);
+        views::whitespace::show_window(ctx, self);
+        views::diagnostics::show_window(ctx, self);
+        views::job_queue::show_window(ctx, self);
+        views::new_document::show_window(ctx, self);
+        views::provenance::show_window(ctx, self);
+        views::text_search::show_window(ctx, self);
+        views::virtual_collections::show_window(ctx, self);
+        views::kwic_export::show_window(ctx, self);
+        views::frequency::show_window(ctx, self);
+        views::component_browser::show_window(ctx, self);
+        views::ngrams::show_window(ctx, self);
+        views::ordering_chain::show_window(ctx, self);
+        views::detached::show_windows(ctx, self);
         let has_pending_updates = self.has_pending_updates();
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -384,6 +1353,124 @@ impl AnnatomicApp {
                     {
                         self.apply_pending_updates();
                     }
+                    if ui
+                        .add_enabled(
+                            has_pending_updates,
+                            Button::new("Discard pending changes")
+                                .shortcut_text(ctx.format_shortcut(&DISCARD_SHORTCUT)),
+                        )
+                        .on_hover_text("Revert the editors to the state of the corpus")
+                        .clicked()
+                    {
+                        self.discard_pending_updates();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.project.read_only,
+                            Button::new("Optimize corpus storage"),
+                        )
+                        .on_hover_text("Use more compact graph storages for the selected corpus")
+                        .clicked()
+                    {
+                        self.apply_pending_updates();
+                        self.project.optimize_storage();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.unload_requested,
+                            Button::new("Unload corpus from memory"),
+                        )
+                        .on_hover_text(
+                            "Apply all pending changes and free the memory used by the selected corpus",
+                        )
+                        .clicked()
+                    {
+                        self.apply_pending_updates();
+                        self.unload_requested = true;
+                    }
+                    if let Some(editor) = self.current_editor.get_mut() {
+                        editor.add_file_menu_entries(ui);
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("Export concordance (KWIC)…"),
+                        )
+                        .clicked()
+                    {
+                        self.apply_pending_updates();
+                        self.kwic_export.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("Export document metadata (CSV)…"),
+                        )
+                        .clicked()
+                    {
+                        let dlg = rfd::FileDialog::new()
+                            .add_filter("CSV (*.csv)", &["csv"])
+                            .set_file_name("metadata.csv");
+                        if let Some(path) = dlg.save_file() {
+                            self.apply_pending_updates();
+                            self.project.export_metadata_csv(path);
+                        }
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.project.read_only,
+                            Button::new("Import document metadata (CSV)…"),
+                        )
+                        .on_hover_text("Change the metadata to the values of an edited export")
+                        .clicked()
+                    {
+                        let dlg = rfd::FileDialog::new().add_filter("CSV (*.csv)", &["csv"]);
+                        if let Some(path) = dlg.pick_file() {
+                            self.apply_pending_updates();
+                            self.project.import_metadata_csv(path);
+                        }
+                    }
+                    ui.separator();
+                    self.profile_menu(ui);
+                    if ui.button("Export project configuration…").clicked() {
+                        let dlg = rfd::FileDialog::new()
+                            .add_filter("TOML (*.toml)", &["toml"])
+                            .set_file_name("annatomic-project.toml");
+                        if let Some(path) = dlg.save_file() {
+                            let result = self.export_project_configuration(&path);
+                            self.notifier.report_result(result);
+                        }
+                    }
+                    if ui.button("Import project configuration…").clicked() {
+                        let dlg = rfd::FileDialog::new().add_filter("TOML (*.toml)", &["toml"]);
+                        if let Some(path) = dlg.pick_file() {
+                            let result = self.import_project_configuration(&path);
+                            self.notifier.report_result(result);
+                        }
+                    }
+                    if ui
+                        .add_enabled(!self.project.portable, Button::new("Make project portable"))
+                        .on_hover_text(
+                            "Store corpus locations relative to the corpus storage directory, \
+                             so it can be moved to another machine",
+                        )
+                        .clicked()
+                    {
+                        let outside = self.project.make_portable();
+                        if outside.is_empty() {
+                            self.notifier.add_toast(Toast::info(
+                                "All corpus locations are relative to the storage directory.",
+                            ));
+                        } else {
+                            self.notifier.add_toast(Toast::warning(format!(
+                                "Corpora outside the storage directory keep their absolute location: {}",
+                                outside.join(", ")
+                            )));
+                        }
+                    }
+                    if ui.button("Settings…").clicked() {
+                        self.settings_open = true;
+                    }
                     if ui
                         .add(Button::new("Quit").shortcut_text(ctx.format_shortcut(&QUIT_SHORTCUT)))
                         .clicked()
@@ -396,6 +1483,57 @@ impl AnnatomicApp {
                         editor.add_edit_menu_entries(ui);
                     }
 
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.project.read_only,
+                            Button::new("Rename annotation…"),
+                        )
+                        .clicked()
+                    {
+                        self.rename_annotation.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.project.read_only,
+                            Button::new("Map annotation values…"),
+                        )
+                        .clicked()
+                    {
+                        self.value_mapping.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.project.read_only,
+                            Button::new("Review annotations…"),
+                        )
+                        .clicked()
+                    {
+                        self.apply_pending_updates();
+                        self.review.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.project.read_only,
+                            Button::new("Normalize whitespace…"),
+                        )
+                        .clicked()
+                    {
+                        self.whitespace.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some() && !self.project.read_only,
+                            Button::new("Detect document languages"),
+                        )
+                        .on_hover_text(
+                            "Add the language of the token text as metadata to the documents without one",
+                        )
+                        .clicked()
+                    {
+                        self.apply_pending_updates();
+                        self.project.detect_document_languages();
+                    }
+                    ui.separator();
                     if ui
                         .add_enabled(
                             self.project.has_undo(),
@@ -414,22 +1552,136 @@ impl AnnatomicApp {
                     {
                         self.project.redo();
                     }
+                    self.checkpoint_menu(ui);
                 });
                 ui.menu_button("View", |ui| {
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("Search text…"),
+                        )
+                        .clicked()
+                    {
+                        self.text_search.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("Virtual collections…"),
+                        )
+                        .on_hover_text("Documents grouped by saved AQL queries")
+                        .clicked()
+                    {
+                        self.virtual_collections.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("Frequency analysis…"),
+                        )
+                        .clicked()
+                    {
+                        self.frequency.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("Components…"),
+                        )
+                        .on_hover_text("List, rename and delete the components of the graph")
+                        .clicked()
+                    {
+                        self.component_browser.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("N-grams and collocations…"),
+                        )
+                        .clicked()
+                    {
+                        self.ngrams.open = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.project.selected_corpus.is_some(),
+                            Button::new("Provenance of selection…"),
+                        )
+                        .clicked()
+                    {
+                        let selected_node = self
+                            .current_editor
+                            .get()
+                            .and_then(|editor| editor.get_selected_node_name());
+                        self.provenance.open_for(selected_node);
+                    }
+                    if self.args.dev
+                        && ui
+                            .add_enabled(
+                                matches!(self.main_view, MainView::EditDocument { .. }),
+                                Button::new("Ordering chains of document…"),
+                            )
+                            .on_hover_text("Show the ordering components to diagnose broken chains")
+                            .clicked()
+                    {
+                        self.ordering_chain.open = true;
+                    }
+                    if ui
+                        .button("Jobs…")
+                        .on_hover_text("Queued, running and recently finished jobs")
+                        .clicked()
+                    {
+                        self.job_queue.open = true;
+                    }
+                    ui.separator();
                     egui::gui_zoom::zoom_menu_buttons(ui);
                 });
+                ui.menu_button("Help", |ui| {
+                    if ui
+                        .add(
+                            Button::new("Keyboard shortcuts…")
+                                .shortcut_text(ctx.format_shortcut(&SHORTCUT_HELP_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.shortcuts_open = true;
+                    }
+                    if ui.button("Create diagnostic bundle…").clicked() {
+                        self.diagnostics.open = true;
+                    }
+                });
                 ui.add_space(16.0);
                 ui.separator();
-                let marker_color = if ui.ctx().theme() == Theme::Light {
-                    CHANGE_PENDING_COLOR_LIGHT
-                } else {
-                    CHANGE_PENDING_COLOR_DARK
-                };
-                if self.has_pending_updates() {
+                let marker_color = appearance::change_pending_color(ui);
+                if self.settings.manual_commit && self.has_pending_updates() {
+                    let count = self
+                        .current_editor
+                        .get()
+                        .map(|editor| editor.pending_update_count())
+                        .unwrap_or_default();
+                    ui.label(
+                        RichText::new(format!("{count} uncommitted changes")).color(marker_color),
+                    )
+                    .on_hover_text(format!(
+                        "Save with {} to apply them",
+                        ctx.format_shortcut(&SAVE_SHORTCUT)
+                    ));
+                    if ui.small_button("Discard").clicked() {
+                        self.discard_pending_updates();
+                    }
+                } else if self.has_pending_updates() {
                     ui.label(RichText::new("Has pending changes").color(marker_color));
                 } else {
                     ui.label("No pending changes");
                 }
+                if self.project.read_only {
+                    ui.separator();
+                    ui.label(RichText::new("Read-only").color(marker_color))
+                        .on_hover_text("The corpus is opened by another annatomic instance.");
+                    if ui.button("Take over lock").clicked() {
+                        self.project.force_lock_selected_corpus();
+                    }
+                }
                 ui.separator();
                 ui.add_space(16.0);
                 if self.args.dev {
@@ -437,12 +1689,48 @@ impl AnnatomicApp {
                         ui.label(format!("CPU usage: {:.1} ms / frame", seconds * 1000.0));
                         ui.add_space(16.0);
                     }
+                    let (undo_memory, undo_steps) = self.project.undo_memory_usage();
+                    ui.label(format!(
+                        "Undo history: {undo_steps} steps, {:.1} MiB",
+                        undo_memory as f64 / (1024.0 * 1024.0)
+                    ));
+                    ui.add_space(16.0);
+                    let process_memory = memory_stats::memory_stats()
+                        .map(|stats| stats.physical_mem)
+                        .unwrap_or_default();
+                    let cache_memory = if let Some(size) = self.project.cache_memory_usage() {
+                        format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0))
+                    } else {
+                        "not loaded".to_string()
+                    };
+                    ui.label(format!(
+                        "Memory: {:.1} MiB (corpus {cache_memory})",
+                        process_memory as f64 / (1024.0 * 1024.0)
+                    ))
+                    .on_hover_text(
+                        "Physical memory of the application and the approximate memory the cached corpus used when it was loaded",
+                    );
+                    ui.add_space(16.0);
                 }
 
                 egui::widgets::global_theme_preference_switch(ui);
             });
         });
 
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if let Some(corpus) = &self.project.selected_corpus {
+                    ui.label(format!("Corpus: {}", corpus.name));
+                    ui.separator();
+                }
+                if let Some(editor) = self.current_editor.get() {
+                    editor.status_bar(ui);
+                    ui.separator();
+                    ui.label(format!("{} pending actions", editor.pending_update_count()));
+                }
+            });
+        });
+
         egui::CentralPanel::default().show(ctx, |ui| {
             let has_jobs = self.jobs.clone().show(ui, self);
             if !has_jobs {
@@ -456,6 +1744,16 @@ impl AnnatomicApp {
                 }
             }
         });
+        let mut navigation_request = None;
+        if let Some(editor) = self.current_editor.get_mut() {
+            if editor.take_disabled_confirmation() {
+                self.settings.confirm_deletion = false;
+            }
+            navigation_request = editor.take_navigation_request();
+        }
+        if let Some((document, token_index)) = navigation_request {
+            self.navigate_to_token(document, token_index);
+        }
     }
 }
 
@@ -465,6 +1763,12 @@ impl eframe::App for AnnatomicApp {
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
 
+    /// The application state is saved at the interval configured in the
+    /// settings and not only at the default interval of the framework.
+    fn auto_save_interval(&self) -> Duration {
+        self.settings.autosave_interval()
+    }
+
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         self.load_editor(false);
@@ -473,7 +1777,9 @@ impl eframe::App for AnnatomicApp {
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         // Persist the changes in the annotation graph
-        self.notifier
-            .report_result(self.project.persist_changes_on_exit());
+        self.notifier.report_result(
+            self.project
+                .persist_changes_on_exit(self.settings.optimize_storage_on_exit),
+        );
     }
 }
@@ -0,0 +1,154 @@
+use egui::{Color32, Id, Stroke, Theme, Ui, Visuals};
+use serde::{Deserialize, Serialize};
+
+use super::{CHANGE_PENDING_COLOR_DARK, CHANGE_PENDING_COLOR_LIGHT};
+
+#[cfg(test)]
+mod tests;
+
+const COLOR_SCHEME_ID: &str = "annatomic-color-scheme";
+
+/// Colors for the segmentation layers of the default scheme.
+const DEFAULT_LAYER_COLORS: &[Color32] = &[
+    Color32::from_rgb(100, 150, 230),
+    Color32::from_rgb(230, 160, 60),
+    Color32::from_rgb(110, 190, 110),
+    Color32::from_rgb(200, 110, 200),
+    Color32::from_rgb(90, 190, 190),
+];
+
+/// The palette by Okabe and Ito, which can be distinguished with all common
+/// forms of color blindness.
+const COLOR_BLIND_SAFE_LAYER_COLORS: &[Color32] = &[
+    Color32::from_rgb(0x00, 0x72, 0xB2),
+    Color32::from_rgb(0xE6, 0x9F, 0x00),
+    Color32::from_rgb(0x00, 0x9E, 0x73),
+    Color32::from_rgb(0xCC, 0x79, 0xA7),
+    Color32::from_rgb(0x56, 0xB4, 0xE9),
+    Color32::from_rgb(0xF0, 0xE4, 0x42),
+];
+
+const HIGH_CONTRAST_LAYER_COLORS_DARK: &[Color32] = &[
+    Color32::from_rgb(0, 255, 255),
+    Color32::from_rgb(255, 255, 0),
+    Color32::from_rgb(0, 255, 0),
+    Color32::from_rgb(255, 0, 255),
+];
+
+const HIGH_CONTRAST_LAYER_COLORS_LIGHT: &[Color32] = &[
+    Color32::from_rgb(0, 0, 200),
+    Color32::from_rgb(160, 80, 0),
+    Color32::from_rgb(0, 120, 0),
+    Color32::from_rgb(150, 0, 150),
+];
+
+/// Color presets of the user interface, which can be combined with the
+/// light or dark theme.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub(crate) enum ColorScheme {
+    #[default]
+    Default,
+    HighContrast,
+    ColorBlindSafe,
+}
+
+impl ColorScheme {
+    pub(crate) const ALL: [ColorScheme; 3] = [
+        ColorScheme::Default,
+        ColorScheme::HighContrast,
+        ColorScheme::ColorBlindSafe,
+    ];
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            ColorScheme::Default => "Default",
+            ColorScheme::HighContrast => "High contrast",
+            ColorScheme::ColorBlindSafe => "Color-blind safe",
+        }
+    }
+
+    /// The color scheme that has been applied to the context.
+    pub(crate) fn current(ctx: &egui::Context) -> Self {
+        ctx.data(|d| d.get_temp(Id::new(COLOR_SCHEME_ID)))
+            .unwrap_or_default()
+    }
+
+    /// Color to mark elements with changes that have not been applied yet.
+    pub(crate) fn change_pending_color(&self, theme: Theme) -> Color32 {
+        match (self, theme) {
+            (ColorScheme::Default, Theme::Dark) => CHANGE_PENDING_COLOR_DARK,
+            (ColorScheme::Default, Theme::Light) => CHANGE_PENDING_COLOR_LIGHT,
+            (ColorScheme::HighContrast, Theme::Dark) => Color32::from_rgb(170, 0, 170),
+            (ColorScheme::HighContrast, Theme::Light) => Color32::from_rgb(255, 170, 0),
+            (ColorScheme::ColorBlindSafe, Theme::Dark) => Color32::from_rgb(0xD5, 0x5E, 0x00),
+            (ColorScheme::ColorBlindSafe, Theme::Light) => Color32::from_rgb(0xE6, 0x9F, 0x00),
+        }
+    }
+
+    /// Color to distinguish the segmentation layer with the given index.
+    pub(crate) fn layer_color(&self, theme: Theme, layer_idx: usize) -> Color32 {
+        let palette = match (self, theme) {
+            (ColorScheme::Default, _) => DEFAULT_LAYER_COLORS,
+            (ColorScheme::HighContrast, Theme::Dark) => HIGH_CONTRAST_LAYER_COLORS_DARK,
+            (ColorScheme::HighContrast, Theme::Light) => HIGH_CONTRAST_LAYER_COLORS_LIGHT,
+            (ColorScheme::ColorBlindSafe, _) => COLOR_BLIND_SAFE_LAYER_COLORS,
+        };
+        palette[layer_idx % palette.len()]
+    }
+
+    fn visuals(&self, theme: Theme) -> Visuals {
+        let mut visuals = theme.default_visuals();
+        if *self == ColorScheme::HighContrast {
+            let (background, foreground, selection) = match theme {
+                Theme::Dark => (
+                    Color32::BLACK,
+                    Color32::WHITE,
+                    Color32::from_rgb(0, 90, 200),
+                ),
+                Theme::Light => (
+                    Color32::WHITE,
+                    Color32::BLACK,
+                    Color32::from_rgb(120, 180, 255),
+                ),
+            };
+            visuals.override_text_color = Some(foreground);
+            visuals.panel_fill = background;
+            visuals.window_fill = background;
+            visuals.extreme_bg_color = background;
+            visuals.faint_bg_color = background;
+            visuals.window_stroke = Stroke::new(2.0, foreground);
+            visuals.widgets.noninteractive.bg_stroke = Stroke::new(1.0, foreground);
+            visuals.widgets.inactive.bg_stroke = Stroke::new(1.0, foreground);
+            visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, foreground);
+            visuals.widgets.active.bg_stroke = Stroke::new(2.0, foreground);
+            visuals.selection.bg_fill = selection;
+            visuals.selection.stroke = Stroke::new(2.0, foreground);
+        }
+        visuals
+    }
+
+    /// Change the visuals of the context to this scheme, if it is not already
+    /// applied.
+    pub(crate) fn apply(&self, ctx: &egui::Context) {
+        let is_applied = ctx
+            .data(|d| d.get_temp::<ColorScheme>(Id::new(COLOR_SCHEME_ID)))
+            .is_some_and(|applied| applied == *self);
+        if !is_applied {
+            ctx.set_visuals_of(Theme::Dark, self.visuals(Theme::Dark));
+            ctx.set_visuals_of(Theme::Light, self.visuals(Theme::Light));
+            ctx.data_mut(|d| d.insert_temp(Id::new(COLOR_SCHEME_ID), *self));
+        }
+    }
+}
+
+/// Color to mark elements with changes that have not been applied yet,
+/// according to the current color scheme and theme.
+pub(crate) fn change_pending_color(ui: &Ui) -> Color32 {
+    ColorScheme::current(ui.ctx()).change_pending_color(ui.ctx().theme())
+}
+
+/// Color of the segmentation layer with the given index, according to the
+/// current color scheme and theme.
+pub(crate) fn layer_color(ui: &Ui, layer_idx: usize) -> Color32 {
+    ColorScheme::current(ui.ctx()).layer_color(ui.ctx().theme(), layer_idx)
+}
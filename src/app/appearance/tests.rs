@@ -0,0 +1,34 @@
+use egui::Theme;
+use pretty_assertions::assert_eq;
+
+use super::ColorScheme;
+
+#[test]
+fn layer_colors_are_distinguishable() {
+    for scheme in ColorScheme::ALL {
+        for theme in [Theme::Dark, Theme::Light] {
+            let first = scheme.layer_color(theme, 0);
+            assert_ne!(first, scheme.layer_color(theme, 1));
+            assert_ne!(first, scheme.layer_color(theme, 2));
+            assert_ne!(
+                scheme.change_pending_color(theme),
+                theme.default_visuals().panel_fill
+            );
+        }
+    }
+    // The palette is repeated if there are more layers than colors
+    assert_eq!(
+        ColorScheme::ColorBlindSafe.layer_color(Theme::Dark, 0),
+        ColorScheme::ColorBlindSafe.layer_color(Theme::Dark, 6)
+    );
+}
+
+#[test]
+fn apply_color_scheme() {
+    let ctx = egui::Context::default();
+    assert_eq!(ColorScheme::Default, ColorScheme::current(&ctx));
+    ColorScheme::HighContrast.apply(&ctx);
+    assert_eq!(ColorScheme::HighContrast, ColorScheme::current(&ctx));
+    ctx.set_theme(Theme::Dark);
+    assert_eq!(egui::Color32::BLACK, ctx.style().visuals.panel_fill);
+}
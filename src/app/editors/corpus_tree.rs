@@ -1,12 +1,17 @@
-use std::{collections::HashSet, fmt::Debug, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::{Arc, OnceLock},
+};
 
 use anyhow::Context;
 use egui::{
-    mutex::RwLock, Button, CollapsingHeader, Color32, Id, RichText, ScrollArea, TextEdit, Theme,
-    Ui, Widget,
+    mutex::RwLock, pos2, vec2, Button, CollapsingHeader, Color32, CursorIcon, Id, Rect, RichText,
+    ScrollArea, Sense, TextEdit, Ui, UiBuilder, Widget, WidgetInfo,
 };
 use egui_extras::{Column, TableRow};
 use egui_notify::Toast;
+use egui_phosphor::regular::{BOOKS, FILE_TEXT, FOLDER};
 use graphannis::{
     graph::{AnnoKey, Edge, NodeID, WriteableGraphStorage},
     model::{AnnotationComponent, AnnotationComponentType::PartOf},
@@ -18,17 +23,32 @@ use graphannis::{
 };
 use graphannis_core::{
     annostorage::ValueSearch,
-    graph::{storage::adjacencylist::AdjacencyListStorage, ANNIS_NS, NODE_NAME_KEY, NODE_TYPE},
+    graph::{
+        storage::{adjacencylist::AdjacencyListStorage, GraphStorage},
+        ANNIS_NS, NODE_NAME_KEY, NODE_TYPE,
+    },
 };
 
 use crate::app::{
-    job_executor::JobExecutor, views::Editor, Notifier, CHANGE_PENDING_COLOR_DARK,
-    CHANGE_PENDING_COLOR_LIGHT,
+    appearance::change_pending_color,
+    job_executor::JobExecutor,
+    project::history::UndoGroup,
+    util::language::{Language, LANGUAGE_KEY},
+    views::Editor,
+    Notifier,
 };
 
 #[cfg(test)]
 mod tests;
 
+/// Annotations in the ANNIS namespace whose value can not be edited, because
+/// they are needed to find the nodes.
+const READ_ONLY_ANNIS_NAMES: [&str; 2] = ["node_name", "node_type"];
+
+/// Persisted ratio of the width used by the corpus structure.
+const SPLIT_RATIO_ID: &str = "corpus-tree-split-ratio";
+const SEPARATOR_WIDTH: f32 = 8.0;
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
 struct MetaEntry {
     current_namespace: String,
@@ -39,21 +59,318 @@ struct MetaEntry {
     original_value: String,
 }
 
+/// A column of the metadata table the entries can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetaColumn {
+    Namespace,
+    Name,
+    Value,
+}
+
+impl MetaColumn {
+    /// The original text of the entry in this column. Edited entries keep
+    /// their position until the changes are applied.
+    fn text(self, entry: &MetaEntry) -> &str {
+        match self {
+            MetaColumn::Namespace => &entry.original_namespace,
+            MetaColumn::Name => &entry.original_name,
+            MetaColumn::Value => &entry.original_value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MetaSort {
+    column: MetaColumn,
+    descending: bool,
+}
+
+/// Indices of the entries whose namespace, name or value contains the filter
+/// text (ignoring the case), in the sort order if there is one.
+fn visible_entries(entries: &[MetaEntry], filter: &str, sort: Option<MetaSort>) -> Vec<usize> {
+    let filter = filter.trim().to_lowercase();
+    let mut result: Vec<usize> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| {
+            filter.is_empty()
+                || [MetaColumn::Namespace, MetaColumn::Name, MetaColumn::Value]
+                    .into_iter()
+                    .any(|column| column.text(entry).to_lowercase().contains(&filter))
+                || format!("{}::{}", entry.original_namespace, entry.original_name)
+                    .to_lowercase()
+                    .contains(&filter)
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+    if let Some(sort) = sort {
+        result.sort_by(|a, b| {
+            let order = sort
+                .column
+                .text(&entries[*a])
+                .cmp(sort.column.text(&entries[*b]));
+            if sort.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+    }
+    result
+}
+
+/// Describes why the namespace and name of the entry at the given index can
+/// not be committed, if there is any problem.
+fn entry_problem(entries: &[MetaEntry], idx: usize) -> Option<String> {
+    let entry = &entries[idx];
+    let key_changed = entry.current_namespace != entry.original_namespace
+        || entry.current_name != entry.original_name;
+    if entry.current_name.trim().is_empty() {
+        Some("The name must not be empty.".to_string())
+    } else if key_changed
+        && (entry.current_namespace == ANNIS_NS || entry.original_namespace == ANNIS_NS)
+    {
+        Some(format!(
+            "The \"{ANNIS_NS}\" namespace is reserved for internal annotations."
+        ))
+    } else if entries.iter().enumerate().any(|(other_idx, other)| {
+        other_idx != idx
+            && other.current_namespace == entry.current_namespace
+            && other.current_name == entry.current_name
+    }) {
+        Some(format!(
+            "There is another entry with namespace \"{}\" and name \"{}\".",
+            entry.current_namespace, entry.current_name
+        ))
+    } else {
+        None
+    }
+}
+
+/// Background of the fields of entries that can not be committed.
+fn invalid_color(ui: &Ui) -> Color32 {
+    ui.visuals().error_fg_color.gamma_multiply(0.4)
+}
+
 #[derive(Clone, PartialEq, Default, Debug)]
 struct Data {
     parent_node_name: String,
     node_annos: Vec<MetaEntry>,
     changed_keys: HashSet<AnnoKey>,
+    /// Entries whose value is edited in a multiline text area even if it
+    /// does not contain a line break yet.
+    multiline_keys: HashSet<AnnoKey>,
     new_entry: MetaEntry,
 }
 
+impl Data {
+    /// Number of text lines shown for the value of the entry.
+    fn value_lines(&self, entry_idx: usize) -> usize {
+        let entry = &self.node_annos[entry_idx];
+        let lines = entry.current_value.lines().count().max(1);
+        let key = AnnoKey {
+            ns: entry.original_namespace.clone().into(),
+            name: entry.original_name.clone().into(),
+        };
+        if lines > 1 || self.multiline_keys.contains(&key) {
+            // Leave room for the next line
+            lines + 1
+        } else {
+            1
+        }
+    }
+}
+
+/// Number of direct children and of documents below a node of the corpus
+/// structure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct NodeCounts {
+    children: usize,
+    documents: usize,
+}
+
+impl NodeCounts {
+    fn description(&self) -> String {
+        let documents = if self.documents == 1 {
+            "1 document".to_string()
+        } else {
+            format!("{} documents", self.documents)
+        };
+        if self.children == self.documents {
+            documents
+        } else if self.children == 1 {
+            format!("1 child, {documents}")
+        } else {
+            format!("{} children, {documents}", self.children)
+        }
+    }
+}
+
+/// A node of the corpus structure as it is shown in the tree.
+#[derive(Debug, Clone)]
+struct StructureNode {
+    id: NodeID,
+    name: String,
+    counts: NodeCounts,
+    /// The value of the language metadata, shown as a badge for documents.
+    language: Option<String>,
+}
+
+/// The child nodes of a node in the corpus structure. They are loaded in a
+/// background job when the node is expanded for the first time.
+#[derive(Debug, Clone)]
+enum ChildNodes {
+    Loading,
+    Loaded(Vec<StructureNode>),
+    Failed,
+}
+
+/// The PartOf edges between the corpus nodes, inverted so they point from
+/// each node to its children, and the number of children and documents of
+/// each node.
+struct StructureIndex {
+    gs: AdjacencyListStorage,
+    node_counts: HashMap<NodeID, NodeCounts>,
+}
+
+impl StructureIndex {
+    fn load(graph: &RwLock<AnnotationGraph>) -> anyhow::Result<Self> {
+        let part_of_component = AnnotationComponent::new(PartOf, ANNIS_NS.into(), "".into());
+        {
+            // Only the component of the corpus structure is needed
+            let mut graph = graph.write();
+            graph.ensure_loaded_parallel(&[part_of_component.clone()])?;
+        }
+        let graph = graph.read();
+
+        let partof = graph
+            .get_graphstorage(&part_of_component)
+            .context("Missing PartOf component")?;
+        let corpus_nodes = graph.get_node_annos().exact_anno_search(
+            Some(ANNIS_NS),
+            NODE_TYPE,
+            ValueSearch::Some("corpus"),
+        );
+        let mut gs = AdjacencyListStorage::new();
+        for source in corpus_nodes {
+            let source = source?.node;
+            for target in partof.get_outgoing_edges(source) {
+                let target = target?;
+                let edge = Edge { source, target };
+                gs.add_edge(edge.inverse())?;
+            }
+        }
+        gs.calculate_statistics()?;
+        let node_counts = count_corpus_nodes(&gs)?;
+        Ok(Self { gs, node_counts })
+    }
+
+    /// The children of the parent node or the root nodes if there is no
+    /// parent.
+    fn child_nodes(
+        &self,
+        graph: &AnnotationGraph,
+        parent: Option<NodeID>,
+    ) -> anyhow::Result<Vec<StructureNode>> {
+        let ids: graphannis_core::errors::Result<Vec<NodeID>> = match parent {
+            Some(parent) => self.gs.get_outgoing_edges(parent).collect(),
+            None => self.gs.root_nodes().collect(),
+        };
+        let mut result = Vec::new();
+        for id in ids? {
+            let node_annos = graph.get_node_annos();
+            let name = node_annos
+                .get_value_for_item(&id, &NODE_NAME_KEY)?
+                .context("Node name not found")?;
+            let language = node_annos.get_value_for_item(&id, &LANGUAGE_KEY)?;
+            result.push(StructureNode {
+                id,
+                name: name.to_string(),
+                counts: self.node_counts.get(&id).copied().unwrap_or_default(),
+                language: language.map(|l| l.to_string()),
+            });
+        }
+        Ok(result)
+    }
+}
+
+/// Show the language code of a document in a small frame.
+fn language_badge(ui: &mut Ui, code: &str) {
+    let hover_text = match Language::from_code(code) {
+        Some(language) if language.right_to_left => {
+            format!("Language: {} (right-to-left)", language.name)
+        }
+        Some(language) => format!("Language: {}", language.name),
+        None => format!("Language: {code}"),
+    };
+    egui::Frame::new()
+        .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
+        .corner_radius(3.0)
+        .inner_margin(egui::Margin::symmetric(3, 0))
+        .show(ui, |ui| {
+            ui.label(RichText::new(code).small());
+        })
+        .response
+        .on_hover_text(hover_text);
+}
+
+/// Count the children and documents of all nodes of the corpus structure.
+/// Documents are the nodes without any children.
+fn count_corpus_nodes(gs: &dyn GraphStorage) -> anyhow::Result<HashMap<NodeID, NodeCounts>> {
+    let mut counts = HashMap::new();
+    for root in gs.root_nodes() {
+        count_below(gs, root?, &mut counts)?;
+    }
+    Ok(counts)
+}
+
+fn count_below(
+    gs: &dyn GraphStorage,
+    node: NodeID,
+    counts: &mut HashMap<NodeID, NodeCounts>,
+) -> anyhow::Result<NodeCounts> {
+    if let Some(result) = counts.get(&node) {
+        return Ok(*result);
+    }
+    // Insert the node first, so a cycle does not lead to an endless recursion
+    counts.insert(node, NodeCounts::default());
+    let children: graphannis_core::errors::Result<Vec<NodeID>> =
+        gs.get_outgoing_edges(node).collect();
+    let children = children?;
+    let mut result = NodeCounts {
+        children: children.len(),
+        documents: 0,
+    };
+    for child in children {
+        let child_counts = count_below(gs, child, counts)?;
+        result.documents += if child_counts.children == 0 {
+            1
+        } else {
+            child_counts.documents
+        };
+    }
+    counts.insert(node, result);
+    Ok(result)
+}
+
 pub(crate) struct CorpusTree {
     selected_corpus_node: Option<NodeID>,
     data: Data,
-    gs: Box<dyn WriteableGraphStorage>,
+    /// Created by the job that loads the root nodes.
+    index: Arc<OnceLock<StructureIndex>>,
+    /// The loaded child nodes by their parent, with `None` as the parent of
+    /// the root nodes.
+    structure: Arc<RwLock<HashMap<Option<NodeID>, ChildNodes>>>,
     graph: Arc<RwLock<AnnotationGraph>>,
     jobs: JobExecutor,
     notifier: Notifier,
+    /// The new name while the selected node is renamed.
+    rename_node: Option<String>,
+    /// Only metadata entries containing this text are shown.
+    metadata_filter: String,
+    metadata_sort: Option<MetaSort>,
+    /// Pre-filled namespace of new metadata entries.
+    default_namespace: String,
 }
 
 impl Debug for CorpusTree {
@@ -72,130 +389,258 @@ impl CorpusTree {
         jobs: JobExecutor,
         notifier: Notifier,
     ) -> anyhow::Result<Self> {
-        // Create our own graph storage with inverted edges
-        let mut inverted_corpus_graph = AdjacencyListStorage::new();
-        {
-            let part_of_component = AnnotationComponent::new(PartOf, ANNIS_NS.into(), "".into());
-            {
-                let mut graph = graph.write();
-                let all_partof_components = graph.get_all_components(Some(PartOf), None);
-                graph.ensure_loaded_parallel(&all_partof_components)?;
-            }
-            let graph = graph.read();
-
-            let partof = graph
-                .get_graphstorage(&part_of_component)
-                .context("Missing PartOf component")?;
-            let corpus_nodes = graph.get_node_annos().exact_anno_search(
-                Some(ANNIS_NS),
-                NODE_TYPE,
-                ValueSearch::Some("corpus"),
-            );
-            for source in corpus_nodes {
-                let source = source?.node;
-                for target in partof.get_outgoing_edges(source) {
-                    let target = target?;
-                    let edge = Edge { source, target };
-                    inverted_corpus_graph.add_edge(edge.inverse())?;
-                }
-            }
-            inverted_corpus_graph.calculate_statistics()?;
-        }
-
         let mut result = Self {
             selected_corpus_node,
             data: Data::default(),
-            gs: Box::new(inverted_corpus_graph),
+            index: Arc::new(OnceLock::new()),
+            structure: Arc::new(RwLock::new(HashMap::new())),
             jobs,
             notifier,
             graph,
+            rename_node: None,
+            metadata_filter: String::new(),
+            metadata_sort: None,
+            default_namespace: String::new(),
         };
 
+        // The structure is loaded in the background, so the editor is shown
+        // before large corpora are completely loaded.
+        result.load_child_nodes(None);
         result.update_data_after_selection();
 
         Ok(result)
     }
 
+    /// Start a background job that loads the children of the parent node,
+    /// or the root nodes if there is no parent.
+    fn load_child_nodes(&self, parent: Option<NodeID>) {
+        self.structure.write().insert(parent, ChildNodes::Loading);
+        let title = if let Some(parent) = parent {
+            format!("Loading children of corpus node {parent}")
+        } else {
+            "Loading corpus structure".to_string()
+        };
+        let graph = self.graph.clone();
+        let index = self.index.clone();
+        let structure = self.structure.clone();
+        self.jobs.add_background(
+            &title,
+            move |_| {
+                let result = (|| -> anyhow::Result<Vec<StructureNode>> {
+                    let index = match index.get() {
+                        Some(index) => index,
+                        None => {
+                            let loaded = StructureIndex::load(&graph)?;
+                            index.get_or_init(|| loaded)
+                        }
+                    };
+                    let graph = graph.read();
+                    index.child_nodes(&graph, parent)
+                })();
+                let mut structure = structure.write();
+                match result {
+                    Ok(child_nodes) => {
+                        structure.insert(parent, ChildNodes::Loaded(child_nodes));
+                        Ok(())
+                    }
+                    Err(e) => {
+                        structure.insert(parent, ChildNodes::Failed);
+                        Err(e)
+                    }
+                }
+            },
+            |_, _| {},
+        );
+    }
+
+    /// The child nodes of the parent node if they have been loaded. Otherwise
+    /// a placeholder is shown instead and loading them is started if
+    /// necessary.
+    fn child_nodes_or_placeholder(
+        &self,
+        ui: &mut Ui,
+        parent: Option<NodeID>,
+    ) -> Option<Vec<StructureNode>> {
+        let state = self.structure.read().get(&parent).cloned();
+        match state {
+            Some(ChildNodes::Loaded(child_nodes)) => Some(child_nodes),
+            Some(ChildNodes::Failed) => {
+                ui.weak("Could not load the corpus structure");
+                None
+            }
+            Some(ChildNodes::Loading) | None => {
+                if state.is_none() {
+                    self.load_child_nodes(parent);
+                }
+                ui.horizontal(|ui| {
+                    // The spinner also repaints until the nodes are loaded
+                    ui.spinner();
+                    ui.weak("Loading…");
+                });
+                None
+            }
+        }
+    }
+
     fn show_structure(&mut self, ui: &mut Ui) {
-        let root_nodes: graphannis_core::errors::Result<Vec<_>> = self.gs.root_nodes().collect();
-        let root_nodes = self
-            .notifier
-            .unwrap_or_default(root_nodes.context("Could not get root nodes"));
         ScrollArea::vertical().show(ui, |ui| {
+            let Some(root_nodes) = self.child_nodes_or_placeholder(ui, None) else {
+                return;
+            };
             if root_nodes.len() > 1 {
                 CollapsingHeader::new("<root>")
                     .default_open(true)
                     .show(ui, |ui| {
                         for root_node in root_nodes.iter() {
-                            self.recursive_corpus_structure(ui, *root_node, 0)
+                            self.recursive_corpus_structure(ui, root_node, 0)
                         }
                     });
             } else if let Some(root_node) = root_nodes.first() {
-                self.recursive_corpus_structure(ui, *root_node, 0)
+                self.recursive_corpus_structure(ui, root_node, 0)
             }
         });
     }
 
     fn show_meta_editor(&mut self, ui: &mut Ui) {
-        let marker_color = if ui.ctx().theme() == Theme::Light {
-            CHANGE_PENDING_COLOR_LIGHT
-        } else {
-            CHANGE_PENDING_COLOR_DARK
-        };
+        let marker_color = change_pending_color(ui);
 
         if self.selected_corpus_node.is_some() {
             let text_style_body = egui::TextStyle::Body.resolve(ui.style());
+            let line_height = ui.fonts(|f| f.row_height(&text_style_body));
+
+            self.show_rename_node(ui);
+            if self.first_problem().is_some() {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    "Changes are not applied until the marked entries are fixed.",
+                );
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(egui_phosphor::regular::MAGNIFYING_GLASS);
+                TextEdit::singleline(&mut self.metadata_filter)
+                    .hint_text("Filter by key or value")
+                    .id(Id::from("metadata-filter"))
+                    .ui(ui);
+                if !self.metadata_filter.is_empty()
+                    && ui
+                        .button(egui_phosphor::regular::X)
+                        .on_hover_text("Show all entries")
+                        .clicked()
+                {
+                    self.metadata_filter.clear();
+                }
+            });
+            let visible = visible_entries(
+                &self.data.node_annos,
+                &self.metadata_filter,
+                self.metadata_sort,
+            );
+            if visible.len() < self.data.node_annos.len() {
+                ui.weak(format!(
+                    "Showing {} of {} entries",
+                    visible.len(),
+                    self.data.node_annos.len()
+                ));
+            }
 
-            // Use one third of the width for the namesspace/name information
-            // and the other 2/3 for the value. Subtract the space needed for
-            // the actions before.
+            // Initially use one third of the width for the namesspace/name
+            // information and the other 2/3 for the value. Subtract the space
+            // needed for the actions before. The widths changed by the user
+            // are persisted by the table itself.
             let available_width = ui.available_width() - 40.0;
             let namespace_name_width = available_width / 3.0;
             let value_width = (available_width / 3.0) * 2.0;
 
             // Calculate the heights needed for each line.
             egui_extras::TableBuilder::new(ui)
-                .columns(Column::exact(namespace_name_width / 2.0), 2)
-                .column(Column::exact(value_width))
+                .id_salt("metadata-table")
+                .columns(
+                    Column::initial(namespace_name_width / 2.0)
+                        .at_least(40.0)
+                        .resizable(true),
+                    2,
+                )
+                .column(Column::initial(value_width).at_least(40.0).resizable(true))
                 .column(Column::auto())
                 .header(text_style_body.size + 2.0, |mut header| {
-                    header.col(|ui| {
-                        ui.label(RichText::new("Namespace").underline());
-                    });
-                    header.col(|ui| {
-                        ui.label(RichText::new("Name").underline());
-                    });
-                    header.col(|ui| {
-                        ui.label(RichText::new("Value").underline());
-                    });
+                    for (column, label) in [
+                        (MetaColumn::Namespace, "Namespace"),
+                        (MetaColumn::Name, "Name"),
+                        (MetaColumn::Value, "Value"),
+                    ] {
+                        header.col(|ui| self.sortable_header(ui, column, label));
+                    }
                     header.col(|_ui| {});
                 })
                 .body(|body| {
-                    body.rows(
-                        text_style_body.size + 10.0,
-                        self.data.node_annos.len() + 1,
-                        |mut row| {
-                            if row.index() < self.data.node_annos.len() {
-                                self.show_existing_metadata_entries(&mut row, marker_color);
-                            } else {
-                                self.show_new_metadata_row(&mut row);
+                    // Rows with multiline values grow with the number of lines
+                    let heights: Vec<f32> = visible
+                        .iter()
+                        .map(|idx| self.data.value_lines(*idx))
+                        .chain(std::iter::once(1))
+                        .map(|lines| text_style_body.size + 10.0 + (lines - 1) as f32 * line_height)
+                        .collect();
+                    body.heterogeneous_rows(heights.into_iter(), |mut row| {
+                        if let Some(entry_idx) = visible.get(row.index()).copied() {
+                            // A deleted entry shifts the remaining ones until
+                            // the next frame
+                            if entry_idx < self.data.node_annos.len() {
+                                self.show_existing_metadata_entries(
+                                    &mut row,
+                                    entry_idx,
+                                    marker_color,
+                                );
                             }
-                        },
-                    );
+                        } else {
+                            self.show_new_metadata_row(&mut row);
+                        }
+                    });
                 });
         } else {
             ui.label("Select a corpus/document node to edit it.");
         }
     }
 
+    /// Header of a metadata column that changes the sort order when
+    /// clicked: ascending, descending and unsorted.
+    fn sortable_header(&mut self, ui: &mut Ui, column: MetaColumn, label: &str) {
+        let sort = self.metadata_sort.filter(|sort| sort.column == column);
+        let text = match sort {
+            Some(MetaSort {
+                descending: false, ..
+            }) => format!("{label} {}", egui_phosphor::regular::CARET_UP),
+            Some(MetaSort {
+                descending: true, ..
+            }) => format!("{label} {}", egui_phosphor::regular::CARET_DOWN),
+            None => label.to_string(),
+        };
+        let response = ui
+            .add(Button::new(RichText::new(text).underline()).frame(false))
+            .on_hover_text("Sort by this column");
+        if response.clicked() {
+            self.metadata_sort = match sort {
+                None => Some(MetaSort {
+                    column,
+                    descending: false,
+                }),
+                Some(MetaSort {
+                    descending: false, ..
+                }) => Some(MetaSort {
+                    column,
+                    descending: true,
+                }),
+                Some(_) => None,
+            };
+        }
+    }
+
     fn show_existing_metadata_entries(
         &mut self,
         row: &mut TableRow<'_, '_>,
+        mut entry_idx: usize,
         marker_color: Color32,
     ) {
-        // Next rows are the actual ones
-        let mut entry_idx = row.index();
-
         let anno_key_for_row = AnnoKey {
             ns: self.data.node_annos[entry_idx]
                 .original_namespace
@@ -205,16 +650,29 @@ impl CorpusTree {
         };
 
         let has_pending_changes = self.data.changed_keys.contains(&anno_key_for_row);
+        let problem = entry_problem(&self.data.node_annos, entry_idx);
+        // Reserved annotations can not be renamed or deleted, and the ones
+        // needed to find the nodes can only be changed by renaming the node
+        let is_reserved = anno_key_for_row.ns == ANNIS_NS;
+        let is_read_only =
+            is_reserved && READ_ONLY_ANNIS_NAMES.contains(&anno_key_for_row.name.as_str());
+        let value_lines = self.data.value_lines(entry_idx);
         let mut any_column_changed = false;
         let mut any_lost_focus = false;
 
         row.col(|ui| {
             let entry = &mut self.data.node_annos[entry_idx];
-            let mut text_edit = TextEdit::singleline(&mut entry.current_namespace);
-            if has_pending_changes {
+            let mut text_edit =
+                TextEdit::singleline(&mut entry.current_namespace).interactive(!is_reserved);
+            if problem.is_some() {
+                text_edit = text_edit.background_color(invalid_color(ui));
+            } else if has_pending_changes {
                 text_edit = text_edit.background_color(marker_color);
             }
-            let text_edit = text_edit.ui(ui);
+            let mut text_edit = text_edit.ui(ui);
+            if let Some(problem) = &problem {
+                text_edit = text_edit.on_hover_text(problem);
+            }
 
             if text_edit.changed() {
                 any_column_changed = true;
@@ -225,11 +683,17 @@ impl CorpusTree {
         });
         row.col(|ui| {
             let entry = &mut self.data.node_annos[entry_idx];
-            let mut text_edit = TextEdit::singleline(&mut entry.current_name);
-            if has_pending_changes {
+            let mut text_edit =
+                TextEdit::singleline(&mut entry.current_name).interactive(!is_reserved);
+            if problem.is_some() {
+                text_edit = text_edit.background_color(invalid_color(ui));
+            } else if has_pending_changes {
                 text_edit = text_edit.background_color(marker_color);
             }
-            let text_edit = text_edit.ui(ui);
+            let mut text_edit = text_edit.ui(ui);
+            if let Some(problem) = &problem {
+                text_edit = text_edit.on_hover_text(problem);
+            }
 
             if text_edit.changed() {
                 any_column_changed = true;
@@ -240,11 +704,19 @@ impl CorpusTree {
         });
         row.col(|ui| {
             let entry = &mut self.data.node_annos[entry_idx];
-            let mut text_edit = TextEdit::singleline(&mut entry.current_value);
+            let mut text_edit = if value_lines > 1 {
+                TextEdit::multiline(&mut entry.current_value).desired_rows(value_lines)
+            } else {
+                TextEdit::singleline(&mut entry.current_value)
+            }
+            .interactive(!is_read_only);
             if has_pending_changes {
                 text_edit = text_edit.background_color(marker_color);
             }
-            let text_edit = text_edit.ui(ui);
+            let mut text_edit = text_edit.ui(ui);
+            if is_read_only {
+                text_edit = text_edit.on_hover_text("Rename the node to change its name.");
+            }
 
             if text_edit.changed() {
                 any_column_changed = true;
@@ -255,10 +727,42 @@ impl CorpusTree {
         });
 
         row.col(|ui| {
-            let delete_button = Button::new(RichText::new(egui_phosphor::regular::TRASH)).ui(ui);
+            let delete_button = ui
+                .add_enabled(
+                    !is_reserved,
+                    Button::new(RichText::new(egui_phosphor::regular::TRASH)),
+                )
+                .on_disabled_hover_text("Reserved annotations can not be deleted");
             if delete_button.hovered() {
                 delete_button.show_tooltip_text("Delete metadata entry");
             }
+            if value_lines == 1 && !is_read_only {
+                let multiline_button =
+                    Button::new(RichText::new(egui_phosphor::regular::TEXT_ALIGN_LEFT))
+                        .ui(ui)
+                        .on_hover_text("Edit the value as multiline text");
+                if multiline_button.clicked() {
+                    self.data.multiline_keys.insert(anno_key_for_row.clone());
+                }
+            }
+            if is_reserved && anno_key_for_row.name == "node_name" {
+                let rename_button = ui
+                    .add_enabled(
+                        !self.has_pending_updates(),
+                        Button::new(RichText::new(egui_phosphor::regular::PENCIL_SIMPLE)),
+                    )
+                    .on_hover_text("Rename the node and all nodes below it")
+                    .on_disabled_hover_text("Apply the pending changes before renaming");
+                if rename_button.clicked() {
+                    let short_name = self
+                        .data
+                        .parent_node_name
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default();
+                    self.rename_node = Some(short_name.to_string());
+                }
+            }
             if delete_button.clicked() {
                 self.data.changed_keys.insert(anno_key_for_row.clone());
                 self.data.node_annos.remove(entry_idx);
@@ -314,10 +818,61 @@ impl CorpusTree {
         });
     }
 
+    fn show_rename_node(&mut self, ui: &mut Ui) {
+        let Some(new_name) = &mut self.rename_node else {
+            return;
+        };
+        let mut rename = false;
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            ui.label("New name");
+            let response = TextEdit::singleline(new_name).desired_width(200.0).ui(ui);
+            if ui.button("Rename").clicked()
+                || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+            {
+                rename = true;
+            }
+            if ui.button("Cancel").clicked() {
+                cancel = true;
+            }
+        });
+        if rename {
+            self.rename_selected_node();
+        } else if cancel {
+            self.rename_node = None;
+        }
+    }
+
+    /// Rename the selected node and all nodes below it, instead of changing
+    /// its reserved `annis::node_name` annotation directly.
+    fn rename_selected_node(&mut self) {
+        if let Some(new_name) = self.rename_node.take() {
+            let node_name = self.data.parent_node_name.clone();
+            // Renaming persists the corpus, which is done by the project
+            self.jobs.add(
+                "Requesting to rename the node",
+                |_| Ok(()),
+                move |_, app| {
+                    let result = app.project.rename_document(&node_name, &new_name);
+                    app.notifier.report_result(result);
+                },
+            );
+        }
+    }
+
+    /// The problem of the first entry that prevents committing the changes.
+    fn first_problem(&self) -> Option<String> {
+        (0..self.data.node_annos.len()).find_map(|idx| entry_problem(&self.data.node_annos, idx))
+    }
+
     fn add_new_entry(&mut self) {
         if self.data.new_entry.current_name.is_empty() {
             self.notifier
                 .add_toast(Toast::error("Cannot add entry with empty name"));
+        } else if self.data.new_entry.current_namespace == ANNIS_NS {
+            self.notifier.add_toast(Toast::error(format!(
+                "Cannot add entry in the reserved \"{ANNIS_NS}\" namespace."
+            )));
         } else if self.data.node_annos.iter().any(|e| {
             e.current_namespace == self.data.new_entry.current_namespace
                 && e.current_name == self.data.new_entry.current_name
@@ -341,7 +896,10 @@ impl CorpusTree {
                 name: self.data.clone().new_entry.current_name.into(),
             });
             self.data.node_annos.sort();
-            self.data.new_entry = MetaEntry::default();
+            self.data.new_entry = MetaEntry {
+                current_namespace: self.default_namespace.clone(),
+                ..Default::default()
+            };
 
             self.apply_pending_updates_for_editor();
         }
@@ -351,6 +909,7 @@ impl CorpusTree {
         if let Some(parent) = self.selected_corpus_node {
             self.data.node_annos.clear();
             self.data.changed_keys.clear();
+            self.data.multiline_keys.clear();
 
             let graph = self.graph.read();
             let anno_keys = graph
@@ -390,58 +949,124 @@ impl CorpusTree {
 
     fn select_corpus_node(&mut self, selection: Option<NodeID>) {
         self.selected_corpus_node = selection;
+        self.rename_node = None;
         self.update_data_after_selection();
     }
 
-    fn recursive_corpus_structure(&mut self, ui: &mut Ui, parent: NodeID, level: usize) {
-        let child_nodes: graphannis_core::errors::Result<Vec<NodeID>> =
-            self.gs.get_outgoing_edges(parent).collect();
-        let child_nodes = self
-            .notifier
-            .unwrap_or_default(child_nodes.context("Could not get child nodes"));
-        let parent_node_name = {
-            let graph = self.graph.read();
-            match graph
-                .get_node_annos()
-                .get_value_for_item(&parent, &NODE_NAME_KEY)
-            {
-                Ok(o) => o.map(|o| o.to_string()),
-                Err(e) => {
-                    self.notifier.report_error(e.into());
-                    None
-                }
-            }
-        };
-
-        if let Some(parent_node_name) = parent_node_name {
-            if child_nodes.is_empty() {
-                let is_selected = self.selected_corpus_node.is_some_and(|n| n == parent);
+    fn recursive_corpus_structure(&mut self, ui: &mut Ui, node: &StructureNode, level: usize) {
+        let parent = node.id;
+        let parent_node_name = &node.name;
+        if node.counts.children == 0 {
+            let is_selected = self.selected_corpus_node.is_some_and(|n| n == parent);
 
-                let label = ui.selectable_label(is_selected, parent_node_name.clone());
-                if !is_selected && label.gained_focus() {
-                    self.select_corpus_node(Some(parent));
-                } else if label.clicked() {
-                    self.apply_pending_updates_for_editor();
-                    label.request_focus();
-                    if is_selected {
-                        self.select_corpus_node(None);
-                    } else {
-                        self.select_corpus_node(Some(parent));
+            let label = ui
+                .horizontal(|ui| {
+                    let label =
+                        ui.selectable_label(is_selected, format!("{FILE_TEXT} {parent_node_name}"));
+                    if let Some(language) = &node.language {
+                        language_badge(ui, language);
                     }
+                    label
+                })
+                .inner;
+            // The icon is not part of the name used by screen readers
+            label.widget_info(|| {
+                WidgetInfo::selected(
+                    egui::WidgetType::SelectableLabel,
+                    true,
+                    is_selected,
+                    parent_node_name.clone(),
+                )
+            });
+            if !is_selected && label.gained_focus() {
+                self.select_corpus_node(Some(parent));
+            } else if label.clicked() {
+                self.apply_pending_updates_for_editor();
+                label.request_focus();
+                if is_selected {
+                    self.select_corpus_node(None);
+                } else {
+                    self.select_corpus_node(Some(parent));
                 }
-            } else {
-                CollapsingHeader::new(parent_node_name)
-                    .default_open(level == 0)
-                    .show(ui, |ui| {
-                        for child_corpus in &child_nodes {
-                            self.recursive_corpus_structure(ui, *child_corpus, level + 1);
-                        }
-                    });
             }
         } else {
-            self.notifier.add_toast(Toast::error("Node name not found"));
+            let icon = if level == 0 { BOOKS } else { FOLDER };
+            let header = CollapsingHeader::new(format!(
+                "{icon} {parent_node_name}  ({})",
+                node.counts.description()
+            ))
+            .id_salt(parent_node_name)
+            .default_open(level == 0)
+            .show(ui, |ui| {
+                // Only expanded nodes load their children
+                if let Some(child_nodes) = self.child_nodes_or_placeholder(ui, Some(parent)) {
+                    for child_node in &child_nodes {
+                        self.recursive_corpus_structure(ui, child_node, level + 1);
+                    }
+                }
+            });
+            header.header_response.widget_info(|| {
+                WidgetInfo::labeled(
+                    egui::WidgetType::CollapsingHeader,
+                    true,
+                    parent_node_name.clone(),
+                )
+            });
         }
     }
+
+    /// Show the corpus structure and the metadata editor side by side,
+    /// separated by a handle that can be dragged to change their widths.
+    fn show_split(&mut self, ui: &mut Ui) {
+        let ratio_id = Id::new(SPLIT_RATIO_ID);
+        let ratio: f32 = ui.data_mut(|d| d.get_persisted(ratio_id)).unwrap_or(0.5);
+        let rect = ui.available_rect_before_wrap();
+        let left_width = (rect.width() - SEPARATOR_WIDTH) * ratio;
+        let left_rect = Rect::from_min_size(rect.min, vec2(left_width, rect.height()));
+        let right_rect = Rect::from_min_max(
+            pos2(left_rect.right() + SEPARATOR_WIDTH, rect.top()),
+            rect.max,
+        );
+
+        let mut left_ui = ui.new_child(UiBuilder::new().max_rect(left_rect));
+        left_ui.push_id("corpus_structure", |ui| {
+            self.show_structure(ui);
+        });
+        let mut right_ui = ui.new_child(UiBuilder::new().max_rect(right_rect));
+        right_ui.push_id("meta_editor", |ui| self.show_meta_editor(ui));
+        let height = left_ui
+            .min_rect()
+            .height()
+            .max(right_ui.min_rect().height());
+
+        let separator_rect = Rect::from_min_size(
+            pos2(left_rect.right(), rect.top()),
+            vec2(SEPARATOR_WIDTH, height),
+        );
+        let response = ui.interact(separator_rect, ui.id().with(SPLIT_RATIO_ID), Sense::drag());
+        response.widget_info(|| {
+            WidgetInfo::labeled(egui::WidgetType::Other, true, "Resize corpus structure")
+        });
+        if response.hovered() || response.dragged() {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+        }
+        if let Some(pointer) = response
+            .interact_pointer_pos()
+            .filter(|_| response.dragged())
+        {
+            let new_ratio = ((pointer.x - rect.left()) / rect.width()).clamp(0.1, 0.9);
+            ui.data_mut(|d| d.insert_persisted(ratio_id, new_ratio));
+        }
+        let stroke = if response.hovered() || response.dragged() {
+            ui.visuals().widgets.hovered.fg_stroke
+        } else {
+            ui.visuals().widgets.noninteractive.bg_stroke
+        };
+        ui.painter()
+            .vline(separator_rect.center().x, separator_rect.y_range(), stroke);
+
+        ui.advance_cursor_after_rect(Rect::from_min_size(rect.min, vec2(rect.width(), height)));
+    }
 }
 
 impl Editor for CorpusTree {
@@ -449,12 +1074,7 @@ impl Editor for CorpusTree {
         ui.group(|ui| {
             ui.heading("Corpus editor");
 
-            ui.columns_const(|[c1, c2]| {
-                c1.push_id("corpus_structure", |ui| {
-                    self.show_structure(ui);
-                });
-                c2.push_id("meta_editor", |ui| self.show_meta_editor(ui));
-            });
+            self.show_split(ui);
         });
     }
 
@@ -462,17 +1082,31 @@ impl Editor for CorpusTree {
         !self.data.changed_keys.is_empty()
     }
 
+    fn discard_pending_updates(&mut self) {
+        // Read the entries of the selected node from the graph again
+        self.update_data_after_selection();
+    }
+
+    fn set_default_namespace(&mut self, namespace: String) {
+        // Keep a namespace the user already entered for the new entry
+        if self.data.new_entry.current_namespace == self.default_namespace {
+            self.data.new_entry.current_namespace = namespace.clone();
+        }
+        self.default_namespace = namespace;
+    }
+
     fn any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
 
-    fn apply_pending_updates_for_editor(&mut self) {
-        if self.has_pending_updates() {
+    fn apply_pending_updates_in_group(&mut self, group: Option<UndoGroup>) {
+        // Invalid entries stay pending until they are fixed
+        if self.has_pending_updates() && self.first_problem().is_none() {
             // apply all changes as updates to our internal corpus graph
             let parent_node_name = self.data.parent_node_name.clone();
             let node_annos = self.data.node_annos.clone();
             let mut changed_keys = self.data.changed_keys.clone();
-            self.jobs.add(
+            self.jobs.add_interactive(
                 "Applying pending metadata updates",
                 move |_| {
                     let mut update = GraphUpdate::new();
@@ -509,8 +1143,9 @@ impl Editor for CorpusTree {
 
                     Ok(update)
                 },
-                |update, app| {
-                    app.project.add_changeset(update);
+                move |update, app| {
+                    app.project
+                        .add_grouped_changeset_then(update, group, |_| {});
                 },
             );
             self.data.node_annos.sort();
@@ -521,4 +1156,9 @@ impl Editor for CorpusTree {
     fn get_selected_corpus_node(&self) -> Option<NodeID> {
         self.selected_corpus_node
     }
+
+    fn get_selected_node_name(&self) -> Option<String> {
+        self.selected_corpus_node
+            .map(|_| self.data.parent_node_name.clone())
+    }
 }
@@ -13,7 +13,15 @@ use egui_kittest::{
     Harness,
 };
 use egui_phosphor::regular::{PLUS_CIRCLE, TRASH};
-use graphannis::aql;
+use graphannis::{
+    aql,
+    graph::{Edge, WriteableGraphStorage},
+};
+use graphannis_core::graph::storage::adjacencylist::AdjacencyListStorage;
+
+use super::{
+    count_corpus_nodes, visible_entries, ChildNodes, CorpusTree, MetaColumn, MetaSort, NodeCounts,
+};
 
 #[test]
 fn show_metadata() {
@@ -178,3 +186,198 @@ fn add_and_delete_entry() {
 
     assert_screenshots![r1, r2];
 }
+
+#[test]
+fn persist_split_ratio() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    harness.run();
+    harness.get_by_label("single_sentence").click();
+    wait_for_editor(&mut harness, app_state.clone());
+
+    let ratio_id = Id::new(super::SPLIT_RATIO_ID);
+    harness
+        .ctx
+        .data_mut(|d| d.insert_persisted(ratio_id, 0.25_f32));
+    harness.run();
+    harness.get_by_label("Resize corpus structure");
+    let ratio: Option<f32> = harness.ctx.data_mut(|d| d.get_persisted(ratio_id));
+    assert_eq!(Some(0.25), ratio);
+}
+
+#[test]
+fn validate_metadata_entries() {
+    let entry = |ns: &str, name: &str| super::MetaEntry {
+        current_namespace: ns.to_string(),
+        current_name: name.to_string(),
+        current_value: "value".to_string(),
+        original_namespace: ns.to_string(),
+        original_name: name.to_string(),
+        original_value: "value".to_string(),
+    };
+    let mut entries = vec![
+        entry("annis", "doc"),
+        entry("", "author"),
+        entry("", "date"),
+    ];
+    assert_eq!(None, super::entry_problem(&entries, 0));
+    assert_eq!(None, super::entry_problem(&entries, 1));
+
+    entries[1].current_name = " ".to_string();
+    assert!(super::entry_problem(&entries, 1)
+        .unwrap()
+        .contains("must not be empty"));
+
+    // Renaming into the same key as another entry
+    entries[1].current_name = "date".to_string();
+    assert!(super::entry_problem(&entries, 1)
+        .unwrap()
+        .contains("another entry"));
+    assert!(super::entry_problem(&entries, 2).is_some());
+
+    // Only the value of reserved annotations can be changed
+    entries[1].current_name = "author".to_string();
+    entries[1].current_namespace = "annis".to_string();
+    assert!(super::entry_problem(&entries, 1)
+        .unwrap()
+        .contains("reserved"));
+    entries[0].current_value = "other".to_string();
+    assert_eq!(None, super::entry_problem(&entries, 0));
+    entries[0].current_name = "document".to_string();
+    assert!(super::entry_problem(&entries, 0).is_some());
+}
+
+#[test]
+fn filter_and_sort_metadata_entries() {
+    let entry = |ns: &str, name: &str, value: &str| super::MetaEntry {
+        current_namespace: ns.to_string(),
+        current_name: name.to_string(),
+        current_value: value.to_string(),
+        original_namespace: ns.to_string(),
+        original_name: name.to_string(),
+        original_value: value.to_string(),
+    };
+    let mut entries = vec![
+        entry("annis", "doc", "doc1"),
+        entry("", "author", "Jane Doe"),
+        entry("meta", "date", "2023"),
+        entry("meta", "Genre", "letter"),
+    ];
+    assert_eq!(vec![0, 1, 2, 3], visible_entries(&entries, "", None));
+    assert_eq!(vec![0, 1], visible_entries(&entries, "DO", None));
+    assert_eq!(vec![2, 3], visible_entries(&entries, " meta ", None));
+    assert_eq!(vec![3], visible_entries(&entries, "meta::genre", None));
+    assert!(visible_entries(&entries, "unknown", None).is_empty());
+
+    let by_name = Some(MetaSort {
+        column: MetaColumn::Name,
+        descending: false,
+    });
+    assert_eq!(vec![3, 1, 2, 0], visible_entries(&entries, "", by_name));
+    let by_value_descending = Some(MetaSort {
+        column: MetaColumn::Value,
+        descending: true,
+    });
+    assert_eq!(
+        vec![3, 0, 1, 2],
+        visible_entries(&entries, "", by_value_descending)
+    );
+    assert_eq!(
+        vec![0, 1],
+        visible_entries(&entries, "do", by_value_descending)
+    );
+
+    // Entries keep their position and stay visible while they are edited
+    entries[1].current_value = "Anonymous".to_string();
+    assert_eq!(vec![1], visible_entries(&entries, "doe", None));
+    assert_eq!(
+        vec![3, 0, 1, 2],
+        visible_entries(&entries, "", by_value_descending)
+    );
+}
+
+#[test]
+fn grow_rows_with_multiline_values() {
+    let mut data = super::Data::default();
+    data.node_annos.push(super::MetaEntry {
+        current_name: "description".to_string(),
+        current_value: "A short description".to_string(),
+        original_name: "description".to_string(),
+        original_value: "A short description".to_string(),
+        ..Default::default()
+    });
+    assert_eq!(1, data.value_lines(0));
+
+    data.node_annos[0].current_value = "First line\nSecond line".to_string();
+    assert_eq!(3, data.value_lines(0));
+
+    // Entries can be edited as multiline text before they have a line break
+    data.node_annos[0].current_value = "A short description".to_string();
+    data.multiline_keys.insert(graphannis::graph::AnnoKey {
+        ns: "".into(),
+        name: "description".into(),
+    });
+    assert_eq!(2, data.value_lines(0));
+}
+
+#[test]
+fn count_children_and_documents() {
+    // A corpus with a document and a subcorpus with two documents
+    let mut gs = AdjacencyListStorage::new();
+    for (source, target) in [(1, 2), (1, 3), (3, 4), (3, 5)] {
+        gs.add_edge(Edge { source, target }).unwrap();
+    }
+    let counts = count_corpus_nodes(&gs).unwrap();
+
+    let corpus = counts[&1];
+    assert_eq!(
+        NodeCounts {
+            children: 2,
+            documents: 3
+        },
+        corpus
+    );
+    assert_eq!("2 children, 3 documents", corpus.description());
+    assert_eq!("2 documents", counts[&3].description());
+    assert_eq!(0, counts[&2].children);
+}
+
+#[test]
+fn load_children_of_expanded_nodes() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    harness.run();
+    harness.get_by_label("single_sentence").click();
+    wait_for_editor(&mut harness, app_state.clone());
+
+    // The expanded root node and its document are loaded
+    harness.get_by_label("single_sentence/zossen");
+    {
+        let mut app_state = app_state.write();
+        let editor = app_state
+            .current_editor
+            .get_mut()
+            .unwrap()
+            .any_mut()
+            .downcast_mut::<CorpusTree>()
+            .unwrap();
+        let structure = editor.structure.read();
+        assert_eq!(2, structure.len());
+        let Some(ChildNodes::Loaded(root_nodes)) = structure.get(&None) else {
+            panic!("Root nodes not loaded");
+        };
+        assert_eq!(1, root_nodes.len());
+        assert_eq!("single_sentence", root_nodes[0].name);
+        let Some(ChildNodes::Loaded(documents)) = structure.get(&Some(root_nodes[0].id)) else {
+            panic!("Documents not loaded");
+        };
+        assert_eq!(1, documents.len());
+        assert_eq!(0, documents[0].counts.children);
+    }
+}
@@ -1,32 +1,120 @@
 use std::{
     cmp::Ordering,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs::File,
+    io::{BufReader, BufWriter},
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
+use crate::api::token_helper::{TokenHelper, TOKEN_KEY};
 use crate::app::{
-    util::token_helper::{TokenHelper, TOKEN_KEY},
-    views::Editor,
-    widgets::{Token, TokenEditor},
+    appearance::layer_color,
+    project::history::UndoGroup,
+    util::{
+        annis_config::LayerSettings,
+        datasource::{
+            add_datasource, datasources_of_document, new_datasource_name, Datasource,
+            DATASOURCE_NODE_TYPE,
+        },
+        html_export::write_document_html,
+        language::{Language, LANGUAGE_KEY},
+        media::{
+            is_audio, is_video, linked_files, open_externally, resolve_linked_file, FfmpegBackend,
+            MediaBackend,
+        },
+        nested_spans::load_spans,
+        svg_export::{pointing_arcs, write_document_svg},
+        time_annotation::{TimeRange, TIME_KEY},
+        token_pattern::TokenPattern,
+        tokenizer::{TextToken, TokenizerKind},
+        waveform::Waveform,
+        whitespace::{WHITESPACE_AFTER, WHITESPACE_BEFORE},
+    },
+    views::{
+        rename_annotation::{key_label, parse_key},
+        Editor, ShortcutEntry, ViewState,
+    },
+    widgets::{label_rank, token_details, value_editor, Token, TokenEditor, TokenHeader},
     JobExecutor,
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use egui::{
-    mutex::RwLock, Button, Key, KeyboardShortcut, Modifiers, Pos2, Rangef, Rect, ScrollArea,
-    TextEdit, Ui, Widget,
+    mutex::RwLock, Align2, Button, CollapsingHeader, Color32, ComboBox, FontId, Key,
+    KeyboardShortcut, Modifiers, Pos2, Rangef, Rect, RichText, ScrollArea, Sense, Stroke, TextEdit,
+    Ui, Vec2, Widget,
 };
+use egui_notify::Toast;
 use graphannis::{
     graph::{AnnoKey, NodeID},
     model::AnnotationComponentType,
     update::{GraphUpdate, UpdateEvent},
     AnnotationGraph,
 };
-use graphannis_core::graph::{ANNIS_NS, NODE_NAME_KEY};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    dfs::CycleSafeDFS,
+    graph::{storage::GraphStorage, ANNIS_NS, NODE_NAME_KEY},
+    types::Component,
+};
+use rfd::FileDialog;
+
+use active_layer::{apply_extend_span, apply_merge_spans, apply_split_span};
+use concordance::ConcordancePanel;
+use duplicate_spans::DuplicateSpans;
+use edge_annotations::{EdgeAnnotationEditor, EdgeAnnotations};
+use nested_spans::apply_add_nested_span;
+use time_editing::{apply_set_time_range, TimeEditor};
+use timeline::{layers_without_ordering, InsertTimelineDialog, NewLayerDialog};
+use token_filter::TokenFilter;
+use video::MediaPlayer;
 
+mod active_layer;
+mod concordance;
+mod duplicate_spans;
+mod edge_annotations;
+mod label_limit;
+mod nested_spans;
+pub(crate) mod prefetch;
 #[cfg(test)]
 mod tests;
+mod time_editing;
+mod timeline;
+mod token_filter;
+mod video;
 
 const DELETE_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::Delete);
+const SELECT_BY_PATTERN_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND, Key::F);
+const ADD_SPAN_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::Enter);
+const EDIT_SPAN_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::F2);
+const NESTED_SPAN_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::N);
+const DUPLICATE_SPAN_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::D);
+const TOKEN_HEADER_ID: &str = "document-editor-token-header";
+const LABEL_ORDER_ID: &str = "document-editor-label-order";
+const MULTILINE_LAYERS_ID: &str = "document-editor-multiline-layers";
+const WHITESPACE_SPACING_ID: &str = "document-editor-whitespace-spacing";
+const LAYER_NAMES_ID: &str = "document-editor-layer-names";
+const CONCORDANCE_ID: &str = "document-editor-concordance";
+/// Width of a single space between two token when the whitespace is
+/// displayed as spacing.
+const SPACE_WIDTH: f32 = 6.0;
+/// Width of the gap that marks a line break between two token.
+const LINE_BREAK_WIDTH: f32 = 24.0;
+/// Inserts a line break into values of layers that allow multi-line values,
+/// while Enter alone commits the value.
+const LINE_BREAK_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::SHIFT, Key::Enter);
+const WAVEFORM_HEIGHT: f32 = 48.0;
+/// Frames per second used when stepping through the media.
+const MEDIA_FRAME_RATE: f64 = 25.0;
+/// Documents with at least this many base token are painted with the
+/// lightweight token row.
+const PAINTED_TOKEN_THRESHOLD: usize = 10_000;
+/// Names of segmentation layers and span annotations that mark sentences.
+const SENTENCE_NAMES: [&str; 3] = ["sentence", "sent", "s"];
+/// Token values that end with one of these characters end a sentence.
+const SENTENCE_END: [char; 3] = ['.', '!', '?'];
 
 #[derive(Clone)]
 struct LayoutInfo {
@@ -46,14 +134,247 @@ enum EditorActions {
     AddSegmentationSpan {
         segmentation: String,
         selected_token: HashSet<String>,
+        /// Value of the new span, which is empty for newly created spans.
+        value: String,
+    },
+    /// Split the span of a segmentation layer, so the given base token is
+    /// the first one of a new span.
+    SplitSpan {
+        segmentation: String,
+        node_name: String,
+        first_token_of_new_span: String,
+    },
+    /// Merge adjacent spans of a segmentation layer into the first one,
+    /// which gets the given value.
+    MergeSpans {
+        node_names: Vec<String>,
+        value: String,
+    },
+    /// Let a span of a segmentation layer cover additional base token.
+    ExtendSpan {
+        node_name: String,
+        covered_token: Vec<String>,
     },
     DeleteNode {
         node_name: String,
     },
+    AddNodeLabels {
+        node_name: String,
+        labels: BTreeMap<AnnoKey, String>,
+    },
+    AddDatasource,
+    SetEdgeLabel {
+        edge: EdgeAnnotations,
+        key: AnnoKey,
+        value: Option<String>,
+    },
+    /// Insert the tokenized text after the given token or as the first token
+    /// of an empty document.
+    InsertToken {
+        after_token: Option<String>,
+        text: String,
+        tokenizer: TokenizerKind,
+    },
+    /// Add a span with the annotation that covers the given base token.
+    AddNestedSpan {
+        key: AnnoKey,
+        value: String,
+        covered_token: Vec<String>,
+    },
+    /// Insert base token without a value, which are only used as items of a
+    /// timeline that the segmentation layers refer to.
+    InsertTimelineItems {
+        after_token: Option<String>,
+        count: usize,
+    },
+    SplitDocument {
+        first_token_of_new_document: String,
+    },
+    SetTimeRange {
+        node_name: String,
+        start: f64,
+        end: f64,
+    },
+}
+
+#[derive(Clone, Default)]
+struct PatternSelectionDialog {
+    open: bool,
+    pattern: String,
+    /// The segmentation layer to match against or `None` for the base token.
+    segmentation: Option<String>,
+    message: Option<Result<usize, String>>,
+}
+
+/// Deletions that are only executed after the user confirmed them.
+#[derive(Clone, Debug, PartialEq)]
+enum Deletion {
+    SelectedNodes,
+    /// Resolve several duplicates at once, either by merging or deleting.
+    DuplicateSpans(Vec<(DuplicateSpans, bool)>),
+}
+
+#[derive(Clone, Debug)]
+struct DeletionConfirmation {
+    deletion: Deletion,
+    /// Number of deleted nodes by their kind.
+    affected: BTreeMap<String, usize>,
+    dont_ask_again: bool,
+}
+
+/// Actions of the context menu of a token or span.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ContextMenuAction {
+    EditValue,
+    AddAnnotation,
+    AddSpan,
+    Delete,
+    Inspect,
+}
+
+/// Adds an annotation to the node that has been chosen in the context menu.
+#[derive(Clone, Default)]
+struct AddAnnotationDialog {
+    node_name: Option<String>,
+    key: String,
+    value: String,
+    error: Option<String>,
+}
+
+#[derive(Clone, Default)]
+struct InsertTextDialog {
+    open: bool,
+    text: String,
 }
 
 type StateUpdateFn = Box<dyn FnOnce(&mut DocumentEditor) + Send + Sync>;
 
+/// The ordered token and segmentation nodes of a document, which are needed
+/// to create a [`DocumentEditor`].
+#[derive(Clone)]
+pub(crate) struct DocumentContent {
+    parent_name: String,
+    token: Vec<Token>,
+    segmentations: BTreeMap<String, Vec<Token>>,
+    /// Spans that are not part of a segmentation layer.
+    spans: Vec<Token>,
+    datasources: Vec<Datasource>,
+    language: Option<Language>,
+    /// True if not all token of the document are connected by the ordering.
+    ordering_broken: bool,
+}
+
+impl DocumentContent {
+    pub(crate) fn load(selected_corpus_node: NodeID, graph: &AnnotationGraph) -> Result<Self> {
+        let mut token = Vec::new();
+        let mut segmentations = BTreeMap::new();
+
+        let tok_helper = TokenHelper::new(graph)?;
+        let parent_name = graph
+            .get_node_annos()
+            .get_value_for_item(&selected_corpus_node, &NODE_NAME_KEY)?
+            .unwrap_or_default()
+            .to_string();
+        let mut token_to_index = HashMap::new();
+        // Corpora without any token have no base ordering component yet
+        let mut token_ids = Vec::new();
+        let mut number_of_chains = 0;
+        if let Some(ordering_gs) = tok_helper.get_ordering_gs(None) {
+            token_ids = tok_helper.get_ordered_token(&parent_name, None)?;
+            for t in token_ids.iter() {
+                if !ordering_gs.has_ingoing_edges(*t)? {
+                    number_of_chains += 1;
+                }
+            }
+        }
+        let mut ordering_broken = number_of_chains > 1;
+        // Only a missing or split ordering chain can leave out token of the
+        // document, so the corpus structure is only searched in this case
+        if number_of_chains != 1 {
+            let ordered: HashSet<_> = token_ids.iter().copied().collect();
+            let mut missing: Vec<_> = unordered_token(graph, &tok_helper, selected_corpus_node)?
+                .into_iter()
+                .filter(|t| !ordered.contains(t))
+                .collect();
+            // A single token is not connected by any ordering edge
+            ordering_broken |= !missing.is_empty() && token_ids.len() + missing.len() > 1;
+            // Show the token even if they are not ordered, so they are not
+            // hidden and the document is not mistaken for an empty one
+            missing.sort_unstable();
+            token_ids.extend(missing);
+        }
+        for (idx, node_id) in token_ids.iter().enumerate() {
+            let t = Token::from_graph(*node_id, idx, idx, graph)?;
+            token.push(t);
+            token_to_index.insert(node_id, idx);
+        }
+
+        // Find all ordering components other than the base layer
+        for ordering_component in
+            graph.get_all_components(Some(AnnotationComponentType::Ordering), None)
+        {
+            if ordering_component.layer != ANNIS_NS || !ordering_component.name.is_empty() {
+                let token_ids =
+                    tok_helper.get_ordered_token(&parent_name, Some(&ordering_component.name))?;
+                for node_id in token_ids.iter() {
+                    let covered = tok_helper.covered_token(*node_id)?;
+                    let start = covered.first().and_then(|t| token_to_index.get(t));
+                    let end = covered.last().and_then(|t| token_to_index.get(t));
+                    if let (Some(start), Some(end)) = (start, end) {
+                        let t = Token::from_graph(*node_id, *start, *end, graph)?;
+
+                        segmentations
+                            .entry(ordering_component.name.to_string())
+                            .or_insert_with(Vec::default)
+                            .push(t);
+                    }
+                }
+            }
+        }
+        // A layer with a single node has no ordering component yet
+        for key in layers_without_ordering(graph)? {
+            for m in graph.get_node_annos().exact_anno_search(
+                Some(ANNIS_NS),
+                &key.name,
+                ValueSearch::Any,
+            ) {
+                let node_id = m?.node;
+                if !graph
+                    .get_node_annos()
+                    .has_value_for_item(&node_id, &TOKEN_KEY)?
+                {
+                    continue;
+                }
+                let covered = tok_helper.covered_token(node_id)?;
+                let start = covered.first().and_then(|t| token_to_index.get(t));
+                let end = covered.last().and_then(|t| token_to_index.get(t));
+                if let (Some(start), Some(end)) = (start, end) {
+                    let t = Token::from_graph(node_id, *start, *end, graph)?;
+                    segmentations
+                        .entry(key.name.to_string())
+                        .or_insert_with(Vec::default)
+                        .push(t);
+                }
+            }
+        }
+        let spans = load_spans(graph, &token_ids)?;
+        let datasources = datasources_of_document(graph, selected_corpus_node)?;
+        let language = graph
+            .get_node_annos()
+            .get_value_for_item(&selected_corpus_node, &LANGUAGE_KEY)?
+            .and_then(|code| Language::from_code(&code));
+        Ok(Self {
+            parent_name,
+            token,
+            segmentations,
+            spans,
+            datasources,
+            language,
+            ordering_broken,
+        })
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct DocumentEditor {
     parent_name: String,
@@ -63,9 +384,117 @@ pub(crate) struct DocumentEditor {
     selected_nodes: HashSet<String>,
     currently_edited_node: Option<String>,
     current_edited_value: String,
+    /// Move the keyboard focus to the editor of the currently edited node.
+    focus_edited_node: bool,
+    /// True while an input method (IME) composes text in the value editor.
+    ime_composing: bool,
+    /// Segmentation layers whose values can contain line breaks.
+    multiline_layers: BTreeSet<String>,
+    /// Display the whitespace between the base token as horizontal space
+    /// instead of visible characters inside the token.
+    whitespace_spacing: bool,
+    /// Show the names of the layers at the left edge of the editor.
+    layer_names: bool,
     pending_actions: Vec<EditorActions>,
     segmentations: BTreeMap<String, Vec<Token>>,
+    /// Spans that are not part of a segmentation layer.
+    spans: Vec<Token>,
+    /// Spans with this annotation key are displayed as nested brackets
+    /// above the token, e.g. `ner` for named entities.
+    nested_span_key: String,
+    /// Whether duplicating a span to another layer also copies its value.
+    duplicate_with_value: bool,
+    /// Annotation value of newly created nested spans.
+    nested_span_value: String,
+    /// Why the last nested span could not be created.
+    nested_span_error: Option<String>,
+    /// The text data sources of the document.
+    datasources: Vec<Datasource>,
+    /// The segmentation layer that is the target of span creation and editing.
+    active_layer: Option<String>,
+    token_header: TokenHeader,
+    /// Maximum number of labels shown for each token, 0 to show all.
+    max_labels: usize,
+    /// The selection when the token layout has been calculated. Selected
+    /// token show all their labels, which can change their size.
+    layout_selection: HashSet<String>,
+    /// Paint each token in a single pass instead of nesting widgets, which
+    /// keeps large documents responsive.
+    painted_token: bool,
+    has_time_annotations: bool,
+    time_editor: Option<TimeEditor>,
+    /// Media files linked to this document that exist on disk.
+    linked_media: Vec<PathBuf>,
+    waveform: Option<Arc<Waveform>>,
+    /// Plays the first linked video and audio file, if a media backend is
+    /// available.
+    media_players: Vec<MediaPlayer>,
+    /// The current media position in seconds.
+    playback_position: Option<f64>,
+    playing: bool,
     layout_info: LayoutInfo,
+    pattern_selection: PatternSelectionDialog,
+    insert_text: InsertTextDialog,
+    insert_timeline: InsertTimelineDialog,
+    new_layer: NewLayerDialog,
+    /// Text entered in an empty document to create the first token from.
+    initial_text: String,
+    /// True if not all token of the document are connected by the ordering,
+    /// so they might be shown in the wrong order.
+    ordering_broken: bool,
+    token_filter: TokenFilter,
+    /// Result of the last duplicate check, the dialog is shown if this is set.
+    duplicate_spans: Option<Vec<DuplicateSpans>>,
+    /// Ask the user before deleting nodes.
+    confirm_deletion: bool,
+    /// Show all annotations of token and spans when hovering them.
+    token_tooltips: bool,
+    deletion_confirmation: Option<DeletionConfirmation>,
+    /// Set when the user disabled the confirmation, so it can be stored in
+    /// the settings.
+    confirmation_disabled: bool,
+    edge_annotations: EdgeAnnotationEditor,
+    /// Action chosen in the context menu of a node, which is executed after
+    /// all nodes have been shown.
+    context_menu_request: Option<(String, ContextMenuAction)>,
+    add_annotation_dialog: AddAnnotationDialog,
+    /// Node whose details are shown in their own window.
+    inspected_node: Option<String>,
+    concordance: ConcordancePanel,
+    /// Document and base token index of an occurrence in another document
+    /// the user wants to see.
+    navigation_request: Option<(NodeID, usize)>,
+    /// Number of labels for each annotation key (without the ANNIS namespace)
+    label_counts: BTreeMap<AnnoKey, usize>,
+    hidden_labels: BTreeSet<AnnoKey>,
+    /// Labels with these keys are shown first in each token.
+    label_order: Vec<AnnoKey>,
+    /// Layer settings of the corpus, which also define the types of the
+    /// annotation values.
+    layer_settings: LayerSettings,
+    /// Segmentation layers that are shown before all other ones, which are
+    /// ordered by their name.
+    segmentation_order: Vec<String>,
+    neighbour_documents: (Option<NodeID>, Option<NodeID>),
+    /// Keep the actions until they are explicitly committed, e.g. by saving.
+    manual_commit: bool,
+    /// Splits inserted text into token.
+    tokenizer: TokenizerKind,
+    /// The language of the document from its metadata. Text of right-to-left
+    /// languages is aligned to the right when it is edited.
+    language: Option<Language>,
+    /// Namespace that is pre-filled for new annotations.
+    default_namespace: String,
+    /// File to save a screenshot of the token and spans to.
+    png_export: Option<PathBuf>,
+    screenshot_requested: bool,
+    /// The area the token and spans have been displayed in.
+    view_rect: Option<Rect>,
+    /// Index of the first base token that is at least partially visible.
+    first_visible_token: usize,
+    /// Index of the base token to scroll to as soon as its position is known
+    /// and whether it is centered or shown at the start of the view.
+    scroll_request: Option<(usize, egui::Align)>,
     jobs: JobExecutor,
 }
 
@@ -75,49 +504,26 @@ impl DocumentEditor {
         graph: Arc<RwLock<AnnotationGraph>>,
         jobs: JobExecutor,
     ) -> Result<Self> {
-        let mut token = Vec::new();
-        let mut segmentations = BTreeMap::new();
-        let parent_name;
+        let content = DocumentContent::load(selected_corpus_node, &graph.read())?;
+        Ok(Self::create_from_content(content, graph, jobs))
+    }
 
-        {
-            let graph = graph.read();
-            let tok_helper = TokenHelper::new(&graph)?;
-            parent_name = graph
-                .get_node_annos()
-                .get_value_for_item(&selected_corpus_node, &NODE_NAME_KEY)?
-                .unwrap_or_default()
-                .to_string();
-            let mut token_to_index = HashMap::new();
-            let token_ids = tok_helper.get_ordered_token(&parent_name, None)?;
-            for (idx, node_id) in token_ids.iter().enumerate() {
-                let t = Token::from_graph(*node_id, idx, idx, &graph)?;
-                token.push(t);
-                token_to_index.insert(node_id, idx);
-            }
-
-            // Find all ordering components other than the base layer
-            for ordering_component in
-                graph.get_all_components(Some(AnnotationComponentType::Ordering), None)
-            {
-                if ordering_component.layer != ANNIS_NS || !ordering_component.name.is_empty() {
-                    let token_ids = tok_helper
-                        .get_ordered_token(&parent_name, Some(&ordering_component.name))?;
-                    for node_id in token_ids.iter() {
-                        let covered = tok_helper.covered_token(*node_id)?;
-                        let start = covered.first().and_then(|t| token_to_index.get(t));
-                        let end = covered.last().and_then(|t| token_to_index.get(t));
-                        if let (Some(start), Some(end)) = (start, end) {
-                            let t = Token::from_graph(*node_id, *start, *end, &graph)?;
-
-                            segmentations
-                                .entry(ordering_component.name.to_string())
-                                .or_insert_with(Vec::default)
-                                .push(t);
-                        }
-                    }
-                }
-            }
-        }
+    /// Create the editor from already loaded document content, e.g. one that
+    /// has been prefetched in the background.
+    pub(crate) fn create_from_content(
+        content: DocumentContent,
+        graph: Arc<RwLock<AnnotationGraph>>,
+        jobs: JobExecutor,
+    ) -> Self {
+        let DocumentContent {
+            parent_name,
+            token,
+            segmentations,
+            spans,
+            datasources,
+            language,
+            ordering_broken,
+        } = content;
         let nr_token = token.len();
 
         let token_index_by_name = token
@@ -126,7 +532,18 @@ impl DocumentEditor {
             .map(|(idx, t)| (t.node_name.clone(), idx))
             .collect();
 
-        Ok(Self {
+        let has_time_annotations = token
+            .iter()
+            .chain(segmentations.values().flatten())
+            .any(|t| t.labels.contains_key(&TIME_KEY));
+        let mut label_counts = BTreeMap::new();
+        for t in token.iter().chain(segmentations.values().flatten()) {
+            for key in t.labels.keys().filter(|k| k.ns != ANNIS_NS) {
+                *label_counts.entry(key.clone()).or_default() += 1;
+            }
+        }
+
+        Self {
             parent_name,
             graph,
             token,
@@ -138,25 +555,158 @@ impl DocumentEditor {
                 token_offset_start: vec![0.0; nr_token],
                 token_offset_end: vec![0.0; nr_token],
             },
+            active_layer: segmentations.keys().next().cloned(),
             segmentations,
+            spans,
+            nested_span_key: String::new(),
+            duplicate_with_value: true,
+            nested_span_value: String::new(),
+            nested_span_error: None,
+            datasources,
+            token_header: TokenHeader::default(),
+            max_labels: 0,
+            layout_selection: HashSet::new(),
+            painted_token: nr_token >= PAINTED_TOKEN_THRESHOLD,
+            has_time_annotations,
+            time_editor: None,
+            linked_media: Vec::new(),
+            waveform: None,
+            media_players: Vec::new(),
+            playback_position: None,
+            playing: false,
             selected_nodes: HashSet::new(),
             pending_actions: Vec::new(),
             currently_edited_node: None,
             current_edited_value: String::new(),
+            focus_edited_node: false,
+            ime_composing: false,
+            multiline_layers: BTreeSet::new(),
+            whitespace_spacing: false,
+            layer_names: true,
+            pattern_selection: PatternSelectionDialog::default(),
+            insert_text: InsertTextDialog::default(),
+            insert_timeline: InsertTimelineDialog::default(),
+            new_layer: NewLayerDialog::default(),
+            initial_text: String::new(),
+            ordering_broken,
+            token_filter: TokenFilter::default(),
+            duplicate_spans: None,
+            confirm_deletion: false,
+            token_tooltips: false,
+            deletion_confirmation: None,
+            confirmation_disabled: false,
+            edge_annotations: EdgeAnnotationEditor::default(),
+            context_menu_request: None,
+            add_annotation_dialog: AddAnnotationDialog::default(),
+            inspected_node: None,
+            concordance: ConcordancePanel::default(),
+            navigation_request: None,
+            label_counts,
+            hidden_labels: BTreeSet::new(),
+            label_order: Vec::new(),
+            layer_settings: LayerSettings::default(),
+            segmentation_order: Vec::new(),
+            neighbour_documents: (None, None),
+            manual_commit: false,
+            tokenizer: TokenizerKind::default(),
+            language,
+            default_namespace: String::new(),
+            png_export: None,
+            screenshot_requested: false,
+            view_rect: None,
+            first_visible_token: 0,
+            scroll_request: None,
             jobs,
-        })
+        }
+    }
+
+    /// Scrolls smoothly to the base token with the given index, so it is in
+    /// the center of the view. The cached layout offsets are used, so this
+    /// also works for token that are currently not rendered.
+    pub(crate) fn scroll_to_token(&mut self, index: usize) {
+        if !self.token.is_empty() {
+            self.scroll_request = Some((index.min(self.token.len() - 1), egui::Align::Center));
+        }
     }
 
+    /// Scrolls to the first base token covered by the node, which can be a
+    /// base token, a segmentation node or a span. Returns `false` if the node
+    /// is not part of this document.
+    pub(crate) fn scroll_to_node(&mut self, node_name: &str) -> bool {
+        if let Some(index) = self.node_position(node_name) {
+            self.scroll_to_token(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Hide and order the annotations and segmentation layers as configured
+    /// for the corpus, e.g. in its ANNIS configuration.
+    pub(crate) fn apply_layer_settings(&mut self, settings: &LayerSettings) {
+        self.layer_settings = settings.clone();
+        if settings.is_empty() {
+            return;
+        }
+        self.hidden_labels.extend(
+            self.label_counts
+                .keys()
+                .filter(|k| settings.is_hidden(k))
+                .cloned(),
+        );
+        self.label_order = settings.ordered_keys(self.label_counts.keys());
+        let mut ranked: Vec<(usize, String)> = self
+            .segmentations
+            .keys()
+            .filter_map(|name| settings.segmentation_rank(name).map(|r| (r, name.clone())))
+            .collect();
+        ranked.sort();
+        self.segmentation_order = ranked.into_iter().map(|(_, name)| name).collect();
+        self.active_layer = self.ordered_segmentations().into_iter().next();
+        self.layout_info.valid = false;
+    }
+
+    /// The names of the segmentation layers in the order they are shown and
+    /// colored.
+    fn ordered_segmentations(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.segmentations.keys().cloned().collect();
+        names.sort_by_key(|name| {
+            self.segmentation_order
+                .iter()
+                .position(|n| n == name)
+                .unwrap_or(usize::MAX)
+        });
+        names
+    }
+
+    /// Set the previous and next document in the corpus tree, which can be
+    /// navigated to from this editor.
+    pub(crate) fn set_neighbour_documents(&mut self, neighbours: (Option<NodeID>, Option<NodeID>)) {
+        self.neighbour_documents = neighbours;
+    }
+
+    /// Shows the segmentation nodes covering at least one of the base token
+    /// from `visible_token`, the other ones are not rendered at all.
     fn show_segmentation_layers(
         &mut self,
         ui: &mut Ui,
         token_offset_to_rect: &[Option<Rect>],
+        visible_token: RangeInclusive<usize>,
         mut current_span_offset: f32,
-    ) {
+    ) -> Vec<(String, Rangef)> {
+        let mut rows = Vec::with_capacity(self.segmentations.len());
         let ui_style = ui.style().clone();
-        for (_, seg_token) in self.segmentations.iter_mut() {
+        let label_limit = self.label_limit();
+        let can_add_span = self.active_layer.is_some();
+        for (layer_idx, layer_name) in self.ordered_segmentations().iter().enumerate() {
+            let Some(seg_token) = self.segmentations.get_mut(layer_name) else {
+                continue;
+            };
+            let layer_color = layer_color(ui, layer_idx);
             let mut max_node_height = 0.0;
-            for t in seg_token.iter_mut() {
+            let visible_spans =
+                visible_span_range(seg_token, *visible_token.start(), *visible_token.end());
+            for t in seg_token[visible_spans].iter_mut() {
                 // Get the base token covered by this span and use them to create a rectangle
                 let mut covered_span = Rangef::NOTHING;
                 for token_rect in token_offset_to_rect
@@ -175,43 +725,105 @@ impl DocumentEditor {
 
                     if ui.is_rect_visible(segmentation_rectangle) {
                         if self.currently_edited_node == Some(t.node_name.clone()) {
-                            let segmentation_editor =
-                                TextEdit::singleline(&mut self.current_edited_value);
+                            let multiline = self.multiline_layers.contains(layer_name)
+                                || self.current_edited_value.contains('\n');
+                            let composing = self.ime_composing;
+                            let (commit, cancel) = ui.input_mut(|i| {
+                                // Enter confirms the text of an input method
+                                // and must not end the editing
+                                if composing {
+                                    i.events.retain(|e| {
+                                        !matches!(
+                                            e,
+                                            egui::Event::Key {
+                                                key: Key::Enter,
+                                                ..
+                                            }
+                                        )
+                                    });
+                                }
+                                let commit = multiline
+                                    && !i.modifiers.shift
+                                    && i.consume_key(Modifiers::NONE, Key::Enter);
+                                (commit, i.key_pressed(Key::Escape))
+                            });
+                            let align = self.text_align();
+                            let segmentation_editor = if multiline {
+                                TextEdit::multiline(&mut self.current_edited_value)
+                                    .desired_rows(self.current_edited_value.lines().count().max(1))
+                                    .return_key(LINE_BREAK_SHORTCUT)
+                            } else {
+                                TextEdit::singleline(&mut self.current_edited_value)
+                            }
+                            .horizontal_align(align);
                             let segmentation_editor =
                                 ui.put(segmentation_rectangle, segmentation_editor);
                             max_node_height =
                                 segmentation_editor.rect.height().max(max_node_height);
-                            if segmentation_editor.lost_focus() {
+                            self.ime_composing = ui.input(|i| {
+                                i.events.iter().fold(composing, |composing, e| match e {
+                                    egui::Event::Ime(egui::ImeEvent::Preedit(text)) => {
+                                        !text.is_empty()
+                                    }
+                                    egui::Event::Ime(_) => false,
+                                    _ => composing,
+                                })
+                            });
+                            if self.focus_edited_node || (cancel && composing) {
+                                // Escape only ends the composition of an input
+                                // method, but not the editing
+                                segmentation_editor.request_focus();
+                                self.focus_edited_node = false;
+                            } else if commit {
+                                segmentation_editor.surrender_focus();
+                            }
+                            if cancel && !composing {
+                                // Discard the changes
+                                self.currently_edited_node = None;
+                                self.current_edited_value =
+                                    t.labels.get(&TOKEN_KEY).cloned().unwrap_or_default();
+                            } else if segmentation_editor.lost_focus() || commit {
                                 self.currently_edited_node = None;
                                 self.selected_nodes.remove(&t.node_name);
                                 let new_value = self.current_edited_value.clone();
-                                let old_value = t.labels.get(&TOKEN_KEY);
-                                if Some(&new_value) != old_value {
-                                    t.labels
-                                        .insert(TOKEN_KEY.as_ref().clone(), new_value.clone());
-
+                                if let Some(action) = modify_value(t, new_value) {
                                     self.layout_info.valid = false;
-                                    self.pending_actions.push(
-                                        EditorActions::ModifySegmentationValue {
-                                            node_name: t.node_name.clone(),
-                                            new_value: new_value.clone(),
-                                        },
-                                    );
+                                    self.pending_actions.push(action);
                                 }
                             }
                         } else {
                             let selected = self.selected_nodes.contains(&t.node_name);
+                            let at_playback_position =
+                                is_at_position(&self.token, self.playback_position, t);
+                            let opacity = self.token_filter.opacity(t);
                             let segmentation_editor = TokenEditor::with_exact_width(
                                 t,
                                 selected,
                                 Some(segmentation_rectangle.width()),
-                            );
+                            )
+                            .hide_labels(&self.hidden_labels)
+                            .label_order(&self.label_order)
+                            .header(self.token_header)
+                            .max_labels(label_limit)
+                            .highlight(at_playback_position)
+                            .layer_color(layer_color)
+                            .opacity(opacity)
+                            .painted(self.painted_token)
+                            .details_tooltip(self.token_tooltips);
 
                             let segmentation_editor =
                                 ui.put(segmentation_rectangle, segmentation_editor);
                             max_node_height =
                                 segmentation_editor.rect.height().max(max_node_height);
-                            if segmentation_editor.clicked() {
+                            let mut context_action = None;
+                            segmentation_editor.context_menu(|ui| {
+                                context_action =
+                                    node_context_menu(ui, &t.node_name, true, can_add_span);
+                            });
+                            if let Some(action) = context_action {
+                                self.context_menu_request = Some((t.node_name.clone(), action));
+                            }
+                            if segmentation_editor.clicked() && opacity > 0.0 {
                                 if selected {
                                     // Already selected, allow editing
                                     self.currently_edited_node = Some(t.node_name.clone());
@@ -240,157 +852,1700 @@ impl DocumentEditor {
                     }
                 }
             }
+            rows.push((
+                layer_name.clone(),
+                Rangef::new(current_span_offset, current_span_offset + max_node_height),
+            ));
             current_span_offset += max_node_height + ui_style.spacing.item_spacing.y;
         }
+        rows
     }
 
-    fn select_range(&mut self, token_position: usize) {
-        // Mark a range of token, find a suitable token as start for the range first
-        let mut selected_token_indices: BTreeSet<_> = self
-            .selected_nodes
-            .iter()
-            .filter_map(|selected_node| self.token_index_by_name.get(selected_node))
-            .copied()
-            .collect();
-        let after = selected_token_indices.split_off(&token_position);
-
-        if let Some(after) = after.first() {
-            for i in token_position..*after {
-                self.selected_nodes.insert(self.token[i].node_name.clone());
-            }
-        } else if let Some(before) = selected_token_indices.last() {
-            for i in *before..token_position {
-                self.selected_nodes.insert(self.token[i].node_name.clone());
+    /// Paint the names of the token and segmentation layers at the left edge
+    /// of the visible part of the document, so they stay visible when
+    /// scrolling horizontally.
+    fn show_layer_names(&self, ui: &Ui, view_rect: Rect, rows: &[(String, Rangef)]) {
+        let painter = ui.painter_at(view_rect);
+        let visuals = ui.visuals();
+        let margin = Vec2::new(4.0, 2.0);
+        for (name, y_range) in rows {
+            if y_range.span() <= 0.0 {
+                continue;
             }
+            let text = if name.is_empty() { "(default)" } else { name };
+            let galley = painter.layout_no_wrap(
+                text.to_string(),
+                FontId::proportional(11.0),
+                visuals.strong_text_color(),
+            );
+            let rect = Rect::from_min_size(
+                Pos2::new(view_rect.left(), y_range.min),
+                galley.size() + 2.0 * margin,
+            );
+            painter.rect_filled(rect, 2.0, visuals.window_fill.gamma_multiply(0.9));
+            painter.rect_stroke(
+                rect,
+                2.0,
+                Stroke::new(1.0, visuals.widgets.noninteractive.bg_stroke.color),
+                egui::StrokeKind::Inside,
+            );
+            painter.galley(rect.min + margin, galley, visuals.strong_text_color());
         }
-        self.selected_nodes
-            .insert(self.token[token_position].node_name.clone());
     }
 
-    /// Adds an empty segmentation node that spans the currently selected token.
-    ///
-    /// - `layer_idx` The segmentation layer to add the new node to. **Starts with 1.**
-    fn add_segmentation_for_selection(&mut self, layer_idx: usize) {
-        if let Some((seg_name, _token)) = self.segmentations.iter().nth(layer_idx.saturating_sub(1))
-        {
-            if !self.selected_nodes.is_empty() {
-                // Apply changes to internal data model
-                let mut selected_token_indices: Vec<_> = self
-                    .selected_nodes
-                    .iter()
-                    .filter_map(|n| self.token_index_by_name.get(n))
-                    .copied()
-                    .collect();
-                selected_token_indices.sort();
-                {
-                    let graph = self.graph.read();
-                    if let Ok(tok_helper) = TokenHelper::new(&graph) {
-                        // Schedule an update of the underlaying graph
-                        let selected_token: HashSet<_> = self
-                            .selected_nodes
-                            .iter()
-                            .filter(|node_name| {
-                                if let Ok(Some(node_id)) =
-                                    graph.get_node_annos().get_node_id_from_name(node_name)
-                                {
-                                    tok_helper.is_token(node_id).unwrap_or(false)
-                                } else {
-                                    false
-                                }
-                            })
-                            .cloned()
-                            .collect();
-
-                        self.pending_actions
-                            .push(EditorActions::AddSegmentationSpan {
-                                segmentation: seg_name.clone(),
-                                selected_token,
-                            });
-                    }
-                }
-                self.apply_pending_updates_for_editor();
+    /// The suggested file name for exports of this document.
+    fn export_file_name(&self, extension: &str) -> String {
+        let document_name = self
+            .parent_name
+            .rsplit_once('/')
+            .map_or(self.parent_name.as_str(), |(_, name)| name);
+        format!("{document_name}.{extension}")
+    }
+
+    /// Export the token, the segmentation spans, the pointing relations and
+    /// all visible annotations as SVG image.
+    fn export_svg(&self) {
+        let dlg = FileDialog::new()
+            .add_filter("SVG (*.svg)", &["svg"])
+            .set_file_name(self.export_file_name("svg"));
+        if let Some(path) = dlg.save_file() {
+            let graph = self.graph.clone();
+            let token = self.token.clone();
+            let segmentations = self.segmentations.clone();
+            let hidden_labels = self.hidden_labels.clone();
+            self.jobs.add(
+                "Exporting document as SVG",
+                move |_job| {
+                    let node_names = token
+                        .iter()
+                        .chain(segmentations.values().flatten())
+                        .map(|t| t.node_name.as_str());
+                    let arcs = pointing_arcs(&graph.read(), node_names)?;
+                    let output = BufWriter::new(File::create(&path)?);
+                    write_document_svg(&token, &segmentations, &hidden_labels, &arcs, output)?;
+                    Ok(path)
+                },
+                |path, app| {
+                    app.notifier.add_toast(Toast::info(format!(
+                        "Exported document to {}",
+                        path.display()
+                    )));
+                },
+            );
+        }
+    }
+
+    /// Save the currently displayed part of the document as PNG image once
+    /// the screenshot requested in [`DocumentEditor::show`] is available.
+    fn export_png_from_screenshot(&mut self, ctx: &egui::Context) {
+        if self.png_export.is_none() {
+            return;
+        }
+        if !self.screenshot_requested {
+            // Request the screenshot in the frame after the menu was closed
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+            self.screenshot_requested = true;
+            return;
+        }
+        let screenshot = ctx.input(|i| {
+            i.raw.events.iter().find_map(|e| match e {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        if let (Some(screenshot), Some(view_rect)) = (screenshot, self.view_rect) {
+            self.screenshot_requested = false;
+            if let Some(path) = self.png_export.take() {
+                let image = screenshot.region(&view_rect, Some(ctx.pixels_per_point()));
+                self.jobs.add(
+                    "Exporting view as PNG",
+                    move |_job| {
+                        image::save_buffer(
+                            &path,
+                            image.as_raw(),
+                            image.size[0] as u32,
+                            image.size[1] as u32,
+                            image::ExtendedColorType::Rgba8,
+                        )?;
+                        Ok(path)
+                    },
+                    |path, app| {
+                        app.notifier
+                            .add_toast(Toast::info(format!("Exported view to {}", path.display())));
+                    },
+                );
             }
         }
     }
 
-    fn delete_selected_nodes(&mut self) {
-        self.layout_info.valid = false;
-        for (_, segmentation_token) in self.segmentations.iter_mut() {
-            segmentation_token.retain(|t| !self.selected_nodes.contains(&t.node_name));
+    /// Export the token, the segmentation spans and all visible annotations
+    /// as HTML file.
+    fn export_html(&self) {
+        let dlg = FileDialog::new()
+            .add_filter("HTML (*.html)", &["html"])
+            .set_file_name(self.export_file_name("html"));
+        if let Some(path) = dlg.save_file() {
+            let title = self.parent_name.clone();
+            let token = self.token.clone();
+            let segmentations = self.segmentations.clone();
+            let hidden_labels = self.hidden_labels.clone();
+            self.jobs.add(
+                "Exporting document as HTML",
+                move |_job| {
+                    let output = BufWriter::new(File::create(&path)?);
+                    write_document_html(&title, &token, &segmentations, &hidden_labels, output)?;
+                    Ok(path)
+                },
+                |path, app| {
+                    app.notifier.add_toast(Toast::info(format!(
+                        "Exported document to {}",
+                        path.display()
+                    )));
+                },
+            );
         }
-        for n in self.selected_nodes.iter() {
-            self.pending_actions.push(EditorActions::DeleteNode {
-                node_name: n.clone(),
-            });
+    }
+
+    /// Alignment of edited text, which depends on the writing direction of
+    /// the document language.
+    fn text_align(&self) -> egui::Align {
+        if self.language.is_some_and(|l| l.right_to_left) {
+            egui::Align::RIGHT
+        } else {
+            egui::Align::LEFT
         }
-        self.selected_nodes.clear();
-        self.apply_pending_updates_for_editor();
     }
-}
 
-impl Editor for DocumentEditor {
-    fn show(&mut self, ui: &mut Ui) {
-        let ui_style = ui.style().clone();
-        let mut current_span_offset: f32 = 0.0;
+    /// Apply the pending actions right away, unless they should only be
+    /// committed manually.
+    fn apply_pending_updates_automatically(&mut self) {
+        if !self.manual_commit {
+            self.apply_pending_updates_for_editor();
+        }
+    }
 
-        // Remember the location of each token, so we can paint the spans with
-        // the same range later
-        let mut token_offset_to_rect = vec![None; self.token.len()];
-        ScrollArea::horizontal().show_viewport(ui, |ui, visible_rect| {
-            if self.layout_info.first_frame {
-                ui.scroll_to_cursor(Some(egui::Align::LEFT));
+    fn show_toolbar(&mut self, ui: &mut Ui) {
+        let token_header_id = egui::Id::new(TOKEN_HEADER_ID);
+        if self.layout_info.first_frame {
+            if let Some(header) = ui.data_mut(|d| d.get_persisted(token_header_id)) {
+                self.token_header = header;
             }
-            // If we already calculated the token positions once, only render
-            // the token and their covering spans that are currently displayed
-            let mut first_visible_token: usize = 0;
-            let last_token_index = self.token.len() - 1;
-            let mut last_visible_token: usize = last_token_index;
-            let visible_range = visible_rect.x_range().min..visible_rect.x_range().max;
-            if self.layout_info.valid {
-                first_visible_token = self
-                    .layout_info
-                    .token_offset_start
-                    .partition_point(|x| {
-                        x.partial_cmp(&visible_range.start)
-                            .unwrap_or(Ordering::Equal)
-                            .is_lt()
-                    })
-                    .saturating_sub(1);
-                last_visible_token = self
-                    .layout_info
-                    .token_offset_end
-                    .partition_point(|x| {
-                        x.partial_cmp(&visible_range.end)
-                            .unwrap_or(Ordering::Equal)
-                            .is_lt()
-                    })
-                    .saturating_add(1);
+            // The order from the layer settings of the corpus takes precedence
+            if let Some(label_order) = ui
+                .data_mut(|d| d.get_persisted(egui::Id::new(LABEL_ORDER_ID)))
+                .filter(|_| self.label_order.is_empty())
+            {
+                self.label_order = label_order;
             }
-            if last_visible_token > last_token_index {
-                last_visible_token = last_token_index
+            if let Some(layers) =
+                ui.data_mut(|d| d.get_persisted(egui::Id::new(MULTILINE_LAYERS_ID)))
+            {
+                self.multiline_layers = layers;
             }
-
-            ui.horizontal(|ui| {
-                if self.layout_info.valid && first_visible_token > 0 {
-                    // Add the space needed for the non-rendered token at the beginning
-                    ui.add_space(self.layout_info.token_offset_end[first_visible_token - 1]);
+            if let Some(spacing) =
+                ui.data_mut(|d| d.get_persisted(egui::Id::new(WHITESPACE_SPACING_ID)))
+            {
+                self.whitespace_spacing = spacing;
+            }
+            if let Some(layer_names) =
+                ui.data_mut(|d| d.get_persisted(egui::Id::new(LAYER_NAMES_ID)))
+            {
+                self.layer_names = layer_names;
+            }
+            if let Some(concordance) =
+                ui.data_mut(|d| d.get_persisted(egui::Id::new(CONCORDANCE_ID)))
+            {
+                self.concordance.open = concordance;
+            }
+        }
+        let old_header = self.token_header;
+        let old_multiline_layers = self.multiline_layers.clone();
+        let old_whitespace_spacing = self.whitespace_spacing;
+        let old_layer_names = self.layer_names;
+        let old_concordance = self.concordance.open;
+        ui.horizontal(|ui| {
+            if !self.segmentations.is_empty() {
+                self.show_active_layer_selection(ui);
+                if let Some(layer) = self.active_layer.clone() {
+                    let mut multiline = self.multiline_layers.contains(&layer);
+                    if ui
+                        .toggle_value(&mut multiline, "Line breaks")
+                        .on_hover_text(format!(
+                            "Allow line breaks in the values of the active layer, which are \
+                             inserted with {}",
+                            ui.ctx().format_shortcut(&LINE_BREAK_SHORTCUT)
+                        ))
+                        .changed()
+                    {
+                        if multiline {
+                            self.multiline_layers.insert(layer);
+                        } else {
+                            self.multiline_layers.remove(&layer);
+                        }
+                    }
                 }
-
-                for token_position in first_visible_token..=last_visible_token {
-                    let token_node_name = &self.token[token_position].node_name;
-                    let minimal_token_width = self
-                        .layout_info
-                        .min_token_width
-                        .get(self.token[token_position].start)
-                        .copied();
-                    let token_start = self.token[token_position].start;
-                    let response = TokenEditor::with_min_width(
+                ui.toggle_value(&mut self.layer_names, "Layer names")
+                    .on_hover_text("Show the names of the layers at the left edge of the editor");
+                ui.separator();
+            }
+            ComboBox::from_label("Token header")
+                .selected_text(self.token_header.description())
+                .show_ui(ui, |ui| {
+                    for header in TokenHeader::ALL {
+                        ui.selectable_value(&mut self.token_header, header, header.description());
+                    }
+                });
+            self.show_label_limit(ui);
+            ui.toggle_value(&mut self.whitespace_spacing, "Text spacing")
+                .on_hover_text(
+                    "Show the whitespace between the token as space and line breaks as \
+                     vertical lines instead of visible characters",
+                );
+            ui.toggle_value(&mut self.concordance.open, "Concordance")
+                .on_hover_text(
+                "Show the other occurrences of the selected token's text or lemma in the corpus",
+            );
+            ui.separator();
+            self.token_filter.show(ui);
+        });
+        self.show_nested_span_inputs(ui);
+        if old_multiline_layers != self.multiline_layers {
+            let layers = self.multiline_layers.clone();
+            ui.data_mut(|d| d.insert_persisted(egui::Id::new(MULTILINE_LAYERS_ID), layers));
+        }
+        if old_layer_names != self.layer_names {
+            ui.data_mut(|d| d.insert_persisted(egui::Id::new(LAYER_NAMES_ID), self.layer_names));
+        }
+        if old_concordance != self.concordance.open {
+            ui.data_mut(|d| {
+                d.insert_persisted(egui::Id::new(CONCORDANCE_ID), self.concordance.open)
+            });
+        }
+        if old_whitespace_spacing != self.whitespace_spacing {
+            ui.data_mut(|d| {
+                d.insert_persisted(
+                    egui::Id::new(WHITESPACE_SPACING_ID),
+                    self.whitespace_spacing,
+                )
+            });
+            self.layout_info.valid = false;
+        }
+        if old_header != self.token_header {
+            ui.data_mut(|d| d.insert_persisted(token_header_id, self.token_header));
+            // The header can change the width of the token
+            self.layout_info.valid = false;
+        }
+    }
+
+    /// Find the media files linked to the document, load the waveform of the
+    /// first audio file and play the first audio and video file in the media
+    /// panel. The audio of the video is only played if there is no linked
+    /// audio file.
+    pub(crate) fn load_linked_media(&mut self, corpus_location: &Path) -> Result<()> {
+        let graph = self.graph.read();
+        let document = graph
+            .get_node_annos()
+            .get_node_id_from_name(&self.parent_name)?
+            .context("Missing document node")?;
+        for file in linked_files(&graph, document)? {
+            if let Some(path) = resolve_linked_file(&file, corpus_location) {
+                if is_audio(&path) && self.waveform.is_none() {
+                    let waveform = Waveform::from_wav(BufReader::new(File::open(&path)?))
+                        .with_context(|| format!("Could not read audio file {}", path.display()))?;
+                    self.waveform = Some(Arc::new(waveform));
+                }
+                self.linked_media.push(path);
+            }
+        }
+        if let Some(backend) = FfmpegBackend::detect() {
+            let backend: Arc<dyn MediaBackend> = Arc::new(backend);
+            let audio = self.linked_media.iter().find(|p| is_audio(p));
+            if let Some(video) = self.linked_media.iter().find(|p| is_video(p)) {
+                self.media_players.push(MediaPlayer::new(
+                    video.clone(),
+                    backend.clone(),
+                    true,
+                    audio.is_none(),
+                ));
+            }
+            if let Some(audio) = audio {
+                self.media_players
+                    .push(MediaPlayer::new(audio.clone(), backend, false, true));
+            }
+        }
+        Ok(())
+    }
+
+    fn shows_waveform(&self) -> bool {
+        self.waveform.is_some() && self.has_time_annotations
+    }
+
+    /// Paints the waveform above each visible base token, stretched to the
+    /// time range of the token.
+    fn show_waveform(&mut self, ui: &mut Ui, top: f32, token_offset_to_rect: &[Option<Rect>]) {
+        let Some(waveform) = self.waveform.clone() else {
+            return;
+        };
+        let color = ui.visuals().weak_text_color();
+        let mut clicked = None;
+        for (idx, token_rect) in token_offset_to_rect.iter().enumerate() {
+            let (Some(token_rect), Some(t)) = (token_rect, self.token.get(idx)) else {
+                continue;
+            };
+            let Some(TimeRange {
+                start,
+                end: Some(end),
+            }) = self.time_range(t)
+            else {
+                continue;
+            };
+            let strip = Rect::from_x_y_ranges(token_rect.x_range(), top..=top + WAVEFORM_HEIGHT);
+            if strip.width() <= 0.0 || end <= start {
+                continue;
+            }
+            let painter = ui.painter_at(strip);
+            let middle = strip.center().y;
+            let seconds_per_pixel = (end - start) / strip.width() as f64;
+            let mut x = strip.left();
+            while x < strip.right() {
+                let time = start + (x - strip.left()) as f64 * seconds_per_pixel;
+                let amplitude = waveform.peak_at(time).unwrap_or(0.0) * WAVEFORM_HEIGHT / 2.0;
+                painter.line_segment(
+                    [
+                        Pos2::new(x, middle - amplitude),
+                        Pos2::new(x, middle + amplitude),
+                    ],
+                    Stroke::new(1.0, color),
+                );
+                x += 1.0;
+            }
+            if let Some(position) = self.playback_position.filter(|p| *p >= start && *p < end) {
+                let x = strip.left() + ((position - start) / seconds_per_pixel) as f32;
+                painter.line_segment(
+                    [Pos2::new(x, strip.top()), Pos2::new(x, strip.bottom())],
+                    Stroke::new(2.0, Color32::RED),
+                );
+            }
+            let response = ui.interact(strip, ui.id().with(("waveform", idx)), Sense::click());
+            if response.clicked() {
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    let time = start + (pointer.x - strip.left()) as f64 * seconds_per_pixel;
+                    clicked = Some((idx, time));
+                }
+            }
+        }
+        if let Some((token_idx, time)) = clicked {
+            self.seek(time, token_idx);
+        }
+    }
+
+    /// True if the time range of the token includes the playback position.
+    fn is_at_playback_position(&self, t: &Token) -> bool {
+        is_at_position(&self.token, self.playback_position, t)
+    }
+
+    /// End of the media, which is the end of the last timed token or the
+    /// duration of the audio.
+    fn media_duration(&self) -> f64 {
+        let token_end = self
+            .token
+            .iter()
+            .rev()
+            .find_map(|t| self.time_range(t).and_then(|r| r.end))
+            .unwrap_or_default();
+        let audio_end = self
+            .waveform
+            .as_ref()
+            .map(|w| w.duration())
+            .unwrap_or_default();
+        token_end.max(audio_end)
+    }
+
+    fn step_media(&mut self, seconds: f64) {
+        let position = self.playback_position.unwrap_or_default() + seconds;
+        self.playback_position = Some(position.clamp(0.0, self.media_duration()));
+    }
+
+    /// Shows the controls for the media position and the video frame at this
+    /// position. Videos that can not be decoded by a media backend can be
+    /// opened with an external player.
+    fn show_media_panel(&mut self, ui: &mut Ui) {
+        if !self.has_time_annotations || (self.linked_media.is_empty() && self.waveform.is_none()) {
+            return;
+        }
+        if self.playing {
+            let dt = ui.input(|i| i.stable_dt) as f64;
+            self.step_media(dt);
+            if self.playback_position.unwrap_or_default() >= self.media_duration() {
+                self.playing = false;
+            }
+            ui.ctx().request_repaint();
+        }
+        ui.horizontal(|ui| {
+            let frame = 1.0 / MEDIA_FRAME_RATE;
+            if ui
+                .button(egui_phosphor::regular::SKIP_BACK)
+                .on_hover_text("Previous frame")
+                .clicked()
+            {
+                self.playing = false;
+                self.step_media(-frame);
+            }
+            let play_icon = if self.playing {
+                egui_phosphor::regular::PAUSE
+            } else {
+                egui_phosphor::regular::PLAY
+            };
+            if ui.button(play_icon).clicked() {
+                self.playing = !self.playing;
+            }
+            if ui
+                .button(egui_phosphor::regular::SKIP_FORWARD)
+                .on_hover_text("Next frame")
+                .clicked()
+            {
+                self.playing = false;
+                self.step_media(frame);
+            }
+            let position = self.playback_position.unwrap_or_default();
+            ui.label(format!(
+                "{:02}:{:06.3}",
+                (position / 60.0).floor(),
+                position % 60.0
+            ));
+            let shown_video = self
+                .media_players
+                .iter()
+                .find(|p| p.shows_video())
+                .map(|p| p.media());
+            for video in self
+                .linked_media
+                .iter()
+                .filter(|p| is_video(p) && Some(p.as_path()) != shown_video)
+            {
+                let file_name = video.file_name().unwrap_or_default().to_string_lossy();
+                if ui
+                    .button(format!("Open {file_name}"))
+                    .on_hover_text("Open the video in an external player")
+                    .clicked()
+                {
+                    if let Err(e) = open_externally(video) {
+                        log::warn!("Could not open {}: {e:?}", video.display());
+                    }
+                }
+            }
+        });
+        let position = self.playback_position.unwrap_or_default();
+        for player in &mut self.media_players {
+            player.update(ui.ctx(), &self.jobs, position, self.playing);
+            if player.shows_video() {
+                player.show(ui);
+            }
+        }
+    }
+
+    /// Sets the playback position and selects the node of the active layer
+    /// at this position, or the base token if there is none.
+    fn seek(&mut self, time: f64, token_idx: usize) {
+        self.playback_position = Some(time);
+        let segment = self
+            .active_layer
+            .as_ref()
+            .and_then(|layer| self.segmentations.get(layer))
+            .and_then(|spans| {
+                spans
+                    .iter()
+                    .find(|t| t.start <= token_idx && token_idx <= t.end)
+            })
+            .map(|t| t.node_name.clone());
+        let selected = segment.or_else(|| self.token.get(token_idx).map(|t| t.node_name.clone()));
+        if let Some(selected) = selected {
+            self.selected_nodes.clear();
+            self.selected_nodes.insert(selected);
+        }
+    }
+
+    fn show_statistics(&mut self, ui: &mut Ui) {
+        let mut toggled_key = None;
+        let mut toggled_namespace = None;
+        let mut moved_key = None;
+        CollapsingHeader::new("Document statistics")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(format!("{} token", self.token.len()));
+                    for (seg_name, seg_token) in self.segmentations.iter() {
+                        ui.separator();
+                        ui.label(format!("{seg_name}: {} spans", seg_token.len()));
+                    }
+                });
+                self.show_datasources(ui);
+                for media in self.linked_media.iter() {
+                    ui.label(format!("Linked media: {}", media.display()));
+                }
+                if !self.label_counts.is_empty() {
+                    ui.label(
+                        "Annotations (click to show or hide, right-click to change their order):",
+                    );
+                    for (ns, keys) in self.labels_by_namespace() {
+                        ui.horizontal_wrapped(|ui| {
+                            let any_visible = keys.iter().any(|k| !self.hidden_labels.contains(k));
+                            let ns_label = if ns.is_empty() {
+                                "(no namespace)"
+                            } else {
+                                ns.as_str()
+                            };
+                            if ui
+                                .selectable_label(any_visible, RichText::new(ns_label).strong())
+                                .on_hover_text("Show or hide all annotations of this namespace")
+                                .clicked()
+                            {
+                                toggled_namespace = Some(ns.clone());
+                            }
+                            for key in keys {
+                                let count = self.label_counts.get(&key).copied().unwrap_or(0);
+                                let is_visible = !self.hidden_labels.contains(&key);
+                                let response = ui.selectable_label(
+                                    is_visible,
+                                    format!("{} ({count})", key_label(&key)),
+                                );
+                                if response.clicked() {
+                                    toggled_key = Some(key.clone());
+                                }
+                                response.context_menu(|ui| {
+                                    if ui.button("Show first").clicked() {
+                                        moved_key = Some((key.clone(), true));
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Show last").clicked() {
+                                        moved_key = Some((key.clone(), false));
+                                        ui.close_menu();
+                                    }
+                                });
+                            }
+                        });
+                    }
+                }
+            });
+        if let Some(key) = toggled_key {
+            self.toggle_label_visibility(key);
+        }
+        if let Some(ns) = toggled_namespace {
+            self.toggle_namespace_visibility(&ns);
+        }
+        if let Some((key, first)) = moved_key {
+            self.move_label(key, first);
+            let label_order = self.label_order.clone();
+            ui.data_mut(|d| d.insert_persisted(egui::Id::new(LABEL_ORDER_ID), label_order));
+        }
+    }
+
+    /// The annotation keys of the document grouped by their namespace. The
+    /// keys of each namespace are sorted by the label order.
+    fn labels_by_namespace(&self) -> BTreeMap<String, Vec<AnnoKey>> {
+        let mut result: BTreeMap<String, Vec<AnnoKey>> = BTreeMap::new();
+        for key in self.label_counts.keys() {
+            result
+                .entry(key.ns.to_string())
+                .or_default()
+                .push(key.clone());
+        }
+        for keys in result.values_mut() {
+            keys.sort_by_key(|k| label_rank(&self.label_order, k));
+        }
+        result
+    }
+
+    /// Show a label before or after all other labels of the token.
+    fn move_label(&mut self, key: AnnoKey, first: bool) {
+        self.label_order.retain(|k| k != &key);
+        if first {
+            self.label_order.insert(0, key);
+        } else {
+            // Keys not in the order are shown last, so make sure the key is
+            // sorted after them
+            for other in self.label_counts.keys() {
+                if !self.label_order.contains(other) && other != &key {
+                    self.label_order.push(other.clone());
+                }
+            }
+            self.label_order.push(key);
+        }
+        self.layout_info.valid = false;
+    }
+
+    /// Hide all labels of the namespace, or show them again if all of them
+    /// are already hidden.
+    fn toggle_namespace_visibility(&mut self, ns: &str) {
+        let keys: Vec<AnnoKey> = self
+            .label_counts
+            .keys()
+            .filter(|k| k.ns == ns)
+            .cloned()
+            .collect();
+        if keys.iter().any(|k| !self.hidden_labels.contains(k)) {
+            self.hidden_labels.extend(keys);
+        } else {
+            for k in keys {
+                self.hidden_labels.remove(&k);
+            }
+        }
+        self.layout_info.valid = false;
+    }
+
+    /// Show the occurrences of the selected token in a panel at the right
+    /// side. Occurrences in other documents are opened by the application.
+    fn show_concordance(&mut self, ui: &mut Ui) {
+        if !self.concordance.open {
+            return;
+        }
+        let selected = if self.selected_nodes.len() == 1 {
+            self.selected_nodes
+                .iter()
+                .next()
+                .and_then(|n| self.node_by_name(n))
+                .cloned()
+        } else {
+            None
+        };
+        self.concordance
+            .update(selected.as_ref(), &self.graph, &self.jobs);
+        let clicked = egui::SidePanel::right(ui.id().with("concordance-panel"))
+            .resizable(true)
+            .default_width(280.0)
+            .show_inside(ui, |ui| self.concordance.show(ui))
+            .inner;
+        if let Some(occurrence) = clicked {
+            if occurrence.document_name == self.parent_name {
+                self.selected_nodes.clear();
+                self.selected_nodes.insert(occurrence.node_name.clone());
+                self.scroll_to_node(&occurrence.node_name);
+            } else {
+                self.navigation_request = Some((occurrence.document, occurrence.token_index));
+            }
+        }
+    }
+
+    fn show_edge_annotations(&mut self, ui: &mut Ui) {
+        let selected_node = if self.selected_nodes.len() == 1 {
+            self.selected_nodes.iter().next().cloned()
+        } else {
+            None
+        };
+        if self.edge_annotations.node_name != selected_node {
+            let graph = self.graph.read();
+            self.edge_annotations.load(&graph, selected_node.as_deref());
+        }
+        if selected_node.is_none() {
+            return;
+        }
+        let mut changes = Vec::new();
+        CollapsingHeader::new("Edges of selection")
+            .default_open(false)
+            .show(ui, |ui| {
+                changes = self.edge_annotations.show(ui, &self.layer_settings);
+            });
+        for change in changes {
+            self.pending_actions.push(EditorActions::SetEdgeLabel {
+                edge: change.edge,
+                key: change.key,
+                value: change.value,
+            });
+        }
+    }
+
+    fn show_datasources(&mut self, ui: &mut Ui) {
+        let mut deleted = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Texts:");
+            for ds in self.datasources.iter() {
+                ui.label(format!("{} ({} nodes)", ds.short_name(), ds.node_count))
+                    .on_hover_text(&ds.node_name);
+                if ds.node_count == 0
+                    && ui
+                        .small_button(egui_phosphor::regular::TRASH)
+                        .on_hover_text("Delete empty text")
+                        .clicked()
+                {
+                    deleted = Some(ds.node_name.clone());
+                }
+            }
+            if ui
+                .small_button(egui_phosphor::regular::PLUS)
+                .on_hover_text("Add a new text to the document")
+                .clicked()
+            {
+                self.pending_actions.push(EditorActions::AddDatasource);
+            }
+        });
+        if let Some(node_name) = deleted {
+            self.datasources.retain(|ds| ds.node_name != node_name);
+            self.pending_actions
+                .push(EditorActions::DeleteNode { node_name });
+        }
+    }
+
+    fn toggle_label_visibility(&mut self, key: AnnoKey) {
+        if !self.hidden_labels.remove(&key) {
+            self.hidden_labels.insert(key);
+        }
+        // The token sizes change when labels are hidden or shown
+        self.layout_info.valid = false;
+    }
+
+    fn select_range(&mut self, token_position: usize) {
+        // Mark a range of token, find a suitable token as start for the range first
+        let mut selected_token_indices: BTreeSet<_> = self
+            .selected_nodes
+            .iter()
+            .filter_map(|selected_node| self.token_index_by_name.get(selected_node))
+            .copied()
+            .collect();
+        let after = selected_token_indices.split_off(&token_position);
+
+        if let Some(after) = after.first() {
+            for i in token_position..*after {
+                self.selected_nodes.insert(self.token[i].node_name.clone());
+            }
+        } else if let Some(before) = selected_token_indices.last() {
+            for i in *before..token_position {
+                self.selected_nodes.insert(self.token[i].node_name.clone());
+            }
+        }
+        self.selected_nodes
+            .insert(self.token[token_position].node_name.clone());
+    }
+
+    /// Replaces the current selection with all token of the given layer that
+    /// match the pattern and returns the number of selected nodes.
+    ///
+    /// - `segmentation` The segmentation layer to search in or `None` for the
+    ///   base token.
+    fn select_by_pattern(&mut self, pattern: &TokenPattern, segmentation: Option<&str>) -> usize {
+        let candidates = if let Some(segmentation) = segmentation {
+            self.segmentations
+                .get(segmentation)
+                .map(|t| t.as_slice())
+                .unwrap_or_default()
+        } else {
+            self.token.as_slice()
+        };
+        self.selected_nodes = candidates
+            .iter()
+            .filter(|t| pattern.matches(t))
+            .map(|t| t.node_name.clone())
+            .collect();
+        self.currently_edited_node = None;
+        self.selected_nodes.len()
+    }
+
+    fn show_pattern_selection_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.pattern_selection.open;
+        let mut apply = false;
+        egui::Window::new("Select by pattern")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Use a regular expression for the token value, or an annotation pattern like pos=\"NN\" or ns:lemma=/be.*/.");
+                let response = TextEdit::singleline(&mut self.pattern_selection.pattern)
+                    .hint_text("Pattern")
+                    .id(egui::Id::from("select-by-pattern"))
+                    .ui(ui);
+                if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    apply = true;
+                }
+                let layer_label = |segmentation: &Option<String>| {
+                    segmentation
+                        .clone()
+                        .unwrap_or_else(|| "Base token".to_string())
+                };
+                ComboBox::from_label("Layer")
+                    .selected_text(layer_label(&self.pattern_selection.segmentation))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut self.pattern_selection.segmentation,
+                            None,
+                            layer_label(&None),
+                        );
+                        for seg_name in self.segmentations.keys() {
+                            ui.selectable_value(
+                                &mut self.pattern_selection.segmentation,
+                                Some(seg_name.clone()),
+                                seg_name,
+                            );
+                        }
+                    });
+                if ui.button("Select matching").clicked() {
+                    apply = true;
+                }
+                match &self.pattern_selection.message {
+                    Some(Ok(count)) => {
+                        ui.label(format!("Selected {count} nodes"));
+                    }
+                    Some(Err(msg)) => {
+                        ui.label(RichText::new(msg).color(Color32::RED));
+                    }
+                    None => {}
+                }
+            });
+        if apply {
+            let result = TokenPattern::parse(&self.pattern_selection.pattern)
+                .map(|pattern| {
+                    let segmentation = self.pattern_selection.segmentation.clone();
+                    self.select_by_pattern(&pattern, segmentation.as_deref())
+                })
+                .map_err(|e| e.to_string());
+            self.pattern_selection.message = Some(result);
+        }
+        self.pattern_selection.open = open;
+    }
+
+    /// The base token after which new token are inserted, which is the last
+    /// selected base token.
+    fn insertion_point(&self) -> Option<String> {
+        self.selected_nodes
+            .iter()
+            .filter_map(|n| self.token_index_by_name.get(n))
+            .max()
+            .map(|idx| self.token[*idx].node_name.clone())
+    }
+
+    /// Tokenizes the given text and inserts the new token after the last
+    /// selected base token.
+    fn insert_text_after_selection(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        if let Some(after_token) = self.insertion_point() {
+            self.pending_actions.push(EditorActions::InsertToken {
+                after_token: Some(after_token),
+                text: text.to_string(),
+                tokenizer: self.tokenizer.clone(),
+            });
+            self.apply_pending_updates_automatically();
+        }
+    }
+
+    /// Tokenizes the given text and creates the first token and the ordering
+    /// chain of an empty document.
+    fn create_initial_token(&mut self, text: &str) {
+        if text.trim().is_empty() || !self.token.is_empty() {
+            return;
+        }
+        self.pending_actions.push(EditorActions::InsertToken {
+            after_token: None,
+            text: text.to_string(),
+            tokenizer: self.tokenizer.clone(),
+        });
+        self.apply_pending_updates_automatically();
+    }
+
+    /// Adds the empty segmentation layers, e.g. of the template of a new
+    /// document, without changing the active layer.
+    pub(crate) fn add_empty_layers(&mut self, names: &[String]) {
+        for name in names {
+            let name = name.trim();
+            if self.validate_layer_name(name).is_ok() {
+                self.segmentations.insert(name.to_string(), Vec::new());
+            }
+        }
+    }
+
+    fn show_empty_document(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.label("This document has no token yet.");
+        let mut create = false;
+        let align = self.text_align();
+        ui.horizontal(|ui| {
+            let response = TextEdit::singleline(&mut self.initial_text)
+                .horizontal_align(align)
+                .hint_text("Start typing to create token")
+                .id(egui::Id::from("initial-text"))
+                .desired_width(400.0)
+                .ui(ui);
+            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                create = true;
+            }
+            if ui
+                .add_enabled(
+                    !self.initial_text.trim().is_empty(),
+                    Button::new("Create token"),
+                )
+                .clicked()
+            {
+                create = true;
+            }
+        });
+        self.show_create_timeline(ui);
+        if create {
+            let text = std::mem::take(&mut self.initial_text);
+            self.create_initial_token(&text);
+        }
+    }
+
+    /// The first selected base token, if it is a valid position to split the
+    /// document at.
+    fn split_position(&self) -> Option<String> {
+        self.selected_nodes
+            .iter()
+            .filter_map(|n| self.token_index_by_name.get(n))
+            .min()
+            .filter(|idx| **idx > 0)
+            .map(|idx| self.token[*idx].node_name.clone())
+    }
+
+    /// Moves the first selected token and all following token into a new
+    /// document.
+    fn split_document_at_selection(&mut self) {
+        if let Some(first_token_of_new_document) = self.split_position() {
+            self.selected_nodes.clear();
+            self.pending_actions.push(EditorActions::SplitDocument {
+                first_token_of_new_document,
+            });
+            self.apply_pending_updates_automatically();
+        }
+    }
+
+    fn show_insert_text_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.insert_text.open;
+        let align = self.text_align();
+        let mut apply = false;
+        egui::Window::new("Insert text")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("The text is split into token and inserted after the selected token.");
+                TextEdit::multiline(&mut self.insert_text.text)
+                    .horizontal_align(align)
+                    .hint_text("Text")
+                    .id(egui::Id::from("insert-text"))
+                    .ui(ui);
+                if ui
+                    .add_enabled(self.insertion_point().is_some(), Button::new("Insert"))
+                    .clicked()
+                {
+                    apply = true;
+                }
+            });
+        if apply {
+            let text = std::mem::take(&mut self.insert_text.text);
+            self.insert_text_after_selection(&text);
+            open = false;
+        }
+        self.insert_text.open = open;
+    }
+
+    /// The number keys that add a span on the segmentation layer with the
+    /// given (1-based) index.
+    fn layer_shortcuts(&self) -> Vec<(usize, String, KeyboardShortcut)> {
+        self.segmentations
+            .keys()
+            .take(self.segmentations.len().saturating_sub(1))
+            .enumerate()
+            .filter_map(|(idx, layer)| {
+                let layer_idx = idx + 1;
+                Key::from_name(&layer_idx.to_string()).map(|key| {
+                    (
+                        layer_idx,
+                        layer.clone(),
+                        KeyboardShortcut::new(Modifiers::NONE, key),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Adds an empty segmentation node that spans the currently selected token.
+    ///
+    /// - `layer_idx` The segmentation layer to add the new node to. **Starts with 1.**
+    fn add_segmentation_for_selection(&mut self, layer_idx: usize) {
+        if let Some(seg_name) = self
+            .segmentations
+            .keys()
+            .nth(layer_idx.saturating_sub(1))
+            .cloned()
+        {
+            self.active_layer = Some(seg_name.clone());
+            self.add_segmentation_to_layer(&seg_name);
+        }
+    }
+
+    fn add_segmentation_to_layer(&mut self, seg_name: &str) {
+        if !self.selected_nodes.is_empty() {
+            // Apply changes to internal data model
+            let mut selected_token_indices: Vec<_> = self
+                .selected_nodes
+                .iter()
+                .filter_map(|n| self.token_index_by_name.get(n))
+                .copied()
+                .collect();
+            selected_token_indices.sort();
+            if let (Some(start), Some(end)) = (
+                selected_token_indices.first(),
+                selected_token_indices.last(),
+            ) {
+                // Do not add the same span twice, e.g. when the shortcut is
+                // pressed repeatedly
+                let already_covered = self
+                    .segmentations
+                    .get(seg_name)
+                    .is_some_and(|spans| spans.iter().any(|t| t.start == *start && t.end == *end));
+                if already_covered {
+                    return;
+                }
+            }
+            {
+                let graph = self.graph.read();
+                if let Ok(tok_helper) = TokenHelper::new(&graph) {
+                    // Schedule an update of the underlaying graph
+                    let selected_token: HashSet<_> = self
+                        .selected_nodes
+                        .iter()
+                        .filter(|node_name| {
+                            if let Ok(Some(node_id)) =
+                                graph.get_node_annos().get_node_id_from_name(node_name)
+                            {
+                                tok_helper.is_token(node_id).unwrap_or(false)
+                            } else {
+                                false
+                            }
+                        })
+                        .cloned()
+                        .collect();
+
+                    self.pending_actions
+                        .push(EditorActions::AddSegmentationSpan {
+                            segmentation: seg_name.to_string(),
+                            selected_token,
+                            value: String::new(),
+                        });
+                }
+            }
+            self.apply_pending_updates_automatically();
+        }
+    }
+
+    /// The layer and the span if exactly one segmentation node is selected.
+    fn selected_span(&self) -> Option<(String, Token)> {
+        if self.selected_nodes.len() != 1 {
+            return None;
+        }
+        let node_name = self.selected_nodes.iter().next()?;
+        self.segmentations.iter().find_map(|(layer, spans)| {
+            spans
+                .iter()
+                .find(|t| &t.node_name == node_name)
+                .map(|t| (layer.clone(), t.clone()))
+        })
+    }
+
+    /// Adds a span to the target layer that covers the same token as the
+    /// selected span. Its value is only copied if `duplicate_with_value` is
+    /// set.
+    fn duplicate_selected_span(&mut self, target_layer: &str) {
+        let Some((layer, span)) = self.selected_span() else {
+            return;
+        };
+        let already_covered = self.segmentations.get(target_layer).is_some_and(|spans| {
+            spans
+                .iter()
+                .any(|t| t.start == span.start && t.end == span.end)
+        });
+        if layer == target_layer || already_covered {
+            return;
+        }
+        let selected_token = self
+            .token
+            .get(span.start..=span.end)
+            .unwrap_or_default()
+            .iter()
+            .map(|t| t.node_name.clone())
+            .collect();
+        let value = if self.duplicate_with_value {
+            span.labels.get(&TOKEN_KEY).cloned().unwrap_or_default()
+        } else {
+            String::new()
+        };
+        self.pending_actions
+            .push(EditorActions::AddSegmentationSpan {
+                segmentation: target_layer.to_string(),
+                selected_token,
+                value,
+            });
+        self.apply_pending_updates_automatically();
+    }
+
+    /// A description of the kind of the node, e.g. the segmentation layer.
+    fn node_kind(&self, node_name: &str) -> String {
+        if self.token_index_by_name.contains_key(node_name) {
+            "token".to_string()
+        } else if let Some(layer) = self
+            .segmentations
+            .iter()
+            .find(|(_, spans)| spans.iter().any(|t| t.node_name == node_name))
+            .map(|(layer, _)| layer)
+        {
+            format!("spans of layer \"{layer}\"")
+        } else if self.spans.iter().any(|t| t.node_name == node_name) {
+            "nested spans".to_string()
+        } else {
+            "other nodes".to_string()
+        }
+    }
+
+    /// Number of nodes the deletion removes, grouped by their kind.
+    fn affected_nodes(&self, deletion: &Deletion) -> BTreeMap<String, usize> {
+        let mut result = BTreeMap::new();
+        let node_names: Vec<&String> = match deletion {
+            Deletion::SelectedNodes => self.selected_nodes.iter().collect(),
+            Deletion::DuplicateSpans(resolved) => resolved
+                .iter()
+                .flat_map(|(d, _)| d.node_names.iter().skip(1))
+                .collect(),
+        };
+        for node_name in node_names {
+            *result.entry(self.node_kind(node_name)).or_default() += 1;
+        }
+        result
+    }
+
+    /// Execute the deletion or ask the user to confirm it first.
+    fn request_deletion(&mut self, deletion: Deletion) {
+        if self.confirm_deletion {
+            let affected = self.affected_nodes(&deletion);
+            if !affected.is_empty() {
+                self.deletion_confirmation = Some(DeletionConfirmation {
+                    deletion,
+                    affected,
+                    dont_ask_again: false,
+                });
+                return;
+            }
+        }
+        self.execute_deletion(deletion);
+    }
+
+    fn execute_deletion(&mut self, deletion: Deletion) {
+        match deletion {
+            Deletion::SelectedNodes => self.delete_selected_nodes(),
+            Deletion::DuplicateSpans(resolved) => {
+                for (duplicates, merge) in resolved {
+                    self.resolve_duplicate_spans(&duplicates, merge);
+                }
+            }
+        }
+    }
+
+    fn show_deletion_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(confirmation) = &mut self.deletion_confirmation else {
+            return;
+        };
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Confirm deletion")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                let total: usize = confirmation.affected.values().sum();
+                ui.label(format!("Delete {total} node(s)?"));
+                for (kind, count) in confirmation.affected.iter() {
+                    ui.label(format!("• {count} {kind}"));
+                }
+                ui.checkbox(&mut confirmation.dont_ask_again, "Don't ask again");
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(RichText::new("Delete").color(ui.visuals().error_fg_color))
+                        .clicked()
+                    {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            cancelled = true;
+        }
+        if confirmed {
+            if let Some(confirmation) = self.deletion_confirmation.take() {
+                if confirmation.dont_ask_again {
+                    self.confirm_deletion = false;
+                    self.confirmation_disabled = true;
+                }
+                self.execute_deletion(confirmation.deletion);
+            }
+        } else if cancelled {
+            self.deletion_confirmation = None;
+        }
+    }
+
+    /// The token or span with the node name.
+    fn node_by_name(&self, node_name: &str) -> Option<&Token> {
+        self.token_index_by_name
+            .get(node_name)
+            .and_then(|idx| self.token.get(*idx))
+            .or_else(|| {
+                self.segmentations
+                    .values()
+                    .flatten()
+                    .chain(self.spans.iter())
+                    .find(|t| t.node_name == node_name)
+            })
+    }
+
+    /// Execute the action that has been chosen in the context menu of a node.
+    /// Actions on a node that is not selected replace the selection with it.
+    fn handle_context_menu_request(&mut self) {
+        let Some((node_name, action)) = self.context_menu_request.take() else {
+            return;
+        };
+        if action != ContextMenuAction::Inspect && !self.selected_nodes.contains(&node_name) {
+            self.selected_nodes.clear();
+            self.selected_nodes.insert(node_name.clone());
+        }
+        match action {
+            ContextMenuAction::EditValue => {
+                if let Some(t) = self.node_by_name(&node_name) {
+                    self.current_edited_value =
+                        t.labels.get(&TOKEN_KEY).cloned().unwrap_or_default();
+                    self.selected_nodes.clear();
+                    self.selected_nodes.insert(node_name.clone());
+                    self.currently_edited_node = Some(node_name);
+                    self.focus_edited_node = true;
+                }
+            }
+            ContextMenuAction::AddAnnotation => {
+                self.add_annotation_dialog = AddAnnotationDialog {
+                    node_name: Some(node_name),
+                    key: new_annotation_key(&self.default_namespace),
+                    ..Default::default()
+                };
+            }
+            ContextMenuAction::AddSpan => self.add_segmentation_to_active_layer(),
+            ContextMenuAction::Delete => self.request_deletion(Deletion::SelectedNodes),
+            ContextMenuAction::Inspect => self.inspected_node = Some(node_name),
+        }
+    }
+
+    fn show_add_annotation_dialog(&mut self, ctx: &egui::Context) {
+        let Some(node_name) = self.add_annotation_dialog.node_name.clone() else {
+            return;
+        };
+        let mut open = true;
+        let mut apply = false;
+        let value_type = self
+            .layer_settings
+            .value_type(&parse_key(&self.add_annotation_dialog.key))
+            .cloned();
+        egui::Window::new("Add annotation")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let dialog = &mut self.add_annotation_dialog;
+                ui.label(RichText::new(&node_name).weak());
+                egui::Grid::new("add-annotation-grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Key");
+                        TextEdit::singleline(&mut dialog.key)
+                            .hint_text("ns:name")
+                            .ui(ui);
+                        ui.end_row();
+                        ui.label("Value");
+                        value_editor(ui, value_type.as_ref(), &mut dialog.value);
+                        ui.end_row();
+                    });
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                if ui
+                    .add_enabled(!dialog.key.trim().is_empty(), Button::new("Add"))
+                    .clicked()
+                {
+                    apply = true;
+                }
+            });
+        if apply {
+            let key = parse_key(&self.add_annotation_dialog.key);
+            let value = self.add_annotation_dialog.value.clone();
+            match self.add_annotation(&node_name, key, value) {
+                Ok(()) => open = false,
+                Err(e) => self.add_annotation_dialog.error = Some(e.to_string()),
+            }
+        }
+        if !open {
+            self.add_annotation_dialog = AddAnnotationDialog::default();
+        }
+    }
+
+    /// Show the node name, the covered token and all annotations of the
+    /// inspected node in a window.
+    fn show_node_details(&mut self, ctx: &egui::Context) {
+        let Some(node_name) = &self.inspected_node else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Node details")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| match self.node_by_name(node_name) {
+                Some(t) => token_details(ui, t),
+                None => {
+                    ui.label("The node is not part of the document anymore.");
+                }
+            });
+        if !open {
+            self.inspected_node = None;
+        }
+    }
+
+    /// Replace the selection with the given nodes. Nodes that are not part
+    /// of the document are ignored. Returns the number of selected nodes.
+    pub(crate) fn select<I, S>(&mut self, node_names: I) -> usize
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.selected_nodes.clear();
+        for node_name in node_names {
+            let node_name = node_name.into();
+            if self.node_position(&node_name).is_some() {
+                self.selected_nodes.insert(node_name);
+            }
+        }
+        self.selected_nodes.len()
+    }
+
+    /// Replace the selection with the base token in the range of token
+    /// indexes.
+    pub(crate) fn select_token_range(&mut self, range: RangeInclusive<usize>) -> usize {
+        let token: Vec<String> = self
+            .token
+            .get(range)
+            .unwrap_or_default()
+            .iter()
+            .map(|t| t.node_name.clone())
+            .collect();
+        self.select(token)
+    }
+
+    /// Index of the first selected base token.
+    fn first_selected_token(&self) -> Option<usize> {
+        self.selected_nodes
+            .iter()
+            .filter_map(|n| self.token_index_by_name.get(n))
+            .min()
+            .copied()
+    }
+
+    /// The span of the segmentation layer that covers the base token.
+    fn covering_span(&self, layer: &str, token_idx: usize) -> Option<&Token> {
+        self.segmentations.get(layer).and_then(|spans| {
+            spans
+                .iter()
+                .find(|t| t.start <= token_idx && token_idx <= t.end)
+        })
+    }
+
+    /// Select the span of the active layer that covers the base token, e.g.
+    /// the word a token belongs to. Returns whether there is such a span.
+    pub(crate) fn select_covering_span(&mut self, token_idx: usize) -> bool {
+        let span = self
+            .active_layer
+            .as_ref()
+            .and_then(|layer| self.covering_span(layer, token_idx))
+            .map(|t| t.node_name.clone());
+        if let Some(span) = span {
+            self.select([span]);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Select the sentence that contains the base token. If there is a
+    /// segmentation layer or a nested span annotation for sentences, the
+    /// smallest sentence span covering the token is selected. Otherwise, the
+    /// base token between the sentence-ending punctuation before and after
+    /// the token are selected. Returns the number of selected nodes.
+    pub(crate) fn select_sentence(&mut self, token_idx: usize) -> usize {
+        let is_sentence_key = |key: &AnnoKey| SENTENCE_NAMES.contains(&key.name.as_str());
+        let sentence_span = self
+            .segmentations
+            .iter()
+            .filter(|(layer, _)| SENTENCE_NAMES.contains(&layer.as_str()))
+            .flat_map(|(_, spans)| spans.iter())
+            .chain(
+                self.spans
+                    .iter()
+                    .filter(|t| t.labels.keys().any(is_sentence_key)),
+            )
+            .filter(|t| t.start <= token_idx && token_idx <= t.end)
+            .min_by_key(|t| t.end - t.start)
+            .map(|t| t.node_name.clone());
+        if let Some(span) = sentence_span {
+            return self.select([span]);
+        }
+
+        let ends_sentence = |t: &Token| {
+            t.labels
+                .get(&TOKEN_KEY)
+                .is_some_and(|v| v.ends_with(SENTENCE_END))
+        };
+        let start = self.token[..token_idx]
+            .iter()
+            .rposition(ends_sentence)
+            .map_or(0, |idx| idx + 1);
+        let end = self.token[token_idx..]
+            .iter()
+            .position(ends_sentence)
+            .map_or(self.token.len() - 1, |idx| token_idx + idx);
+        self.select_token_range(start..=end)
+    }
+
+    /// Index of the first base token covered by the node, if the node is
+    /// part of the document.
+    fn node_position(&self, node_name: &str) -> Option<usize> {
+        self.token_index_by_name
+            .get(node_name)
+            .copied()
+            .or_else(|| {
+                self.segmentations
+                    .values()
+                    .flatten()
+                    .chain(self.spans.iter())
+                    .find(|t| t.node_name == node_name)
+                    .map(|t| t.start)
+            })
+    }
+
+    /// Change the value of a segmentation node, like editing it in the user
+    /// interface does.
+    pub(crate) fn edit_value(&mut self, node_name: &str, new_value: &str) -> Result<()> {
+        let span = self
+            .segmentations
+            .values_mut()
+            .flatten()
+            .find(|t| t.node_name == node_name)
+            .with_context(|| format!("Unknown segmentation node {node_name}"))?;
+        if let Some(action) = modify_value(span, new_value.to_string()) {
+            self.layout_info.valid = false;
+            self.pending_actions.push(action);
+            self.apply_pending_updates_automatically();
+        }
+        Ok(())
+    }
+
+    /// Add an annotation to a token or span. Annotations in the ANNIS
+    /// namespace can not be added, since they define the structure of the
+    /// document.
+    pub(crate) fn add_annotation(
+        &mut self,
+        node_name: &str,
+        key: AnnoKey,
+        value: String,
+    ) -> Result<()> {
+        if key.name.is_empty() {
+            bail!("The annotation name must not be empty");
+        }
+        if key.ns == ANNIS_NS {
+            bail!("Annotations in the \"{ANNIS_NS}\" namespace can not be added");
+        }
+        self.layer_settings.validate_value(&key, &value)?;
+        let node = self
+            .token
+            .iter_mut()
+            .chain(self.segmentations.values_mut().flatten())
+            .chain(self.spans.iter_mut())
+            .find(|t| t.node_name == node_name)
+            .with_context(|| format!("Unknown node {node_name}"))?;
+        if node.labels.insert(key.clone(), value.clone()).is_none() {
+            *self.label_counts.entry(key.clone()).or_default() += 1;
+        }
+        self.pending_actions.push(EditorActions::AddNodeLabels {
+            node_name: node_name.to_string(),
+            labels: BTreeMap::from([(key, value)]),
+        });
+        self.layout_info.valid = false;
+        self.apply_pending_updates_automatically();
+        Ok(())
+    }
+
+    /// Add an empty span on the segmentation layer that covers the base
+    /// token in the range, like the shortcut for adding spans does.
+    pub(crate) fn add_span(&mut self, layer: &str, range: RangeInclusive<usize>) -> Result<()> {
+        if !self.segmentations.contains_key(layer) {
+            bail!("Unknown segmentation layer {layer}");
+        }
+        if *range.end() >= self.token.len() || range.is_empty() {
+            bail!("Invalid token range {range:?}");
+        }
+        self.select_token_range(range);
+        self.add_segmentation_to_layer(layer);
+        Ok(())
+    }
+
+    /// Delete the given nodes without asking for confirmation.
+    pub(crate) fn delete<I, S>(&mut self, node_names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if self.select(node_names) > 0 {
+            self.delete_selected_nodes();
+        }
+    }
+
+    fn delete_selected_nodes(&mut self) {
+        self.layout_info.valid = false;
+        for (_, segmentation_token) in self.segmentations.iter_mut() {
+            segmentation_token.retain(|t| !self.selected_nodes.contains(&t.node_name));
+        }
+        self.spans
+            .retain(|t| !self.selected_nodes.contains(&t.node_name));
+        for n in self.selected_nodes.iter() {
+            self.pending_actions.push(EditorActions::DeleteNode {
+                node_name: n.clone(),
+            });
+        }
+        self.selected_nodes.clear();
+        self.apply_pending_updates_automatically();
+    }
+}
+
+impl Editor for DocumentEditor {
+    fn show(&mut self, ui: &mut Ui) {
+        self.show_toolbar(ui);
+        if self.token.is_empty() {
+            self.show_statistics(ui);
+            self.show_empty_document(ui);
+            self.apply_pending_updates_automatically();
+            return;
+        }
+        self.update_layout_selection();
+        if self.ordering_broken {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(egui_phosphor::regular::WARNING).color(Color32::RED));
+                ui.label("The order of the token in this document is broken. The token might be shown in the wrong order.");
+            });
+        }
+        self.show_concordance(ui);
+        self.show_time_editor(ui);
+        self.show_media_panel(ui);
+        self.show_statistics(ui);
+        self.show_edge_annotations(ui);
+        let ui_style = ui.style().clone();
+        let mut current_span_offset: f32 = 0.0;
+
+        // Remember the location of each token, so we can paint the spans with
+        // the same range later
+        let mut token_offset_to_rect = vec![None; self.token.len()];
+        // The vertical range of the token row and each segmentation layer
+        let mut layer_rows = Vec::new();
+        let scroll_area = ScrollArea::horizontal().show_viewport(ui, |ui, visible_rect| {
+            if self.layout_info.first_frame {
+                ui.scroll_to_cursor(Some(egui::Align::LEFT));
+            }
+            // If we already calculated the token positions once, only render
+            // the token and their covering spans that are currently displayed
+            let mut first_visible_token: usize = 0;
+            let last_token_index = self.token.len() - 1;
+            let mut last_visible_token: usize = last_token_index;
+            let visible_range = visible_rect.x_range().min..visible_rect.x_range().max;
+            if self.layout_info.valid {
+                first_visible_token = self
+                    .layout_info
+                    .token_offset_start
+                    .partition_point(|x| {
+                        x.partial_cmp(&visible_range.start)
+                            .unwrap_or(Ordering::Equal)
+                            .is_lt()
+                    })
+                    .saturating_sub(1);
+                last_visible_token = self
+                    .layout_info
+                    .token_offset_end
+                    .partition_point(|x| {
+                        x.partial_cmp(&visible_range.end)
+                            .unwrap_or(Ordering::Equal)
+                            .is_lt()
+                    })
+                    .saturating_add(1);
+            }
+            if last_visible_token > last_token_index {
+                last_visible_token = last_token_index
+            }
+            self.first_visible_token = if self.layout_info.valid {
+                self.layout_info
+                    .token_offset_end
+                    .partition_point(|x| *x <= visible_range.start)
+                    .min(last_token_index)
+            } else {
+                0
+            };
+
+            let nested_spans_top = ui.cursor().top();
+            ui.add_space(self.nested_spans_height());
+
+            let waveform_top = ui.cursor().top();
+            let shows_waveform = self.shows_waveform();
+            if shows_waveform {
+                ui.add_space(WAVEFORM_HEIGHT);
+            }
+
+            let mut line_breaks = Vec::new();
+            let token_row_top = ui.cursor().top();
+            let can_add_span = self.active_layer.is_some();
+            ui.horizontal(|ui| {
+                if self.whitespace_spacing {
+                    // The gaps between the token are added explicitly
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                }
+                if self.layout_info.valid && first_visible_token > 0 {
+                    // Add the space needed for the non-rendered token at the beginning
+                    ui.add_space(self.layout_info.token_offset_end[first_visible_token - 1]);
+                }
+
+                for token_position in first_visible_token..=last_visible_token {
+                    if self.whitespace_spacing && token_position > 0 {
+                        let (gap, line_break) = whitespace_gap(
+                            &self.token[token_position - 1],
+                            &self.token[token_position],
+                        );
+                        let (rect, _) = ui.allocate_exact_size(Vec2::new(gap, 0.0), Sense::hover());
+                        if line_break {
+                            line_breaks.push(rect.center().x);
+                        }
+                    }
+                    let token_node_name = &self.token[token_position].node_name;
+                    let minimal_token_width = self
+                        .layout_info
+                        .min_token_width
+                        .get(self.token[token_position].start)
+                        .copied();
+                    let token_start = self.token[token_position].start;
+                    let opacity = self.token_filter.opacity(&self.token[token_position]);
+                    let response = TokenEditor::with_min_width(
                         &self.token[token_position],
                         self.selected_nodes.contains(token_node_name),
                         minimal_token_width,
                     )
+                    .hide_labels(&self.hidden_labels)
+                    .label_order(&self.label_order)
+                    .header(self.token_header)
+                    .max_labels(self.label_limit())
+                    .highlight(self.is_at_playback_position(&self.token[token_position]))
+                    .opacity(opacity)
+                    .painted(self.painted_token)
+                    .details_tooltip(self.token_tooltips)
+                    .show_whitespace(!self.whitespace_spacing)
                     .ui(ui);
-                    if response.clicked() {
+                    if response.clicked() && opacity > 0.0 {
                         let shift_pressed = ui.ctx().input(|i| i.modifiers.shift_only());
                         if shift_pressed {
                             self.select_range(token_position);
@@ -408,6 +2563,22 @@ impl Editor for DocumentEditor {
                             self.selected_nodes.insert(token_node_name.clone());
                         }
                     }
+                    if opacity > 0.0 {
+                        if response.triple_clicked() {
+                            self.select_sentence(token_position);
+                        } else if response.double_clicked() {
+                            self.select_covering_span(token_position);
+                        }
+                    }
+                    let token_node_name = &self.token[token_position].node_name;
+                    let mut context_action = None;
+                    response.context_menu(|ui| {
+                        context_action =
+                            node_context_menu(ui, token_node_name, false, can_add_span);
+                    });
+                    if let Some(action) = context_action {
+                        self.context_menu_request = Some((token_node_name.clone(), action));
+                    }
                     let token_rect = response.rect;
                     current_span_offset = current_span_offset.max(token_rect.bottom());
                     token_offset_to_rect[token_start] = Some(token_rect);
@@ -429,15 +2600,54 @@ impl Editor for DocumentEditor {
                     }
                 }
             });
+            for x in line_breaks {
+                ui.painter().vline(
+                    x,
+                    Rangef::new(token_row_top, current_span_offset),
+                    Stroke::new(1.0, ui.visuals().weak_text_color()),
+                );
+            }
+            layer_rows.push((
+                "tok".to_string(),
+                Rangef::new(token_row_top, current_span_offset),
+            ));
+            self.show_nested_spans(ui, nested_spans_top, &token_offset_to_rect);
+            if shows_waveform {
+                self.show_waveform(ui, waveform_top, &token_offset_to_rect);
+            }
             current_span_offset += ui_style.spacing.item_spacing.y;
 
             if self.layout_info.min_token_width.is_empty() {
                 self.layout_info.min_token_width = vec![0.0; self.token.len()];
             }
 
-            ui.vertical(|ui| {
-                self.show_segmentation_layers(ui, &token_offset_to_rect, current_span_offset)
+            let segmentation_rows = ui.vertical(|ui| {
+                self.show_segmentation_layers(
+                    ui,
+                    &token_offset_to_rect,
+                    first_visible_token..=last_visible_token,
+                    current_span_offset,
+                )
             });
+            layer_rows.extend(segmentation_rows.inner);
+
+            if let Some((index, align)) = self
+                .scroll_request
+                .filter(|_| !self.layout_info.first_frame)
+            {
+                let start = self.layout_info.token_offset_start[index];
+                let end = self.layout_info.token_offset_end[index];
+                if end > start {
+                    // Positive deltas move the content to the right
+                    let target = if align == egui::Align::Min {
+                        start
+                    } else {
+                        (start + end - visible_rect.width()) / 2.0
+                    };
+                    ui.scroll_with_delta(Vec2::new(visible_range.start - target, 0.0));
+                    self.scroll_request = None;
+                }
+            }
 
             // Add additional space for the scrollbar
             ui.add_space(10.0);
@@ -445,10 +2655,24 @@ impl Editor for DocumentEditor {
             if visible_range.start == 0.0 && !self.layout_info.min_token_width.is_empty() {
                 self.layout_info.valid = true;
             }
-            self.apply_pending_updates_for_editor();
+            self.apply_pending_updates_automatically();
         });
+        self.view_rect = Some(scroll_area.inner_rect);
+        if self.layer_names && !self.segmentations.is_empty() {
+            self.show_layer_names(ui, scroll_area.inner_rect, &layer_rows);
+        }
+        self.export_png_from_screenshot(ui.ctx());
 
         self.layout_info.first_frame = false;
+        self.show_pattern_selection_dialog(ui.ctx());
+        self.show_insert_text_dialog(ui.ctx());
+        self.show_insert_timeline_dialog(ui.ctx());
+        self.show_new_layer_dialog(ui.ctx());
+        self.show_duplicate_spans_dialog(ui.ctx());
+        self.show_deletion_confirmation(ui.ctx());
+        self.handle_context_menu_request();
+        self.show_add_annotation_dialog(ui.ctx());
+        self.show_node_details(ui.ctx());
     }
 
     fn any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -459,77 +2683,446 @@ impl Editor for DocumentEditor {
         !self.pending_actions.is_empty()
     }
 
-    fn apply_pending_updates_for_editor(&mut self) {
+    fn pending_update_count(&self) -> usize {
+        self.pending_actions.len()
+    }
+
+    fn set_manual_commit(&mut self, manual_commit: bool) {
+        self.manual_commit = manual_commit;
+    }
+
+    fn set_tokenizer(&mut self, tokenizer: TokenizerKind) {
+        self.tokenizer = tokenizer;
+    }
+
+    fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    fn set_default_namespace(&mut self, namespace: String) {
+        self.edge_annotations.default_namespace = namespace.clone();
+        self.default_namespace = namespace;
+    }
+
+    fn set_confirm_deletion(&mut self, confirm: bool) {
+        self.confirm_deletion = confirm;
+    }
+
+    fn set_token_tooltips(&mut self, show: bool) {
+        self.token_tooltips = show;
+    }
+
+    fn take_disabled_confirmation(&mut self) -> bool {
+        std::mem::take(&mut self.confirmation_disabled)
+    }
+
+    fn take_navigation_request(&mut self) -> Option<(NodeID, usize)> {
+        self.navigation_request.take()
+    }
+
+    fn discard_pending_updates(&mut self) {
+        self.pending_actions.clear();
+    }
+
+    fn apply_pending_updates_in_group(&mut self, group: Option<UndoGroup>) {
         if !self.has_pending_updates() {
             return;
         }
-        let graph = self.graph.clone();
-        let pending_actions = std::mem::take(&mut self.pending_actions);
-        let parent_name = self.parent_name.clone();
-        self.jobs.add(
-            "Applying editor action",
-            move |_job| {
-                let mut graph_updates = GraphUpdate::new();
-                let graph = graph.read();
-
-                let mut state_updates = Vec::new();
-                for action in pending_actions {
-                    let editor_state_update =
-                        action.apply(&graph, &parent_name, &mut graph_updates)?;
-                    state_updates.push(editor_state_update);
-                }
-
-                Ok((graph_updates, state_updates))
-            },
-            |(graph_updates, state_updates), app| {
-                app.project.add_changeset(graph_updates);
-                if let Some(editor) = app.current_editor.get_mut() {
-                    let downcasted = editor.any_mut().downcast_mut::<DocumentEditor>();
-                    if let Some(editor) = downcasted {
-                        for u in state_updates {
-                            u(editor);
-                        }
+        let graph = self.graph.clone();
+        let pending_actions = std::mem::take(&mut self.pending_actions);
+        let parent_name = self.parent_name.clone();
+        let editor_parent_name = parent_name.clone();
+        self.jobs.add_interactive(
+            "Applying editor action",
+            move |_job| {
+                let mut graph_updates = GraphUpdate::new();
+                let graph = graph.read();
+                let (state_updates, needs_reload) =
+                    apply_actions(pending_actions, &graph, &parent_name, &mut graph_updates)?;
+                Ok((graph_updates, state_updates, needs_reload))
+            },
+            move |(graph_updates, state_updates, needs_reload), app| {
+                if needs_reload {
+                    // The token indexes changed, create the editor again from the updated graph
+                    app.project
+                        .add_grouped_changeset_then(graph_updates, group, |app| {
+                            app.load_editor(true)
+                        });
+                    return;
+                }
+                app.project
+                    .add_grouped_changeset_then(graph_updates, group, |_| {});
+                // The document might be shown in the main view or in an
+                // additional window
+                let editor = app
+                    .editors_mut()
+                    .filter_map(|editor| editor.any_mut().downcast_mut::<DocumentEditor>())
+                    .find(|editor| editor.parent_name == editor_parent_name);
+                if let Some(editor) = editor {
+                    for u in state_updates {
+                        u(editor);
+                    }
+                }
+            },
+        );
+    }
+
+    fn get_selected_corpus_node(&self) -> Option<NodeID> {
+        None
+    }
+
+    fn view_state(&self) -> Option<ViewState> {
+        Some(ViewState {
+            node_name: self.parent_name.clone(),
+            selected_nodes: self.selected_nodes.clone(),
+            active_layer: self.active_layer.clone(),
+            first_visible_token: self.first_visible_token,
+        })
+    }
+
+    fn restore_view_state(&mut self, state: ViewState) {
+        if state.node_name != self.parent_name {
+            return;
+        }
+        // Nodes and layers might not exist anymore after undo
+        self.selected_nodes = state
+            .selected_nodes
+            .into_iter()
+            .filter(|n| self.node_position(n).is_some())
+            .collect();
+        if let Some(layer) = state
+            .active_layer
+            .filter(|l| self.segmentations.contains_key(l))
+        {
+            self.active_layer = Some(layer);
+        }
+        if state.first_visible_token > 0 && !self.token.is_empty() {
+            self.scroll_request = Some((
+                state.first_visible_token.min(self.token.len() - 1),
+                egui::Align::Min,
+            ));
+        }
+    }
+
+    fn get_neighbour_documents(&self) -> (Option<NodeID>, Option<NodeID>) {
+        self.neighbour_documents
+    }
+
+    fn get_selected_node_name(&self) -> Option<String> {
+        if self.selected_nodes.len() == 1 {
+            self.selected_nodes.iter().next().cloned()
+        } else {
+            None
+        }
+    }
+
+    fn consume_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_shortcut(&SELECT_BY_PATTERN_SHORTCUT)) {
+            self.pattern_selection.open = true;
+        }
+        // Text inputs, e.g. in dialogs, should still receive these keys
+        if !self.selected_nodes.is_empty()
+            && self.currently_edited_node.is_none()
+            && !ctx.wants_keyboard_input()
+        {
+            // Consume the pasted text, so no other widget inserts it as well
+            let pasted_text = ctx.input_mut(|i| {
+                let idx = i
+                    .events
+                    .iter()
+                    .position(|e| matches!(e, egui::Event::Paste(_)))?;
+                match i.events.remove(idx) {
+                    egui::Event::Paste(text) => Some(text),
+                    _ => None,
+                }
+            });
+            if let Some(text) = pasted_text {
+                self.insert_text_after_selection(&text);
+            } else if ctx.input_mut(|i| i.consume_shortcut(&DELETE_SHORTCUT)) {
+                self.request_deletion(Deletion::SelectedNodes);
+            } else if ctx.input_mut(|i| i.consume_shortcut(&ADD_SPAN_SHORTCUT)) {
+                self.add_segmentation_to_active_layer();
+            } else if ctx.input_mut(|i| i.consume_shortcut(&EDIT_SPAN_SHORTCUT)) {
+                self.edit_active_layer_span();
+            } else if ctx.input_mut(|i| i.consume_shortcut(&NESTED_SPAN_SHORTCUT)) {
+                self.add_nested_span_for_selection();
+            } else if ctx.input_mut(|i| i.consume_shortcut(&DUPLICATE_SPAN_SHORTCUT)) {
+                if let Some(layer) = self.active_layer.clone() {
+                    self.duplicate_selected_span(&layer);
+                }
+            } else {
+                for (layer_idx, _, shortcut) in self.layer_shortcuts() {
+                    if ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+                        self.add_segmentation_for_selection(layer_idx);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_file_menu_entries(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .add_enabled(
+                !self.token.is_empty(),
+                Button::new("Export document as HTML…"),
+            )
+            .on_hover_text("Open the exported file in a web browser to print it as PDF")
+            .clicked()
+        {
+            self.export_html();
+        }
+        if ui
+            .add_enabled(
+                !self.token.is_empty(),
+                Button::new("Export document as SVG…"),
+            )
+            .clicked()
+        {
+            self.export_svg();
+        }
+        if ui
+            .add_enabled(
+                !self.token.is_empty(),
+                Button::new("Export visible part as PNG…"),
+            )
+            .clicked()
+        {
+            let dlg = FileDialog::new()
+                .add_filter("PNG (*.png)", &["png"])
+                .set_file_name(self.export_file_name("png"));
+            self.png_export = dlg.save_file();
+            self.screenshot_requested = false;
+        }
+    }
+
+    fn status_bar(&self, ui: &mut Ui) {
+        ui.label(&self.parent_name);
+        ui.separator();
+        if self.is_timeline() {
+            ui.label(format!("{} timeline items", self.token.len()));
+        } else {
+            ui.label(format!("{} token", self.token.len()));
+        }
+        if !self.token.is_empty() {
+            ui.separator();
+            ui.label(format!(
+                "First visible token: {}",
+                self.first_visible_token + 1
+            ));
+        }
+        ui.separator();
+        ui.label(format!("{} selected", self.selected_nodes.len()));
+    }
+
+    fn shortcuts(&self) -> Vec<ShortcutEntry> {
+        let entry = |description: &str, shortcut: KeyboardShortcut| ShortcutEntry {
+            context: "Document editor",
+            description: description.to_string(),
+            shortcut,
+        };
+        let mut result = vec![
+            entry("Select by pattern", SELECT_BY_PATTERN_SHORTCUT),
+            entry("Delete selected nodes", DELETE_SHORTCUT),
+            entry("Add span on active layer", ADD_SPAN_SHORTCUT),
+            entry("Edit span on active layer", EDIT_SPAN_SHORTCUT),
+            entry("Add nested span", NESTED_SPAN_SHORTCUT),
+            entry(
+                "Duplicate selected span to active layer",
+                DUPLICATE_SPAN_SHORTCUT,
+            ),
+        ];
+        for (_, layer, shortcut) in self.layer_shortcuts() {
+            result.push(entry(&format!("Add span on layer \"{layer}\""), shortcut));
+        }
+        result
+    }
+
+    fn add_edit_menu_entries(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .add_enabled(
+                !self.selected_nodes.is_empty(),
+                Button::new("Delete selected")
+                    .shortcut_text(ui.ctx().format_shortcut(&DELETE_SHORTCUT)),
+            )
+            .clicked()
+        {
+            self.request_deletion(Deletion::SelectedNodes);
+        }
+        if ui
+            .add(
+                Button::new("Select by pattern…")
+                    .shortcut_text(ui.ctx().format_shortcut(&SELECT_BY_PATTERN_SHORTCUT)),
+            )
+            .clicked()
+        {
+            self.pattern_selection.open = true;
+        }
+        let first_selected = self.first_selected_token();
+        if ui
+            .add_enabled(
+                first_selected.is_some() && self.active_layer.is_some(),
+                Button::new("Select span on active layer").shortcut_text("Double-click"),
+            )
+            .clicked()
+        {
+            if let Some(token_idx) = first_selected {
+                self.select_covering_span(token_idx);
+            }
+        }
+        if ui
+            .add_enabled(
+                first_selected.is_some(),
+                Button::new("Select sentence").shortcut_text("Triple-click"),
+            )
+            .clicked()
+        {
+            if let Some(token_idx) = first_selected {
+                self.select_sentence(token_idx);
+            }
+        }
+        if ui
+            .add_enabled(
+                self.insertion_point().is_some(),
+                Button::new("Insert text after selection…"),
+            )
+            .clicked()
+        {
+            self.insert_text.open = true;
+        }
+        if ui
+            .add_enabled(
+                self.insertion_point().is_some(),
+                Button::new("Insert timeline items after selection…"),
+            )
+            .clicked()
+        {
+            self.insert_timeline.open = true;
+        }
+        if ui.button("New segmentation layer…").clicked() {
+            self.new_layer.open = true;
+        }
+        let has_active_layer = self.active_layer.is_some();
+        if ui
+            .add_enabled(
+                has_active_layer && !self.selected_nodes.is_empty(),
+                Button::new("Add span on active layer")
+                    .shortcut_text(ui.ctx().format_shortcut(&ADD_SPAN_SHORTCUT)),
+            )
+            .clicked()
+        {
+            self.add_segmentation_to_active_layer();
+        }
+        if ui
+            .add_enabled(
+                has_active_layer && !self.selected_nodes.is_empty(),
+                Button::new("Edit span on active layer")
+                    .shortcut_text(ui.ctx().format_shortcut(&EDIT_SPAN_SHORTCUT)),
+            )
+            .clicked()
+        {
+            self.edit_active_layer_span();
+        }
+        let split_span = self.split_span_action();
+        if ui
+            .add_enabled(
+                split_span.is_some(),
+                Button::new("Split span on active layer before selection"),
+            )
+            .clicked()
+        {
+            self.edit_active_layer_spans(split_span);
+        }
+        let merge_spans = self.merge_spans_action();
+        if ui
+            .add_enabled(
+                merge_spans.is_some(),
+                Button::new("Merge selected spans on active layer"),
+            )
+            .clicked()
+        {
+            self.edit_active_layer_spans(merge_spans);
+        }
+        let extend_span = self.extend_span_action();
+        if ui
+            .add_enabled(
+                extend_span.is_some(),
+                Button::new("Extend span on active layer to selection"),
+            )
+            .clicked()
+        {
+            self.edit_active_layer_spans(extend_span);
+        }
+        if ui
+            .add_enabled(
+                self.nested_span_key().is_some() && !self.selected_nodes.is_empty(),
+                Button::new("Add nested span")
+                    .shortcut_text(ui.ctx().format_shortcut(&NESTED_SPAN_SHORTCUT)),
+            )
+            .clicked()
+        {
+            self.add_nested_span_for_selection();
+        }
+        let selected_span_layer = self.selected_span().map(|(layer, _)| layer);
+        ui.add_enabled_ui(selected_span_layer.is_some(), |ui| {
+            ui.menu_button("Duplicate selected span to layer", |ui| {
+                ui.checkbox(&mut self.duplicate_with_value, "Copy value");
+                ui.separator();
+                let target_layers: Vec<String> = self
+                    .segmentations
+                    .keys()
+                    .filter(|l| !l.is_empty() && Some(*l) != selected_span_layer.as_ref())
+                    .cloned()
+                    .collect();
+                for layer in target_layers {
+                    let mut button = Button::new(&layer);
+                    if self.active_layer.as_ref() == Some(&layer) {
+                        button = button
+                            .shortcut_text(ui.ctx().format_shortcut(&DUPLICATE_SPAN_SHORTCUT));
                     }
-                }
-            },
-        );
-    }
-
-    fn get_selected_corpus_node(&self) -> Option<NodeID> {
-        None
-    }
-
-    fn consume_shortcuts(&mut self, ctx: &egui::Context) {
-        if !self.selected_nodes.is_empty() && self.currently_edited_node.is_none() {
-            if ctx.input_mut(|i| i.consume_shortcut(&DELETE_SHORTCUT)) {
-                self.delete_selected_nodes();
-            } else {
-                for layer_idx in 1..self.segmentations.len() {
-                    if let Some(key) = Key::from_name(&layer_idx.to_string()) {
-                        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, key)) {
-                            self.add_segmentation_for_selection(layer_idx);
-                        }
+                    if ui.add(button).clicked() {
+                        self.duplicate_selected_span(&layer);
+                        ui.close_menu();
                     }
                 }
-            }
+            });
+        });
+        if ui
+            .add_enabled(
+                self.split_position().is_some(),
+                Button::new("Split document before selection"),
+            )
+            .clicked()
+        {
+            self.split_document_at_selection();
         }
-    }
-
-    fn add_edit_menu_entries(&mut self, ui: &mut egui::Ui) {
         if ui
             .add_enabled(
-                !self.selected_nodes.is_empty(),
-                Button::new("Delete selected")
-                    .shortcut_text(ui.ctx().format_shortcut(&DELETE_SHORTCUT)),
+                !self.segmentations.is_empty(),
+                Button::new("Find duplicate spans…"),
             )
             .clicked()
         {
-            self.delete_selected_nodes();
+            self.check_duplicate_spans();
         }
     }
 }
 
 impl EditorActions {
+    /// Whether the editor needs to be created again from the graph after the
+    /// action has been applied.
+    fn requires_reload(&self) -> bool {
+        matches!(
+            self,
+            EditorActions::InsertToken { .. }
+                | EditorActions::InsertTimelineItems { .. }
+                | EditorActions::SplitDocument { .. }
+                | EditorActions::SetTimeRange { .. }
+                | EditorActions::SplitSpan { .. }
+                | EditorActions::MergeSpans { .. }
+                | EditorActions::ExtendSpan { .. }
+        )
+    }
+
     fn apply(
         self,
         graph: &AnnotationGraph,
@@ -557,7 +3150,34 @@ impl EditorActions {
             EditorActions::AddSegmentationSpan {
                 segmentation,
                 selected_token: selected_nodes,
-            } => apply_add_segmentation(graph, parent_name, updates, segmentation, selected_nodes)?,
+                value,
+            } => apply_add_segmentation(
+                graph,
+                parent_name,
+                updates,
+                segmentation,
+                selected_nodes,
+                value,
+            )?,
+            EditorActions::SplitSpan {
+                segmentation,
+                node_name,
+                first_token_of_new_span,
+            } => apply_split_span(
+                graph,
+                parent_name,
+                updates,
+                &segmentation,
+                &node_name,
+                &first_token_of_new_span,
+            )?,
+            EditorActions::MergeSpans { node_names, value } => {
+                apply_merge_spans(graph, updates, &node_names, &value)?
+            }
+            EditorActions::ExtendSpan {
+                node_name,
+                covered_token,
+            } => apply_extend_span(graph, updates, &node_name, &covered_token)?,
             EditorActions::DeleteNode { node_name } => {
                 let node_id = graph
                     .get_node_annos()
@@ -566,43 +3186,181 @@ impl EditorActions {
                 updates.add_event(UpdateEvent::DeleteNode {
                     node_name: node_name.to_string(),
                 })?;
-                // Bridge any ordering edges that connect to this node to the remaining ones before and after
-                for c in graph.get_all_components(Some(AnnotationComponentType::Ordering), None) {
-                    if let Some(gs) = graph.get_graphstorage_as_ref(&c) {
-                        let mut ingoing = gs.get_ingoing_edges(node_id);
-                        let mut outgoing = gs.get_outgoing_edges(node_id);
-                        if let (Some(ingoing), Some(outgoing)) = (ingoing.next(), outgoing.next()) {
-                            let ingoing = graph
-                                .get_node_annos()
-                                .get_value_for_item(&ingoing?, &NODE_NAME_KEY)?
-                                .context("Missing node name")?;
-                            let outgoing = graph
-                                .get_node_annos()
-                                .get_value_for_item(&outgoing?, &NODE_NAME_KEY)?
-                                .context("Missing node name")?;
-                            updates.add_event(UpdateEvent::DeleteEdge {
-                                source_node: ingoing.to_string(),
-                                target_node: outgoing.to_string(),
-                                layer: c.layer.to_string(),
-                                component_type: c.get_type().to_string(),
-                                component_name: c.name.to_string(),
-                            })?;
-                        }
-                    }
+                // Bridge the ordering edges that connect to this node to the remaining ones before and after
+                bridge_ordering_gaps(graph, &HashSet::from([node_id]), updates)?;
+                Box::new(|_| {})
+            }
+            EditorActions::AddNodeLabels { node_name, labels } => {
+                for (key, value) in labels {
+                    updates.add_event(UpdateEvent::AddNodeLabel {
+                        node_name: node_name.clone(),
+                        anno_ns: key.ns.to_string(),
+                        anno_name: key.name.to_string(),
+                        anno_value: value,
+                    })?;
                 }
                 Box::new(|_| {})
             }
+            EditorActions::AddDatasource => {
+                let node_name = new_datasource_name(graph, parent_name)?;
+                add_datasource(updates, parent_name, &node_name)?;
+                Box::new(move |editor| {
+                    editor.datasources.push(Datasource {
+                        node_name,
+                        node_count: 0,
+                    });
+                })
+            }
+            EditorActions::SetEdgeLabel { edge, key, value } => {
+                edge.update_label(updates, &key, value.as_deref())?;
+                Box::new(|_| {})
+            }
+            EditorActions::InsertToken {
+                after_token,
+                text,
+                tokenizer,
+            } => apply_insert_token(
+                graph,
+                parent_name,
+                updates,
+                after_token.as_deref(),
+                &text[..text.len() - text.trim_start().len()],
+                tokenizer.create()?.tokenize(&text),
+            )?,
+            EditorActions::AddNestedSpan {
+                key,
+                value,
+                covered_token,
+            } => apply_add_nested_span(graph, parent_name, updates, key, value, covered_token)?,
+            EditorActions::InsertTimelineItems { after_token, count } => apply_insert_token(
+                graph,
+                parent_name,
+                updates,
+                after_token.as_deref(),
+                "",
+                vec![TextToken::default(); count],
+            )?,
+            EditorActions::SplitDocument {
+                first_token_of_new_document,
+            } => apply_split_document(graph, parent_name, updates, &first_token_of_new_document)?,
+            EditorActions::SetTimeRange {
+                node_name,
+                start,
+                end,
+            } => apply_set_time_range(graph, updates, &node_name, start, end)?,
         };
         Ok(state_update)
     }
 }
 
+/// The range of the spans of a segmentation layer that cover at least one of
+/// the base token from `first_token` to `last_token`. The spans of a layer
+/// are ordered and do not overlap, so both their start and end are sorted.
+fn visible_span_range(spans: &[Token], first_token: usize, last_token: usize) -> Range<usize> {
+    let start = spans.partition_point(|t| t.end < first_token);
+    let end = spans.partition_point(|t| t.start <= last_token).max(start);
+    start..end
+}
+
+/// The pre-filled key of a new annotation, which only consists of the
+/// namespace if there is one.
+pub(crate) fn new_annotation_key(namespace: &str) -> String {
+    if namespace.is_empty() {
+        String::new()
+    } else {
+        format!("{namespace}:")
+    }
+}
+
+/// Set the value of the token or span and return the action that changes it in
+/// the graph, or `None` if the value did not change.
+fn modify_value(t: &mut Token, new_value: String) -> Option<EditorActions> {
+    if t.labels.get(&TOKEN_KEY) == Some(&new_value) {
+        return None;
+    }
+    t.labels
+        .insert(TOKEN_KEY.as_ref().clone(), new_value.clone());
+    Some(EditorActions::ModifySegmentationValue {
+        node_name: t.node_name.clone(),
+        new_value,
+    })
+}
+
+/// The horizontal space between two neighbouring base token that represents
+/// the whitespace after the first and before the second token. Returns
+/// whether the whitespace contains a line break, which is shown as a wider
+/// gap, since all token are displayed in a single row.
+fn whitespace_gap(previous: &Token, token: &Token) -> (f32, bool) {
+    let whitespace: String = [(previous, WHITESPACE_AFTER), (token, WHITESPACE_BEFORE)]
+        .into_iter()
+        .filter_map(|(t, name)| {
+            t.labels.get(&AnnoKey {
+                ns: ANNIS_NS.into(),
+                name: name.into(),
+            })
+        })
+        .map(String::as_str)
+        .collect();
+    if whitespace.contains('\n') {
+        (LINE_BREAK_WIDTH, true)
+    } else {
+        let spaces: usize = whitespace
+            .chars()
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum();
+        (spaces as f32 * SPACE_WIDTH, false)
+    }
+}
+
+/// Show the context menu of a token or span. Returns the chosen action, which
+/// needs to be executed by the editor.
+fn node_context_menu(
+    ui: &mut Ui,
+    node_name: &str,
+    editable: bool,
+    can_add_span: bool,
+) -> Option<ContextMenuAction> {
+    let mut action = None;
+    if ui
+        .add_enabled(editable, Button::new("Edit value"))
+        .clicked()
+    {
+        action = Some(ContextMenuAction::EditValue);
+    }
+    if ui.button("Add annotation…").clicked() {
+        action = Some(ContextMenuAction::AddAnnotation);
+    }
+    if ui
+        .add_enabled(can_add_span, Button::new("Add span over selection"))
+        .on_disabled_hover_text("Choose an active layer first")
+        .clicked()
+    {
+        action = Some(ContextMenuAction::AddSpan);
+    }
+    if ui.button("Delete").clicked() {
+        action = Some(ContextMenuAction::Delete);
+    }
+    ui.separator();
+    if ui.button("Inspect").clicked() {
+        action = Some(ContextMenuAction::Inspect);
+    }
+    if ui.button("Copy node name").clicked() {
+        ui.ctx().copy_text(node_name.to_string());
+        ui.close_menu();
+    }
+    if action.is_some() {
+        ui.close_menu();
+    }
+    action
+}
+
 fn apply_add_segmentation(
     graph: &AnnotationGraph,
     parent_name: &str,
     updates: &mut GraphUpdate,
     segmentation: String,
     selected_token: HashSet<String>,
+    value: String,
 ) -> anyhow::Result<StateUpdateFn> {
     let new_node_name = format!(
         "{}#{}",
@@ -653,7 +3411,7 @@ fn apply_add_segmentation(
         node_name: new_node_name.clone(),
         anno_ns: TOKEN_KEY.ns.to_string(),
         anno_name: TOKEN_KEY.name.to_string(),
-        anno_value: String::default(),
+        anno_value: value.clone(),
     })?;
     updates.add_event(UpdateEvent::AddNodeLabel {
         node_name: new_node_name.clone(),
@@ -674,45 +3432,54 @@ fn apply_add_segmentation(
     let first_covered = sorted_covered_token.first().cloned();
     let last_covered = sorted_covered_token.last().cloned();
 
-    // Find the segmentations node before and after the selection to add the Ordering edges
-    let matching_ordering_components =
-        graph.get_all_components(Some(AnnotationComponentType::Ordering), Some(&segmentation));
-    if let Some(ordering_component) = matching_ordering_components.first() {
-        if let Some(first_covered) = &first_covered {
-            if let Some(token_before) =
-                tok_helper.get_token_before(first_covered.0, Some(&segmentation))?
-            {
-                let token_before = graph
-                    .get_node_annos()
-                    .get_value_for_item(&token_before, &NODE_NAME_KEY)?
-                    .context("Missing node name")?;
+    // Find the segmentations node before and after the selection to add the
+    // Ordering edges. A new layer has no ordering component until it has two
+    // nodes.
+    let ordering_component = graph
+        .get_all_components(Some(AnnotationComponentType::Ordering), Some(&segmentation))
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| {
+            Component::new(
+                AnnotationComponentType::Ordering,
+                ANNIS_NS.into(),
+                segmentation.as_str().into(),
+            )
+        });
+    if let Some(first_covered) = &first_covered {
+        if let Some(token_before) =
+            tok_helper.get_token_before(first_covered.0, Some(&segmentation))?
+        {
+            let token_before = graph
+                .get_node_annos()
+                .get_value_for_item(&token_before, &NODE_NAME_KEY)?
+                .context("Missing node name")?;
 
-                updates.add_event(UpdateEvent::AddEdge {
-                    source_node: token_before.to_string(),
-                    target_node: new_node_name.clone(),
-                    layer: ordering_component.layer.to_string(),
-                    component_type: ordering_component.get_type().to_string(),
-                    component_name: ordering_component.name.to_string(),
-                })?;
-            }
+            updates.add_event(UpdateEvent::AddEdge {
+                source_node: token_before.to_string(),
+                target_node: new_node_name.clone(),
+                layer: ordering_component.layer.to_string(),
+                component_type: ordering_component.get_type().to_string(),
+                component_name: ordering_component.name.to_string(),
+            })?;
         }
-        if let Some(last_covered) = &last_covered {
-            if let Some(token_after) =
-                tok_helper.get_token_after(last_covered.0, Some(&segmentation))?
-            {
-                let token_after = graph
-                    .get_node_annos()
-                    .get_value_for_item(&token_after, &NODE_NAME_KEY)?
-                    .context("Missing node name")?;
+    }
+    if let Some(last_covered) = &last_covered {
+        if let Some(token_after) =
+            tok_helper.get_token_after(last_covered.0, Some(&segmentation))?
+        {
+            let token_after = graph
+                .get_node_annos()
+                .get_value_for_item(&token_after, &NODE_NAME_KEY)?
+                .context("Missing node name")?;
 
-                updates.add_event(UpdateEvent::AddEdge {
-                    source_node: new_node_name.clone(),
-                    target_node: token_after.to_string(),
-                    layer: ordering_component.layer.to_string(),
-                    component_type: ordering_component.get_type().to_string(),
-                    component_name: ordering_component.name.to_string(),
-                })?;
-            }
+            updates.add_event(UpdateEvent::AddEdge {
+                source_node: new_node_name.clone(),
+                target_node: token_after.to_string(),
+                layer: ordering_component.layer.to_string(),
+                component_type: ordering_component.get_type().to_string(),
+                component_name: ordering_component.name.to_string(),
+            })?;
         }
     }
 
@@ -740,7 +3507,7 @@ fn apply_add_segmentation(
                 .copied()
                 .unwrap_or(base_token_length);
             let mut new_token_labels = BTreeMap::new();
-            new_token_labels.insert(TOKEN_KEY.as_ref().clone(), String::default());
+            new_token_labels.insert(TOKEN_KEY.as_ref().clone(), value);
             new_token_labels.insert(
                 AnnoKey {
                     name: segmentation.into(),
@@ -762,3 +3529,504 @@ fn apply_add_segmentation(
     });
     Ok(state_updater)
 }
+
+fn apply_insert_token(
+    graph: &AnnotationGraph,
+    parent_name: &str,
+    updates: &mut GraphUpdate,
+    after_token: Option<&str>,
+    leading_whitespace: &str,
+    mut new_token: Vec<TextToken>,
+) -> anyhow::Result<StateUpdateFn> {
+    let node_annos = graph.get_node_annos();
+    let after_id = if let Some(after_token) = after_token {
+        Some(
+            node_annos
+                .get_node_id_from_name(after_token)?
+                .context("Missing node id")?,
+        )
+    } else {
+        None
+    };
+    let tok_helper = TokenHelper::new(graph)?;
+    let next_id = if let Some(after_id) = after_id {
+        tok_helper.get_token_after(after_id, None)?
+    } else {
+        None
+    };
+    let next_token = if let Some(next_id) = next_id {
+        Some(
+            node_annos
+                .get_value_for_item(&next_id, &NODE_NAME_KEY)?
+                .context("Missing node name")?
+                .to_string(),
+        )
+    } else {
+        None
+    };
+
+    // The whitespace after the insertion point moves behind the new token.
+    // Whitespace at the start of the inserted text replaces it.
+    if let (Some(after_token), Some(after_id)) = (after_token, after_id) {
+        let whitespace_key = AnnoKey {
+            ns: ANNIS_NS.into(),
+            name: WHITESPACE_AFTER.into(),
+        };
+        let old_whitespace = node_annos
+            .get_value_for_item(&after_id, &whitespace_key)?
+            .map(|ws| ws.to_string())
+            .unwrap_or_default();
+        if let Some(last_token) = new_token.last_mut() {
+            if last_token.whitespace_after.is_empty() {
+                last_token.whitespace_after = old_whitespace.clone();
+            }
+            if !leading_whitespace.is_empty() && leading_whitespace != old_whitespace {
+                updates.add_event(UpdateEvent::AddNodeLabel {
+                    node_name: after_token.to_string(),
+                    anno_ns: ANNIS_NS.to_string(),
+                    anno_name: WHITESPACE_AFTER.to_string(),
+                    anno_value: leading_whitespace.to_string(),
+                })?;
+            }
+        }
+    }
+
+    // Add the new token to the same parent (e.g. a data source) as the existing
+    // token or to the first data source of an empty document
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let mut token_parent = parent_name.to_string();
+    if let Some(after_id) = after_id {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&part_of) {
+            if let Some(parent) = gs.get_outgoing_edges(after_id).next() {
+                if let Some(name) = node_annos.get_value_for_item(&parent?, &NODE_NAME_KEY)? {
+                    token_parent = name.to_string();
+                }
+            }
+        }
+    } else if let Some(parent_id) = node_annos.get_node_id_from_name(parent_name)? {
+        if let Some(datasource) = datasources_of_document(graph, parent_id)?.first() {
+            token_parent = datasource.node_name.clone();
+        }
+    }
+
+    // Spans that cover the token before and after the insertion point also cover the new token
+    let mut covering_spans = Vec::new();
+    if let (Some(after_id), Some(next_id)) = (after_id, next_id) {
+        for c in graph.get_all_components(Some(AnnotationComponentType::Coverage), None) {
+            if let Some(gs) = graph.get_graphstorage_as_ref(&c) {
+                for span in gs.get_ingoing_edges(after_id) {
+                    let span = span?;
+                    if gs.is_connected(span, next_id, 1, std::ops::Bound::Included(1))? {
+                        let span_name = node_annos
+                            .get_value_for_item(&span, &NODE_NAME_KEY)?
+                            .context("Missing node name")?;
+                        covering_spans.push((span_name.to_string(), c.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    let first_id = node_annos
+        .get_largest_item()?
+        .map(|id| id + 1)
+        .unwrap_or_default();
+    let ordering = Component::new(
+        AnnotationComponentType::Ordering,
+        ANNIS_NS.into(),
+        "".into(),
+    );
+    if let (Some(after_token), Some(next_token)) = (after_token, &next_token) {
+        updates.add_event(UpdateEvent::DeleteEdge {
+            source_node: after_token.to_string(),
+            target_node: next_token.clone(),
+            layer: ordering.layer.to_string(),
+            component_type: ordering.get_type().to_string(),
+            component_name: ordering.name.to_string(),
+        })?;
+    }
+
+    let mut previous_token = after_token.map(str::to_string);
+    for (i, t) in new_token.into_iter().enumerate() {
+        let new_node_name = format!("{parent_name}#{}", first_id + i as NodeID);
+        updates.add_event(UpdateEvent::AddNode {
+            node_name: new_node_name.clone(),
+            node_type: "node".to_string(),
+        })?;
+        updates.add_event(UpdateEvent::AddNodeLabel {
+            node_name: new_node_name.clone(),
+            anno_ns: TOKEN_KEY.ns.to_string(),
+            anno_name: TOKEN_KEY.name.to_string(),
+            anno_value: t.value,
+        })?;
+        if !t.whitespace_after.is_empty() {
+            updates.add_event(UpdateEvent::AddNodeLabel {
+                node_name: new_node_name.clone(),
+                anno_ns: ANNIS_NS.to_string(),
+                anno_name: WHITESPACE_AFTER.to_string(),
+                anno_value: t.whitespace_after,
+            })?;
+        }
+        updates.add_event(UpdateEvent::AddEdge {
+            source_node: new_node_name.clone(),
+            target_node: token_parent.clone(),
+            layer: ANNIS_NS.to_string(),
+            component_type: AnnotationComponentType::PartOf.to_string(),
+            component_name: "".to_string(),
+        })?;
+        if let Some(previous_token) = previous_token {
+            updates.add_event(UpdateEvent::AddEdge {
+                source_node: previous_token,
+                target_node: new_node_name.clone(),
+                layer: ordering.layer.to_string(),
+                component_type: ordering.get_type().to_string(),
+                component_name: ordering.name.to_string(),
+            })?;
+        }
+        for (span, c) in &covering_spans {
+            updates.add_event(UpdateEvent::AddEdge {
+                source_node: span.clone(),
+                target_node: new_node_name.clone(),
+                layer: c.layer.to_string(),
+                component_type: c.get_type().to_string(),
+                component_name: c.name.to_string(),
+            })?;
+        }
+        previous_token = Some(new_node_name);
+    }
+    if let (Some(previous_token), Some(next_token)) = (previous_token, next_token) {
+        updates.add_event(UpdateEvent::AddEdge {
+            source_node: previous_token,
+            target_node: next_token,
+            layer: ordering.layer.to_string(),
+            component_type: ordering.get_type().to_string(),
+            component_name: ordering.name.to_string(),
+        })?;
+    }
+
+    Ok(Box::new(|_| {}))
+}
+
+/// Moves the given token, all following token and the spans covering only
+/// these token into a new document. Spans that cover token of both documents
+/// stay in the original document and lose their coverage of the moved token.
+fn apply_split_document(
+    graph: &AnnotationGraph,
+    parent_name: &str,
+    updates: &mut GraphUpdate,
+    first_token_of_new_document: &str,
+) -> anyhow::Result<StateUpdateFn> {
+    let node_annos = graph.get_node_annos();
+    let tok_helper = TokenHelper::new(graph)?;
+    let token = tok_helper.get_ordered_token(parent_name, None)?;
+    let split_id = node_annos
+        .get_node_id_from_name(first_token_of_new_document)?
+        .context("Missing node id")?;
+    let split_idx = token
+        .iter()
+        .position(|t| *t == split_id)
+        .context("Token is not part of the document")?;
+    if split_idx == 0 {
+        bail!("Can not split a document at its first token");
+    }
+    let moved_token: HashSet<NodeID> = token[split_idx..].iter().copied().collect();
+
+    // Find a name for the new document that is not used yet
+    let mut new_document = format!("{parent_name}_2");
+    let mut suffix = 2;
+    while node_annos.get_node_id_from_name(&new_document)?.is_some() {
+        suffix += 1;
+        new_document = format!("{parent_name}_{suffix}");
+    }
+    let doc_name = new_document
+        .rsplit_once('/')
+        .map(|(_, doc)| doc)
+        .unwrap_or(&new_document)
+        .to_string();
+
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let part_of_gs = graph
+        .get_graphstorage_as_ref(&part_of)
+        .context("Missing PartOf component")?;
+    let parent_id = node_annos
+        .get_node_id_from_name(parent_name)?
+        .context("Missing node id")?;
+
+    updates.add_event(UpdateEvent::AddNode {
+        node_name: new_document.clone(),
+        node_type: "corpus".to_string(),
+    })?;
+    updates.add_event(UpdateEvent::AddNodeLabel {
+        node_name: new_document.clone(),
+        anno_ns: ANNIS_NS.to_string(),
+        anno_name: "doc".to_string(),
+        anno_value: doc_name,
+    })?;
+    for corpus in part_of_gs.get_outgoing_edges(parent_id) {
+        let corpus = corpus?;
+        let corpus_name = node_annos
+            .get_value_for_item(&corpus, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        updates.add_event(UpdateEvent::AddEdge {
+            source_node: new_document.clone(),
+            target_node: corpus_name.to_string(),
+            layer: ANNIS_NS.to_string(),
+            component_type: AnnotationComponentType::PartOf.to_string(),
+            component_name: "".to_string(),
+        })?;
+    }
+
+    // Collect all spans covering the moved token and decide if they are moved as well
+    let coverage_components: Vec<_> = graph
+        .get_all_components(Some(AnnotationComponentType::Coverage), None)
+        .into_iter()
+        .filter_map(|c| graph.get_graphstorage_as_ref(&c).map(|gs| (c.clone(), gs)))
+        .collect();
+    let mut moved_nodes: Vec<NodeID> = token[split_idx..].to_vec();
+    let mut visited_spans = HashSet::new();
+    for t in &token[split_idx..] {
+        for (_, gs) in &coverage_components {
+            for span in gs.get_ingoing_edges(*t) {
+                let span = span?;
+                if !visited_spans.insert(span) {
+                    continue;
+                }
+                let mut covers_remaining = false;
+                for (_, gs) in &coverage_components {
+                    for covered in gs.get_outgoing_edges(span) {
+                        if !moved_token.contains(&covered?) {
+                            covers_remaining = true;
+                        }
+                    }
+                }
+                if covers_remaining {
+                    // Only remove the coverage of the moved token
+                    for (c, gs) in &coverage_components {
+                        for covered in gs.get_outgoing_edges(span) {
+                            let covered = covered?;
+                            if moved_token.contains(&covered) {
+                                updates.add_event(UpdateEvent::DeleteEdge {
+                                    source_node: node_name_of(graph, span)?,
+                                    target_node: node_name_of(graph, covered)?,
+                                    layer: c.layer.to_string(),
+                                    component_type: c.get_type().to_string(),
+                                    component_name: c.name.to_string(),
+                                })?;
+                            }
+                        }
+                    }
+                } else {
+                    moved_nodes.push(span);
+                }
+            }
+        }
+    }
+    let moved_nodes_set: HashSet<NodeID> = moved_nodes.iter().copied().collect();
+
+    // Rewrite the PartOf edges of the moved nodes, data sources are duplicated for the new document
+    let mut new_parents: HashMap<String, String> = HashMap::new();
+    new_parents.insert(parent_name.to_string(), new_document.clone());
+    for n in &moved_nodes {
+        let n_name = node_name_of(graph, *n)?;
+        for old_parent in part_of_gs.get_outgoing_edges(*n) {
+            let old_parent = node_name_of(graph, old_parent?)?;
+            let new_parent = if let Some(new_parent) = new_parents.get(&old_parent) {
+                new_parent.clone()
+            } else {
+                let suffix = old_parent
+                    .rsplit_once('#')
+                    .map(|(_, suffix)| suffix)
+                    .unwrap_or(DATASOURCE_NODE_TYPE);
+                let new_parent = format!("{new_document}#{suffix}");
+                add_datasource(updates, &new_document, &new_parent)?;
+                new_parents.insert(old_parent.clone(), new_parent.clone());
+                new_parent
+            };
+            updates.add_event(UpdateEvent::DeleteEdge {
+                source_node: n_name.clone(),
+                target_node: old_parent,
+                layer: ANNIS_NS.to_string(),
+                component_type: AnnotationComponentType::PartOf.to_string(),
+                component_name: "".to_string(),
+            })?;
+            updates.add_event(UpdateEvent::AddEdge {
+                source_node: n_name.clone(),
+                target_node: new_parent,
+                layer: ANNIS_NS.to_string(),
+                component_type: AnnotationComponentType::PartOf.to_string(),
+                component_name: "".to_string(),
+            })?;
+        }
+    }
+
+    // Cut all ordering chains at the document boundary
+    for c in graph.get_all_components(Some(AnnotationComponentType::Ordering), None) {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&c) {
+            for n in &moved_nodes {
+                for before in gs.get_ingoing_edges(*n) {
+                    let before = before?;
+                    if !moved_nodes_set.contains(&before) {
+                        updates.add_event(UpdateEvent::DeleteEdge {
+                            source_node: node_name_of(graph, before)?,
+                            target_node: node_name_of(graph, *n)?,
+                            layer: c.layer.to_string(),
+                            component_type: c.get_type().to_string(),
+                            component_name: c.name.to_string(),
+                        })?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Box::new(|_| {}))
+}
+
+/// Apply all actions and return the functions that update the editor state
+/// and whether the editor needs to be reloaded afterwards. Deleted nodes are
+/// bridged in all ordering components together, so that deleting adjacent
+/// nodes does not refer to nodes that have been deleted before.
+fn apply_actions(
+    actions: Vec<EditorActions>,
+    graph: &AnnotationGraph,
+    parent_name: &str,
+    updates: &mut GraphUpdate,
+) -> Result<(Vec<StateUpdateFn>, bool)> {
+    let mut deleted_nodes = HashSet::new();
+    let mut state_updates = Vec::new();
+    let mut needs_reload = false;
+    for action in actions {
+        needs_reload |= action.requires_reload();
+        if let EditorActions::DeleteNode { node_name } = &action {
+            let node_id = graph
+                .get_node_annos()
+                .get_node_id_from_name(node_name)?
+                .context("Missing node ID")?;
+            deleted_nodes.insert(node_id);
+            updates.add_event(UpdateEvent::DeleteNode {
+                node_name: node_name.to_string(),
+            })?;
+        } else {
+            state_updates.push(action.apply(graph, parent_name, updates)?);
+        }
+    }
+    bridge_ordering_gaps(graph, &deleted_nodes, updates)?;
+    Ok((state_updates, needs_reload))
+}
+
+/// Add the ordering edges that close the gaps left by the deleted nodes in
+/// all ordering components. A sequence of deleted nodes is bridged by a single
+/// edge and deleted nodes at the start or the end of a chain need no new edge.
+fn bridge_ordering_gaps(
+    graph: &AnnotationGraph,
+    deleted_nodes: &HashSet<NodeID>,
+    updates: &mut GraphUpdate,
+) -> Result<()> {
+    for c in graph.get_all_components(Some(AnnotationComponentType::Ordering), None) {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&c) {
+            let mut bridges = BTreeSet::new();
+            for n in deleted_nodes {
+                let before = remaining_neighbour(gs, *n, deleted_nodes, false)?;
+                let after = remaining_neighbour(gs, *n, deleted_nodes, true)?;
+                if let (Some(before), Some(after)) = (before, after) {
+                    bridges.insert((before, after));
+                }
+            }
+            for (before, after) in bridges {
+                updates.add_event(UpdateEvent::AddEdge {
+                    source_node: node_name_of(graph, before)?,
+                    target_node: node_name_of(graph, after)?,
+                    layer: c.layer.to_string(),
+                    component_type: c.get_type().to_string(),
+                    component_name: c.name.to_string(),
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Follow the ordering edges from the node in the given direction until a
+/// node is reached that is not deleted.
+fn remaining_neighbour(
+    gs: &dyn GraphStorage,
+    node: NodeID,
+    deleted_nodes: &HashSet<NodeID>,
+    forward: bool,
+) -> Result<Option<NodeID>> {
+    let mut visited = HashSet::from([node]);
+    let mut current = node;
+    loop {
+        let next = if forward {
+            gs.get_outgoing_edges(current).next()
+        } else {
+            gs.get_ingoing_edges(current).next()
+        };
+        match next {
+            Some(next) => {
+                let next = next?;
+                if !deleted_nodes.contains(&next) {
+                    return Ok(Some(next));
+                }
+                if !visited.insert(next) {
+                    // The ordering has a cycle of deleted nodes
+                    return Ok(None);
+                }
+                current = next;
+            }
+            None => return Ok(None),
+        }
+    }
+}
+
+/// All base token that are part of the document, regardless of their order.
+fn unordered_token(
+    graph: &AnnotationGraph,
+    tok_helper: &TokenHelper,
+    document: NodeID,
+) -> Result<Vec<NodeID>> {
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let mut result = Vec::new();
+    if let Some(gs) = graph.get_graphstorage_as_ref(&part_of) {
+        for step in CycleSafeDFS::new_inverse(gs.as_edgecontainer(), document, 1, usize::MAX) {
+            let node = step?.node;
+            if tok_helper.is_token(node)? {
+                result.push(node);
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn node_name_of(graph: &AnnotationGraph, node: NodeID) -> anyhow::Result<String> {
+    let name = graph
+        .get_node_annos()
+        .get_value_for_item(&node, &NODE_NAME_KEY)?
+        .context("Missing node name")?;
+    Ok(name.to_string())
+}
+
+/// The time range of a token or segmentation node, either from its own time
+/// annotation or the ones of the covered base token.
+fn time_range_of(base_token: &[Token], t: &Token) -> Option<TimeRange> {
+    if let Some(range) = t.labels.get(&TIME_KEY).and_then(|v| TimeRange::parse(v)) {
+        return Some(range);
+    }
+    let token_time = |idx: usize| {
+        base_token
+            .get(idx)
+            .and_then(|t| t.labels.get(&TIME_KEY))
+            .and_then(|v| TimeRange::parse(v))
+    };
+    let start = token_time(t.start)?.start;
+    let end = token_time(t.end).and_then(|r| r.end);
+    Some(TimeRange { start, end })
+}
+
+fn is_at_position(base_token: &[Token], position: Option<f64>, t: &Token) -> bool {
+    if let (Some(position), Some(range)) = (position, time_range_of(base_token, t)) {
+        position >= range.start && range.end.is_none_or(|end| position < end)
+    } else {
+        false
+    }
+}
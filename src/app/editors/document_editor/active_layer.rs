@@ -0,0 +1,416 @@
+use std::{collections::HashSet, ops::RangeInclusive};
+
+use anyhow::{bail, Context, Result};
+use egui::{ComboBox, Ui};
+use graphannis::{
+    graph::NodeID,
+    model::{AnnotationComponent, AnnotationComponentType},
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::{
+    graph::{ANNIS_NS, NODE_NAME_KEY, NODE_TYPE_KEY},
+    types::Component,
+};
+
+use super::{
+    bridge_ordering_gaps, node_name_of, whitespace_gap, DocumentEditor, EditorActions,
+    StateUpdateFn,
+};
+use crate::{
+    api::token_helper::{TokenHelper, TOKEN_KEY},
+    app::widgets::Token,
+};
+
+const ACTIVE_LAYER_ID: &str = "document-editor-active-layer";
+
+impl DocumentEditor {
+    /// Show the selection of the active layer. The layer is persisted and
+    /// restored in other documents that have a layer with the same name.
+    pub(super) fn show_active_layer_selection(&mut self, ui: &mut Ui) {
+        let active_layer_id = egui::Id::new(ACTIVE_LAYER_ID);
+        if self.layout_info.first_frame {
+            // Restore the layer that has been active the last time, if it exists in this document
+            let persisted: Option<String> = ui.data_mut(|d| d.get_persisted(active_layer_id));
+            if let Some(layer) = persisted.filter(|l| self.segmentations.contains_key(l)) {
+                self.active_layer = Some(layer);
+            }
+        }
+        let old_layer = self.active_layer.clone();
+        ComboBox::from_label("Active layer")
+            .selected_text(self.active_layer.clone().unwrap_or_default())
+            .show_ui(ui, |ui| {
+                for seg_name in self.segmentations.keys() {
+                    ui.selectable_value(&mut self.active_layer, Some(seg_name.clone()), seg_name);
+                }
+            });
+        if old_layer != self.active_layer {
+            if let Some(layer) = self.active_layer.clone() {
+                ui.data_mut(|d| d.insert_persisted(active_layer_id, layer));
+            }
+        }
+    }
+
+    /// Adds an empty segmentation node to the active layer that spans the
+    /// currently selected token.
+    pub(super) fn add_segmentation_to_active_layer(&mut self) {
+        if let Some(seg_name) = self.active_layer.clone() {
+            self.add_segmentation_to_layer(&seg_name);
+        }
+    }
+
+    /// Starts editing the value of the node of the active layer that covers
+    /// the first selected token.
+    pub(super) fn edit_active_layer_span(&mut self) {
+        let first_selected = self.first_selected_token();
+        if let (Some(layer), Some(token_idx)) = (&self.active_layer, first_selected) {
+            if let Some(span) = self.covering_span(layer, token_idx).cloned() {
+                self.selected_nodes.clear();
+                self.selected_nodes.insert(span.node_name.clone());
+                self.current_edited_value =
+                    span.labels.get(&TOKEN_KEY).cloned().unwrap_or_default();
+                self.currently_edited_node = Some(span.node_name);
+                self.focus_edited_node = true;
+            }
+        }
+    }
+
+    /// The range of base token covered by the selected nodes.
+    fn selected_token_range(&self) -> Option<RangeInclusive<usize>> {
+        let (start, end) = self
+            .selected_nodes
+            .iter()
+            .filter_map(|n| self.node_by_name(n))
+            .fold(None, |range: Option<(usize, usize)>, t| match range {
+                Some((start, end)) => Some((start.min(t.start), end.max(t.end))),
+                None => Some((t.start, t.end)),
+            })?;
+        Some(start..=end)
+    }
+
+    /// The spans of the active layer that cover any base token of the range,
+    /// in the order of the document.
+    fn active_layer_spans(&self, range: &RangeInclusive<usize>) -> Vec<&Token> {
+        let mut spans: Vec<_> = self
+            .active_layer
+            .as_ref()
+            .filter(|layer| !layer.is_empty())
+            .and_then(|layer| self.segmentations.get(layer))
+            .into_iter()
+            .flatten()
+            .filter(|t| t.start <= *range.end() && *range.start() <= t.end)
+            .collect();
+        spans.sort_by_key(|t| t.start);
+        spans
+    }
+
+    /// Split the span of the active layer that covers the first selected
+    /// base token, so that the token is the first one of a new span.
+    pub(super) fn split_span_action(&self) -> Option<EditorActions> {
+        let layer = self
+            .active_layer
+            .as_ref()
+            .filter(|layer| !layer.is_empty())?;
+        let token_idx = self.first_selected_token()?;
+        let span = self
+            .covering_span(layer, token_idx)
+            .filter(|t| t.start < token_idx)?;
+        Some(EditorActions::SplitSpan {
+            segmentation: layer.clone(),
+            node_name: span.node_name.clone(),
+            first_token_of_new_span: self.token[token_idx].node_name.clone(),
+        })
+    }
+
+    /// Merge all spans of the active layer that overlap with the selection
+    /// into the first one. The values are joined with a space if there is
+    /// whitespace between the spans.
+    pub(super) fn merge_spans_action(&self) -> Option<EditorActions> {
+        let spans = self.active_layer_spans(&self.selected_token_range()?);
+        if spans.len() < 2 {
+            return None;
+        }
+        let mut value = String::new();
+        for (idx, span) in spans.iter().enumerate() {
+            if idx > 0 {
+                let previous = &spans[idx - 1];
+                let has_whitespace =
+                    match (self.token.get(previous.end), self.token.get(span.start)) {
+                        (Some(before), Some(after)) => whitespace_gap(before, after).0 > 0.0,
+                        _ => false,
+                    };
+                if has_whitespace && !value.is_empty() {
+                    value.push(' ');
+                }
+            }
+            value.push_str(span.labels.get(&TOKEN_KEY).map_or("", String::as_str));
+        }
+        Some(EditorActions::MergeSpans {
+            node_names: spans.iter().map(|t| t.node_name.clone()).collect(),
+            value,
+        })
+    }
+
+    /// Extend the single span of the active layer that overlaps with the
+    /// selection, so that it covers all selected base token. The span can
+    /// not be extended over other spans of the layer.
+    pub(super) fn extend_span_action(&self) -> Option<EditorActions> {
+        let range = self.selected_token_range()?;
+        let spans = self.active_layer_spans(&range);
+        let [span] = spans[..] else {
+            return None;
+        };
+        let extended = *range.start().min(&span.start)..=*range.end().max(&span.end);
+        if self.active_layer_spans(&extended).len() != 1
+            || (*extended.start() == span.start && *extended.end() == span.end)
+        {
+            return None;
+        }
+        let covered_token = extended
+            .filter(|idx| *idx < span.start || *idx > span.end)
+            .filter_map(|idx| self.token.get(idx))
+            .map(|t| t.node_name.clone())
+            .collect();
+        Some(EditorActions::ExtendSpan {
+            node_name: span.node_name.clone(),
+            covered_token,
+        })
+    }
+
+    /// Apply an action that changes the spans of the active layer. The
+    /// editor is created again afterwards, so the selection is cleared.
+    pub(super) fn edit_active_layer_spans(&mut self, action: Option<EditorActions>) {
+        if let Some(action) = action {
+            self.selected_nodes.clear();
+            self.pending_actions.push(action);
+            self.apply_pending_updates_automatically();
+        }
+    }
+}
+
+/// Split the span before the given base token. The new span covers the
+/// remaining token and follows the span in the ordering of the layer. It
+/// has the same annotations, except for an empty value.
+pub(super) fn apply_split_span(
+    graph: &AnnotationGraph,
+    parent_name: &str,
+    updates: &mut GraphUpdate,
+    segmentation: &str,
+    node_name: &str,
+    first_token_of_new_span: &str,
+) -> Result<StateUpdateFn> {
+    let node_annos = graph.get_node_annos();
+    let node = node_annos
+        .get_node_id_from_name(node_name)?
+        .context("Missing node ID")?;
+    let first_token = node_annos
+        .get_node_id_from_name(first_token_of_new_span)?
+        .context("Missing node ID")?;
+
+    let covered = covered_token(graph, node)?;
+    let mut sorted_token: Vec<_> = covered.iter().map(|(_, t)| *t).collect();
+    TokenHelper::new(graph)?.sort_token(&mut sorted_token, None)?;
+    let moved_token: HashSet<_> = match sorted_token.iter().position(|t| *t == first_token) {
+        Some(idx) if idx > 0 => sorted_token[idx..].iter().copied().collect(),
+        _ => bail!("The span can not be split before {first_token_of_new_span}"),
+    };
+
+    let new_node_name = format!(
+        "{parent_name}#{}",
+        node_annos
+            .get_largest_item()?
+            .map(|id| id + 1)
+            .unwrap_or_default()
+    );
+    updates.add_event(UpdateEvent::AddNode {
+        node_name: new_node_name.clone(),
+        node_type: "node".to_string(),
+    })?;
+    updates.add_event(UpdateEvent::AddEdge {
+        source_node: new_node_name.clone(),
+        target_node: parent_name.to_string(),
+        layer: ANNIS_NS.to_string(),
+        component_type: AnnotationComponentType::PartOf.to_string(),
+        component_name: "".to_string(),
+    })?;
+    for anno in node_annos.get_annotations_for_item(&node)? {
+        if anno.key == *NODE_NAME_KEY.as_ref() || anno.key == *NODE_TYPE_KEY.as_ref() {
+            continue;
+        }
+        let anno_value = if anno.key == *TOKEN_KEY.as_ref() {
+            String::new()
+        } else {
+            anno.val.to_string()
+        };
+        updates.add_event(UpdateEvent::AddNodeLabel {
+            node_name: new_node_name.clone(),
+            anno_ns: anno.key.ns.to_string(),
+            anno_name: anno.key.name.to_string(),
+            anno_value,
+        })?;
+    }
+
+    for (component, target) in covered {
+        if moved_token.contains(&target) {
+            let target_node = node_name_of(graph, target)?;
+            updates.add_event(edge_event(false, &component, node_name, &target_node))?;
+            updates.add_event(edge_event(true, &component, &new_node_name, &target_node))?;
+        }
+    }
+
+    let ordering_component = graph
+        .get_all_components(Some(AnnotationComponentType::Ordering), Some(segmentation))
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| {
+            Component::new(
+                AnnotationComponentType::Ordering,
+                ANNIS_NS.into(),
+                segmentation.into(),
+            )
+        });
+    if let Some(gs) = graph.get_graphstorage_as_ref(&ordering_component) {
+        if let Some(next) = gs.get_outgoing_edges(node).next() {
+            let next = node_name_of(graph, next?)?;
+            updates.add_event(edge_event(false, &ordering_component, node_name, &next))?;
+            updates.add_event(edge_event(true, &ordering_component, &new_node_name, &next))?;
+        }
+    }
+    updates.add_event(edge_event(
+        true,
+        &ordering_component,
+        node_name,
+        &new_node_name,
+    ))?;
+    Ok(Box::new(|_| {}))
+}
+
+/// Merge the spans into the first one, which covers all their token and gets
+/// the annotations of the other spans that it does not have itself. The
+/// other spans are deleted.
+pub(super) fn apply_merge_spans(
+    graph: &AnnotationGraph,
+    updates: &mut GraphUpdate,
+    node_names: &[String],
+    value: &str,
+) -> Result<StateUpdateFn> {
+    let Some((kept, merged)) = node_names.split_first() else {
+        return Ok(Box::new(|_| {}));
+    };
+    let node_annos = graph.get_node_annos();
+    let kept_node = node_annos
+        .get_node_id_from_name(kept)?
+        .context("Missing node ID")?;
+    let mut existing_keys: HashSet<_> = node_annos
+        .get_annotations_for_item(&kept_node)?
+        .into_iter()
+        .map(|anno| anno.key)
+        .collect();
+    let mut deleted_nodes = HashSet::new();
+    for node_name in merged {
+        let node = node_annos
+            .get_node_id_from_name(node_name)?
+            .context("Missing node ID")?;
+        for (component, target) in covered_token(graph, node)? {
+            let target_node = node_name_of(graph, target)?;
+            updates.add_event(edge_event(true, &component, kept, &target_node))?;
+        }
+        for anno in node_annos.get_annotations_for_item(&node)? {
+            if anno.key.ns != ANNIS_NS && existing_keys.insert(anno.key.clone()) {
+                updates.add_event(UpdateEvent::AddNodeLabel {
+                    node_name: kept.clone(),
+                    anno_ns: anno.key.ns.to_string(),
+                    anno_name: anno.key.name.to_string(),
+                    anno_value: anno.val.to_string(),
+                })?;
+            }
+        }
+        updates.add_event(UpdateEvent::DeleteNode {
+            node_name: node_name.clone(),
+        })?;
+        deleted_nodes.insert(node);
+    }
+    bridge_ordering_gaps(graph, &deleted_nodes, updates)?;
+
+    updates.add_event(UpdateEvent::DeleteNodeLabel {
+        node_name: kept.clone(),
+        anno_ns: TOKEN_KEY.ns.to_string(),
+        anno_name: TOKEN_KEY.name.to_string(),
+    })?;
+    updates.add_event(UpdateEvent::AddNodeLabel {
+        node_name: kept.clone(),
+        anno_ns: TOKEN_KEY.ns.to_string(),
+        anno_name: TOKEN_KEY.name.to_string(),
+        anno_value: value.to_string(),
+    })?;
+    Ok(Box::new(|_| {}))
+}
+
+/// Let the span cover the additional base token, using the same coverage
+/// component as its existing token.
+pub(super) fn apply_extend_span(
+    graph: &AnnotationGraph,
+    updates: &mut GraphUpdate,
+    node_name: &str,
+    new_token: &[String],
+) -> Result<StateUpdateFn> {
+    let node = graph
+        .get_node_annos()
+        .get_node_id_from_name(node_name)?
+        .context("Missing node ID")?;
+    let component = covered_token(graph, node)?
+        .into_iter()
+        .next()
+        .map(|(c, _)| c)
+        .unwrap_or_else(|| Component::new(AnnotationComponentType::Coverage, "".into(), "".into()));
+    for target_node in new_token {
+        updates.add_event(edge_event(true, &component, node_name, target_node))?;
+    }
+    Ok(Box::new(|_| {}))
+}
+
+/// The token covered by the node with the coverage component of each edge.
+fn covered_token(
+    graph: &AnnotationGraph,
+    node: NodeID,
+) -> Result<Vec<(AnnotationComponent, NodeID)>> {
+    let mut result = Vec::new();
+    for component in graph.get_all_components(Some(AnnotationComponentType::Coverage), None) {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&component) {
+            for target in gs.get_outgoing_edges(node) {
+                result.push((component.clone(), target?));
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn edge_event(
+    add: bool,
+    component: &AnnotationComponent,
+    source_node: &str,
+    target_node: &str,
+) -> UpdateEvent {
+    let source_node = source_node.to_string();
+    let target_node = target_node.to_string();
+    let layer = component.layer.to_string();
+    let component_type = component.get_type().to_string();
+    let component_name = component.name.to_string();
+    if add {
+        UpdateEvent::AddEdge {
+            source_node,
+            target_node,
+            layer,
+            component_type,
+            component_name,
+        }
+    } else {
+        UpdateEvent::DeleteEdge {
+            source_node,
+            target_node,
+            layer,
+            component_type,
+            component_name,
+        }
+    }
+}
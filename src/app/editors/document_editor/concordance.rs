@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use egui::{mutex::RwLock, RichText, ScrollArea, Ui};
+use graphannis::{graph::AnnoKey, AnnotationGraph};
+
+use crate::api::token_helper::TOKEN_KEY;
+use crate::app::{
+    job_executor::JobExecutor,
+    util::concordance::{occurrences, Occurrence},
+    views::rename_annotation::key_label,
+    widgets::Token,
+};
+
+/// Maximum number of occurrences that are searched for.
+const MAX_OCCURRENCES: usize = 200;
+
+/// Which annotation of the selected token the occurrences must share.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ConcordanceMode {
+    #[default]
+    Text,
+    Lemma,
+}
+
+enum ConcordanceState {
+    Loading,
+    Loaded(Vec<Occurrence>),
+    Failed(String),
+}
+
+fn search(graph: &RwLock<AnnotationGraph>, key: &AnnoKey, value: &str) -> Result<Vec<Occurrence>> {
+    graph.write().ensure_loaded_all()?;
+    let graph = graph.read();
+    occurrences(&graph, key, value, MAX_OCCURRENCES)
+}
+
+/// Side panel with the other occurrences of the selected token's text or
+/// lemma in the corpus, so annotation decisions can be kept consistent.
+#[derive(Clone, Default)]
+pub(crate) struct ConcordancePanel {
+    pub(crate) open: bool,
+    pub(crate) mode: ConcordanceMode,
+    /// The selected node and the annotation key and value that have been
+    /// searched for.
+    query: Option<(String, AnnoKey, String)>,
+    /// Written by the background job. A new search replaces it, so results
+    /// of outdated searches are ignored.
+    state: Arc<RwLock<Option<ConcordanceState>>>,
+}
+
+impl ConcordancePanel {
+    fn query_for(&self, token: &Token) -> Option<(AnnoKey, String)> {
+        match self.mode {
+            ConcordanceMode::Text => token
+                .labels
+                .get(TOKEN_KEY.as_ref())
+                .map(|value| (TOKEN_KEY.as_ref().clone(), value.clone())),
+            ConcordanceMode::Lemma => token
+                .labels
+                .iter()
+                .find(|(key, _)| key.name == "lemma")
+                .map(|(key, value)| (key.clone(), value.clone())),
+        }
+    }
+
+    /// Search the occurrences in the background if the selection or the
+    /// mode changed.
+    pub(crate) fn update(
+        &mut self,
+        selected: Option<&Token>,
+        graph: &Arc<RwLock<AnnotationGraph>>,
+        jobs: &JobExecutor,
+    ) {
+        let query = selected.and_then(|token| {
+            self.query_for(token)
+                .map(|(key, value)| (token.node_name.clone(), key, value))
+        });
+        if query == self.query {
+            return;
+        }
+        self.query = query;
+        self.state = Arc::new(RwLock::new(None));
+        let Some((_, key, value)) = self.query.clone() else {
+            return;
+        };
+        *self.state.write() = Some(ConcordanceState::Loading);
+        let state = self.state.clone();
+        let graph = graph.clone();
+        jobs.add_background(
+            &format!("Searching occurrences of {}={value}", key_label(&key)),
+            move |_job| {
+                let result = search(&graph, &key, &value);
+                *state.write() = Some(match result {
+                    Ok(result) => ConcordanceState::Loaded(result),
+                    Err(e) => ConcordanceState::Failed(format!("{e:#}")),
+                });
+                Ok(())
+            },
+            |_, _| {},
+        );
+    }
+
+    /// Show the occurrences and return the one the user clicked on.
+    pub(crate) fn show(&mut self, ui: &mut Ui) -> Option<Occurrence> {
+        let mut clicked = None;
+        ui.heading("Concordance");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.mode, ConcordanceMode::Text, "Token text");
+            ui.selectable_value(&mut self.mode, ConcordanceMode::Lemma, "Lemma");
+        });
+        ui.separator();
+        let Some((selected_node, key, value)) = &self.query else {
+            ui.label(match self.mode {
+                ConcordanceMode::Text => "Select a single token to see where its text occurs.",
+                ConcordanceMode::Lemma => {
+                    "Select a single token with a lemma annotation to see where the lemma occurs."
+                }
+            });
+            return None;
+        };
+        ui.label(format!("{}={value}", key_label(key)));
+        match &*self.state.read() {
+            Some(ConcordanceState::Loading) | None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Searching…");
+                });
+            }
+            Some(ConcordanceState::Failed(e)) => {
+                ui.label(format!("Could not search the occurrences: {e}"));
+            }
+            Some(ConcordanceState::Loaded(result)) => {
+                if result.len() >= MAX_OCCURRENCES {
+                    ui.label(format!("The first {MAX_OCCURRENCES} occurrences:"));
+                } else {
+                    ui.label(format!("{} occurrence(s):", result.len()));
+                }
+                ScrollArea::vertical().show(ui, |ui| {
+                    for occurrence in result {
+                        let is_selected = &occurrence.node_name == selected_node;
+                        ui.horizontal_wrapped(|ui| {
+                            ui.spacing_mut().item_spacing.x = 4.0;
+                            ui.label(RichText::new(&occurrence.left_context).weak());
+                            let text = RichText::new(&occurrence.text).strong();
+                            let response = if is_selected {
+                                ui.label(text).on_hover_text("This is the selected token")
+                            } else {
+                                ui.link(text)
+                                    .on_hover_text(format!("Show in {}", occurrence.document_name))
+                            };
+                            if response.clicked() && !is_selected {
+                                clicked = Some(occurrence.clone());
+                            }
+                            ui.label(RichText::new(&occurrence.right_context).weak());
+                        });
+                        let annotations: Vec<String> = occurrence
+                            .annotations
+                            .iter()
+                            .map(|(key, value)| format!("{}={value}", key_label(key)))
+                            .collect();
+                        if !annotations.is_empty() {
+                            ui.label(RichText::new(annotations.join(", ")).small());
+                        }
+                        ui.separator();
+                    }
+                });
+            }
+        }
+        clicked
+    }
+}
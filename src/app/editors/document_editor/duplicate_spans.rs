@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use egui::ScrollArea;
+use graphannis_core::graph::ANNIS_NS;
+
+use super::{Deletion, DocumentEditor, EditorActions};
+use crate::{api::token_helper::TOKEN_KEY, app::widgets::Token};
+
+/// Segmentation nodes of the same layer that cover exactly the same token.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct DuplicateSpans {
+    pub(super) layer: String,
+    pub(super) start: usize,
+    pub(super) end: usize,
+    /// The first node is kept when the duplicates are resolved.
+    pub(super) node_names: Vec<String>,
+}
+
+impl DocumentEditor {
+    pub(super) fn check_duplicate_spans(&mut self) {
+        self.duplicate_spans = Some(find_duplicate_spans(&self.segmentations));
+    }
+
+    /// Keep only the first node of the duplicates and delete the others. If
+    /// `merge` is true, the annotations of the deleted nodes that are missing
+    /// on the kept node are added to it.
+    pub(super) fn resolve_duplicate_spans(&mut self, duplicates: &DuplicateSpans, merge: bool) {
+        let Some(spans) = self.segmentations.get_mut(&duplicates.layer) else {
+            return;
+        };
+        let Some((kept, removed)) = duplicates.node_names.split_first() else {
+            return;
+        };
+        if merge {
+            let mut merged_labels = BTreeMap::new();
+            if let Some(kept_span) = spans.iter().find(|t| &t.node_name == kept) {
+                for t in spans.iter().filter(|t| removed.contains(&t.node_name)) {
+                    for (key, value) in t.labels.iter() {
+                        if key.ns != ANNIS_NS && !kept_span.labels.contains_key(key) {
+                            merged_labels
+                                .entry(key.clone())
+                                .or_insert_with(|| value.clone());
+                        }
+                    }
+                }
+            }
+            if let Some(kept_span) = spans.iter_mut().find(|t| &t.node_name == kept) {
+                kept_span.labels.extend(merged_labels.clone());
+            }
+            if !merged_labels.is_empty() {
+                self.pending_actions.push(EditorActions::AddNodeLabels {
+                    node_name: kept.clone(),
+                    labels: merged_labels,
+                });
+            }
+        }
+        spans.retain(|t| !removed.contains(&t.node_name));
+        for node_name in removed {
+            self.selected_nodes.remove(node_name);
+            self.pending_actions.push(EditorActions::DeleteNode {
+                node_name: node_name.clone(),
+            });
+        }
+        self.layout_info.valid = false;
+        if let Some(found) = &mut self.duplicate_spans {
+            found.retain(|d| d != duplicates);
+        }
+    }
+
+    pub(super) fn show_duplicate_spans_dialog(&mut self, ctx: &egui::Context) {
+        let Some(found) = self.duplicate_spans.clone() else {
+            return;
+        };
+        let mut open = true;
+        let mut resolve = Vec::new();
+        let mut show_token = None;
+        egui::Window::new("Duplicate spans")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if found.is_empty() {
+                    ui.label("No duplicate spans found.");
+                    return;
+                }
+                ui.label("These spans cover the same token as another span on the same layer.");
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("duplicate-spans")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for duplicates in found.iter() {
+                                ui.label(&duplicates.layer);
+                                let covered_text = self.token[duplicates.start..=duplicates.end]
+                                    .iter()
+                                    .filter_map(|t| t.labels.get(&TOKEN_KEY))
+                                    .map(|v| v.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join(" ");
+                                if ui
+                                    .link(covered_text)
+                                    .on_hover_text("Show in the document")
+                                    .clicked()
+                                {
+                                    show_token = Some(duplicates.start);
+                                }
+                                ui.label(format!("{} spans", duplicates.node_names.len()));
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .button("Merge")
+                                        .on_hover_text(
+                                            "Keep the first span and add the annotations of the others to it",
+                                        )
+                                        .clicked()
+                                    {
+                                        resolve.push((duplicates.clone(), true));
+                                    }
+                                    if ui
+                                        .button("Delete")
+                                        .on_hover_text("Keep only the first span")
+                                        .clicked()
+                                    {
+                                        resolve.push((duplicates.clone(), false));
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Merge all").clicked() {
+                        resolve.extend(found.iter().map(|d| (d.clone(), true)));
+                    }
+                    if ui.button("Delete all").clicked() {
+                        resolve.extend(found.iter().map(|d| (d.clone(), false)));
+                    }
+                });
+            });
+        if let Some(index) = show_token {
+            self.scroll_to_token(index);
+        }
+        if resolve.len() > 1 {
+            self.request_deletion(Deletion::DuplicateSpans(resolve));
+        } else {
+            for (duplicates, merge) in resolve {
+                self.resolve_duplicate_spans(&duplicates, merge);
+            }
+        }
+        if !open {
+            self.duplicate_spans = None;
+        }
+    }
+}
+
+/// Find all segmentation nodes that cover the same token as another node
+/// of the same layer.
+pub(super) fn find_duplicate_spans(
+    segmentations: &BTreeMap<String, Vec<Token>>,
+) -> Vec<DuplicateSpans> {
+    let mut result = Vec::new();
+    for (layer, spans) in segmentations.iter() {
+        let mut by_range: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+        for t in spans.iter() {
+            by_range
+                .entry((t.start, t.end))
+                .or_default()
+                .push(t.node_name.clone());
+        }
+        for ((start, end), node_names) in by_range {
+            if node_names.len() > 1 {
+                result.push(DuplicateSpans {
+                    layer: layer.clone(),
+                    start,
+                    end,
+                    node_names,
+                });
+            }
+        }
+    }
+    result
+}
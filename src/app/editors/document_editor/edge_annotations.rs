@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use egui::{Grid, RichText, TextEdit, Ui};
+use graphannis::{
+    graph::AnnoKey,
+    model::AnnotationComponentType,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::{
+    graph::NODE_NAME_KEY,
+    types::{Component, Edge},
+};
+
+use super::new_annotation_key;
+use crate::app::{
+    util::annis_config::LayerSettings, views::rename_annotation::parse_key, widgets::value_editor,
+};
+
+/// An edge of a pointing or dominance component and its annotations.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EdgeAnnotations {
+    pub(crate) source_node: String,
+    pub(crate) target_node: String,
+    pub(crate) component: Component<AnnotationComponentType>,
+    pub(crate) labels: BTreeMap<AnnoKey, String>,
+}
+
+impl EdgeAnnotations {
+    fn description(&self) -> String {
+        let short_name = |n: &str| n.rsplit_once('#').map_or(n, |(_, n)| n).to_string();
+        let component = if self.component.name.is_empty() {
+            self.component.get_type().to_string()
+        } else {
+            format!("{} {}", self.component.get_type(), self.component.name)
+        };
+        format!(
+            "{} → {} ({component})",
+            short_name(&self.source_node),
+            short_name(&self.target_node)
+        )
+    }
+
+    /// Add the events that set (or delete if `value` is `None`) the label of
+    /// this edge.
+    pub(crate) fn update_label(
+        &self,
+        updates: &mut GraphUpdate,
+        key: &AnnoKey,
+        value: Option<&str>,
+    ) -> Result<()> {
+        updates.add_event(UpdateEvent::DeleteEdgeLabel {
+            source_node: self.source_node.clone(),
+            target_node: self.target_node.clone(),
+            layer: self.component.layer.to_string(),
+            component_type: self.component.get_type().to_string(),
+            component_name: self.component.name.to_string(),
+            anno_ns: key.ns.to_string(),
+            anno_name: key.name.to_string(),
+        })?;
+        if let Some(value) = value {
+            updates.add_event(UpdateEvent::AddEdgeLabel {
+                source_node: self.source_node.clone(),
+                target_node: self.target_node.clone(),
+                layer: self.component.layer.to_string(),
+                component_type: self.component.get_type().to_string(),
+                component_name: self.component.name.to_string(),
+                anno_ns: key.ns.to_string(),
+                anno_name: key.name.to_string(),
+                anno_value: value.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Find all pointing and dominance edges that start or end at the given node.
+pub(crate) fn edges_of_node(
+    graph: &AnnotationGraph,
+    node_name: &str,
+) -> Result<Vec<EdgeAnnotations>> {
+    let node_annos = graph.get_node_annos();
+    let node_id = node_annos
+        .get_node_id_from_name(node_name)?
+        .context("Missing node ID")?;
+    let name_of = |n| -> Result<String> {
+        Ok(node_annos
+            .get_value_for_item(&n, &NODE_NAME_KEY)?
+            .context("Missing node name")?
+            .to_string())
+    };
+
+    let mut result = Vec::new();
+    for ctype in [
+        AnnotationComponentType::Pointing,
+        AnnotationComponentType::Dominance,
+    ] {
+        for component in graph.get_all_components(Some(ctype), None) {
+            if let Some(gs) = graph.get_graphstorage_as_ref(&component) {
+                let outgoing = gs
+                    .get_outgoing_edges(node_id)
+                    .map(|target| target.map(|target| (node_id, target)));
+                let ingoing = gs
+                    .get_ingoing_edges(node_id)
+                    .map(|source| source.map(|source| (source, node_id)));
+                for edge in outgoing.chain(ingoing) {
+                    let (source, target) = edge?;
+                    let mut labels = BTreeMap::new();
+                    for anno in gs
+                        .get_anno_storage()
+                        .get_annotations_for_item(&Edge { source, target })?
+                    {
+                        labels.insert(anno.key, anno.val.to_string());
+                    }
+                    result.push(EdgeAnnotations {
+                        source_node: name_of(source)?,
+                        target_node: name_of(target)?,
+                        component: component.clone(),
+                        labels,
+                    });
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// A change to a label of an edge made in the [`EdgeAnnotationEditor`].
+pub(crate) struct EdgeLabelChange {
+    pub(crate) edge: EdgeAnnotations,
+    pub(crate) key: AnnoKey,
+    pub(crate) value: Option<String>,
+}
+
+/// Lists the edges of the selected node and allows to edit the labels of
+/// one of them.
+#[derive(Default)]
+pub(crate) struct EdgeAnnotationEditor {
+    /// The node the edges have been loaded for.
+    pub(crate) node_name: Option<String>,
+    pub(crate) edges: Vec<EdgeAnnotations>,
+    selected_edge: Option<usize>,
+    /// Values of the labels of the selected edge while they are edited.
+    edited_values: BTreeMap<AnnoKey, String>,
+    new_key: String,
+    new_value: String,
+    error: Option<String>,
+    /// Namespace that is pre-filled for new annotations.
+    pub(crate) default_namespace: String,
+}
+
+impl EdgeAnnotationEditor {
+    /// Load the edges of the node, unless they have already been loaded.
+    pub(crate) fn load(&mut self, graph: &AnnotationGraph, node_name: Option<&str>) {
+        if self.node_name.as_deref() == node_name {
+            return;
+        }
+        *self = Self {
+            node_name: node_name.map(str::to_string),
+            new_key: new_annotation_key(&self.default_namespace),
+            default_namespace: std::mem::take(&mut self.default_namespace),
+            ..Default::default()
+        };
+        if let Some(node_name) = node_name {
+            match edges_of_node(graph, node_name) {
+                Ok(edges) => self.edges = edges,
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+    }
+
+    pub(crate) fn select_edge(&mut self, idx: Option<usize>) {
+        self.selected_edge = idx.filter(|idx| *idx < self.edges.len());
+        self.edited_values = self
+            .selected_edge
+            .map(|idx| self.edges[idx].labels.clone())
+            .unwrap_or_default();
+    }
+
+    /// Change the label of the selected edge in the local state and return
+    /// the change that needs to be applied to the graph.
+    pub(crate) fn set_label(
+        &mut self,
+        key: AnnoKey,
+        value: Option<String>,
+    ) -> Option<EdgeLabelChange> {
+        let edge = self.edges.get_mut(self.selected_edge?)?;
+        if let Some(value) = &value {
+            edge.labels.insert(key.clone(), value.clone());
+            self.edited_values.insert(key.clone(), value.clone());
+        } else {
+            edge.labels.remove(&key);
+            self.edited_values.remove(&key);
+        }
+        Some(EdgeLabelChange {
+            edge: edge.clone(),
+            key,
+            value,
+        })
+    }
+
+    /// Show the edges and the labels of the selected edge. Values of
+    /// annotations with a type in the layer settings must be valid for it.
+    pub(crate) fn show(&mut self, ui: &mut Ui, settings: &LayerSettings) -> Vec<EdgeLabelChange> {
+        let mut changes = Vec::new();
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        if self.edges.is_empty() {
+            ui.label("The selected node has no pointing or dominance edges.");
+            return changes;
+        }
+        let mut selected_edge = self.selected_edge;
+        ui.horizontal_wrapped(|ui| {
+            for (idx, edge) in self.edges.iter().enumerate() {
+                if ui
+                    .selectable_label(selected_edge == Some(idx), edge.description())
+                    .clicked()
+                {
+                    selected_edge = if selected_edge == Some(idx) {
+                        None
+                    } else {
+                        Some(idx)
+                    };
+                }
+            }
+        });
+        if selected_edge != self.selected_edge {
+            self.select_edge(selected_edge);
+        }
+        if self.selected_edge.is_none() {
+            return changes;
+        }
+
+        let mut updated_labels = Vec::new();
+        Grid::new("edge-annotations")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (key, value) in self.edited_values.iter_mut() {
+                    ui.label(qualified_name(key));
+                    let response = ui.text_edit_singleline(value);
+                    if response.lost_focus()
+                        && self
+                            .selected_edge
+                            .and_then(|idx| self.edges[idx].labels.get(key))
+                            != Some(&*value)
+                    {
+                        updated_labels.push((key.clone(), Some(value.clone())));
+                    }
+                    if ui
+                        .button(egui_phosphor::regular::TRASH)
+                        .on_hover_text("Delete annotation")
+                        .clicked()
+                    {
+                        updated_labels.push((key.clone(), None));
+                    }
+                    ui.end_row();
+                }
+                ui.add(TextEdit::singleline(&mut self.new_key).hint_text("ns:name"));
+                let new_value_type = settings.value_type(&parse_key(&self.new_key));
+                if new_value_type.is_some() {
+                    value_editor(ui, new_value_type, &mut self.new_value);
+                } else {
+                    ui.add(TextEdit::singleline(&mut self.new_value).hint_text("value"));
+                }
+                if ui
+                    .add_enabled(!self.new_key.trim().is_empty(), egui::Button::new("Add"))
+                    .clicked()
+                {
+                    updated_labels.push((parse_key(&self.new_key), Some(self.new_value.clone())));
+                    self.new_key = new_annotation_key(&self.default_namespace);
+                    self.new_value.clear();
+                }
+                ui.end_row();
+            });
+        for (key, value) in updated_labels {
+            if let Some(value) = &value {
+                if let Err(e) = settings.validate_value(&key, value) {
+                    self.error = Some(format!("{e:#}"));
+                    // Show the stored value again
+                    self.select_edge(self.selected_edge);
+                    continue;
+                }
+            }
+            self.error = None;
+            changes.extend(self.set_label(key, value));
+        }
+        if changes.is_empty() && self.edited_values.is_empty() {
+            ui.label(RichText::new("The edge has no annotations yet.").weak());
+        }
+        changes
+    }
+}
+
+fn qualified_name(key: &AnnoKey) -> String {
+    if key.ns.is_empty() {
+        key.name.to_string()
+    } else {
+        format!("{}:{}", key.ns, key.name)
+    }
+}
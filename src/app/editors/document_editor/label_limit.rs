@@ -0,0 +1,50 @@
+use egui::{DragValue, Ui};
+
+use super::DocumentEditor;
+
+const MAX_LABELS_ID: &str = "document-editor-max-labels";
+
+impl DocumentEditor {
+    /// Show the input for the maximum number of labels shown for each
+    /// token. The limit is persisted, so it also applies to other documents.
+    pub(super) fn show_label_limit(&mut self, ui: &mut Ui) {
+        let max_labels_id = egui::Id::new(MAX_LABELS_ID);
+        if self.layout_info.first_frame {
+            if let Some(max_labels) = ui.data_mut(|d| d.get_persisted(max_labels_id)) {
+                self.max_labels = max_labels;
+            }
+        }
+        let old_max_labels = self.max_labels;
+        ui.add(
+            DragValue::new(&mut self.max_labels)
+                .range(0..=100)
+                .custom_formatter(|n, _| {
+                    if n == 0.0 {
+                        "all".to_string()
+                    } else {
+                        n.to_string()
+                    }
+                }),
+        )
+        .on_hover_text("Maximum number of labels shown for each token");
+        ui.label("Labels");
+        if old_max_labels != self.max_labels {
+            ui.data_mut(|d| d.insert_persisted(max_labels_id, self.max_labels));
+            self.layout_info.valid = false;
+        }
+    }
+
+    /// The maximum number of labels shown for each token, if limited.
+    pub(super) fn label_limit(&self) -> Option<usize> {
+        (self.max_labels > 0).then_some(self.max_labels)
+    }
+
+    /// Invalidate the layout if the selection changed, because selected token
+    /// show all their labels and might have a different size.
+    pub(super) fn update_layout_selection(&mut self) {
+        if self.max_labels > 0 && self.layout_selection != self.selected_nodes {
+            self.layout_selection = self.selected_nodes.clone();
+            self.layout_info.valid = false;
+        }
+    }
+}
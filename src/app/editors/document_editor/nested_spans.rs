@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use egui::{
+    Align2, Color32, FontId, Pos2, Rangef, Rect, RichText, Sense, Stroke, TextEdit, Ui, Widget,
+    WidgetInfo,
+};
+use graphannis::{
+    graph::AnnoKey,
+    model::AnnotationComponentType,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::graph::ANNIS_NS;
+
+use super::{node_context_menu, DocumentEditor, EditorActions, StateUpdateFn};
+use crate::app::{
+    util::nested_spans::{nesting_depths, validate_nesting},
+    views::rename_annotation::parse_key,
+    widgets::{token_details, Token},
+};
+
+const NESTED_SPAN_KEY_ID: &str = "document-editor-nested-span-key";
+/// Height of each level of the nested span brackets above the token.
+const NESTED_SPAN_ROW_HEIGHT: f32 = 20.0;
+
+impl DocumentEditor {
+    /// Show the inputs for the key and the value of new nested spans. The
+    /// key is persisted, so the same spans are shown in other documents.
+    pub(super) fn show_nested_span_inputs(&mut self, ui: &mut Ui) {
+        let key_id = egui::Id::new(NESTED_SPAN_KEY_ID);
+        if self.layout_info.first_frame {
+            if let Some(key) = ui.data_mut(|d| d.get_persisted(key_id)) {
+                self.nested_span_key = key;
+            }
+        }
+        let old_key = self.nested_span_key.clone();
+        ui.horizontal(|ui| {
+            ui.label("Nested spans");
+            TextEdit::singleline(&mut self.nested_span_key)
+                .hint_text("Key, e.g. ner")
+                .desired_width(100.0)
+                .ui(ui);
+            TextEdit::singleline(&mut self.nested_span_value)
+                .hint_text("Value of new spans")
+                .desired_width(120.0)
+                .ui(ui);
+            if let Some(error) = &self.nested_span_error {
+                ui.label(RichText::new(egui_phosphor::regular::WARNING).color(Color32::RED))
+                    .on_hover_text(error);
+            }
+        });
+        if old_key != self.nested_span_key {
+            let key = self.nested_span_key.clone();
+            ui.data_mut(|d| d.insert_persisted(key_id, key));
+            self.nested_span_error = None;
+        }
+    }
+
+    /// The height needed to paint the nested spans above the token.
+    pub(super) fn nested_spans_height(&self) -> f32 {
+        let levels = self
+            .nested_spans()
+            .iter()
+            .map(|(_, depth)| depth + 1)
+            .max()
+            .unwrap_or(0);
+        levels as f32 * NESTED_SPAN_ROW_HEIGHT
+    }
+
+    /// The annotation key of the nested spans, if one has been entered.
+    pub(super) fn nested_span_key(&self) -> Option<AnnoKey> {
+        Some(self.nested_span_key.trim())
+            .filter(|k| !k.is_empty())
+            .map(parse_key)
+    }
+
+    /// The spans with the nested span key and how deep they are nested.
+    pub(super) fn nested_spans(&self) -> Vec<(&Token, usize)> {
+        let Some(key) = self.nested_span_key() else {
+            return Vec::new();
+        };
+        let spans: Vec<&Token> = self
+            .spans
+            .iter()
+            .filter(|t| t.labels.contains_key(&key))
+            .collect();
+        let ranges: Vec<_> = spans.iter().map(|t| (t.start, t.end)).collect();
+        spans.into_iter().zip(nesting_depths(&ranges)).collect()
+    }
+
+    /// Adds a span with the nested span key that covers the selected base
+    /// token, unless it would partially overlap another span with this key.
+    pub(super) fn add_nested_span_for_selection(&mut self) {
+        let Some(key) = self.nested_span_key() else {
+            self.nested_span_error =
+                Some("Enter the annotation key of the nested spans first".to_string());
+            return;
+        };
+        let selected: Vec<usize> = self
+            .selected_nodes
+            .iter()
+            .filter_map(|n| self.token_index_by_name.get(n))
+            .copied()
+            .collect();
+        let (Some(start), Some(end)) = (selected.iter().min(), selected.iter().max()) else {
+            return;
+        };
+        let existing: Vec<_> = self
+            .nested_spans()
+            .iter()
+            .map(|(t, _)| (t.start, t.end))
+            .collect();
+        if let Err(e) = validate_nesting(&existing, (*start, *end)) {
+            self.nested_span_error = Some(e);
+            return;
+        }
+        self.nested_span_error = None;
+        self.pending_actions.push(EditorActions::AddNestedSpan {
+            key,
+            value: self.nested_span_value.clone(),
+            covered_token: self.token[*start..=*end]
+                .iter()
+                .map(|t| t.node_name.clone())
+                .collect(),
+        });
+        self.apply_pending_updates_automatically();
+    }
+
+    /// Paints the nested spans as brackets above the token. Outer spans are
+    /// further away from the token than the spans nested in them.
+    pub(super) fn show_nested_spans(
+        &mut self,
+        ui: &mut Ui,
+        top: f32,
+        token_offset_to_rect: &[Option<Rect>],
+    ) {
+        let Some(key) = self.nested_span_key() else {
+            return;
+        };
+        let mut clicked = None;
+        let mut context_request = None;
+        let can_add_span = self.active_layer.is_some();
+        for (t, depth) in self.nested_spans() {
+            let mut covered_span = Rangef::NOTHING;
+            for token_rect in token_offset_to_rect
+                .iter()
+                .take(t.end + 1)
+                .skip(t.start)
+                .flatten()
+            {
+                covered_span.min = covered_span.min.min(token_rect.left());
+                covered_span.max = covered_span.max.max(token_rect.right());
+            }
+            if covered_span.span() <= 0.0 {
+                continue;
+            }
+            let row_top = top + depth as f32 * NESTED_SPAN_ROW_HEIGHT;
+            let rect = Rect::from_x_y_ranges(
+                covered_span.shrink(2.0),
+                row_top..=row_top + NESTED_SPAN_ROW_HEIGHT,
+            );
+            let selected = self.selected_nodes.contains(&t.node_name);
+            let stroke = if selected {
+                Stroke::new(2.0, ui.visuals().selection.stroke.color)
+            } else {
+                Stroke::new(1.0, ui.visuals().text_color())
+            };
+            let value = t.labels.get(&key).cloned().unwrap_or_default();
+            let painter = ui.painter();
+            let line_y = rect.bottom() - 4.0;
+            painter.line_segment(
+                [
+                    Pos2::new(rect.left(), line_y),
+                    Pos2::new(rect.right(), line_y),
+                ],
+                stroke,
+            );
+            for x in [rect.left(), rect.right()] {
+                painter.line_segment([Pos2::new(x, line_y), Pos2::new(x, rect.bottom())], stroke);
+            }
+            painter.text(
+                Pos2::new(rect.center().x, line_y - 1.0),
+                Align2::CENTER_BOTTOM,
+                &value,
+                FontId::proportional(12.0),
+                stroke.color,
+            );
+            let widget_label = format!(
+                "Nested span {value} ranging from {} to {} ({})",
+                t.start, t.end, t.node_name
+            );
+            let response = ui.interact(
+                rect,
+                ui.id().with(("nested-span", &t.node_name)),
+                Sense::click(),
+            );
+            let response = if self.token_tooltips {
+                response.on_hover_ui(|ui| token_details(ui, t))
+            } else {
+                response.on_hover_text(format!("{}={value}", self.nested_span_key.trim()))
+            };
+            response.widget_info(|| {
+                WidgetInfo::labeled(egui::WidgetType::Other, true, widget_label.clone())
+            });
+            if response.clicked() {
+                clicked = Some(t.node_name.clone());
+            }
+            response.context_menu(|ui| {
+                if let Some(action) = node_context_menu(ui, &t.node_name, false, can_add_span) {
+                    context_request = Some((t.node_name.clone(), action));
+                }
+            });
+        }
+        if let Some(node_name) = clicked {
+            self.selected_nodes.clear();
+            self.selected_nodes.insert(node_name);
+        }
+        if context_request.is_some() {
+            self.context_menu_request = context_request;
+        }
+    }
+}
+
+/// Adds a span with the annotation to the document that covers the token.
+pub(super) fn apply_add_nested_span(
+    graph: &AnnotationGraph,
+    parent_name: &str,
+    updates: &mut GraphUpdate,
+    key: AnnoKey,
+    value: String,
+    covered_token: Vec<String>,
+) -> anyhow::Result<StateUpdateFn> {
+    let new_node_name = format!(
+        "{}#{}",
+        &parent_name,
+        graph
+            .get_node_annos()
+            .get_largest_item()?
+            .map(|id| id + 1)
+            .unwrap_or_default()
+    );
+    updates.add_event(UpdateEvent::AddNode {
+        node_name: new_node_name.clone(),
+        node_type: "node".to_string(),
+    })?;
+    updates.add_event(UpdateEvent::AddEdge {
+        source_node: new_node_name.clone(),
+        target_node: parent_name.to_string(),
+        layer: ANNIS_NS.to_string(),
+        component_type: AnnotationComponentType::PartOf.to_string(),
+        component_name: "".to_string(),
+    })?;
+    updates.add_event(UpdateEvent::AddNodeLabel {
+        node_name: new_node_name.clone(),
+        anno_ns: key.ns.to_string(),
+        anno_name: key.name.to_string(),
+        anno_value: value.clone(),
+    })?;
+    for target_node in &covered_token {
+        updates.add_event(UpdateEvent::AddEdge {
+            source_node: new_node_name.clone(),
+            target_node: target_node.clone(),
+            layer: "".to_string(),
+            component_type: AnnotationComponentType::Coverage.to_string(),
+            component_name: "".to_string(),
+        })?;
+    }
+
+    let state_updater = Box::new(move |editor: &mut DocumentEditor| {
+        let covered: Vec<usize> = covered_token
+            .iter()
+            .filter_map(|n| editor.token_index_by_name.get(n))
+            .copied()
+            .collect();
+        if let (Some(start), Some(end)) = (covered.iter().min(), covered.iter().max()) {
+            let mut labels = BTreeMap::new();
+            labels.insert(key, value);
+            editor.spans.push(Token {
+                node_name: new_node_name,
+                start: *start,
+                end: *end,
+                labels,
+            });
+            editor
+                .spans
+                .sort_by_key(|t| (t.start, std::cmp::Reverse(t.end)));
+        }
+    });
+    Ok(state_updater)
+}
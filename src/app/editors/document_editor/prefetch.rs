@@ -0,0 +1,92 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use egui::mutex::{Mutex, RwLock};
+use graphannis::{graph::NodeID, AnnotationGraph};
+
+use crate::{api::CorpusCache, app::util::corpus_structure::neighbour_documents};
+
+use super::DocumentContent;
+
+#[derive(Default)]
+struct InnerPrefetch {
+    /// The generation of the corpus cache the content has been loaded from.
+    generation: u64,
+    documents: HashMap<NodeID, DocumentContent>,
+}
+
+impl InnerPrefetch {
+    /// Discard the content loaded from an older version of the graph.
+    fn discard_outdated(&mut self, generation: u64) {
+        if self.generation != generation {
+            self.generation = generation;
+            self.documents.clear();
+        }
+    }
+}
+
+/// Content of documents that has been loaded in the background before the
+/// user opens them.
+#[derive(Clone, Default)]
+pub(crate) struct DocumentPrefetch {
+    inner: Arc<Mutex<InnerPrefetch>>,
+}
+
+impl DocumentPrefetch {
+    /// Remove the prefetched content of the document from the cache and
+    /// return it, unless the cached graph has changed since it was loaded.
+    pub(crate) fn take(&self, document: NodeID, cache: &CorpusCache) -> Option<DocumentContent> {
+        let mut inner = self.inner.lock();
+        inner.discard_outdated(cache.generation());
+        inner.documents.remove(&document)
+    }
+
+    /// Load the documents before and after the given document in the corpus
+    /// tree in a background thread.
+    pub(crate) fn prefetch_neighbours(
+        &self,
+        document: NodeID,
+        graph: Arc<RwLock<AnnotationGraph>>,
+        cache: CorpusCache,
+    ) {
+        let prefetch = self.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = prefetch.load_neighbours(document, &graph, &cache) {
+                log::warn!("Could not prefetch neighbouring documents: {e:?}");
+            }
+        });
+    }
+
+    pub(crate) fn load_neighbours(
+        &self,
+        document: NodeID,
+        graph: &RwLock<AnnotationGraph>,
+        cache: &CorpusCache,
+    ) -> Result<()> {
+        let graph = graph.read();
+        let generation = cache.generation();
+        self.inner.lock().discard_outdated(generation);
+        let (previous, next) = neighbour_documents(&graph, document)?;
+        let neighbours: Vec<_> = [previous, next].into_iter().flatten().collect();
+
+        // Only keep the content of the documents that are likely opened next
+        self.inner
+            .lock()
+            .documents
+            .retain(|d, _| neighbours.contains(d));
+
+        for neighbour in neighbours {
+            if self.inner.lock().documents.contains_key(&neighbour) {
+                continue;
+            }
+            let content = DocumentContent::load(neighbour, &graph)?;
+            let mut inner = self.inner.lock();
+            if cache.generation() != generation {
+                // The graph has changed in the meantime
+                return Ok(());
+            }
+            inner.documents.insert(neighbour, content);
+        }
+        Ok(())
+    }
+}
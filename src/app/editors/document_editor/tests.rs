@@ -1,24 +1,59 @@
-use std::sync::Arc;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use anyhow::Context;
-use egui::{accesskit::Role, mutex::RwLock, Vec2};
+use egui::{accesskit::Role, mutex::RwLock, ColorImage, Vec2};
 use egui_kittest::{
     kittest::{Key, Node, Queryable},
     Harness,
 };
-use graphannis::model::AnnotationComponentType;
+use graphannis::{
+    graph::AnnoKey,
+    model::AnnotationComponentType,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::graph::NODE_NAME_KEY;
 
 use crate::{
+    api::{
+        token_helper::{TokenHelper, TOKEN_KEY},
+        CorpusCache,
+    },
     app::{
         set_fonts,
         tests::{
             create_app_with_corpus, create_test_harness, wait_for_editor, wait_until_jobs_finished,
         },
+        util::{
+            annis_config::LayerSettings,
+            example_generator,
+            media::{AudioPlayback, FrameStream, MediaBackend},
+            time_annotation::TIME_KEY,
+            tokenizer::TokenizerKind,
+            value_type::ValueType,
+        },
+        widgets::{label_rank, Token},
     },
     assert_screenshots,
 };
 
-use super::{DocumentEditor, Editor, JobExecutor};
+use super::{
+    apply_actions,
+    duplicate_spans::find_duplicate_spans,
+    edge_annotations::{edges_of_node, EdgeAnnotationEditor},
+    prefetch::DocumentPrefetch,
+    time_editing::TimeEditor,
+    token_filter::{FilterMode, DIMMED_OPACITY},
+    video::MediaPlayer,
+    visible_span_range, whitespace_gap, ContextMenuAction, Deletion, DocumentEditor, Editor,
+    EditorActions, JobExecutor, TokenHeader, TokenPattern, LAYER_NAMES_ID,
+};
 
 fn create_example_ui(
     graphml: &[u8],
@@ -84,6 +119,49 @@ fn select_token_range() {
     assert_screenshots![r1, r2];
 }
 
+#[test]
+fn show_token_details_on_hover() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness
+        .ctx
+        .style_mut(|style| style.interaction.tooltip_delay = 0.0);
+    editor.write().set_token_tooltips(true);
+    harness.run();
+    harness
+        .get_by_label_contains("Token ranging from 1 to 1")
+        .hover();
+    harness.run();
+
+    // The tooltip also lists the annotations of the ANNIS namespace
+    harness.get_by_label("Covers token 1");
+    harness.get_by_label("annis:tok");
+    assert!(editor.read().selected_nodes.is_empty());
+}
+
+#[test]
+fn select_token_by_pattern() {
+    let (_harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    let mut editor = editor.write();
+
+    let pattern = TokenPattern::parse("pos=\"NN\"").unwrap();
+    assert_eq!(2, editor.select_by_pattern(&pattern, None));
+    assert!(editor.selected_nodes.contains("single_sentence/zossen#t2"));
+    assert!(editor.selected_nodes.contains("single_sentence/zossen#t7"));
+
+    let pattern = TokenPattern::parse("/[A-Z].*/").unwrap();
+    assert_eq!(3, editor.select_by_pattern(&pattern, None));
+
+    let pattern = TokenPattern::parse("pos=/V.*/").unwrap();
+    assert_eq!(0, editor.select_by_pattern(&pattern, Some("missing")));
+    assert!(editor.selected_nodes.is_empty());
+}
+
 #[test]
 fn render_segmentation_spans() {
     let (mut harness, _) = create_example_ui(
@@ -126,6 +204,109 @@ fn change_segmentation_value() {
     harness.snapshot("change_segmentation_value");
 }
 
+#[test]
+fn commit_and_cancel_span_value() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.set_size(Vec2::new(2100.0, 210.0));
+    harness.run();
+    editor.write().set_manual_commit(true);
+    let start_editing = || {
+        let mut editor = editor.write();
+        editor.selected_nodes.clear();
+        let first_token = editor.token[0].node_name.clone();
+        editor.selected_nodes.insert(first_token);
+        editor.edit_active_layer_span();
+        editor.current_edited_value.clone()
+    };
+    let key_event = |key| egui::Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: egui::Modifiers::NONE,
+    };
+
+    // Escape discards the changes
+    let value = start_editing();
+    harness.run();
+    get_text_input(&harness, &value).type_text("x");
+    harness.run();
+    get_text_input(&harness, &format!("{value}x")).key_press(Key::Escape);
+    harness.run();
+    assert!(editor.read().currently_edited_node.is_none());
+    assert_eq!(0, editor.read().pending_update_count());
+
+    // Enter only confirms the composed text of an input method
+    start_editing();
+    harness.run();
+    harness.input_mut().events.extend([
+        egui::Event::Ime(egui::ImeEvent::Enabled),
+        egui::Event::Ime(egui::ImeEvent::Preedit("日".to_string())),
+    ]);
+    harness.run();
+    assert!(editor.read().ime_composing);
+    harness.input_mut().events.push(key_event(egui::Key::Enter));
+    harness.run();
+    assert!(editor.read().currently_edited_node.is_some());
+    harness.input_mut().events.extend([
+        egui::Event::Ime(egui::ImeEvent::Commit("日本".to_string())),
+        egui::Event::Ime(egui::ImeEvent::Disabled),
+    ]);
+    harness.run();
+    assert!(!editor.read().ime_composing);
+    assert!(editor.read().currently_edited_node.is_some());
+
+    // Enter commits the value
+    harness.input_mut().events.push(key_event(egui::Key::Enter));
+    harness.run();
+    assert!(editor.read().currently_edited_node.is_none());
+    assert_eq!(1, editor.read().pending_update_count());
+}
+
+#[test]
+fn multiline_span_values() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.set_size(Vec2::new(2100.0, 210.0));
+    harness.run();
+    {
+        let mut editor = editor.write();
+        editor.set_manual_commit(true);
+        editor.multiline_layers.insert("diplomatic".to_string());
+        let first_token = editor.token[0].node_name.clone();
+        editor.selected_nodes.insert(first_token);
+        editor.edit_active_layer_span();
+        editor.current_edited_value = "first\nsecond".to_string();
+    }
+    harness.run();
+    let shift_enter = egui::Event::Key {
+        key: egui::Key::Enter,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: egui::Modifiers::SHIFT,
+    };
+    harness.input_mut().events.push(shift_enter.clone());
+    harness.run();
+    {
+        let editor = editor.read();
+        assert!(editor.currently_edited_node.is_some());
+        assert_eq!(2, editor.current_edited_value.matches('\n').count());
+    }
+    harness.input_mut().events.push(egui::Event::Key {
+        modifiers: egui::Modifiers::NONE,
+        ..shift_enter
+    });
+    harness.run();
+    assert!(editor.read().currently_edited_node.is_none());
+    assert_eq!(1, editor.read().pending_update_count());
+}
+
 #[test]
 fn delete_and_add_segmentation() {
     let app_state = create_app_with_corpus(
@@ -210,3 +391,1854 @@ fn get_text_input<'a>(harness: &'a Harness<'_>, value: &'a str) -> Node<'a> {
         .context(format!("Missing text input with value \"{value}\""))
         .unwrap()
 }
+
+#[test]
+fn toggle_label_visibility() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    let pos_key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "pos".into(),
+    };
+    assert_eq!(Some(&8), editor.read().label_counts.get(&pos_key));
+
+    harness.get_by_label("Document statistics").click();
+    harness.run();
+    harness.get_by_label("default_ns:pos (8)").click();
+    harness.run();
+    assert!(editor.read().hidden_labels.contains(&pos_key));
+    assert!(harness.query_by_label("NN").is_none());
+
+    harness.get_by_label("default_ns:pos (8)").click();
+    harness.run();
+    assert!(editor.read().hidden_labels.is_empty());
+}
+
+#[test]
+fn group_and_order_labels_by_namespace() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    let pos_key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "pos".into(),
+    };
+    let mut editor = editor.write();
+    let groups = editor.labels_by_namespace();
+    assert_eq!(Some(&vec![pos_key.clone()]), groups.get("default_ns"));
+
+    // Hide and show all labels of a namespace
+    editor.toggle_namespace_visibility("default_ns");
+    assert!(editor.hidden_labels.contains(&pos_key));
+    editor.toggle_namespace_visibility("default_ns");
+    assert!(!editor.hidden_labels.contains(&pos_key));
+
+    // Change the position of a label
+    editor.move_label(pos_key.clone(), false);
+    assert_eq!(Some(&pos_key), editor.label_order.last());
+    assert!(!editor.layout_info.valid);
+    editor.move_label(pos_key.clone(), true);
+    assert_eq!(1, editor.label_order.len());
+    assert_eq!(0, label_rank(&editor.label_order, &pos_key));
+    drop(editor);
+    harness.run();
+}
+
+#[test]
+fn insert_text_after_token() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    harness.set_size(Vec2::new(1200.0, 600.0));
+    harness.run();
+
+    // Open the document editor
+    harness.get_by_label("single_sentence").click();
+    wait_for_editor(&mut harness, app_state.clone());
+    harness.get_by_label("single_sentence/zossen").click();
+    harness.run();
+    harness.get_by_label("Open selected in editor").click();
+    harness.run();
+    wait_for_editor(&mut harness, app_state.clone());
+
+    {
+        let mut app_state = app_state.write();
+        let editor = app_state
+            .current_editor
+            .get_mut()
+            .unwrap()
+            .any_mut()
+            .downcast_mut::<DocumentEditor>()
+            .unwrap();
+        // Insert after "Zossen"
+        editor.selected_nodes.clear();
+        editor
+            .selected_nodes
+            .insert("single_sentence/zossen#t4".to_string());
+        editor.insert_text_after_selection("(Brandenburg)");
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    wait_for_editor(&mut harness, app_state.clone());
+
+    let mut app_state = app_state.write();
+    let editor = app_state
+        .current_editor
+        .get_mut()
+        .unwrap()
+        .any_mut()
+        .downcast_mut::<DocumentEditor>()
+        .unwrap();
+    let token_values: Vec<_> = editor
+        .token
+        .iter()
+        .map(|t| t.labels.get(&TOKEN_KEY).cloned().unwrap_or_default())
+        .collect();
+    assert_eq!(
+        vec![
+            "Die",
+            "Jugendlichen",
+            "in",
+            "Zossen",
+            "(",
+            "Brandenburg",
+            ")",
+            "wollen",
+            "ein",
+            "Musikcafé",
+            "."
+        ],
+        token_values
+    );
+}
+
+#[test]
+fn whitespace_around_inserted_token() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let mut graph = AnnotationGraph::with_default_graphstorages(false).unwrap();
+    graph.apply_update(&mut updates, |_| {}).unwrap();
+
+    // The whitespace after the insertion point moves behind the new token
+    for (after_token, text) in [("root/doc1#tok1", "big"), ("root/doc1#tok9", " wide")] {
+        let mut update = GraphUpdate::new();
+        EditorActions::InsertToken {
+            after_token: Some(after_token.to_string()),
+            text: text.to_string(),
+            tokenizer: TokenizerKind::default(),
+        }
+        .apply(&graph, "root/doc1", &mut update)
+        .unwrap();
+        graph.apply_update(&mut update, |_| {}).unwrap();
+    }
+
+    let tok_helper = TokenHelper::new(&graph).unwrap();
+    let node_annos = graph.get_node_annos();
+    let whitespace_key = AnnoKey {
+        ns: "annis".into(),
+        name: "tok-whitespace-after".into(),
+    };
+    let mut text = String::new();
+    for t in tok_helper.get_ordered_token("root/doc1", None).unwrap() {
+        text.push_str(
+            &node_annos
+                .get_value_for_item(&t, &TOKEN_KEY)
+                .unwrap()
+                .unwrap(),
+        );
+        if let Some(ws) = node_annos.get_value_for_item(&t, &whitespace_key).unwrap() {
+            text.push_str(&ws);
+        }
+    }
+    assert_eq!(
+        "Is this big example more complicated than it appears to be wide?",
+        text
+    );
+}
+
+#[test]
+fn split_document() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    harness.set_size(Vec2::new(1200.0, 600.0));
+    harness.run();
+
+    harness.get_by_label("single_sentence").click();
+    wait_for_editor(&mut harness, app_state.clone());
+    harness.get_by_label("single_sentence/zossen").click();
+    harness.run();
+    harness.get_by_label("Open selected in editor").click();
+    harness.run();
+    wait_for_editor(&mut harness, app_state.clone());
+
+    {
+        let mut app_state = app_state.write();
+        let editor = app_state
+            .current_editor
+            .get_mut()
+            .unwrap()
+            .any_mut()
+            .downcast_mut::<DocumentEditor>()
+            .unwrap();
+        // Split before "wollen"
+        editor.selected_nodes.clear();
+        editor
+            .selected_nodes
+            .insert("single_sentence/zossen#t5".to_string());
+        editor.split_document_at_selection();
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    wait_for_editor(&mut harness, app_state.clone());
+
+    let mut app_state = app_state.write();
+    let editor = app_state
+        .current_editor
+        .get_mut()
+        .unwrap()
+        .any_mut()
+        .downcast_mut::<DocumentEditor>()
+        .unwrap();
+    assert_eq!(4, editor.token.len());
+
+    let graph = editor.graph.read();
+    let tok_helper = TokenHelper::new(&graph).unwrap();
+    let new_document_token = tok_helper
+        .get_ordered_token("single_sentence/zossen_2", None)
+        .unwrap();
+    assert_eq!(
+        "wollen ein Musikcafé .",
+        tok_helper.spanned_text(&new_document_token).unwrap()
+    );
+}
+
+#[test]
+fn edit_span_on_active_layer() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.set_size(Vec2::new(2100.0, 210.0));
+    harness.run();
+    assert_eq!(Some("diplomatic"), editor.read().active_layer.as_deref());
+
+    {
+        let mut editor = editor.write();
+        editor.active_layer = Some("norm".to_string());
+        let first_token = editor.token[0].node_name.clone();
+        editor.selected_nodes.insert(first_token);
+        editor.edit_active_layer_span();
+    }
+    harness.run();
+
+    let editor = editor.read();
+    let expected_span = editor.segmentations["norm"]
+        .iter()
+        .find(|t| t.start == 0)
+        .map(|t| t.node_name.clone());
+    assert!(expected_span.is_some());
+    assert_eq!(expected_span, editor.currently_edited_node);
+}
+
+/// An editor for the example document with the "seg" segmentation layer as
+/// active layer.
+fn create_segmentation_editor() -> DocumentEditor {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let mut graph = AnnotationGraph::with_default_graphstorages(false).unwrap();
+    graph.apply_update(&mut updates, |_| {}).unwrap();
+    let document = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let mut editor = DocumentEditor::create_from_graph(
+        document,
+        Arc::new(RwLock::new(graph)),
+        JobExecutor::default(),
+    )
+    .unwrap();
+    editor.active_layer = Some("seg".to_string());
+    editor
+}
+
+fn select_nodes(editor: &mut DocumentEditor, node_names: &[&str]) {
+    editor.selected_nodes = node_names.iter().map(|n| n.to_string()).collect();
+}
+
+/// Apply the action to the graph of the editor.
+fn apply_action(editor: &DocumentEditor, action: EditorActions) {
+    let mut updates = GraphUpdate::new();
+    action
+        .apply(&editor.graph.read(), &editor.parent_name, &mut updates)
+        .unwrap();
+    editor
+        .graph
+        .write()
+        .apply_update(&mut updates, |_| {})
+        .unwrap();
+}
+
+/// The names of the base token covered by the node.
+fn covered_token_names(graph: &AnnotationGraph, node_name: &str) -> Vec<String> {
+    let node_annos = graph.get_node_annos();
+    let node = node_annos
+        .get_node_id_from_name(node_name)
+        .unwrap()
+        .unwrap();
+    let mut covered = TokenHelper::new(graph)
+        .unwrap()
+        .covered_token(node)
+        .unwrap();
+    TokenHelper::new(graph)
+        .unwrap()
+        .sort_token(&mut covered, None)
+        .unwrap();
+    covered
+        .into_iter()
+        .map(|t| {
+            node_annos
+                .get_value_for_item(&t, &NODE_NAME_KEY)
+                .unwrap()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn split_span_of_active_layer() {
+    let mut editor = create_segmentation_editor();
+    // The first token of a span can not start a new span
+    select_nodes(&mut editor, &["root/doc1#tok1"]);
+    assert!(editor.split_span_action().is_none());
+    select_nodes(&mut editor, &["root/doc1#tok2"]);
+    let action = editor.split_span_action();
+    assert!(matches!(
+        &action,
+        Some(EditorActions::SplitSpan { node_name, first_token_of_new_span, .. })
+            if node_name == "root/doc1#seg1" && first_token_of_new_span == "root/doc1#tok2"
+    ));
+    apply_action(&editor, action.unwrap());
+
+    let graph = editor.graph.read();
+    assert_eq!(
+        vec!["root/doc1#tok1"],
+        covered_token_names(&graph, "root/doc1#seg1")
+    );
+    let new_span = ordering_successor(&graph, "seg", "root/doc1#seg1").unwrap();
+    assert_eq!(
+        vec!["root/doc1#tok2", "root/doc1#tok3"],
+        covered_token_names(&graph, &new_span)
+    );
+    assert_eq!(
+        Some("root/doc1#seg2".to_string()),
+        ordering_successor(&graph, "seg", &new_span)
+    );
+}
+
+#[test]
+fn merge_spans_of_active_layer() {
+    let mut editor = create_segmentation_editor();
+    select_nodes(&mut editor, &["root/doc1#seg2"]);
+    assert!(editor.merge_spans_action().is_none());
+    // Spans are merged if any of their token is selected
+    select_nodes(&mut editor, &["root/doc1#seg2", "root/doc1#tok5"]);
+    let action = editor.merge_spans_action();
+    assert!(matches!(
+        &action,
+        Some(EditorActions::MergeSpans { node_names, value })
+            if node_names == &["root/doc1#seg2", "root/doc1#seg3"] && value == "more complicated"
+    ));
+    apply_action(&editor, action.unwrap());
+
+    let graph = editor.graph.read();
+    let node_annos = graph.get_node_annos();
+    assert_eq!(
+        None,
+        node_annos.get_node_id_from_name("root/doc1#seg3").unwrap()
+    );
+    assert_eq!(
+        vec!["root/doc1#tok4", "root/doc1#tok5"],
+        covered_token_names(&graph, "root/doc1#seg2")
+    );
+    let merged = node_annos
+        .get_node_id_from_name("root/doc1#seg2")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Some("more complicated"),
+        node_annos
+            .get_value_for_item(&merged, &TOKEN_KEY)
+            .unwrap()
+            .as_deref()
+    );
+    assert_eq!(
+        Some("root/doc1#seg2".to_string()),
+        ordering_successor(&graph, "seg", "root/doc1#seg1")
+    );
+    assert_eq!(None, ordering_successor(&graph, "seg", "root/doc1#seg2"));
+}
+
+#[test]
+fn extend_span_of_active_layer() {
+    let mut editor = create_segmentation_editor();
+    // The selection overlaps with two spans
+    select_nodes(&mut editor, &["root/doc1#seg2", "root/doc1#tok3"]);
+    assert!(editor.extend_span_action().is_none());
+    select_nodes(&mut editor, &["root/doc1#seg3"]);
+    assert!(editor.extend_span_action().is_none());
+    // The token between the span and the selected token are covered as well
+    select_nodes(&mut editor, &["root/doc1#seg3", "root/doc1#tok7"]);
+    let action = editor.extend_span_action();
+    assert!(matches!(
+        &action,
+        Some(EditorActions::ExtendSpan { node_name, covered_token })
+            if node_name == "root/doc1#seg3"
+                && covered_token == &["root/doc1#tok6", "root/doc1#tok7"]
+    ));
+    apply_action(&editor, action.unwrap());
+
+    let graph = editor.graph.read();
+    assert_eq!(
+        vec!["root/doc1#tok5", "root/doc1#tok6", "root/doc1#tok7"],
+        covered_token_names(&graph, "root/doc1#seg3")
+    );
+}
+
+#[test]
+fn duplicate_span_to_other_layer() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.run();
+
+    let mut editor = editor.write();
+    editor.set_manual_commit(true);
+    editor.create_layer("gloss");
+    let span = editor.segmentations["norm"][0].clone();
+    let value = span.labels[&TOKEN_KEY].clone();
+    assert!(!value.is_empty());
+    editor.select([span.node_name.as_str()]);
+
+    // Spans can not be duplicated to their own layer
+    editor.duplicate_selected_span("norm");
+    assert_eq!(0, editor.pending_update_count());
+
+    editor.duplicate_selected_span("gloss");
+    assert_eq!(1, editor.pending_update_count());
+    let action = editor.pending_actions.pop().unwrap();
+    let mut update = GraphUpdate::new();
+    action
+        .apply(
+            &editor.graph.read(),
+            "SegmentationWithGaps/doc01",
+            &mut update,
+        )
+        .unwrap();
+    editor
+        .graph
+        .write()
+        .apply_update(&mut update, |_| {})
+        .unwrap();
+
+    let graph = editor.graph.read();
+    let node_annos = graph.get_node_annos();
+    let duplicates: Vec<_> = node_annos
+        .exact_anno_search(
+            Some("annis"),
+            "gloss",
+            graphannis_core::annostorage::ValueSearch::Any,
+        )
+        .map(|m| m.unwrap().node)
+        .collect();
+    assert_eq!(1, duplicates.len());
+    assert_eq!(
+        Some(value.as_str()),
+        node_annos
+            .get_value_for_item(&duplicates[0], &TOKEN_KEY)
+            .unwrap()
+            .as_deref()
+    );
+    let tok_helper = TokenHelper::new(&graph).unwrap();
+    let covered = tok_helper.covered_token(duplicates[0]).unwrap();
+    assert_eq!(span.end - span.start + 1, covered.len());
+}
+
+#[test]
+fn show_node_names_in_token_header() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    assert!(harness.query_by_label("t4").is_none());
+
+    editor.write().token_header = TokenHeader::ShortId;
+    harness.run();
+    assert!(harness.query_by_label("t4").is_some());
+
+    editor.write().token_header = TokenHeader::NodeName;
+    harness.run();
+    assert!(harness
+        .query_by_label("single_sentence/zossen#t4")
+        .is_some());
+}
+
+#[test]
+fn validate_time_range() {
+    let mut editor = TimeEditor {
+        node_name: "n".to_string(),
+        start: 1.0,
+        end: 2.0,
+        previous_end: Some(0.5),
+        next_start: Some(2.5),
+    };
+    assert!(editor.validate().is_ok());
+    editor.start = 2.0;
+    assert!(editor.validate().is_err());
+    editor.start = 0.4;
+    assert!(editor.validate().is_err());
+    editor.start = 1.0;
+    editor.end = 3.0;
+    assert!(editor.validate().is_err());
+}
+
+#[test]
+fn set_time_range_of_token() {
+    let (mut graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let mut update = GraphUpdate::new();
+    for (i, t) in ["t1", "t2", "t3"].iter().enumerate() {
+        update
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: format!("single_sentence/zossen#{t}"),
+                anno_ns: "annis".to_string(),
+                anno_name: "time".to_string(),
+                anno_value: format!("{i}-{}", i + 1),
+            })
+            .unwrap();
+    }
+    graph.apply_update(&mut update, |_| {}).unwrap();
+
+    let mut update = GraphUpdate::new();
+    EditorActions::SetTimeRange {
+        node_name: "single_sentence/zossen#t2".to_string(),
+        start: 1.25,
+        end: 1.75,
+    }
+    .apply(&graph, "single_sentence/zossen", &mut update)
+    .unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+
+    let node_annos = graph.get_node_annos();
+    let t2 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t2")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "1.25-1.75",
+        node_annos
+            .get_value_for_item(&t2, &TIME_KEY)
+            .unwrap()
+            .unwrap()
+    );
+}
+
+#[test]
+fn seek_selects_segment_of_active_layer() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.run();
+
+    let mut editor = editor.write();
+    editor.active_layer = Some("norm".to_string());
+    editor.seek(1.5, 0);
+    assert_eq!(Some(1.5), editor.playback_position);
+    let expected_span = editor.segmentations["norm"]
+        .iter()
+        .find(|t| t.start == 0)
+        .map(|t| t.node_name.clone())
+        .unwrap();
+    assert!(editor.selected_nodes.contains(&expected_span));
+    assert_eq!(1, editor.selected_nodes.len());
+}
+
+#[test]
+fn highlight_token_at_playback_position() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+
+    let mut editor = editor.write();
+    for (i, t) in editor.token.iter_mut().enumerate() {
+        t.labels
+            .insert(TIME_KEY.as_ref().clone(), format!("{i}-{}", i + 1));
+    }
+    editor.has_time_annotations = true;
+    assert_eq!(8.0, editor.media_duration());
+
+    editor.playback_position = Some(2.5);
+    let at_position: Vec<_> = editor
+        .token
+        .iter()
+        .filter(|t| editor.is_at_playback_position(t))
+        .map(|t| t.node_name.clone())
+        .collect();
+    assert_eq!(vec!["single_sentence/zossen#t3".to_string()], at_position);
+
+    // Stepping is limited by the media duration
+    editor.step_media(10.0);
+    assert_eq!(Some(8.0), editor.playback_position);
+}
+
+/// Creates an endless stream of single-colored frames and records the
+/// positions the video and audio are started at.
+#[derive(Default)]
+struct FakeMediaBackend {
+    video_positions: egui::mutex::Mutex<Vec<f64>>,
+    audio_positions: egui::mutex::Mutex<Vec<f64>>,
+    decoded_frames: Arc<AtomicUsize>,
+}
+
+struct FakeFrames {
+    decoded_frames: Arc<AtomicUsize>,
+}
+
+impl FrameStream for FakeFrames {
+    fn next_frame(&mut self) -> anyhow::Result<Option<ColorImage>> {
+        self.decoded_frames.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(ColorImage::new([4, 3], egui::Color32::RED)))
+    }
+}
+
+impl MediaBackend for FakeMediaBackend {
+    fn decode_video(
+        &self,
+        video: &std::path::Path,
+        position: f64,
+        _frame_rate: f64,
+    ) -> anyhow::Result<Box<dyn FrameStream>> {
+        if video.ends_with("broken.mp4") {
+            anyhow::bail!("Broken video");
+        }
+        self.video_positions.lock().push(position);
+        Ok(Box::new(FakeFrames {
+            decoded_frames: self.decoded_frames.clone(),
+        }))
+    }
+
+    fn play_audio(&self, _media: &std::path::Path, position: f64) -> anyhow::Result<AudioPlayback> {
+        self.audio_positions.lock().push(position);
+        Ok(AudioPlayback::default())
+    }
+}
+
+#[test]
+fn play_media_at_playback_position() {
+    let backend = Arc::new(FakeMediaBackend::default());
+    let jobs = JobExecutor::default();
+    let position = Arc::new(RwLock::new((1.0, false)));
+    let players = Arc::new(RwLock::new(vec![
+        MediaPlayer::new("example.mp4".into(), backend.clone(), true, true),
+        MediaPlayer::new("broken.mp4".into(), backend.clone(), true, false),
+    ]));
+    let position_for_closure = position.clone();
+    let mut harness = Harness::builder().build_ui(move |ui| {
+        let (position, playing) = *position_for_closure.read();
+        for player in players.write().iter_mut() {
+            player.update(ui.ctx(), &jobs, position, playing);
+            player.show(ui);
+        }
+    });
+    harness.step();
+    // Both frames are shown once they have been decoded
+    wait_until(&mut harness, |h| {
+        h.query_by_label("Broken video").is_some()
+            && h.query_all_by_label("Loading video").next().is_none()
+    });
+    assert_eq!(vec![1.0], *backend.video_positions.lock());
+    assert!(backend.audio_positions.lock().is_empty());
+
+    // The following frames are decoded from the same stream
+    *position.write() = (1.08, false);
+    wait_until(&mut harness, |_| {
+        backend.decoded_frames.load(Ordering::Relaxed) == 3
+    });
+    assert_eq!(vec![1.0], *backend.video_positions.lock());
+
+    // Seeking far ahead decodes the video from the new position, the audio
+    // is only played while playing
+    *position.write() = (10.0, true);
+    wait_until(&mut harness, |_| {
+        backend.video_positions.lock().len() == 2 && !backend.audio_positions.lock().is_empty()
+    });
+    assert_eq!(vec![1.0, 10.0], *backend.video_positions.lock());
+    assert_eq!(Some(&10.0), backend.audio_positions.lock().first());
+}
+
+fn wait_until(harness: &mut Harness<'_>, mut condition: impl FnMut(&Harness) -> bool) {
+    for _ in 0..100 {
+        if condition(harness) {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        harness.step();
+    }
+    panic!("Condition not reached");
+}
+
+#[test]
+fn prefetch_neighbouring_documents() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    updates
+        .add_event(UpdateEvent::AddNode {
+            node_name: "root/doc2".to_string(),
+            node_type: "corpus".to_string(),
+        })
+        .unwrap();
+    updates
+        .add_event(UpdateEvent::AddEdge {
+            source_node: "root/doc2".to_string(),
+            target_node: "root".to_string(),
+            layer: "annis".to_string(),
+            component_type: "PartOf".to_string(),
+            component_name: "".to_string(),
+        })
+        .unwrap();
+    example_generator::create_tokens(&mut updates, Some("root/doc2"));
+    let graph = example_generator::create_graph(&mut updates);
+
+    let node_id = |name: &str| {
+        graph
+            .get_node_annos()
+            .get_node_id_from_name(name)
+            .unwrap()
+            .unwrap()
+    };
+    let doc1 = node_id("root/doc1");
+    let doc2 = node_id("root/doc2");
+    let graph = Arc::new(RwLock::new(graph));
+
+    let cache = CorpusCache::default();
+    let prefetch = DocumentPrefetch::default();
+    prefetch.load_neighbours(doc1, &graph, &cache).unwrap();
+    assert!(prefetch.take(doc1, &cache).is_none());
+
+    let content = prefetch.take(doc2, &cache).unwrap();
+    let prefetched_editor =
+        DocumentEditor::create_from_content(content, graph.clone(), JobExecutor::default());
+    let editor =
+        DocumentEditor::create_from_graph(doc2, graph.clone(), JobExecutor::default()).unwrap();
+    assert_eq!(11, prefetched_editor.token.len());
+    assert_eq!(editor.token, prefetched_editor.token);
+    assert_eq!(editor.segmentations, prefetched_editor.segmentations);
+
+    // Changes to the graph discard the prefetched content
+    prefetch.load_neighbours(doc1, &graph, &cache).unwrap();
+    cache.invalidate();
+    assert!(prefetch.take(doc2, &cache).is_none());
+}
+
+#[test]
+fn filter_token_by_annotation() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    {
+        let mut editor = editor.write();
+        editor.token_filter.text = "pos=\"NN\"".to_string();
+        editor.token_filter.update_pattern();
+        assert!(editor.token_filter.error.is_none());
+        assert_eq!(1.0, editor.token_filter.opacity(&editor.token[1]));
+        assert_eq!(
+            DIMMED_OPACITY,
+            editor.token_filter.opacity(&editor.token[0])
+        );
+
+        editor.token_filter.mode = FilterMode::Hide;
+        assert_eq!(0.0, editor.token_filter.opacity(&editor.token[0]));
+    }
+    harness.run();
+
+    // Hidden token keep their place, but can not be selected
+    harness
+        .get_by_label_contains("Token ranging from 0 to 0")
+        .click();
+    harness.run();
+    assert!(editor.read().selected_nodes.is_empty());
+    harness
+        .get_by_label_contains("Token ranging from 1 to 1")
+        .click();
+    harness.run();
+    assert_eq!(1, editor.read().selected_nodes.len());
+
+    let mut editor = editor.write();
+    editor.token_filter.text = "pos=VERB".to_string();
+    editor.token_filter.update_pattern();
+    assert!(editor.token_filter.error.is_some());
+    assert_eq!(1.0, editor.token_filter.opacity(&editor.token[0]));
+}
+
+#[test]
+fn resolve_duplicate_spans() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    // Add a second span for "more" on the same segmentation layer
+    example_generator::make_span(&mut updates, "root/doc1#seg2b", &["root/doc1#tok4"], true);
+    for (anno_ns, anno_name, anno_value) in [("annis", "tok", "more"), ("default_ns", "note", "x")]
+    {
+        updates
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: "root/doc1#seg2b".to_string(),
+                anno_ns: anno_ns.to_string(),
+                anno_name: anno_name.to_string(),
+                anno_value: anno_value.to_string(),
+            })
+            .unwrap();
+    }
+    updates
+        .add_event(UpdateEvent::AddEdge {
+            source_node: "root/doc1#seg2b".to_string(),
+            target_node: "root/doc1".to_string(),
+            layer: "annis".to_string(),
+            component_type: "PartOf".to_string(),
+            component_name: "".to_string(),
+        })
+        .unwrap();
+    updates
+        .add_event(UpdateEvent::DeleteEdge {
+            source_node: "root/doc1#seg2".to_string(),
+            target_node: "root/doc1#seg3".to_string(),
+            layer: "annis".to_string(),
+            component_type: "Ordering".to_string(),
+            component_name: "seg".to_string(),
+        })
+        .unwrap();
+    for (source_node, target_node) in [
+        ("root/doc1#seg2", "root/doc1#seg2b"),
+        ("root/doc1#seg2b", "root/doc1#seg3"),
+    ] {
+        updates
+            .add_event(UpdateEvent::AddEdge {
+                source_node: source_node.to_string(),
+                target_node: target_node.to_string(),
+                layer: "annis".to_string(),
+                component_type: "Ordering".to_string(),
+                component_name: "seg".to_string(),
+            })
+            .unwrap();
+    }
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let graph = Arc::new(RwLock::new(graph));
+
+    let mut editor =
+        DocumentEditor::create_from_graph(doc1, graph.clone(), JobExecutor::default()).unwrap();
+    let duplicates = find_duplicate_spans(&editor.segmentations);
+    assert_eq!(1, duplicates.len());
+    assert_eq!("seg", duplicates[0].layer);
+    assert_eq!(
+        vec!["root/doc1#seg2".to_string(), "root/doc1#seg2b".to_string()],
+        duplicates[0].node_names
+    );
+
+    // Adding the same span again is ignored
+    editor.selected_nodes.clear();
+    editor.selected_nodes.insert("root/doc1#tok4".to_string());
+    editor.add_segmentation_for_selection(1);
+    assert!(editor.pending_actions.is_empty());
+
+    editor.resolve_duplicate_spans(&duplicates[0], true);
+    assert!(find_duplicate_spans(&editor.segmentations).is_empty());
+
+    let mut update = GraphUpdate::new();
+    for action in std::mem::take(&mut editor.pending_actions) {
+        action
+            .apply(&graph.read(), "root/doc1", &mut update)
+            .unwrap();
+    }
+    graph.write().apply_update(&mut update, |_| {}).unwrap();
+
+    let editor = DocumentEditor::create_from_graph(doc1, graph, JobExecutor::default()).unwrap();
+    assert!(find_duplicate_spans(&editor.segmentations).is_empty());
+    let spans = editor.segmentations.get("seg").unwrap();
+    assert_eq!(3, spans.len());
+    let note_key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "note".into(),
+    };
+    assert_eq!(
+        Some(&"x".to_string()),
+        spans
+            .iter()
+            .find(|t| t.node_name == "root/doc1#seg2")
+            .and_then(|t| t.labels.get(&note_key))
+    );
+}
+
+#[test]
+fn edit_edge_annotations() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    updates
+        .add_event(UpdateEvent::AddEdge {
+            source_node: "root/doc1#tok1".to_string(),
+            target_node: "root/doc1#tok2".to_string(),
+            layer: "dep".to_string(),
+            component_type: "Pointing".to_string(),
+            component_name: "dep".to_string(),
+        })
+        .unwrap();
+    updates
+        .add_event(UpdateEvent::AddEdgeLabel {
+            source_node: "root/doc1#tok1".to_string(),
+            target_node: "root/doc1#tok2".to_string(),
+            layer: "dep".to_string(),
+            component_type: "Pointing".to_string(),
+            component_name: "dep".to_string(),
+            anno_ns: "".to_string(),
+            anno_name: "func".to_string(),
+            anno_value: "subj".to_string(),
+        })
+        .unwrap();
+    let mut graph = example_generator::create_graph(&mut updates);
+
+    let func_key = AnnoKey {
+        ns: "".into(),
+        name: "func".into(),
+    };
+    let mut edge_editor = EdgeAnnotationEditor::default();
+    // The edge is found from both the source and the target node
+    edge_editor.load(&graph, Some("root/doc1#tok2"));
+    assert_eq!(1, edge_editor.edges.len());
+    edge_editor.load(&graph, Some("root/doc1#tok1"));
+    assert_eq!(1, edge_editor.edges.len());
+    let edge = &edge_editor.edges[0];
+    assert_eq!("root/doc1#tok1", edge.source_node);
+    assert_eq!("root/doc1#tok2", edge.target_node);
+    assert_eq!(Some(&"subj".to_string()), edge.labels.get(&func_key));
+
+    edge_editor.select_edge(Some(0));
+    let change = edge_editor
+        .set_label(func_key.clone(), Some("obj".to_string()))
+        .unwrap();
+    let mut update = GraphUpdate::new();
+    EditorActions::SetEdgeLabel {
+        edge: change.edge,
+        key: change.key,
+        value: change.value,
+    }
+    .apply(&graph, "root/doc1", &mut update)
+    .unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+
+    let edges = edges_of_node(&graph, "root/doc1#tok1").unwrap();
+    assert_eq!(Some(&"obj".to_string()), edges[0].labels.get(&func_key));
+
+    // Delete the annotation again
+    let change = edge_editor.set_label(func_key.clone(), None).unwrap();
+    let mut update = GraphUpdate::new();
+    change
+        .edge
+        .update_label(&mut update, &change.key, None)
+        .unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+    let edges = edges_of_node(&graph, "root/doc1#tok1").unwrap();
+    assert!(edges[0].labels.is_empty());
+}
+
+#[test]
+fn create_token_in_empty_document() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let graph = Arc::new(RwLock::new(graph));
+
+    let editor =
+        DocumentEditor::create_from_graph(doc1, graph.clone(), JobExecutor::default()).unwrap();
+    assert!(editor.token.is_empty());
+
+    // Showing the empty document must not panic
+    let editor = Arc::new(RwLock::new(editor));
+    let editor_for_closure = editor.clone();
+    let mut harness = Harness::builder().build_ui(move |ui| {
+        set_fonts(ui.ctx());
+        editor_for_closure.write().show(ui);
+    });
+    harness.run();
+    harness.get_by_label("Create token");
+
+    let mut update = GraphUpdate::new();
+    EditorActions::InsertToken {
+        after_token: None,
+        text: "Hello world!".to_string(),
+        tokenizer: TokenizerKind::default(),
+    }
+    .apply(&graph.read(), "root/doc1", &mut update)
+    .unwrap();
+    graph.write().apply_update(&mut update, |_| {}).unwrap();
+
+    let editor = DocumentEditor::create_from_graph(doc1, graph, JobExecutor::default()).unwrap();
+    let token_values: Vec<_> = editor
+        .token
+        .iter()
+        .map(|t| t.labels.get(&TOKEN_KEY).cloned().unwrap_or_default())
+        .collect();
+    assert_eq!(vec!["Hello", "world", "!"], token_values);
+    // The token are added to the existing data source
+    assert_eq!(3, editor.datasources[0].node_count);
+}
+
+#[test]
+fn show_token_with_broken_ordering() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    // Token of the document that are not connected by any ordering edge
+    for (node_name, value) in [("root/doc1#t1", "Hello"), ("root/doc1#t2", "world")] {
+        example_generator::create_token_node(
+            &mut updates,
+            node_name,
+            value,
+            None,
+            None,
+            Some("root/doc1"),
+        );
+    }
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let graph = Arc::new(RwLock::new(graph));
+
+    let editor = DocumentEditor::create_from_graph(doc1, graph, JobExecutor::default()).unwrap();
+    assert_eq!(2, editor.token.len());
+    assert!(editor.ordering_broken);
+
+    // The document must not be offered as an empty one
+    let editor = Arc::new(RwLock::new(editor));
+    let editor_for_closure = editor.clone();
+    let mut harness = Harness::builder().build_ui(move |ui| {
+        set_fonts(ui.ctx());
+        editor_for_closure.write().show(ui);
+    });
+    harness.run();
+    assert!(harness.query_by_label("Create token").is_none());
+    harness.get_by_label_contains("The order of the token in this document is broken");
+}
+
+#[test]
+fn show_token_outside_of_split_ordering() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    for (node_name, value) in [
+        ("root/doc1#t1", "a"),
+        ("root/doc1#t2", "b"),
+        ("root/doc1#t3", "c"),
+        ("root/doc1#t4", "d"),
+        ("root/doc1#t5", "e"),
+    ] {
+        example_generator::create_token_node(
+            &mut updates,
+            node_name,
+            value,
+            None,
+            None,
+            Some("root/doc1"),
+        );
+    }
+    // Two ordering chains and a token without any ordering edge
+    for (source_node, target_node) in [
+        ("root/doc1#t1", "root/doc1#t2"),
+        ("root/doc1#t3", "root/doc1#t4"),
+    ] {
+        updates
+            .add_event(UpdateEvent::AddEdge {
+                source_node: source_node.to_string(),
+                target_node: target_node.to_string(),
+                layer: "annis".to_string(),
+                component_type: "Ordering".to_string(),
+                component_name: "".to_string(),
+            })
+            .unwrap();
+    }
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let graph = Arc::new(RwLock::new(graph));
+
+    let editor = DocumentEditor::create_from_graph(doc1, graph, JobExecutor::default()).unwrap();
+    assert!(editor.ordering_broken);
+    let mut token_names: Vec<_> = editor.token.iter().map(|t| t.node_name.as_str()).collect();
+    // The unordered token is shown after the ordering chains
+    assert_eq!(Some(&"root/doc1#t5"), token_names.last());
+    token_names.sort_unstable();
+    assert_eq!(
+        vec![
+            "root/doc1#t1",
+            "root/doc1#t2",
+            "root/doc1#t3",
+            "root/doc1#t4",
+            "root/doc1#t5"
+        ],
+        token_names
+    );
+}
+
+#[test]
+fn manual_commit_keeps_pending_actions() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    {
+        let mut editor = editor.write();
+        editor.set_manual_commit(true);
+        editor.delete(["single_sentence/zossen#t4"]);
+    }
+    harness.run();
+    assert_eq!(1, editor.read().pending_update_count());
+    assert!(!editor.read().jobs.has_running_jobs());
+
+    editor.write().discard_pending_updates();
+    assert!(!editor.read().has_pending_updates());
+}
+
+/// Apply the pending actions of the editor to its graph.
+fn apply_pending_actions(editor: &mut DocumentEditor, document: &str) {
+    let actions = std::mem::take(&mut editor.pending_actions);
+    let mut update = GraphUpdate::new();
+    apply_actions(actions, &editor.graph.read(), document, &mut update).unwrap();
+    editor
+        .graph
+        .write()
+        .apply_update(&mut update, |_| {})
+        .unwrap();
+}
+
+#[test]
+fn edit_with_action_api() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.run();
+    let mut editor = editor.write();
+    editor.set_manual_commit(true);
+
+    // Unknown nodes are not selected
+    assert_eq!(0, editor.select(["SegmentationWithGaps/doc01#unknown"]));
+    assert_eq!(3, editor.select_token_range(0..=2));
+
+    let span = editor.segmentations["norm"][0].node_name.clone();
+    editor.edit_value(&span, "changed").unwrap();
+    // Setting the same value again does not create another action
+    editor.edit_value(&span, "changed").unwrap();
+    assert!(editor
+        .edit_value("SegmentationWithGaps/doc01#unknown", "x")
+        .is_err());
+    assert_eq!(1, editor.pending_update_count());
+
+    assert!(editor.add_span("unknown", 0..=1).is_err());
+    assert!(editor.add_span("norm", 0..=1000).is_err());
+    editor.create_layer("gloss");
+    editor.add_span("gloss", 0..=1).unwrap();
+    assert_eq!(2, editor.pending_update_count());
+
+    let deleted = editor.segmentations["norm"][1].node_name.clone();
+    editor.delete([deleted.as_str()]);
+    assert_eq!(3, editor.pending_update_count());
+    assert!(editor.selected_nodes.is_empty());
+
+    apply_pending_actions(&mut editor, "SegmentationWithGaps/doc01");
+    let graph = editor.graph.read();
+    let node_annos = graph.get_node_annos();
+    let span_id = node_annos.get_node_id_from_name(&span).unwrap().unwrap();
+    assert_eq!(
+        Some("changed"),
+        node_annos
+            .get_value_for_item(&span_id, &TOKEN_KEY)
+            .unwrap()
+            .as_deref()
+    );
+    assert_eq!(None, node_annos.get_node_id_from_name(&deleted).unwrap());
+    let gloss_spans = node_annos
+        .exact_anno_search(
+            Some("annis"),
+            "gloss",
+            graphannis_core::annostorage::ValueSearch::Any,
+        )
+        .count();
+    assert_eq!(1, gloss_spans);
+}
+
+#[test]
+fn restore_view_state() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.run();
+    let mut state = {
+        let mut editor = editor.write();
+        let span = editor.segmentations["norm"][1].clone();
+        assert_eq!(1, editor.select([span.node_name]));
+        editor.active_layer = Some("norm".to_string());
+        editor.view_state().unwrap()
+    };
+    state
+        .selected_nodes
+        .insert("SegmentationWithGaps/doc01#deleted".to_string());
+    state.first_visible_token = 2;
+
+    let graph = editor.read().graph.clone();
+    let document = graph
+        .read()
+        .get_node_annos()
+        .get_node_id_from_name("SegmentationWithGaps/doc01")
+        .unwrap()
+        .unwrap();
+    let mut reloaded =
+        DocumentEditor::create_from_graph(document, graph, JobExecutor::default()).unwrap();
+    reloaded.restore_view_state(state.clone());
+    // Nodes that do not exist anymore are not selected
+    assert_eq!(1, reloaded.selected_nodes.len());
+    assert_eq!(editor.read().selected_nodes, reloaded.selected_nodes);
+    assert_eq!(Some("norm".to_string()), reloaded.active_layer);
+    assert_eq!(Some((2, egui::Align::Min)), reloaded.scroll_request);
+
+    // The state of other documents is ignored
+    let mut other_document = state.clone();
+    other_document.node_name = "SegmentationWithGaps/other".to_string();
+    reloaded.selected_nodes.clear();
+    reloaded.scroll_request = None;
+    reloaded.restore_view_state(other_document);
+    assert!(reloaded.selected_nodes.is_empty());
+    assert_eq!(None, reloaded.scroll_request);
+}
+
+#[test]
+fn confirm_deletion_of_selected_nodes() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    {
+        let mut editor = editor.write();
+        editor.set_manual_commit(true);
+        editor.set_confirm_deletion(true);
+        editor.select(["single_sentence/zossen#t4"]);
+        editor.request_deletion(Deletion::SelectedNodes);
+        assert_eq!(0, editor.pending_update_count());
+    }
+    harness.run();
+    harness.get_by_label("Delete 1 node(s)?");
+    harness.get_by_label("• 1 token");
+
+    // Cancelling keeps the node
+    harness.get_by_label("Cancel").click();
+    harness.run();
+    assert!(editor.read().deletion_confirmation.is_none());
+    assert_eq!(0, editor.read().pending_update_count());
+
+    editor.write().request_deletion(Deletion::SelectedNodes);
+    harness.run();
+    harness.get_by_label("Don't ask again").click();
+    harness.run();
+    harness.get_by_label("Delete").click();
+    harness.run();
+    assert_eq!(1, editor.read().pending_update_count());
+    assert!(editor.write().take_disabled_confirmation());
+    assert!(!editor.write().take_disabled_confirmation());
+    assert!(!editor.read().confirm_deletion);
+}
+
+#[test]
+fn list_layer_shortcuts() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let editor = DocumentEditor::create_from_graph(
+        doc1,
+        Arc::new(RwLock::new(graph)),
+        JobExecutor::default(),
+    )
+    .unwrap();
+
+    let shortcuts = editor.shortcuts();
+    let layer_shortcut = shortcuts
+        .iter()
+        .find(|e| e.description == "Add span on layer \"seg\"")
+        .unwrap();
+    assert_eq!(egui::Key::Num1, layer_shortcut.shortcut.logical_key);
+    assert!(shortcuts.iter().all(|e| e.context == "Document editor"));
+}
+
+/// The node after the given one in the ordering component with the name.
+fn ordering_successor(
+    graph: &graphannis::AnnotationGraph,
+    component_name: &str,
+    node_name: &str,
+) -> Option<String> {
+    let node_annos = graph.get_node_annos();
+    let node = node_annos.get_node_id_from_name(node_name).unwrap()?;
+    let component = graph
+        .get_all_components(
+            Some(AnnotationComponentType::Ordering),
+            Some(component_name),
+        )
+        .into_iter()
+        .next()?;
+    let gs = graph.get_graphstorage_as_ref(&component)?;
+    let next = gs.get_outgoing_edges(node).next()?.unwrap();
+    Some(
+        node_annos
+            .get_value_for_item(&next, &graphannis_core::graph::NODE_NAME_KEY)
+            .unwrap()
+            .unwrap()
+            .to_string(),
+    )
+}
+
+#[test]
+fn bridge_ordering_when_deleting_nodes() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+
+    // Delete a gap of adjacent token, the first and last token and a
+    // segmentation node in the middle of its chain
+    let actions = [
+        "root/doc1#tok2",
+        "root/doc1#tok3",
+        "root/doc1#tok0",
+        "root/doc1#tok10",
+        "root/doc1#seg2",
+    ]
+    .into_iter()
+    .map(|node_name| EditorActions::DeleteNode {
+        node_name: node_name.to_string(),
+    })
+    .collect();
+    let mut update = GraphUpdate::new();
+    apply_actions(actions, &graph, "root/doc1", &mut update).unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+
+    assert_eq!(
+        Some("root/doc1#tok4".to_string()),
+        ordering_successor(&graph, "", "root/doc1#tok1")
+    );
+    // The last remaining token is the end of the chain
+    assert_eq!(None, ordering_successor(&graph, "", "root/doc1#tok9"));
+    assert_eq!(
+        Some("root/doc1#seg3".to_string()),
+        ordering_successor(&graph, "seg", "root/doc1#seg1")
+    );
+}
+
+#[test]
+fn show_status_of_editor() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    assert_eq!(0, editor.read().first_visible_token);
+    editor
+        .write()
+        .selected_nodes
+        .insert("single_sentence/zossen#t4".to_string());
+
+    let mut status_harness = Harness::builder().build_ui(move |ui| {
+        ui.horizontal(|ui| editor.read().status_bar(ui));
+    });
+    status_harness.run();
+    status_harness.get_by_label("8 token");
+    status_harness.get_by_label("First visible token: 1");
+    status_harness.get_by_label("1 selected");
+}
+
+#[test]
+fn author_timeline_with_independent_layers() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let graph = Arc::new(RwLock::new(graph));
+    let apply = |action: EditorActions| {
+        let mut update = GraphUpdate::new();
+        action
+            .apply(&graph.read(), "root/doc1", &mut update)
+            .unwrap();
+        graph.write().apply_update(&mut update, |_| {}).unwrap();
+    };
+
+    apply(EditorActions::InsertTimelineItems {
+        after_token: None,
+        count: 4,
+    });
+    let mut editor =
+        DocumentEditor::create_from_graph(doc1, graph.clone(), JobExecutor::default()).unwrap();
+    assert_eq!(4, editor.token.len());
+    assert!(editor.is_timeline());
+
+    // Layers can be created before they have any nodes
+    assert!(editor.validate_layer_name("tok").is_err());
+    editor.create_layer("speaker1");
+    assert_eq!(Some("speaker1".to_string()), editor.active_layer);
+    assert!(editor.validate_layer_name("speaker1").is_err());
+
+    let timeline: Vec<String> = editor.token.iter().map(|t| t.node_name.clone()).collect();
+    let span = |segmentation: &str, items: &[usize]| EditorActions::AddSegmentationSpan {
+        segmentation: segmentation.to_string(),
+        selected_token: items.iter().map(|i| timeline[*i].clone()).collect(),
+        value: String::new(),
+    };
+    apply(span("speaker1", &[0, 1]));
+    // The first node of a layer is shown without an ordering component
+    let editor =
+        DocumentEditor::create_from_graph(doc1, graph.clone(), JobExecutor::default()).unwrap();
+    let first = editor.segmentations["speaker1"][0].clone();
+    assert_eq!((0, 1), (first.start, first.end));
+
+    apply(span("speaker1", &[2, 3]));
+    apply(span("speaker2", &[1, 2]));
+    let editor =
+        DocumentEditor::create_from_graph(doc1, graph.clone(), JobExecutor::default()).unwrap();
+    assert_eq!(2, editor.segmentations["speaker1"].len());
+    assert_eq!(1, editor.segmentations["speaker2"].len());
+    let second = &editor.segmentations["speaker1"][1];
+    assert_eq!(
+        Some(second.node_name.clone()),
+        ordering_successor(&graph.read(), "speaker1", &first.node_name)
+    );
+}
+
+#[test]
+fn add_nested_spans() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let graph = Arc::new(RwLock::new(graph));
+
+    // Select the token range and add a span, returns the editor loaded again
+    let add_span = |first: usize, last: usize, value: &str| {
+        let mut editor =
+            DocumentEditor::create_from_graph(doc1, graph.clone(), JobExecutor::default()).unwrap();
+        editor.set_manual_commit(true);
+        editor.nested_span_key = "ner".to_string();
+        editor.nested_span_value = value.to_string();
+        for i in [first, last] {
+            editor.selected_nodes.insert(format!("root/doc1#tok{i}"));
+        }
+        editor.add_nested_span_for_selection();
+        let actions = std::mem::take(&mut editor.pending_actions);
+        let mut update = GraphUpdate::new();
+        apply_actions(actions, &graph.read(), "root/doc1", &mut update).unwrap();
+        graph.write().apply_update(&mut update, |_| {}).unwrap();
+        editor.nested_span_error
+    };
+
+    assert_eq!(None, add_span(1, 4, "ORG"));
+    assert_eq!(None, add_span(2, 3, "PER"));
+    // Partially overlapping spans are rejected
+    assert!(add_span(3, 6, "LOC").is_some());
+
+    let mut editor =
+        DocumentEditor::create_from_graph(doc1, graph, JobExecutor::default()).unwrap();
+    editor.nested_span_key = "ner".to_string();
+    let nested: Vec<_> = editor
+        .nested_spans()
+        .into_iter()
+        .map(|(t, depth)| (t.start, t.end, depth))
+        .collect();
+    assert_eq!(vec![(1, 4, 0), (2, 3, 1)], nested);
+}
+
+#[test]
+fn limit_number_of_visible_labels() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    for (name, value) in [("lemma", "be"), ("pos", "VBZ"), ("morph", "3sg")] {
+        updates
+            .add_event(graphannis::update::UpdateEvent::AddNodeLabel {
+                node_name: "root/doc1#tok1".to_string(),
+                anno_ns: "".to_string(),
+                anno_name: name.to_string(),
+                anno_value: value.to_string(),
+            })
+            .unwrap();
+    }
+    let graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let mut editor = DocumentEditor::create_from_graph(
+        doc1,
+        Arc::new(RwLock::new(graph)),
+        JobExecutor::default(),
+    )
+    .unwrap();
+    editor.max_labels = 1;
+    let editor = Arc::new(RwLock::new(editor));
+    let editor_for_closure = editor.clone();
+    let mut harness = Harness::builder().build_ui(move |ui| {
+        set_fonts(ui.ctx());
+        editor_for_closure.write().show(ui);
+    });
+    harness.run();
+    harness.get_by_label("+2 more");
+
+    // Selected token show all labels
+    editor
+        .write()
+        .selected_nodes
+        .insert("root/doc1#tok1".to_string());
+    harness.run();
+    assert!(harness.query_by_label("+2 more").is_none());
+}
+
+#[test]
+fn scroll_to_nodes_of_the_document() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    assert!(!editor
+        .write()
+        .scroll_to_node("single_sentence/zossen#unknown"));
+    assert_eq!(None, editor.read().scroll_request);
+
+    assert!(editor.write().scroll_to_node("single_sentence/zossen#t4"));
+    assert_eq!(Some((3, egui::Align::Center)), editor.read().scroll_request);
+    // Spans are shown at their first covered token
+    assert!(editor.write().scroll_to_node("single_sentence/zossen#n1"));
+    // The request is handled as soon as the token position is known
+    harness.run();
+    assert_eq!(None, editor.read().scroll_request);
+
+    editor.write().scroll_to_token(100);
+    assert_eq!(Some((7, egui::Align::Center)), editor.read().scroll_request);
+}
+
+#[test]
+fn only_spans_covering_visible_token_are_rendered() {
+    let span = |start, end| Token {
+        node_name: format!("seg{start}"),
+        start,
+        end,
+        labels: Default::default(),
+    };
+    let spans = vec![span(0, 1), span(2, 4), span(5, 5), span(6, 9)];
+    assert_eq!(1..3, visible_span_range(&spans, 3, 5));
+    assert_eq!(0..4, visible_span_range(&spans, 0, 100));
+    assert_eq!(3..4, visible_span_range(&spans, 7, 8));
+    assert_eq!(4..4, visible_span_range(&spans, 10, 12));
+}
+
+#[test]
+fn paint_token_in_single_pass() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    assert!(harness.query_by_label("NN").is_some());
+
+    // Small documents use nested widgets for each line of a token
+    assert!(!editor.read().painted_token);
+    editor.write().painted_token = true;
+    harness.run();
+    assert!(harness.query_by_label("NN").is_none());
+
+    // Painted token can still be selected
+    harness
+        .get_by_label_contains("Token ranging from 1 to 1")
+        .click();
+    harness.run();
+    assert_eq!(1, editor.read().selected_nodes.len());
+}
+
+#[test]
+fn whitespace_as_spacing() {
+    let token = |name: &str, whitespace_after: Option<&str>| {
+        let mut labels = BTreeMap::new();
+        if let Some(whitespace) = whitespace_after {
+            labels.insert(
+                AnnoKey {
+                    ns: "annis".into(),
+                    name: "tok-whitespace-after".into(),
+                },
+                whitespace.to_string(),
+            );
+        }
+        Token {
+            node_name: name.to_string(),
+            start: 0,
+            end: 0,
+            labels,
+        }
+    };
+    let next = token("doc#t2", None);
+
+    assert_eq!((0.0, false), whitespace_gap(&token("doc#t1", None), &next));
+    let (single_space, line_break) = whitespace_gap(&token("doc#t1", Some(" ")), &next);
+    assert!(single_space > 0.0);
+    assert!(!line_break);
+    let (two_spaces, _) = whitespace_gap(&token("doc#t1", Some("  ")), &next);
+    assert_eq!(2.0 * single_space, two_spaces);
+    let (_, line_break) = whitespace_gap(&token("doc#t1", Some(" \n")), &next);
+    assert!(line_break);
+
+    // Token are still shown when the whitespace is displayed as spacing
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    editor.write().whitespace_spacing = true;
+    harness.run();
+    harness.get_by_label_contains("Token ranging from 1 to 1");
+}
+
+#[test]
+fn context_menu_actions() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    let token_name = editor.read().token[1].node_name.clone();
+
+    editor.write().context_menu_request = Some((token_name.clone(), ContextMenuAction::Inspect));
+    harness.run();
+    harness.get_by_label("Node details");
+    harness.get_by_label("Covers token 1");
+    // Inspecting a node does not change the selection
+    assert!(editor.read().selected_nodes.is_empty());
+
+    editor.write().context_menu_request =
+        Some((token_name.clone(), ContextMenuAction::AddAnnotation));
+    harness.run();
+    assert_eq!(
+        Some(&token_name),
+        editor.read().add_annotation_dialog.node_name.as_ref()
+    );
+    assert!(editor.read().selected_nodes.contains(&token_name));
+
+    let annis_key = AnnoKey {
+        ns: "annis".into(),
+        name: "tok".into(),
+    };
+    assert!(editor
+        .write()
+        .add_annotation(&token_name, annis_key, "changed".to_string())
+        .is_err());
+    let key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "note".into(),
+    };
+    editor
+        .write()
+        .add_annotation(&token_name, key.clone(), "checked".to_string())
+        .unwrap();
+    harness.run();
+    let editor = editor.read();
+    assert_eq!(
+        Some(&"checked".to_string()),
+        editor.node_by_name(&token_name).unwrap().labels.get(&key)
+    );
+    assert_eq!(Some(&1), editor.label_counts.get(&key));
+}
+
+#[test]
+fn typed_annotation_values() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    editor.write().apply_layer_settings(&LayerSettings {
+        value_types: [(
+            "certainty".to_string(),
+            ValueType::Number {
+                min: Some(0.0),
+                max: Some(1.0),
+                integer: false,
+            },
+        )]
+        .into(),
+        ..Default::default()
+    });
+    let token_name = editor.read().token[1].node_name.clone();
+    let key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "certainty".into(),
+    };
+    assert!(editor
+        .write()
+        .add_annotation(&token_name, key.clone(), "high".to_string())
+        .is_err());
+    assert!(editor
+        .write()
+        .add_annotation(&token_name, key.clone(), "1.5".to_string())
+        .is_err());
+    editor
+        .write()
+        .add_annotation(&token_name, key.clone(), "0.8".to_string())
+        .unwrap();
+
+    // The dialog shows a number editor for the value
+    editor.write().context_menu_request =
+        Some((token_name.clone(), ContextMenuAction::AddAnnotation));
+    harness.run();
+    editor.write().add_annotation_dialog.key = "certainty".to_string();
+    harness.run();
+    harness.run();
+    assert_eq!("0", editor.read().add_annotation_dialog.value);
+
+    let editor = editor.read();
+    assert_eq!(
+        Some(&"0.8".to_string()),
+        editor.node_by_name(&token_name).unwrap().labels.get(&key)
+    );
+}
+
+#[test]
+fn prefill_default_namespace() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    let token_name = editor.read().token[1].node_name.clone();
+    editor.write().context_menu_request =
+        Some((token_name.clone(), ContextMenuAction::AddAnnotation));
+    harness.run();
+    assert_eq!("", editor.read().add_annotation_dialog.key);
+    editor.write().add_annotation_dialog.node_name = None;
+
+    editor
+        .write()
+        .set_default_namespace("default_ns".to_string());
+    editor.write().context_menu_request =
+        Some((token_name.clone(), ContextMenuAction::AddAnnotation));
+    harness.run();
+    assert_eq!("default_ns:", editor.read().add_annotation_dialog.key);
+    assert_eq!(
+        "default_ns",
+        editor.read().edge_annotations.default_namespace
+    );
+}
+
+#[test]
+fn select_covering_span_and_sentence() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.run();
+    {
+        let mut editor = editor.write();
+        let layer = editor.active_layer.clone().unwrap();
+        let span = editor.covering_span(&layer, 0).unwrap().node_name.clone();
+        assert!(editor.select_covering_span(0));
+        assert_eq!(1, editor.selected_nodes.len());
+        assert!(editor.selected_nodes.contains(&span));
+    }
+
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/single_sentence.graphml")[..],
+        "single_sentence/zossen",
+    );
+    harness.run();
+    let mut editor = editor.write();
+    // There is no active layer
+    assert!(!editor.select_covering_span(0));
+    assert!(editor.selected_nodes.is_empty());
+    // The sentence ends with the last token, which is a full stop
+    assert_eq!(8, editor.select_sentence(3));
+    assert!(editor.selected_nodes.contains(&editor.token[7].node_name));
+}
+
+#[test]
+fn toggle_layer_names() {
+    let (mut harness, editor) = create_example_ui(
+        &include_bytes!("../../../../tests/data/SegmentationWithGaps.graphml")[..],
+        "SegmentationWithGaps/doc01",
+    );
+    harness.set_size(Vec2::new(2100.0, 210.0));
+    harness.run();
+    assert!(editor.read().layer_names);
+
+    harness.get_by_label("Layer names").click();
+    harness.run();
+    assert!(!editor.read().layer_names);
+    let persisted: Option<bool> = harness
+        .ctx
+        .data_mut(|d| d.get_persisted(egui::Id::new(LAYER_NAMES_ID)));
+    assert_eq!(Some(false), persisted);
+}
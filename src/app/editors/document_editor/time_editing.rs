@@ -0,0 +1,217 @@
+use anyhow::{bail, Context};
+use egui::{Button, Color32, DragValue, RichText, Ui};
+use graphannis::{
+    graph::NodeID,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+
+use super::{node_name_of, time_range_of, DocumentEditor, EditorActions, StateUpdateFn};
+use crate::{
+    api::token_helper::TokenHelper,
+    app::{
+        util::time_annotation::{TimeRange, TIME_KEY},
+        widgets::Token,
+    },
+};
+
+/// Start and end time of the selected node that are currently edited.
+#[derive(Clone, PartialEq)]
+pub(super) struct TimeEditor {
+    pub(super) node_name: String,
+    pub(super) start: f64,
+    pub(super) end: f64,
+    /// End of the node before in the same layer
+    pub(super) previous_end: Option<f64>,
+    /// Start of the node after in the same layer
+    pub(super) next_start: Option<f64>,
+}
+
+impl TimeEditor {
+    pub(super) fn validate(&self) -> Result<(), String> {
+        if self.start >= self.end {
+            Err("The start time must be before the end time.".to_string())
+        } else if self
+            .previous_end
+            .is_some_and(|previous_end| self.start < previous_end)
+        {
+            Err("The start time overlaps with the previous node.".to_string())
+        } else if self
+            .next_start
+            .is_some_and(|next_start| self.end > next_start)
+        {
+            Err("The end time overlaps with the next node.".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl DocumentEditor {
+    /// The time range of a token or segmentation node of this document.
+    pub(super) fn time_range(&self, t: &Token) -> Option<TimeRange> {
+        time_range_of(&self.token, t)
+    }
+
+    /// Creates the time editor state for the single selected node.
+    fn create_time_editor(&self) -> Option<TimeEditor> {
+        if self.selected_nodes.len() != 1 {
+            return None;
+        }
+        let selected = self.selected_nodes.iter().next()?;
+        let layer = if self.token_index_by_name.contains_key(selected) {
+            self.token.as_slice()
+        } else {
+            self.segmentations
+                .values()
+                .find(|seg_token| seg_token.iter().any(|t| &t.node_name == selected))?
+                .as_slice()
+        };
+        let idx = layer.iter().position(|t| &t.node_name == selected)?;
+        let range = self.time_range(&layer[idx])?;
+        let previous_end = idx
+            .checked_sub(1)
+            .and_then(|i| layer.get(i))
+            .and_then(|t| self.time_range(t))
+            .and_then(|r| r.end);
+        let next_start = layer
+            .get(idx + 1)
+            .and_then(|t| self.time_range(t))
+            .map(|r| r.start);
+        Some(TimeEditor {
+            node_name: selected.clone(),
+            start: range.start,
+            end: range.end.unwrap_or(range.start),
+            previous_end,
+            next_start,
+        })
+    }
+
+    pub(super) fn show_time_editor(&mut self, ui: &mut Ui) {
+        if !self.has_time_annotations {
+            return;
+        }
+        let selected_changed = match (&self.time_editor, self.selected_nodes.len()) {
+            (Some(editor), 1) => !self.selected_nodes.contains(&editor.node_name),
+            (None, 1) => true,
+            (Some(_), _) => true,
+            (None, _) => false,
+        };
+        if selected_changed {
+            self.time_editor = self.create_time_editor();
+        }
+        let mut apply = None;
+        if let Some(time_editor) = &mut self.time_editor {
+            ui.horizontal(|ui| {
+                ui.label("Time of selected node");
+                ui.add(
+                    DragValue::new(&mut time_editor.start)
+                        .range(0.0..=f64::MAX)
+                        .speed(0.01)
+                        .max_decimals(3)
+                        .suffix(" s"),
+                );
+                ui.label("to");
+                ui.add(
+                    DragValue::new(&mut time_editor.end)
+                        .range(0.0..=f64::MAX)
+                        .speed(0.01)
+                        .max_decimals(3)
+                        .suffix(" s"),
+                );
+                let validation = time_editor.validate();
+                if ui
+                    .add_enabled(validation.is_ok(), Button::new("Apply time"))
+                    .clicked()
+                {
+                    apply = Some(EditorActions::SetTimeRange {
+                        node_name: time_editor.node_name.clone(),
+                        start: time_editor.start,
+                        end: time_editor.end,
+                    });
+                }
+                if let Err(msg) = validation {
+                    ui.label(RichText::new(msg).color(Color32::RED));
+                }
+            });
+        }
+        if let Some(action) = apply {
+            self.pending_actions.push(action);
+            self.apply_pending_updates_automatically();
+        }
+    }
+}
+
+/// Sets the time range of a node. If the node has no time annotation itself,
+/// the start of the first and the end of the last covered token are changed.
+pub(super) fn apply_set_time_range(
+    graph: &AnnotationGraph,
+    updates: &mut GraphUpdate,
+    node_name: &str,
+    start: f64,
+    end: f64,
+) -> anyhow::Result<StateUpdateFn> {
+    let node_annos = graph.get_node_annos();
+    let node_id = node_annos
+        .get_node_id_from_name(node_name)?
+        .context("Missing node id")?;
+    let mut add_time = |node_name: String, range: TimeRange| {
+        updates.add_event(UpdateEvent::AddNodeLabel {
+            node_name,
+            anno_ns: TIME_KEY.ns.to_string(),
+            anno_name: TIME_KEY.name.to_string(),
+            anno_value: range.to_string(),
+        })
+    };
+
+    let tok_helper = TokenHelper::new(graph)?;
+    let has_own_time = node_annos
+        .get_value_for_item(&node_id, &TIME_KEY)?
+        .is_some();
+    if has_own_time || tok_helper.is_token(node_id)? {
+        add_time(
+            node_name.to_string(),
+            TimeRange {
+                start,
+                end: Some(end),
+            },
+        )?;
+    } else {
+        let covered = tok_helper.covered_token(node_id)?;
+        let existing_range = |t: NodeID| -> anyhow::Result<Option<TimeRange>> {
+            let value = node_annos.get_value_for_item(&t, &TIME_KEY)?;
+            Ok(value.and_then(|v| TimeRange::parse(&v)))
+        };
+        match (covered.first(), covered.last()) {
+            (Some(first), Some(last)) if first == last => {
+                add_time(
+                    node_name_of(graph, *first)?,
+                    TimeRange {
+                        start,
+                        end: Some(end),
+                    },
+                )?;
+            }
+            (Some(first), Some(last)) => {
+                let first_end = existing_range(*first)?.and_then(|r| r.end);
+                add_time(
+                    node_name_of(graph, *first)?,
+                    TimeRange {
+                        start,
+                        end: first_end,
+                    },
+                )?;
+                let last_start = existing_range(*last)?.map(|r| r.start).unwrap_or(start);
+                add_time(
+                    node_name_of(graph, *last)?,
+                    TimeRange {
+                        start: last_start,
+                        end: Some(end),
+                    },
+                )?;
+            }
+            _ => bail!("The node does not cover any token"),
+        }
+    }
+    Ok(Box::new(|_| {}))
+}
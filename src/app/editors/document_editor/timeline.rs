@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use egui::{Button, DragValue, TextEdit, Ui, Widget};
+use graphannis::{graph::AnnoKey, model::AnnotationComponentType, AnnotationGraph};
+use graphannis_core::graph::ANNIS_NS;
+
+use super::{DocumentEditor, EditorActions};
+use crate::api::token_helper::TOKEN_KEY;
+
+/// Names of annotations in the ANNIS namespace that do not mark the nodes of
+/// a segmentation layer.
+const NON_SEGMENTATION_NAMES: [&str; 7] = [
+    "tok",
+    "node_name",
+    "node_type",
+    "layer",
+    "time",
+    "tok-whitespace-before",
+    "tok-whitespace-after",
+];
+
+#[derive(Clone)]
+pub(super) struct InsertTimelineDialog {
+    pub(super) open: bool,
+    pub(super) count: usize,
+}
+
+impl Default for InsertTimelineDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            count: 10,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub(super) struct NewLayerDialog {
+    pub(super) open: bool,
+    pub(super) name: String,
+}
+
+impl DocumentEditor {
+    /// True if the base token have no values and only serve as a timeline
+    /// for the segmentation layers.
+    pub(super) fn is_timeline(&self) -> bool {
+        !self.token.is_empty()
+            && self
+                .token
+                .iter()
+                .all(|t| t.labels.get(&TOKEN_KEY).is_none_or(|v| v.is_empty()))
+    }
+
+    /// Inserts empty timeline items after the last selected base token or as
+    /// the first items of an empty document.
+    pub(super) fn insert_timeline_items(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let after_token = self.insertion_point();
+        if after_token.is_none() && !self.token.is_empty() {
+            return;
+        }
+        self.pending_actions
+            .push(EditorActions::InsertTimelineItems { after_token, count });
+        self.apply_pending_updates_automatically();
+    }
+
+    /// Checks if a new segmentation layer with this name can be created.
+    pub(super) fn validate_layer_name(&self, name: &str) -> Result<(), String> {
+        if name.is_empty() {
+            Err("The name must not be empty".to_string())
+        } else if !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            Err("Only letters, digits, \"_\" and \"-\" are allowed".to_string())
+        } else if NON_SEGMENTATION_NAMES.contains(&name) {
+            Err(format!("\"{name}\" is reserved by ANNIS"))
+        } else if self.segmentations.contains_key(name) {
+            Err(format!("Layer \"{name}\" already exists"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Adds an empty segmentation layer and makes it the active one. The layer
+    /// is only added to the graph with its first span.
+    pub(super) fn create_layer(&mut self, name: &str) {
+        if self.validate_layer_name(name).is_ok() {
+            self.segmentations.insert(name.to_string(), Vec::new());
+            self.active_layer = Some(name.to_string());
+        }
+    }
+
+    pub(super) fn show_insert_timeline_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.insert_timeline.open;
+        let mut apply = false;
+        egui::Window::new("Insert timeline items")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Empty base token are inserted after the selected token.");
+                ui.horizontal(|ui| {
+                    ui.label("Number of items");
+                    ui.add(DragValue::new(&mut self.insert_timeline.count).range(1..=10_000));
+                });
+                if ui
+                    .add_enabled(self.insertion_point().is_some(), Button::new("Insert"))
+                    .clicked()
+                {
+                    apply = true;
+                }
+            });
+        if apply {
+            self.insert_timeline_items(self.insert_timeline.count);
+            open = false;
+        }
+        self.insert_timeline.open = open;
+    }
+
+    pub(super) fn show_new_layer_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.new_layer.open;
+        let mut create = false;
+        egui::Window::new("New segmentation layer")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                TextEdit::singleline(&mut self.new_layer.name)
+                    .hint_text("Name of the layer")
+                    .id(egui::Id::from("new-layer-name"))
+                    .ui(ui);
+                let validation = self.validate_layer_name(self.new_layer.name.trim());
+                if let Err(message) = &validation {
+                    if !self.new_layer.name.is_empty() {
+                        ui.colored_label(ui.visuals().error_fg_color, message);
+                    }
+                }
+                if ui
+                    .add_enabled(validation.is_ok(), Button::new("Create layer"))
+                    .clicked()
+                {
+                    create = true;
+                }
+            });
+        if create {
+            let name = std::mem::take(&mut self.new_layer.name);
+            self.create_layer(name.trim());
+            open = false;
+        }
+        self.new_layer.open = open;
+    }
+
+    /// Show the input to create a timeline in an empty document.
+    pub(super) fn show_create_timeline(&mut self, ui: &mut Ui) {
+        let mut create_timeline = false;
+        ui.horizontal(|ui| {
+            ui.label("Or create a timeline with");
+            ui.add(DragValue::new(&mut self.insert_timeline.count).range(1..=10_000));
+            ui.label("empty items for independent segmentation layers");
+            if ui.button("Create timeline").clicked() {
+                create_timeline = true;
+            }
+        });
+        if create_timeline {
+            self.insert_timeline_items(self.insert_timeline.count);
+        }
+    }
+}
+
+/// Segmentation layers that have no ordering component, because they only
+/// consist of a single node, e.g. after the first span of a new layer has been
+/// added.
+pub(super) fn layers_without_ordering(graph: &AnnotationGraph) -> Result<Vec<AnnoKey>> {
+    let ordered: HashSet<String> = graph
+        .get_all_components(Some(AnnotationComponentType::Ordering), None)
+        .into_iter()
+        .map(|c| c.name.to_string())
+        .collect();
+    let result = graph
+        .get_node_annos()
+        .annotation_keys()?
+        .into_iter()
+        .filter(|k| {
+            k.ns == ANNIS_NS
+                && !NON_SEGMENTATION_NAMES.contains(&k.name.as_str())
+                && !ordered.contains(k.name.as_str())
+        })
+        .collect();
+    Ok(result)
+}
@@ -0,0 +1,73 @@
+use egui::{Color32, ComboBox, RichText, TextEdit, Ui, Widget};
+
+use crate::app::{util::token_pattern::TokenPattern, widgets::Token};
+
+/// Opacity of the token that do not match the filter.
+pub(super) const DIMMED_OPACITY: f32 = 0.25;
+
+/// How token that do not match the filter are displayed.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub(super) enum FilterMode {
+    #[default]
+    Dim,
+    Hide,
+}
+
+/// Filter to focus on the token and segmentation nodes matching a pattern.
+#[derive(Clone, Default)]
+pub(super) struct TokenFilter {
+    pub(super) text: String,
+    pub(super) pattern: Option<TokenPattern>,
+    pub(super) error: Option<String>,
+    pub(super) mode: FilterMode,
+}
+
+impl TokenFilter {
+    pub(super) fn update_pattern(&mut self) {
+        self.error = None;
+        self.pattern = None;
+        if !self.text.trim().is_empty() {
+            match TokenPattern::parse(&self.text) {
+                Ok(pattern) => self.pattern = Some(pattern),
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+    }
+
+    /// The opacity to draw the token with. Token that are hidden have an
+    /// opacity of 0.
+    pub(super) fn opacity(&self, t: &Token) -> f32 {
+        match &self.pattern {
+            Some(pattern) if !pattern.matches(t) => match self.mode {
+                FilterMode::Dim => DIMMED_OPACITY,
+                FilterMode::Hide => 0.0,
+            },
+            _ => 1.0,
+        }
+    }
+
+    /// Show the input for the filter pattern and how the other token are
+    /// displayed.
+    pub(super) fn show(&mut self, ui: &mut Ui) {
+        let response = TextEdit::singleline(&mut self.text)
+            .hint_text("Filter, e.g. pos=\"VERB\"")
+            .desired_width(160.0)
+            .ui(ui);
+        if response.changed() {
+            self.update_pattern();
+        }
+        ComboBox::from_id_salt("token-filter-mode")
+            .selected_text(match self.mode {
+                FilterMode::Dim => "Dim others",
+                FilterMode::Hide => "Hide others",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.mode, FilterMode::Dim, "Dim others");
+                ui.selectable_value(&mut self.mode, FilterMode::Hide, "Hide others");
+            });
+        if let Some(error) = &self.error {
+            ui.label(RichText::new(egui_phosphor::regular::WARNING).color(Color32::RED))
+                .on_hover_text(error);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use egui::{load::SizedTexture, mutex::Mutex, ColorImage, TextureHandle, TextureOptions, Ui};
+
+use crate::app::{
+    job_executor::FgJob,
+    util::media::{AudioPlayback, FrameStream, MediaBackend},
+    JobExecutor,
+};
+
+use super::MEDIA_FRAME_RATE;
+
+/// Maximum height of the video frame in the media panel.
+const VIDEO_HEIGHT: f32 = 240.0;
+/// How long the decoding job waits before checking the playback position
+/// again, if there is nothing to decode.
+const IDLE_INTERVAL: Duration = Duration::from_millis(10);
+/// Decoding continues from the current frame if the requested frame is at
+/// most this many frames ahead, otherwise the video is decoded from the
+/// requested position again.
+const MAX_SKIPPED_FRAMES: i64 = 2 * MEDIA_FRAME_RATE as i64;
+/// The audio is restarted at the playback position if it differs more than
+/// this many seconds, e.g. after seeking while playing.
+const MAX_AUDIO_OFFSET: f64 = 0.2;
+
+/// State shared by the media panel and the job that decodes the media file.
+#[derive(Default)]
+struct SharedMedia {
+    /// Playback position in seconds.
+    position: f64,
+    playing: bool,
+    /// The last decoded frame, which has not been shown yet.
+    decoded: Option<ColorImage>,
+    error: Option<String>,
+}
+
+/// Plays a linked media file. The video frames and the audio are decoded by
+/// a single long running job for each file, which follows the playback
+/// position and ends when the player and all its clones have been dropped.
+#[derive(Clone)]
+pub(crate) struct MediaPlayer {
+    media: PathBuf,
+    backend: Arc<dyn MediaBackend>,
+    shared: Arc<Mutex<SharedMedia>>,
+    shows_video: bool,
+    plays_audio: bool,
+    started: bool,
+    texture: Option<TextureHandle>,
+}
+
+impl MediaPlayer {
+    pub(crate) fn new(
+        media: PathBuf,
+        backend: Arc<dyn MediaBackend>,
+        shows_video: bool,
+        plays_audio: bool,
+    ) -> Self {
+        Self {
+            media,
+            backend,
+            shared: Arc::new(Mutex::new(SharedMedia::default())),
+            shows_video,
+            plays_audio,
+            started: false,
+            texture: None,
+        }
+    }
+
+    pub(crate) fn media(&self) -> &Path {
+        &self.media
+    }
+
+    pub(crate) fn shows_video(&self) -> bool {
+        self.shows_video
+    }
+
+    /// Pass the playback position (in seconds) to the decoding job, which is
+    /// started the first time.
+    pub(crate) fn update(
+        &mut self,
+        ctx: &egui::Context,
+        jobs: &JobExecutor,
+        position: f64,
+        playing: bool,
+    ) {
+        {
+            let mut shared = self.shared.lock();
+            shared.position = position;
+            shared.playing = playing;
+        }
+        if !self.started {
+            self.started = true;
+            self.start_job(ctx, jobs);
+        }
+    }
+
+    /// Show the latest decoded frame of the video.
+    pub(crate) fn show(&mut self, ui: &mut Ui) {
+        let (decoded, error) = {
+            let mut shared = self.shared.lock();
+            (shared.decoded.take(), shared.error.clone())
+        };
+        if let Some(image) = decoded {
+            if let Some(texture) = &mut self.texture {
+                texture.set(image, TextureOptions::LINEAR);
+            } else {
+                self.texture = Some(ui.ctx().load_texture(
+                    "video-frame",
+                    image,
+                    TextureOptions::LINEAR,
+                ));
+            }
+        }
+
+        if let Some(error) = error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if let Some(texture) = &self.texture {
+            ui.add(
+                egui::Image::from_texture(SizedTexture::from_handle(texture))
+                    .max_height(VIDEO_HEIGHT)
+                    .shrink_to_fit(),
+            );
+        } else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Loading video");
+            });
+        }
+    }
+
+    fn start_job(&self, ctx: &egui::Context, jobs: &JobExecutor) {
+        let file_name = self.media.file_name().unwrap_or_default().to_string_lossy();
+        let decoder = MediaDecoder {
+            media: self.media.clone(),
+            backend: self.backend.clone(),
+            shared: self.shared.clone(),
+            shows_video: self.shows_video,
+            plays_audio: self.plays_audio,
+            ctx: ctx.clone(),
+        };
+        jobs.add_background(
+            &format!("Playing media {file_name}"),
+            move |job| {
+                job.allow_cancel();
+                let result = decoder.run(&job);
+                if let Err(e) = &result {
+                    decoder.shared.lock().error = Some(format!("{e:#}"));
+                    decoder.ctx.request_repaint();
+                }
+                result
+            },
+            |_, _| {},
+        );
+    }
+}
+
+/// Decodes the media file in the job of a [`MediaPlayer`].
+struct MediaDecoder {
+    media: PathBuf,
+    backend: Arc<dyn MediaBackend>,
+    shared: Arc<Mutex<SharedMedia>>,
+    shows_video: bool,
+    plays_audio: bool,
+    ctx: egui::Context,
+}
+
+impl MediaDecoder {
+    fn run(&self, job: &FgJob) -> Result<()> {
+        // The decoded frames and the number of the next frame
+        let mut frames: Option<(Box<dyn FrameStream>, i64)> = None;
+        let mut shown_frame = None;
+        // The audio and the playback position when it has been started
+        let mut audio: Option<(AudioPlayback, f64, Instant)> = None;
+        let mut audio_failed = false;
+        // The player holds the other reference to the shared state, so the
+        // job ends when the player has been dropped
+        while Arc::strong_count(&self.shared) > 1 {
+            job.check_cancelled()?;
+            let (position, playing) = {
+                let shared = self.shared.lock();
+                (shared.position, shared.playing)
+            };
+
+            if !playing {
+                audio = None;
+            } else if self.plays_audio && !audio_failed {
+                let offset = audio.as_ref().map(|(_, started_at, started)| {
+                    (started_at + started.elapsed().as_secs_f64() - position).abs()
+                });
+                if offset.is_none_or(|offset| offset > MAX_AUDIO_OFFSET) {
+                    // Stop the previous playback before starting the new one
+                    audio = None;
+                    match self.backend.play_audio(&self.media, position) {
+                        Ok(playback) => audio = Some((playback, position, Instant::now())),
+                        Err(e) => {
+                            // The video can still be shown without audio
+                            log::warn!("Could not play audio of {}: {e:#}", self.media.display());
+                            audio_failed = true;
+                        }
+                    }
+                }
+            }
+
+            let frame = frame_number(position);
+            if self.shows_video && shown_frame != Some(frame) {
+                let continues = frames
+                    .as_ref()
+                    .is_some_and(|(_, next)| *next <= frame && frame <= *next + MAX_SKIPPED_FRAMES);
+                if !continues {
+                    let start = frame as f64 / MEDIA_FRAME_RATE;
+                    let stream = self
+                        .backend
+                        .decode_video(&self.media, start, MEDIA_FRAME_RATE)?;
+                    frames = Some((stream, frame));
+                }
+                if let Some((stream, next)) = &mut frames {
+                    let decoded_frame = *next;
+                    *next += 1;
+                    match stream.next_frame()? {
+                        Some(image) if decoded_frame == frame => {
+                            self.shared.lock().decoded = Some(image);
+                            shown_frame = Some(frame);
+                            self.ctx.request_repaint();
+                        }
+                        // Skip frames until the requested one is reached
+                        Some(_) => {}
+                        None => {
+                            // The position is after the end of the video
+                            frames = None;
+                            shown_frame = Some(frame);
+                        }
+                    }
+                }
+            } else {
+                std::thread::sleep(IDLE_INTERVAL);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number of the frame that is shown at the position (in seconds).
+fn frame_number(position: f64) -> i64 {
+    (position.max(0.0) * MEDIA_FRAME_RATE).round() as i64
+}
@@ -1,7 +1,15 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
 
 use egui::{mutex::RwLock, Ui};
-use log::debug;
+use egui_notify::Toast;
+use log::{debug, warn};
 
 use super::AnnatomicApp;
 
@@ -12,8 +20,28 @@ use super::AnnatomicApp;
 #[derive(Clone, Default)]
 pub(crate) struct FgJob {
     msg: Arc<RwLock<Option<String>>>,
+    cancellable: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    priority: JobPriority,
+    queued_at: Option<Instant>,
+    /// Set when a worker thread starts the job.
+    started_at: Arc<OnceLock<Instant>>,
+    /// Set if the job has been removed from the queue before it started.
+    removed: Arc<AtomicBool>,
 }
 
+/// Error returned by a job that has been cancelled by the user.
+#[derive(Debug)]
+pub(crate) struct JobCancelled;
+
+impl std::fmt::Display for JobCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Job has been cancelled")
+    }
+}
+
+impl std::error::Error for JobCancelled {}
+
 impl FgJob {
     pub(crate) fn update_message<S>(&self, message: S)
     where
@@ -22,15 +50,95 @@ impl FgJob {
         let mut lock = self.msg.write();
         lock.replace(message.into());
     }
+
+    /// Allow the user to cancel this job. The worker must call
+    /// [`FgJob::check_cancelled`] regularly.
+    pub(crate) fn allow_cancel(&self) {
+        self.cancellable.store(true, atomic::Ordering::Relaxed);
+    }
+
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, atomic::Ordering::Relaxed);
+    }
+
+    /// Returns a [`JobCancelled`] error if the user cancelled the job.
+    pub(crate) fn check_cancelled(&self) -> anyhow::Result<()> {
+        if self.cancelled.load(atomic::Ordering::Relaxed) {
+            Err(JobCancelled.into())
+        } else {
+            Ok(())
+        }
+    }
 }
 
 type FnStateUpdate = Box<dyn FnOnce(&mut AnnatomicApp) + Send + Sync>;
 
+/// Interactive jobs taking longer than this are logged, since the user has
+/// to wait for them after each edit.
+const SLOW_INTERACTIVE_JOB: Duration = Duration::from_secs(2);
+
+/// Number of finished jobs that are kept to be shown in the job overview.
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// How a job has ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum JobOutcome {
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// A job that has been added, but not finished yet.
+#[derive(Clone, Debug)]
+pub(crate) struct ActiveJob {
+    pub(crate) title: String,
+    pub(crate) priority: JobPriority,
+    pub(crate) message: Option<String>,
+    /// How long the job has been waiting in the queue or `None` if it has
+    /// already been started.
+    pub(crate) queued_for: Option<Duration>,
+    /// How long the job has been running or `None` if it is still queued.
+    pub(crate) running_for: Option<Duration>,
+}
+
+/// A job that has recently finished.
+#[derive(Clone, Debug)]
+pub(crate) struct FinishedJob {
+    pub(crate) title: String,
+    pub(crate) outcome: JobOutcome,
+    /// Time the job has been waiting in the queue before it started.
+    pub(crate) waited: Duration,
+    /// Time the worker needed to execute the job.
+    pub(crate) duration: Duration,
+    pub(crate) finished_at: Instant,
+}
+
+/// Determines which worker threads execute a job.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum JobPriority {
+    /// Short jobs triggered by an edit, e.g. applying a changeset. They run
+    /// on a dedicated worker, so they are not delayed by batch jobs.
+    Interactive,
+    /// Long running jobs like exports, imports or validations that share the
+    /// global thread pool.
+    #[default]
+    Batch,
+    /// Jobs that load data for a view that stays usable in the meantime,
+    /// e.g. the children of an expanded node. They share the global thread
+    /// pool, but the view is not replaced by their progress.
+    Background,
+}
+
 #[derive(Default, Clone)]
 pub(crate) struct JobExecutor {
     running: Arc<RwLock<BTreeMap<String, FgJob>>>,
     finished: Arc<RwLock<BTreeMap<String, FnStateUpdate>>>,
     failed: Arc<RwLock<BTreeMap<String, anyhow::Error>>>,
+    /// The most recently finished jobs, the latest one first.
+    recently_finished: Arc<RwLock<VecDeque<FinishedJob>>>,
+    /// Created on the first interactive job. If the thread can not be
+    /// created, interactive jobs use the global thread pool instead.
+    interactive_worker: Arc<OnceLock<Option<rayon::ThreadPool>>>,
 }
 
 impl JobExecutor {
@@ -40,22 +148,110 @@ impl JobExecutor {
         U: FnOnce(R, &mut AnnatomicApp) + Send + Sync + 'static,
         R: Send + Sync + 'static,
     {
-        debug!("Adding foreground job \"{title}\"");
+        self.add_with_priority(title, JobPriority::Batch, worker, state_updater);
+    }
+
+    /// Add a job that should be applied promptly, even while long running
+    /// batch jobs occupy the global thread pool.
+    pub(crate) fn add_interactive<F, U, R>(&self, title: &str, worker: F, state_updater: U)
+    where
+        F: FnOnce(FgJob) -> anyhow::Result<R> + Send + 'static,
+        U: FnOnce(R, &mut AnnatomicApp) + Send + Sync + 'static,
+        R: Send + Sync + 'static,
+    {
+        self.add_with_priority(title, JobPriority::Interactive, worker, state_updater);
+    }
+
+    /// Add a job that runs while the current view is still shown.
+    pub(crate) fn add_background<F, U, R>(&self, title: &str, worker: F, state_updater: U)
+    where
+        F: FnOnce(FgJob) -> anyhow::Result<R> + Send + 'static,
+        U: FnOnce(R, &mut AnnatomicApp) + Send + Sync + 'static,
+        R: Send + Sync + 'static,
+    {
+        self.add_with_priority(title, JobPriority::Background, worker, state_updater);
+    }
+
+    fn interactive_worker(&self) -> Option<&rayon::ThreadPool> {
+        self.interactive_worker
+            .get_or_init(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(1)
+                    .thread_name(|_| "interactive-jobs".to_string())
+                    .build()
+                    .inspect_err(|e| warn!("Could not create worker for interactive jobs: {e}"))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    pub(crate) fn add_with_priority<F, U, R>(
+        &self,
+        title: &str,
+        priority: JobPriority,
+        worker: F,
+        state_updater: U,
+    ) where
+        F: FnOnce(FgJob) -> anyhow::Result<R> + Send + 'static,
+        U: FnOnce(R, &mut AnnatomicApp) + Send + Sync + 'static,
+        R: Send + Sync + 'static,
+    {
+        debug!("Adding foreground job \"{title}\" with priority {priority:?}");
         let running_jobs = self.running.clone();
         let failed_jobs = self.failed.clone();
         let finished_jobs = self.finished.clone();
+        let recently_finished = self.recently_finished.clone();
 
-        let single_job = FgJob::default();
+        let single_job = FgJob {
+            priority,
+            queued_at: Some(Instant::now()),
+            ..Default::default()
+        };
         {
             let mut lock = running_jobs.write();
             lock.insert(title.to_string(), single_job.clone());
             debug!("Number of currently running jobs: {}", lock.len());
         }
         let title = title.to_string();
-        rayon::spawn(move || {
+        let job = move || {
+            let started = Instant::now();
+            {
+                // Removing a queued job holds the same lock, so the job is
+                // either removed or started, but not both
+                let _jobs = running_jobs.write();
+                if single_job.removed.load(atomic::Ordering::Relaxed) {
+                    debug!("Skipping foreground job \"{title}\" removed from the queue");
+                    return;
+                }
+                single_job.started_at.get_or_init(|| started);
+            }
             debug!("Spawning foreground job \"{title}\"");
-            let result = worker(single_job);
-            debug!("Finished foreground job \"{title}\"");
+            let waited = single_job
+                .queued_at
+                .map(|queued_at| started.saturating_duration_since(queued_at))
+                .unwrap_or_default();
+            let result = worker(single_job.clone());
+            let duration = started.elapsed();
+            debug!("Finished foreground job \"{title}\" after {duration:?}");
+            if priority == JobPriority::Interactive && duration > SLOW_INTERACTIVE_JOB {
+                warn!("Interactive job \"{title}\" took {duration:?}");
+            }
+            let outcome = match &result {
+                Ok(_) => JobOutcome::Succeeded,
+                Err(e) if e.is::<JobCancelled>() => JobOutcome::Cancelled,
+                Err(_) => JobOutcome::Failed,
+            };
+            {
+                let mut recently_finished = recently_finished.write();
+                recently_finished.push_front(FinishedJob {
+                    title: title.clone(),
+                    outcome,
+                    waited,
+                    duration,
+                    finished_at: Instant::now(),
+                });
+                recently_finished.truncate(MAX_FINISHED_JOBS);
+            }
             match result {
                 Ok(result) => {
                     let mut finished_jobs = finished_jobs.write();
@@ -70,14 +266,31 @@ impl JobExecutor {
                 }
             }
             let mut jobs = running_jobs.write();
-            jobs.remove(&title);
-        });
+            if jobs
+                .get(&title)
+                .is_some_and(|job| Arc::ptr_eq(&job.started_at, &single_job.started_at))
+            {
+                jobs.remove(&title);
+            }
+        };
+        match priority {
+            JobPriority::Interactive => match self.interactive_worker() {
+                Some(pool) => pool.spawn(job),
+                None => rayon::spawn(job),
+            },
+            JobPriority::Batch | JobPriority::Background => rayon::spawn(job),
+        }
     }
 
     pub(super) fn show(&self, ui: &mut Ui, app: &mut AnnatomicApp) -> bool {
         let mut failed_jobs = self.failed.write();
-        while let Some((_title, e)) = failed_jobs.pop_first() {
-            app.notifier.report_error(e);
+        while let Some((title, e)) = failed_jobs.pop_first() {
+            if e.is::<JobCancelled>() {
+                app.notifier
+                    .add_toast(Toast::info(format!("{title}: cancelled")));
+            } else {
+                app.notifier.report_error(e);
+            }
         }
 
         let mut finished_jobs = self.finished.write();
@@ -86,31 +299,109 @@ impl JobExecutor {
         }
 
         let running_jobs = self.running.read();
-        let has_jobs = !running_jobs.is_empty();
-        for (title, job) in running_jobs.iter() {
+        let mut has_jobs = false;
+        let mut remove_requested = None;
+        for (title, job) in running_jobs
+            .iter()
+            .filter(|(_, job)| job.priority != JobPriority::Background)
+        {
+            has_jobs = true;
+            let is_queued = job.started_at.get().is_none();
             ui.horizontal(|ui| {
-                ui.spinner();
+                if is_queued {
+                    ui.label(egui_phosphor::regular::HOURGLASS);
+                } else {
+                    ui.spinner();
+                }
                 ui.heading(title);
             });
+            if is_queued {
+                ui.label("Waiting for other jobs to finish");
+                if ui.button("Remove from queue").clicked() {
+                    remove_requested = Some(title.clone());
+                }
+                continue;
+            }
 
             let msg = job.msg.read();
             ui.label(
                 msg.clone()
                     .unwrap_or_else(|| "Please wait for the background job to finish".into()),
             );
+            if job.cancellable.load(atomic::Ordering::Relaxed) {
+                let cancelled = job.cancelled.load(atomic::Ordering::Relaxed);
+                if ui
+                    .add_enabled(!cancelled, egui::Button::new("Cancel"))
+                    .clicked()
+                {
+                    job.cancel();
+                }
+            }
+        }
+        drop(running_jobs);
+        if let Some(title) = remove_requested {
+            self.remove_queued(&title);
         }
 
         has_jobs
     }
 
+    /// Remove a job that has not been started yet, so it is never executed.
+    /// Returns false if there is no such job or it is already running.
+    pub(crate) fn remove_queued(&self, title: &str) -> bool {
+        let mut running_jobs = self.running.write();
+        if running_jobs
+            .get(title)
+            .is_some_and(|job| job.started_at.get().is_none())
+        {
+            if let Some(job) = running_jobs.remove(title) {
+                job.removed.store(true, atomic::Ordering::Relaxed);
+                debug!("Removed foreground job \"{title}\" from the queue");
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The jobs that are queued or running, ordered by their title.
+    pub(crate) fn active_jobs(&self) -> Vec<ActiveJob> {
+        let now = Instant::now();
+        self.running
+            .read()
+            .iter()
+            .map(|(title, job)| {
+                let started_at = job.started_at.get();
+                ActiveJob {
+                    title: title.clone(),
+                    priority: job.priority,
+                    message: job.msg.read().clone(),
+                    queued_for: match (started_at, job.queued_at) {
+                        (None, Some(queued_at)) => Some(now.saturating_duration_since(queued_at)),
+                        (None, None) => Some(Duration::ZERO),
+                        (Some(_), _) => None,
+                    },
+                    running_for: started_at
+                        .map(|started_at| now.saturating_duration_since(*started_at)),
+                }
+            })
+            .collect()
+    }
+
+    /// The most recently finished jobs, the latest one first.
+    pub(crate) fn recently_finished(&self) -> Vec<FinishedJob> {
+        self.recently_finished.read().iter().cloned().collect()
+    }
+
     pub(crate) fn has_active_job_with_title(&self, title: &str) -> bool {
         let running_jobs = self.running.read();
         running_jobs.contains_key(title)
     }
 
-    #[cfg(test)]
     pub(crate) fn has_running_jobs(&self) -> bool {
         let running_jobs = self.running.read();
         !running_jobs.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests;
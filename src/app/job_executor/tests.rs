@@ -0,0 +1,103 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Barrier,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{JobExecutor, JobOutcome, MAX_FINISHED_JOBS};
+
+fn wait_for_job(jobs: &JobExecutor, title: &str) -> bool {
+    let started = Instant::now();
+    while jobs.has_active_job_with_title(title) {
+        if started.elapsed() > Duration::from_secs(10) {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    true
+}
+
+#[test]
+fn interactive_jobs_run_while_batch_jobs_are_busy() {
+    let jobs = JobExecutor::default();
+    // Block all threads of the global pool until the interactive job is done
+    let number_of_threads = rayon::current_num_threads();
+    let barrier = Arc::new(Barrier::new(number_of_threads + 1));
+    for i in 0..number_of_threads {
+        let barrier = barrier.clone();
+        jobs.add(
+            &format!("Batch job {i}"),
+            move |_| {
+                barrier.wait();
+                Ok(())
+            },
+            |_, _| {},
+        );
+    }
+
+    jobs.add_interactive("Interactive job", |_| Ok(()), |_, _| {});
+    let finished = wait_for_job(&jobs, "Interactive job");
+    barrier.wait();
+
+    assert!(finished);
+    for i in 0..number_of_threads {
+        assert!(wait_for_job(&jobs, &format!("Batch job {i}")));
+    }
+    assert!(!jobs.has_running_jobs());
+}
+
+#[test]
+fn remove_queued_job() {
+    let jobs = JobExecutor::default();
+    // Keep all threads of the global pool busy, so the next job is queued
+    let number_of_threads = rayon::current_num_threads();
+    let barrier = Arc::new(Barrier::new(number_of_threads + 1));
+    for i in 0..number_of_threads {
+        let barrier = barrier.clone();
+        jobs.add(
+            &format!("Batch job {i}"),
+            move |_| {
+                barrier.wait();
+                Ok(())
+            },
+            |_, _| {},
+        );
+    }
+    let executed = Arc::new(AtomicBool::new(false));
+    {
+        let executed = executed.clone();
+        jobs.add(
+            "Queued job",
+            move |_| {
+                executed.store(true, Ordering::Relaxed);
+                Ok(())
+            },
+            |_, _| {},
+        );
+    }
+    let queued_job = jobs
+        .active_jobs()
+        .into_iter()
+        .find(|job| job.title == "Queued job")
+        .unwrap();
+    assert!(queued_job.queued_for.is_some());
+    assert!(queued_job.running_for.is_none());
+
+    assert!(jobs.remove_queued("Queued job"));
+    assert!(!jobs.has_active_job_with_title("Queued job"));
+    barrier.wait();
+    for i in 0..number_of_threads {
+        assert!(wait_for_job(&jobs, &format!("Batch job {i}")));
+    }
+    // Running or finished jobs can not be removed
+    assert!(!jobs.remove_queued("Batch job 0"));
+
+    assert!(!executed.load(Ordering::Relaxed));
+    let finished = jobs.recently_finished();
+    assert_eq!(number_of_threads.min(MAX_FINISHED_JOBS), finished.len());
+    assert!(finished
+        .iter()
+        .all(|job| job.outcome == JobOutcome::Succeeded && job.title.starts_with("Batch job")));
+}
@@ -1,31 +1,57 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fs::File,
-    io::BufWriter,
+    io::{BufReader, BufWriter},
     path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
-use anyhow::{Context, Ok, Result};
-use cache::CorpusCache;
+use anyhow::{anyhow, bail, Context, Ok, Result};
+use history::{Checkpoint, UndoGroup, UndoHistory};
+use lock::{CorpusLock, LockState};
 
-use egui::util::undoer::{self, Undoer};
 use egui_notify::Toast;
 use graphannis::{
+    graph::{AnnoKey, NodeID},
+    model::AnnotationComponent,
     update::{GraphUpdate, UpdateEvent},
     AnnotationGraph,
 };
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{ANNIS_NS, NODE_TYPE_KEY},
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::job_executor::JobExecutor;
-use super::{Notifier, APP_ID};
+use crate::api::{
+    changeset::apply_changeset,
+    create_corpus, export_verification, graphml_export,
+    import_report::{self, ImportReport},
+    load_graph, provenance, CorpusCache,
+};
+
+use super::{
+    editors::document_editor::prefetch::DocumentPrefetch,
+    job_executor::JobExecutor,
+    util::{
+        annis_config::LayerSettings,
+        document_template::{new_document_update, parent_for_new_document, DocumentTemplate},
+        health_check::{check_corpus_health, HealthReport},
+        language, metadata_csv,
+        tokenizer::TokenizerKind,
+    },
+};
+use super::{AnnatomicApp, Notifier, APP_ID};
 
-#[cfg(test)]
 use egui::mutex::RwLock;
-#[cfg(test)]
-use std::sync::Arc;
 
-mod cache;
+pub(crate) mod configuration;
+pub(crate) mod history;
+mod integrity;
+mod lock;
+pub(crate) mod refactoring;
 #[cfg(test)]
 mod tests;
 
@@ -36,6 +62,39 @@ pub(crate) struct Corpus {
     diff_to_last_save: Vec<UpdateEvent>,
 }
 
+/// Number of changesets per corpus that have been queued but whose results
+/// have not been applied to the project yet.
+type InFlightCounts = Arc<RwLock<BTreeMap<String, usize>>>;
+
+/// Marks a changeset of a corpus as in flight until it is dropped, which
+/// happens after the job has finished, either successfully or with an error.
+struct InFlightChangeset {
+    counts: InFlightCounts,
+    corpus_name: String,
+}
+
+impl InFlightChangeset {
+    fn new(counts: InFlightCounts, corpus_name: &str) -> Self {
+        *counts.write().entry(corpus_name.to_string()).or_default() += 1;
+        Self {
+            counts,
+            corpus_name: corpus_name.to_string(),
+        }
+    }
+}
+
+impl Drop for InFlightChangeset {
+    fn drop(&mut self) {
+        let mut counts = self.counts.write();
+        if let Some(count) = counts.get_mut(&self.corpus_name) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                counts.remove(&self.corpus_name);
+            }
+        }
+    }
+}
+
 impl Corpus {
     pub(crate) fn new<S, P>(name: S, location: P) -> Self
     where
@@ -55,23 +114,86 @@ pub(crate) struct Project {
     updates_pending: bool,
     pub(crate) selected_corpus: Option<Corpus>,
     pub(crate) scheduled_for_deletion: Option<String>,
+    /// Locations of the corpora. Relative locations are relative to the
+    /// corpus storage directory, use [`Project::corpus_location`] to resolve
+    /// them.
     pub(crate) corpus_locations: BTreeMap<String, PathBuf>,
+    /// Store the locations of new corpora relative to the corpus storage
+    /// directory, so the directory can be moved to another machine.
+    #[serde(default)]
+    pub(crate) portable: bool,
+    /// User-defined tags of the corpora, either plain or of the form
+    /// `category:value`.
+    #[serde(default)]
+    pub(crate) corpus_tags: BTreeMap<String, BTreeSet<String>>,
+    /// Tokenizers of the corpora that do not use the default one.
+    #[serde(default)]
+    pub(crate) corpus_tokenizers: BTreeMap<String, TokenizerKind>,
+    /// Layer settings of the corpora, e.g. from their ANNIS configuration.
+    #[serde(default)]
+    pub(crate) corpus_layers: BTreeMap<String, LayerSettings>,
+    /// Namespace that is pre-filled for new annotations of the corpora.
+    #[serde(default)]
+    pub(crate) corpus_namespaces: BTreeMap<String, String>,
+    /// Corpora that are shown first in the corpus list, in this order.
+    #[serde(default)]
+    pub(crate) pinned_corpora: Vec<String>,
+    /// Templates for new documents by their name.
+    #[serde(default)]
+    pub(crate) document_templates: BTreeMap<String, DocumentTemplate>,
+    /// Name of the profile if it has its own storage directory for corpora.
+    #[serde(default)]
+    pub(crate) storage_profile: Option<String>,
+    /// Corpora whose location does not exist on disk anymore and that need
+    /// to be resolved by the user.
+    #[serde(skip)]
+    pub(crate) missing_corpora: Vec<String>,
+    /// If true, the selected corpus is locked by another instance and must not be changed.
+    #[serde(skip)]
+    pub(crate) read_only: bool,
+    /// Result of the health check of the selected corpus, which is run in
+    /// the background when the corpus is selected.
+    #[serde(skip)]
+    pub(crate) health_report: Option<HealthReport>,
+    #[serde(skip)]
+    corpus_lock: Option<Arc<CorpusLock>>,
+    #[serde(skip, default = "new_instance_id")]
+    instance_id: String,
     #[serde(skip)]
     pub(super) corpus_cache: CorpusCache,
+    /// Documents of the selected corpus that have been loaded in advance.
+    #[serde(skip)]
+    pub(super) prefetched_documents: DocumentPrefetch,
     #[serde(skip)]
     notifier: Notifier,
     #[serde(skip)]
     jobs: JobExecutor,
     #[serde(skip)]
-    undoer: Undoer<Corpus>,
+    undoer: UndoHistory,
+    /// Checkpoints of the selected corpus. They are only valid until the
+    /// undo history is reset, e.g. when the corpus is saved.
+    #[serde(skip)]
+    checkpoints: Vec<Checkpoint>,
+    #[serde(skip)]
+    in_flight_changesets: InFlightCounts,
+    /// If set, changes are recorded in the provenance log with this annotator name.
+    #[serde(skip)]
+    provenance_annotator: Option<String>,
+    /// Namespace and initials of the annotation added to new and changed nodes.
+    #[serde(skip)]
+    annotator_stamp: Option<(String, String)>,
+    /// If set, the changes since the last save are regularly replayed to
+    /// check that undo and redo restore the same graph.
+    #[serde(skip)]
+    integrity_check_interval: Option<Duration>,
+    #[serde(skip)]
+    last_integrity_check: Option<Instant>,
 }
 
-fn default_undoer() -> Undoer<Corpus> {
-    let undo_settings = undoer::Settings {
-        max_undos: 10,
-        ..Default::default()
-    };
-    Undoer::with_settings(undo_settings)
+const INTEGRITY_CHECK_JOB: &str = "Checking consistency of unsaved changes";
+
+fn new_instance_id() -> String {
+    Uuid::new_v4().to_string()
 }
 
 impl Project {
@@ -80,26 +202,116 @@ impl Project {
             updates_pending: false,
             selected_corpus: None,
             corpus_cache: CorpusCache::default(),
+            prefetched_documents: DocumentPrefetch::default(),
             scheduled_for_deletion: None,
             corpus_locations: BTreeMap::new(),
+            portable: false,
+            corpus_tags: BTreeMap::new(),
+            corpus_tokenizers: BTreeMap::new(),
+            corpus_layers: BTreeMap::new(),
+            corpus_namespaces: BTreeMap::new(),
+            pinned_corpora: Vec::new(),
+            document_templates: BTreeMap::new(),
+            storage_profile: None,
+            missing_corpora: Vec::new(),
+            read_only: false,
+            health_report: None,
+            corpus_lock: None,
+            instance_id: new_instance_id(),
             notifier,
             jobs,
-            undoer: default_undoer(),
+            undoer: UndoHistory::default(),
+            checkpoints: Vec::new(),
+            in_flight_changesets: InFlightCounts::default(),
+            provenance_annotator: None,
+            annotator_stamp: None,
+            integrity_check_interval: None,
+            last_integrity_check: None,
         }
     }
 
     pub(crate) fn corpus_storage_dir(&self) -> Result<PathBuf> {
-        let result = eframe::storage_dir(APP_ID)
-            .context("Unable to get local file storage path")
-            .map(|p| p.join("corpora"))?;
+        let storage_dir =
+            eframe::storage_dir(APP_ID).context("Unable to get local file storage path")?;
+        let result = if let Some(profile) = &self.storage_profile {
+            storage_dir.join("profiles").join(profile).join("corpora")
+        } else {
+            storage_dir.join("corpora")
+        };
         Ok(result)
     }
 
+    /// The absolute location of the corpus with the given name.
+    pub(crate) fn corpus_location(&self, corpus_name: &str) -> Option<PathBuf> {
+        self.corpus_locations
+            .get(corpus_name)
+            .map(|location| self.resolve_location(location))
+    }
+
+    fn resolve_location(&self, location: &Path) -> PathBuf {
+        if location.is_relative() {
+            match self.corpus_storage_dir() {
+                Ok(storage_dir) => return storage_dir.join(location),
+                Err(e) => log::warn!("Could not resolve relative corpus location: {e}"),
+            }
+        }
+        location.to_path_buf()
+    }
+
+    /// The location as it is stored in the project. For portable projects,
+    /// locations inside the corpus storage directory are relative to it.
+    pub(crate) fn stored_location(&self, location: PathBuf) -> PathBuf {
+        if self.portable {
+            if let Ok(storage_dir) = self.corpus_storage_dir() {
+                if let Ok(relative) = location.strip_prefix(&storage_dir) {
+                    return relative.to_path_buf();
+                }
+            }
+        }
+        location
+    }
+
+    /// Store the locations of all corpora inside the corpus storage
+    /// directory relative to it. Returns the names of the corpora that are
+    /// stored elsewhere and still use an absolute location.
+    pub(crate) fn make_portable(&mut self) -> Vec<String> {
+        self.portable = true;
+        let mut outside = Vec::new();
+        let names: Vec<String> = self.corpus_locations.keys().cloned().collect();
+        for name in names {
+            if let Some(location) = self.corpus_location(&name) {
+                let stored = self.stored_location(location);
+                if stored.is_absolute() {
+                    outside.push(name.clone());
+                }
+                self.corpus_locations.insert(name, stored);
+            }
+        }
+        outside
+    }
+
     pub(crate) fn delete_corpus(&mut self, corpus_name: String) {
         self.scheduled_for_deletion = None;
 
+        if self.read_only
+            && self
+                .selected_corpus
+                .as_ref()
+                .is_some_and(|c| c.name == corpus_name)
+        {
+            self.notifier.add_toast(Toast::error(format!(
+                "Corpus \"{corpus_name}\" is locked by another instance and can not be deleted."
+            )));
+            return;
+        }
+        self.corpus_tags.remove(&corpus_name);
+        self.corpus_tokenizers.remove(&corpus_name);
+        self.corpus_layers.remove(&corpus_name);
+        self.corpus_namespaces.remove(&corpus_name);
+        self.pinned_corpora.retain(|c| *c != corpus_name);
         // Delete the folder where the corpus is stored
-        if let Some(location) = self.corpus_locations.remove(&corpus_name) {
+        if let Some(location) = self.corpus_location(&corpus_name) {
+            self.corpus_locations.remove(&corpus_name);
             let title = format!(
                 "Deleting corpus \"{corpus_name}\" from {}",
                 location.to_string_lossy()
@@ -127,12 +339,23 @@ impl Project {
         }
 
         self.selected_corpus = None;
+        self.corpus_lock = None;
+        self.read_only = false;
+        self.health_report = None;
         if let Some(name) = selection {
-            if let Some(location) = self.corpus_locations.get(&name) {
+            if let Some(location) = self.corpus_location(&name) {
+                if !location.is_dir() {
+                    if !self.missing_corpora.contains(&name) {
+                        self.missing_corpora.push(name);
+                    }
+                    return;
+                }
+                self.lock_corpus(location.clone(), false);
                 let new_selection = Corpus::new(name, location);
-                self.undoer = default_undoer();
+                self.reset_undo_history();
                 self.undoer.add_undo(&new_selection);
                 self.selected_corpus = Some(new_selection);
+                self.check_health();
             } else {
                 self.notifier
                     .add_toast(Toast::error(format!("Missing location for corpus {name}")));
@@ -140,62 +363,662 @@ impl Project {
         }
     }
 
+    /// Check in the background whether all components of the selected
+    /// corpus can be loaded and the ones needed by the editors exist.
+    pub(crate) fn check_health(&self) {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add_background(
+                &format!("Checking corpus \"{}\"", selected_corpus.name),
+                move |job| {
+                    job.update_message("Loading corpus if necessary");
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    job.update_message("Checking components");
+                    let report = check_corpus_health(&mut graph.write())?;
+                    Ok((selected_corpus.name, report))
+                },
+                |(corpus_name, report), app| {
+                    // Ignore the result if another corpus has been selected in the meantime
+                    if app
+                        .project
+                        .selected_corpus
+                        .as_ref()
+                        .is_some_and(|c| c.name == corpus_name)
+                    {
+                        if !report.problems.is_empty() {
+                            app.notifier.add_toast(Toast::warning(format!(
+                                "Corpus \"{corpus_name}\" has {} problem(s), see the start view.",
+                                report.problems.len()
+                            )));
+                        }
+                        app.project.health_report = Some(report);
+                    }
+                },
+            );
+        }
+    }
+
+    /// Create the lock file for the corpus at the given location. If the
+    /// corpus is already locked by another instance, the project is switched
+    /// to read-only mode.
+    fn lock_corpus(&mut self, location: PathBuf, force: bool) {
+        match CorpusLock::try_acquire(&location, &self.instance_id, force) {
+            Ok(LockState::Acquired(lock)) => {
+                self.corpus_lock = Some(Arc::new(lock));
+                self.read_only = false;
+            }
+            Ok(LockState::HeldByOther(owner)) => {
+                self.corpus_lock = None;
+                self.read_only = true;
+                self.notifier.add_toast(Toast::warning(format!(
+                    "The corpus is opened by another annatomic instance ({owner}). Opening it read-only."
+                )));
+            }
+            Err(e) => {
+                // Lock files are a safety net, failing to create them should not prevent editing
+                self.corpus_lock = None;
+                self.read_only = false;
+                self.notifier
+                    .report_error(e.context("Could not create corpus lock file"));
+            }
+        }
+    }
+
+    /// Take over the lock of the selected corpus, e.g. if the lock is a
+    /// leftover from a crashed instance.
+    pub(crate) fn force_lock_selected_corpus(&mut self) {
+        if let Some(location) = self.selected_corpus.as_ref().map(|c| c.location.clone()) {
+            self.lock_corpus(location, true);
+        }
+    }
+
+    /// Check that all corpus locations still exist and remember the ones
+    /// that have been moved or deleted.
+    pub(crate) fn check_corpus_locations(&mut self) {
+        let names: Vec<String> = self.corpus_locations.keys().cloned().collect();
+        for name in names {
+            let exists = self.corpus_location(&name).is_some_and(|l| l.is_dir());
+            if !exists && !self.missing_corpora.contains(&name) {
+                self.missing_corpora.push(name);
+            }
+        }
+        if let Some(selected_corpus) = &self.selected_corpus {
+            if self.missing_corpora.contains(&selected_corpus.name) {
+                self.selected_corpus = None;
+            }
+        }
+    }
+
+    /// Use a new location for a corpus that has been moved on disk. The
+    /// location must contain a corpus that can be loaded.
+    pub(crate) fn relocate_corpus(
+        &mut self,
+        corpus_name: &str,
+        new_location: PathBuf,
+    ) -> Result<()> {
+        if !new_location.is_dir() {
+            anyhow::bail!(
+                "Corpus location {} is not a directory",
+                new_location.to_string_lossy()
+            );
+        }
+        validate_corpus_location(&new_location).with_context(|| {
+            format!(
+                "{} does not contain a graphANNIS corpus",
+                new_location.to_string_lossy()
+            )
+        })?;
+        let new_location = self.stored_location(new_location);
+        self.corpus_locations
+            .insert(corpus_name.to_string(), new_location);
+        self.missing_corpora.retain(|c| c != corpus_name);
+        Ok(())
+    }
+
+    /// Rename the corpus entry of the project. If `rename_nodes` is true, the
+    /// top-level corpus node and all nodes below it are renamed in the graph
+    /// as well, which is only possible for the selected corpus.
+    pub(crate) fn rename_corpus(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+        rename_nodes: bool,
+    ) -> Result<()> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            anyhow::bail!("Empty corpus name not allowed");
+        }
+        if self.corpus_locations.contains_key(new_name) {
+            anyhow::bail!("Corpus \"{new_name}\" already exists");
+        }
+        let is_selected = self
+            .selected_corpus
+            .as_ref()
+            .is_some_and(|c| c.name == old_name);
+        if rename_nodes && !is_selected {
+            anyhow::bail!("Only the nodes of the selected corpus can be renamed");
+        }
+        if rename_nodes && self.read_only {
+            anyhow::bail!("Corpus is opened read-only, its nodes can not be renamed");
+        }
+        let stored_location = self
+            .corpus_locations
+            .remove(old_name)
+            .with_context(|| format!("Unknown corpus \"{old_name}\""))?;
+        self.corpus_locations
+            .insert(new_name.to_string(), stored_location);
+        if let Some(tags) = self.corpus_tags.remove(old_name) {
+            self.corpus_tags.insert(new_name.to_string(), tags);
+        }
+        if let Some(tokenizer) = self.corpus_tokenizers.remove(old_name) {
+            self.corpus_tokenizers
+                .insert(new_name.to_string(), tokenizer);
+        }
+        if let Some(layers) = self.corpus_layers.remove(old_name) {
+            self.corpus_layers.insert(new_name.to_string(), layers);
+        }
+        if let Some(namespace) = self.corpus_namespaces.remove(old_name) {
+            self.corpus_namespaces
+                .insert(new_name.to_string(), namespace);
+        }
+        for c in self
+            .missing_corpora
+            .iter_mut()
+            .chain(self.pinned_corpora.iter_mut())
+        {
+            if c == old_name {
+                *c = new_name.to_string();
+            }
+        }
+        if let Some(selected_corpus) = &mut self.selected_corpus {
+            if is_selected {
+                selected_corpus.name = new_name.to_string();
+            }
+        }
+
+        if let (true, Some(selected_corpus)) = (rename_nodes, self.selected_corpus.clone()) {
+            // The nodes are renamed with a changeset of the selected corpus,
+            // so the renaming can be undone like any other change
+            let corpus_cache = self.corpus_cache.clone();
+            let new_name = new_name.to_string();
+            self.jobs.add(
+                "Renaming corpus nodes",
+                move |job| {
+                    job.update_message("Loading corpus if necessary");
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    graph.write().ensure_loaded_all()?;
+                    let (update, renamed) =
+                        refactoring::rename_corpus_nodes(&graph.read(), &new_name, &job)?;
+                    Ok((update, renamed, new_name))
+                },
+                |(update, renamed, new_name), app| {
+                    if app
+                        .project
+                        .selected_corpus
+                        .as_ref()
+                        .is_none_or(|c| c.name != new_name)
+                    {
+                        app.notifier.report_error(anyhow!(
+                            "The corpus \"{new_name}\" is not selected anymore, its nodes have not been renamed"
+                        ));
+                        return;
+                    }
+                    app.project.add_changeset_then(update, move |app| {
+                        app.notifier.add_toast(Toast::info(format!(
+                            "Renamed {renamed} nodes of corpus \"{new_name}\"."
+                        )));
+                        app.load_editor(true);
+                    });
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Rename a document or sub-corpus node of the selected corpus and all
+    /// nodes below it, so that their node names stay consistent.
+    pub(crate) fn rename_document(&mut self, node_name: &str, new_name: &str) -> Result<()> {
+        let new_name = new_name.trim();
+        if self.read_only {
+            anyhow::bail!("Corpus is opened read-only, its nodes can not be renamed");
+        }
+        let selected_corpus = self
+            .selected_corpus
+            .as_ref()
+            .context("No corpus selected")?;
+        let location = selected_corpus.location.clone();
+        let corpus_name = selected_corpus.name.clone();
+        let corpus_cache = self.corpus_cache.clone();
+        let node_name = node_name.to_string();
+        let new_name = new_name.to_string();
+        self.jobs.add(
+            "Renaming document",
+            move |job| {
+                job.update_message("Loading corpus if necessary");
+                let graph = corpus_cache.get(&location)?;
+                let mut graph = graph.write();
+                let renamed =
+                    refactoring::rename_document_nodes(&mut graph, &node_name, &new_name, &job)?;
+                job.update_message("Persisting corpus");
+                graph.persist_to(&location)?;
+                corpus_cache.invalidate();
+                Ok(renamed)
+            },
+            move |renamed, app| {
+                if app
+                    .project
+                    .selected_corpus
+                    .as_ref()
+                    .is_some_and(|c| c.name == corpus_name)
+                {
+                    // The undo history refers to the old node names
+                    app.project.reset_undo_history();
+                    if let Some(selected_corpus) = app.project.selected_corpus.as_mut() {
+                        selected_corpus.diff_to_last_save.clear();
+                        app.project.undoer.add_undo(selected_corpus);
+                    }
+                    app.load_editor(true);
+                }
+                app.notifier
+                    .add_toast(Toast::info(format!("Renamed {renamed} nodes.")));
+            },
+        );
+        Ok(())
+    }
+
+    pub(crate) fn add_corpus_tag(&mut self, corpus_name: &str, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() {
+            self.corpus_tags
+                .entry(corpus_name.to_string())
+                .or_default()
+                .insert(tag.to_string());
+        }
+    }
+
+    pub(crate) fn remove_corpus_tag(&mut self, corpus_name: &str, tag: &str) {
+        if let Some(tags) = self.corpus_tags.get_mut(corpus_name) {
+            tags.remove(tag);
+            if tags.is_empty() {
+                self.corpus_tags.remove(corpus_name);
+            }
+        }
+    }
+
+    pub(crate) fn is_pinned(&self, corpus_name: &str) -> bool {
+        self.pinned_corpora.iter().any(|c| c == corpus_name)
+    }
+
+    /// Show the corpus before all unpinned corpora in the corpus list.
+    pub(crate) fn pin_corpus(&mut self, corpus_name: &str) {
+        if !self.is_pinned(corpus_name) {
+            self.pinned_corpora.push(corpus_name.to_string());
+        }
+    }
+
+    pub(crate) fn unpin_corpus(&mut self, corpus_name: &str) {
+        self.pinned_corpora.retain(|c| c != corpus_name);
+    }
+
+    /// Move a pinned corpus by `offset` positions, e.g. `-1` to show it
+    /// before the previous pinned corpus.
+    pub(crate) fn move_pinned_corpus(&mut self, corpus_name: &str, offset: isize) {
+        if let Some(idx) = self.pinned_corpora.iter().position(|c| c == corpus_name) {
+            let new_idx = idx
+                .saturating_add_signed(offset)
+                .min(self.pinned_corpora.len() - 1);
+            let corpus = self.pinned_corpora.remove(idx);
+            self.pinned_corpora.insert(new_idx, corpus);
+        }
+    }
+
+    /// Remove a corpus entry from the project without touching any files.
+    pub(crate) fn forget_corpus(&mut self, corpus_name: &str) {
+        self.corpus_locations.remove(corpus_name);
+        self.corpus_tags.remove(corpus_name);
+        self.corpus_tokenizers.remove(corpus_name);
+        self.corpus_layers.remove(corpus_name);
+        self.corpus_namespaces.remove(corpus_name);
+        self.pinned_corpora.retain(|c| c != corpus_name);
+        self.missing_corpora.retain(|c| c != corpus_name);
+        if self
+            .selected_corpus
+            .as_ref()
+            .is_some_and(|c| c.name == corpus_name)
+        {
+            self.selected_corpus = None;
+        }
+    }
+
+    /// Import a GraphML file as new corpus in a background job. If no corpus
+    /// name is given, the file name is used. An existing corpus entry with the
+    /// same name will point to the imported corpus afterwards.
+    ///
+    /// ZIP files, e.g. exported from ANNIS, can contain several GraphML
+    /// files, which are each imported as their own corpus named after the
+    /// contained file. A corpus name can only be given if the ZIP file
+    /// contains a single GraphML file.
+    pub(crate) fn import_from_graphml(&self, path: PathBuf, corpus_name: Option<String>) {
+        let job_title = format!("Importing {}", path.to_string_lossy());
+        let parent_dir = self.corpus_storage_dir();
+        self.jobs.add(
+            &job_title,
+            move |job| {
+                let parent_dir = parent_dir?;
+                let file_name = path
+                    .file_stem()
+                    .map(|f| f.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "UnknownCorpus".to_string());
+                let mut imported = Vec::new();
+                if graphml_export::is_zip_file(&path) {
+                    job.allow_cancel();
+                    let mut archive = zip::ZipArchive::new(BufReader::new(File::open(&path)?))?;
+                    let entries = graphml_export::graphml_entries(&archive);
+                    if corpus_name.is_some() && entries.len() != 1 {
+                        bail!(
+                            "The ZIP file must contain exactly one GraphML file, but has {}",
+                            entries.len()
+                        );
+                    }
+                    for (idx, entry_name) in entries.iter().enumerate() {
+                        job.check_cancelled()?;
+                        let corpus_name = corpus_name.clone().unwrap_or_else(|| {
+                            Path::new(entry_name)
+                                .file_stem()
+                                .map(|f| f.to_string_lossy().to_string())
+                                .unwrap_or_else(|| entry_name.clone())
+                        });
+                        let label = format!(
+                            "Importing \"{corpus_name}\" ({}/{})",
+                            idx + 1,
+                            entries.len()
+                        );
+                        let entry = BufReader::new(archive.by_name(entry_name)?);
+                        let location = new_corpus_location(&parent_dir)?;
+                        let (report, layers) = import_report::import_corpus(
+                            entry,
+                            &corpus_name,
+                            &location,
+                            Some(&label),
+                            &job,
+                        )?;
+                        imported.push((corpus_name, location, report, layers));
+                    }
+                } else {
+                    let corpus_name = corpus_name.unwrap_or_else(|| file_name.clone());
+                    let input_file = BufReader::new(File::open(&path)?);
+                    let location = new_corpus_location(&parent_dir)?;
+                    let (report, layers) = import_report::import_corpus(
+                        input_file,
+                        &corpus_name,
+                        &location,
+                        None,
+                        &job,
+                    )?;
+                    imported.push((corpus_name, location, report, layers));
+                }
+                Ok((file_name, imported))
+            },
+            |(file_name, imported), app| {
+                let mut reports = Vec::new();
+                for (name, location, report, layers) in imported.iter().cloned() {
+                    app.project.missing_corpora.retain(|c| c != &name);
+                    if let Some(layers) = layers {
+                        app.project.corpus_layers.insert(name.clone(), layers);
+                    }
+                    let location = app.project.stored_location(location);
+                    app.project.corpus_locations.insert(name, location);
+                    reports.push(report);
+                }
+                if reports.len() == 1 {
+                    app.import_report = reports.pop();
+                } else {
+                    app.import_report = Some(ImportReport::combine(&file_name, reports));
+                }
+                if let Some((name, _, _, _)) = imported.into_iter().next() {
+                    app.select_corpus(Some(name));
+                }
+            },
+        );
+    }
+
+    /// Export the given corpora as a single ZIP file, which contains a GraphML
+    /// file for each corpus.
+    pub(crate) fn export_bundle(&self, corpus_names: Vec<String>, location: &Path) {
+        let mut corpus_locations = BTreeMap::new();
+        for name in corpus_names.iter() {
+            if let Some(corpus_location) = self.corpus_location(name) {
+                corpus_locations.insert(name.clone(), corpus_location);
+            }
+        }
+        let selected_location = self.selected_corpus.as_ref().map(|c| c.location.clone());
+        let corpus_cache = self.corpus_cache.clone();
+        let job_title = format!("Exporting {}", location.to_string_lossy());
+        let location = location.to_path_buf();
+        let message = format!("to {}", location.to_string_lossy());
+        self.jobs.add(
+            &job_title,
+            move |job| {
+                job.allow_cancel();
+                let corpus_names: Vec<String> = corpus_locations.keys().cloned().collect();
+                graphml_export::export_bundle(&corpus_names, &location, &job, |name| {
+                    let corpus_location = &corpus_locations[name];
+                    if Some(corpus_location) == selected_location.as_ref() {
+                        // Use the cached graph, which includes changes that
+                        // are not saved yet
+                        corpus_cache.get(corpus_location)
+                    } else {
+                        // Load the other corpora without replacing the cached one
+                        let mut graph = load_graph(corpus_location)?;
+                        graph.ensure_loaded_all()?;
+                        Ok(Arc::new(RwLock::new(graph)))
+                    }
+                })?;
+                Ok(corpus_names.len())
+            },
+            move |number_of_corpora, app| {
+                app.notifier.add_toast(Toast::info(format!(
+                    "Exported {number_of_corpora} corpora {message}"
+                )));
+            },
+        );
+    }
+
     pub(crate) fn new_empty_corpus(&mut self, name: &str) -> Result<()> {
         let id = Uuid::new_v4();
         let location = self.corpus_storage_dir()?.join(id.to_string());
-        let mut graph = AnnotationGraph::with_default_graphstorages(false)?;
-        graph.persist_to(&location)?;
+        create_corpus(&location)?;
+        let location = self.stored_location(location);
         self.corpus_locations.insert(name.to_string(), location);
         Ok(())
     }
 
-    pub(crate) fn add_changeset(&mut self, mut update: GraphUpdate) {
+    /// Whether changesets of the corpus have been queued, but are not
+    /// applied to the project yet.
+    pub(crate) fn has_changesets_in_flight(&self, corpus_name: &str) -> bool {
+        self.in_flight_changesets.read().contains_key(corpus_name)
+    }
+
+    pub(crate) fn add_changeset(&mut self, update: GraphUpdate) {
+        self.add_changeset_then(update, |_| {});
+    }
+
+    /// Applies the update like [`Project::add_changeset`] and executes
+    /// `after_update` once the changes have been applied to the graph.
+    pub(crate) fn add_changeset_then<F>(&mut self, update: GraphUpdate, after_update: F)
+    where
+        F: FnOnce(&mut AnnatomicApp) + Send + Sync + 'static,
+    {
+        self.add_grouped_changeset_then(update, None, after_update);
+    }
+
+    /// Applies the update like [`Project::add_changeset_then`]. All
+    /// changesets of the same `group` are undone as a single step.
+    pub(crate) fn add_grouped_changeset_then<F>(
+        &mut self,
+        update: GraphUpdate,
+        group: Option<UndoGroup>,
+        after_update: F,
+    ) where
+        F: FnOnce(&mut AnnatomicApp) + Send + Sync + 'static,
+    {
+        if self.read_only {
+            self.notifier.add_toast(Toast::warning(
+                "Corpus is opened read-only, changes are not applied.",
+            ));
+            return;
+        }
         if let Some(selected_corpus) = self.selected_corpus.clone() {
             self.updates_pending = true;
             let corpus_cache = self.corpus_cache.clone();
-            self.jobs.add(
+            let provenance_annotator = self.provenance_annotator.clone();
+            let annotator_stamp = self.annotator_stamp.clone();
+            let in_flight =
+                InFlightChangeset::new(self.in_flight_changesets.clone(), &selected_corpus.name);
+            self.jobs.add_interactive(
                 "Updating corpus",
                 move |job| {
-                    job.update_message("Storing update events");
-                    let mut added_events = Vec::with_capacity(update.len()?);
-                    for event in update.iter()? {
-                        let event = event?;
-                        added_events.push(event.1);
-                    }
                     job.update_message("Loading corpus if necessary");
                     let graph = corpus_cache.get(&selected_corpus.location)?;
-                    job.update_message("Applying updates");
-                    let mut graph = graph.write();
-                    graph.apply_update_keep_statistics(&mut update, |msg| {
-                        job.update_message(format!("Applying updates: {msg}"))
-                    })?;
+                    let result =
+                        apply_changeset(&mut graph.write(), update, annotator_stamp.as_ref(), &job);
+                    // Even a rolled back changeset re-creates deleted nodes
+                    // with new IDs
+                    corpus_cache.invalidate();
+                    let added_events = result?;
 
-                    Ok(added_events)
+                    let provenance_error = provenance_annotator.and_then(|annotator| {
+                        provenance::record_changes(
+                            &selected_corpus.location,
+                            &annotator,
+                            &added_events,
+                        )
+                        .err()
+                    });
+
+                    Ok((added_events, provenance_error, in_flight))
                 },
-                |added_events, app| {
-                    if let Some(selected_corpus) = &mut app.project.selected_corpus {
-                        selected_corpus.diff_to_last_save.extend(added_events);
-                        app.project.undoer.add_undo(selected_corpus);
+                |(added_events, provenance_error, in_flight), app| {
+                    if let Some(e) = provenance_error {
+                        app.notifier
+                            .report_error(e.context("Could not record the provenance of changes"));
+                    }
+                    match &mut app.project.selected_corpus {
+                        Some(selected_corpus) if selected_corpus.name == in_flight.corpus_name => {
+                            selected_corpus.diff_to_last_save.extend(added_events);
+                            match group {
+                                Some(group) => {
+                                    app.project.undoer.add_undo_in_group(selected_corpus, group)
+                                }
+                                None => app.project.undoer.add_undo(selected_corpus),
+                            }
+                        }
+                        _ => {
+                            log::warn!(
+                                "Corpus {} was deselected before the changeset was applied",
+                                in_flight.corpus_name
+                            );
+                        }
                     }
+                    drop(in_flight);
                     app.project.updates_pending = false;
+                    after_update(app);
                 },
             );
         }
     }
 
-    pub(crate) fn persist_changes_on_exit(&mut self) -> Result<()> {
+    /// Persist the selected corpus. If `optimize` is true, the graph storages
+    /// are optimized before.
+    pub(crate) fn persist_changes_on_exit(&mut self, optimize: bool) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
         if let Some(selected_corpus) = self.selected_corpus.clone() {
             self.updates_pending = true;
             let corpus_cache = self.corpus_cache.clone();
             let graph = corpus_cache.get(&selected_corpus.location)?;
             let mut graph = graph.write();
+            if optimize {
+                graph.ensure_loaded_all()?;
+                graph.optimize_gs_impl(true)?;
+            }
             graph.persist_to(&selected_corpus.location)?;
-            self.undoer = default_undoer();
+            self.reset_undo_history();
         }
+        self.corpus_lock = None;
         Ok(())
     }
 
-    pub(crate) fn export_to_graphml(&self, location: &Path) {
+    /// Convert the graph storages of the selected corpus to the most compact
+    /// implementation and persist the result in a background job.
+    pub(crate) fn optimize_storage(&mut self) {
+        if self.read_only {
+            return;
+        }
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Optimizing corpus storage",
+                move |job| {
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    let mut graph = graph.write();
+                    job.update_message("Loading all graph storages");
+                    graph.ensure_loaded_all()?;
+                    job.update_message("Optimizing graph storages");
+                    graph.optimize_gs_impl(true)?;
+                    job.update_message("Persisting corpus");
+                    graph.persist_to(&selected_corpus.location)?;
+                    Ok(selected_corpus.name)
+                },
+                |corpus_name, app| {
+                    app.notifier.add_toast(Toast::info(format!(
+                        "Optimized the storage of corpus \"{corpus_name}\"."
+                    )));
+                },
+            );
+        }
+    }
+
+    /// Deselect the current corpus and remove its graph from memory. Changes
+    /// are persisted before, unless the corpus is opened read-only.
+    pub(crate) fn unload_corpus(&mut self) {
+        let persist = !self.read_only;
+        let corpus_name = self.selected_corpus.as_ref().map(|c| c.name.clone());
+        // Other instances must not open the corpus before it is persisted, so
+        // the lock is only released by the job
+        let corpus_lock = self.corpus_lock.take();
+        self.select_corpus(None);
+        self.reset_undo_history();
+
+        let corpus_cache = self.corpus_cache.clone();
+        self.jobs.add(
+            "Unloading corpus",
+            move |_| {
+                corpus_cache.unload(persist)?;
+                drop(corpus_lock);
+                Ok(corpus_name)
+            },
+            |corpus_name, app| {
+                if let Some(corpus_name) = corpus_name {
+                    app.notifier.add_toast(Toast::info(format!(
+                        "Unloaded corpus \"{corpus_name}\" from memory."
+                    )));
+                }
+            },
+        );
+    }
+
+    /// Approximate number of bytes used by the cached corpus graph.
+    pub(crate) fn cache_memory_usage(&self) -> Option<usize> {
+        self.corpus_cache.approximate_size()
+    }
+
+    /// Export the selected corpus as GraphML file. If the location ends with
+    /// `.zip`, the GraphML file is compressed as ZIP file as used by ANNIS.
+    /// If `verify` is true, the exported file is imported again and compared
+    /// with the corpus.
+    pub(crate) fn export_to_graphml(&self, location: &Path, verify: bool) {
         if let Some(selected_corpus) = self.selected_corpus.clone() {
             let corpus_cache = self.corpus_cache.clone();
             let job_title = format!("Exporting {}", location.to_string_lossy());
@@ -203,26 +1026,469 @@ impl Project {
             self.jobs.add(
                 &job_title,
                 move |job| {
+                    job.allow_cancel();
                     let graph = corpus_cache.get(&selected_corpus.location)?;
-                    let outfile = File::create(location)?;
-                    let buffered_writer = BufWriter::new(outfile);
-                    let graph = graph.read();
-                    graphannis_core::graph::serialization::graphml::export_stable_order(
-                        &graph,
-                        None,
-                        buffered_writer,
-                        |msg| {
-                            job.update_message(msg);
-                        },
+                    graphml_export::export_graph(
+                        &graph.read(),
+                        &selected_corpus.name,
+                        &location,
+                        &job,
                     )?;
 
-                    Ok(())
+                    if verify {
+                        job.update_message("Importing exported file for verification");
+                        let exported = graphml_export::read_export(&location, |status| {
+                            job.update_message(format!("Verifying export: {status}"));
+                        })?;
+                        job.update_message("Comparing exported file with corpus");
+                        graph.write().ensure_loaded_all()?;
+                        let graph = graph.read();
+                        let differences = export_verification::compare_graphs(&graph, &exported)?;
+                        Ok(Some((location, differences)))
+                    } else {
+                        Ok(None)
+                    }
+                },
+                |verification, app| {
+                    if let Some((location, differences)) = verification {
+                        if differences.is_empty() {
+                            app.notifier.add_toast(Toast::info(format!(
+                                "Verified export to {}, no differences found.",
+                                location.to_string_lossy()
+                            )));
+                        } else {
+                            app.export_differences = Some((location, differences));
+                        }
+                    }
+                },
+            );
+        }
+    }
+
+    /// Load all node annotation keys of the selected corpus, except the ones in
+    /// the ANNIS namespace, and pass them to `state_updater`.
+    pub(crate) fn load_annotation_keys<U>(&self, state_updater: U)
+    where
+        U: FnOnce(Vec<AnnoKey>, &mut AnnatomicApp) + Send + Sync + 'static,
+    {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Loading annotation keys",
+                move |_job| {
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    let graph = graph.read();
+                    let keys: Vec<AnnoKey> = graph
+                        .get_node_annos()
+                        .annotation_keys()?
+                        .into_iter()
+                        .filter(|k| k.ns != ANNIS_NS)
+                        .collect();
+                    Ok(keys)
+                },
+                state_updater,
+            );
+        }
+    }
+
+    /// Replace the values of all annotations with the given `key` in the
+    /// selected corpus according to the `mapping` from old to new values.
+    pub(crate) fn map_annotation_values(&self, key: AnnoKey, mapping: BTreeMap<String, String>) {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Mapping annotation values",
+                move |job| {
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    let graph = graph.read();
+                    refactoring::map_annotation_values(&graph, &key, &mapping, &job)
+                },
+                |(update, unmapped), app| {
+                    let number_of_changes = update.len().unwrap_or_default();
+                    app.project.add_changeset_then(update, move |app| {
+                        app.notifier.add_toast(Toast::info(format!(
+                            "Changed the value of {number_of_changes} annotation(s)."
+                        )));
+                        app.value_mapping.unmapped = Some(unmapped);
+                        app.load_editor(true);
+                    });
+                },
+            );
+        }
+    }
+
+    /// Load the summary of all components of the selected corpus and pass
+    /// them to `state_updater`.
+    pub(crate) fn load_components<U>(&self, state_updater: U)
+    where
+        U: FnOnce(Vec<refactoring::ComponentInfo>, &mut AnnatomicApp) + Send + Sync + 'static,
+    {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Loading components",
+                move |job| {
+                    job.update_message("Loading corpus if necessary");
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    graph.write().ensure_loaded_all()?;
+                    let graph = graph.read();
+                    refactoring::component_infos(&graph)
+                },
+                state_updater,
+            );
+        }
+    }
+
+    /// Delete all edges of the component in the selected corpus, or move them
+    /// to a new layer and name if `new_layer_and_name` is given.
+    pub(crate) fn change_component(
+        &self,
+        component: AnnotationComponent,
+        new_layer_and_name: Option<(String, String)>,
+    ) {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Changing component",
+                move |job| {
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    graph.write().ensure_loaded_all()?;
+                    let graph = graph.read();
+                    if let Some((layer, name)) = new_layer_and_name {
+                        refactoring::rename_component(&graph, &component, &layer, &name, &job)
+                    } else {
+                        refactoring::delete_component(&graph, &component, &job)
+                    }
+                },
+                |update, app| {
+                    app.project.add_changeset_then(update, move |app| {
+                        app.component_browser.components = None;
+                        app.load_editor(true);
+                    });
+                },
+            );
+        }
+    }
+
+    /// Write the metadata of all documents of the selected corpus as CSV file.
+    pub(crate) fn export_metadata_csv(&self, location: PathBuf) {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Exporting document metadata",
+                move |_job| {
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    graph.write().ensure_loaded_all()?;
+                    let output = BufWriter::new(File::create(&location)?);
+                    metadata_csv::write_metadata_csv(&graph.read(), output)
+                },
+                |documents, app| {
+                    app.notifier.add_toast(Toast::info(format!(
+                        "Exported the metadata of {documents} document(s)."
+                    )));
+                },
+            );
+        }
+    }
+
+    /// Change the metadata of the documents of the selected corpus to the
+    /// values of an edited CSV file created by [`Self::export_metadata_csv`].
+    pub(crate) fn import_metadata_csv(&self, location: PathBuf) {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Importing document metadata",
+                move |_job| {
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    let input = BufReader::new(File::open(&location)?);
+                    let update = metadata_csv::metadata_updates(&graph.read(), input)?;
+                    Ok(update)
+                },
+                |update, app| {
+                    let number_of_changes = update.len().unwrap_or_default();
+                    app.project.add_changeset_then(update, move |app| {
+                        app.notifier.add_toast(Toast::info(format!(
+                            "Changed {number_of_changes} metadata entries."
+                        )));
+                        app.load_editor(true);
+                    });
+                },
+            );
+        }
+    }
+
+    /// Add a new document structured as given by the `template` to the
+    /// selected corpus and open it in the editor. The document is added next
+    /// to the `selected` node of the corpus tree, see
+    /// [`parent_for_new_document`].
+    pub(crate) fn create_document(
+        &self,
+        selected: Option<NodeID>,
+        document_name: String,
+        template: DocumentTemplate,
+        tokenizer: TokenizerKind,
+    ) {
+        if self.read_only {
+            return;
+        }
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            let layers = template.segmentation_layers.clone();
+            self.jobs.add(
+                &format!("Creating document \"{}\"", document_name.trim()),
+                move |job| {
+                    job.update_message("Loading corpus if necessary");
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    graph.write().ensure_loaded_all()?;
+                    let graph = graph.read();
+                    let parent = parent_for_new_document(&graph, selected)?;
+                    new_document_update(&graph, parent, &document_name, &template, &tokenizer)
+                },
+                move |(node_name, update), app| {
+                    app.project.add_changeset_then(update, move |app| {
+                        app.open_created_document(&node_name, layers);
+                    });
                 },
-                |_, _| {},
             );
         }
     }
 
+    /// Detect the language of the documents of the selected corpus that do not
+    /// have a language annotation yet and store it as document metadata.
+    pub(crate) fn detect_document_languages(&self) {
+        if self.read_only {
+            return;
+        }
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Detecting document languages",
+                move |job| {
+                    job.update_message("Loading corpus if necessary");
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    graph.write().ensure_loaded_all()?;
+                    let languages =
+                        language::detect_document_languages(&graph.read(), |idx, total| {
+                            job.update_message(format!("Analyzing document {}/{total}", idx + 1))
+                        })?;
+                    let update = language::language_update(&languages)?;
+                    Ok((update, languages.len()))
+                },
+                |(update, number_of_documents), app| {
+                    if number_of_documents == 0 {
+                        app.notifier.add_toast(Toast::info(
+                            "No language detected for the documents without a language annotation.",
+                        ));
+                        return;
+                    }
+                    app.project.add_changeset_then(update, move |app| {
+                        app.notifier.add_toast(Toast::info(format!(
+                            "Detected the language of {number_of_documents} document(s)."
+                        )));
+                        app.load_editor(true);
+                    });
+                },
+            );
+        }
+    }
+
+    /// Rename all node annotations with the key `old_key` to `new_key` in the
+    /// selected corpus.
+    pub(crate) fn rename_annotation_key(&self, old_key: AnnoKey, new_key: AnnoKey) {
+        if let Some(selected_corpus) = self.selected_corpus.clone() {
+            let corpus_cache = self.corpus_cache.clone();
+            self.jobs.add(
+                "Renaming annotation",
+                move |job| {
+                    let graph = corpus_cache.get(&selected_corpus.location)?;
+                    let graph = graph.read();
+                    let update =
+                        refactoring::rename_annotation_key(&graph, &old_key, &new_key, &job)?;
+                    let number_of_nodes = update.len()? / 2;
+                    Ok((update, number_of_nodes))
+                },
+                |(update, number_of_nodes), app| {
+                    app.project.add_changeset_then(update, move |app| {
+                        app.notifier.add_toast(Toast::info(format!(
+                            "Renamed the annotation of {number_of_nodes} node(s)."
+                        )));
+                        app.load_editor(true);
+                    });
+                },
+            );
+        }
+    }
+
+    /// Configure the maximum number of undo steps and the approximate memory
+    /// (in bytes) the undo history is allowed to use.
+    pub(crate) fn set_undo_limits(&mut self, max_undos: usize, memory_budget: usize) {
+        self.undoer.set_limits(max_undos, memory_budget);
+    }
+
+    pub(crate) fn set_provenance_annotator(&mut self, annotator: Option<String>) {
+        self.provenance_annotator = annotator;
+    }
+
+    pub(crate) fn set_annotator_stamp(&mut self, stamp: Option<(String, String)>) {
+        self.annotator_stamp = stamp;
+    }
+
+    /// Enable the regular integrity check of unsaved changes, or disable it with `None`.
+    pub(crate) fn set_integrity_check_interval(&mut self, interval: Option<Duration>) {
+        self.integrity_check_interval = interval;
+    }
+
+    /// Start [`Self::check_integrity`] if the check is enabled and its
+    /// interval has passed.
+    pub(crate) fn check_integrity_if_due(&mut self) {
+        let Some(interval) = self.integrity_check_interval else {
+            return;
+        };
+        match self.last_integrity_check {
+            Some(last) if last.elapsed() >= interval => {
+                self.last_integrity_check = Some(Instant::now());
+                self.check_integrity();
+            }
+            Some(_) => {}
+            None => self.last_integrity_check = Some(Instant::now()),
+        }
+    }
+
+    /// Replay the changes since the last save on a shadow copy of the stored
+    /// corpus in the background, like undo and redo do, and alert the user if
+    /// the result differs from the graph in memory.
+    pub(crate) fn check_integrity(&self) {
+        let Some(selected_corpus) = self.selected_corpus.clone() else {
+            return;
+        };
+        if selected_corpus.diff_to_last_save.is_empty()
+            || self.updates_pending
+            || self.has_changesets_in_flight(&selected_corpus.name)
+            || self.jobs.has_active_job_with_title(INTEGRITY_CHECK_JOB)
+        {
+            return;
+        }
+        let corpus_cache = self.corpus_cache.clone();
+        self.jobs.add_background(
+            INTEGRITY_CHECK_JOB,
+            move |job| {
+                let live = corpus_cache.get(&selected_corpus.location)?;
+                let differences = integrity::replay_differences(
+                    &selected_corpus.location,
+                    &selected_corpus.diff_to_last_save,
+                    &live,
+                    &job,
+                )?;
+                Ok((selected_corpus, differences))
+            },
+            |(checked, differences), app| {
+                if differences.is_empty() {
+                    log::debug!("Replayed changes of corpus {} are consistent", checked.name);
+                    return;
+                }
+                // Changes applied while the check was running make the graphs
+                // differ as well and are no reason for an alert.
+                let unchanged = app.project.selected_corpus.as_ref().is_some_and(|c| {
+                    c.name == checked.name
+                        && c.diff_to_last_save.len() == checked.diff_to_last_save.len()
+                }) && !app.project.has_changesets_in_flight(&checked.name);
+                if unchanged {
+                    app.notifier.report_error(anyhow!(
+                        "Replaying the unsaved changes of corpus \"{}\" results in a different graph, undo and redo might not restore its current state. Differences: {}",
+                        checked.name,
+                        differences.join("; ")
+                    ));
+                }
+            },
+        );
+    }
+
+    /// Approximate memory used by the undo history in bytes and the number of undo steps.
+    pub(crate) fn undo_memory_usage(&self) -> (usize, usize) {
+        (self.undoer.memory_usage(), self.undoer.undo_count())
+    }
+
+    fn reset_undo_history(&mut self) {
+        self.undoer.clear();
+        self.checkpoints.clear();
+    }
+
+    pub(crate) fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Remember the current state of the selected corpus under the given name.
+    pub(crate) fn create_checkpoint(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+        if let Some(selected_corpus) = &self.selected_corpus {
+            self.checkpoints.push(Checkpoint {
+                name: name.to_string(),
+                state: selected_corpus.clone(),
+            });
+        }
+    }
+
+    /// Whether the selected corpus is in the state of the checkpoint.
+    pub(crate) fn is_at_checkpoint(&self, idx: usize) -> bool {
+        self.checkpoints
+            .get(idx)
+            .is_some_and(|c| self.selected_corpus.as_ref() == Some(&c.state))
+    }
+
+    /// Restore the state of the checkpoint. Reverting adds a new undo point,
+    /// so it can be undone like any other change.
+    pub(crate) fn revert_to_checkpoint(&mut self, idx: usize) {
+        let Some(checkpoint) = self.checkpoints.get(idx).cloned() else {
+            return;
+        };
+        if self.is_at_checkpoint(idx) {
+            return;
+        }
+        if let Some(selected_corpus) = &self.selected_corpus {
+            if selected_corpus.name != checkpoint.state.name {
+                return;
+            }
+            self.undoer.add_undo(selected_corpus);
+            self.undoer.add_undo(&checkpoint.state);
+            self.restore_state(
+                checkpoint.state,
+                &format!("Reverting to checkpoint \"{}\"", checkpoint.name),
+            );
+        }
+    }
+
+    /// Set the selected corpus to an earlier or later state. The corpus is
+    /// reloaded from disk and the changes of the state are applied again.
+    fn restore_state(&mut self, new_state: Corpus, title: &str) {
+        self.selected_corpus = Some(new_state.clone());
+        let corpus_cache = self.corpus_cache.clone();
+        self.jobs.add(
+            title,
+            move |j| {
+                j.update_message("Loading old corpus state from disk");
+                let lock = corpus_cache.load_from_disk(&new_state.location)?;
+                {
+                    let mut graph = lock.write();
+                    j.update_message("Applying updates");
+                    let mut updates = GraphUpdate::new();
+                    for event in new_state.diff_to_last_save.iter() {
+                        updates.add_event(event.clone())?;
+                    }
+                    graph.apply_update_keep_statistics(&mut updates, |msg| {
+                        j.update_message(format!("Applying updates: {}", msg));
+                    })?;
+                }
+                Ok(lock)
+            },
+            |_, app| {
+                app.load_editor(true);
+            },
+        );
+    }
+
     pub(crate) fn has_undo(&self) -> bool {
         self.selected_corpus
             .as_ref()
@@ -232,31 +1498,7 @@ impl Project {
     pub(crate) fn undo(&mut self) {
         if let Some(selected_corpus) = &mut self.selected_corpus {
             if let Some(new_state) = self.undoer.undo(selected_corpus).cloned() {
-                self.selected_corpus = Some(new_state.clone());
-                let corpus_cache = self.corpus_cache.clone();
-                // Reload the corpus from disk and apply the outstanding changes
-                self.jobs.add(
-                    "Undoing changes",
-                    move |j| {
-                        j.update_message("Loading old corpus state from disk");
-                        let lock = corpus_cache.load_from_disk(&new_state.location)?;
-                        {
-                            let mut graph = lock.write();
-                            j.update_message("Applying updates");
-                            let mut updates = GraphUpdate::new();
-                            for event in new_state.diff_to_last_save.iter() {
-                                updates.add_event(event.clone())?;
-                            }
-                            graph.apply_update_keep_statistics(&mut updates, |msg| {
-                                j.update_message(format!("Applying updates: {}", msg));
-                            })?;
-                        }
-                        Ok(lock)
-                    },
-                    |_, app| {
-                        app.load_editor(true);
-                    },
-                );
+                self.restore_state(new_state, "Undoing changes");
             }
         }
     }
@@ -270,31 +1512,7 @@ impl Project {
     pub(crate) fn redo(&mut self) {
         if let Some(selected_corpus) = &mut self.selected_corpus {
             if let Some(new_state) = self.undoer.redo(selected_corpus).cloned() {
-                self.selected_corpus = Some(new_state.clone());
-                let corpus_cache = self.corpus_cache.clone();
-                // Reload the corpus from disk and apply the outstanding changes
-                self.jobs.add(
-                    "Redoing changes",
-                    move |j| {
-                        j.update_message("Loading old corpus state from disk");
-                        let lock = corpus_cache.load_from_disk(&new_state.location)?;
-                        {
-                            let mut graph = lock.write();
-                            j.update_message("Applying updates");
-                            let mut updates = GraphUpdate::new();
-                            for event in new_state.diff_to_last_save.iter() {
-                                updates.add_event(event.clone())?;
-                            }
-                            graph.apply_update_keep_statistics(&mut updates, |msg| {
-                                j.update_message(format!("Applying updates: {}", msg));
-                            })?;
-                        }
-                        Ok(lock)
-                    },
-                    |_, app| {
-                        app.load_editor(true);
-                    },
-                );
+                self.restore_state(new_state, "Redoing changes");
             }
         }
     }
@@ -303,6 +1521,18 @@ impl Project {
     pub(crate) fn load_after_init(&mut self, notifier: Notifier, jobs: JobExecutor) -> Result<()> {
         self.notifier = notifier;
         self.jobs = jobs;
+        self.check_corpus_locations();
+        // The storage directory might have been moved since the corpus was selected
+        let location = self
+            .selected_corpus
+            .as_ref()
+            .and_then(|c| self.corpus_location(&c.name));
+        if let (Some(selection), Some(location)) = (&mut self.selected_corpus, location) {
+            selection.location = location;
+        }
+        if let Some(location) = self.selected_corpus.as_ref().map(|c| c.location.clone()) {
+            self.lock_corpus(location, false);
+        }
         if let Some(selection) = &mut self.selected_corpus {
             selection.diff_to_last_save.clear();
             self.undoer.add_undo(selection);
@@ -320,3 +1550,30 @@ impl Project {
         }
     }
 }
+
+/// Check the imported graph and persist it as a new corpus in the storage
+/// directory.
+/// Create a new directory for a corpus in the storage directory.
+fn new_corpus_location(parent_dir: &Path) -> Result<PathBuf> {
+    let location = parent_dir.join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&location)?;
+    Ok(location)
+}
+
+/// Load the corpus at the location and check that it has a corpus node.
+fn validate_corpus_location(location: &Path) -> Result<()> {
+    let graph = load_graph(location)?;
+    let has_corpus_node = graph
+        .get_node_annos()
+        .exact_anno_search(
+            Some(NODE_TYPE_KEY.ns.as_str()),
+            NODE_TYPE_KEY.name.as_str(),
+            ValueSearch::Some("corpus"),
+        )
+        .next()
+        .is_some();
+    if !has_corpus_node {
+        bail!("The corpus has no corpus node");
+    }
+    Ok(())
+}
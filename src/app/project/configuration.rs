@@ -0,0 +1,60 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    settings::Settings,
+    util::{
+        annis_config::LayerSettings, document_template::DocumentTemplate, tokenizer::TokenizerKind,
+    },
+};
+
+/// The configuration of a project that can be shared between the machines of
+/// several annotators as a single TOML file.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct ProjectConfiguration {
+    pub(crate) settings: Settings,
+    /// Corpus locations can be relative to the corpus storage directory.
+    pub(crate) corpus_locations: BTreeMap<String, PathBuf>,
+    /// Store new corpus locations relative to the corpus storage directory.
+    pub(crate) portable: bool,
+    pub(crate) corpus_tags: BTreeMap<String, BTreeSet<String>>,
+    pub(crate) corpus_tokenizers: BTreeMap<String, TokenizerKind>,
+    pub(crate) corpus_layers: BTreeMap<String, LayerSettings>,
+    pub(crate) corpus_namespaces: BTreeMap<String, String>,
+    /// Corpora shown first in the corpus list, in this order.
+    pub(crate) pinned_corpora: Vec<String>,
+    /// Templates for new documents by their name.
+    pub(crate) document_templates: BTreeMap<String, DocumentTemplate>,
+    /// Saved text search queries by the name of the corpus.
+    pub(crate) saved_queries: BTreeMap<String, BTreeMap<String, String>>,
+    /// AQL queries of the virtual collections by the name of the corpus.
+    pub(crate) virtual_collections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ProjectConfiguration {
+    pub(crate) fn to_toml(&self) -> Result<String> {
+        let result = toml::to_string_pretty(self)?;
+        Ok(result)
+    }
+
+    pub(crate) fn from_toml(content: &str) -> Result<Self> {
+        let result = toml::from_str(content).context("Invalid project configuration")?;
+        Ok(result)
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_toml(&content)
+    }
+}
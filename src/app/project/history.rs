@@ -0,0 +1,298 @@
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use graphannis::update::UpdateEvent;
+
+use super::Corpus;
+
+#[cfg(test)]
+mod tests;
+
+static NEXT_UNDO_GROUP: AtomicU64 = AtomicU64::new(0);
+
+/// Marks the changesets of a composite operation, e.g. applying the pending
+/// changes of all editors at once. They are undone and redone as a single
+/// step, regardless of when each of them has been applied.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct UndoGroup(u64);
+
+impl UndoGroup {
+    pub(crate) fn new() -> Self {
+        Self(NEXT_UNDO_GROUP.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A named state of the selected corpus within the current session, which
+/// the user can revert to.
+#[derive(Clone)]
+pub(crate) struct Checkpoint {
+    pub(crate) name: String,
+    pub(crate) state: Corpus,
+}
+
+impl Checkpoint {
+    /// Number of changes since the last save that are part of the state.
+    pub(crate) fn number_of_changes(&self) -> usize {
+        self.state.diff_to_last_save.len()
+    }
+}
+
+/// Undo history of the selected corpus.
+///
+/// This follows the same semantics as [`egui::util::undoer::Undoer`], but
+/// additionally limits the memory used by all stored states. When the memory
+/// budget is exceeded, the oldest undo points are removed first.
+#[derive(Clone)]
+pub(crate) struct UndoHistory {
+    max_undos: usize,
+    memory_budget: usize,
+    /// New undo points are added to the back.
+    undos: VecDeque<Corpus>,
+    /// Stores the redos immediately after a sequence of undos.
+    redos: Vec<Corpus>,
+    /// The group of the latest undo point, if it has been added by
+    /// [`UndoHistory::add_undo_in_group`].
+    latest_group: Option<UndoGroup>,
+    /// Approximate number of bytes of all undo and redo points, updated
+    /// whenever a point is added or removed.
+    memory_usage: usize,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self::new(10, usize::MAX)
+    }
+}
+
+impl UndoHistory {
+    /// Create a new history.
+    ///
+    /// - `max_undos` The maximum number of undo points to keep.
+    /// - `memory_budget` The approximate number of bytes all stored undo
+    ///   points can use.
+    pub(crate) fn new(max_undos: usize, memory_budget: usize) -> Self {
+        Self {
+            max_undos,
+            memory_budget,
+            undos: VecDeque::new(),
+            redos: Vec::new(),
+            latest_group: None,
+            memory_usage: 0,
+        }
+    }
+
+    /// Remove all undo and redo points, but keep the limits.
+    pub(crate) fn clear(&mut self) {
+        self.undos.clear();
+        self.redos.clear();
+        self.latest_group = None;
+        self.memory_usage = 0;
+    }
+
+    /// Change the limits and remove undo points if necessary.
+    pub(crate) fn set_limits(&mut self, max_undos: usize, memory_budget: usize) {
+        self.max_undos = max_undos;
+        self.memory_budget = memory_budget;
+        self.trim();
+    }
+
+    pub(crate) fn has_undo(&self, current_state: &Corpus) -> bool {
+        match self.undos.len() {
+            0 => false,
+            1 => self.undos.back() != Some(current_state),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn has_redo(&self, current_state: &Corpus) -> bool {
+        !self.redos.is_empty() && self.undos.back() == Some(current_state)
+    }
+
+    pub(crate) fn undo(&mut self, current_state: &Corpus) -> Option<&Corpus> {
+        if self.has_undo(current_state) {
+            self.latest_group = None;
+            if self.undos.back() == Some(current_state) {
+                if let Some(last) = self.undos.pop_back() {
+                    self.redos.push(last);
+                }
+            } else {
+                self.memory_usage += current_state.approximate_size();
+                self.redos.push(current_state.clone());
+            }
+            self.undos.back()
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn redo(&mut self, current_state: &Corpus) -> Option<&Corpus> {
+        if !self.undos.is_empty() && self.undos.back() != Some(current_state) {
+            // State changed since the last undo, the redos are not valid anymore
+            self.clear_redos();
+            None
+        } else if let Some(state) = self.redos.pop() {
+            self.latest_group = None;
+            self.undos.push_back(state);
+            self.undos.back()
+        } else {
+            None
+        }
+    }
+
+    /// Add an undo point if there has been a change since the latest one.
+    pub(crate) fn add_undo(&mut self, current_state: &Corpus) {
+        if self.undos.back() != Some(current_state) {
+            self.push_undo(current_state);
+            self.clear_redos();
+            self.latest_group = None;
+        }
+        self.trim();
+    }
+
+    /// Add an undo point for a changeset of the group. If the latest undo
+    /// point belongs to the same group, it is replaced instead, so the whole
+    /// group is undone in one step.
+    pub(crate) fn add_undo_in_group(&mut self, current_state: &Corpus, group: UndoGroup) {
+        if self.undos.back() == Some(current_state) {
+            return;
+        }
+        // The state before the group must be kept as the point to undo to
+        if self.latest_group == Some(group) && self.undos.len() > 1 {
+            if let Some(removed) = self.undos.pop_back() {
+                self.memory_usage -= removed.approximate_size();
+            }
+        }
+        self.push_undo(current_state);
+        self.clear_redos();
+        self.latest_group = Some(group);
+        self.trim();
+    }
+
+    /// Approximate number of bytes used by all undo and redo points.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    pub(crate) fn undo_count(&self) -> usize {
+        self.undos.len()
+    }
+
+    fn push_undo(&mut self, state: &Corpus) {
+        self.memory_usage += state.approximate_size();
+        self.undos.push_back(state.clone());
+    }
+
+    fn clear_redos(&mut self) {
+        for state in self.redos.drain(..) {
+            self.memory_usage -= state.approximate_size();
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.undos.len() > self.max_undos.max(1) {
+            if let Some(removed) = self.undos.pop_front() {
+                self.memory_usage -= removed.approximate_size();
+            }
+        }
+        // Always keep the latest undo point, even if it is larger than the budget
+        while self.undos.len() > 1 && self.memory_usage > self.memory_budget {
+            let removed = if !self.redos.is_empty() {
+                Some(self.redos.remove(0))
+            } else {
+                self.undos.pop_front()
+            };
+            if let Some(removed) = removed {
+                self.memory_usage -= removed.approximate_size();
+            }
+        }
+    }
+}
+
+impl Corpus {
+    /// Approximate number of bytes used by this state, dominated by the
+    /// update events since the last save.
+    pub(crate) fn approximate_size(&self) -> usize {
+        let events: usize = self.diff_to_last_save.iter().map(event_size).sum();
+        std::mem::size_of::<Corpus>() + self.name.len() + self.location.as_os_str().len() + events
+    }
+}
+
+fn event_size(event: &UpdateEvent) -> usize {
+    let strings = match event {
+        UpdateEvent::AddNode {
+            node_name,
+            node_type,
+        } => node_name.len() + node_type.len(),
+        UpdateEvent::DeleteNode { node_name } => node_name.len(),
+        UpdateEvent::AddNodeLabel {
+            node_name,
+            anno_ns,
+            anno_name,
+            anno_value,
+        } => node_name.len() + anno_ns.len() + anno_name.len() + anno_value.len(),
+        UpdateEvent::DeleteNodeLabel {
+            node_name,
+            anno_ns,
+            anno_name,
+        } => node_name.len() + anno_ns.len() + anno_name.len(),
+        UpdateEvent::AddEdge {
+            source_node,
+            target_node,
+            layer,
+            component_type,
+            component_name,
+        }
+        | UpdateEvent::DeleteEdge {
+            source_node,
+            target_node,
+            layer,
+            component_type,
+            component_name,
+        } => {
+            source_node.len()
+                + target_node.len()
+                + layer.len()
+                + component_type.len()
+                + component_name.len()
+        }
+        UpdateEvent::AddEdgeLabel {
+            source_node,
+            target_node,
+            layer,
+            component_type,
+            component_name,
+            anno_ns,
+            anno_name,
+            anno_value,
+        } => {
+            source_node.len()
+                + target_node.len()
+                + layer.len()
+                + component_type.len()
+                + component_name.len()
+                + anno_ns.len()
+                + anno_name.len()
+                + anno_value.len()
+        }
+        UpdateEvent::DeleteEdgeLabel {
+            source_node,
+            target_node,
+            layer,
+            component_type,
+            component_name,
+            anno_ns,
+            anno_name,
+        } => {
+            source_node.len()
+                + target_node.len()
+                + layer.len()
+                + component_type.len()
+                + component_name.len()
+                + anno_ns.len()
+                + anno_name.len()
+        }
+    };
+    std::mem::size_of::<UpdateEvent>() + strings
+}
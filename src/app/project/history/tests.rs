@@ -0,0 +1,123 @@
+use graphannis::update::UpdateEvent;
+use pretty_assertions::assert_eq;
+
+use super::{UndoGroup, UndoHistory};
+use crate::app::project::Corpus;
+
+fn corpus_with_events(nr_events: usize) -> Corpus {
+    let mut corpus = Corpus::new("test", "/tmp/test");
+    for i in 0..nr_events {
+        corpus.diff_to_last_save.push(UpdateEvent::AddNode {
+            node_name: format!("node{i}"),
+            node_type: "node".to_string(),
+        });
+    }
+    corpus
+}
+
+#[test]
+fn undo_and_redo() {
+    let mut history = UndoHistory::new(10, usize::MAX);
+    let s1 = corpus_with_events(0);
+    let s2 = corpus_with_events(1);
+    history.add_undo(&s1);
+    history.add_undo(&s2);
+    assert!(history.has_undo(&s2));
+    assert!(!history.has_redo(&s2));
+
+    let undone = history.undo(&s2).cloned().unwrap();
+    assert!(undone == s1);
+    assert!(history.has_redo(&s1));
+    let redone = history.redo(&s1).cloned().unwrap();
+    assert!(redone == s2);
+}
+
+#[test]
+fn limit_number_of_undos() {
+    let mut history = UndoHistory::new(3, usize::MAX);
+    for i in 0..10 {
+        history.add_undo(&corpus_with_events(i));
+    }
+    assert_eq!(3, history.undo_count());
+
+    history.set_limits(2, usize::MAX);
+    assert_eq!(2, history.undo_count());
+}
+
+#[test]
+fn trim_to_memory_budget() {
+    let single_state_size = corpus_with_events(100).approximate_size();
+    // Allow roughly three of the large states
+    let mut history = UndoHistory::new(100, single_state_size * 3);
+    for i in 0..10 {
+        history.add_undo(&corpus_with_events(100 + i));
+    }
+    assert!(history.memory_usage() <= single_state_size * 3 + 1000);
+    assert!(history.undo_count() < 10);
+    assert!(history.undo_count() >= 2);
+
+    // The latest state is always kept, even if it exceeds the budget
+    history.set_limits(100, 1);
+    assert_eq!(1, history.undo_count());
+}
+
+#[test]
+fn undo_group_in_one_step() {
+    let mut history = UndoHistory::new(10, usize::MAX);
+    let s0 = corpus_with_events(0);
+    let s1 = corpus_with_events(1);
+    let s2 = corpus_with_events(2);
+    let s3 = corpus_with_events(3);
+    history.add_undo(&s0);
+
+    let group = UndoGroup::new();
+    history.add_undo_in_group(&s1, group);
+    history.add_undo_in_group(&s2, group);
+    assert_eq!(2, history.undo_count());
+    // Another group gets its own undo point
+    history.add_undo_in_group(&s3, UndoGroup::new());
+    assert_eq!(3, history.undo_count());
+
+    let undone = history.undo(&s3).cloned().unwrap();
+    assert!(undone == s2);
+    let undone = history.undo(&s2).cloned().unwrap();
+    assert!(undone == s0);
+    let redone = history.redo(&s0).cloned().unwrap();
+    assert!(redone == s2);
+
+    // Undoing ends the group, later changesets of it are not merged into
+    // the restored undo point
+    history.add_undo_in_group(&s1, group);
+    assert_eq!(3, history.undo_count());
+}
+
+#[test]
+fn running_memory_usage() {
+    let recomputed = |history: &UndoHistory| -> usize {
+        history
+            .undos
+            .iter()
+            .chain(history.redos.iter())
+            .map(|c| c.approximate_size())
+            .sum()
+    };
+    let mut history = UndoHistory::new(4, usize::MAX);
+    let states: Vec<_> = (0..6).map(corpus_with_events).collect();
+    for s in states.iter() {
+        history.add_undo(s);
+        assert_eq!(recomputed(&history), history.memory_usage());
+    }
+    history.undo(&states[5]);
+    history.undo(&states[4]);
+    assert_eq!(recomputed(&history), history.memory_usage());
+    // A new undo point removes the redos
+    let group = UndoGroup::new();
+    history.add_undo_in_group(&corpus_with_events(10), group);
+    history.add_undo_in_group(&corpus_with_events(11), group);
+    assert_eq!(recomputed(&history), history.memory_usage());
+
+    history.set_limits(4, 1);
+    assert_eq!(recomputed(&history), history.memory_usage());
+    history.clear();
+    assert_eq!(0, history.memory_usage());
+}
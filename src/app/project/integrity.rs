@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use egui::mutex::RwLock;
+use graphannis::{
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use uuid::Uuid;
+
+use super::export_verification::compare_labeled_graphs;
+use crate::app::job_executor::FgJob;
+
+/// Apply the changes since the last save to a shadow copy of the stored
+/// corpus and describe all differences to the `live` graph. Undo and redo
+/// rely on both being the same.
+///
+/// The corpus directory is copied first, because applying updates to an
+/// imported graph writes to the directory it has been imported from.
+pub(crate) fn replay_differences(
+    location: &Path,
+    diff_to_last_save: &[UpdateEvent],
+    live: &RwLock<AnnotationGraph>,
+    job: &FgJob,
+) -> Result<Vec<String>> {
+    let shadow_location =
+        std::env::temp_dir().join(format!("annatomic-integrity-{}", Uuid::new_v4()));
+    let result = replay_on_copy(location, &shadow_location, diff_to_last_save, live, job);
+    if let Err(e) = std::fs::remove_dir_all(&shadow_location) {
+        log::warn!(
+            "Could not remove shadow copy {}: {e}",
+            shadow_location.to_string_lossy()
+        );
+    }
+    result
+}
+
+fn replay_on_copy(
+    location: &Path,
+    shadow_location: &Path,
+    diff_to_last_save: &[UpdateEvent],
+    live: &RwLock<AnnotationGraph>,
+    job: &FgJob,
+) -> Result<Vec<String>> {
+    job.update_message("Copying stored corpus");
+    copy_dir(location, shadow_location).context("Could not create shadow copy of the corpus")?;
+    let mut shadow = AnnotationGraph::new(false)?;
+    shadow.import(shadow_location)?;
+
+    job.update_message("Replaying changes");
+    let mut update = GraphUpdate::new();
+    for event in diff_to_last_save {
+        update.add_event(event.clone())?;
+    }
+    shadow.apply_update_keep_statistics(&mut update, |msg| {
+        job.update_message(format!("Replaying changes: {msg}"))
+    })?;
+    shadow.ensure_loaded_all()?;
+
+    job.update_message("Comparing with the corpus");
+    live.write().ensure_loaded_all()?;
+    let live = live.read();
+    compare_labeled_graphs(&live, "corpus", &shadow, "replayed changes")
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<()> {
+    std::fs::create_dir_all(target)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+#[cfg(test)]
+mod tests;
+
+const LOCK_FILE_NAME: &str = "annatomic.lock";
+const UNKNOWN_HOST: &str = "unknown host";
+
+/// A lock file inside a corpus location that marks the corpus as being
+/// edited by one annatomic instance. The lock file is removed when this
+/// object is dropped.
+#[derive(Debug)]
+pub(crate) struct CorpusLock {
+    path: PathBuf,
+    owner: String,
+}
+
+pub(crate) enum LockState {
+    Acquired(CorpusLock),
+    /// The corpus is already locked by another instance, described by the
+    /// contained owner information.
+    HeldByOther(String),
+}
+
+/// The content of a lock file, e.g. `<instance id> (process 42 on myhost)`.
+#[derive(Debug, PartialEq)]
+struct LockOwner {
+    instance_id: String,
+    pid: u32,
+    host: String,
+}
+
+impl LockOwner {
+    fn parse(content: &str) -> Option<Self> {
+        let (rest, host) = content.strip_suffix(')')?.rsplit_once(" on ")?;
+        let (instance_id, pid) = rest.rsplit_once(" (process ")?;
+        Some(Self {
+            instance_id: instance_id.to_string(),
+            pid: pid.parse().ok()?,
+            host: host.to_string(),
+        })
+    }
+
+    /// A lock is stale if it has been created on this host by a process that
+    /// does not exist anymore, e.g. because annatomic crashed.
+    fn is_stale(&self) -> bool {
+        self.host != UNKNOWN_HOST
+            && self.host == hostname()
+            && self.pid != std::process::id()
+            && process_exists(self.pid) == Some(false)
+    }
+}
+
+impl Display for LockOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (process {} on {})",
+            self.instance_id, self.pid, self.host
+        )
+    }
+}
+
+impl CorpusLock {
+    /// Try to create the lock file for the given corpus location. Stale lock
+    /// files of crashed instances on the same host are replaced.
+    ///
+    /// - `instance_id` Unique identifier of this annatomic instance.
+    /// - `force` Replace an existing lock file, e.g. when it is a leftover
+    ///   of a crashed instance on another host.
+    pub(crate) fn try_acquire(
+        corpus_location: &Path,
+        instance_id: &str,
+        force: bool,
+    ) -> Result<LockState> {
+        let path = corpus_location.join(LOCK_FILE_NAME);
+        let owner = LockOwner {
+            instance_id: instance_id.to_string(),
+            pid: std::process::id(),
+            host: hostname(),
+        }
+        .to_string();
+        if force {
+            remove_lock_file(&path)?;
+        }
+
+        let mut replaced_stale_lock = false;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut f) => {
+                    f.write_all(owner.as_bytes())?;
+                    return Ok(LockState::Acquired(CorpusLock { path, owner }));
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let existing_owner = std::fs::read_to_string(&path).unwrap_or_default();
+                    let parsed_owner = LockOwner::parse(&existing_owner);
+                    if parsed_owner
+                        .as_ref()
+                        .is_some_and(|o| o.instance_id == instance_id)
+                    {
+                        // We already own this lock
+                        return Ok(LockState::Acquired(CorpusLock {
+                            path,
+                            owner: existing_owner,
+                        }));
+                    } else if !replaced_stale_lock
+                        && parsed_owner.as_ref().is_some_and(LockOwner::is_stale)
+                    {
+                        log::info!("Replacing stale lock file of {existing_owner}");
+                        remove_lock_file(&path)?;
+                        replaced_stale_lock = true;
+                    } else {
+                        return Ok(LockState::HeldByOther(existing_owner));
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl Drop for CorpusLock {
+    fn drop(&mut self) {
+        // Only remove the lock file if it has not been taken over by another instance
+        let is_ours = std::fs::read_to_string(&self.path).is_ok_and(|o| o == self.owner);
+        if is_ours {
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                log::warn!(
+                    "Could not remove lock file {}: {e}",
+                    self.path.to_string_lossy()
+                );
+            }
+        }
+    }
+}
+
+fn remove_lock_file(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn hostname() -> String {
+    if cfg!(target_os = "linux") {
+        if let Ok(name) = std::fs::read_to_string("/proc/sys/kernel/hostname") {
+            return name.trim().to_string();
+        }
+    }
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| UNKNOWN_HOST.to_string())
+}
+
+/// Whether a process with the given ID is running on this host, or `None`
+/// if this can not be determined.
+fn process_exists(pid: u32) -> Option<bool> {
+    if cfg!(target_os = "linux") {
+        Some(Path::new("/proc").join(pid.to_string()).exists())
+    } else if cfg!(windows) {
+        let output = std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+    } else if cfg!(unix) {
+        let status = std::process::Command::new("ps")
+            .args(["-p", &pid.to_string()])
+            .stdout(std::process::Stdio::null())
+            .status()
+            .ok()?;
+        Some(status.success())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,91 @@
+use tempfile::TempDir;
+
+use super::{hostname, CorpusLock, LockOwner, LockState, LOCK_FILE_NAME};
+
+#[test]
+fn lock_held_by_other_instance() {
+    let corpus_dir = TempDir::new().unwrap();
+
+    let first = CorpusLock::try_acquire(corpus_dir.path(), "first", false).unwrap();
+    assert!(matches!(first, LockState::Acquired(_)));
+    assert!(corpus_dir.path().join(LOCK_FILE_NAME).exists());
+
+    // The same instance can re-acquire the lock, but not another one
+    let again = CorpusLock::try_acquire(corpus_dir.path(), "first", false).unwrap();
+    assert!(matches!(again, LockState::Acquired(_)));
+    std::mem::forget(again);
+    let second = CorpusLock::try_acquire(corpus_dir.path(), "second", false).unwrap();
+    match second {
+        LockState::HeldByOther(owner) => assert!(owner.starts_with("first")),
+        LockState::Acquired(_) => panic!("Lock should be held by other instance"),
+    }
+
+    // Releasing the lock removes the file so the other instance can acquire it
+    std::mem::drop(first);
+    assert!(!corpus_dir.path().join(LOCK_FILE_NAME).exists());
+    let second = CorpusLock::try_acquire(corpus_dir.path(), "second", false).unwrap();
+    assert!(matches!(second, LockState::Acquired(_)));
+}
+
+#[test]
+fn force_lock() {
+    let corpus_dir = TempDir::new().unwrap();
+    let first = CorpusLock::try_acquire(corpus_dir.path(), "first", false).unwrap();
+    let second = CorpusLock::try_acquire(corpus_dir.path(), "second", true).unwrap();
+    assert!(matches!(second, LockState::Acquired(_)));
+
+    // Dropping the old lock must not remove the lock file of the new owner
+    std::mem::drop(first);
+    assert!(corpus_dir.path().join(LOCK_FILE_NAME).exists());
+}
+
+#[test]
+fn parse_lock_owner() {
+    let owner = LockOwner {
+        instance_id: "abc-123".to_string(),
+        pid: 42,
+        host: "my host".to_string(),
+    };
+    assert_eq!(
+        Some(owner),
+        LockOwner::parse("abc-123 (process 42 on my host)")
+    );
+    assert_eq!(None, LockOwner::parse("abc-123"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn replace_stale_lock() {
+    let corpus_dir = TempDir::new().unwrap();
+    let lock_file = corpus_dir.path().join(LOCK_FILE_NAME);
+    // No process can have this ID, so the instance must have crashed
+    let crashed = LockOwner {
+        instance_id: "crashed".to_string(),
+        pid: u32::MAX,
+        host: hostname(),
+    };
+    std::fs::write(&lock_file, crashed.to_string()).unwrap();
+    let lock = CorpusLock::try_acquire(corpus_dir.path(), "new", false).unwrap();
+    assert!(matches!(lock, LockState::Acquired(_)));
+    assert!(std::fs::read_to_string(&lock_file)
+        .unwrap()
+        .starts_with("new"));
+
+    // Locks of running processes or other hosts are kept
+    std::mem::drop(lock);
+    for owner in [
+        LockOwner {
+            pid: std::process::id(),
+            ..crashed
+        },
+        LockOwner {
+            pid: u32::MAX,
+            instance_id: "crashed".to_string(),
+            host: "another host".to_string(),
+        },
+    ] {
+        std::fs::write(&lock_file, owner.to_string()).unwrap();
+        let lock = CorpusLock::try_acquire(corpus_dir.path(), "new", false).unwrap();
+        assert!(matches!(lock, LockState::HeldByOther(_)));
+    }
+}
@@ -0,0 +1,555 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Read,
+};
+
+use anyhow::{bail, Context, Result};
+use graphannis::{
+    graph::AnnoKey,
+    model::AnnotationComponentType,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{storage::GraphStorage, ANNIS_NS, NODE_NAME_KEY, NODE_TYPE_KEY},
+    types::{Annotation, Component, Edge, NodeID},
+};
+
+use crate::app::job_executor::FgJob;
+
+/// Number of nodes after which the progress message is updated.
+const CHUNK_SIZE: usize = 1_000;
+
+/// The nodes affected by renaming an annotation key.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct RenamePreview {
+    /// Number of nodes that have an annotation with the old key.
+    pub(crate) affected: usize,
+    /// Number of affected nodes that already have an annotation with the new
+    /// key, which would be overwritten.
+    pub(crate) overwritten: usize,
+}
+
+fn nodes_with_key(graph: &AnnotationGraph, key: &AnnoKey) -> Result<Vec<NodeID>> {
+    let mut result = Vec::new();
+    for m in
+        graph
+            .get_node_annos()
+            .exact_anno_search(Some(key.ns.as_str()), &key.name, ValueSearch::Any)
+    {
+        result.push(m?.node);
+    }
+    Ok(result)
+}
+
+pub(crate) fn preview_rename(
+    graph: &AnnotationGraph,
+    old_key: &AnnoKey,
+    new_key: &AnnoKey,
+) -> Result<RenamePreview> {
+    let nodes = nodes_with_key(graph, old_key)?;
+    let mut overwritten = 0;
+    for n in nodes.iter() {
+        if graph
+            .get_node_annos()
+            .get_value_for_item(n, new_key)?
+            .is_some()
+        {
+            overwritten += 1;
+        }
+    }
+    Ok(RenamePreview {
+        affected: nodes.len(),
+        overwritten,
+    })
+}
+
+/// Creates the update events that rename all node annotations with the key
+/// `old_key` to `new_key` in the whole corpus.
+pub(crate) fn rename_annotation_key(
+    graph: &AnnotationGraph,
+    old_key: &AnnoKey,
+    new_key: &AnnoKey,
+    job: &FgJob,
+) -> Result<GraphUpdate> {
+    if new_key.name.is_empty() {
+        bail!("The new annotation name must not be empty");
+    }
+    if old_key == new_key {
+        bail!("The new annotation key is the same as the old one");
+    }
+    let node_annos = graph.get_node_annos();
+    let nodes = nodes_with_key(graph, old_key)?;
+    let mut update = GraphUpdate::new();
+    for (chunk_idx, chunk) in nodes.chunks(CHUNK_SIZE).enumerate() {
+        job.update_message(format!(
+            "Renaming annotations {}/{}",
+            (chunk_idx * CHUNK_SIZE) + chunk.len(),
+            nodes.len()
+        ));
+        for n in chunk {
+            let node_name = node_annos
+                .get_value_for_item(n, &NODE_NAME_KEY)?
+                .unwrap_or_default()
+                .to_string();
+            let value = node_annos
+                .get_value_for_item(n, old_key)?
+                .unwrap_or_default()
+                .to_string();
+            update.add_event(UpdateEvent::DeleteNodeLabel {
+                node_name: node_name.clone(),
+                anno_ns: old_key.ns.to_string(),
+                anno_name: old_key.name.to_string(),
+            })?;
+            update.add_event(UpdateEvent::AddNodeLabel {
+                node_name,
+                anno_ns: new_key.ns.to_string(),
+                anno_name: new_key.name.to_string(),
+                anno_value: value,
+            })?;
+        }
+    }
+    Ok(update)
+}
+
+/// Summary of a component of the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ComponentInfo {
+    pub(crate) component: Component<AnnotationComponentType>,
+    pub(crate) edges: usize,
+    /// The graph storage implementation, e.g. `AdjacencyListV1`.
+    pub(crate) implementation: String,
+}
+
+fn component_edges(gs: &dyn GraphStorage) -> Result<Vec<Edge>> {
+    let mut result = Vec::new();
+    for source in gs.source_nodes() {
+        let source = source?;
+        for target in gs.get_outgoing_edges(source) {
+            result.push(Edge {
+                source,
+                target: target?,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// List all components of the graph, which needs to be fully loaded.
+pub(crate) fn component_infos(graph: &AnnotationGraph) -> Result<Vec<ComponentInfo>> {
+    let mut result = Vec::new();
+    for component in graph.get_all_components(None, None) {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&component) {
+            let mut edges = 0;
+            for source in gs.source_nodes() {
+                edges += gs.get_outgoing_edges(source?).count();
+            }
+            result.push(ComponentInfo {
+                component,
+                edges,
+                implementation: gs.serialization_id(),
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Creates the update events that delete all edges of the component, and
+/// optionally add them with their annotations to the component `new_component`.
+fn move_component_edges(
+    graph: &AnnotationGraph,
+    component: &Component<AnnotationComponentType>,
+    new_component: Option<&Component<AnnotationComponentType>>,
+    job: &FgJob,
+) -> Result<GraphUpdate> {
+    let gs = graph
+        .get_graphstorage_as_ref(component)
+        .with_context(|| format!("Component {component} not found"))?;
+    let node_annos = graph.get_node_annos();
+    let node_name = |n: NodeID| -> Result<String> {
+        let name = node_annos
+            .get_value_for_item(&n, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        Ok(name.to_string())
+    };
+    let edges = component_edges(gs)?;
+    let mut update = GraphUpdate::new();
+    for (chunk_idx, chunk) in edges.chunks(CHUNK_SIZE).enumerate() {
+        job.update_message(format!(
+            "Changing edges {}/{}",
+            (chunk_idx * CHUNK_SIZE) + chunk.len(),
+            edges.len()
+        ));
+        for edge in chunk {
+            let source_node = node_name(edge.source)?;
+            let target_node = node_name(edge.target)?;
+            update.add_event(UpdateEvent::DeleteEdge {
+                source_node: source_node.clone(),
+                target_node: target_node.clone(),
+                layer: component.layer.to_string(),
+                component_type: component.get_type().to_string(),
+                component_name: component.name.to_string(),
+            })?;
+            if let Some(new_component) = new_component {
+                update.add_event(UpdateEvent::AddEdge {
+                    source_node: source_node.clone(),
+                    target_node: target_node.clone(),
+                    layer: new_component.layer.to_string(),
+                    component_type: new_component.get_type().to_string(),
+                    component_name: new_component.name.to_string(),
+                })?;
+                for anno in gs.get_anno_storage().get_annotations_for_item(edge)? {
+                    update.add_event(UpdateEvent::AddEdgeLabel {
+                        source_node: source_node.clone(),
+                        target_node: target_node.clone(),
+                        layer: new_component.layer.to_string(),
+                        component_type: new_component.get_type().to_string(),
+                        component_name: new_component.name.to_string(),
+                        anno_ns: anno.key.ns.to_string(),
+                        anno_name: anno.key.name.to_string(),
+                        anno_value: anno.val.to_string(),
+                    })?;
+                }
+            }
+        }
+    }
+    Ok(update)
+}
+
+/// Creates the update events that delete all edges of the component.
+pub(crate) fn delete_component(
+    graph: &AnnotationGraph,
+    component: &Component<AnnotationComponentType>,
+    job: &FgJob,
+) -> Result<GraphUpdate> {
+    move_component_edges(graph, component, None, job)
+}
+
+/// Creates the update events that move all edges of the component and their
+/// annotations to a component of the same type with a new layer and name.
+pub(crate) fn rename_component(
+    graph: &AnnotationGraph,
+    component: &Component<AnnotationComponentType>,
+    new_layer: &str,
+    new_name: &str,
+    job: &FgJob,
+) -> Result<GraphUpdate> {
+    let new_component = Component::new(component.get_type(), new_layer.into(), new_name.into());
+    if &new_component == component {
+        bail!("The new layer and name are the same as the old ones");
+    }
+    if graph.get_graphstorage_as_ref(&new_component).is_some() {
+        bail!("Component {new_component} already exists");
+    }
+    move_component_edges(graph, component, Some(&new_component), job)
+}
+
+/// Reads a mapping from old to new annotation values from a CSV file with two
+/// columns and without a header row.
+pub(crate) fn parse_value_mapping<R: Read>(input: R) -> Result<BTreeMap<String, String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(input);
+    let mut mapping = BTreeMap::new();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record?;
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let old_value = record.get(0).unwrap_or_default();
+        let new_value = record
+            .get(1)
+            .with_context(|| format!("Missing new value in line {}", idx + 1))?;
+        mapping.insert(old_value.to_string(), new_value.to_string());
+    }
+    Ok(mapping)
+}
+
+/// Creates the update events that replace the values of all annotations with
+/// the given `key` according to the `mapping`.
+///
+/// Returns the update and the values that have no entry in the mapping,
+/// together with how often they occur.
+pub(crate) fn map_annotation_values(
+    graph: &AnnotationGraph,
+    key: &AnnoKey,
+    mapping: &BTreeMap<String, String>,
+    job: &FgJob,
+) -> Result<(GraphUpdate, BTreeMap<String, usize>)> {
+    let node_annos = graph.get_node_annos();
+    let nodes = nodes_with_key(graph, key)?;
+    let mut update = GraphUpdate::new();
+    let mut unmapped = BTreeMap::new();
+    for (chunk_idx, chunk) in nodes.chunks(CHUNK_SIZE).enumerate() {
+        job.update_message(format!(
+            "Mapping annotation values {}/{}",
+            (chunk_idx * CHUNK_SIZE) + chunk.len(),
+            nodes.len()
+        ));
+        for n in chunk {
+            let value = node_annos.get_value_for_item(n, key)?.unwrap_or_default();
+            match mapping.get(value.as_ref()) {
+                Some(new_value) if new_value != value.as_ref() => {
+                    let node_name = node_annos
+                        .get_value_for_item(n, &NODE_NAME_KEY)?
+                        .unwrap_or_default()
+                        .to_string();
+                    update.add_event(UpdateEvent::AddNodeLabel {
+                        node_name,
+                        anno_ns: key.ns.to_string(),
+                        anno_name: key.name.to_string(),
+                        anno_value: new_value.clone(),
+                    })?;
+                }
+                Some(_) => {}
+                None => {
+                    *unmapped.entry(value.to_string()).or_default() += 1;
+                }
+            }
+        }
+    }
+    Ok((update, unmapped))
+}
+
+/// Creates the update events that rename the top-level corpus node to
+/// `new_name` and replace its name in the names of all nodes, e.g.
+/// `old/doc1#t1` becomes `new/doc1#t1`. Node names can not be changed with
+/// update events, so each node is added again with the new name, its
+/// annotations and edges, and the old node is deleted. All components of the
+/// graph must be loaded. Returns the update and the number of renamed nodes.
+pub(crate) fn rename_corpus_nodes(
+    graph: &AnnotationGraph,
+    new_name: &str,
+    job: &FgJob,
+) -> Result<(GraphUpdate, usize)> {
+    if new_name.is_empty() || new_name.contains(['/', '#']) {
+        bail!("\"{new_name}\" is not a valid name for a corpus node");
+    }
+    let node_annos = graph.get_node_annos();
+    let top_level = top_level_corpus_node(graph)?;
+    let old_name = node_annos
+        .get_value_for_item(&top_level, &NODE_NAME_KEY)?
+        .context("Missing node name")?
+        .to_string();
+    if old_name == new_name {
+        bail!("The top-level corpus node is already named \"{new_name}\"");
+    }
+
+    let mut renamed = HashMap::new();
+    for n in nodes_with_key(graph, &NODE_NAME_KEY)? {
+        let node_name = node_annos
+            .get_value_for_item(&n, &NODE_NAME_KEY)?
+            .unwrap_or_default();
+        if let Some(rest) = node_name.strip_prefix(old_name.as_str()) {
+            if rest.is_empty() || rest.starts_with('/') || rest.starts_with('#') {
+                let new_node_name = format!("{new_name}{rest}");
+                if node_annos.get_node_id_from_name(&new_node_name)?.is_some() {
+                    bail!("Node \"{new_node_name}\" already exists");
+                }
+                renamed.insert(n, new_node_name);
+            }
+        }
+    }
+
+    let mut update = GraphUpdate::new();
+    let components = graph.get_all_components(None, None);
+    for (idx, (n, new_node_name)) in renamed.iter().enumerate() {
+        if idx % CHUNK_SIZE == 0 {
+            job.update_message(format!("Renaming nodes {idx}/{}", renamed.len()));
+        }
+        let node_type = node_annos
+            .get_value_for_item(n, &NODE_TYPE_KEY)?
+            .unwrap_or_else(|| "node".into());
+        update.add_event(UpdateEvent::AddNode {
+            node_name: new_node_name.clone(),
+            node_type: node_type.to_string(),
+        })?;
+        for anno in node_annos.get_annotations_for_item(n)? {
+            if anno.key != *NODE_NAME_KEY.as_ref() && anno.key != *NODE_TYPE_KEY.as_ref() {
+                update.add_event(UpdateEvent::AddNodeLabel {
+                    node_name: new_node_name.clone(),
+                    anno_ns: anno.key.ns.to_string(),
+                    anno_name: anno.key.name.to_string(),
+                    anno_value: anno.val.to_string(),
+                })?;
+            }
+        }
+    }
+    // Edges can only be added once both of their nodes exist
+    for (n, new_node_name) in renamed.iter() {
+        for c in components.iter() {
+            let Some(gs) = graph.get_graphstorage_as_ref(c) else {
+                continue;
+            };
+            for target in gs.get_outgoing_edges(*n) {
+                let target = target?;
+                let target_name = match renamed.get(&target) {
+                    Some(name) => name.clone(),
+                    None => node_name_of(graph, target)?,
+                };
+                add_edge_events(&mut update, gs, c, *n, target, new_node_name, &target_name)?;
+            }
+            // Edges from nodes that keep their name
+            for source in gs.get_ingoing_edges(*n) {
+                let source = source?;
+                if !renamed.contains_key(&source) {
+                    let source_name = node_name_of(graph, source)?;
+                    add_edge_events(&mut update, gs, c, source, *n, &source_name, new_node_name)?;
+                }
+            }
+        }
+    }
+    for n in renamed.keys() {
+        update.add_event(UpdateEvent::DeleteNode {
+            node_name: node_name_of(graph, *n)?,
+        })?;
+    }
+    Ok((update, renamed.len()))
+}
+
+/// The corpus node that is not part of any other corpus.
+fn top_level_corpus_node(graph: &AnnotationGraph) -> Result<NodeID> {
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let part_of = graph.get_graphstorage_as_ref(&part_of);
+    for m in graph.get_node_annos().exact_anno_search(
+        Some(NODE_TYPE_KEY.ns.as_str()),
+        NODE_TYPE_KEY.name.as_str(),
+        ValueSearch::Some("corpus"),
+    ) {
+        let node = m?.node;
+        if part_of.is_none_or(|gs| gs.get_outgoing_edges(node).next().is_none()) {
+            return Ok(node);
+        }
+    }
+    bail!("The corpus has no top-level corpus node")
+}
+
+fn node_name_of(graph: &AnnotationGraph, node: NodeID) -> Result<String> {
+    let node_name = graph
+        .get_node_annos()
+        .get_value_for_item(&node, &NODE_NAME_KEY)?
+        .context("Missing node name")?;
+    Ok(node_name.to_string())
+}
+
+/// Add the edge between the (possibly renamed) nodes and its annotations.
+fn add_edge_events(
+    update: &mut GraphUpdate,
+    gs: &dyn GraphStorage,
+    component: &Component<AnnotationComponentType>,
+    source: NodeID,
+    target: NodeID,
+    source_name: &str,
+    target_name: &str,
+) -> Result<()> {
+    update.add_event(UpdateEvent::AddEdge {
+        source_node: source_name.to_string(),
+        target_node: target_name.to_string(),
+        layer: component.layer.to_string(),
+        component_type: component.get_type().to_string(),
+        component_name: component.name.to_string(),
+    })?;
+    for anno in gs
+        .get_anno_storage()
+        .get_annotations_for_item(&Edge { source, target })?
+    {
+        update.add_event(UpdateEvent::AddEdgeLabel {
+            source_node: source_name.to_string(),
+            target_node: target_name.to_string(),
+            layer: component.layer.to_string(),
+            component_type: component.get_type().to_string(),
+            component_name: component.name.to_string(),
+            anno_ns: anno.key.ns.to_string(),
+            anno_name: anno.key.name.to_string(),
+            anno_value: anno.val.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Rename the corpus or document node `node_name` to `new_name` inside the
+/// same parent corpus and all nodes below it, e.g. `corpus/doc1#t1` becomes
+/// `corpus/doc2#t1` when renaming `corpus/doc1` to `doc2`. The `annis::doc`
+/// annotation of the node is changed as well. Returns the number of renamed
+/// nodes.
+pub(crate) fn rename_document_nodes(
+    graph: &mut AnnotationGraph,
+    node_name: &str,
+    new_name: &str,
+    job: &FgJob,
+) -> Result<usize> {
+    if new_name.is_empty() || new_name.contains(['/', '#']) {
+        bail!("\"{new_name}\" is not a valid name for a document");
+    }
+    let Some((parent, _)) = node_name.rsplit_once('/') else {
+        bail!("The top-level corpus node \"{node_name}\" can only be renamed with the corpus");
+    };
+    let new_node_name = format!("{parent}/{new_name}");
+    graph.ensure_loaded_all()?;
+    let node_annos = graph.get_node_annos();
+    if node_annos.get_node_id_from_name(&new_node_name)?.is_some() {
+        bail!("Node \"{new_node_name}\" already exists");
+    }
+    let node = node_annos
+        .get_node_id_from_name(node_name)?
+        .with_context(|| format!("Unknown node \"{node_name}\""))?;
+    let doc_key = AnnoKey {
+        ns: ANNIS_NS.into(),
+        name: "doc".into(),
+    };
+    let has_doc_name = node_annos.get_value_for_item(&node, &doc_key)?.is_some();
+
+    let renamed = rename_nodes_with_prefix(graph, node_name, &new_node_name, job)?;
+    if has_doc_name {
+        graph.get_node_annos_mut().insert(
+            node,
+            Annotation {
+                key: doc_key,
+                val: new_name.into(),
+            },
+        )?;
+    }
+    Ok(renamed)
+}
+
+/// Replace the prefix `old_name` of all node names that are equal to it or
+/// continue with a path or fragment separator.
+fn rename_nodes_with_prefix(
+    graph: &mut AnnotationGraph,
+    old_name: &str,
+    new_name: &str,
+    job: &FgJob,
+) -> Result<usize> {
+    let mut renamed = Vec::new();
+    for n in nodes_with_key(graph, &NODE_NAME_KEY)? {
+        let node_name = graph
+            .get_node_annos()
+            .get_value_for_item(&n, &NODE_NAME_KEY)?
+            .unwrap_or_default();
+        if let Some(rest) = node_name.strip_prefix(old_name) {
+            if rest.is_empty() || rest.starts_with('/') || rest.starts_with('#') {
+                renamed.push((n, format!("{new_name}{rest}")));
+            }
+        }
+    }
+    let node_annos = graph.get_node_annos_mut();
+    for (chunk_idx, chunk) in renamed.chunks(CHUNK_SIZE).enumerate() {
+        job.update_message(format!(
+            "Renaming nodes {}/{}",
+            (chunk_idx * CHUNK_SIZE) + chunk.len(),
+            renamed.len()
+        ));
+        for (n, new_node_name) in chunk {
+            node_annos.insert(
+                *n,
+                Annotation {
+                    key: NODE_NAME_KEY.as_ref().clone(),
+                    val: new_node_name.into(),
+                },
+            )?;
+        }
+    }
+    Ok(renamed.len())
+}
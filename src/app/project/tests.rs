@@ -1,7 +1,35 @@
+use std::{cell::Cell, sync::Arc};
+
+use egui::mutex::RwLock;
+use graphannis::{graph::AnnoKey, model::AnnotationComponentType};
+use graphannis_core::types::Component;
 use insta::assert_snapshot;
 use tempfile::NamedTempFile;
 
-use crate::app::tests::{create_app_with_corpus, create_test_harness, wait_until_jobs_finished};
+use graphannis::update::{GraphUpdate, UpdateEvent};
+
+use super::{
+    configuration::ProjectConfiguration,
+    integrity::replay_differences,
+    refactoring::{
+        component_infos, delete_component, map_annotation_values, parse_value_mapping,
+        preview_rename, rename_annotation_key, rename_component, rename_corpus_nodes,
+        rename_document_nodes, RenamePreview,
+    },
+};
+use crate::api::{
+    export_verification::compare_graphs,
+    graphml_export::{export_bundle, export_graph, graphml_entries, is_zip_file, read_export},
+    import_report::{GraphmlScanner, ImportReport},
+    provenance::changes_of_node,
+    token_helper::TokenHelper,
+};
+use crate::app::{
+    job_executor::{FgJob, JobCancelled},
+    tests::{create_app_with_corpus, create_test_harness, wait_until_jobs_finished},
+    util::{document_template::DocumentTemplate, tokenizer::TokenizerKind},
+    AnnatomicApp, MainView,
+};
 
 #[test]
 fn export_corpus() {
@@ -19,7 +47,9 @@ fn export_corpus() {
             .project
             .select_corpus(Some("single_sentence".to_string()));
 
-        app_state.project.export_to_graphml(export_location.path());
+        app_state
+            .project
+            .export_to_graphml(export_location.path(), false);
     }
 
     // Execute the running jobs and check that the file has been created
@@ -28,3 +58,1044 @@ fn export_corpus() {
     let actual_graphml = std::fs::read_to_string(export_location.path()).unwrap();
     assert_snapshot!(actual_graphml);
 }
+
+#[test]
+fn export_corpus_as_zip() {
+    let (graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let export_dir = tempfile::TempDir::new().unwrap();
+    let location = export_dir.path().join("single_sentence.graphml.zip");
+    assert!(is_zip_file(&location));
+
+    export_graph(&graph, "single_sentence", &location, &FgJob::default()).unwrap();
+    let first_export = std::fs::read(&location).unwrap();
+    let exported = read_export(&location, |_| {}).unwrap();
+    assert!(compare_graphs(&graph, &exported).unwrap().is_empty());
+
+    // Exporting the same corpus again results in the same file
+    export_graph(&graph, "single_sentence", &location, &FgJob::default()).unwrap();
+    assert_eq!(first_export, std::fs::read(&location).unwrap());
+}
+
+#[test]
+fn export_and_read_corpus_bundle() {
+    let import = |graphml: &[u8]| {
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            graphml,
+            false,
+            |_| {},
+        )
+        .unwrap()
+        .0
+    };
+    let export_dir = tempfile::TempDir::new().unwrap();
+    let location = export_dir.path().join("bundle.zip");
+    let corpus_names = vec!["single_sentence".to_string(), "tokens".to_string()];
+    export_bundle(&corpus_names, &location, &FgJob::default(), |name| {
+        let graph = if name == "single_sentence" {
+            import(&include_bytes!("../../../tests/data/single_sentence.graphml")[..])
+        } else {
+            import(&include_bytes!("../../../tests/data/SegmentationWithGaps.graphml")[..])
+        };
+        Ok(Arc::new(RwLock::new(graph)))
+    })
+    .unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&location).unwrap()).unwrap();
+    let entries = graphml_entries(&archive);
+    assert_eq!(vec!["single_sentence.graphml", "tokens.graphml"], entries);
+    for (entry, expected) in entries.iter().zip([
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+        &include_bytes!("../../../tests/data/SegmentationWithGaps.graphml")[..],
+    ]) {
+        let (exported, _config) =
+            crate::api::read_graphml(archive.by_name(entry).unwrap(), |_| {}).unwrap();
+        assert!(compare_graphs(&import(expected), &exported)
+            .unwrap()
+            .is_empty());
+    }
+
+    let combined = ImportReport::combine(
+        "bundle",
+        vec![
+            ImportReport {
+                corpus_name: "a".to_string(),
+                documents: 1,
+                token: 2,
+                issues: vec!["Missing token".to_string()],
+            },
+            ImportReport {
+                corpus_name: "b".to_string(),
+                documents: 2,
+                token: 3,
+                issues: Vec::new(),
+            },
+        ],
+    );
+    assert_eq!(3, combined.documents);
+    assert_eq!(5, combined.token);
+    assert_eq!(vec!["a: Missing token"], combined.issues);
+}
+
+#[test]
+fn cancel_export_removes_file() {
+    let (graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let export_dir = tempfile::TempDir::new().unwrap();
+    let location = export_dir.path().join("single_sentence.graphml");
+    let job = FgJob::default();
+    job.cancel();
+
+    let result = export_graph(&graph, "single_sentence", &location, &job);
+    assert!(result.unwrap_err().is::<JobCancelled>());
+    assert!(!location.exists());
+}
+
+#[test]
+fn missing_corpus_location() {
+    let mut app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let existing_location = app_state.project.corpus_locations["single_sentence"].clone();
+    app_state.project.corpus_locations.insert(
+        "moved".to_string(),
+        existing_location.join("does-not-exist"),
+    );
+
+    app_state.project.check_corpus_locations();
+    assert_eq!(vec!["moved".to_string()], app_state.project.missing_corpora);
+
+    // Selecting the missing corpus is not possible
+    app_state.project.select_corpus(Some("moved".to_string()));
+    assert!(app_state.project.selected_corpus.is_none());
+
+    // Directories without a corpus are rejected
+    let empty_dir = tempfile::TempDir::new().unwrap();
+    assert!(app_state
+        .project
+        .relocate_corpus("moved", empty_dir.path().to_path_buf())
+        .is_err());
+    assert_eq!(vec!["moved".to_string()], app_state.project.missing_corpora);
+
+    // Point the corpus to an existing location
+    app_state
+        .project
+        .relocate_corpus("moved", existing_location)
+        .unwrap();
+    assert!(app_state.project.missing_corpora.is_empty());
+    app_state.project.select_corpus(Some("moved".to_string()));
+    assert!(app_state.project.selected_corpus.is_some());
+
+    app_state.project.forget_corpus("moved");
+    assert!(app_state.project.selected_corpus.is_none());
+    assert!(!app_state.project.corpus_locations.contains_key("moved"));
+}
+
+#[test]
+fn import_report_for_valid_corpus() {
+    let input_read = Cell::new(false);
+    let mut documents = Vec::new();
+    let mut input = GraphmlScanner::new(
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+        &input_read,
+        |idx, document| documents.push((idx, document.to_string())),
+    );
+    let (graph, config) = graphannis_core::graph::serialization::graphml::import::<
+        AnnotationComponentType,
+        _,
+        _,
+    >(&mut input, false, |_| {})
+    .unwrap();
+    let edges = input.into_edges();
+    assert!(input_read.get());
+    assert_eq!(vec![(1, "single_sentence/zossen".to_string())], documents);
+
+    let report = ImportReport::create(
+        "single_sentence",
+        &graph,
+        config.as_deref(),
+        &edges,
+        &FgJob::default(),
+    )
+    .unwrap();
+    assert_eq!(1, report.documents);
+    assert_eq!(8, report.token);
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+}
+
+#[test]
+fn import_report_with_edges_left_out() {
+    let (graph, config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    // Scan a file with additional edges that are not part of the graph
+    let graphml = String::from_utf8_lossy(include_bytes!(
+        "../../../tests/data/single_sentence.graphml"
+    ))
+    .replace(
+        "<edge id=\"e0\"",
+        r#"<edge source="single_sentence/zossen#missing" target="single_sentence/zossen#t1" label="Dominance/syntax/"></edge>
+        <edge source="single_sentence/zossen#t1" target="single_sentence/zossen#t2" label="Unknown/default_ns/"></edge>
+        <edge id="e0""#,
+    );
+    let input_read = Cell::new(false);
+    let mut input = GraphmlScanner::new(graphml.as_bytes(), &input_read, |_, _| {});
+    std::io::copy(&mut input, &mut std::io::sink()).unwrap();
+    let edges = input.into_edges();
+
+    let report = ImportReport::create(
+        "single_sentence",
+        &graph,
+        config.as_deref(),
+        &edges,
+        &FgJob::default(),
+    )
+    .unwrap();
+    assert_eq!(2, report.issues.len(), "{:?}", report.issues);
+    assert!(report.issues[0].contains("single_sentence/zossen#missing"));
+    assert!(report.issues[1].contains("Unknown/default_ns/"));
+}
+
+#[test]
+fn export_and_verify_corpus() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let export_location = NamedTempFile::new().unwrap();
+
+    let (mut harness, app_state) = create_test_harness(app_state);
+    {
+        let mut app_state = app_state.write();
+        app_state
+            .project
+            .select_corpus(Some("single_sentence".to_string()));
+        app_state
+            .project
+            .export_to_graphml(export_location.path(), true);
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    assert!(app_state.export_differences.is_none());
+}
+
+#[test]
+fn compare_different_graphs() {
+    let import = || {
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap()
+        .0
+    };
+    let original = import();
+    let mut changed = import();
+    assert!(compare_graphs(&original, &changed).unwrap().is_empty());
+
+    let mut update = GraphUpdate::new();
+    update
+        .add_event(UpdateEvent::DeleteNodeLabel {
+            node_name: "single_sentence/zossen#t1".to_string(),
+            anno_ns: "default_ns".to_string(),
+            anno_name: "pos".to_string(),
+        })
+        .unwrap();
+    changed.apply_update(&mut update, |_| {}).unwrap();
+
+    let differences = compare_graphs(&original, &changed).unwrap();
+    assert_eq!(
+        vec!["Annotation \"default_ns::pos\": 8 in corpus, 7 in exported file".to_string()],
+        differences
+    );
+}
+
+#[test]
+fn rename_annotation() {
+    let (mut graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let old_key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "pos".into(),
+    };
+    let new_key = AnnoKey {
+        ns: "".into(),
+        name: "upos".into(),
+    };
+    assert_eq!(
+        RenamePreview {
+            affected: 8,
+            overwritten: 0
+        },
+        preview_rename(&graph, &old_key, &new_key).unwrap()
+    );
+
+    let mut update = rename_annotation_key(&graph, &old_key, &new_key, &FgJob::default()).unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+
+    let node_annos = graph.get_node_annos();
+    assert_eq!(
+        0,
+        node_annos
+            .number_of_annotations_by_name(Some("default_ns"), "pos")
+            .unwrap()
+    );
+    assert_eq!(
+        8,
+        node_annos
+            .number_of_annotations_by_name(Some(""), "upos")
+            .unwrap()
+    );
+    let t2 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t2")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "NN",
+        node_annos
+            .get_value_for_item(&t2, &new_key)
+            .unwrap()
+            .unwrap()
+    );
+}
+
+#[test]
+fn rename_document_and_its_nodes() {
+    let (mut graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let job = FgJob::default();
+    assert!(rename_document_nodes(&mut graph, "single_sentence/zossen", "a/b", &job).is_err());
+    assert!(rename_document_nodes(&mut graph, "single_sentence", "other", &job).is_err());
+
+    let renamed =
+        rename_document_nodes(&mut graph, "single_sentence/zossen", "potsdam", &job).unwrap();
+    assert!(renamed > 1);
+    let node_annos = graph.get_node_annos();
+    assert!(node_annos
+        .get_node_id_from_name("single_sentence/zossen#t1")
+        .unwrap()
+        .is_none());
+    assert!(node_annos
+        .get_node_id_from_name("single_sentence/potsdam#t1")
+        .unwrap()
+        .is_some());
+    let doc = node_annos
+        .get_node_id_from_name("single_sentence/potsdam")
+        .unwrap()
+        .unwrap();
+    let doc_key = AnnoKey {
+        ns: "annis".into(),
+        name: "doc".into(),
+    };
+    assert_eq!(
+        "potsdam",
+        node_annos
+            .get_value_for_item(&doc, &doc_key)
+            .unwrap()
+            .unwrap()
+    );
+    // The top-level corpus node is not affected
+    assert!(node_annos
+        .get_node_id_from_name("single_sentence")
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn rename_and_delete_component() {
+    let (mut graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let job = FgJob::default();
+    let edges_of = |graph: &graphannis::AnnotationGraph, layer: &str, name: &str| {
+        component_infos(graph)
+            .unwrap()
+            .into_iter()
+            .find(|info| {
+                info.component.get_type() == AnnotationComponentType::Dominance
+                    && info.component.layer == layer
+                    && info.component.name == name
+            })
+            .map(|info| info.edges)
+    };
+    assert_eq!(Some(12), edges_of(&graph, "syntax", "edge"));
+    let component = Component::new(
+        AnnotationComponentType::Dominance,
+        "syntax".into(),
+        "edge".into(),
+    );
+    assert!(rename_component(&graph, &component, "syntax", "edge", &job).is_err());
+
+    let mut update = rename_component(&graph, &component, "tiger", "edge", &job).unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+    assert_eq!(Some(12), edges_of(&graph, "tiger", "edge"));
+    assert!(edges_of(&graph, "syntax", "edge").unwrap_or_default() == 0);
+
+    let renamed = Component::new(
+        AnnotationComponentType::Dominance,
+        "tiger".into(),
+        "edge".into(),
+    );
+    let mut update = delete_component(&graph, &renamed, &job).unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+    assert!(edges_of(&graph, "tiger", "edge").unwrap_or_default() == 0);
+}
+
+#[test]
+fn map_values_with_csv() {
+    let (mut graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let mapping = parse_value_mapping("NN,NOUN\nART,DET\n\nNE,PROPN\n".as_bytes()).unwrap();
+    assert_eq!(3, mapping.len());
+    assert!(parse_value_mapping("NN\n".as_bytes()).is_err());
+
+    let key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "pos".into(),
+    };
+    let (mut update, unmapped) =
+        map_annotation_values(&graph, &key, &mapping, &FgJob::default()).unwrap();
+    assert_eq!(
+        vec![("$.", 1), ("APPR", 1), ("VMFIN", 1)],
+        unmapped
+            .iter()
+            .map(|(value, count)| (value.as_str(), *count))
+            .collect::<Vec<_>>()
+    );
+    graph.apply_update(&mut update, |_| {}).unwrap();
+
+    let node_annos = graph.get_node_annos();
+    let t1 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t1")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "DET",
+        node_annos.get_value_for_item(&t1, &key).unwrap().unwrap()
+    );
+    let t3 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t3")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "APPR",
+        node_annos.get_value_for_item(&t3, &key).unwrap().unwrap()
+    );
+}
+
+#[test]
+fn optimize_corpus_storage() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    {
+        let mut app_state = app_state.write();
+        app_state
+            .project
+            .select_corpus(Some("single_sentence".to_string()));
+        app_state.project.optimize_storage();
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    let graph = app_state.project.get_selected_graph().unwrap().unwrap();
+    let graph = graph.read();
+    assert_eq!(
+        8,
+        graph
+            .get_node_annos()
+            .number_of_annotations_by_name(Some("annis"), "tok")
+            .unwrap()
+    );
+}
+
+#[test]
+fn unload_corpus_persists_changes() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    {
+        let mut app_state = app_state.write();
+        app_state
+            .project
+            .select_corpus(Some("single_sentence".to_string()));
+        let mut update = GraphUpdate::new();
+        update
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: "single_sentence/zossen#t1".into(),
+                anno_ns: "default_ns".into(),
+                anno_name: "pos".into(),
+                anno_value: "DET".into(),
+            })
+            .unwrap();
+        app_state.project.add_changeset(update);
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    assert!(app_state.read().project.cache_memory_usage().is_some());
+
+    app_state.write().project.unload_corpus();
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    {
+        let app_state = app_state.read();
+        assert!(app_state.project.selected_corpus.is_none());
+        assert!(app_state.project.cache_memory_usage().is_none());
+    }
+
+    // Loading the corpus again must include the change
+    let mut app_state = app_state.write();
+    app_state
+        .project
+        .select_corpus(Some("single_sentence".to_string()));
+    let graph = app_state.project.get_selected_graph().unwrap().unwrap();
+    let graph = graph.read();
+    let node_annos = graph.get_node_annos();
+    let t1 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t1")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "DET",
+        node_annos
+            .get_value_for_item(
+                &t1,
+                &AnnoKey {
+                    ns: "default_ns".into(),
+                    name: "pos".into()
+                }
+            )
+            .unwrap()
+            .unwrap()
+    );
+}
+
+fn pos_of_t1(app_state: &RwLock<AnnatomicApp>) -> Option<String> {
+    let graph = app_state
+        .read()
+        .project
+        .get_selected_graph()
+        .unwrap()
+        .unwrap();
+    let graph = graph.read();
+    let node_annos = graph.get_node_annos();
+    let t1 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t1")
+        .unwrap()
+        .unwrap();
+    node_annos
+        .get_value_for_item(
+            &t1,
+            &AnnoKey {
+                ns: "default_ns".into(),
+                name: "pos".into(),
+            },
+        )
+        .unwrap()
+        .map(|v| v.to_string())
+}
+
+#[test]
+fn revert_to_named_checkpoint() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    {
+        let mut app_state = app_state.write();
+        app_state
+            .project
+            .select_corpus(Some("single_sentence".to_string()));
+        app_state.project.create_checkpoint(" before tagging ");
+        let mut update = GraphUpdate::new();
+        update
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: "single_sentence/zossen#t1".into(),
+                anno_ns: "default_ns".into(),
+                anno_name: "pos".into(),
+                anno_value: "DET".into(),
+            })
+            .unwrap();
+        app_state.project.add_changeset(update);
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    assert_eq!(Some("DET".to_string()), pos_of_t1(&app_state));
+    {
+        let app_state = app_state.read();
+        let checkpoints = app_state.project.checkpoints();
+        assert_eq!(1, checkpoints.len());
+        assert_eq!("before tagging", checkpoints[0].name);
+        assert_eq!(0, checkpoints[0].number_of_changes());
+        assert!(!app_state.project.is_at_checkpoint(0));
+    }
+
+    app_state.write().project.revert_to_checkpoint(0);
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    assert_eq!(None, pos_of_t1(&app_state));
+    assert!(app_state.read().project.is_at_checkpoint(0));
+
+    // Reverting can be undone
+    app_state.write().project.undo();
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    assert_eq!(Some("DET".to_string()), pos_of_t1(&app_state));
+}
+
+#[test]
+fn health_check_on_selection() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    app_state
+        .write()
+        .project
+        .select_corpus(Some("single_sentence".to_string()));
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    let report = app_state.project.health_report.as_ref().unwrap();
+    assert!(report.problems.is_empty());
+    assert_eq!(1, report.number_of_documents);
+    assert!(report.number_of_token > 0);
+}
+
+#[test]
+fn create_document_from_template() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    app_state
+        .write()
+        .project
+        .select_corpus(Some("single_sentence".to_string()));
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+    let template = DocumentTemplate {
+        metadata: [("genre".to_string(), "letter".to_string())].into(),
+        boilerplate: "Dear Sir".to_string(),
+        ..Default::default()
+    };
+    app_state.read().project.create_document(
+        None,
+        "letter".to_string(),
+        template,
+        TokenizerKind::Whitespace,
+    );
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let graph = app_state
+        .read()
+        .project
+        .get_selected_graph()
+        .unwrap()
+        .unwrap();
+    let graph = graph.read();
+    let document = graph
+        .get_node_annos()
+        .get_node_id_from_name("single_sentence/letter")
+        .unwrap()
+        .unwrap();
+    assert!(graph
+        .get_node_annos()
+        .get_node_id_from_name("single_sentence/zossen/letter")
+        .unwrap()
+        .is_none());
+    // The new document is opened in the editor
+    assert!(matches!(
+        app_state.read().main_view,
+        MainView::EditDocument { node_id } if node_id == document
+    ));
+}
+
+#[test]
+fn project_configuration_roundtrip() {
+    let mut config = ProjectConfiguration::default();
+    config.settings.annotator = "shared".to_string();
+    config.settings.max_undos = 3;
+    config
+        .corpus_locations
+        .insert("example".to_string(), "/data/corpora/example".into());
+    config
+        .corpus_tags
+        .insert("example".to_string(), ["language:de".to_string()].into());
+    config.saved_queries.insert(
+        "example".to_string(),
+        [("greeting".to_string(), "hello".to_string())].into(),
+    );
+    config.virtual_collections.insert(
+        "example".to_string(),
+        [("finite verbs".to_string(), "pos=\"VVFIN\"".to_string())].into(),
+    );
+    config.pinned_corpora = vec!["example".to_string()];
+
+    let serialized = config.to_toml().unwrap();
+    assert_eq!(
+        config,
+        ProjectConfiguration::from_toml(&serialized).unwrap()
+    );
+    assert!(ProjectConfiguration::from_toml("settings = 1").is_err());
+
+    // The local annotator is kept when the configuration is applied
+    let mut app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    app_state.settings.annotator = "local".to_string();
+    app_state.apply_project_configuration(config);
+    assert_eq!("local", app_state.settings.annotator);
+    assert_eq!(3, app_state.settings.max_undos);
+    assert!(app_state
+        .project
+        .corpus_locations
+        .contains_key("single_sentence"));
+    assert_eq!(
+        vec!["example".to_string()],
+        app_state.project.missing_corpora
+    );
+    assert_eq!(
+        "hello",
+        app_state.project_configuration().saved_queries["example"]["greeting"]
+    );
+    assert_eq!(
+        "pos=\"VVFIN\"",
+        app_state.project_configuration().virtual_collections["example"]["finite verbs"]
+    );
+    assert!(app_state.project.is_pinned("example"));
+}
+
+#[test]
+fn rename_corpus_and_its_nodes() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    {
+        let mut app_state = app_state.write();
+        app_state
+            .project
+            .select_corpus(Some("single_sentence".to_string()));
+        app_state
+            .project
+            .corpus_tags
+            .insert("single_sentence".to_string(), ["de".to_string()].into());
+        assert!(app_state
+            .project
+            .rename_corpus("single_sentence", "  ", false)
+            .is_err());
+        // Only the nodes of the selected corpus can be renamed
+        let location = app_state.project.corpus_locations["single_sentence"].clone();
+        app_state
+            .project
+            .corpus_locations
+            .insert("other".to_string(), location);
+        assert!(app_state
+            .project
+            .rename_corpus("other", "other_corpus", true)
+            .is_err());
+        assert!(app_state.project.corpus_locations.contains_key("other"));
+        app_state
+            .project
+            .rename_corpus("single_sentence", "zossen_corpus", true)
+            .unwrap();
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    assert!(!app_state
+        .project
+        .corpus_locations
+        .contains_key("single_sentence"));
+    assert!(app_state
+        .project
+        .corpus_locations
+        .contains_key("zossen_corpus"));
+    assert!(app_state.project.corpus_tags.contains_key("zossen_corpus"));
+    assert_eq!(
+        "zossen_corpus",
+        app_state.project.selected_corpus.as_ref().unwrap().name
+    );
+    let graph = app_state.project.get_selected_graph().unwrap().unwrap();
+    let graph = graph.read();
+    let node_annos = graph.get_node_annos();
+    assert!(node_annos
+        .get_node_id_from_name("zossen_corpus/zossen#t1")
+        .unwrap()
+        .is_some());
+    assert!(node_annos
+        .get_node_id_from_name("zossen_corpus")
+        .unwrap()
+        .is_some());
+    assert!(node_annos
+        .get_node_id_from_name("single_sentence/zossen#t1")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn rename_corpus_nodes_with_changeset() {
+    let (mut graph, _config) =
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap();
+    let job = FgJob::default();
+    let ordered_token = |graph: &graphannis::AnnotationGraph, document: &str| {
+        let tok_helper = TokenHelper::new(graph).unwrap();
+        tok_helper
+            .get_ordered_token(document, None)
+            .unwrap()
+            .into_iter()
+            .map(|t| tok_helper.spanned_text(&[t]).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let token_before = ordered_token(&graph, "single_sentence/zossen");
+    assert!(rename_corpus_nodes(&graph, "a/b", &job).is_err());
+
+    let (mut update, renamed) = rename_corpus_nodes(&graph, "renamed", &job).unwrap();
+    assert!(renamed > 1);
+    graph.apply_update(&mut update, |_| {}).unwrap();
+    let node_annos = graph.get_node_annos();
+    assert!(node_annos
+        .get_node_id_from_name("single_sentence/zossen#t1")
+        .unwrap()
+        .is_none());
+    assert!(node_annos
+        .get_node_id_from_name("renamed")
+        .unwrap()
+        .is_some());
+    // Annotations and edges are kept
+    assert_eq!(token_before, ordered_token(&graph, "renamed/zossen"));
+
+    // Existing nodes are not overwritten
+    let mut update = GraphUpdate::new();
+    update
+        .add_event(UpdateEvent::AddNode {
+            node_name: "taken".to_string(),
+            node_type: "corpus".to_string(),
+        })
+        .unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+    assert!(rename_corpus_nodes(&graph, "taken", &job).is_err());
+}
+
+#[test]
+fn replay_changes_on_shadow_copy() {
+    let import = || {
+        graphannis_core::graph::serialization::graphml::import::<AnnotationComponentType, _, _>(
+            &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+            false,
+            |_| {},
+        )
+        .unwrap()
+        .0
+    };
+    let mut stored = import();
+    let location = tempfile::TempDir::new().unwrap();
+    stored.persist_to(location.path()).unwrap();
+
+    let diff = vec![UpdateEvent::AddNodeLabel {
+        node_name: "single_sentence/zossen#t1".into(),
+        anno_ns: "default_ns".into(),
+        anno_name: "lemma".into(),
+        anno_value: "die".into(),
+    }];
+    let mut live = import();
+    let mut update = GraphUpdate::new();
+    for event in diff.iter() {
+        update.add_event(event.clone()).unwrap();
+    }
+    live.apply_update(&mut update, |_| {}).unwrap();
+    let live = RwLock::new(live);
+
+    let job = FgJob::default();
+    assert!(replay_differences(location.path(), &diff, &live, &job)
+        .unwrap()
+        .is_empty());
+
+    // A change that is part of the diff, but not of the live graph
+    let mut diverged = diff.clone();
+    diverged.push(UpdateEvent::AddNode {
+        node_name: "single_sentence/zossen#missing".into(),
+        node_type: "node".into(),
+    });
+    let differences = replay_differences(location.path(), &diverged, &live, &job).unwrap();
+    assert!(!differences.is_empty());
+    assert!(differences
+        .iter()
+        .all(|d| d.contains("in replayed changes")));
+}
+
+#[test]
+fn record_provenance_of_changeset() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    {
+        let mut app_state = app_state.write();
+        app_state.settings.record_provenance = true;
+        app_state.settings.annotator = "Jane".to_string();
+        app_state.apply_settings();
+        app_state
+            .project
+            .select_corpus(Some("single_sentence".to_string()));
+        let mut update = GraphUpdate::new();
+        update
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: "single_sentence/zossen#t1".into(),
+                anno_ns: "default_ns".into(),
+                anno_name: "pos".into(),
+                anno_value: "DET".into(),
+            })
+            .unwrap();
+        app_state.project.add_changeset(update);
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    let location = &app_state.project.selected_corpus.as_ref().unwrap().location;
+    let changes = changes_of_node(location, "single_sentence/zossen#t1").unwrap();
+    assert_eq!(1, changes.len());
+    assert_eq!("Jane", changes[0].annotator);
+    assert_eq!("Set default_ns::pos to \"DET\"", changes[0].change);
+    assert!(changes_of_node(location, "single_sentence/zossen#t2")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn stamp_changeset_with_annotator() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    {
+        let mut app_state = app_state.write();
+        app_state.settings.annotator_initials = "JD".to_string();
+        app_state.settings.stamp_annotations = true;
+        app_state.settings.stamp_namespace = "team".to_string();
+        app_state.apply_settings();
+        app_state
+            .project
+            .select_corpus(Some("single_sentence".to_string()));
+        let mut update = GraphUpdate::new();
+        update
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: "single_sentence/zossen#t1".into(),
+                anno_ns: "default_ns".into(),
+                anno_name: "pos".into(),
+                anno_value: "DET".into(),
+            })
+            .unwrap();
+        app_state.project.add_changeset(update);
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    let graph = app_state.project.get_selected_graph().unwrap().unwrap();
+    let graph = graph.read();
+    let node_annos = graph.get_node_annos();
+    let annotator_key = AnnoKey {
+        ns: "team".into(),
+        name: "annotator".into(),
+    };
+    let t1 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t1")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "JD",
+        node_annos
+            .get_value_for_item(&t1, &annotator_key)
+            .unwrap()
+            .unwrap()
+    );
+    let t2 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#t2")
+        .unwrap()
+        .unwrap();
+    assert!(node_annos
+        .get_value_for_item(&t2, &annotator_key)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn portable_corpus_locations() {
+    let mut app_state = crate::AnnatomicApp::default();
+    let project = &mut app_state.project;
+    let storage_dir = project.corpus_storage_dir().unwrap();
+    let outside_dir = tempfile::TempDir::new().unwrap();
+    project
+        .corpus_locations
+        .insert("inside".to_string(), storage_dir.join("corpus-id"));
+    project
+        .corpus_locations
+        .insert("outside".to_string(), outside_dir.path().to_path_buf());
+
+    let outside = project.make_portable();
+    assert_eq!(vec!["outside".to_string()], outside);
+    assert_eq!(
+        std::path::Path::new("corpus-id"),
+        project.corpus_locations["inside"]
+    );
+    assert_eq!(
+        Some(storage_dir.join("corpus-id")),
+        project.corpus_location("inside")
+    );
+    assert_eq!(
+        Some(outside_dir.path().to_path_buf()),
+        project.corpus_location("outside")
+    );
+    // New corpora are stored relative to the storage directory as well
+    assert_eq!(
+        std::path::PathBuf::from("other-id"),
+        project.stored_location(storage_dir.join("other-id"))
+    );
+}
@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use egui::{ComboBox, DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::{appearance::ColorScheme, util::tokenizer::TokenizerKind},
+    AnnatomicApp,
+};
+
+/// Persisted settings of the application that can be changed by the user.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct Settings {
+    /// Maximum number of undo steps kept for the selected corpus.
+    pub(crate) max_undos: usize,
+    /// Approximate memory in MiB the undo history is allowed to use.
+    pub(crate) undo_memory_budget_mib: usize,
+    /// Import exported files again and compare them with the corpus.
+    pub(crate) verify_exports: bool,
+    /// Choose more compact graph storages when the corpus is persisted on exit.
+    pub(crate) optimize_storage_on_exit: bool,
+    /// Record who changed which node in a provenance log of the corpus.
+    pub(crate) record_provenance: bool,
+    /// Name of the annotator used in the provenance log.
+    pub(crate) annotator: String,
+    pub(crate) annotator_initials: String,
+    /// Add an annotation with the initials of the annotator to new and changed nodes.
+    pub(crate) stamp_annotations: bool,
+    /// Namespace of the annotation with the initials of the annotator.
+    pub(crate) stamp_namespace: String,
+    pub(crate) color_scheme: ColorScheme,
+    /// Keep the edits in the document editor until the user saves them.
+    pub(crate) manual_commit: bool,
+    /// Tokenizer for inserted text, unless the corpus has its own.
+    pub(crate) tokenizer: TokenizerKind,
+    /// Ask before nodes are deleted in the editor.
+    pub(crate) confirm_deletion: bool,
+    /// Show all annotations of token and spans when hovering them.
+    pub(crate) token_tooltips: bool,
+    /// Regularly check that replaying the unsaved changes, as done by undo
+    /// and redo, results in the same graph.
+    pub(crate) integrity_check: bool,
+    /// Minutes between two of these checks.
+    pub(crate) integrity_check_interval_min: u64,
+    /// Seconds between two saves of the application state, e.g. the
+    /// selected corpus and the layout of the editors.
+    pub(crate) autosave_interval_sec: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_undos: 10,
+            undo_memory_budget_mib: 256,
+            verify_exports: true,
+            optimize_storage_on_exit: false,
+            record_provenance: true,
+            annotator: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_default(),
+            annotator_initials: String::default(),
+            stamp_annotations: false,
+            stamp_namespace: "annatomic".to_string(),
+            color_scheme: ColorScheme::default(),
+            manual_commit: false,
+            tokenizer: TokenizerKind::default(),
+            confirm_deletion: true,
+            token_tooltips: true,
+            integrity_check: false,
+            integrity_check_interval_min: 10,
+            autosave_interval_sec: 30,
+        }
+    }
+}
+
+impl Settings {
+    pub(crate) fn undo_memory_budget(&self) -> usize {
+        self.undo_memory_budget_mib.saturating_mul(1024 * 1024)
+    }
+
+    pub(crate) fn integrity_check_interval(&self) -> Option<Duration> {
+        self.integrity_check
+            .then(|| Duration::from_secs(self.integrity_check_interval_min.max(1) * 60))
+    }
+
+    pub(crate) fn autosave_interval(&self) -> Duration {
+        Duration::from_secs(self.autosave_interval_sec.max(1))
+    }
+}
+
+/// Show the settings window if it has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    let mut open = app.settings_open;
+    egui::Window::new("Settings")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let old_settings = app.settings.clone();
+            appearance_settings(ui, &mut app.settings);
+            ui.separator();
+            annotator_settings(ui, &mut app.settings);
+            ui.separator();
+            editing_settings(ui, &mut app.settings);
+            ui.separator();
+            undo_settings(ui, &mut app.settings);
+            ui.separator();
+            provenance_settings(ui, &mut app.settings);
+            ui.separator();
+            storage_settings(ui, &mut app.settings);
+            if old_settings != app.settings {
+                app.apply_settings();
+            }
+        });
+    app.settings_open = open;
+}
+
+fn appearance_settings(ui: &mut Ui, settings: &mut Settings) {
+    ui.heading("Appearance");
+    Grid::new("appearance-settings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Theme");
+            egui::widgets::global_theme_preference_buttons(ui);
+            ui.end_row();
+
+            ui.label("Colors");
+            ComboBox::from_id_salt("color-scheme")
+                .selected_text(settings.color_scheme.description())
+                .show_ui(ui, |ui| {
+                    for scheme in ColorScheme::ALL {
+                        ui.selectable_value(
+                            &mut settings.color_scheme,
+                            scheme,
+                            scheme.description(),
+                        );
+                    }
+                });
+            ui.end_row();
+        });
+}
+
+fn annotator_settings(ui: &mut Ui, settings: &mut Settings) {
+    ui.heading("Annotator");
+    Grid::new("annotator-settings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut settings.annotator);
+            ui.end_row();
+
+            ui.label("Initials");
+            ui.text_edit_singleline(&mut settings.annotator_initials);
+            ui.end_row();
+        });
+    ui.checkbox(
+        &mut settings.stamp_annotations,
+        "Stamp new annotations with the initials",
+    )
+    .on_hover_text("Adds an \"annotator\" annotation to each new or changed node.");
+    ui.add_enabled_ui(settings.stamp_annotations, |ui| {
+        Grid::new("stamp-settings").num_columns(2).show(ui, |ui| {
+            ui.label("Namespace");
+            ui.text_edit_singleline(&mut settings.stamp_namespace);
+            ui.end_row();
+        });
+    });
+}
+
+fn editing_settings(ui: &mut Ui, settings: &mut Settings) {
+    ui.heading("Editing");
+    ui.checkbox(
+        &mut settings.manual_commit,
+        "Only apply changes when saving",
+    )
+    .on_hover_text(
+        "Edits in the document editor are collected and applied to the corpus when saving them (Ctrl+S) instead of immediately.",
+    );
+    ui.checkbox(&mut settings.confirm_deletion, "Ask before deleting nodes")
+        .on_hover_text(
+            "Show the number and kind of the affected nodes before deleting the selection or several spans at once.",
+        );
+    ui.checkbox(
+        &mut settings.token_tooltips,
+        "Show annotation details when hovering token",
+    )
+    .on_hover_text(
+        "Show the node name, the covered token and all annotations, including the ones in the annis namespace.",
+    );
+    ui.horizontal(|ui| {
+        ui.label("Default tokenizer");
+        tokenizer_selection(ui, "default-tokenizer", &mut settings.tokenizer);
+    });
+}
+
+/// Select one of the built-in tokenizers or a language preset. The pattern
+/// of regular expression tokenizers can be edited.
+pub(crate) fn tokenizer_selection(ui: &mut Ui, id_salt: &str, tokenizer: &mut TokenizerKind) {
+    ComboBox::from_id_salt(id_salt)
+        .selected_text(tokenizer.label())
+        .show_ui(ui, |ui| {
+            for kind in [
+                TokenizerKind::Whitespace,
+                TokenizerKind::Punctuation,
+                TokenizerKind::UnicodeWords,
+            ] {
+                let label = kind.label();
+                ui.selectable_value(tokenizer, kind, label);
+            }
+            if ui
+                .selectable_label(
+                    matches!(tokenizer, TokenizerKind::Regex(_)),
+                    TokenizerKind::Regex(String::new()).label(),
+                )
+                .clicked()
+                && !matches!(tokenizer, TokenizerKind::Regex(_))
+            {
+                *tokenizer = TokenizerKind::Regex(r"\w+|[^\w\s]".to_string());
+            }
+            ui.separator();
+            for (language, preset) in TokenizerKind::language_presets() {
+                ui.selectable_value(tokenizer, preset, format!("Preset for {language}"));
+            }
+        });
+    if let TokenizerKind::Regex(pattern) = tokenizer {
+        ui.text_edit_singleline(pattern)
+            .on_hover_text("Each match of the regular expression is a token");
+    }
+}
+
+fn undo_settings(ui: &mut Ui, settings: &mut Settings) {
+    ui.heading("Undo history");
+    Grid::new("undo-settings").num_columns(2).show(ui, |ui| {
+        ui.label("Maximum undo steps");
+        ui.add(DragValue::new(&mut settings.max_undos).range(1..=1000));
+        ui.end_row();
+
+        ui.label("Memory budget");
+        ui.add(
+            DragValue::new(&mut settings.undo_memory_budget_mib)
+                .range(1..=16384)
+                .suffix(" MiB"),
+        );
+        ui.end_row();
+    });
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut settings.integrity_check, "Check unsaved changes every")
+            .on_hover_text(
+                "Replay the unsaved changes on a copy of the stored corpus in the background and report if the result differs from the corpus, which means undo and redo would not restore the current state.",
+            );
+        ui.add_enabled(
+            settings.integrity_check,
+            DragValue::new(&mut settings.integrity_check_interval_min)
+                .range(1..=1440)
+                .suffix(" min"),
+        );
+    });
+}
+
+fn provenance_settings(ui: &mut Ui, settings: &mut Settings) {
+    ui.heading("Provenance");
+    ui.checkbox(&mut settings.record_provenance, "Record changes")
+        .on_hover_text("Log which annotator changed which node and when in the corpus directory.");
+}
+
+fn storage_settings(ui: &mut Ui, settings: &mut Settings) {
+    ui.heading("Storage (advanced)");
+    ui.checkbox(
+        &mut settings.optimize_storage_on_exit,
+        "Optimize graph storages when closing",
+    )
+    .on_hover_text(
+        "Converts the graph storages to more compact implementations, which reduces the disk size and loading time of finished corpora, but makes closing the application slower.",
+    );
+    ui.horizontal(|ui| {
+        ui.label("Save application state every")
+            .on_hover_text(
+                "The selected corpus, the settings and the layout of the editors are saved regularly, so they are not lost if the application crashes. Changes of the corpus are saved independently of this.",
+            );
+        ui.add(
+            DragValue::new(&mut settings.autosave_interval_sec)
+                .range(1..=3600)
+                .suffix(" s"),
+        );
+    });
+}
@@ -2,7 +2,11 @@ use std::{io::Read, path::PathBuf};
 
 use egui::{mutex::RwLock, Context, Id};
 use egui_kittest::{kittest::Queryable, Harness};
-use graphannis::model::AnnotationComponentType;
+use graphannis::{
+    graph::AnnoKey,
+    model::AnnotationComponentType,
+    update::{GraphUpdate, UpdateEvent},
+};
 use tempfile::TempDir;
 
 use super::*;
@@ -171,3 +175,356 @@ fn show_main_page() {
 
     harness.snapshot("show_main_page");
 }
+
+#[test]
+fn navigate_to_document() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    app_state
+        .write()
+        .select_corpus(Some("single_sentence".to_string()));
+    wait_for_editor(&mut harness, app_state.clone());
+
+    let document = {
+        let app_state = app_state.read();
+        let graph = app_state.project.get_selected_graph().unwrap().unwrap();
+        let graph = graph.read();
+        graph
+            .get_node_annos()
+            .get_node_id_from_name("single_sentence/zossen")
+            .unwrap()
+            .unwrap()
+    };
+    app_state.write().navigate_to_document(document);
+    wait_for_editor(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    assert!(app_state.main_view == MainView::EditDocument { node_id: document });
+    assert!(app_state.requested_document.is_none());
+    // There is only one document in the corpus
+    assert_eq!(
+        (None, None),
+        app_state
+            .current_editor
+            .get()
+            .unwrap()
+            .get_neighbour_documents()
+    );
+}
+
+#[test]
+fn autosave_interval_from_settings() {
+    let mut app_state = crate::AnnatomicApp::default();
+    assert_eq!(
+        Duration::from_secs(30),
+        eframe::App::auto_save_interval(&app_state)
+    );
+    app_state.settings.autosave_interval_sec = 5;
+    assert_eq!(
+        Duration::from_secs(5),
+        eframe::App::auto_save_interval(&app_state)
+    );
+    // The state is never saved continuously
+    app_state.settings.autosave_interval_sec = 0;
+    assert_eq!(
+        Duration::from_secs(1),
+        eframe::App::auto_save_interval(&app_state)
+    );
+}
+
+#[test]
+fn ask_before_switching_with_pending_changes() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    app_state
+        .write()
+        .select_corpus(Some("single_sentence".to_string()));
+    wait_for_editor(&mut harness, app_state.clone());
+    let document = {
+        let app_state = app_state.read();
+        let graph = app_state.project.get_selected_graph().unwrap().unwrap();
+        let graph = graph.read();
+        graph
+            .get_node_annos()
+            .get_node_id_from_name("single_sentence/zossen")
+            .unwrap()
+            .unwrap()
+    };
+    app_state.write().navigate_to_document(document);
+    wait_for_editor(&mut harness, app_state.clone());
+
+    let add_pending_change = |app_state: &mut crate::AnnatomicApp| {
+        app_state.settings.manual_commit = true;
+        app_state.apply_settings();
+        let editor = app_state
+            .current_editor
+            .get_mut()
+            .and_then(|editor| editor.any_mut().downcast_mut::<DocumentEditor>())
+            .unwrap();
+        editor
+            .add_annotation(
+                "single_sentence/zossen#n1",
+                AnnoKey {
+                    ns: "default_ns".into(),
+                    name: "checked".into(),
+                },
+                "yes".to_string(),
+            )
+            .unwrap();
+        assert!(app_state.has_pending_updates());
+    };
+    add_pending_change(&mut app_state.write());
+
+    // Cancelling keeps the corpus and the changes
+    app_state.write().select_corpus(None);
+    harness.run();
+    assert!(app_state.read().switch_request.is_some());
+    harness.get_by_label("Cancel").click();
+    harness.run();
+    {
+        let app_state = app_state.read();
+        assert!(app_state.switch_request.is_none());
+        assert!(app_state.has_pending_updates());
+        assert!(app_state.project.selected_corpus.is_some());
+    }
+
+    // Discarding the changes reloads the editor without them
+    app_state.write().navigate_to_document(document);
+    harness.run();
+    harness.get_by_label("Discard and continue").click();
+    wait_for_editor(&mut harness, app_state.clone());
+    {
+        let app_state = app_state.read();
+        assert!(app_state.switch_request.is_none());
+        assert!(!app_state.has_pending_updates());
+        assert!(app_state.main_view == MainView::EditDocument { node_id: document });
+    }
+
+    // Applying the changes before closing the corpus
+    add_pending_change(&mut app_state.write());
+    app_state.write().select_corpus(None);
+    harness.run();
+    harness.get_by_label("Apply and continue").click();
+    for _ in 0..MAX_WAIT_STEPS {
+        harness.step();
+        if app_state.read().project.selected_corpus.is_none() {
+            break;
+        }
+    }
+    let app_state = app_state.read();
+    assert!(app_state.switch_request.is_none());
+    assert!(app_state.project.selected_corpus.is_none());
+}
+
+#[test]
+fn discard_pending_changes_with_shortcut() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    app_state
+        .write()
+        .select_corpus(Some("single_sentence".to_string()));
+    wait_for_editor(&mut harness, app_state.clone());
+    let (graph, document) = {
+        let app_state = app_state.read();
+        let graph = app_state.project.get_selected_graph().unwrap().unwrap();
+        let document = graph
+            .read()
+            .get_node_annos()
+            .get_node_id_from_name("single_sentence/zossen")
+            .unwrap()
+            .unwrap();
+        (graph, document)
+    };
+    app_state.write().navigate_to_document(document);
+    wait_for_editor(&mut harness, app_state.clone());
+
+    let key = AnnoKey {
+        ns: "default_ns".into(),
+        name: "checked".into(),
+    };
+    {
+        let mut app_state = app_state.write();
+        app_state.settings.manual_commit = true;
+        app_state.apply_settings();
+        let editor = app_state
+            .current_editor
+            .get_mut()
+            .and_then(|editor| editor.any_mut().downcast_mut::<DocumentEditor>())
+            .unwrap();
+        editor
+            .add_annotation("single_sentence/zossen#n1", key.clone(), "yes".to_string())
+            .unwrap();
+        assert!(app_state.has_pending_updates());
+    }
+
+    harness.input_mut().events.push(egui::Event::Key {
+        key: DISCARD_SHORTCUT.logical_key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: DISCARD_SHORTCUT.modifiers,
+    });
+    wait_for_editor(&mut harness, app_state.clone());
+
+    assert!(!app_state.read().has_pending_updates());
+    let graph = graph.read();
+    let node_annos = graph.get_node_annos();
+    let n1 = node_annos
+        .get_node_id_from_name("single_sentence/zossen#n1")
+        .unwrap()
+        .unwrap();
+    assert_eq!(None, node_annos.get_value_for_item(&n1, &key).unwrap());
+}
+
+#[test]
+fn switch_between_profiles() {
+    let mut app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    app_state.settings.max_undos = 5;
+
+    app_state.switch_profile("Lab");
+    assert_eq!("Lab", app_state.active_profile);
+    assert!(app_state.project.corpus_locations.is_empty());
+    assert_eq!(Some("Lab".to_string()), app_state.project.storage_profile);
+    assert_eq!(Settings::default().max_undos, app_state.settings.max_undos);
+
+    // Invalid names are ignored
+    app_state.switch_profile("../other");
+    assert_eq!("Lab", app_state.active_profile);
+
+    app_state.switch_profile(DEFAULT_PROFILE);
+    assert_eq!(DEFAULT_PROFILE, app_state.active_profile);
+    assert!(app_state
+        .project
+        .corpus_locations
+        .contains_key("single_sentence"));
+    assert_eq!(None, app_state.project.storage_profile);
+    assert_eq!(5, app_state.settings.max_undos);
+    assert!(app_state.profiles.contains_key("Lab"));
+}
+
+#[test]
+fn safe_mode_keeps_only_corpora() {
+    let mut persisted = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    persisted
+        .project
+        .select_corpus(Some("single_sentence".to_string()));
+    persisted.settings.max_undos = 5;
+    persisted.main_view = MainView::EditDocument { node_id: 1 };
+
+    let app_state = crate::AnnatomicApp::safe_mode_state(Some(persisted));
+    assert!(app_state
+        .project
+        .corpus_locations
+        .contains_key("single_sentence"));
+    assert!(app_state.project.selected_corpus.is_none());
+    assert!(app_state.main_view == MainView::Start);
+    assert_eq!(Settings::default().max_undos, app_state.settings.max_undos);
+
+    // Without any persisted state, the default state is used
+    let app_state = crate::AnnatomicApp::safe_mode_state(None);
+    assert!(app_state.project.corpus_locations.is_empty());
+}
+
+#[test]
+fn switch_corpus_after_changesets_are_applied() {
+    let mut app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    let other = create_app_with_corpus(
+        "other",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    app_state
+        .project
+        .corpus_locations
+        .extend(other.project.corpus_locations);
+    app_state
+        .project
+        .select_corpus(Some("single_sentence".to_string()));
+    let (mut harness, app_state) = create_test_harness(app_state);
+
+    let mut update = GraphUpdate::new();
+    update
+        .add_event(UpdateEvent::AddNodeLabel {
+            node_name: "single_sentence/zossen#n1".to_string(),
+            anno_ns: "default_ns".to_string(),
+            anno_name: "checked".to_string(),
+            anno_value: "yes".to_string(),
+        })
+        .unwrap();
+    {
+        let mut app_state = app_state.write();
+        app_state.project.add_changeset(update);
+        app_state.select_corpus(Some("other".to_string()));
+        // The selection is kept until the changeset has been applied
+        assert!(app_state
+            .project
+            .has_changesets_in_flight("single_sentence"));
+        assert_eq!(
+            Some("single_sentence"),
+            app_state
+                .project
+                .selected_corpus
+                .as_ref()
+                .map(|c| c.name.as_str())
+        );
+    }
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    let app_state = app_state.read();
+    assert!(!app_state
+        .project
+        .has_changesets_in_flight("single_sentence"));
+    assert!(app_state.requested_corpus.is_none());
+    assert_eq!(
+        Some("other"),
+        app_state
+            .project
+            .selected_corpus
+            .as_ref()
+            .map(|c| c.name.as_str())
+    );
+}
+
+#[test]
+fn open_document_in_new_window() {
+    let app_state = create_app_with_corpus(
+        "single_sentence",
+        &include_bytes!("../../tests/data/single_sentence.graphml")[..],
+    );
+    let (mut harness, app_state) = create_test_harness(app_state);
+    harness.set_size(egui::Vec2::new(1200.0, 800.0));
+    harness.run();
+
+    harness.get_by_label("single_sentence").click();
+    wait_for_editor(&mut harness, app_state.clone());
+    harness.get_by_label("single_sentence/zossen").click();
+    harness.run();
+    harness.get_by_label("Open selected in new window").click();
+    harness.run();
+    wait_until_jobs_finished(&mut harness, app_state.clone());
+
+    // The test harness has no native windows, so the document is shown in a
+    // window next to the corpus editor
+    harness.get_by_label("single_sentence/zossen - annatomic");
+    harness.get_by_label_contains("Token ranging from 1 to 1");
+    let app_state = app_state.read();
+    assert_eq!(1, app_state.detached_documents.len());
+    assert!(matches!(app_state.main_view, MainView::Start));
+}
@@ -1,6 +1,32 @@
+pub(crate) mod annis_config;
+pub(crate) mod concordance;
+pub(crate) mod corpus_groups;
+pub(crate) mod corpus_structure;
+pub(crate) mod datasource;
+pub(crate) mod diagnostics;
+pub(crate) mod document_template;
 #[cfg(test)]
 pub(crate) mod example_generator;
-pub(crate) mod token_helper;
+pub(crate) mod frequency;
+pub(crate) mod health_check;
+pub(crate) mod html_export;
+pub(crate) mod kwic;
+pub(crate) mod language;
+pub(crate) mod media;
+pub(crate) mod metadata_csv;
+pub(crate) mod nested_spans;
+pub(crate) mod ngrams;
+pub(crate) mod ordering_chain;
+pub(crate) mod review;
+pub(crate) mod svg_export;
+pub(crate) mod text_index;
+pub(crate) mod time_annotation;
+pub(crate) mod token_pattern;
+pub(crate) mod tokenizer;
+pub(crate) mod value_type;
+pub(crate) mod virtual_collection;
+pub(crate) mod waveform;
+pub(crate) mod whitespace;
 
 pub(crate) fn make_whitespace_visible<S: AsRef<str>>(v: S) -> String {
     let result: String = v
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use graphannis::graph::AnnoKey;
+use serde::{Deserialize, Serialize};
+
+use super::value_type::ValueType;
+
+/// Characters that are only used in the regular expressions of ANNIS
+/// visualizer mappings, but not in plain annotation names.
+const REGEX_CHARACTERS: &[char] = &['*', '+', '?', '[', ']', '(', ')', '{', '}', '|', '^', '$'];
+
+/// How the annotations and segmentation layers of a corpus are shown in the
+/// document editor. The settings are taken from the ANNIS configuration of an
+/// imported corpus.
+///
+/// Annotations are given as qualified name (`ns::name`) or as name only,
+/// which matches the annotations with this name in all namespaces.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+#[serde(default)]
+pub(crate) struct LayerSettings {
+    /// Annotations that are hidden when a document is opened.
+    pub(crate) hidden_annos: Vec<String>,
+    /// Annotations in the order they are shown. Segmentation layers are
+    /// shown and colored in the order of their annotation name.
+    pub(crate) anno_order: Vec<String>,
+    /// Types of the annotation values that are not free text, e.g. numbers
+    /// or dates.
+    pub(crate) value_types: BTreeMap<String, ValueType>,
+}
+
+impl LayerSettings {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hidden_annos.is_empty() && self.anno_order.is_empty() && self.value_types.is_empty()
+    }
+
+    pub(crate) fn is_hidden(&self, key: &AnnoKey) -> bool {
+        self.hidden_annos
+            .iter()
+            .any(|pattern| matches(pattern, key))
+    }
+
+    /// The annotation keys in the configured order. Keys that are not
+    /// mentioned are not included.
+    pub(crate) fn ordered_keys<'a, I>(&self, keys: I) -> Vec<AnnoKey>
+    where
+        I: IntoIterator<Item = &'a AnnoKey> + Clone,
+    {
+        let mut result: Vec<AnnoKey> = Vec::new();
+        for pattern in self.anno_order.iter() {
+            for key in keys.clone() {
+                if matches(pattern, key) && !result.contains(key) {
+                    result.push(key.clone());
+                }
+            }
+        }
+        result
+    }
+
+    /// The type of the annotation values. A type given for the qualified
+    /// name is preferred over one for the name only.
+    pub(crate) fn value_type(&self, key: &AnnoKey) -> Option<&ValueType> {
+        self.value_types
+            .get(&format!("{}::{}", key.ns, key.name))
+            .or_else(|| self.value_types.get(key.name.as_str()))
+    }
+
+    /// Check that the value matches the type of the annotation, if it has
+    /// one.
+    pub(crate) fn validate_value(&self, key: &AnnoKey, value: &str) -> Result<()> {
+        if let Some(value_type) = self.value_type(key) {
+            value_type
+                .validate(value)
+                .with_context(|| format!("Invalid value for annotation {}", key.name))?;
+        }
+        Ok(())
+    }
+
+    /// Position of the segmentation layer in the configured order, if it is
+    /// mentioned at all.
+    pub(crate) fn segmentation_rank(&self, segmentation: &str) -> Option<usize> {
+        self.anno_order.iter().position(|pattern| {
+            let name = pattern
+                .rsplit_once("::")
+                .map_or(pattern.as_str(), |(_, n)| n);
+            name == segmentation
+        })
+    }
+}
+
+fn matches(pattern: &str, key: &AnnoKey) -> bool {
+    match pattern.split_once("::") {
+        Some((ns, name)) => key.ns == ns && key.name == name,
+        None => key.name == pattern,
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AnnisConfig {
+    view: ViewConfig,
+    context: ContextConfig,
+    visualizers: Vec<VisualizerConfig>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ViewConfig {
+    base_text_segmentation: Option<String>,
+    hidden_annos: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ContextConfig {
+    segmentation: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct VisualizerConfig {
+    visibility: Option<String>,
+    mappings: BTreeMap<String, toml::Value>,
+}
+
+/// The annotation names of an `annos` mapping of a visualizer, e.g.
+/// `/default_ns::pos/, /lemma/`. Regular expressions that match more than a
+/// single name are ignored.
+fn mapped_annos(mapping: &str) -> impl Iterator<Item = String> + '_ {
+    mapping
+        .split(',')
+        .map(|entry| entry.trim().trim_matches('/').trim())
+        .filter(|entry| !entry.is_empty() && !entry.contains(REGEX_CHARACTERS))
+        .map(|entry| entry.replace("\\.", "."))
+}
+
+/// Read the layer settings from an ANNIS corpus configuration
+/// (`corpus-config.toml`), as it is embedded in GraphML files exported from
+/// ANNIS. The base text segmentation is shown first, followed by the
+/// annotations of the visualizers in their configured order.
+pub(crate) fn layer_settings(config: &str) -> Result<LayerSettings> {
+    let config: AnnisConfig =
+        toml::from_str(config).context("Invalid ANNIS corpus configuration")?;
+    let mut result = LayerSettings {
+        hidden_annos: config.view.hidden_annos,
+        anno_order: Vec::new(),
+        value_types: BTreeMap::new(),
+    };
+    let mut add = |name: String| {
+        if !result.anno_order.contains(&name) {
+            result.anno_order.push(name);
+        }
+    };
+    for segmentation in [
+        config.view.base_text_segmentation,
+        config.context.segmentation,
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|s| !s.is_empty())
+    {
+        add(segmentation);
+    }
+    for vis in config.visualizers {
+        if vis.visibility.as_deref() == Some("removed") {
+            continue;
+        }
+        if let Some(annos) = vis.mappings.get("annos").and_then(|v| v.as_str()) {
+            mapped_annos(annos).for_each(&mut add);
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,131 @@
+use graphannis::graph::AnnoKey;
+use pretty_assertions::assert_eq;
+
+use super::{layer_settings, LayerSettings};
+use crate::app::util::value_type::ValueType;
+
+const CONFIG: &str = r#"
+[context]
+default = 5
+segmentation = "norm"
+
+[view]
+base_text_segmentation = "dipl"
+page_size = 10
+hidden_annos = ["default_ns::lemma", "inflection"]
+
+[[visualizers]]
+element = "node"
+layer = "dipl"
+vis_type = "grid"
+display_name = "grid"
+visibility = "hidden"
+
+[visualizers.mappings]
+annos = "/dipl::dipl/, /default_ns::pos/, /default_ns::lemma/, /norm/"
+hide_tok = "true"
+
+[[visualizers]]
+element = "node"
+vis_type = "grid"
+display_name = "removed"
+visibility = "removed"
+
+[visualizers.mappings]
+annos = "/default_ns::removed/"
+
+[[visualizers]]
+element = "node"
+vis_type = "grid"
+display_name = "regex"
+
+[visualizers.mappings]
+annos = "/.*/, /clean/"
+"#;
+
+fn key(ns: &str, name: &str) -> AnnoKey {
+    AnnoKey {
+        ns: ns.into(),
+        name: name.into(),
+    }
+}
+
+#[test]
+fn read_annis_corpus_configuration() {
+    let settings = layer_settings(CONFIG).unwrap();
+    assert_eq!(
+        LayerSettings {
+            hidden_annos: vec!["default_ns::lemma".to_string(), "inflection".to_string()],
+            anno_order: vec![
+                "dipl".to_string(),
+                "norm".to_string(),
+                "dipl::dipl".to_string(),
+                "default_ns::pos".to_string(),
+                "default_ns::lemma".to_string(),
+                "clean".to_string(),
+            ],
+            value_types: Default::default(),
+        },
+        settings
+    );
+
+    assert!(settings.is_hidden(&key("default_ns", "lemma")));
+    assert!(settings.is_hidden(&key("other_ns", "inflection")));
+    assert!(!settings.is_hidden(&key("other_ns", "lemma")));
+
+    let keys = [
+        key("default_ns", "lemma"),
+        key("default_ns", "other"),
+        key("default_ns", "pos"),
+        key("", "clean"),
+    ];
+    assert_eq!(
+        vec![
+            key("default_ns", "pos"),
+            key("default_ns", "lemma"),
+            key("", "clean")
+        ],
+        settings.ordered_keys(keys.iter())
+    );
+    assert_eq!(Some(0), settings.segmentation_rank("dipl"));
+    assert_eq!(Some(1), settings.segmentation_rank("norm"));
+    assert_eq!(None, settings.segmentation_rank("clean2"));
+}
+
+#[test]
+fn value_types_of_annotations() {
+    let settings = LayerSettings {
+        value_types: [
+            ("certainty".to_string(), ValueType::Boolean),
+            ("default_ns::certainty".to_string(), ValueType::Date),
+        ]
+        .into(),
+        ..Default::default()
+    };
+    assert!(!settings.is_empty());
+    assert_eq!(
+        Some(&ValueType::Date),
+        settings.value_type(&key("default_ns", "certainty"))
+    );
+    assert_eq!(
+        Some(&ValueType::Boolean),
+        settings.value_type(&key("other_ns", "certainty"))
+    );
+    assert_eq!(None, settings.value_type(&key("default_ns", "pos")));
+
+    assert!(settings
+        .validate_value(&key("other_ns", "certainty"), "true")
+        .is_ok());
+    assert!(settings
+        .validate_value(&key("other_ns", "certainty"), "maybe")
+        .is_err());
+    assert!(settings
+        .validate_value(&key("default_ns", "pos"), "anything")
+        .is_ok());
+}
+
+#[test]
+fn empty_and_invalid_configuration() {
+    assert!(layer_settings("").unwrap().is_empty());
+    assert!(layer_settings("[view").is_err());
+}
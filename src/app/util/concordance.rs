@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use graphannis::{
+    graph::{AnnoKey, NodeID},
+    model::AnnotationComponentType,
+    AnnotationGraph,
+};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{storage::GraphStorage, ANNIS_NS, NODE_NAME_KEY},
+    types::Component,
+};
+
+use super::corpus_structure::ordered_documents;
+use crate::api::token_helper::{TokenHelper, TOKEN_KEY};
+
+/// Number of base token shown before and after an occurrence.
+const CONTEXT_SIZE: usize = 4;
+
+/// A node of the corpus with the same annotation value as the selected one.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Occurrence {
+    pub(crate) document: NodeID,
+    pub(crate) document_name: String,
+    pub(crate) node_name: String,
+    /// Index of the first base token covered by the node in the document.
+    pub(crate) token_index: usize,
+    pub(crate) left_context: String,
+    /// The text of the base token covered by the node.
+    pub(crate) text: String,
+    pub(crate) right_context: String,
+    /// The annotations of the node, except the ones in the ANNIS namespace.
+    pub(crate) annotations: BTreeMap<AnnoKey, String>,
+}
+
+/// Find the nodes that have the annotation `key` with the given `value`, in
+/// the order of the documents and their token. At most `max_hits`
+/// occurrences are returned. All components of the graph must have been
+/// loaded.
+pub(crate) fn occurrences(
+    graph: &AnnotationGraph,
+    key: &AnnoKey,
+    value: &str,
+    max_hits: usize,
+) -> Result<Vec<Occurrence>> {
+    let tok_helper = TokenHelper::new(graph)?;
+    let node_annos = graph.get_node_annos();
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let part_of = graph
+        .get_graphstorage_as_ref(&part_of)
+        .context("Missing PartOf component")?;
+
+    // Group the matching nodes and the base token they cover by document
+    let mut matches_by_document: HashMap<NodeID, Vec<(NodeID, Vec<NodeID>)>> = HashMap::new();
+    for m in
+        node_annos.exact_anno_search(Some(key.ns.as_str()), &key.name, ValueSearch::Some(value))
+    {
+        let node = m?.node;
+        let covered = if tok_helper.is_token(node)? {
+            vec![node]
+        } else {
+            tok_helper.covered_token(node)?
+        };
+        let Some(first_token) = covered.first() else {
+            continue;
+        };
+        if let Some(document) = part_of.get_outgoing_edges(*first_token).next() {
+            matches_by_document
+                .entry(document?)
+                .or_default()
+                .push((node, covered));
+        }
+    }
+
+    let mut result = Vec::new();
+    for document in ordered_documents(graph)? {
+        let Some(mut matches) = matches_by_document.remove(&document) else {
+            continue;
+        };
+        let document_name = node_annos
+            .get_value_for_item(&document, &NODE_NAME_KEY)?
+            .context("Missing node name")?
+            .to_string();
+        let ordered_token = tok_helper.get_ordered_token(&document_name, None)?;
+        let token_index: HashMap<NodeID, usize> = ordered_token
+            .iter()
+            .enumerate()
+            .map(|(idx, t)| (*t, idx))
+            .collect();
+        let mut token_values = Vec::with_capacity(ordered_token.len());
+        for t in ordered_token.iter() {
+            let value = node_annos
+                .get_value_for_item(t, &TOKEN_KEY)?
+                .unwrap_or_default();
+            token_values.push(value.to_string());
+        }
+
+        matches
+            .sort_by_key(|(_, covered)| covered.first().and_then(|t| token_index.get(t)).copied());
+        for (node, covered) in matches {
+            let (Some(start), Some(end)) = (
+                covered.first().and_then(|t| token_index.get(t)).copied(),
+                covered.last().and_then(|t| token_index.get(t)).copied(),
+            ) else {
+                continue;
+            };
+            let mut annotations = BTreeMap::new();
+            for anno in node_annos.get_annotations_for_item(&node)? {
+                if anno.key.ns != ANNIS_NS {
+                    annotations.insert(anno.key, anno.val.to_string());
+                }
+            }
+            let context_end = (end + 1 + CONTEXT_SIZE).min(token_values.len());
+            result.push(Occurrence {
+                document,
+                document_name: document_name.clone(),
+                node_name: node_annos
+                    .get_value_for_item(&node, &NODE_NAME_KEY)?
+                    .context("Missing node name")?
+                    .to_string(),
+                token_index: start,
+                left_context: token_values[start.saturating_sub(CONTEXT_SIZE)..start].join(" "),
+                text: token_values[start..=end].join(" "),
+                right_context: token_values[(end + 1)..context_end].join(" "),
+                annotations,
+            });
+            if result.len() >= max_hits {
+                return Ok(result);
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests;
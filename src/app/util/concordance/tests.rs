@@ -0,0 +1,92 @@
+use graphannis::{
+    graph::AnnoKey,
+    update::{GraphUpdate, UpdateEvent},
+};
+use graphannis_core::graph::ANNIS_NS;
+use pretty_assertions::assert_eq;
+
+use crate::{api::token_helper::TOKEN_KEY, app::util::example_generator};
+
+use super::occurrences;
+
+fn lemma_key() -> AnnoKey {
+    AnnoKey {
+        ns: "default_ns".into(),
+        name: "lemma".into(),
+    }
+}
+
+/// Adds a second document and lemmas to one token of each document.
+fn add_second_document(updates: &mut GraphUpdate) {
+    updates
+        .add_event(UpdateEvent::AddNode {
+            node_name: "root/doc2".to_string(),
+            node_type: "corpus".to_string(),
+        })
+        .unwrap();
+    updates
+        .add_event(UpdateEvent::AddEdge {
+            source_node: "root/doc2".to_string(),
+            target_node: "root".to_string(),
+            layer: ANNIS_NS.to_string(),
+            component_type: "PartOf".to_string(),
+            component_name: "".to_string(),
+        })
+        .unwrap();
+    example_generator::create_tokens(updates, Some("root/doc2"));
+    for (node_name, lemma) in [("root/doc1#tok7", "it"), ("root/doc2#tok1", "this")] {
+        updates
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: node_name.to_string(),
+                anno_ns: "default_ns".to_string(),
+                anno_name: "lemma".to_string(),
+                anno_value: lemma.to_string(),
+            })
+            .unwrap();
+    }
+}
+
+#[test]
+fn find_occurrences_of_token_text() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_second_document(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+
+    let result = occurrences(&graph, &TOKEN_KEY, "example", 10).unwrap();
+    assert_eq!(2, result.len());
+    assert_eq!("root/doc1", result[0].document_name);
+    assert_eq!("root/doc1#tok2", result[0].node_name);
+    assert_eq!(2, result[0].token_index);
+    assert_eq!("Is this", result[0].left_context);
+    assert_eq!("example", result[0].text);
+    assert_eq!("more complicated than it", result[0].right_context);
+    assert_eq!("root/doc2", result[1].document_name);
+
+    let result = occurrences(&graph, &TOKEN_KEY, "example", 1).unwrap();
+    assert_eq!(1, result.len());
+    assert!(occurrences(&graph, &TOKEN_KEY, "missing", 10)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn find_occurrences_of_lemma() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_second_document(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+
+    let result = occurrences(&graph, &lemma_key(), "this", 10).unwrap();
+    assert_eq!(1, result.len());
+    assert_eq!("root/doc2#tok1", result[0].node_name);
+    assert_eq!("this", result[0].text);
+    assert_eq!(
+        Some(&"this".to_string()),
+        result[0].annotations.get(&lemma_key())
+    );
+    // Annotations of the ANNIS namespace are not included
+    assert_eq!(1, result[0].annotations.len());
+}
@@ -0,0 +1,121 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+/// How the corpora of the start view are grouped.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum CorpusGrouping {
+    /// Show all corpora in a single list.
+    #[default]
+    None,
+    /// Create one group for each plain tag (tags without a category).
+    Tag,
+    /// Group by the value of tags with the given category, e.g. `language`
+    /// for tags like `language:de`.
+    Category(String),
+}
+
+impl CorpusGrouping {
+    pub(crate) fn label(&self) -> String {
+        match self {
+            CorpusGrouping::None => "Nothing".to_string(),
+            CorpusGrouping::Tag => "Tags".to_string(),
+            CorpusGrouping::Category(category) => category.clone(),
+        }
+    }
+}
+
+/// Split a tag of the form `category:value` into its parts. Tags without a
+/// category only have a value.
+pub(crate) fn split_tag(tag: &str) -> (Option<&str>, &str) {
+    match tag.split_once(':') {
+        Some((category, value)) if !category.trim().is_empty() => {
+            (Some(category.trim()), value.trim())
+        }
+        _ => (None, tag.trim()),
+    }
+}
+
+/// All categories used by the tags of any corpus.
+pub(crate) fn tag_categories(tags: &BTreeMap<String, BTreeSet<String>>) -> BTreeSet<String> {
+    tags.values()
+        .flatten()
+        .filter_map(|t| split_tag(t).0)
+        .map(str::to_string)
+        .collect()
+}
+
+/// True if the search text is contained in the corpus name or one of its tags,
+/// ignoring the case.
+fn matches_filter(corpus: &str, tags: Option<&BTreeSet<String>>, filter: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    filter.is_empty()
+        || corpus.to_lowercase().contains(&filter)
+        || tags.is_some_and(|tags| tags.iter().any(|t| t.to_lowercase().contains(&filter)))
+}
+
+/// Order the corpora so the pinned ones come first, in the order they have
+/// been pinned, followed by the others in their original order. Pinned
+/// corpora that are not part of `corpora` are ignored.
+pub(crate) fn pinned_first<'a, I>(corpora: I, pinned: &[String]) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let corpora: Vec<&String> = corpora.into_iter().collect();
+    let mut result: Vec<String> = pinned
+        .iter()
+        .filter(|p| corpora.contains(p))
+        .cloned()
+        .collect();
+    for c in corpora {
+        if !pinned.contains(c) {
+            result.push(c.clone());
+        }
+    }
+    result
+}
+
+/// Group the corpora that match the search text. Corpora with several
+/// matching tags are part of each of these groups, corpora without any are
+/// in the group with the empty name.
+pub(crate) fn group_corpora(
+    corpora: &[String],
+    tags: &BTreeMap<String, BTreeSet<String>>,
+    grouping: &CorpusGrouping,
+    filter: &str,
+) -> BTreeMap<String, Vec<String>> {
+    let mut result: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for corpus in corpora {
+        let corpus_tags = tags.get(corpus);
+        if !matches_filter(corpus, corpus_tags, filter) {
+            continue;
+        }
+        let groups: BTreeSet<&str> = corpus_tags
+            .into_iter()
+            .flatten()
+            .map(|t| split_tag(t))
+            .filter_map(|(category, value)| match grouping {
+                CorpusGrouping::None => None,
+                CorpusGrouping::Tag => category.is_none().then_some(value),
+                CorpusGrouping::Category(c) => (category == Some(c.as_str())).then_some(value),
+            })
+            .collect();
+        if groups.is_empty() {
+            result
+                .entry(String::new())
+                .or_default()
+                .push(corpus.clone());
+        } else {
+            for group in groups {
+                result
+                    .entry(group.to_string())
+                    .or_default()
+                    .push(corpus.clone());
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests;
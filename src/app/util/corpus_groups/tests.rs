@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use pretty_assertions::assert_eq;
+
+use super::{group_corpora, pinned_first, split_tag, tag_categories, CorpusGrouping};
+
+fn example_tags() -> BTreeMap<String, BTreeSet<String>> {
+    let mut tags = BTreeMap::new();
+    tags.insert(
+        "alpha".to_string(),
+        BTreeSet::from(["language:de".to_string(), "historical".to_string()]),
+    );
+    tags.insert(
+        "beta".to_string(),
+        BTreeSet::from([
+            "language:en".to_string(),
+            "language:de".to_string(),
+            "project: Tiger".to_string(),
+        ]),
+    );
+    tags
+}
+
+fn corpora() -> Vec<String> {
+    vec!["alpha".to_string(), "beta".to_string(), "gamma".to_string()]
+}
+
+fn names(corpora: &[&str]) -> Vec<String> {
+    corpora.iter().map(|c| c.to_string()).collect()
+}
+
+#[test]
+fn split_tags_into_category_and_value() {
+    assert_eq!((Some("language"), "de"), split_tag("language:de"));
+    assert_eq!((Some("project"), "Tiger"), split_tag("project: Tiger"));
+    assert_eq!((None, "historical"), split_tag("historical"));
+    assert_eq!((None, ":odd"), split_tag(":odd"));
+    assert_eq!(
+        BTreeSet::from(["language".to_string(), "project".to_string()]),
+        tag_categories(&example_tags())
+    );
+}
+
+#[test]
+fn group_by_category() {
+    let groups = group_corpora(
+        &corpora(),
+        &example_tags(),
+        &CorpusGrouping::Category("language".to_string()),
+        "",
+    );
+    let expected = BTreeMap::from([
+        (String::new(), names(&["gamma"])),
+        ("de".to_string(), names(&["alpha", "beta"])),
+        ("en".to_string(), names(&["beta"])),
+    ]);
+    assert_eq!(expected, groups);
+
+    let groups = group_corpora(&corpora(), &example_tags(), &CorpusGrouping::Tag, "");
+    let expected = BTreeMap::from([
+        (String::new(), names(&["beta", "gamma"])),
+        ("historical".to_string(), names(&["alpha"])),
+    ]);
+    assert_eq!(expected, groups);
+
+    let groups = group_corpora(&corpora(), &example_tags(), &CorpusGrouping::None, "");
+    let expected = BTreeMap::from([(String::new(), corpora())]);
+    assert_eq!(expected, groups);
+}
+
+#[test]
+fn filter_by_name_and_tags() {
+    let groups = group_corpora(&corpora(), &example_tags(), &CorpusGrouping::None, "GAM");
+    assert_eq!(BTreeMap::from([(String::new(), names(&["gamma"]))]), groups);
+
+    let groups = group_corpora(&corpora(), &example_tags(), &CorpusGrouping::None, "tiger");
+    assert_eq!(BTreeMap::from([(String::new(), names(&["beta"]))]), groups);
+
+    let groups = group_corpora(&corpora(), &example_tags(), &CorpusGrouping::None, "none");
+    assert!(groups.is_empty());
+}
+
+#[test]
+fn pinned_corpora_first() {
+    let pinned = names(&["gamma", "removed", "beta"]);
+    assert_eq!(
+        names(&["gamma", "beta", "alpha"]),
+        pinned_first(&corpora(), &pinned)
+    );
+    assert_eq!(corpora(), pinned_first(&corpora(), &[]));
+
+    // The pinned order is kept within the groups
+    let groups = group_corpora(
+        &pinned_first(&corpora(), &pinned),
+        &example_tags(),
+        &CorpusGrouping::Category("language".to_string()),
+        "",
+    );
+    assert_eq!(Some(&names(&["beta", "alpha"])), groups.get("de"));
+    assert_eq!(Some(&names(&["gamma"])), groups.get(""));
+}
@@ -0,0 +1,79 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use graphannis::{model::AnnotationComponentType, AnnotationGraph};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{ANNIS_NS, NODE_TYPE},
+    types::{Component, NodeID},
+};
+
+/// Get all documents of the graph in the order they appear in the corpus
+/// tree. Documents are the corpus nodes without any sub-corpus.
+pub(crate) fn ordered_documents(graph: &AnnotationGraph) -> Result<Vec<NodeID>> {
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let Some(part_of) = graph.get_graphstorage_as_ref(&part_of) else {
+        return Ok(Vec::new());
+    };
+
+    let mut corpus_nodes = HashSet::new();
+    for m in graph.get_node_annos().exact_anno_search(
+        Some(ANNIS_NS),
+        NODE_TYPE,
+        ValueSearch::Some("corpus"),
+    ) {
+        corpus_nodes.insert(m?.node);
+    }
+
+    let mut roots = Vec::new();
+    let mut children: BTreeMap<NodeID, Vec<NodeID>> = BTreeMap::new();
+    for node in corpus_nodes.iter() {
+        let mut parent = None;
+        for target in part_of.get_outgoing_edges(*node) {
+            let target = target?;
+            if corpus_nodes.contains(&target) {
+                parent = Some(target);
+                break;
+            }
+        }
+        if let Some(parent) = parent {
+            children.entry(parent).or_default().push(*node);
+        } else {
+            roots.push(*node);
+        }
+    }
+    // The corpus tree sorts the child nodes by their ID
+    roots.sort_unstable();
+    for child_nodes in children.values_mut() {
+        child_nodes.sort_unstable();
+    }
+
+    let mut result = Vec::new();
+    let mut stack: Vec<NodeID> = roots.into_iter().rev().collect();
+    while let Some(node) = stack.pop() {
+        if let Some(child_nodes) = children.get(&node) {
+            stack.extend(child_nodes.iter().rev());
+        } else {
+            result.push(node);
+        }
+    }
+    Ok(result)
+}
+
+/// Get the documents before and after the given document in the corpus tree.
+pub(crate) fn neighbour_documents(
+    graph: &AnnotationGraph,
+    document: NodeID,
+) -> Result<(Option<NodeID>, Option<NodeID>)> {
+    let documents = ordered_documents(graph)?;
+    if let Some(idx) = documents.iter().position(|d| *d == document) {
+        let previous = idx.checked_sub(1).and_then(|idx| documents.get(idx));
+        let next = documents.get(idx + 1);
+        Ok((previous.copied(), next.copied()))
+    } else {
+        Ok((None, None))
+    }
+}
+
+#[cfg(test)]
+mod tests;
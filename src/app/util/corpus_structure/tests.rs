@@ -0,0 +1,59 @@
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use graphannis_core::graph::{ANNIS_NS, NODE_NAME_KEY};
+use pretty_assertions::assert_eq;
+
+use super::{neighbour_documents, ordered_documents};
+use crate::app::util::example_generator;
+
+fn add_corpus(update: &mut GraphUpdate, node_name: &str, parent: Option<&str>) {
+    update
+        .add_event(UpdateEvent::AddNode {
+            node_name: node_name.to_string(),
+            node_type: "corpus".to_string(),
+        })
+        .unwrap();
+    if let Some(parent) = parent {
+        update
+            .add_event(UpdateEvent::AddEdge {
+                source_node: node_name.to_string(),
+                target_node: parent.to_string(),
+                layer: ANNIS_NS.to_string(),
+                component_type: "PartOf".to_string(),
+                component_name: "".to_string(),
+            })
+            .unwrap();
+    }
+}
+
+#[test]
+fn documents_in_tree_order() {
+    let mut updates = GraphUpdate::new();
+    add_corpus(&mut updates, "root", None);
+    add_corpus(&mut updates, "root/doc1", Some("root"));
+    add_corpus(&mut updates, "root/sub", Some("root"));
+    add_corpus(&mut updates, "root/sub/doc2", Some("root/sub"));
+    add_corpus(&mut updates, "root/doc3", Some("root"));
+    let g = example_generator::create_graph(&mut updates);
+
+    let node_name = |id| {
+        g.get_node_annos()
+            .get_value_for_item(&id, &NODE_NAME_KEY)
+            .unwrap()
+            .unwrap()
+            .to_string()
+    };
+
+    let documents = ordered_documents(&g).unwrap();
+    assert_eq!(
+        vec!["root/doc1", "root/sub/doc2", "root/doc3"],
+        documents.iter().map(|d| node_name(*d)).collect::<Vec<_>>()
+    );
+
+    let (previous, next) = neighbour_documents(&g, documents[1]).unwrap();
+    assert_eq!(Some(documents[0]), previous);
+    assert_eq!(Some(documents[2]), next);
+
+    let (previous, next) = neighbour_documents(&g, documents[0]).unwrap();
+    assert_eq!(None, previous);
+    assert_eq!(Some(documents[1]), next);
+}
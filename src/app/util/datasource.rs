@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use graphannis::{
+    model::AnnotationComponentType,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::{
+    graph::{ANNIS_NS, NODE_NAME_KEY, NODE_TYPE_KEY},
+    types::{Component, NodeID},
+};
+
+/// The node type of the text data sources token can be part of.
+pub(crate) const DATASOURCE_NODE_TYPE: &str = "datasource";
+
+/// A data source (text) node of a document.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Datasource {
+    pub(crate) node_name: String,
+    /// Number of nodes that are part of this data source.
+    pub(crate) node_count: usize,
+}
+
+impl Datasource {
+    /// The name of the data source without the document path.
+    pub(crate) fn short_name(&self) -> &str {
+        self.node_name
+            .rsplit_once('#')
+            .map_or(self.node_name.as_str(), |(_, name)| name)
+    }
+}
+
+/// Get all data source nodes that are part of the given document.
+pub(crate) fn datasources_of_document(
+    graph: &AnnotationGraph,
+    document: NodeID,
+) -> Result<Vec<Datasource>> {
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let Some(part_of) = graph.get_graphstorage_as_ref(&part_of) else {
+        return Ok(Vec::new());
+    };
+    let node_annos = graph.get_node_annos();
+    let mut result = Vec::new();
+    for child in part_of.get_ingoing_edges(document) {
+        let child = child?;
+        let node_type = node_annos.get_value_for_item(&child, &NODE_TYPE_KEY)?;
+        if node_type.as_deref() == Some(DATASOURCE_NODE_TYPE) {
+            let node_name = node_annos
+                .get_value_for_item(&child, &NODE_NAME_KEY)?
+                .context("Missing node name")?;
+            let node_count = part_of.get_ingoing_edges(child).count();
+            result.push(Datasource {
+                node_name: node_name.to_string(),
+                node_count,
+            });
+        }
+    }
+    result.sort_by(|a, b| a.node_name.cmp(&b.node_name));
+    Ok(result)
+}
+
+/// Find a name for a new data source of the document that is not used yet.
+pub(crate) fn new_datasource_name(graph: &AnnotationGraph, document: &str) -> Result<String> {
+    let node_annos = graph.get_node_annos();
+    let mut suffix = 1;
+    loop {
+        let candidate = format!("{document}#text{suffix}");
+        if node_annos.get_node_id_from_name(&candidate)?.is_none() {
+            return Ok(candidate);
+        }
+        suffix += 1;
+    }
+}
+
+/// Add the events to create a data source node that is part of the document.
+pub(crate) fn add_datasource(
+    updates: &mut GraphUpdate,
+    document: &str,
+    datasource: &str,
+) -> Result<()> {
+    updates.add_event(UpdateEvent::AddNode {
+        node_name: datasource.to_string(),
+        node_type: DATASOURCE_NODE_TYPE.to_string(),
+    })?;
+    updates.add_event(UpdateEvent::AddEdge {
+        source_node: datasource.to_string(),
+        target_node: document.to_string(),
+        layer: ANNIS_NS.to_string(),
+        component_type: AnnotationComponentType::PartOf.to_string(),
+        component_name: "".to_string(),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
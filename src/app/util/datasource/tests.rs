@@ -0,0 +1,38 @@
+use graphannis::update::GraphUpdate;
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{add_datasource, datasources_of_document, new_datasource_name, Datasource};
+
+#[test]
+fn list_and_add_datasources() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1#text1"));
+    let mut graph = example_generator::create_graph(&mut updates);
+    let doc1 = graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        vec![Datasource {
+            node_name: "root/doc1#text1".to_string(),
+            node_count: 11,
+        }],
+        datasources_of_document(&graph, doc1).unwrap()
+    );
+
+    let new_name = new_datasource_name(&graph, "root/doc1").unwrap();
+    assert_eq!("root/doc1#text2", new_name);
+    let mut updates = GraphUpdate::new();
+    add_datasource(&mut updates, "root/doc1", &new_name).unwrap();
+    graph.apply_update(&mut updates, |_msg| {}).unwrap();
+
+    let datasources = datasources_of_document(&graph, doc1).unwrap();
+    assert_eq!(2, datasources.len());
+    assert_eq!("text2", datasources[1].short_name());
+    assert_eq!(0, datasources[1].node_count);
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+use anyhow::Result;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// Number of log messages that are kept for the diagnostic bundle.
+const MAX_RECENT_LOGS: usize = 1000;
+
+static RECENT_LOGS: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+
+/// Logs to stderr like `env_logger` and keeps the most recent messages with
+/// at least the info level in memory, so they can be added to a diagnostic
+/// bundle.
+struct RecentLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for RecentLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= Level::Info || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+        if record.level() <= Level::Info {
+            remember_log_message(format!(
+                "{} {} {}: {}",
+                OffsetDateTime::now_utc()
+                    .format(&Rfc3339)
+                    .unwrap_or_default(),
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+fn remember_log_message(message: String) {
+    let logs = RECENT_LOGS.get_or_init(|| Mutex::new(VecDeque::new()));
+    if let Ok(mut logs) = logs.lock() {
+        if logs.len() >= MAX_RECENT_LOGS {
+            logs.pop_front();
+        }
+        logs.push_back(message);
+    }
+}
+
+/// Initialize logging to stderr, which can be configured with the
+/// `RUST_LOG` environment variable, and remember the recent log messages for
+/// diagnostic bundles.
+pub fn init_logging() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter().max(LevelFilter::Info);
+    if log::set_boxed_logger(Box::new(RecentLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// The recent log messages, oldest first.
+pub(crate) fn recent_logs() -> Vec<String> {
+    RECENT_LOGS
+        .get()
+        .and_then(|logs| logs.lock().ok().map(|logs| logs.iter().cloned().collect()))
+        .unwrap_or_default()
+}
+
+/// Information about the application and the system it runs on.
+pub(crate) fn environment_info() -> String {
+    let mut result = format!(
+        "annatomic {}\nOS: {} ({})\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    if let Some(stats) = memory_stats::memory_stats() {
+        result.push_str(&format!(
+            "Physical memory used: {} MB\n",
+            stats.physical_mem / 1_000_000
+        ));
+    }
+    result
+}
+
+/// Write the files with the given names and content into a ZIP file.
+pub(crate) fn write_diagnostic_bundle(location: &Path, files: &[(&str, String)]) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(location)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in files {
+        zip.start_file(*name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,45 @@
+use std::io::Read;
+
+use super::{environment_info, recent_logs, remember_log_message, write_diagnostic_bundle};
+
+#[test]
+fn keep_recent_log_messages() {
+    remember_log_message("First test message".to_string());
+    remember_log_message("Second test message".to_string());
+    let logs = recent_logs();
+    let first = logs.iter().position(|m| m == "First test message");
+    let second = logs.iter().position(|m| m == "Second test message");
+    assert!(first.is_some());
+    assert!(first < second);
+}
+
+#[test]
+fn write_bundle_with_all_files() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let location = dir.path().join("diagnostics.zip");
+    write_diagnostic_bundle(
+        &location,
+        &[
+            ("environment.txt", environment_info()),
+            ("log.txt", "A log message".to_string()),
+        ],
+    )
+    .unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&location).unwrap()).unwrap();
+    assert_eq!(2, archive.len());
+    let mut log = String::new();
+    archive
+        .by_name("log.txt")
+        .unwrap()
+        .read_to_string(&mut log)
+        .unwrap();
+    assert_eq!("A log message", log);
+    let mut environment = String::new();
+    archive
+        .by_name("environment.txt")
+        .unwrap()
+        .read_to_string(&mut environment)
+        .unwrap();
+    assert!(environment.starts_with("annatomic "));
+}
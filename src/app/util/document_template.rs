@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use graphannis::{
+    graph::NodeID,
+    model::AnnotationComponentType,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{storage::GraphStorage, ANNIS_NS, NODE_NAME_KEY, NODE_TYPE_KEY},
+    types::Component,
+};
+use serde::{Deserialize, Serialize};
+
+use super::tokenizer::TokenizerKind;
+use crate::{api::token_helper::TOKEN_KEY, app::views::rename_annotation::parse_key};
+
+/// The structure new documents start with, e.g. the metadata that all
+/// documents of a project must have.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct DocumentTemplate {
+    /// Metadata of the document by their key of the form `ns:name` or `name`.
+    pub(crate) metadata: BTreeMap<String, String>,
+    /// Segmentation layers that are shown empty in the editor of the new
+    /// document. They are only added to the graph with their first span.
+    pub(crate) segmentation_layers: Vec<String>,
+    /// Text that is tokenized to create the first token of the document.
+    pub(crate) boilerplate: String,
+    /// Annotations of spans covering all token of the boilerplate text,
+    /// by their key of the form `ns:name` or `name`.
+    pub(crate) boilerplate_spans: BTreeMap<String, String>,
+}
+
+/// Parse lines of the form `key=value`. Empty lines are ignored.
+pub(crate) fn parse_annotation_lines(text: &str) -> Result<BTreeMap<String, String>> {
+    let mut result = BTreeMap::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("The line \"{line}\" must have the form key=value");
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("The key of the line \"{line}\" must not be empty");
+        }
+        result.insert(key.to_string(), value.trim().to_string());
+    }
+    Ok(result)
+}
+
+/// The annotations as lines of the form `key=value`.
+pub(crate) fn annotation_lines(annotations: &BTreeMap<String, String>) -> String {
+    annotations
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The corpus node a new document is added to if `selected` is selected in
+/// the corpus tree. Documents get a new sibling instead of a child. Without
+/// a selection, the document is added to the top-level corpus.
+pub(crate) fn parent_for_new_document(
+    graph: &AnnotationGraph,
+    selected: Option<NodeID>,
+) -> Result<NodeID> {
+    let node_annos = graph.get_node_annos();
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let part_of = graph.get_graphstorage_as_ref(&part_of);
+    let Some(selected) = selected else {
+        for m in node_annos.exact_anno_search(
+            Some(NODE_TYPE_KEY.ns.as_str()),
+            NODE_TYPE_KEY.name.as_str(),
+            ValueSearch::Some("corpus"),
+        ) {
+            let node = m?.node;
+            if part_of.is_none_or(|gs| gs.get_outgoing_edges(node).next().is_none()) {
+                return Ok(node);
+            }
+        }
+        bail!("The corpus has no top-level corpus node");
+    };
+    let Some(part_of) = part_of else {
+        return Ok(selected);
+    };
+    let Some(parent) = part_of.get_outgoing_edges(selected).next() else {
+        // The top-level corpus
+        return Ok(selected);
+    };
+    for child in part_of.get_ingoing_edges(selected) {
+        let child_type = node_annos.get_value_for_item(&child?, &NODE_TYPE_KEY)?;
+        if child_type.as_deref() == Some("corpus") {
+            // A sub-corpus
+            return Ok(selected);
+        }
+    }
+    Ok(parent?)
+}
+
+/// Create a new document as child of the `parent` corpus node, structured as
+/// given by the `template`. Returns the node name of the new document and
+/// the update that creates it.
+pub(crate) fn new_document_update(
+    graph: &AnnotationGraph,
+    parent: NodeID,
+    document_name: &str,
+    template: &DocumentTemplate,
+    tokenizer: &TokenizerKind,
+) -> Result<(String, GraphUpdate)> {
+    let document_name = document_name.trim();
+    if document_name.is_empty() {
+        bail!("The name of the document must not be empty");
+    }
+    if document_name.contains(['/', '#']) {
+        bail!("The name of the document must not contain \"/\" or \"#\"");
+    }
+    let node_annos = graph.get_node_annos();
+    let parent_name = node_annos
+        .get_value_for_item(&parent, &NODE_NAME_KEY)?
+        .context("Missing node name")?;
+    let node_name = format!("{parent_name}/{document_name}");
+    if node_annos.get_node_id_from_name(&node_name)?.is_some() {
+        bail!("Node \"{node_name}\" already exists");
+    }
+
+    let mut update = GraphUpdate::new();
+    update.add_event(UpdateEvent::AddNode {
+        node_name: node_name.clone(),
+        node_type: "corpus".to_string(),
+    })?;
+    update.add_event(UpdateEvent::AddNodeLabel {
+        node_name: node_name.clone(),
+        anno_ns: ANNIS_NS.to_string(),
+        anno_name: "doc".to_string(),
+        anno_value: document_name.to_string(),
+    })?;
+    update.add_event(UpdateEvent::AddEdge {
+        source_node: node_name.clone(),
+        target_node: parent_name.to_string(),
+        layer: ANNIS_NS.to_string(),
+        component_type: AnnotationComponentType::PartOf.to_string(),
+        component_name: "".to_string(),
+    })?;
+    for (key, value) in template.metadata.iter() {
+        let key = parse_key(key);
+        update.add_event(UpdateEvent::AddNodeLabel {
+            node_name: node_name.clone(),
+            anno_ns: key.ns.to_string(),
+            anno_name: key.name.to_string(),
+            anno_value: value.clone(),
+        })?;
+    }
+
+    let new_token = tokenizer.create()?.tokenize(&template.boilerplate);
+    if new_token.is_empty() {
+        return Ok((node_name, update));
+    }
+    let mut next_id = node_annos
+        .get_largest_item()?
+        .map(|id| id + 1)
+        .unwrap_or_default();
+    let mut token_names = Vec::with_capacity(new_token.len());
+    for t in new_token {
+        let token_name = format!("{node_name}#{next_id}");
+        next_id += 1;
+        update.add_event(UpdateEvent::AddNode {
+            node_name: token_name.clone(),
+            node_type: "node".to_string(),
+        })?;
+        update.add_event(UpdateEvent::AddNodeLabel {
+            node_name: token_name.clone(),
+            anno_ns: TOKEN_KEY.ns.to_string(),
+            anno_name: TOKEN_KEY.name.to_string(),
+            anno_value: t.value,
+        })?;
+        if !t.whitespace_after.is_empty() {
+            update.add_event(UpdateEvent::AddNodeLabel {
+                node_name: token_name.clone(),
+                anno_ns: ANNIS_NS.to_string(),
+                anno_name: "tok-whitespace-after".to_string(),
+                anno_value: t.whitespace_after,
+            })?;
+        }
+        update.add_event(UpdateEvent::AddEdge {
+            source_node: token_name.clone(),
+            target_node: node_name.clone(),
+            layer: ANNIS_NS.to_string(),
+            component_type: AnnotationComponentType::PartOf.to_string(),
+            component_name: "".to_string(),
+        })?;
+        if let Some(previous_token) = token_names.last() {
+            update.add_event(UpdateEvent::AddEdge {
+                source_node: previous_token.clone(),
+                target_node: token_name.clone(),
+                layer: ANNIS_NS.to_string(),
+                component_type: AnnotationComponentType::Ordering.to_string(),
+                component_name: "".to_string(),
+            })?;
+        }
+        token_names.push(token_name);
+    }
+
+    for (key, value) in template.boilerplate_spans.iter() {
+        let key = parse_key(key);
+        let span_name = format!("{node_name}#{next_id}");
+        next_id += 1;
+        update.add_event(UpdateEvent::AddNode {
+            node_name: span_name.clone(),
+            node_type: "node".to_string(),
+        })?;
+        update.add_event(UpdateEvent::AddEdge {
+            source_node: span_name.clone(),
+            target_node: node_name.clone(),
+            layer: ANNIS_NS.to_string(),
+            component_type: AnnotationComponentType::PartOf.to_string(),
+            component_name: "".to_string(),
+        })?;
+        update.add_event(UpdateEvent::AddNodeLabel {
+            node_name: span_name.clone(),
+            anno_ns: key.ns.to_string(),
+            anno_name: key.name.to_string(),
+            anno_value: value.clone(),
+        })?;
+        for token_name in token_names.iter() {
+            update.add_event(UpdateEvent::AddEdge {
+                source_node: span_name.clone(),
+                target_node: token_name.clone(),
+                layer: "".to_string(),
+                component_type: AnnotationComponentType::Coverage.to_string(),
+                component_name: "".to_string(),
+            })?;
+        }
+    }
+    Ok((node_name, update))
+}
+
+#[cfg(test)]
+mod tests;
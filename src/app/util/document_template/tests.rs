@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use graphannis::{
+    graph::{AnnoKey, NodeID},
+    update::GraphUpdate,
+    AnnotationGraph,
+};
+use graphannis_core::{annostorage::ValueSearch, graph::NODE_NAME_KEY};
+use pretty_assertions::assert_eq;
+
+use crate::{
+    api::token_helper::{TokenHelper, TOKEN_KEY},
+    app::util::{example_generator, tokenizer::TokenizerKind},
+};
+
+use super::{
+    annotation_lines, new_document_update, parent_for_new_document, parse_annotation_lines,
+    DocumentTemplate,
+};
+
+fn root(graph: &AnnotationGraph) -> NodeID {
+    graph
+        .get_node_annos()
+        .get_node_id_from_name("root")
+        .unwrap()
+        .unwrap()
+}
+
+fn value(graph: &AnnotationGraph, node_name: &str, ns: &str, name: &str) -> Option<String> {
+    let node_annos = graph.get_node_annos();
+    let node = node_annos.get_node_id_from_name(node_name).unwrap()?;
+    let key = AnnoKey {
+        ns: ns.into(),
+        name: name.into(),
+    };
+    node_annos
+        .get_value_for_item(&node, &key)
+        .unwrap()
+        .map(|v| v.to_string())
+}
+
+#[test]
+fn document_from_template() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let mut graph = example_generator::create_graph(&mut updates);
+    let template = DocumentTemplate {
+        metadata: [
+            ("genre".to_string(), "letter".to_string()),
+            ("meta:status".to_string(), "draft".to_string()),
+        ]
+        .into(),
+        segmentation_layers: vec!["dipl".to_string()],
+        boilerplate: "Dear Sir,".to_string(),
+        boilerplate_spans: [("default_ns:section".to_string(), "salutation".to_string())].into(),
+    };
+    let (document, mut update) = new_document_update(
+        &graph,
+        root(&graph),
+        " letter1 ",
+        &template,
+        &TokenizerKind::Punctuation,
+    )
+    .unwrap();
+    assert_eq!("root/letter1", document);
+    graph.apply_update(&mut update, |_msg| {}).unwrap();
+
+    assert_eq!(
+        Some("letter1".to_string()),
+        value(&graph, &document, "annis", "doc")
+    );
+    assert_eq!(
+        Some("letter".to_string()),
+        value(&graph, &document, "", "genre")
+    );
+    assert_eq!(
+        Some("draft".to_string()),
+        value(&graph, &document, "meta", "status")
+    );
+
+    let tok_helper = TokenHelper::new(&graph).unwrap();
+    let token = tok_helper.get_ordered_token(&document, None).unwrap();
+    let node_annos = graph.get_node_annos();
+    let values: Vec<String> = token
+        .iter()
+        .map(|t| {
+            node_annos
+                .get_value_for_item(t, &TOKEN_KEY)
+                .unwrap()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(vec!["Dear", "Sir", ","], values);
+
+    // The span covers all token of the boilerplate text
+    let span_names: Vec<String> = node_annos
+        .exact_anno_search(Some("default_ns"), "section", ValueSearch::Any)
+        .map(|m| {
+            node_annos
+                .get_value_for_item(&m.unwrap().node, &NODE_NAME_KEY)
+                .unwrap()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(1, span_names.len());
+    let span = node_annos
+        .get_node_id_from_name(&span_names[0])
+        .unwrap()
+        .unwrap();
+    assert_eq!(token, tok_helper.covered_token(span).unwrap());
+}
+
+#[test]
+fn empty_template_and_invalid_names() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    let template = DocumentTemplate::default();
+    let (_, update) = new_document_update(
+        &graph,
+        root(&graph),
+        "doc2",
+        &template,
+        &TokenizerKind::Whitespace,
+    )
+    .unwrap();
+    // Only the document node, its name and its PartOf edge
+    assert_eq!(3, update.iter().unwrap().count());
+
+    for name in ["", "  ", "doc1", "a/b", "a#b"] {
+        assert!(new_document_update(
+            &graph,
+            root(&graph),
+            name,
+            &template,
+            &TokenizerKind::Whitespace
+        )
+        .is_err());
+    }
+}
+
+#[test]
+fn parent_of_new_documents() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    let node_annos = graph.get_node_annos();
+    let doc1 = node_annos
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap();
+    let root = root(&graph);
+    assert_eq!(root, parent_for_new_document(&graph, None).unwrap());
+    assert_eq!(root, parent_for_new_document(&graph, Some(root)).unwrap());
+    // Documents get a sibling
+    assert_eq!(root, parent_for_new_document(&graph, Some(doc1)).unwrap());
+}
+
+#[test]
+fn annotation_lines_roundtrip() {
+    let annotations = parse_annotation_lines("genre = letter\n\nmeta:status=draft\n").unwrap();
+    assert_eq!(
+        BTreeMap::from([
+            ("genre".to_string(), "letter".to_string()),
+            ("meta:status".to_string(), "draft".to_string()),
+        ]),
+        annotations
+    );
+    assert_eq!(
+        "genre=letter\nmeta:status=draft",
+        annotation_lines(&annotations)
+    );
+    assert!(parse_annotation_lines("genre").is_err());
+    assert!(parse_annotation_lines("=letter").is_err());
+}
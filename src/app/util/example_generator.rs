@@ -1,4 +1,4 @@
-use graphannis::model::AnnotationComponentType;
+use graphannis::{model::AnnotationComponentType, AnnotationGraph};
 use graphannis_core::graph::{
     update::{GraphUpdate, UpdateEvent},
     ANNIS_NS,
@@ -294,3 +294,10 @@ pub fn make_span(
             .unwrap();
     }
 }
+
+/// Create a new in-memory graph and apply the update events to it.
+pub fn create_graph(update: &mut GraphUpdate) -> AnnotationGraph {
+    let mut graph = AnnotationGraph::with_default_graphstorages(false).unwrap();
+    graph.apply_update(update, |_msg| {}).unwrap();
+    graph
+}
@@ -0,0 +1,140 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Write,
+};
+
+use anyhow::Result;
+use graphannis::{
+    aql,
+    graph::{AnnoKey, NodeID},
+    AnnotationGraph,
+};
+use graphannis_core::annostorage::ValueSearch;
+
+use crate::app::{job_executor::FgJob, views::rename_annotation::key_label};
+
+/// Check for a cancelled job after this number of nodes.
+const CANCEL_CHECK_INTERVAL: usize = 10_000;
+
+/// How often each combination of annotation values occurs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct FrequencyTable {
+    pub(crate) keys: Vec<AnnoKey>,
+    /// The values for each key and their count, ordered by the count.
+    pub(crate) rows: Vec<(Vec<String>, usize)>,
+}
+
+impl FrequencyTable {
+    pub(crate) fn total(&self) -> usize {
+        self.rows.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Sort the rows by the values of the given column or by the count if
+    /// the column is `None`.
+    pub(crate) fn sort_by(&mut self, column: Option<usize>, ascending: bool) {
+        self.rows.sort_by(|a, b| {
+            let ordering = match column {
+                Some(column) => a.0.get(column).cmp(&b.0.get(column)),
+                None => a.1.cmp(&b.1),
+            };
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+    }
+
+    pub(crate) fn write_csv<W: Write>(&self, output: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(output);
+        let mut header: Vec<String> = self.keys.iter().map(key_label).collect();
+        header.push("count".to_string());
+        writer.write_record(&header)?;
+        for (values, count) in self.rows.iter() {
+            let mut record = values.clone();
+            record.push(count.to_string());
+            writer.write_record(&record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Count the combinations of the values of the annotation `keys` of each
+/// node that has all of them. If a `subset_query` is given, only the nodes
+/// matched by this AQL query are counted.
+pub(crate) fn frequencies(
+    graph: &AnnotationGraph,
+    keys: &[AnnoKey],
+    subset_query: Option<&str>,
+    job: &FgJob,
+) -> Result<FrequencyTable> {
+    let Some(first_key) = keys.first() else {
+        return Ok(FrequencyTable::default());
+    };
+    let node_annos = graph.get_node_annos();
+
+    let mut nodes = BTreeSet::new();
+    if let Some(subset_query) = subset_query {
+        let query = aql::parse(subset_query, false)?;
+        for (idx, m) in aql::execute_query_on_graph(graph, &query, true, None)?.enumerate() {
+            if idx % CANCEL_CHECK_INTERVAL == 0 {
+                job.check_cancelled()?;
+            }
+            nodes.extend(m?.iter().map(|m| m.node));
+        }
+    } else {
+        for (idx, m) in node_annos
+            .exact_anno_search(
+                Some(first_key.ns.as_str()),
+                first_key.name.as_str(),
+                ValueSearch::Any,
+            )
+            .enumerate()
+        {
+            if idx % CANCEL_CHECK_INTERVAL == 0 {
+                job.check_cancelled()?;
+            }
+            nodes.insert(m?.node);
+        }
+    }
+
+    let mut counts: HashMap<Vec<String>, usize> = HashMap::new();
+    for (idx, node) in nodes.into_iter().enumerate() {
+        if idx % CANCEL_CHECK_INTERVAL == 0 {
+            job.check_cancelled()?;
+        }
+        if let Some(values) = values_of_node(graph, node, keys)? {
+            *counts.entry(values).or_default() += 1;
+        }
+    }
+
+    let mut table = FrequencyTable {
+        keys: keys.to_vec(),
+        rows: counts.into_iter().collect(),
+    };
+    // Most frequent first, with the values as tie breaker for a stable order
+    table
+        .rows
+        .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(table)
+}
+
+fn values_of_node(
+    graph: &AnnotationGraph,
+    node: NodeID,
+    keys: &[AnnoKey],
+) -> Result<Option<Vec<String>>> {
+    let mut values = Vec::with_capacity(keys.len());
+    for key in keys {
+        if let Some(value) = graph.get_node_annos().get_value_for_item(&node, key)? {
+            values.push(value.to_string());
+        } else {
+            return Ok(None);
+        }
+    }
+    Ok(Some(values))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,104 @@
+use graphannis::{
+    graph::AnnoKey,
+    update::{GraphUpdate, UpdateEvent},
+};
+use pretty_assertions::assert_eq;
+
+use crate::app::{job_executor::FgJob, util::example_generator};
+
+use super::frequencies;
+
+/// Adds part of speech and lemma annotations to some of the example token.
+fn add_pos_and_lemma(updates: &mut GraphUpdate) {
+    let annotations = [
+        (0, "VERB", "be"),
+        (1, "PRON", "this"),
+        (2, "NOUN", "example"),
+        (6, "PRON", "it"),
+        (7, "VERB", "appear"),
+        (9, "VERB", "be"),
+    ];
+    for (idx, pos, lemma) in annotations {
+        for (name, value) in [("pos", pos), ("lemma", lemma)] {
+            updates
+                .add_event(UpdateEvent::AddNodeLabel {
+                    node_name: format!("root/doc1#tok{idx}"),
+                    anno_ns: "default_ns".to_string(),
+                    anno_name: name.to_string(),
+                    anno_value: value.to_string(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn key(name: &str) -> AnnoKey {
+    AnnoKey {
+        ns: "default_ns".into(),
+        name: name.into(),
+    }
+}
+
+fn row(values: &[&str], count: usize) -> (Vec<String>, usize) {
+    (values.iter().map(|v| v.to_string()).collect(), count)
+}
+
+#[test]
+fn count_single_key() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_pos_and_lemma(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let table = frequencies(&graph, &[key("pos")], None, &FgJob::default()).unwrap();
+    assert_eq!(
+        vec![row(&["VERB"], 3), row(&["PRON"], 2), row(&["NOUN"], 1)],
+        table.rows
+    );
+    assert_eq!(6, table.total());
+}
+
+#[test]
+fn count_key_combination_in_subset() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_pos_and_lemma(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let mut table = frequencies(
+        &graph,
+        &[key("lemma"), key("pos")],
+        Some("pos=\"VERB\""),
+        &FgJob::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![row(&["be", "VERB"], 2), row(&["appear", "VERB"], 1)],
+        table.rows
+    );
+
+    table.sort_by(Some(0), true);
+    assert_eq!(
+        vec![row(&["appear", "VERB"], 1), row(&["be", "VERB"], 2)],
+        table.rows
+    );
+
+    let mut output = Vec::new();
+    table.write_csv(&mut output).unwrap();
+    assert_eq!(
+        "default_ns:lemma,default_ns:pos,count\nappear,VERB,1\nbe,VERB,2\n",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+#[test]
+fn cancel_counting() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_pos_and_lemma(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let job = FgJob::default();
+    job.cancel();
+    assert!(frequencies(&graph, &[key("pos")], None, &job).is_err());
+}
@@ -0,0 +1,130 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use graphannis::{model::AnnotationComponentType, AnnotationGraph};
+use graphannis_core::{annostorage::ValueSearch, graph::ANNIS_NS, types::Component};
+
+use super::corpus_structure::ordered_documents;
+
+/// A problem of a corpus that would otherwise only show up when one of its
+/// documents is opened in the editor.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum HealthProblem {
+    /// The component can not be loaded from the corpus directory.
+    ComponentNotLoadable { component: String, error: String },
+    /// Without the `PartOf` component, the documents can not be listed.
+    MissingPartOf,
+    /// Without the base `Ordering` component, the token of the documents
+    /// can not be shown in their order.
+    MissingOrdering,
+    /// There are documents, but none of them has any token.
+    NoToken,
+}
+
+impl HealthProblem {
+    /// What the user can do about the problem.
+    pub(crate) fn hint(&self) -> &'static str {
+        match self {
+            HealthProblem::ComponentNotLoadable { .. } => {
+                "The corpus files might be damaged. Delete the component in the component \
+                 browser or import the corpus again from its original file."
+            }
+            HealthProblem::MissingPartOf => {
+                "Import the corpus again from its original file. If the problem remains, \
+                 the file does not contain a corpus structure."
+            }
+            HealthProblem::MissingOrdering => {
+                "The documents can not be edited. Import the corpus again from its original \
+                 file."
+            }
+            HealthProblem::NoToken => "Open a document and add its text to start annotating.",
+        }
+    }
+}
+
+impl Display for HealthProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HealthProblem::ComponentNotLoadable { component, error } => {
+                write!(f, "Component {component} can not be loaded: {error}")
+            }
+            HealthProblem::MissingPartOf => write!(f, "The corpus structure is missing"),
+            HealthProblem::MissingOrdering => {
+                write!(f, "The order of the token is missing")
+            }
+            HealthProblem::NoToken => write!(f, "None of the documents has any token"),
+        }
+    }
+}
+
+/// Result of a quick check of a corpus after it has been selected.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct HealthReport {
+    pub(crate) number_of_documents: usize,
+    pub(crate) number_of_token: usize,
+    pub(crate) problems: Vec<HealthProblem>,
+}
+
+/// Load all components of the graph and check that the components needed by
+/// the editors exist. Components that can not be loaded are reported as
+/// problems instead of failing the check.
+pub(crate) fn check_corpus_health(graph: &mut AnnotationGraph) -> Result<HealthReport> {
+    let mut report = HealthReport::default();
+    for component in graph.get_all_components(None, None) {
+        if let Err(e) = graph.ensure_loaded(&component) {
+            report.problems.push(HealthProblem::ComponentNotLoadable {
+                component: format!(
+                    "{}/{}/{}",
+                    component.get_type(),
+                    component.layer,
+                    component.name
+                ),
+                error: format!("{e:#}"),
+            });
+        }
+    }
+
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    if graph.get_graphstorage_as_ref(&part_of).is_none() {
+        report.problems.push(HealthProblem::MissingPartOf);
+    }
+    report.number_of_documents = ordered_documents(graph)?.len();
+
+    let coverage: Vec<_> = graph
+        .get_all_components(Some(AnnotationComponentType::Coverage), None)
+        .into_iter()
+        .filter_map(|c| graph.get_graphstorage_as_ref(&c))
+        .collect();
+    for m in graph
+        .get_node_annos()
+        .exact_anno_search(Some(ANNIS_NS), "tok", ValueSearch::Any)
+    {
+        let node = m?.node;
+        let mut is_segmentation = false;
+        for gs in coverage.iter() {
+            if gs.has_outgoing_edges(node)? {
+                is_segmentation = true;
+                break;
+            }
+        }
+        if !is_segmentation {
+            report.number_of_token += 1;
+        }
+    }
+
+    let ordering = Component::new(
+        AnnotationComponentType::Ordering,
+        ANNIS_NS.into(),
+        "".into(),
+    );
+    if report.number_of_token > 1 && graph.get_graphstorage_as_ref(&ordering).is_none() {
+        report.problems.push(HealthProblem::MissingOrdering);
+    }
+    if report.number_of_token == 0 && report.number_of_documents > 0 {
+        report.problems.push(HealthProblem::NoToken);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests;
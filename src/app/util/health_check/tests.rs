@@ -0,0 +1,70 @@
+use graphannis::{
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{check_corpus_health, HealthProblem};
+
+#[test]
+fn healthy_corpus() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+
+    let report = check_corpus_health(&mut graph).unwrap();
+    assert_eq!(Vec::<HealthProblem>::new(), report.problems);
+    assert_eq!(1, report.number_of_documents);
+    // Segmentation nodes are not counted
+    assert_eq!(11, report.number_of_token);
+
+    // An empty corpus is healthy as well
+    let mut graph = example_generator::create_graph(&mut GraphUpdate::new());
+    let report = check_corpus_health(&mut graph).unwrap();
+    assert!(report.problems.is_empty());
+}
+
+#[test]
+fn documents_without_token() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+
+    let report = check_corpus_health(&mut graph).unwrap();
+    assert_eq!(vec![HealthProblem::NoToken], report.problems);
+}
+
+#[test]
+fn missing_components() {
+    let mut updates = GraphUpdate::new();
+    for (node_name, token) in [("t1", "a"), ("t2", "b")] {
+        updates
+            .add_event(UpdateEvent::AddNode {
+                node_name: node_name.to_string(),
+                node_type: "node".to_string(),
+            })
+            .unwrap();
+        updates
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: node_name.to_string(),
+                anno_ns: "annis".to_string(),
+                anno_name: "tok".to_string(),
+                anno_value: token.to_string(),
+            })
+            .unwrap();
+    }
+    let mut graph = AnnotationGraph::new(false).unwrap();
+    graph.apply_update(&mut updates, |_msg| {}).unwrap();
+
+    let report = check_corpus_health(&mut graph).unwrap();
+    assert_eq!(
+        vec![HealthProblem::MissingPartOf, HealthProblem::MissingOrdering],
+        report.problems
+    );
+    assert_eq!(0, report.number_of_documents);
+    assert_eq!(2, report.number_of_token);
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+};
+
+use anyhow::Result;
+use egui::Theme;
+use graphannis::graph::AnnoKey;
+use graphannis_core::graph::ANNIS_NS;
+
+use crate::{
+    api::token_helper::TOKEN_KEY,
+    app::{appearance::ColorScheme, widgets::Token},
+};
+
+/// Number of token shown in one line of the exported document.
+const TOKEN_PER_LINE: usize = 15;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1.5em; page-break-inside: avoid; }
+th { text-align: right; font-weight: normal; color: #666; padding-right: 1em; white-space: nowrap; }
+td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: center; }
+tr.token td { font-weight: bold; }
+td.empty { border: none; }
+@media print { body { margin: 0; } }
+"#;
+
+/// Escape the special characters of HTML and XML.
+pub(crate) fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn key_label(key: &AnnoKey) -> String {
+    if key.ns.is_empty() {
+        key.name.to_string()
+    } else {
+        format!("{}:{}", key.ns, key.name)
+    }
+}
+
+/// The annotation keys of the nodes that should be exported.
+fn visible_keys<'a>(
+    nodes: impl Iterator<Item = &'a Token>,
+    hidden_labels: &BTreeSet<AnnoKey>,
+) -> BTreeSet<AnnoKey> {
+    nodes
+        .flat_map(|t| t.labels.keys())
+        .filter(|k| k.ns != ANNIS_NS && !hidden_labels.contains(*k))
+        .cloned()
+        .collect()
+}
+
+/// Write a table row with one cell per span for the token range
+/// `first..=last`. Spans are cut at the borders of the range.
+fn write_span_row<W: Write>(
+    output: &mut W,
+    header: &str,
+    spans: &[&Token],
+    value: impl Fn(&Token) -> Option<String>,
+    (first, last): (usize, usize),
+    background: &str,
+) -> Result<()> {
+    write!(output, "<tr><th>{}</th>", escape(header))?;
+    let mut position = first;
+    for span in spans {
+        let start = span.start.max(first);
+        let end = span.end.min(last);
+        if start < position || start > end {
+            // Overlapping spans of the same layer can't be displayed in one row
+            continue;
+        }
+        for _ in position..start {
+            write!(output, "<td class=\"empty\"></td>")?;
+        }
+        write!(
+            output,
+            "<td colspan=\"{}\" style=\"background-color: {background}\">{}</td>",
+            end - start + 1,
+            escape(&value(*span).unwrap_or_default())
+        )?;
+        position = end + 1;
+    }
+    for _ in position..=last {
+        write!(output, "<td class=\"empty\"></td>")?;
+    }
+    writeln!(output, "</tr>")?;
+    Ok(())
+}
+
+/// Write the token and segmentation spans of a document as standalone HTML
+/// page, which can be printed as PDF by a web browser. Annotations with a key
+/// in `hidden_labels` are not included.
+pub(crate) fn write_document_html<W: Write>(
+    title: &str,
+    token: &[Token],
+    segmentations: &BTreeMap<String, Vec<Token>>,
+    hidden_labels: &BTreeSet<AnnoKey>,
+    mut output: W,
+) -> Result<()> {
+    writeln!(
+        output,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{}</h1>",
+        escape(title),
+        escape(title)
+    )?;
+
+    let token_keys = visible_keys(token.iter(), hidden_labels);
+    let mut layers = Vec::new();
+    for (layer_idx, (layer, spans)) in segmentations.iter().enumerate() {
+        let mut spans: Vec<&Token> = spans.iter().collect();
+        spans.sort_by_key(|t| (t.start, t.end));
+        let keys = visible_keys(spans.iter().copied(), hidden_labels);
+        let color = ColorScheme::Default.layer_color(Theme::Light, layer_idx);
+        let background = format!("rgba({}, {}, {}, 0.35)", color.r(), color.g(), color.b());
+        layers.push((layer, spans, keys, background));
+    }
+
+    for line in token.chunks(TOKEN_PER_LINE) {
+        let (Some(first), Some(last)) = (line.first(), line.last()) else {
+            continue;
+        };
+        let range = (first.start, last.end);
+        writeln!(output, "<table>")?;
+        write!(output, "<tr class=\"token\"><th>tok</th>")?;
+        for t in line {
+            let value = t.labels.get(&TOKEN_KEY).map(String::as_str);
+            write!(output, "<td>{}</td>", escape(value.unwrap_or_default()))?;
+        }
+        writeln!(output, "</tr>")?;
+        for key in token_keys.iter() {
+            write!(output, "<tr><th>{}</th>", escape(&key_label(key)))?;
+            for t in line {
+                let value = t.labels.get(key).map(String::as_str);
+                write!(output, "<td>{}</td>", escape(value.unwrap_or_default()))?;
+            }
+            writeln!(output, "</tr>")?;
+        }
+        for (layer, spans, keys, background) in layers.iter() {
+            let overlapping: Vec<&Token> = spans
+                .iter()
+                .filter(|s| s.end >= range.0 && s.start <= range.1)
+                .copied()
+                .collect();
+            write_span_row(
+                &mut output,
+                layer,
+                &overlapping,
+                |s| s.labels.get(&TOKEN_KEY).cloned(),
+                range,
+                background,
+            )?;
+            for key in keys.iter() {
+                write_span_row(
+                    &mut output,
+                    &format!("{layer}: {}", key_label(key)),
+                    &overlapping,
+                    |s| s.labels.get(key).cloned(),
+                    range,
+                    background,
+                )?;
+            }
+        }
+        writeln!(output, "</table>")?;
+    }
+    writeln!(output, "</body>\n</html>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
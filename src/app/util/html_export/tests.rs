@@ -0,0 +1,70 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use graphannis::graph::AnnoKey;
+use graphannis_core::graph::ANNIS_NS;
+
+use crate::{api::token_helper::TOKEN_KEY, app::widgets::Token};
+
+use super::write_document_html;
+
+fn create_token(name: &str, start: usize, end: usize, labels: &[(&AnnoKey, &str)]) -> Token {
+    Token {
+        node_name: name.to_string(),
+        start,
+        end,
+        labels: labels
+            .iter()
+            .map(|(k, v)| ((*k).clone(), v.to_string()))
+            .collect(),
+    }
+}
+
+#[test]
+fn export_token_and_spans() {
+    let pos = AnnoKey {
+        ns: "default_ns".into(),
+        name: "pos".into(),
+    };
+    let lemma = AnnoKey {
+        ns: "default_ns".into(),
+        name: "lemma".into(),
+    };
+    let node_name_key = AnnoKey {
+        ns: ANNIS_NS.into(),
+        name: "node_name".into(),
+    };
+    let token = vec![
+        create_token("doc#t1", 0, 0, &[(TOKEN_KEY.as_ref(), "a"), (&pos, "DET")]),
+        create_token(
+            "doc#t2",
+            1,
+            1,
+            &[(TOKEN_KEY.as_ref(), "<b>"), (&lemma, "b")],
+        ),
+        create_token(
+            "doc#t3",
+            2,
+            2,
+            &[(TOKEN_KEY.as_ref(), "c"), (&node_name_key, "t3")],
+        ),
+    ];
+    let mut segmentations = BTreeMap::new();
+    segmentations.insert(
+        "seg".to_string(),
+        vec![create_token("doc#s1", 1, 2, &[(TOKEN_KEY.as_ref(), "bc")])],
+    );
+    let hidden = BTreeSet::from([lemma.clone()]);
+
+    let mut output = Vec::new();
+    write_document_html("doc", &token, &segmentations, &hidden, &mut output).unwrap();
+    let html = String::from_utf8(output).unwrap();
+
+    assert!(html
+        .contains("<tr class=\"token\"><th>tok</th><td>a</td><td>&lt;b&gt;</td><td>c</td></tr>"));
+    assert!(html.contains("<tr><th>default_ns:pos</th><td>DET</td><td></td><td></td></tr>"));
+    // Hidden and internal annotations are not exported
+    assert!(!html.contains("default_ns:lemma"));
+    assert!(!html.contains("node_name"));
+    assert!(html.contains("<tr><th>seg</th><td class=\"empty\"></td><td colspan=\"2\""));
+    assert!(html.contains(">bc</td></tr>"));
+}
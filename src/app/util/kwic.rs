@@ -0,0 +1,173 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Write,
+};
+
+use anyhow::{Context, Result};
+use graphannis::{
+    aql,
+    graph::{AnnoKey, NodeID},
+    AnnotationGraph,
+};
+use graphannis_core::graph::{ANNIS_NS, NODE_NAME_KEY};
+use lazy_static::lazy_static;
+
+use crate::{
+    api::token_helper::{TokenHelper, TOKEN_KEY},
+    app::views::rename_annotation::key_label,
+};
+
+use super::corpus_structure::ordered_documents;
+
+lazy_static! {
+    static ref WHITESPACE_AFTER_KEY: AnnoKey = AnnoKey {
+        ns: ANNIS_NS.into(),
+        name: "tok-whitespace-after".into(),
+    };
+}
+
+/// Options of a keyword-in-context export.
+#[derive(Clone, Debug)]
+pub(crate) struct KwicOptions {
+    /// Number of token shown before and after the match.
+    pub(crate) context: usize,
+    /// Field delimiter, e.g. `b','` for CSV or `b'\t'` for TSV.
+    pub(crate) delimiter: u8,
+}
+
+impl Default for KwicOptions {
+    fn default() -> Self {
+        Self {
+            context: 5,
+            delimiter: b',',
+        }
+    }
+}
+
+struct KwicRow {
+    left: String,
+    keyword: String,
+    right: String,
+    document: NodeID,
+}
+
+/// Execute the AQL query and write each match with its left and right
+/// context as one row, followed by the metadata of the document the match
+/// belongs to. Returns the number of written matches.
+///
+/// All components of the graph must have been loaded.
+pub(crate) fn export_kwic<W: Write>(
+    graph: &AnnotationGraph,
+    query: &str,
+    options: &KwicOptions,
+    output: W,
+) -> Result<usize> {
+    let query = aql::parse(query, false)?;
+    let tok_helper = TokenHelper::new(graph)?;
+    let node_annos = graph.get_node_annos();
+
+    // Remember the document and position of each token
+    let mut documents = Vec::new();
+    let mut token_position: HashMap<NodeID, (usize, usize)> = HashMap::new();
+    for document in ordered_documents(graph)? {
+        let document_name = node_annos
+            .get_value_for_item(&document, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let token = tok_helper.get_ordered_token(&document_name, None)?;
+        for (idx, t) in token.iter().enumerate() {
+            token_position.insert(*t, (documents.len(), idx));
+        }
+        documents.push((document, token));
+    }
+
+    let text = |token: &[NodeID]| -> Result<String> {
+        let mut result = String::new();
+        for t in token {
+            if let Some(value) = node_annos.get_value_for_item(t, &TOKEN_KEY)? {
+                result.push_str(&value);
+            }
+            let whitespace = node_annos.get_value_for_item(t, &WHITESPACE_AFTER_KEY)?;
+            result.push_str(whitespace.as_deref().unwrap_or(" "));
+        }
+        Ok(result.trim().to_string())
+    };
+
+    let mut rows = Vec::new();
+    for m in aql::execute_query_on_graph(graph, &query, true, None)? {
+        let m = m?;
+        // The keyword ranges from the first to the last token covered by any matched node
+        let mut matched_range: Option<(usize, usize, usize)> = None;
+        for n in m.iter() {
+            let covered = if tok_helper.is_token(n.node)? {
+                vec![n.node]
+            } else {
+                tok_helper.covered_token(n.node)?
+            };
+            for t in covered {
+                if let Some((doc, pos)) = token_position.get(&t).copied() {
+                    matched_range = match matched_range {
+                        Some((d, start, end)) if d == doc => {
+                            Some((d, start.min(pos), end.max(pos)))
+                        }
+                        Some(existing) => Some(existing),
+                        None => Some((doc, pos, pos)),
+                    };
+                }
+            }
+        }
+        if let Some((doc, start, end)) = matched_range {
+            let (document, token) = &documents[doc];
+            let left_start = start.saturating_sub(options.context);
+            let right_end = (end + 1 + options.context).min(token.len());
+            rows.push(KwicRow {
+                left: text(&token[left_start..start])?,
+                keyword: text(&token[start..=end])?,
+                right: text(&token[(end + 1)..right_end])?,
+                document: *document,
+            });
+        }
+    }
+
+    // Use all metadata of the documents with matches as additional columns
+    let mut metadata_keys = BTreeSet::new();
+    for row in rows.iter() {
+        for anno in node_annos.get_annotations_for_item(&row.document)? {
+            if anno.key.ns != ANNIS_NS {
+                metadata_keys.insert(anno.key);
+            }
+        }
+    }
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(output);
+    let mut header = vec![
+        "left".to_string(),
+        "match".to_string(),
+        "right".to_string(),
+        "document".to_string(),
+    ];
+    header.extend(metadata_keys.iter().map(key_label));
+    writer.write_record(&header)?;
+    for row in rows.iter() {
+        let document_name = node_annos
+            .get_value_for_item(&row.document, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let mut record = vec![
+            row.left.clone(),
+            row.keyword.clone(),
+            row.right.clone(),
+            document_name.to_string(),
+        ];
+        for key in metadata_keys.iter() {
+            let value = node_annos.get_value_for_item(&row.document, key)?;
+            record.push(value.map(|v| v.to_string()).unwrap_or_default());
+        }
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,71 @@
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{export_kwic, KwicOptions};
+
+/// Adds the genre of the example document.
+fn add_genre(updates: &mut GraphUpdate) {
+    updates
+        .add_event(UpdateEvent::AddNodeLabel {
+            node_name: "root/doc1".to_string(),
+            anno_ns: "".to_string(),
+            anno_name: "genre".to_string(),
+            anno_value: "example".to_string(),
+        })
+        .unwrap();
+}
+
+#[test]
+fn export_token_match_with_context() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_genre(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let options = KwicOptions {
+        context: 2,
+        delimiter: b'\t',
+    };
+    let mut output = Vec::new();
+    let count = export_kwic(&graph, "tok=\"more\"", &options, &mut output).unwrap();
+    assert_eq!(1, count);
+    assert_eq!(
+        "left\tmatch\tright\tdocument\tgenre\nthis example\tmore\tcomplicated than\troot/doc1\texample\n",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+#[test]
+fn export_match_at_document_end() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_genre(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let mut output = Vec::new();
+    let count = export_kwic(
+        &graph,
+        "tok=\"be\" . tok",
+        &KwicOptions::default(),
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(1, count);
+    assert_eq!(
+        "left,match,right,document,genre\ncomplicated than it appears to,be ?,,root/doc1,example\n",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+#[test]
+fn invalid_query() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_genre(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let mut output = Vec::new();
+    assert!(export_kwic(&graph, "tok=", &KwicOptions::default(), &mut output).is_err());
+}
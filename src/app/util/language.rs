@@ -0,0 +1,299 @@
+use anyhow::{Context, Result};
+use graphannis::{
+    graph::AnnoKey,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::graph::NODE_NAME_KEY;
+use lazy_static::lazy_static;
+
+use super::{corpus_structure::ordered_documents, tokenizer::TokenizerKind};
+use crate::api::token_helper::{TokenHelper, TOKEN_KEY};
+
+lazy_static! {
+    /// Document metadata with the ISO 639-1 code of the language.
+    pub(crate) static ref LANGUAGE_KEY: AnnoKey = AnnoKey {
+        ns: "".into(),
+        name: "language".into(),
+    };
+}
+
+/// Number of base token of a document used to detect its language.
+const SAMPLE_SIZE: usize = 2000;
+/// Minimal number of function words needed to detect a language written in
+/// the Latin script.
+const MIN_FUNCTION_WORDS: usize = 3;
+
+/// Code, name and whether the language is written from right to left.
+const LANGUAGES: &[(&str, &str, bool)] = &[
+    ("ar", "Arabic", true),
+    ("de", "German", false),
+    ("el", "Greek", false),
+    ("en", "English", false),
+    ("es", "Spanish", false),
+    ("fa", "Persian", true),
+    ("fr", "French", false),
+    ("he", "Hebrew", true),
+    ("it", "Italian", false),
+    ("ja", "Japanese", false),
+    ("nl", "Dutch", false),
+    ("pt", "Portuguese", false),
+    ("ru", "Russian", false),
+    ("zh", "Chinese", false),
+];
+
+/// Frequent words that distinguish languages written in the Latin script.
+const FUNCTION_WORDS: &[(&str, &[&str])] = &[
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ich", "zu", "den", "mit", "sich", "ein",
+            "eine", "auf", "dem", "von", "auch", "es", "war",
+        ],
+    ),
+    (
+        "en",
+        &[
+            "the", "and", "of", "to", "is", "in", "that", "it", "was", "for", "with", "he", "she",
+            "you", "this", "are", "not", "be", "have",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "y", "es", "un", "una", "que", "de", "en", "no", "por",
+            "con", "para", "se", "lo", "del", "como",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "et", "est", "un", "une", "des", "du", "que", "qui", "dans", "pas",
+            "pour", "ne", "je", "il", "avec", "sur",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "la", "e", "di", "che", "è", "un", "una", "per", "non", "con", "del", "della",
+            "sono", "gli", "le", "si", "da", "ma",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "is", "dat", "niet", "ik", "te", "zijn", "op", "met",
+            "voor", "je", "er", "maar", "ook", "die",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "a", "os", "as", "e", "de", "que", "um", "uma", "não", "em", "do", "da", "para",
+            "com", "é", "se", "no", "na",
+        ],
+    ),
+];
+
+/// A language that can be detected from the token text of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Language {
+    /// The ISO 639-1 code, which is stored as document metadata.
+    pub(crate) code: &'static str,
+    pub(crate) name: &'static str,
+    pub(crate) right_to_left: bool,
+}
+
+impl Language {
+    pub(crate) fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_lowercase();
+        LANGUAGES
+            .iter()
+            .find(|(c, _, _)| *c == code)
+            .map(|(code, name, right_to_left)| Language {
+                code,
+                name,
+                right_to_left: *right_to_left,
+            })
+    }
+
+    /// The recommended tokenizer, if there is a preset for the language.
+    pub(crate) fn tokenizer(&self) -> Option<TokenizerKind> {
+        let preset_name = match self.code {
+            "en" => "English",
+            "de" => "German",
+            "fr" => "French",
+            "zh" | "ja" => "Chinese/Japanese",
+            _ => return None,
+        };
+        TokenizerKind::language_presets()
+            .into_iter()
+            .find(|(name, _)| *name == preset_name)
+            .map(|(_, tokenizer)| tokenizer)
+    }
+}
+
+/// The tokenizer of a document: the one configured for its corpus, the preset
+/// for the language of the document or the default tokenizer.
+pub(crate) fn document_tokenizer(
+    corpus_tokenizer: Option<&TokenizerKind>,
+    language: Option<Language>,
+    default: &TokenizerKind,
+) -> TokenizerKind {
+    corpus_tokenizer
+        .cloned()
+        .or_else(|| language.and_then(|l| l.tokenizer()))
+        .unwrap_or_else(|| default.clone())
+}
+
+#[derive(Default)]
+struct ScriptCounts {
+    letters: usize,
+    arabic: usize,
+    persian: usize,
+    hebrew: usize,
+    cyrillic: usize,
+    greek: usize,
+    kana: usize,
+    han: usize,
+}
+
+impl ScriptCounts {
+    fn new(text: &str) -> Self {
+        let mut result = ScriptCounts::default();
+        for c in text.chars().filter(|c| c.is_alphabetic()) {
+            result.letters += 1;
+            match c {
+                // Letters that are used in Persian, but not in Arabic
+                'پ' | 'چ' | 'ژ' | 'گ' => {
+                    result.arabic += 1;
+                    result.persian += 1;
+                }
+                '\u{0600}'..='\u{06FF}'
+                | '\u{0750}'..='\u{077F}'
+                | '\u{FB50}'..='\u{FDFF}'
+                | '\u{FE70}'..='\u{FEFF}' => result.arabic += 1,
+                '\u{0590}'..='\u{05FF}' => result.hebrew += 1,
+                '\u{0400}'..='\u{04FF}' => result.cyrillic += 1,
+                '\u{0370}'..='\u{03FF}' => result.greek += 1,
+                '\u{3040}'..='\u{30FF}' => result.kana += 1,
+                '\u{4E00}'..='\u{9FFF}' => result.han += 1,
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// The language of the script used by the majority of the letters, if
+    /// it is not the Latin script.
+    fn language_code(&self) -> Option<&'static str> {
+        let majority = |count: usize| count * 2 > self.letters;
+        if majority(self.arabic) {
+            Some(if self.persian > 0 { "fa" } else { "ar" })
+        } else if majority(self.hebrew) {
+            Some("he")
+        } else if majority(self.cyrillic) {
+            Some("ru")
+        } else if majority(self.greek) {
+            Some("el")
+        } else if majority(self.kana + self.han) {
+            // Japanese mixes Kanji and Kana, Chinese only uses Han characters
+            Some(if self.kana > 0 { "ja" } else { "zh" })
+        } else {
+            None
+        }
+    }
+}
+
+/// Guess the language of the text by its script or, for the Latin script, by
+/// the number of frequent function words. Returns `None` if the text is too
+/// short or the languages can not be distinguished.
+pub(crate) fn detect_language(text: &str) -> Option<Language> {
+    let scripts = ScriptCounts::new(text);
+    if scripts.letters == 0 {
+        return None;
+    }
+    if let Some(code) = scripts.language_code() {
+        return Language::from_code(code);
+    }
+
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    let mut scores: Vec<(&str, usize)> = FUNCTION_WORDS
+        .iter()
+        .map(|(code, function_words)| {
+            let count = words
+                .iter()
+                .filter(|w| function_words.contains(&w.as_str()))
+                .count();
+            (*code, count)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+    match scores.as_slice() {
+        [(code, best), (_, second), ..] if *best >= MIN_FUNCTION_WORDS && best > second => {
+            Language::from_code(code)
+        }
+        _ => None,
+    }
+}
+
+/// Detect the language of all documents that do not have a language
+/// annotation yet. Returns the node name and language of each document
+/// whose language could be detected.
+pub(crate) fn detect_document_languages<F>(
+    graph: &AnnotationGraph,
+    progress: F,
+) -> Result<Vec<(String, Language)>>
+where
+    F: Fn(usize, usize),
+{
+    let tok_helper = TokenHelper::new(graph)?;
+    let node_annos = graph.get_node_annos();
+    let documents = ordered_documents(graph)?;
+    let mut result = Vec::new();
+    for (idx, document) in documents.iter().enumerate() {
+        progress(idx, documents.len());
+        if node_annos.has_value_for_item(document, &LANGUAGE_KEY)? {
+            continue;
+        }
+        let document_name = node_annos
+            .get_value_for_item(document, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let mut text = String::new();
+        for t in tok_helper
+            .get_ordered_token(&document_name, None)?
+            .iter()
+            .take(SAMPLE_SIZE)
+        {
+            if let Some(value) = node_annos.get_value_for_item(t, &TOKEN_KEY)? {
+                text.push_str(&value);
+                text.push(' ');
+            }
+        }
+        if let Some(language) = detect_language(&text) {
+            result.push((document_name.to_string(), language));
+        }
+    }
+    Ok(result)
+}
+
+/// The update that stores the detected languages as document metadata.
+pub(crate) fn language_update(languages: &[(String, Language)]) -> Result<GraphUpdate> {
+    let mut update = GraphUpdate::new();
+    for (document_name, language) in languages {
+        update.add_event(UpdateEvent::AddNodeLabel {
+            node_name: document_name.clone(),
+            anno_ns: LANGUAGE_KEY.ns.to_string(),
+            anno_name: LANGUAGE_KEY.name.to_string(),
+            anno_value: language.code.to_string(),
+        })?;
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,96 @@
+use graphannis::update::GraphUpdate;
+use pretty_assertions::assert_eq;
+
+use crate::app::util::{example_generator, tokenizer::TokenizerKind};
+
+use super::{
+    detect_document_languages, detect_language, document_tokenizer, language_update, Language,
+};
+
+fn code(text: &str) -> Option<&'static str> {
+    detect_language(text).map(|l| l.code)
+}
+
+#[test]
+fn detect_latin_languages_by_function_words() {
+    assert_eq!(
+        Some("en"),
+        code("The cat is sleeping in the sun and it is not hungry.")
+    );
+    assert_eq!(
+        Some("de"),
+        code("Die Katze schläft in der Sonne und sie ist nicht hungrig.")
+    );
+    assert_eq!(
+        Some("fr"),
+        code("Le chat dort dans le jardin et il ne veut pas manger.")
+    );
+    // Too few function words
+    assert_eq!(None, code("Annotation"));
+    assert_eq!(None, code("1 2 3 ..."));
+}
+
+#[test]
+fn detect_languages_by_script() {
+    let arabic = detect_language("القطة نائمة في الشمس").unwrap();
+    assert_eq!("ar", arabic.code);
+    assert!(arabic.right_to_left);
+    assert_eq!(Some("he"), code("החתול ישן בשמש"));
+    assert_eq!(Some("ru"), code("Кошка спит на солнце"));
+    assert_eq!(Some("ja"), code("猫は太陽の下で寝ています"));
+    assert_eq!(Some("zh"), code("猫在阳光下睡觉"));
+}
+
+#[test]
+fn language_presets() {
+    let english = Language::from_code("EN").unwrap();
+    assert_eq!("English", english.name);
+    assert!(!english.right_to_left);
+    assert!(matches!(english.tokenizer(), Some(TokenizerKind::Regex(_))));
+    assert_eq!(
+        Some(TokenizerKind::UnicodeWords),
+        Language::from_code("zh").unwrap().tokenizer()
+    );
+    assert_eq!(None, Language::from_code("ar").unwrap().tokenizer());
+    assert_eq!(None, Language::from_code("xx"));
+
+    // A tokenizer configured for the corpus is used instead of the preset
+    let chinese = Language::from_code("zh");
+    assert_eq!(
+        TokenizerKind::UnicodeWords,
+        document_tokenizer(None, chinese, &TokenizerKind::Punctuation)
+    );
+    assert_eq!(
+        TokenizerKind::Whitespace,
+        document_tokenizer(
+            Some(&TokenizerKind::Whitespace),
+            chinese,
+            &TokenizerKind::Punctuation
+        )
+    );
+    assert_eq!(
+        TokenizerKind::Punctuation,
+        document_tokenizer(None, None, &TokenizerKind::Punctuation)
+    );
+}
+
+#[test]
+fn detect_and_store_document_language() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let mut graph = example_generator::create_graph(&mut updates);
+
+    let languages = detect_document_languages(&graph, |_, _| {}).unwrap();
+    assert_eq!(
+        vec![("root/doc1".to_string(), Language::from_code("en").unwrap())],
+        languages
+    );
+
+    // Documents with a language annotation are skipped
+    let mut update = language_update(&languages).unwrap();
+    graph.apply_update(&mut update, |_msg| {}).unwrap();
+    assert!(detect_document_languages(&graph, |_, _| {})
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,283 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdout, Command, Stdio},
+    sync::OnceLock,
+};
+
+use anyhow::{bail, Context, Result};
+use egui::ColorImage;
+use graphannis::{model::AnnotationComponentType, AnnotationGraph};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::ANNIS_NS,
+    types::{Component, NodeID},
+};
+
+#[cfg(test)]
+mod tests;
+
+const AUDIO_EXTENSIONS: &[&str] = &["wav"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "webm", "ogv", "mov", "mkv"];
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| extensions.contains(&ext.as_str()))
+}
+
+pub(crate) fn is_audio(path: &Path) -> bool {
+    has_extension(path, AUDIO_EXTENSIONS)
+}
+
+pub(crate) fn is_video(path: &Path) -> bool {
+    has_extension(path, VIDEO_EXTENSIONS)
+}
+
+/// Get the values of all `annis::file` annotations of the file nodes that
+/// are part of the given document.
+pub(crate) fn linked_files(graph: &AnnotationGraph, document: NodeID) -> Result<Vec<String>> {
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let mut result = Vec::new();
+    if let Some(part_of) = graph.get_graphstorage_as_ref(&part_of) {
+        for m in graph
+            .get_node_annos()
+            .exact_anno_search(Some(ANNIS_NS), "file", ValueSearch::Any)
+        {
+            let m = m?;
+            if part_of.is_connected(m.node, document, 1, std::ops::Bound::Unbounded)? {
+                if let Some(file) = graph
+                    .get_node_annos()
+                    .get_value_for_item(&m.node, &m.anno_key)?
+                {
+                    result.push(file.to_string());
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Open the file with the default application of the operating system.
+pub(crate) fn open_externally(path: &Path) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(path).spawn()?;
+    Ok(())
+}
+
+/// Find the linked file on disk. Relative paths are resolved against the
+/// corpus location and its `files` sub-directory.
+pub(crate) fn resolve_linked_file(file: &str, corpus_location: &Path) -> Option<PathBuf> {
+    let path = PathBuf::from(file);
+    let candidates = if path.is_absolute() {
+        vec![path]
+    } else {
+        vec![
+            corpus_location.join(&path),
+            corpus_location.join("files").join(&path),
+        ]
+    };
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+/// Decodes and plays the linked media files for the media panel of the
+/// document editor.
+pub(crate) trait MediaBackend: Send + Sync {
+    /// Decode the frames of the video with the given frame rate, starting
+    /// with the frame at the position (in seconds).
+    fn decode_video(
+        &self,
+        video: &Path,
+        position: f64,
+        frame_rate: f64,
+    ) -> Result<Box<dyn FrameStream>>;
+
+    /// Play the audio of the media file from the position (in seconds) until
+    /// the returned playback is dropped.
+    fn play_audio(&self, media: &Path, position: f64) -> Result<AudioPlayback>;
+}
+
+/// The frames of a video in the order they are shown.
+pub(crate) trait FrameStream: Send {
+    /// The next frame or `None` at the end of the video.
+    fn next_frame(&mut self) -> Result<Option<ColorImage>>;
+}
+
+/// Audio played by an external process, which is stopped when the playback
+/// is dropped.
+#[derive(Default)]
+pub(crate) struct AudioPlayback {
+    process: Option<Child>,
+}
+
+impl Drop for AudioPlayback {
+    fn drop(&mut self) {
+        if let Some(process) = &mut self.process {
+            stop_process(process);
+        }
+    }
+}
+
+/// Decodes videos with `ffmpeg` and plays audio with `ffplay`.
+pub(crate) struct FfmpegBackend;
+
+/// Frames are scaled to this width, which is enough for the video panel.
+const FFMPEG_FRAME_WIDTH: u32 = 640;
+/// Frames with a larger width or height are rejected.
+const MAX_FRAME_SIZE: usize = 8192;
+
+impl FfmpegBackend {
+    /// The backend, if `ffmpeg` is installed and can be executed.
+    pub(crate) fn detect() -> Option<Self> {
+        static AVAILABLE: OnceLock<bool> = OnceLock::new();
+        let available = *AVAILABLE.get_or_init(|| {
+            Command::new("ffmpeg")
+                .arg("-version")
+                .output()
+                .is_ok_and(|output| output.status.success())
+        });
+        available.then_some(Self)
+    }
+}
+
+impl MediaBackend for FfmpegBackend {
+    fn decode_video(
+        &self,
+        video: &Path,
+        position: f64,
+        frame_rate: f64,
+    ) -> Result<Box<dyn FrameStream>> {
+        // The frames are written as a sequence of PPM images, which contain
+        // their size and can be read without an additional decoder
+        let mut process = Command::new("ffmpeg")
+            .args([
+                "-loglevel",
+                "error",
+                "-ss",
+                &format!("{:.3}", position.max(0.0)),
+            ])
+            .arg("-i")
+            .arg(video)
+            .args([
+                "-vf",
+                &format!("fps={frame_rate},scale={FFMPEG_FRAME_WIDTH}:-2"),
+                "-f",
+                "image2pipe",
+                "-vcodec",
+                "ppm",
+                "-",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Could not start ffmpeg")?;
+        let output = process.stdout.take().context("Missing output of ffmpeg")?;
+        Ok(Box::new(FfmpegFrames {
+            process,
+            output: BufReader::new(output),
+        }))
+    }
+
+    fn play_audio(&self, media: &Path, position: f64) -> Result<AudioPlayback> {
+        let process = Command::new("ffplay")
+            .args([
+                "-nodisp",
+                "-autoexit",
+                "-loglevel",
+                "error",
+                "-ss",
+                &format!("{:.3}", position.max(0.0)),
+            ])
+            .arg(media)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Could not start ffplay")?;
+        Ok(AudioPlayback {
+            process: Some(process),
+        })
+    }
+}
+
+/// The frames of a running `ffmpeg` process, which is stopped when the
+/// frames are dropped.
+struct FfmpegFrames {
+    process: Child,
+    output: BufReader<ChildStdout>,
+}
+
+impl FrameStream for FfmpegFrames {
+    fn next_frame(&mut self) -> Result<Option<ColorImage>> {
+        let frame = read_ppm(&mut self.output)?;
+        if frame.is_none() {
+            let status = self.process.wait()?;
+            if !status.success() {
+                let mut message = String::new();
+                if let Some(stderr) = &mut self.process.stderr {
+                    stderr.read_to_string(&mut message)?;
+                }
+                bail!("Could not decode video: {}", message.trim());
+            }
+        }
+        Ok(frame)
+    }
+}
+
+impl Drop for FfmpegFrames {
+    fn drop(&mut self) {
+        stop_process(&mut self.process);
+    }
+}
+
+fn stop_process(process: &mut Child) {
+    // The process might already have exited at the end of the media
+    if let Err(e) = process.kill().and_then(|_| process.wait()) {
+        log::debug!("Could not stop media process: {e}");
+    }
+}
+
+/// Read the next binary PPM image or `None` at the end of the input.
+fn read_ppm<R: BufRead>(input: &mut R) -> Result<Option<ColorImage>> {
+    if input.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    let magic = read_ppm_field(input)?;
+    if magic != "P6" {
+        bail!("Unsupported image format {magic}");
+    }
+    let width: usize = read_ppm_field(input)?.parse()?;
+    let height: usize = read_ppm_field(input)?.parse()?;
+    let max_value = read_ppm_field(input)?;
+    if width > MAX_FRAME_SIZE || height > MAX_FRAME_SIZE || max_value != "255" {
+        bail!("Unsupported frame of size {width}x{height} with maximum value {max_value}");
+    }
+    let mut rgb = vec![0; width * height * 3];
+    input.read_exact(&mut rgb)?;
+    Ok(Some(ColorImage::from_rgb([width, height], &rgb)))
+}
+
+/// A field of the PPM header, which ends with a single whitespace character.
+fn read_ppm_field<R: BufRead>(input: &mut R) -> Result<String> {
+    let mut field = Vec::new();
+    loop {
+        let mut byte = [0];
+        input.read_exact(&mut byte)?;
+        if !byte[0].is_ascii_whitespace() {
+            field.push(byte[0]);
+        } else if !field.is_empty() {
+            return Ok(String::from_utf8_lossy(&field).to_string());
+        }
+    }
+}
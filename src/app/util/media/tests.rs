@@ -0,0 +1,29 @@
+use egui::Color32;
+use pretty_assertions::assert_eq;
+
+use super::read_ppm;
+
+#[test]
+fn read_stream_of_ppm_images() {
+    let mut input = Vec::new();
+    input.extend_from_slice(b"P6\n2 1\n255\n");
+    input.extend_from_slice(&[255, 0, 0, 0, 0, 255]);
+    input.extend_from_slice(b"P6 1 1 255 ");
+    input.extend_from_slice(&[0, 255, 0]);
+    let mut input = &input[..];
+
+    let first = read_ppm(&mut input).unwrap().unwrap();
+    assert_eq!([2, 1], first.size);
+    assert_eq!(vec![Color32::RED, Color32::BLUE], first.pixels);
+    let second = read_ppm(&mut input).unwrap().unwrap();
+    assert_eq!(vec![Color32::GREEN], second.pixels);
+    assert!(read_ppm(&mut input).unwrap().is_none());
+}
+
+#[test]
+fn reject_invalid_ppm_images() {
+    assert!(read_ppm(&mut &b"P3\n1 1\n255\n0 0 0"[..]).is_err());
+    assert!(read_ppm(&mut &b"P6\n100000 100000\n255\n"[..]).is_err());
+    // The pixels of the frame are incomplete
+    assert!(read_ppm(&mut &b"P6\n2 2\n255\n\0\0\0"[..]).is_err());
+}
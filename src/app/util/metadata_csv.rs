@@ -0,0 +1,102 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Read, Write},
+    iter::once,
+};
+
+use anyhow::{bail, Context, Result};
+use graphannis::{
+    graph::AnnoKey,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::graph::{ANNIS_NS, NODE_NAME_KEY};
+
+use super::corpus_structure::ordered_documents;
+use crate::app::views::rename_annotation::{key_label, parse_key};
+
+/// Name of the first column, which identifies the documents.
+pub(crate) const NODE_NAME_COLUMN: &str = "node_name";
+
+/// Write the metadata of all documents as CSV with one row per document and
+/// one column per annotation key. Annotations in the ANNIS namespace are
+/// omitted. Returns the number of documents.
+pub(crate) fn write_metadata_csv<W: Write>(graph: &AnnotationGraph, output: W) -> Result<usize> {
+    let node_annos = graph.get_node_annos();
+    let mut keys = BTreeSet::new();
+    let mut rows = Vec::new();
+    for doc in ordered_documents(graph)? {
+        let node_name = node_annos
+            .get_value_for_item(&doc, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let mut values = BTreeMap::new();
+        for anno in node_annos.get_annotations_for_item(&doc)? {
+            if anno.key.ns != ANNIS_NS {
+                keys.insert(anno.key.clone());
+                values.insert(anno.key, anno.val.to_string());
+            }
+        }
+        rows.push((node_name.to_string(), values));
+    }
+
+    let mut writer = csv::Writer::from_writer(output);
+    writer.write_record(once(NODE_NAME_COLUMN.to_string()).chain(keys.iter().map(key_label)))?;
+    for (node_name, values) in rows.iter() {
+        let values = keys
+            .iter()
+            .map(|k| values.get(k).map(String::as_str).unwrap_or_default());
+        writer.write_record(once(node_name.as_str()).chain(values))?;
+    }
+    writer.flush()?;
+    Ok(rows.len())
+}
+
+/// Create the update events that change the metadata of the documents to the
+/// values of a CSV file in the format of [`write_metadata_csv`]. Empty cells
+/// delete the annotation, and annotations without a column are not changed.
+pub(crate) fn metadata_updates<R: Read>(graph: &AnnotationGraph, input: R) -> Result<GraphUpdate> {
+    let mut reader = csv::Reader::from_reader(input);
+    let header = reader.headers()?.clone();
+    if header.get(0).map(str::trim) != Some(NODE_NAME_COLUMN) {
+        bail!("The first column must be \"{NODE_NAME_COLUMN}\"");
+    }
+    let keys: Vec<AnnoKey> = header.iter().skip(1).map(parse_key).collect();
+    if let Some(key) = keys.iter().find(|k| k.name.is_empty() || k.ns == ANNIS_NS) {
+        bail!("Column \"{}\" can not be imported", key_label(key));
+    }
+
+    let node_annos = graph.get_node_annos();
+    let mut update = GraphUpdate::new();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record?;
+        // The header is the first line
+        let line = idx + 2;
+        let node_name = record.get(0).unwrap_or_default().trim();
+        let node = node_annos
+            .get_node_id_from_name(node_name)?
+            .with_context(|| format!("Unknown document \"{node_name}\" in line {line}"))?;
+        for (key, value) in keys.iter().zip(record.iter().skip(1)) {
+            let current_value = node_annos.get_value_for_item(&node, key)?;
+            if value.is_empty() {
+                if current_value.is_some() {
+                    update.add_event(UpdateEvent::DeleteNodeLabel {
+                        node_name: node_name.to_string(),
+                        anno_ns: key.ns.to_string(),
+                        anno_name: key.name.to_string(),
+                    })?;
+                }
+            } else if current_value.as_deref() != Some(value) {
+                update.add_event(UpdateEvent::AddNodeLabel {
+                    node_name: node_name.to_string(),
+                    anno_ns: key.ns.to_string(),
+                    anno_name: key.name.to_string(),
+                    anno_value: value.to_string(),
+                })?;
+            }
+        }
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use graphannis::{
+    graph::AnnoKey,
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{metadata_updates, write_metadata_csv};
+
+/// Adds the metadata of the example document.
+fn add_metadata(updates: &mut GraphUpdate) {
+    for (name, value) in [("author", "Jane Doe"), ("title", "An example, with comma")] {
+        updates
+            .add_event(UpdateEvent::AddNodeLabel {
+                node_name: "root/doc1".to_string(),
+                anno_ns: "meta".to_string(),
+                anno_name: name.to_string(),
+                anno_value: value.to_string(),
+            })
+            .unwrap();
+    }
+}
+
+fn value(graph: &AnnotationGraph, name: &str) -> Option<String> {
+    let node_annos = graph.get_node_annos();
+    let doc = node_annos.get_node_id_from_name("root/doc1").unwrap()?;
+    let key = AnnoKey {
+        ns: "meta".into(),
+        name: name.into(),
+    };
+    node_annos
+        .get_value_for_item(&doc, &key)
+        .unwrap()
+        .map(|v| v.to_string())
+}
+
+#[test]
+fn export_metadata_of_documents() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    add_metadata(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let mut output = Vec::new();
+    assert_eq!(1, write_metadata_csv(&graph, &mut output).unwrap());
+    assert_eq!(
+        "node_name,meta:author,meta:title\nroot/doc1,Jane Doe,\"An example, with comma\"\n",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+#[test]
+fn import_edited_metadata() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    add_metadata(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+    let csv = "node_name,meta:author,meta:title,meta:year\nroot/doc1,Jane Doe,,2025\n";
+    let mut update = metadata_updates(&graph, csv.as_bytes()).unwrap();
+    // Unchanged values are not part of the update
+    assert_eq!(2, update.len().unwrap());
+    graph.apply_update(&mut update, |_| {}).unwrap();
+
+    assert_eq!(Some("Jane Doe".to_string()), value(&graph, "author"));
+    assert_eq!(None, value(&graph, "title"));
+    assert_eq!(Some("2025".to_string()), value(&graph, "year"));
+}
+
+#[test]
+fn reject_invalid_metadata_files() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    add_metadata(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    assert!(metadata_updates(&graph, "author\nJane Doe\n".as_bytes()).is_err());
+    assert!(metadata_updates(&graph, "node_name,annis:doc\nroot/doc1,other\n".as_bytes()).is_err());
+    let error =
+        metadata_updates(&graph, "node_name,meta:author\nroot/doc2,Jane\n".as_bytes()).unwrap_err();
+    assert_eq!(
+        "Unknown document \"root/doc2\" in line 2",
+        error.to_string()
+    );
+}
@@ -0,0 +1,98 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+};
+
+use anyhow::Result;
+use graphannis::{graph::NodeID, model::AnnotationComponentType, AnnotationGraph};
+
+use crate::{
+    api::token_helper::{TokenHelper, TOKEN_KEY},
+    app::widgets::Token,
+};
+
+/// Load all spans covering the given ordered base token that are not part of
+/// a segmentation layer. The start and end of the spans are the indexes of
+/// the first and last covered base token.
+pub(crate) fn load_spans(graph: &AnnotationGraph, token: &[NodeID]) -> Result<Vec<Token>> {
+    let node_annos = graph.get_node_annos();
+    let tok_helper = TokenHelper::new(graph)?;
+    let token_to_index: HashMap<NodeID, usize> =
+        token.iter().enumerate().map(|(idx, t)| (*t, idx)).collect();
+
+    let mut span_ids = BTreeSet::new();
+    for c in graph.get_all_components(Some(AnnotationComponentType::Coverage), None) {
+        if let Some(gs) = graph.get_graphstorage_as_ref(&c) {
+            for t in token {
+                for span in gs.get_ingoing_edges(*t) {
+                    let span = span?;
+                    // Segmentation nodes have a token value
+                    if !node_annos.has_value_for_item(&span, &TOKEN_KEY)? {
+                        span_ids.insert(span);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(span_ids.len());
+    for span in span_ids {
+        let covered: Vec<usize> = tok_helper
+            .covered_token(span)?
+            .iter()
+            .filter_map(|t| token_to_index.get(t))
+            .copied()
+            .collect();
+        if let (Some(start), Some(end)) = (covered.iter().min(), covered.iter().max()) {
+            result.push(Token::from_graph(span, *start, *end, graph)?);
+        }
+    }
+    result.sort_by_key(|t| (t.start, Reverse(t.end)));
+    Ok(result)
+}
+
+/// The number of spans each span is nested in, given as ranges of covered
+/// token indexes. Of two spans with the same range, the first one is the
+/// outer one.
+pub(crate) fn nesting_depths(ranges: &[(usize, usize)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by_key(|idx| (ranges[*idx].0, Reverse(ranges[*idx].1), *idx));
+
+    let mut result = vec![0; ranges.len()];
+    // End of the spans that contain the current position
+    let mut open_spans: Vec<usize> = Vec::new();
+    for idx in order {
+        let (start, end) = ranges[idx];
+        while open_spans.last().is_some_and(|open_end| *open_end < start) {
+            open_spans.pop();
+        }
+        result[idx] = open_spans.len();
+        open_spans.push(end);
+    }
+    result
+}
+
+/// Checks that a new span can be added to the existing ones of the same
+/// annotation key without partially overlapping any of them.
+pub(crate) fn validate_nesting(
+    existing: &[(usize, usize)],
+    new_span: (usize, usize),
+) -> Result<(), String> {
+    let (start, end) = new_span;
+    for (other_start, other_end) in existing.iter().copied() {
+        if (other_start, other_end) == new_span {
+            return Err("A span with the same range already exists".to_string());
+        }
+        let crosses = (other_start < start && start <= other_end && other_end < end)
+            || (start < other_start && other_start <= end && end < other_end);
+        if crosses {
+            return Err(format!(
+                "The span would partially overlap the span from token {other_start} to {other_end}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,58 @@
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use pretty_assertions::assert_eq;
+
+use crate::{api::token_helper::TokenHelper, app::util::example_generator};
+
+use super::{load_spans, nesting_depths, validate_nesting};
+
+#[test]
+fn depth_of_nested_spans() {
+    // An entity with two nested entities, one of them containing another one
+    let ranges = [(0, 5), (1, 2), (1, 1), (4, 5), (7, 8)];
+    assert_eq!(vec![0, 1, 2, 1, 0], nesting_depths(&ranges));
+    // Spans with the same range are stacked in their given order
+    assert_eq!(vec![0, 1], nesting_depths(&[(3, 4), (3, 4)]));
+}
+
+#[test]
+fn reject_partially_overlapping_spans() {
+    let existing = [(0, 5), (1, 2)];
+    assert_eq!(Ok(()), validate_nesting(&existing, (1, 1)));
+    assert_eq!(Ok(()), validate_nesting(&existing, (0, 7)));
+    assert_eq!(Ok(()), validate_nesting(&existing, (6, 7)));
+    assert!(validate_nesting(&existing, (2, 3)).is_err());
+    assert!(validate_nesting(&existing, (4, 6)).is_err());
+    assert!(validate_nesting(&existing, (1, 2)).is_err());
+}
+
+#[test]
+fn load_spans_without_segmentation_nodes() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    example_generator::create_segmentation(&mut updates);
+    example_generator::make_span(
+        &mut updates,
+        "root/doc1#entity1",
+        &["root/doc1#tok2", "root/doc1#tok3", "root/doc1#tok4"],
+        true,
+    );
+    updates
+        .add_event(UpdateEvent::AddNodeLabel {
+            node_name: "root/doc1#entity1".to_string(),
+            anno_ns: "".to_string(),
+            anno_name: "ner".to_string(),
+            anno_value: "ORG".to_string(),
+        })
+        .unwrap();
+    let graph = example_generator::create_graph(&mut updates);
+
+    let token = TokenHelper::new(&graph)
+        .unwrap()
+        .get_ordered_token("root/doc1", None)
+        .unwrap();
+    let spans = load_spans(&graph, &token).unwrap();
+    assert_eq!(1, spans.len());
+    assert_eq!("root/doc1#entity1", spans[0].node_name);
+    assert_eq!((2, 4), (spans[0].start, spans[0].end));
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use graphannis::{graph::AnnoKey, AnnotationGraph};
+use graphannis_core::graph::NODE_NAME_KEY;
+
+use crate::{
+    api::token_helper::{TokenHelper, TOKEN_KEY},
+    app::job_executor::FgJob,
+};
+
+use super::corpus_structure::ordered_documents;
+
+/// Two values that occur directly after each other and how strongly they
+/// are associated.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Collocation {
+    pub(crate) first: String,
+    pub(crate) second: String,
+    pub(crate) count: usize,
+    /// Pointwise mutual information in bits.
+    pub(crate) pmi: f64,
+    /// Dunning's log-likelihood ratio (G²).
+    pub(crate) log_likelihood: f64,
+}
+
+/// Get the values of the `layer` annotation (or the token values if `None`)
+/// of all token in their order. A token without the annotation ends the
+/// sequence, so n-grams never span over missing values or documents.
+pub(crate) fn value_sequences(
+    graph: &AnnotationGraph,
+    layer: Option<&AnnoKey>,
+    job: &FgJob,
+) -> Result<Vec<Vec<String>>> {
+    let tok_helper = TokenHelper::new(graph)?;
+    let node_annos = graph.get_node_annos();
+    let key = layer.unwrap_or(TOKEN_KEY.as_ref());
+    let mut result = Vec::new();
+    for document in ordered_documents(graph)? {
+        job.check_cancelled()?;
+        let document_name = node_annos
+            .get_value_for_item(&document, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let mut current = Vec::new();
+        for t in tok_helper.get_ordered_token(&document_name, None)? {
+            if let Some(value) = node_annos.get_value_for_item(&t, key)? {
+                current.push(value.to_string());
+            } else if !current.is_empty() {
+                result.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            result.push(current);
+        }
+    }
+    Ok(result)
+}
+
+/// Count all sequences of `n` consecutive values, the most frequent first.
+pub(crate) fn ngram_counts(sequences: &[Vec<String>], n: usize) -> Vec<(Vec<String>, usize)> {
+    let mut counts: HashMap<&[String], usize> = HashMap::new();
+    if n > 0 {
+        for s in sequences {
+            for ngram in s.windows(n) {
+                *counts.entry(ngram).or_default() += 1;
+            }
+        }
+    }
+    let mut result: Vec<_> = counts
+        .into_iter()
+        .map(|(ngram, count)| (ngram.to_vec(), count))
+        .collect();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    result
+}
+
+/// Calculate association measures for all pairs of adjacent values that
+/// occur at least `min_count` times, the strongest association (by
+/// log-likelihood) first.
+pub(crate) fn collocations(sequences: &[Vec<String>], min_count: usize) -> Vec<Collocation> {
+    let mut pair_counts: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut first_counts: HashMap<&str, usize> = HashMap::new();
+    let mut second_counts: HashMap<&str, usize> = HashMap::new();
+    let mut total = 0;
+    for s in sequences {
+        for pair in s.windows(2) {
+            let (first, second) = (pair[0].as_str(), pair[1].as_str());
+            *pair_counts.entry((first, second)).or_default() += 1;
+            *first_counts.entry(first).or_default() += 1;
+            *second_counts.entry(second).or_default() += 1;
+            total += 1;
+        }
+    }
+
+    let n = total as f64;
+    let mut result: Vec<Collocation> = pair_counts
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count.max(1))
+        .map(|((first, second), count)| {
+            let k11 = count as f64;
+            let first_total = first_counts[first] as f64;
+            let second_total = second_counts[second] as f64;
+            let k12 = first_total - k11;
+            let k21 = second_total - k11;
+            let k22 = n - k11 - k12 - k21;
+            let pmi = (k11 * n / (first_total * second_total)).log2();
+
+            let observed_expected = [
+                (k11, first_total * second_total / n),
+                (k12, first_total * (n - second_total) / n),
+                (k21, (n - first_total) * second_total / n),
+                (k22, (n - first_total) * (n - second_total) / n),
+            ];
+            let log_likelihood = 2.0
+                * observed_expected
+                    .iter()
+                    .filter(|(observed, expected)| *observed > 0.0 && *expected > 0.0)
+                    .map(|(observed, expected)| observed * (observed / expected).ln())
+                    .sum::<f64>();
+            Collocation {
+                first: first.to_string(),
+                second: second.to_string(),
+                count,
+                pmi,
+                log_likelihood,
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.log_likelihood
+            .total_cmp(&a.log_likelihood)
+            .then_with(|| (&a.first, &a.second).cmp(&(&b.first, &b.second)))
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests;
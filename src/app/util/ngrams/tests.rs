@@ -0,0 +1,61 @@
+use graphannis::update::GraphUpdate;
+use pretty_assertions::assert_eq;
+
+use crate::app::{job_executor::FgJob, util::example_generator};
+
+use super::{collocations, ngram_counts, value_sequences};
+
+fn sequence(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn count_ngrams() {
+    let sequences = vec![sequence(&["a", "b", "a", "b", "c"]), sequence(&["a", "b"])];
+    assert_eq!(
+        vec![
+            (sequence(&["a", "b"]), 3),
+            (sequence(&["b", "a"]), 1),
+            (sequence(&["b", "c"]), 1)
+        ],
+        ngram_counts(&sequences, 2)
+    );
+    assert_eq!(
+        vec![
+            (sequence(&["a", "b", "a"]), 1),
+            (sequence(&["a", "b", "c"]), 1),
+            (sequence(&["b", "a", "b"]), 1)
+        ],
+        ngram_counts(&sequences, 3)
+    );
+    assert!(ngram_counts(&sequences, 6).is_empty());
+    assert!(ngram_counts(&sequences, 0).is_empty());
+}
+
+#[test]
+fn association_measures() {
+    let sequences = vec![sequence(&["a", "b", "a", "b", "c"])];
+    let result = collocations(&sequences, 2);
+    assert_eq!(1, result.len());
+    assert_eq!("a", result[0].first);
+    assert_eq!("b", result[0].second);
+    assert_eq!(2, result[0].count);
+    assert!((result[0].pmi - 1.0).abs() < 1e-9);
+    assert!((result[0].log_likelihood - 8.0 * 2.0_f64.ln()).abs() < 1e-9);
+
+    assert_eq!(3, collocations(&sequences, 1).len());
+}
+
+#[test]
+fn token_values_of_documents() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+
+    let sequences = value_sequences(&graph, None, &FgJob::default()).unwrap();
+    assert_eq!(1, sequences.len());
+    assert_eq!(11, sequences[0].len());
+    assert_eq!("Is", sequences[0][0]);
+    assert_eq!("?", sequences[0][10]);
+}
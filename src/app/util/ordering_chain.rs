@@ -0,0 +1,221 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+};
+
+use anyhow::{Context, Result};
+use graphannis::{
+    graph::NodeID,
+    model::{AnnotationComponent, AnnotationComponentType},
+    AnnotationGraph,
+};
+use graphannis_core::{
+    dfs::CycleSafeDFS,
+    graph::{ANNIS_NS, NODE_NAME_KEY},
+    types::Component,
+};
+
+/// A problem found in an ordering component of a document.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum OrderingIssue {
+    /// There is no ordering edge between the end of one chain and the start
+    /// of the next one.
+    Break { after: String, before: String },
+    /// A node has more than one successor.
+    Branch {
+        node: String,
+        successors: Vec<String>,
+    },
+    /// A node has more than one predecessor.
+    Merge {
+        node: String,
+        predecessors: Vec<String>,
+    },
+    /// Following the edges leads back to this node.
+    Cycle { node: String },
+    /// An ordering edge points to a node that is not part of the document.
+    LeavesDocument { node: String, target: String },
+}
+
+impl OrderingIssue {
+    /// All nodes that are involved in this issue.
+    pub(crate) fn nodes(&self) -> Vec<&str> {
+        match self {
+            OrderingIssue::Break { after, before } => vec![after, before],
+            OrderingIssue::Branch { node, .. }
+            | OrderingIssue::Merge { node, .. }
+            | OrderingIssue::Cycle { node }
+            | OrderingIssue::LeavesDocument { node, .. } => vec![node],
+        }
+    }
+}
+
+impl Display for OrderingIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderingIssue::Break { after, before } => {
+                write!(f, "Chain is broken between {after} and {before}")
+            }
+            OrderingIssue::Branch { node, successors } => {
+                write!(
+                    f,
+                    "{node} has several successors: {}",
+                    successors.join(", ")
+                )
+            }
+            OrderingIssue::Merge { node, predecessors } => write!(
+                f,
+                "{node} has several predecessors: {}",
+                predecessors.join(", ")
+            ),
+            OrderingIssue::Cycle { node } => write!(f, "Cycle back to {node}"),
+            OrderingIssue::LeavesDocument { node, target } => {
+                write!(f, "{node} points to {target} outside of the document")
+            }
+        }
+    }
+}
+
+/// The chains of nodes an ordering component of a document consists of. A
+/// valid ordering has exactly one chain and no issues.
+#[derive(Clone, Debug)]
+pub(crate) struct OrderingChains {
+    pub(crate) component: AnnotationComponent,
+    pub(crate) chains: Vec<Vec<String>>,
+    pub(crate) issues: Vec<OrderingIssue>,
+}
+
+/// Reconstruct all ordering chains of the given document, including the ones
+/// that are broken or branch.
+pub(crate) fn ordering_chains(
+    graph: &AnnotationGraph,
+    document: NodeID,
+) -> Result<Vec<OrderingChains>> {
+    let part_of = Component::new(AnnotationComponentType::PartOf, ANNIS_NS.into(), "".into());
+    let part_of = graph
+        .get_graphstorage_as_ref(&part_of)
+        .context("Missing PartOf component")?;
+    let mut document_nodes = HashSet::new();
+    for step in CycleSafeDFS::new_inverse(part_of.as_edgecontainer(), document, 1, usize::MAX) {
+        document_nodes.insert(step?.node);
+    }
+
+    let node_annos = graph.get_node_annos();
+    let name_of = |n: NodeID| -> Result<String> {
+        Ok(node_annos
+            .get_value_for_item(&n, &NODE_NAME_KEY)?
+            .context("Missing node name")?
+            .to_string())
+    };
+
+    let mut result = Vec::new();
+    for component in graph.get_all_components(Some(AnnotationComponentType::Ordering), None) {
+        let Some(gs) = graph.get_graphstorage_as_ref(&component) else {
+            continue;
+        };
+        // Collect the sorted successors and predecessors of all nodes of the
+        // document that are part of this component.
+        let mut nodes = BTreeMap::new();
+        for n in document_nodes.iter() {
+            let mut successors = Vec::new();
+            for target in gs.get_outgoing_edges(*n) {
+                let target = target?;
+                successors.push((name_of(target)?, target));
+            }
+            let mut predecessors = Vec::new();
+            for source in gs.get_ingoing_edges(*n) {
+                let source = source?;
+                predecessors.push((name_of(source)?, source));
+            }
+            if !successors.is_empty() || !predecessors.is_empty() {
+                successors.sort();
+                predecessors.sort();
+                nodes.insert(name_of(*n)?, (*n, successors, predecessors));
+            }
+        }
+        if nodes.is_empty() {
+            continue;
+        }
+
+        let mut issues = Vec::new();
+        for (name, (_, successors, predecessors)) in nodes.iter() {
+            if successors.len() > 1 {
+                issues.push(OrderingIssue::Branch {
+                    node: name.clone(),
+                    successors: successors.iter().map(|(n, _)| n.clone()).collect(),
+                });
+            }
+            if predecessors.len() > 1 {
+                issues.push(OrderingIssue::Merge {
+                    node: name.clone(),
+                    predecessors: predecessors.iter().map(|(n, _)| n.clone()).collect(),
+                });
+            }
+            for (target_name, target) in successors {
+                if !document_nodes.contains(target) {
+                    issues.push(OrderingIssue::LeavesDocument {
+                        node: name.clone(),
+                        target: target_name.clone(),
+                    });
+                }
+            }
+        }
+
+        // Follow the edges from the roots first and from all remaining nodes
+        // afterwards, so that cycles without a root are included as well.
+        let roots = nodes
+            .iter()
+            .filter(|(_, (_, _, predecessors))| {
+                predecessors
+                    .iter()
+                    .all(|(_, p)| !document_nodes.contains(p))
+            })
+            .map(|(name, _)| name.clone());
+        let start_nodes: Vec<String> = roots.chain(nodes.keys().cloned()).collect();
+        let mut visited = HashSet::new();
+        let mut chains: Vec<Vec<String>> = Vec::new();
+        for start in start_nodes {
+            if visited.contains(&start) {
+                continue;
+            }
+            let mut chain = Vec::new();
+            let mut current = Some(start);
+            while let Some(name) = current {
+                if chain.contains(&name) {
+                    issues.push(OrderingIssue::Cycle { node: name });
+                    break;
+                }
+                if !visited.insert(name.clone()) {
+                    // Joins a chain that has already been followed
+                    break;
+                }
+                current = nodes.get(&name).and_then(|(_, successors, _)| {
+                    successors
+                        .iter()
+                        .find(|(_, n)| document_nodes.contains(n))
+                        .map(|(n, _)| n.clone())
+                });
+                chain.push(name);
+            }
+            chains.push(chain);
+        }
+        for (previous, next) in chains.iter().zip(chains.iter().skip(1)) {
+            if let (Some(after), Some(before)) = (previous.last(), next.first()) {
+                issues.push(OrderingIssue::Break {
+                    after: after.clone(),
+                    before: before.clone(),
+                });
+            }
+        }
+
+        result.push(OrderingChains {
+            component,
+            chains,
+            issues,
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests;
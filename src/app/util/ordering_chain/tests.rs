@@ -0,0 +1,98 @@
+use graphannis::{
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::graph::ANNIS_NS;
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{ordering_chains, OrderingIssue};
+
+fn ordering_event(source: usize, target: usize, add: bool) -> UpdateEvent {
+    let source_node = format!("root/doc1#tok{source}");
+    let target_node = format!("root/doc1#tok{target}");
+    if add {
+        UpdateEvent::AddEdge {
+            source_node,
+            target_node,
+            layer: ANNIS_NS.to_string(),
+            component_type: "Ordering".to_string(),
+            component_name: "".to_string(),
+        }
+    } else {
+        UpdateEvent::DeleteEdge {
+            source_node,
+            target_node,
+            layer: ANNIS_NS.to_string(),
+            component_type: "Ordering".to_string(),
+            component_name: "".to_string(),
+        }
+    }
+}
+
+fn document_id(graph: &AnnotationGraph) -> u64 {
+    graph
+        .get_node_annos()
+        .get_node_id_from_name("root/doc1")
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn valid_ordering_has_one_chain() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    let result = ordering_chains(&graph, document_id(&graph)).unwrap();
+    assert_eq!(1, result.len());
+    assert_eq!(1, result[0].chains.len());
+    assert_eq!(11, result[0].chains[0].len());
+    assert_eq!("root/doc1#tok0", result[0].chains[0][0]);
+    assert_eq!("root/doc1#tok10", result[0].chains[0][10]);
+    assert_eq!(Vec::<OrderingIssue>::new(), result[0].issues);
+}
+
+#[test]
+fn find_breaks_and_branches() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let mut graph = example_generator::create_graph(&mut updates);
+    let mut updates = GraphUpdate::new();
+    updates.add_event(ordering_event(4, 5, false)).unwrap();
+    graph.apply_update(&mut updates, |_msg| {}).unwrap();
+
+    let result = ordering_chains(&graph, document_id(&graph)).unwrap();
+    assert_eq!(2, result[0].chains.len());
+    assert_eq!(
+        vec![OrderingIssue::Break {
+            after: "root/doc1#tok4".to_string(),
+            before: "root/doc1#tok5".to_string()
+        }],
+        result[0].issues
+    );
+
+    let mut updates = GraphUpdate::new();
+    updates.add_event(ordering_event(4, 5, true)).unwrap();
+    updates.add_event(ordering_event(2, 7, true)).unwrap();
+    graph.apply_update(&mut updates, |_msg| {}).unwrap();
+
+    let result = ordering_chains(&graph, document_id(&graph)).unwrap();
+    assert_eq!(1, result[0].chains.len());
+    assert_eq!(11, result[0].chains[0].len());
+    assert_eq!(
+        vec![
+            OrderingIssue::Branch {
+                node: "root/doc1#tok2".to_string(),
+                successors: vec!["root/doc1#tok3".to_string(), "root/doc1#tok7".to_string()]
+            },
+            OrderingIssue::Merge {
+                node: "root/doc1#tok7".to_string(),
+                predecessors: vec!["root/doc1#tok2".to_string(), "root/doc1#tok6".to_string()]
+            },
+        ],
+        result[0].issues
+    );
+}
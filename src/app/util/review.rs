@@ -0,0 +1,153 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use graphannis::{graph::AnnoKey, update::UpdateEvent, AnnotationGraph};
+use graphannis_core::{
+    annostorage::ValueSearch,
+    graph::{ANNIS_NS, NODE_NAME_KEY},
+};
+
+use crate::api::provenance::ANNOTATOR_ANNO_NAME;
+
+/// Namespace of the annotations that record the result of a review.
+pub(crate) const REVIEW_NS: &str = "review";
+
+/// Which annotations are part of a review.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ReviewFilter {
+    /// All annotations in the namespace, e.g. the one of an automatic tagger.
+    Namespace(String),
+    /// All annotations of the nodes stamped with the initials of an annotator.
+    Annotator {
+        stamp_namespace: String,
+        initials: String,
+    },
+}
+
+/// A single annotation that needs to be accepted or rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ReviewItem {
+    pub(crate) node_name: String,
+    pub(crate) key: AnnoKey,
+    pub(crate) value: String,
+}
+
+/// What happens with an annotation when it is rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub(crate) enum RejectAction {
+    /// Delete the annotation.
+    #[default]
+    Delete,
+    /// Keep the annotation, but mark it as rejected.
+    Flag,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReviewDecision {
+    Accept,
+    Reject(RejectAction),
+}
+
+/// The key of the annotation that records the review result of the
+/// annotation with the given key, e.g. `review::pos` for `pos` and
+/// `review::tagger_pos` for `tagger::pos`.
+pub(crate) fn review_key(key: &AnnoKey) -> AnnoKey {
+    let name = if key.ns.is_empty() {
+        key.name.to_string()
+    } else {
+        format!("{}_{}", key.ns, key.name)
+    };
+    AnnoKey {
+        ns: REVIEW_NS.into(),
+        name: name.into(),
+    }
+}
+
+/// Collect all annotations matching the filter that have not been reviewed
+/// yet, ordered by the node name.
+pub(crate) fn review_queue(
+    graph: &AnnotationGraph,
+    filter: &ReviewFilter,
+) -> Result<Vec<ReviewItem>> {
+    let node_annos = graph.get_node_annos();
+    let mut candidates = BTreeSet::new();
+    match filter {
+        ReviewFilter::Namespace(ns) => {
+            for key in node_annos.annotation_keys()? {
+                if key.ns.as_str() != ns || key.ns == ANNIS_NS || key.ns == REVIEW_NS {
+                    continue;
+                }
+                for m in
+                    node_annos.exact_anno_search(Some(key.ns.as_str()), &key.name, ValueSearch::Any)
+                {
+                    candidates.insert((m?.node, key.clone()));
+                }
+            }
+        }
+        ReviewFilter::Annotator {
+            stamp_namespace,
+            initials,
+        } => {
+            for m in node_annos.exact_anno_search(
+                Some(stamp_namespace.as_str()),
+                ANNOTATOR_ANNO_NAME,
+                ValueSearch::Some(initials.as_str()),
+            ) {
+                let node = m?.node;
+                for anno in node_annos.get_annotations_for_item(&node)? {
+                    let is_stamp = anno.key.ns.as_str() == stamp_namespace
+                        && anno.key.name == ANNOTATOR_ANNO_NAME;
+                    if !is_stamp && anno.key.ns != ANNIS_NS && anno.key.ns != REVIEW_NS {
+                        candidates.insert((node, anno.key));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    for (node, key) in candidates {
+        if node_annos
+            .get_value_for_item(&node, &review_key(&key))?
+            .is_some()
+        {
+            continue;
+        }
+        let node_name = node_annos
+            .get_value_for_item(&node, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let value = node_annos
+            .get_value_for_item(&node, &key)?
+            .unwrap_or_default();
+        result.push(ReviewItem {
+            node_name: node_name.to_string(),
+            key,
+            value: value.to_string(),
+        });
+    }
+    result.sort_by(|a, b| a.node_name.cmp(&b.node_name).then(a.key.cmp(&b.key)));
+    Ok(result)
+}
+
+/// The update events that record the decision for the item.
+pub(crate) fn decision_events(item: &ReviewItem, decision: ReviewDecision) -> Vec<UpdateEvent> {
+    let review_key = review_key(&item.key);
+    let flag = |value: &str| UpdateEvent::AddNodeLabel {
+        node_name: item.node_name.clone(),
+        anno_ns: review_key.ns.to_string(),
+        anno_name: review_key.name.to_string(),
+        anno_value: value.to_string(),
+    };
+    match decision {
+        ReviewDecision::Accept => vec![flag("accepted")],
+        ReviewDecision::Reject(RejectAction::Flag) => vec![flag("rejected")],
+        ReviewDecision::Reject(RejectAction::Delete) => vec![UpdateEvent::DeleteNodeLabel {
+            node_name: item.node_name.clone(),
+            anno_ns: item.key.ns.to_string(),
+            anno_name: item.key.name.to_string(),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests;
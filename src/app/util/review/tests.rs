@@ -0,0 +1,121 @@
+use graphannis::{
+    graph::AnnoKey,
+    update::{GraphUpdate, UpdateEvent},
+};
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{
+    decision_events, review_key, review_queue, RejectAction, ReviewDecision, ReviewFilter,
+    ReviewItem,
+};
+
+fn add_label(updates: &mut GraphUpdate, node_name: &str, ns: &str, name: &str, value: &str) {
+    updates
+        .add_event(UpdateEvent::AddNodeLabel {
+            node_name: node_name.to_string(),
+            anno_ns: ns.to_string(),
+            anno_name: name.to_string(),
+            anno_value: value.to_string(),
+        })
+        .unwrap();
+}
+
+/// Adds annotations of an automatic tagger and an annotator to the example token.
+fn add_review_labels(updates: &mut GraphUpdate) {
+    add_label(updates, "root/doc1#tok1", "tagger", "pos", "VBZ");
+    add_label(updates, "root/doc1#tok2", "tagger", "pos", "DT");
+    add_label(updates, "root/doc1#tok3", "", "lemma", "example");
+    add_label(updates, "root/doc1#tok3", "annatomic", "annotator", "AB");
+}
+
+fn key(ns: &str, name: &str) -> AnnoKey {
+    AnnoKey {
+        ns: ns.into(),
+        name: name.into(),
+    }
+}
+
+#[test]
+fn review_key_includes_namespace() {
+    assert_eq!(key("review", "pos"), review_key(&key("", "pos")));
+    assert_eq!(
+        key("review", "tagger_pos"),
+        review_key(&key("tagger", "pos"))
+    );
+}
+
+#[test]
+fn queue_by_annotator() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_review_labels(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let queue = review_queue(
+        &graph,
+        &ReviewFilter::Annotator {
+            stamp_namespace: "annatomic".to_string(),
+            initials: "AB".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        vec![ReviewItem {
+            node_name: "root/doc1#tok3".to_string(),
+            key: key("", "lemma"),
+            value: "example".to_string(),
+        }],
+        queue
+    );
+}
+
+#[test]
+fn accept_and_reject_items() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_review_labels(&mut updates);
+    let mut graph = example_generator::create_graph(&mut updates);
+    let filter = ReviewFilter::Namespace("tagger".to_string());
+    let queue = review_queue(&graph, &filter).unwrap();
+    assert_eq!(2, queue.len());
+    assert_eq!("root/doc1#tok1", queue[0].node_name);
+    assert_eq!("VBZ", queue[0].value);
+
+    let mut updates = GraphUpdate::new();
+    for event in decision_events(&queue[0], ReviewDecision::Accept)
+        .into_iter()
+        .chain(decision_events(
+            &queue[1],
+            ReviewDecision::Reject(RejectAction::Delete),
+        ))
+    {
+        updates.add_event(event).unwrap();
+    }
+    graph.apply_update(&mut updates, |_msg| {}).unwrap();
+
+    // Reviewed items are not part of the queue anymore
+    assert!(review_queue(&graph, &filter).unwrap().is_empty());
+    let node_annos = graph.get_node_annos();
+    let tok1 = node_annos
+        .get_node_id_from_name("root/doc1#tok1")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "accepted",
+        node_annos
+            .get_value_for_item(&tok1, &key("review", "tagger_pos"))
+            .unwrap()
+            .unwrap()
+    );
+    let tok2 = node_annos
+        .get_node_id_from_name("root/doc1#tok2")
+        .unwrap()
+        .unwrap();
+    assert!(node_annos
+        .get_value_for_item(&tok2, &key("tagger", "pos"))
+        .unwrap()
+        .is_none());
+}
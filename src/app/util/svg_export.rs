@@ -0,0 +1,296 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::Write,
+};
+
+use anyhow::Result;
+use egui::Theme;
+use graphannis::{graph::AnnoKey, model::AnnotationComponentType, AnnotationGraph};
+use graphannis_core::{graph::ANNIS_NS, types::Edge};
+
+use crate::{
+    api::token_helper::TOKEN_KEY,
+    app::{appearance::ColorScheme, util::html_export::escape, widgets::Token},
+};
+
+const CHAR_WIDTH: f32 = 7.5;
+const CELL_PADDING: f32 = 16.0;
+const ROW_HEIGHT: f32 = 24.0;
+const HEADER_WIDTH: f32 = 140.0;
+const MARGIN: f32 = 10.0;
+const MAX_ARC_HEIGHT: f32 = 160.0;
+
+/// A pointing relation between two nodes of the document.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PointingArc {
+    pub(crate) source: String,
+    pub(crate) target: String,
+    pub(crate) label: String,
+}
+
+/// Find all pointing relations between the given nodes.
+pub(crate) fn pointing_arcs<'a>(
+    graph: &AnnotationGraph,
+    node_names: impl Iterator<Item = &'a str>,
+) -> Result<Vec<PointingArc>> {
+    let node_annos = graph.get_node_annos();
+    let mut nodes = HashMap::new();
+    for name in node_names {
+        if let Some(id) = node_annos.get_node_id_from_name(name)? {
+            nodes.insert(id, name);
+        }
+    }
+    let mut result = Vec::new();
+    for component in graph.get_all_components(Some(AnnotationComponentType::Pointing), None) {
+        let Some(gs) = graph.get_graphstorage_as_ref(&component) else {
+            continue;
+        };
+        for (source, source_name) in nodes.iter() {
+            for target in gs.get_outgoing_edges(*source) {
+                let target = target?;
+                let Some(target_name) = nodes.get(&target) else {
+                    continue;
+                };
+                let labels: Vec<String> = gs
+                    .get_anno_storage()
+                    .get_annotations_for_item(&Edge {
+                        source: *source,
+                        target,
+                    })?
+                    .into_iter()
+                    .map(|a| a.val.to_string())
+                    .collect();
+                let label = if labels.is_empty() {
+                    component.name.to_string()
+                } else {
+                    labels.join(", ")
+                };
+                result.push(PointingArc {
+                    source: source_name.to_string(),
+                    target: target_name.to_string(),
+                    label,
+                });
+            }
+        }
+    }
+    result.sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
+    Ok(result)
+}
+
+fn text_width(text: &str) -> f32 {
+    text.chars().count() as f32 * CHAR_WIDTH + CELL_PADDING
+}
+
+fn key_label(key: &AnnoKey) -> String {
+    if key.ns.is_empty() {
+        key.name.to_string()
+    } else {
+        format!("{}:{}", key.ns, key.name)
+    }
+}
+
+fn rgb(color: egui::Color32) -> String {
+    format!("rgb({}, {}, {})", color.r(), color.g(), color.b())
+}
+
+/// Write the token, the segmentation spans and the pointing relations of a
+/// document as SVG image. Annotations with a key in `hidden_labels` are not
+/// included.
+pub(crate) fn write_document_svg<W: Write>(
+    token: &[Token],
+    segmentations: &BTreeMap<String, Vec<Token>>,
+    hidden_labels: &BTreeSet<AnnoKey>,
+    arcs: &[PointingArc],
+    mut output: W,
+) -> Result<()> {
+    let is_visible = |k: &AnnoKey| k.ns != ANNIS_NS && !hidden_labels.contains(k);
+    let token_keys: BTreeSet<AnnoKey> = token
+        .iter()
+        .flat_map(|t| t.labels.keys())
+        .filter(|k| is_visible(*k))
+        .cloned()
+        .collect();
+
+    // The width of each token column is determined by its longest label
+    let mut x = Vec::with_capacity(token.len());
+    let mut width = Vec::with_capacity(token.len());
+    let mut current_x = MARGIN + HEADER_WIDTH;
+    for t in token {
+        let w = t
+            .labels
+            .iter()
+            .filter(|(k, _)| *k == TOKEN_KEY.as_ref() || token_keys.contains(*k))
+            .map(|(_, v)| text_width(v))
+            .fold(CELL_PADDING * 2.0, f32::max);
+        x.push(current_x);
+        width.push(w);
+        current_x += w;
+    }
+    let total_width = current_x + MARGIN;
+
+    // Center and extent of each node that can be the end of an arc
+    let mut node_ranges = HashMap::new();
+    for t in token.iter().chain(segmentations.values().flatten()) {
+        if t.start < x.len() && t.end < x.len() {
+            node_ranges.insert(t.node_name.as_str(), (t.start, t.end));
+        }
+    }
+    let center = |(start, end): (usize, usize)| (x[start] + x[end] + width[end]) / 2.0;
+    let arc_height = |from: f32, to: f32| (20.0 + 0.3 * (to - from).abs()).min(MAX_ARC_HEIGHT);
+    let mut arcs_height: f32 = 0.0;
+    for arc in arcs {
+        if let (Some(s), Some(t)) = (
+            node_ranges.get(arc.source.as_str()),
+            node_ranges.get(arc.target.as_str()),
+        ) {
+            arcs_height = arcs_height.max(arc_height(center(*s), center(*t)) * 0.75 + 16.0);
+        }
+    }
+
+    let layer_rows: usize = segmentations
+        .values()
+        .map(|spans| {
+            1 + spans
+                .iter()
+                .flat_map(|s| s.labels.keys())
+                .filter(|k| is_visible(*k))
+                .collect::<BTreeSet<_>>()
+                .len()
+        })
+        .sum();
+    let rows = 1 + token_keys.len() + layer_rows;
+    let top = MARGIN + arcs_height;
+    let total_height = top + rows as f32 * ROW_HEIGHT + MARGIN;
+
+    writeln!(
+        output,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="{total_height}" viewBox="0 0 {total_width} {total_height}" font-family="sans-serif" font-size="12">"#
+    )?;
+    writeln!(
+        output,
+        r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z"/></marker></defs>"#
+    )?;
+    writeln!(
+        output,
+        r#"<rect width="{total_width}" height="{total_height}" fill="white"/>"#
+    )?;
+
+    // Pointing relations are drawn as arcs above the token
+    for arc in arcs {
+        let (Some(s), Some(t)) = (
+            node_ranges.get(arc.source.as_str()),
+            node_ranges.get(arc.target.as_str()),
+        ) else {
+            continue;
+        };
+        let (sx, tx) = (center(*s), center(*t));
+        let h = arc_height(sx, tx);
+        writeln!(
+            output,
+            r#"<path d="M {sx} {top} C {sx} {c}, {tx} {c}, {tx} {top}" fill="none" stroke="black" marker-end="url(#arrow)"/>"#,
+            c = top - h
+        )?;
+        writeln!(
+            output,
+            r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+            (sx + tx) / 2.0,
+            top - h * 0.75 - 4.0,
+            escape(&arc.label)
+        )?;
+    }
+
+    let mut row_y = top;
+    let write_header = |output: &mut W, y: f32, text: &str| -> Result<()> {
+        writeln!(
+            output,
+            r#"<text x="{MARGIN}" y="{}" fill="gray">{}</text>"#,
+            y + ROW_HEIGHT * 0.65,
+            escape(text)
+        )?;
+        Ok(())
+    };
+    let write_cell = |output: &mut W,
+                      y: f32,
+                      start: usize,
+                      end: usize,
+                      text: &str,
+                      fill: Option<&str>|
+     -> Result<()> {
+        let cell_x = x[start];
+        let cell_width = x[end] + width[end] - cell_x;
+        if let Some(fill) = fill {
+            writeln!(
+                output,
+                r#"<rect x="{cell_x}" y="{}" width="{cell_width}" height="{}" rx="3" fill="{fill}" fill-opacity="0.4" stroke="gray"/>"#,
+                y + 2.0,
+                ROW_HEIGHT - 4.0
+            )?;
+        }
+        writeln!(
+            output,
+            r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+            cell_x + cell_width / 2.0,
+            y + ROW_HEIGHT * 0.65,
+            escape(text)
+        )?;
+        Ok(())
+    };
+
+    write_header(&mut output, row_y, "tok")?;
+    for (idx, t) in token.iter().enumerate() {
+        let value = t
+            .labels
+            .get(&TOKEN_KEY)
+            .map(String::as_str)
+            .unwrap_or_default();
+        write_cell(&mut output, row_y, idx, idx, value, None)?;
+    }
+    row_y += ROW_HEIGHT;
+    for key in token_keys.iter() {
+        write_header(&mut output, row_y, &key_label(key))?;
+        for (idx, t) in token.iter().enumerate() {
+            if let Some(value) = t.labels.get(key) {
+                write_cell(&mut output, row_y, idx, idx, value, None)?;
+            }
+        }
+        row_y += ROW_HEIGHT;
+    }
+
+    for (layer_idx, (layer, spans)) in segmentations.iter().enumerate() {
+        let fill = rgb(ColorScheme::Default.layer_color(Theme::Light, layer_idx));
+        let spans: Vec<&Token> = spans
+            .iter()
+            .filter(|s| s.start <= s.end && s.end < token.len())
+            .collect();
+        let keys: BTreeSet<&AnnoKey> = spans
+            .iter()
+            .flat_map(|s| s.labels.keys())
+            .filter(|k| is_visible(*k))
+            .collect();
+        write_header(&mut output, row_y, layer)?;
+        for s in spans.iter() {
+            let value = s
+                .labels
+                .get(&TOKEN_KEY)
+                .map(String::as_str)
+                .unwrap_or_default();
+            write_cell(&mut output, row_y, s.start, s.end, value, Some(&fill))?;
+        }
+        row_y += ROW_HEIGHT;
+        for key in keys {
+            write_header(&mut output, row_y, &format!("{layer}: {}", key_label(key)))?;
+            for s in spans.iter() {
+                if let Some(value) = s.labels.get(key) {
+                    write_cell(&mut output, row_y, s.start, s.end, value, Some(&fill))?;
+                }
+            }
+            row_y += ROW_HEIGHT;
+        }
+    }
+
+    writeln!(output, "</svg>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
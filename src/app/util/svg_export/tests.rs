@@ -0,0 +1,94 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use pretty_assertions::assert_eq;
+
+use crate::{
+    api::token_helper::TOKEN_KEY,
+    app::{util::example_generator, widgets::Token},
+};
+
+use super::{pointing_arcs, write_document_svg, PointingArc};
+
+fn create_token(name: &str, start: usize, end: usize, value: &str) -> Token {
+    Token {
+        node_name: name.to_string(),
+        start,
+        end,
+        labels: BTreeMap::from([(TOKEN_KEY.as_ref().clone(), value.to_string())]),
+    }
+}
+
+#[test]
+fn find_pointing_arcs() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    updates
+        .add_event(UpdateEvent::AddEdge {
+            source_node: "root/doc1#tok1".to_string(),
+            target_node: "root/doc1#tok3".to_string(),
+            layer: "default_ns".to_string(),
+            component_type: "Pointing".to_string(),
+            component_name: "dep".to_string(),
+        })
+        .unwrap();
+    updates
+        .add_event(UpdateEvent::AddEdgeLabel {
+            source_node: "root/doc1#tok1".to_string(),
+            target_node: "root/doc1#tok3".to_string(),
+            layer: "default_ns".to_string(),
+            component_type: "Pointing".to_string(),
+            component_name: "dep".to_string(),
+            anno_ns: "default_ns".to_string(),
+            anno_name: "func".to_string(),
+            anno_value: "det".to_string(),
+        })
+        .unwrap();
+    let graph = example_generator::create_graph(&mut updates);
+
+    let arcs = pointing_arcs(
+        &graph,
+        ["root/doc1#tok1", "root/doc1#tok2", "root/doc1#tok3"].into_iter(),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![PointingArc {
+            source: "root/doc1#tok1".to_string(),
+            target: "root/doc1#tok3".to_string(),
+            label: "det".to_string(),
+        }],
+        arcs
+    );
+    // Relations to nodes that are not exported are ignored
+    let arcs = pointing_arcs(&graph, ["root/doc1#tok1"].into_iter()).unwrap();
+    assert!(arcs.is_empty());
+}
+
+#[test]
+fn draw_token_spans_and_arcs() {
+    let token = vec![
+        create_token("doc#t1", 0, 0, "a"),
+        create_token("doc#t2", 1, 1, "b&c"),
+        create_token("doc#t3", 2, 2, "d"),
+    ];
+    let segmentations =
+        BTreeMap::from([("seg".to_string(), vec![create_token("doc#s1", 0, 1, "ab")])]);
+    let arcs = vec![PointingArc {
+        source: "doc#s1".to_string(),
+        target: "doc#t3".to_string(),
+        label: "rel".to_string(),
+    }];
+
+    let mut output = Vec::new();
+    write_document_svg(&token, &segmentations, &BTreeSet::new(), &arcs, &mut output).unwrap();
+    let svg = String::from_utf8(output).unwrap();
+
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.contains(">b&amp;c</text>"));
+    assert!(svg.contains(">ab</text>"));
+    assert!(svg.contains(">seg</text>"));
+    assert!(svg.contains("marker-end=\"url(#arrow)\""));
+    assert!(svg.contains(">rel</text>"));
+}
@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use graphannis::{graph::NodeID, AnnotationGraph};
+use graphannis_core::graph::NODE_NAME_KEY;
+
+use super::corpus_structure::ordered_documents;
+use crate::api::token_helper::{TokenHelper, TOKEN_KEY};
+
+/// Minimal score a token needs to be considered similar to a query word.
+const MIN_SCORE: f32 = 0.5;
+
+struct IndexedDocument {
+    node_id: NodeID,
+    node_name: String,
+    /// The original token values.
+    token: Vec<String>,
+    /// The lowercase token values used for matching.
+    lowercase_token: Vec<String>,
+}
+
+/// An index of the token values of all documents, which allows a fuzzy text
+/// search without using AQL.
+pub(crate) struct TextIndex {
+    documents: Vec<IndexedDocument>,
+}
+
+/// A sequence of token matching a text search query.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TextSearchHit {
+    pub(crate) document: NodeID,
+    pub(crate) document_name: String,
+    /// Index of the first matching token in the document.
+    pub(crate) token_index: usize,
+    /// The matching token and some context around them.
+    pub(crate) context: String,
+    /// Relevance of the hit between 0.0 and 1.0 (exact match).
+    pub(crate) score: f32,
+}
+
+impl TextIndex {
+    /// Create the index for all documents of the graph. All components of
+    /// the graph must have been loaded.
+    pub(crate) fn build(graph: &AnnotationGraph) -> Result<Self> {
+        let tok_helper = TokenHelper::new(graph)?;
+        let node_annos = graph.get_node_annos();
+        let mut documents = Vec::new();
+        for node_id in ordered_documents(graph)? {
+            let node_name = node_annos
+                .get_value_for_item(&node_id, &NODE_NAME_KEY)?
+                .context("Missing node name")?
+                .to_string();
+            let mut token = Vec::new();
+            for t in tok_helper.get_ordered_token(&node_name, None)? {
+                let value = node_annos
+                    .get_value_for_item(&t, &TOKEN_KEY)?
+                    .unwrap_or_default();
+                token.push(value.to_string());
+            }
+            let lowercase_token = token.iter().map(|t| t.to_lowercase()).collect();
+            documents.push(IndexedDocument {
+                node_id,
+                node_name,
+                token,
+                lowercase_token,
+            });
+        }
+        Ok(Self { documents })
+    }
+
+    /// Find all token sequences that are similar to the words of the query,
+    /// ordered by their relevance.
+    pub(crate) fn search(&self, query: &str, max_hits: usize) -> Vec<TextSearchHit> {
+        let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let mut hits = Vec::new();
+        for doc in self.documents.iter() {
+            if doc.lowercase_token.len() < words.len() {
+                continue;
+            }
+            for start in 0..=(doc.lowercase_token.len() - words.len()) {
+                let mut score_sum = 0.0;
+                for (offset, w) in words.iter().enumerate() {
+                    let score = similarity(w, &doc.lowercase_token[start + offset]);
+                    if score < MIN_SCORE {
+                        score_sum = 0.0;
+                        break;
+                    }
+                    score_sum += score;
+                }
+                if score_sum > 0.0 {
+                    hits.push(TextSearchHit {
+                        document: doc.node_id,
+                        document_name: doc.node_name.clone(),
+                        token_index: start,
+                        context: context(&doc.token, start, words.len()),
+                        score: score_sum / words.len() as f32,
+                    });
+                }
+            }
+        }
+        // Sort by relevance, but keep the corpus order for hits with the same score
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(max_hits);
+        hits
+    }
+}
+
+/// Similarity of a (lowercase) query word and token value between 0.0 and 1.0.
+fn similarity(word: &str, token: &str) -> f32 {
+    if word == token {
+        1.0
+    } else if token.starts_with(word) {
+        0.9
+    } else if token.contains(word) {
+        0.8
+    } else {
+        let distance = levenshtein(word, token);
+        let length = word.chars().count().max(token.chars().count());
+        // Only allow about one typo for every four characters
+        if distance * 4 > length {
+            0.0
+        } else {
+            0.7 * (1.0 - distance as f32 / length as f32)
+        }
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Join the matched token with a few token before and after them.
+fn context(token: &[String], start: usize, length: usize) -> String {
+    const CONTEXT_SIZE: usize = 3;
+    let context_start = start.saturating_sub(CONTEXT_SIZE);
+    let context_end = (start + length + CONTEXT_SIZE).min(token.len());
+    let mut result = Vec::new();
+    if context_start > 0 {
+        result.push("…".to_string());
+    }
+    for (idx, t) in token[context_start..context_end].iter().enumerate() {
+        let idx = context_start + idx;
+        if idx >= start && idx < start + length {
+            result.push(format!("[{t}]"));
+        } else {
+            result.push(t.clone());
+        }
+    }
+    if context_end < token.len() {
+        result.push("…".to_string());
+    }
+    result.join(" ")
+}
+
+#[cfg(test)]
+mod tests;
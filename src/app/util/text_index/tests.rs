@@ -0,0 +1,47 @@
+use graphannis::update::GraphUpdate;
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{levenshtein, TextIndex};
+
+fn create_index() -> TextIndex {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    TextIndex::build(&graph).unwrap()
+}
+
+#[test]
+fn edit_distance() {
+    assert_eq!(0, levenshtein("example", "example"));
+    assert_eq!(1, levenshtein("exmple", "example"));
+    assert_eq!(3, levenshtein("kitten", "sitting"));
+    assert_eq!(4, levenshtein("", "more"));
+}
+
+#[test]
+fn find_exact_and_similar_token() {
+    let index = create_index();
+
+    let hits = index.search("Example", 10);
+    assert_eq!(1, hits.len());
+    assert_eq!("root/doc1", hits[0].document_name);
+    assert_eq!(2, hits[0].token_index);
+    assert_eq!(1.0, hits[0].score);
+    assert_eq!("Is this [example] more complicated than …", hits[0].context);
+
+    // Typos are tolerated, but rank lower
+    let hits = index.search("compilcated", 10);
+    assert_eq!(1, hits.len());
+    assert_eq!(4, hits[0].token_index);
+    assert!(hits[0].score < 1.0);
+
+    // Multiple words must match consecutive token
+    let hits = index.search("more complicated", 10);
+    assert_eq!(1, hits.len());
+    assert_eq!(3, hits[0].token_index);
+    assert!(index.search("complicated more", 10).is_empty());
+    assert!(index.search("   ", 10).is_empty());
+}
@@ -0,0 +1,47 @@
+use std::{fmt::Display, sync::Arc};
+
+use graphannis::graph::AnnoKey;
+use graphannis_core::graph::ANNIS_NS;
+use lazy_static::lazy_static;
+
+#[cfg(test)]
+mod tests;
+
+lazy_static! {
+    pub static ref TIME_KEY: Arc<AnnoKey> = Arc::from(AnnoKey {
+        ns: ANNIS_NS.into(),
+        name: "time".into(),
+    });
+}
+
+/// The value of an `annis::time` annotation, which is given in seconds as
+/// `start-end` or only `start-` if the end is unknown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TimeRange {
+    pub(crate) start: f64,
+    pub(crate) end: Option<f64>,
+}
+
+impl TimeRange {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let (start, end) = value.trim().split_once('-')?;
+        let start = start.trim().parse::<f64>().ok()?;
+        let end = end.trim();
+        let end = if end.is_empty() {
+            None
+        } else {
+            Some(end.parse::<f64>().ok()?)
+        };
+        Some(TimeRange { start, end })
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(end) = self.end {
+            write!(f, "{}-{}", self.start, end)
+        } else {
+            write!(f, "{}-", self.start)
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use pretty_assertions::assert_eq;
+
+use super::TimeRange;
+
+#[test]
+fn parse_and_format_time() {
+    let range = TimeRange::parse("0.5-1.25").unwrap();
+    assert_eq!(0.5, range.start);
+    assert_eq!(Some(1.25), range.end);
+    assert_eq!("0.5-1.25", range.to_string());
+
+    let open_range = TimeRange::parse("3-").unwrap();
+    assert_eq!(None, open_range.end);
+    assert_eq!("3-", open_range.to_string());
+
+    assert_eq!(None, TimeRange::parse("1.0"));
+    assert_eq!(None, TimeRange::parse("a-b"));
+}
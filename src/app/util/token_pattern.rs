@@ -0,0 +1,92 @@
+use anyhow::{bail, Result};
+use graphannis::graph::AnnoKey;
+use regex::Regex;
+
+use crate::{api::token_helper::TOKEN_KEY, app::widgets::Token};
+
+/// A simple, AQL-like pattern that can be matched against the labels of a
+/// [`Token`].
+///
+/// The following syntax is supported:
+/// - `/regex/` or just `regex` matches the token value,
+/// - `name=/regex/` or `ns:name=/regex/` matches the value of an annotation,
+/// - `name="value"` or `ns:name="value"` matches the exact value of an annotation.
+///
+/// Regular expressions always need to match the whole value, like in AQL.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenPattern {
+    ns: Option<String>,
+    name: Option<String>,
+    value: Regex,
+}
+
+impl TokenPattern {
+    pub(crate) fn parse(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            bail!("Empty pattern");
+        }
+
+        // A quoted value or regex can contain "=" itself and is never split
+        // into key and value
+        let (key, value) = match pattern.split_once('=') {
+            _ if is_literal(pattern, '"') || is_literal(pattern, '/') => (None, pattern),
+            Some((key, value))
+                if !key.is_empty()
+                    && key
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == ':' || c == '_' || c == '-') =>
+            {
+                (Some(key), value)
+            }
+            _ => (None, pattern),
+        };
+
+        let value = if is_literal(value, '"') {
+            regex::escape(&value[1..value.len() - 1])
+        } else if is_literal(value, '/') {
+            value[1..value.len() - 1].to_string()
+        } else if key.is_some() {
+            bail!("Annotation values must be given as \"value\" or /regex/");
+        } else {
+            value.to_string()
+        };
+        let value = Regex::new(&format!("^(?:{value})$"))?;
+
+        let (ns, name) = match key {
+            Some("tok") | None => (None, None),
+            Some(key) => match key.rsplit_once(':') {
+                Some((ns, name)) => (Some(ns.to_string()), Some(name.to_string())),
+                None => (None, Some(key.to_string())),
+            },
+        };
+
+        Ok(Self { ns, name, value })
+    }
+
+    pub(crate) fn matches(&self, token: &Token) -> bool {
+        if let Some(name) = &self.name {
+            token
+                .labels
+                .iter()
+                .any(|(key, value)| self.matches_key(key, name) && self.value.is_match(value))
+        } else {
+            token
+                .labels
+                .get(&TOKEN_KEY)
+                .is_some_and(|value| self.value.is_match(value))
+        }
+    }
+
+    fn matches_key(&self, key: &AnnoKey, name: &str) -> bool {
+        key.name == name && self.ns.as_ref().is_none_or(|ns| key.ns == ns.as_str())
+    }
+}
+
+/// Whether the value is enclosed by the given delimiter, e.g. `"value"`.
+fn is_literal(value: &str, delimiter: char) -> bool {
+    value.len() > 1 && value.starts_with(delimiter) && value.ends_with(delimiter)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use graphannis::graph::AnnoKey;
+
+use super::*;
+
+fn example_token() -> Token {
+    let mut labels = BTreeMap::new();
+    labels.insert(TOKEN_KEY.as_ref().clone(), "Häuser".to_string());
+    labels.insert(
+        AnnoKey {
+            ns: "default_ns".into(),
+            name: "pos".into(),
+        },
+        "NN".to_string(),
+    );
+    Token {
+        node_name: "doc#t1".to_string(),
+        start: 0,
+        end: 0,
+        labels,
+    }
+}
+
+#[test]
+fn match_token_value() {
+    let t = example_token();
+    assert!(TokenPattern::parse("Häuser").unwrap().matches(&t));
+    assert!(TokenPattern::parse("/H.*/").unwrap().matches(&t));
+    assert!(TokenPattern::parse("tok=\"Häuser\"").unwrap().matches(&t));
+    assert!(!TokenPattern::parse("Haus").unwrap().matches(&t));
+}
+
+#[test]
+fn match_annotation_value() {
+    let t = example_token();
+    assert!(TokenPattern::parse("pos=\"NN\"").unwrap().matches(&t));
+    assert!(TokenPattern::parse("default_ns:pos=/N./")
+        .unwrap()
+        .matches(&t));
+    assert!(!TokenPattern::parse("other:pos=/N./").unwrap().matches(&t));
+    assert!(!TokenPattern::parse("lemma=/.*/").unwrap().matches(&t));
+    assert!(TokenPattern::parse("pos=NN").is_err());
+}
+
+#[test]
+fn literal_values_with_equal_sign() {
+    let mut t = example_token();
+    t.labels
+        .insert(TOKEN_KEY.as_ref().clone(), "a=b".to_string());
+    assert!(TokenPattern::parse("/a=b/").unwrap().matches(&t));
+    assert!(TokenPattern::parse("/a=.*/").unwrap().matches(&t));
+    assert!(TokenPattern::parse("\"a=b\"").unwrap().matches(&t));
+    assert!(TokenPattern::parse("tok=/a=b/").unwrap().matches(&t));
+    assert!(!TokenPattern::parse("pos=/a=b/").unwrap().matches(&t));
+}
@@ -0,0 +1,223 @@
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A token created from plain text, including the whitespace that follows it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct TextToken {
+    pub(crate) value: String,
+    pub(crate) whitespace_after: String,
+}
+
+impl TextToken {
+    fn new<S: Into<String>>(value: S) -> Self {
+        Self {
+            value: value.into(),
+            whitespace_after: String::new(),
+        }
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(
+            c,
+            '„' | '“'
+                | '”'
+                | '‘'
+                | '’'
+                | '‚'
+                | '«'
+                | '»'
+                | '‹'
+                | '›'
+                | '…'
+                | '–'
+                | '—'
+                | '¿'
+                | '¡'
+        )
+}
+
+/// Splits plain text into token.
+pub(crate) trait Tokenizer {
+    fn tokenize(&self, text: &str) -> Vec<TextToken>;
+}
+
+/// The built-in tokenizers, which can be selected in the settings and for
+/// each corpus.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub(crate) enum TokenizerKind {
+    /// Split only at whitespace.
+    Whitespace,
+    /// Split at whitespace and separate punctuation from the words.
+    #[default]
+    Punctuation,
+    /// Split at the Unicode word boundaries, which also separates the
+    /// characters of scripts without whitespace between words.
+    UnicodeWords,
+    /// Each match of the regular expression is a token.
+    Regex(String),
+}
+
+impl TokenizerKind {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            TokenizerKind::Whitespace => "Whitespace",
+            TokenizerKind::Punctuation => "Whitespace and punctuation",
+            TokenizerKind::UnicodeWords => "Unicode word boundaries",
+            TokenizerKind::Regex(_) => "Regular expression",
+        }
+    }
+
+    /// Recommended tokenizers for some languages.
+    pub(crate) fn language_presets() -> Vec<(&'static str, TokenizerKind)> {
+        vec![
+            (
+                "English",
+                TokenizerKind::Regex(r"\w+(?:[-.]\w+)*|'\w+|[^\w\s]".to_string()),
+            ),
+            ("German", TokenizerKind::Punctuation),
+            (
+                "French",
+                TokenizerKind::Regex(r"\w+'|\w+(?:-\w+)*|[^\w\s]".to_string()),
+            ),
+            ("Chinese/Japanese", TokenizerKind::UnicodeWords),
+        ]
+    }
+
+    pub(crate) fn create(&self) -> Result<Box<dyn Tokenizer>> {
+        let result: Box<dyn Tokenizer> = match self {
+            TokenizerKind::Whitespace => Box::new(WhitespaceTokenizer),
+            TokenizerKind::Punctuation => Box::new(PunctuationTokenizer),
+            TokenizerKind::UnicodeWords => Box::new(UnicodeWordTokenizer),
+            TokenizerKind::Regex(pattern) => Box::new(RegexTokenizer(Regex::new(pattern)?)),
+        };
+        Ok(result)
+    }
+}
+
+struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<TextToken> {
+        let mut result = Vec::new();
+        split_at_whitespace(text, &mut result, |chunk, result| {
+            result.push(TextToken::new(chunk))
+        });
+        result
+    }
+}
+
+struct PunctuationTokenizer;
+
+impl Tokenizer for PunctuationTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<TextToken> {
+        tokenize(text)
+    }
+}
+
+struct UnicodeWordTokenizer;
+
+impl Tokenizer for UnicodeWordTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<TextToken> {
+        let mut result: Vec<TextToken> = Vec::new();
+        for segment in text.split_word_bounds() {
+            if segment.chars().all(char::is_whitespace) {
+                if let Some(last) = result.last_mut() {
+                    last.whitespace_after.push_str(segment);
+                }
+            } else {
+                result.push(TextToken::new(segment));
+            }
+        }
+        result
+    }
+}
+
+struct RegexTokenizer(Regex);
+
+impl Tokenizer for RegexTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<TextToken> {
+        let mut result = Vec::new();
+        let mut last_end = 0;
+        for m in self.0.find_iter(text) {
+            if m.is_empty() {
+                continue;
+            }
+            // Text that is not matched is split at whitespace
+            split_at_whitespace(&text[last_end..m.start()], &mut result, |chunk, result| {
+                result.push(TextToken::new(chunk))
+            });
+            result.push(TextToken::new(m.as_str()));
+            last_end = m.end();
+        }
+        split_at_whitespace(&text[last_end..], &mut result, |chunk, result| {
+            result.push(TextToken::new(chunk))
+        });
+        result
+    }
+}
+
+/// Split plain text into token at whitespace and separate leading and
+/// trailing punctuation from the words.
+pub(crate) fn tokenize(text: &str) -> Vec<TextToken> {
+    let mut result = Vec::new();
+    split_at_whitespace(text, &mut result, tokenize_chunk);
+    result
+}
+
+/// Split the text at whitespace, add the whitespace to the last token of
+/// `result` and let `split_chunk` create the token for the text in between.
+fn split_at_whitespace<F>(text: &str, result: &mut Vec<TextToken>, split_chunk: F)
+where
+    F: Fn(&str, &mut Vec<TextToken>),
+{
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !chunk.is_empty() {
+                split_chunk(&chunk, result);
+                chunk.clear();
+            }
+            // Leading whitespace of the text is ignored
+            if let Some(last) = result.last_mut() {
+                last.whitespace_after.push(c);
+            }
+        } else {
+            chunk.push(c);
+        }
+    }
+    if !chunk.is_empty() {
+        split_chunk(&chunk, result);
+    }
+}
+
+fn tokenize_chunk(chunk: &str, result: &mut Vec<TextToken>) {
+    let chars: Vec<char> = chunk.chars().collect();
+    let word_start = chars
+        .iter()
+        .position(|c| !is_punctuation(*c))
+        .unwrap_or(chars.len());
+    let word_end = chars
+        .iter()
+        .rposition(|c| !is_punctuation(*c))
+        .map(|p| p + 1)
+        .unwrap_or(word_start);
+
+    for c in &chars[0..word_start] {
+        result.push(TextToken::new(c.to_string()));
+    }
+    if word_start < word_end {
+        result.push(TextToken::new(
+            chars[word_start..word_end].iter().collect::<String>(),
+        ));
+    }
+    for c in &chars[word_end.max(word_start)..] {
+        result.push(TextToken::new(c.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests;
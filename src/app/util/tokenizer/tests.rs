@@ -0,0 +1,69 @@
+use pretty_assertions::assert_eq;
+
+use super::{tokenize, TextToken, TokenizerKind};
+
+fn values(token: &[TextToken]) -> Vec<&str> {
+    token.iter().map(|t| t.value.as_str()).collect()
+}
+
+#[test]
+fn split_whitespace_and_punctuation() {
+    let token = tokenize("  Is this (really) an e-mail? Yes.");
+    assert_eq!(
+        vec!["Is", "this", "(", "really", ")", "an", "e-mail", "?", "Yes", "."],
+        values(&token)
+    );
+    assert_eq!(" ", token[0].whitespace_after);
+    assert_eq!("", token[2].whitespace_after);
+    assert_eq!(" ", token[4].whitespace_after);
+    assert_eq!("", token[9].whitespace_after);
+}
+
+#[test]
+fn only_punctuation() {
+    let token = tokenize("...\n");
+    assert_eq!(vec![".", ".", "."], values(&token));
+    assert_eq!("\n", token[2].whitespace_after);
+}
+
+#[test]
+fn whitespace_tokenizer_keeps_punctuation() {
+    let token = TokenizerKind::Whitespace
+        .create()
+        .unwrap()
+        .tokenize("Is this (really) an e-mail?");
+    assert_eq!(
+        vec!["Is", "this", "(really)", "an", "e-mail?"],
+        values(&token)
+    );
+}
+
+#[test]
+fn unicode_words_without_whitespace() {
+    let token = TokenizerKind::UnicodeWords
+        .create()
+        .unwrap()
+        .tokenize("我爱北京。 Hello world");
+    assert_eq!(
+        vec!["我", "爱", "北", "京", "。", "Hello", "world"],
+        values(&token)
+    );
+    assert_eq!(" ", token[4].whitespace_after);
+}
+
+#[test]
+fn english_preset_separates_clitics() {
+    let (_, english) = TokenizerKind::language_presets()
+        .into_iter()
+        .find(|(language, _)| *language == "English")
+        .unwrap();
+    let token = english.create().unwrap().tokenize("Don't stop-gap it.");
+    assert_eq!(vec!["Don", "'t", "stop-gap", "it", "."], values(&token));
+    assert_eq!(" ", token[1].whitespace_after);
+    assert_eq!("", token[3].whitespace_after);
+}
+
+#[test]
+fn invalid_regex_is_an_error() {
+    assert!(TokenizerKind::Regex("(".to_string()).create().is_err());
+}
@@ -0,0 +1,123 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use time::{Date, Month, OffsetDateTime};
+
+/// The type of the values of an annotation, which determines how the value
+/// is edited. Annotations without a type have free text values.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum ValueType {
+    /// A number, e.g. a certainty score, in an optional range.
+    Number {
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+        /// Only allow whole numbers.
+        #[serde(default)]
+        integer: bool,
+    },
+    /// Either `true` or `false`.
+    Boolean,
+    /// A calendar date in the form `YYYY-MM-DD`.
+    Date,
+}
+
+impl ValueType {
+    /// Check that the value can be represented by this type.
+    pub(crate) fn validate(&self, value: &str) -> Result<()> {
+        match self {
+            ValueType::Number { min, max, integer } => {
+                let number = parse_number(value, *integer)?;
+                if let Some(min) = min.filter(|min| number < *min) {
+                    bail!("The value {value} is smaller than the minimum {min}");
+                }
+                if let Some(max) = max.filter(|max| number > *max) {
+                    bail!("The value {value} is larger than the maximum {max}");
+                }
+            }
+            ValueType::Boolean => {
+                if value != "true" && value != "false" {
+                    bail!("The value {value} must be either \"true\" or \"false\"");
+                }
+            }
+            ValueType::Date => {
+                parse_date(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The value used when an annotation of this type is added, e.g. the
+    /// minimum of a number range.
+    pub(crate) fn default_value(&self) -> String {
+        match self {
+            ValueType::Number { min, max, integer } => {
+                let number = min.unwrap_or_else(|| max.map_or(0.0, |max| max.min(0.0)));
+                format_number(number, *integer)
+            }
+            ValueType::Boolean => "false".to_string(),
+            ValueType::Date => format_date(OffsetDateTime::now_utc().date()),
+        }
+    }
+}
+
+fn parse_number(value: &str, integer: bool) -> Result<f64> {
+    if integer {
+        let number: i64 = value
+            .trim()
+            .parse()
+            .with_context(|| format!("The value {value} is not a whole number"))?;
+        Ok(number as f64)
+    } else {
+        let number: f64 = value
+            .trim()
+            .parse()
+            .with_context(|| format!("The value {value} is not a number"))?;
+        if !number.is_finite() {
+            bail!("The value {value} is not a finite number");
+        }
+        Ok(number)
+    }
+}
+
+/// Format the number without a fractional part for whole numbers.
+pub(crate) fn format_number(number: f64, integer: bool) -> String {
+    if integer {
+        format!("{}", number.round() as i64)
+    } else {
+        number.to_string()
+    }
+}
+
+/// Parse a number value, e.g. to show it in an editor.
+pub(crate) fn number_value(value: &str, integer: bool) -> Option<f64> {
+    parse_number(value, integer).ok()
+}
+
+/// Parse a date of the form `YYYY-MM-DD`.
+pub(crate) fn parse_date(value: &str) -> Result<Date> {
+    let invalid = || format!("The value {value} is not a date of the form YYYY-MM-DD");
+    let mut parts = value.trim().splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        bail!(invalid());
+    };
+    let year: i32 = year.parse().with_context(invalid)?;
+    let month: u8 = month.parse().with_context(invalid)?;
+    let day: u8 = day.parse().with_context(invalid)?;
+    let month = Month::try_from(month).with_context(invalid)?;
+    let date = Date::from_calendar_date(year, month, day).with_context(invalid)?;
+    Ok(date)
+}
+
+pub(crate) fn format_date(date: Date) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day()
+    )
+}
+
+#[cfg(test)]
+mod tests;
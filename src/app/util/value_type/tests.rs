@@ -0,0 +1,76 @@
+use pretty_assertions::assert_eq;
+use time::{Date, Month};
+
+use super::{format_date, parse_date, ValueType};
+
+#[test]
+fn validate_numbers() {
+    let certainty = ValueType::Number {
+        min: Some(0.0),
+        max: Some(1.0),
+        integer: false,
+    };
+    assert!(certainty.validate("0.75").is_ok());
+    assert!(certainty.validate("1").is_ok());
+    assert!(certainty.validate("1.5").is_err());
+    assert!(certainty.validate("-0.1").is_err());
+    assert!(certainty.validate("high").is_err());
+    assert!(certainty.validate("NaN").is_err());
+    assert_eq!("0", certainty.default_value());
+
+    let count = ValueType::Number {
+        min: None,
+        max: Some(-5.0),
+        integer: true,
+    };
+    assert!(count.validate("-10").is_ok());
+    assert!(count.validate("-7.5").is_err());
+    assert_eq!("-5", count.default_value());
+}
+
+#[test]
+fn validate_booleans() {
+    assert!(ValueType::Boolean.validate("true").is_ok());
+    assert!(ValueType::Boolean.validate("false").is_ok());
+    assert!(ValueType::Boolean.validate("yes").is_err());
+}
+
+#[test]
+fn parse_and_format_dates() {
+    let date = parse_date("2024-02-29").unwrap();
+    assert_eq!(
+        Date::from_calendar_date(2024, Month::February, 29).unwrap(),
+        date
+    );
+    assert_eq!("2024-02-29", format_date(date));
+    assert_eq!("0987-01-05", format_date(parse_date("987-1-5").unwrap()));
+
+    assert!(ValueType::Date.validate("2023-02-29").is_err());
+    assert!(ValueType::Date.validate("2023-13-01").is_err());
+    assert!(ValueType::Date.validate("01.02.2023").is_err());
+    assert!(ValueType::Date
+        .validate(&ValueType::Date.default_value())
+        .is_ok());
+}
+
+#[test]
+fn deserialize_value_types() {
+    let value_types: std::collections::BTreeMap<String, ValueType> = toml::from_str(
+        r#"
+certainty = { type = "number", min = 0.0, max = 1.0 }
+reviewed = { type = "boolean" }
+"default_ns::date" = { type = "date" }
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        Some(&ValueType::Number {
+            min: Some(0.0),
+            max: Some(1.0),
+            integer: false
+        }),
+        value_types.get("certainty")
+    );
+    assert_eq!(Some(&ValueType::Boolean), value_types.get("reviewed"));
+    assert_eq!(Some(&ValueType::Date), value_types.get("default_ns::date"));
+}
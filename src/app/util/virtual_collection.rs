@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use graphannis::{aql, graph::NodeID, AnnotationGraph};
+use graphannis_core::graph::NODE_NAME_KEY;
+
+use super::corpus_structure::ordered_documents;
+use crate::api::token_helper::TokenHelper;
+
+/// A document with at least one match of the query of a virtual collection.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CollectionDocument {
+    pub(crate) document: NodeID,
+    pub(crate) document_name: String,
+    /// Index of the first base token covered by any match in the document.
+    pub(crate) first_match: usize,
+    pub(crate) number_of_matches: usize,
+}
+
+/// Execute the AQL query and return the documents with matches in the order
+/// of the corpus tree. A match belongs to the document of the first token
+/// covered by its nodes.
+///
+/// All components of the graph must have been loaded.
+pub(crate) fn matching_documents(
+    graph: &AnnotationGraph,
+    query: &str,
+) -> Result<Vec<CollectionDocument>> {
+    let query = aql::parse(query, false)?;
+    let tok_helper = TokenHelper::new(graph)?;
+    let node_annos = graph.get_node_annos();
+
+    let documents = ordered_documents(graph)?;
+    let mut token_position: HashMap<NodeID, (usize, usize)> = HashMap::new();
+    for (doc_idx, document) in documents.iter().enumerate() {
+        let document_name = node_annos
+            .get_value_for_item(document, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let token = tok_helper.get_ordered_token(&document_name, None)?;
+        for (idx, t) in token.iter().enumerate() {
+            token_position.insert(*t, (doc_idx, idx));
+        }
+    }
+
+    // The first match position and number of matches by document index
+    let mut matches: HashMap<usize, (usize, usize)> = HashMap::new();
+    for m in aql::execute_query_on_graph(graph, &query, true, None)? {
+        let m = m?;
+        let mut first: Option<(usize, usize)> = None;
+        for n in m.iter() {
+            let covered = if tok_helper.is_token(n.node)? {
+                vec![n.node]
+            } else {
+                tok_helper.covered_token(n.node)?
+            };
+            for t in covered {
+                if let Some((doc, pos)) = token_position.get(&t).copied() {
+                    first = match first {
+                        Some((d, start)) if d == doc => Some((d, start.min(pos))),
+                        Some(existing) => Some(existing),
+                        None => Some((doc, pos)),
+                    };
+                }
+            }
+        }
+        if let Some((doc, pos)) = first {
+            let entry = matches.entry(doc).or_insert((pos, 0));
+            entry.0 = entry.0.min(pos);
+            entry.1 += 1;
+        }
+    }
+
+    let mut result = Vec::with_capacity(matches.len());
+    for (doc_idx, document) in documents.into_iter().enumerate() {
+        if let Some((first_match, number_of_matches)) = matches.get(&doc_idx).copied() {
+            result.push(CollectionDocument {
+                document,
+                document_name: node_annos
+                    .get_value_for_item(&document, &NODE_NAME_KEY)?
+                    .context("Missing node name")?
+                    .to_string(),
+                first_match,
+                number_of_matches,
+            });
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests;
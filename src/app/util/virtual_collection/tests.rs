@@ -0,0 +1,70 @@
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use graphannis_core::graph::ANNIS_NS;
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::matching_documents;
+
+/// Adds a second document with the same token.
+fn add_second_document(updates: &mut GraphUpdate) {
+    updates
+        .add_event(UpdateEvent::AddNode {
+            node_name: "root/doc2".to_string(),
+            node_type: "corpus".to_string(),
+        })
+        .unwrap();
+    updates
+        .add_event(UpdateEvent::AddNodeLabel {
+            node_name: "root/doc2".to_string(),
+            anno_ns: ANNIS_NS.to_string(),
+            anno_name: "doc".to_string(),
+            anno_value: "doc2".to_string(),
+        })
+        .unwrap();
+    updates
+        .add_event(UpdateEvent::AddEdge {
+            source_node: "root/doc2".to_string(),
+            target_node: "root".to_string(),
+            layer: ANNIS_NS.to_string(),
+            component_type: "PartOf".to_string(),
+            component_name: "".to_string(),
+        })
+        .unwrap();
+    example_generator::create_tokens(updates, Some("root/doc2"));
+}
+
+#[test]
+fn documents_with_matches() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_second_document(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    let result = matching_documents(&graph, "tok=\"more\"").unwrap();
+    let documents: Vec<_> = result
+        .iter()
+        .map(|d| (d.document_name.as_str(), d.first_match, d.number_of_matches))
+        .collect();
+    assert_eq!(vec![("root/doc1", 3, 1), ("root/doc2", 3, 1)], documents);
+
+    // The first match of a document is used, even if it is found later
+    let result = matching_documents(&graph, "tok=/(be|it)/").unwrap();
+    assert_eq!(2, result.len());
+    assert_eq!(6, result[0].first_match);
+    assert_eq!(2, result[0].number_of_matches);
+}
+
+#[test]
+fn no_matching_documents() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    add_second_document(&mut updates);
+    let graph = example_generator::create_graph(&mut updates);
+    assert_eq!(
+        Vec::<super::CollectionDocument>::new(),
+        matching_documents(&graph, "tok=\"missing\"").unwrap()
+    );
+    assert!(matching_documents(&graph, "tok=").is_err());
+}
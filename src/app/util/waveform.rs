@@ -0,0 +1,165 @@
+use std::io::{ErrorKind, Read};
+
+use anyhow::{bail, Context, Result};
+
+#[cfg(test)]
+mod tests;
+
+/// Number of peak values that are computed for each second of audio.
+pub(crate) const PEAKS_PER_SECOND: usize = 200;
+
+/// Format chunks are 16, 18 or 40 bytes long. Larger chunks are rejected
+/// instead of allocating the size given in the file header.
+const MAX_FORMAT_CHUNK_SIZE: usize = 64;
+
+/// A reduced representation of an audio file that can be painted as waveform.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Waveform {
+    /// The maximum absolute amplitude in the range 0.0 to 1.0 for each
+    /// 1/[`PEAKS_PER_SECOND`] second.
+    pub(crate) peaks: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SampleFormat {
+    Int,
+    Float,
+}
+
+impl Waveform {
+    /// Duration of the audio in seconds.
+    pub(crate) fn duration(&self) -> f64 {
+        self.peaks.len() as f64 / PEAKS_PER_SECOND as f64
+    }
+
+    /// Get the peak at the given time in seconds.
+    pub(crate) fn peak_at(&self, time: f64) -> Option<f32> {
+        if time < 0.0 {
+            return None;
+        }
+        let idx = (time * PEAKS_PER_SECOND as f64) as usize;
+        self.peaks.get(idx).copied()
+    }
+
+    /// Read an uncompressed WAV (RIFF) file with integer or float samples.
+    pub(crate) fn from_wav<R: Read>(mut input: R) -> Result<Self> {
+        let mut header = [0u8; 12];
+        input.read_exact(&mut header)?;
+        if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+            bail!("Not a WAV file");
+        }
+
+        let mut format = None;
+        loop {
+            let mut chunk_header = [0u8; 8];
+            match input.read_exact(&mut chunk_header) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => bail!("Missing data chunk"),
+                Err(e) => return Err(e.into()),
+            }
+            let chunk_size = u32::from_le_bytes(chunk_header[4..8].try_into()?) as usize;
+            match &chunk_header[0..4] {
+                b"fmt " => {
+                    if !(16..=MAX_FORMAT_CHUNK_SIZE).contains(&chunk_size) {
+                        bail!("Invalid format chunk");
+                    }
+                    let mut fmt = Vec::with_capacity(MAX_FORMAT_CHUNK_SIZE);
+                    let padded_size = (chunk_size + chunk_size % 2) as u64;
+                    (&mut input).take(padded_size).read_to_end(&mut fmt)?;
+                    if fmt.len() < chunk_size {
+                        bail!("Truncated format chunk");
+                    }
+                    let audio_format = u16::from_le_bytes([fmt[0], fmt[1]]);
+                    let channels = u16::from_le_bytes([fmt[2], fmt[3]]) as usize;
+                    let sample_rate = u32::from_le_bytes(fmt[4..8].try_into()?) as usize;
+                    let bits = u16::from_le_bytes([fmt[14], fmt[15]]) as usize;
+                    let sample_format = match (audio_format, bits) {
+                        (1, 8 | 16 | 24 | 32) => SampleFormat::Int,
+                        (3, 32) => SampleFormat::Float,
+                        _ => bail!(
+                            "Unsupported WAV sample format {audio_format} with {bits} bits per sample"
+                        ),
+                    };
+                    if channels == 0 || sample_rate == 0 {
+                        bail!("Invalid WAV format");
+                    }
+                    format = Some((sample_format, channels, sample_rate, bits / 8));
+                }
+                b"data" => {
+                    let (sample_format, channels, sample_rate, bytes_per_sample) =
+                        format.context("Data chunk before format chunk")?;
+                    return Self::read_samples(
+                        input.take(chunk_size as u64),
+                        sample_format,
+                        channels,
+                        sample_rate,
+                        bytes_per_sample,
+                    );
+                }
+                _ => {
+                    // Skip unknown chunks
+                    let skip = (chunk_size + chunk_size % 2) as u64;
+                    std::io::copy(&mut (&mut input).take(skip), &mut std::io::sink())?;
+                }
+            }
+        }
+    }
+
+    fn read_samples<R: Read>(
+        mut input: R,
+        sample_format: SampleFormat,
+        channels: usize,
+        sample_rate: usize,
+        bytes_per_sample: usize,
+    ) -> Result<Self> {
+        let frames_per_peak = (sample_rate / PEAKS_PER_SECOND).max(1);
+        let block_size = frames_per_peak * channels * bytes_per_sample;
+        let mut block = vec![0u8; block_size];
+        let mut peaks = Vec::new();
+        loop {
+            let mut filled = 0;
+            while filled < block_size {
+                let read = input.read(&mut block[filled..])?;
+                if read == 0 {
+                    break;
+                }
+                filled += read;
+            }
+            if filled < bytes_per_sample {
+                break;
+            }
+            let peak = block[0..filled]
+                .chunks_exact(bytes_per_sample)
+                .map(|s| sample_amplitude(s, sample_format))
+                .fold(0.0_f32, f32::max);
+            peaks.push(peak.min(1.0));
+            if filled < block_size {
+                break;
+            }
+        }
+        Ok(Waveform { peaks })
+    }
+}
+
+/// The absolute amplitude of a single little-endian sample in the range 0.0 to 1.0.
+fn sample_amplitude(sample: &[u8], sample_format: SampleFormat) -> f32 {
+    match (sample_format, sample.len()) {
+        (SampleFormat::Float, 4) => {
+            f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]).abs()
+        }
+        // 8 bit samples are unsigned
+        (SampleFormat::Int, 1) => (sample[0] as f32 - 128.0).abs() / 128.0,
+        (SampleFormat::Int, 2) => {
+            (i16::from_le_bytes([sample[0], sample[1]]) as f32).abs() / i16::MAX as f32
+        }
+        (SampleFormat::Int, 3) => {
+            let value = i32::from_le_bytes([0, sample[0], sample[1], sample[2]]) >> 8;
+            (value as f32).abs() / 8_388_607.0
+        }
+        (SampleFormat::Int, 4) => {
+            (i32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]) as f32).abs()
+                / i32::MAX as f32
+        }
+        _ => 0.0,
+    }
+}
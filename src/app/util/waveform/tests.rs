@@ -0,0 +1,59 @@
+use pretty_assertions::assert_eq;
+
+use super::{Waveform, PEAKS_PER_SECOND};
+
+/// Creates a mono WAV file with 16 bit samples.
+fn create_wav(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+    let data_size = (samples.len() * 2) as u32;
+    let mut result = Vec::new();
+    result.extend_from_slice(b"RIFF");
+    result.extend_from_slice(&(36 + data_size).to_le_bytes());
+    result.extend_from_slice(b"WAVE");
+    result.extend_from_slice(b"fmt ");
+    result.extend_from_slice(&16u32.to_le_bytes());
+    result.extend_from_slice(&1u16.to_le_bytes());
+    result.extend_from_slice(&1u16.to_le_bytes());
+    result.extend_from_slice(&sample_rate.to_le_bytes());
+    result.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    result.extend_from_slice(&2u16.to_le_bytes());
+    result.extend_from_slice(&16u16.to_le_bytes());
+    result.extend_from_slice(b"data");
+    result.extend_from_slice(&data_size.to_le_bytes());
+    for s in samples {
+        result.extend_from_slice(&s.to_le_bytes());
+    }
+    result
+}
+
+#[test]
+fn read_wav_peaks() {
+    let sample_rate = 8000;
+    // One second of silence followed by one second with the maximum amplitude
+    let mut samples = vec![0; sample_rate];
+    samples.extend(std::iter::repeat_n(i16::MAX, sample_rate));
+    let wav = create_wav(sample_rate as u32, &samples);
+
+    let waveform = Waveform::from_wav(wav.as_slice()).unwrap();
+    assert_eq!(2 * PEAKS_PER_SECOND, waveform.peaks.len());
+    assert_eq!(2.0, waveform.duration());
+    assert_eq!(Some(0.0), waveform.peak_at(0.5));
+    assert_eq!(Some(1.0), waveform.peak_at(1.5));
+    assert_eq!(None, waveform.peak_at(2.5));
+}
+
+#[test]
+fn reject_other_files() {
+    assert!(Waveform::from_wav(&b"no audio file at all"[..]).is_err());
+}
+
+#[test]
+fn reject_oversized_format_chunk() {
+    let mut wav = create_wav(8000, &[0; 10]);
+    // Declare a format chunk of almost 4 GiB
+    wav[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Waveform::from_wav(wav.as_slice()).is_err());
+
+    // A format chunk that is cut off
+    let wav = create_wav(8000, &[0; 10]);
+    assert!(Waveform::from_wav(&wav[..30]).is_err());
+}
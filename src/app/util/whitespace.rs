@@ -0,0 +1,315 @@
+use std::fmt::Display;
+
+use anyhow::{Context, Result};
+use graphannis::{
+    graph::{AnnoKey, NodeID},
+    update::{GraphUpdate, UpdateEvent},
+    AnnotationGraph,
+};
+use graphannis_core::graph::{ANNIS_NS, NODE_NAME_KEY};
+
+use super::corpus_structure::ordered_documents;
+use crate::api::token_helper::{TokenHelper, TOKEN_KEY};
+
+pub(crate) const WHITESPACE_BEFORE: &str = "tok-whitespace-before";
+pub(crate) const WHITESPACE_AFTER: &str = "tok-whitespace-after";
+
+/// Punctuation that is never preceded by whitespace.
+const CLOSING_PUNCTUATION: &[char] = &[',', '.', ')', ']', '}', '…'];
+/// Punctuation that is preceded by whitespace only in some languages, e.g.
+/// in French.
+const HIGH_PUNCTUATION: &[char] = &[';', ':', '!', '?', '»'];
+
+/// How whitespace before punctuation is handled in the language of the corpus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub(crate) enum PunctuationSpacing {
+    /// No whitespace before any punctuation, e.g. in English or German.
+    #[default]
+    NoSpace,
+    /// A space before `;`, `:`, `!`, `?` and `»`, but not before other
+    /// punctuation.
+    French,
+}
+
+impl PunctuationSpacing {
+    pub(crate) const ALL: [PunctuationSpacing; 2] =
+        [PunctuationSpacing::NoSpace, PunctuationSpacing::French];
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            PunctuationSpacing::NoSpace => "No space before punctuation (e.g. English, German)",
+            PunctuationSpacing::French => "Space before ; : ! ? (French)",
+        }
+    }
+
+    /// Whether the token must be preceded by whitespace (`Some(true)`), must
+    /// not be preceded by whitespace (`Some(false)`) or if both is possible.
+    fn space_before(&self, token_value: &str) -> Option<bool> {
+        let is_punctuation =
+            |set: &[char]| !token_value.is_empty() && token_value.chars().all(|c| set.contains(&c));
+        if is_punctuation(CLOSING_PUNCTUATION) {
+            Some(false)
+        } else if is_punctuation(HIGH_PUNCTUATION) {
+            Some(*self == PunctuationSpacing::French)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WhitespaceProblem {
+    /// Several spaces or tabs instead of a single space.
+    RepeatedSpace,
+    /// Line breaks other than `\n` or spaces at the end of a line.
+    LineEnding,
+    /// Whitespace before punctuation that is not used in the language.
+    SpaceBeforePunctuation,
+    /// No whitespace before punctuation that needs it in the language.
+    MissingSpaceBeforePunctuation,
+}
+
+impl Display for WhitespaceProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            WhitespaceProblem::RepeatedSpace => "Repeated spaces",
+            WhitespaceProblem::LineEnding => "Inconsistent line ending",
+            WhitespaceProblem::SpaceBeforePunctuation => "Space before punctuation",
+            WhitespaceProblem::MissingSpaceBeforePunctuation => "Missing space before punctuation",
+        };
+        write!(f, "{description}")
+    }
+}
+
+/// A whitespace annotation of a token and the value it should be changed to.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WhitespaceChange {
+    pub(crate) node_name: String,
+    /// Either [`WHITESPACE_BEFORE`] or [`WHITESPACE_AFTER`].
+    pub(crate) anno_name: &'static str,
+    pub(crate) old_value: Option<String>,
+    pub(crate) new_value: String,
+    pub(crate) problem: WhitespaceProblem,
+}
+
+/// Use `\n` as line ending, remove spaces at the end and beginning of lines
+/// and replace repeated spaces and tabs with a single space.
+fn normalize_whitespace(value: &str) -> (String, Option<WhitespaceProblem>) {
+    let unified = value.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<&str> = unified.split('\n').collect();
+    let last_line = lines.len() - 1;
+    let trimmed: Vec<&str> = lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            // Only the whitespace next to a line break is removed
+            let line = if idx < last_line {
+                line.trim_end_matches([' ', '\t'])
+            } else {
+                line
+            };
+            if idx > 0 {
+                line.trim_start_matches([' ', '\t'])
+            } else {
+                line
+            }
+        })
+        .collect();
+    let line_endings_normalized = trimmed.join("\n");
+    let mut result = String::with_capacity(line_endings_normalized.len());
+    let mut last_was_space = false;
+    for c in line_endings_normalized.chars() {
+        let is_space = c == ' ' || c == '\t';
+        if is_space && last_was_space {
+            continue;
+        }
+        result.push(if is_space { ' ' } else { c });
+        last_was_space = is_space;
+    }
+
+    let problem = if line_endings_normalized != value {
+        Some(WhitespaceProblem::LineEnding)
+    } else if result != value {
+        Some(WhitespaceProblem::RepeatedSpace)
+    } else {
+        None
+    };
+    (result, problem)
+}
+
+/// The whitespace annotations of a single token.
+struct TokenWhitespace {
+    node_name: String,
+    value: String,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl TokenWhitespace {
+    fn load(graph: &AnnotationGraph, node: NodeID) -> Result<Self> {
+        let node_annos = graph.get_node_annos();
+        let get = |name: &str| -> Result<Option<String>> {
+            let key = AnnoKey {
+                ns: ANNIS_NS.into(),
+                name: name.into(),
+            };
+            Ok(node_annos
+                .get_value_for_item(&node, &key)?
+                .map(|v| v.to_string()))
+        };
+        Ok(Self {
+            node_name: node_annos
+                .get_value_for_item(&node, &NODE_NAME_KEY)?
+                .context("Missing node name")?
+                .to_string(),
+            value: node_annos
+                .get_value_for_item(&node, &TOKEN_KEY)?
+                .unwrap_or_default()
+                .to_string(),
+            before: get(WHITESPACE_BEFORE)?,
+            after: get(WHITESPACE_AFTER)?,
+        })
+    }
+}
+
+/// Add a change of the whitespace annotation of the token, unless it already
+/// has the value. A later change of the same annotation replaces the earlier
+/// one.
+fn push_change(
+    changes: &mut Vec<WhitespaceChange>,
+    token: &TokenWhitespace,
+    anno_name: &'static str,
+    new_value: String,
+    problem: WhitespaceProblem,
+) {
+    changes.retain(|c| !(c.node_name == token.node_name && c.anno_name == anno_name));
+    let old_value = if anno_name == WHITESPACE_BEFORE {
+        &token.before
+    } else {
+        &token.after
+    };
+    if old_value.as_ref() != Some(&new_value) {
+        changes.push(WhitespaceChange {
+            node_name: token.node_name.clone(),
+            anno_name,
+            old_value: old_value.clone(),
+            new_value,
+            problem,
+        });
+    }
+}
+
+/// The value of the whitespace annotation after the changes that have been
+/// collected so far.
+fn current_value(
+    changes: &[WhitespaceChange],
+    token: &TokenWhitespace,
+    anno_name: &str,
+) -> Option<String> {
+    if let Some(change) = changes
+        .iter()
+        .find(|c| c.node_name == token.node_name && c.anno_name == anno_name)
+    {
+        Some(change.new_value.clone())
+    } else if anno_name == WHITESPACE_BEFORE {
+        token.before.clone()
+    } else {
+        token.after.clone()
+    }
+}
+
+/// Find all whitespace annotations of the base token that are not
+/// consistent, and the values they should be normalized to.
+pub(crate) fn whitespace_changes(
+    graph: &AnnotationGraph,
+    spacing: PunctuationSpacing,
+) -> Result<Vec<WhitespaceChange>> {
+    let tok_helper = TokenHelper::new(graph)?;
+    if tok_helper.get_ordering_gs(None).is_none() {
+        return Ok(Vec::new());
+    }
+    let node_annos = graph.get_node_annos();
+    let mut changes = Vec::new();
+    for doc in ordered_documents(graph)? {
+        let doc_name = node_annos
+            .get_value_for_item(&doc, &NODE_NAME_KEY)?
+            .context("Missing node name")?;
+        let mut token = Vec::new();
+        for t in tok_helper.get_ordered_token(&doc_name, None)? {
+            token.push(TokenWhitespace::load(graph, t)?);
+        }
+
+        for (idx, t) in token.iter().enumerate() {
+            for (anno_name, value) in [(WHITESPACE_BEFORE, &t.before), (WHITESPACE_AFTER, &t.after)]
+            {
+                if let Some(value) = value {
+                    let (normalized, problem) = normalize_whitespace(value);
+                    if let Some(problem) = problem {
+                        push_change(&mut changes, t, anno_name, normalized, problem);
+                    }
+                }
+            }
+
+            // Check the whitespace between the previous token and this one
+            let Some(previous) = idx.checked_sub(1).and_then(|i| token.get(i)) else {
+                continue;
+            };
+            let previous_after = current_value(&changes, previous, WHITESPACE_AFTER);
+            let before = current_value(&changes, t, WHITESPACE_BEFORE);
+            let gap = format!(
+                "{}{}",
+                previous_after.as_deref().unwrap_or_default(),
+                before.as_deref().unwrap_or_default()
+            );
+            match spacing.space_before(&t.value) {
+                Some(false) if !gap.is_empty() && !gap.contains('\n') => {
+                    let problem = WhitespaceProblem::SpaceBeforePunctuation;
+                    if previous_after.is_some() {
+                        push_change(
+                            &mut changes,
+                            previous,
+                            WHITESPACE_AFTER,
+                            String::new(),
+                            problem,
+                        );
+                    }
+                    if before.is_some() {
+                        push_change(&mut changes, t, WHITESPACE_BEFORE, String::new(), problem);
+                    }
+                }
+                Some(true) if gap.is_empty() => {
+                    let problem = WhitespaceProblem::MissingSpaceBeforePunctuation;
+                    push_change(
+                        &mut changes,
+                        previous,
+                        WHITESPACE_AFTER,
+                        " ".to_string(),
+                        problem,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// The changeset that applies the given changes.
+pub(crate) fn normalization_update<'a, I>(changes: I) -> Result<GraphUpdate>
+where
+    I: IntoIterator<Item = &'a WhitespaceChange>,
+{
+    let mut update = GraphUpdate::new();
+    for c in changes {
+        update.add_event(UpdateEvent::AddNodeLabel {
+            node_name: c.node_name.clone(),
+            anno_ns: ANNIS_NS.to_string(),
+            anno_name: c.anno_name.to_string(),
+            anno_value: c.new_value.clone(),
+        })?;
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,118 @@
+use graphannis::update::{GraphUpdate, UpdateEvent};
+use pretty_assertions::assert_eq;
+
+use crate::app::util::example_generator;
+
+use super::{
+    normalization_update, normalize_whitespace, whitespace_changes, PunctuationSpacing,
+    WhitespaceProblem, WHITESPACE_AFTER, WHITESPACE_BEFORE,
+};
+
+/// Adds the token with the whitespace after each token to the example
+/// document.
+fn create_token(updates: &mut GraphUpdate, token: &[(&str, &str)]) {
+    for (idx, (value, whitespace_after)) in token.iter().enumerate() {
+        example_generator::create_token_node(
+            updates,
+            &format!("root/doc1#t{idx}"),
+            value,
+            None,
+            Some(whitespace_after),
+            Some("root/doc1"),
+        );
+        if idx > 0 {
+            updates
+                .add_event(UpdateEvent::AddEdge {
+                    source_node: format!("root/doc1#t{}", idx - 1),
+                    target_node: format!("root/doc1#t{idx}"),
+                    layer: "annis".to_string(),
+                    component_type: "Ordering".to_string(),
+                    component_name: "".to_string(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+#[test]
+fn normalize_spaces_and_line_endings() {
+    assert_eq!((" ".to_string(), None), normalize_whitespace(" "));
+    assert_eq!(
+        (" ".to_string(), Some(WhitespaceProblem::RepeatedSpace)),
+        normalize_whitespace(" \t ")
+    );
+    assert_eq!(
+        ("\n\n".to_string(), Some(WhitespaceProblem::LineEnding)),
+        normalize_whitespace(" \r\n\r\n ")
+    );
+}
+
+#[test]
+fn find_inconsistent_whitespace() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    create_token(
+        &mut updates,
+        &[
+            ("Hello", "  "),
+            ("world", " "),
+            (",", " "),
+            ("how", " "),
+            ("are", " "),
+            ("you", ""),
+            ("?", ""),
+        ],
+    );
+    let graph = example_generator::create_graph(&mut updates);
+
+    let changes = whitespace_changes(&graph, PunctuationSpacing::NoSpace).unwrap();
+    let summary: Vec<_> = changes
+        .iter()
+        .map(|c| {
+            (
+                c.node_name.as_str(),
+                c.anno_name,
+                c.new_value.as_str(),
+                c.problem,
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                "root/doc1#t0",
+                WHITESPACE_AFTER,
+                " ",
+                WhitespaceProblem::RepeatedSpace
+            ),
+            (
+                "root/doc1#t1",
+                WHITESPACE_AFTER,
+                "",
+                WhitespaceProblem::SpaceBeforePunctuation
+            ),
+        ],
+        summary
+    );
+
+    // French needs a space before the question mark
+    let changes = whitespace_changes(&graph, PunctuationSpacing::French).unwrap();
+    let missing = changes
+        .iter()
+        .find(|c| c.problem == WhitespaceProblem::MissingSpaceBeforePunctuation)
+        .unwrap();
+    assert_eq!("root/doc1#t5", missing.node_name);
+    assert_eq!(" ", missing.new_value);
+    assert!(changes.iter().all(|c| c.anno_name != WHITESPACE_BEFORE));
+
+    // Applying the changes resolves all problems
+    let mut graph = graph;
+    let mut update = normalization_update(&changes).unwrap();
+    graph.apply_update(&mut update, |_| {}).unwrap();
+    assert_eq!(
+        0,
+        whitespace_changes(&graph, PunctuationSpacing::French)
+            .unwrap()
+            .len()
+    );
+}
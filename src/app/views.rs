@@ -1,16 +1,118 @@
-use egui::Ui;
+use std::collections::HashSet;
+
+use egui::{KeyboardShortcut, Ui};
 use graphannis::graph::NodeID;
 
+use super::{
+    project::history::UndoGroup,
+    util::{language::Language, tokenizer::TokenizerKind},
+};
+
+pub(crate) mod component_browser;
+pub(crate) mod detached;
+pub(crate) mod diagnostics;
 pub(crate) mod edit;
+pub(crate) mod frequency;
+pub(crate) mod job_queue;
+pub(crate) mod kwic_export;
+pub(crate) mod map_values;
+pub(crate) mod new_document;
+pub(crate) mod ngrams;
+pub(crate) mod ordering_chain;
+pub(crate) mod provenance;
+pub(crate) mod rename_annotation;
+pub(crate) mod review;
+pub(crate) mod shortcuts;
 pub(crate) mod start;
+pub(crate) mod text_search;
+pub(crate) mod virtual_collections;
+pub(crate) mod whitespace;
+
+/// A keyboard shortcut and the action it triggers.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ShortcutEntry {
+    /// Where the shortcut is active, e.g. in a specific editor.
+    pub(crate) context: &'static str,
+    pub(crate) description: String,
+    pub(crate) shortcut: KeyboardShortcut,
+}
+
+/// What the user currently looks at in an editor. It is restored when the
+/// editor is recreated, e.g. after undo or redo.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ViewState {
+    /// Name of the document or corpus node that is shown in the editor.
+    pub(crate) node_name: String,
+    pub(crate) selected_nodes: HashSet<String>,
+    pub(crate) active_layer: Option<String>,
+    /// Index of the first visible base token.
+    pub(crate) first_visible_token: usize,
+}
 
 pub(crate) trait Editor: Send {
     fn show(&mut self, ui: &mut Ui);
     fn has_pending_updates(&self) -> bool;
-    fn apply_pending_updates_for_editor(&mut self);
+    fn apply_pending_updates_for_editor(&mut self) {
+        self.apply_pending_updates_in_group(None);
+    }
+    /// Apply the pending updates as changesets of the `group`, which are
+    /// undone together with the other changesets of the group.
+    fn apply_pending_updates_in_group(&mut self, group: Option<UndoGroup>);
+    /// Number of changes that have not been applied to the corpus yet.
+    fn pending_update_count(&self) -> usize {
+        usize::from(self.has_pending_updates())
+    }
+    /// Only apply pending changes when explicitly requested, e.g. on save.
+    fn set_manual_commit(&mut self, _manual_commit: bool) {}
+    /// Tokenizer used for text that is inserted as new token.
+    fn set_tokenizer(&mut self, _tokenizer: TokenizerKind) {}
+    /// The language of the shown document, which can determine its tokenizer.
+    fn language(&self) -> Option<Language> {
+        None
+    }
+    /// Namespace that is pre-filled for new annotations.
+    fn set_default_namespace(&mut self, _namespace: String) {}
+    /// Ask the user before nodes are deleted.
+    fn set_confirm_deletion(&mut self, _confirm: bool) {}
+    /// Show the details of token and spans when hovering them.
+    fn set_token_tooltips(&mut self, _show: bool) {}
+    /// True once after the user chose not to be asked again before deleting
+    /// nodes.
+    fn take_disabled_confirmation(&mut self) -> bool {
+        false
+    }
+    /// The document and index of the base token the user wants to open,
+    /// returned once after it has been requested in the editor.
+    fn take_navigation_request(&mut self) -> Option<(NodeID, usize)> {
+        None
+    }
+    /// The selection and viewport of the editor.
+    fn view_state(&self) -> Option<ViewState> {
+        None
+    }
+    /// Restore the selection and viewport, if the state belongs to the same
+    /// node as the editor.
+    fn restore_view_state(&mut self, _state: ViewState) {}
+    /// Forget all changes that have not been applied yet.
+    fn discard_pending_updates(&mut self) {}
     fn get_selected_corpus_node(&self) -> Option<NodeID>;
+    /// The name of the node that is selected in the editor, if exactly one is selected.
+    fn get_selected_node_name(&self) -> Option<String> {
+        None
+    }
+    /// The documents before and after the edited one in the corpus tree.
+    fn get_neighbour_documents(&self) -> (Option<NodeID>, Option<NodeID>) {
+        (None, None)
+    }
     fn consume_shortcuts(&mut self, _ctx: &egui::Context) {}
+    /// All shortcuts handled by [`Editor::consume_shortcuts`].
+    fn shortcuts(&self) -> Vec<ShortcutEntry> {
+        Vec::new()
+    }
+    /// Show information about the state of the editor in the status bar.
+    fn status_bar(&self, _ui: &mut egui::Ui) {}
     fn add_edit_menu_entries(&mut self, _ui: &mut egui::Ui) {}
+    fn add_file_menu_entries(&mut self, _ui: &mut egui::Ui) {}
 
     fn any_mut(&mut self) -> &mut dyn std::any::Any;
 }
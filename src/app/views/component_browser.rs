@@ -0,0 +1,172 @@
+use egui::{RichText, TextEdit, Widget};
+use egui_extras::{Column, TableBuilder};
+use graphannis_core::graph::ANNIS_NS;
+
+use crate::{app::project::refactoring::ComponentInfo, AnnatomicApp};
+
+/// State of the window that lists all components of the selected corpus.
+#[derive(Default, Clone)]
+pub(crate) struct ComponentBrowserDialog {
+    pub(crate) open: bool,
+    pub(crate) components: Option<Vec<ComponentInfo>>,
+    /// Index of the component whose layer and name are edited.
+    renamed: Option<usize>,
+    new_layer: String,
+    new_name: String,
+    /// Index of the component that is deleted after a confirmation.
+    deleted: Option<usize>,
+}
+
+enum ComponentAction {
+    Rename(ComponentInfo, String, String),
+    Delete(ComponentInfo),
+}
+
+/// Show the component browser if the window has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.component_browser.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.component_browser = ComponentBrowserDialog::default();
+        return;
+    }
+    if app.component_browser.components.is_none() {
+        app.component_browser.components = Some(Vec::new());
+        app.project.load_components(|components, app| {
+            app.component_browser.components = Some(components);
+        });
+    }
+
+    let mut open = app.component_browser.open;
+    let mut action = None;
+    let read_only = app.project.read_only;
+    egui::Window::new("Components")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.component_browser;
+            let components = dialog.components.clone().unwrap_or_default();
+            ui.label(format!("{} components", components.len()));
+            TableBuilder::new(ui)
+                .striped(true)
+                .max_scroll_height(400.0)
+                .column(Column::auto())
+                .columns(Column::auto().at_least(80.0).resizable(true), 2)
+                .columns(Column::auto(), 3)
+                .header(20.0, |mut header| {
+                    for title in ["Type", "Layer", "Name", "Edges", "Storage", ""] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(24.0, components.len(), |mut row| {
+                        let idx = row.index();
+                        let info = &components[idx];
+                        let c = &info.component;
+                        let is_renamed = dialog.renamed == Some(idx);
+                        row.col(|ui| {
+                            ui.label(c.get_type().to_string());
+                        });
+                        row.col(|ui| {
+                            if is_renamed {
+                                TextEdit::singleline(&mut dialog.new_layer)
+                                    .desired_width(100.0)
+                                    .ui(ui);
+                            } else {
+                                ui.label(c.layer.as_str());
+                            }
+                        });
+                        row.col(|ui| {
+                            if is_renamed {
+                                TextEdit::singleline(&mut dialog.new_name)
+                                    .desired_width(100.0)
+                                    .ui(ui);
+                            } else {
+                                ui.label(c.name.as_str());
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(info.edges.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(&info.implementation);
+                        });
+                        row.col(|ui| {
+                            ui.add_enabled_ui(!read_only, |ui| {
+                                if is_renamed {
+                                    if ui.button("Apply").clicked() {
+                                        action = Some(ComponentAction::Rename(
+                                            info.clone(),
+                                            dialog.new_layer.trim().to_string(),
+                                            dialog.new_name.trim().to_string(),
+                                        ));
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        dialog.renamed = None;
+                                    }
+                                } else if dialog.deleted == Some(idx) {
+                                    if ui
+                                        .button(
+                                            RichText::new("Confirm delete")
+                                                .color(ui.visuals().error_fg_color),
+                                        )
+                                        .clicked()
+                                    {
+                                        action = Some(ComponentAction::Delete(info.clone()));
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        dialog.deleted = None;
+                                    }
+                                } else {
+                                    if ui.button("Rename…").clicked() {
+                                        dialog.renamed = Some(idx);
+                                        dialog.deleted = None;
+                                        dialog.new_layer = c.layer.to_string();
+                                        dialog.new_name = c.name.to_string();
+                                    }
+                                    if ui
+                                        .button("Delete")
+                                        .on_hover_text("Delete all edges of the component")
+                                        .clicked()
+                                    {
+                                        dialog.deleted = Some(idx);
+                                        dialog.renamed = None;
+                                    }
+                                }
+                            });
+                        });
+                    });
+                });
+            if let Some(info) = dialog
+                .renamed
+                .or(dialog.deleted)
+                .and_then(|idx| components.get(idx))
+            {
+                if info.component.layer == ANNIS_NS {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "Components of the ANNIS layer are needed to show the documents.",
+                    );
+                }
+            }
+        });
+
+    match action {
+        Some(ComponentAction::Rename(info, layer, name)) => {
+            app.apply_pending_updates();
+            app.project
+                .change_component(info.component, Some((layer, name)));
+            app.component_browser.renamed = None;
+        }
+        Some(ComponentAction::Delete(info)) => {
+            app.apply_pending_updates();
+            app.project.change_component(info.component, None);
+            app.component_browser.deleted = None;
+        }
+        None => {}
+    }
+    app.component_browser.open = open;
+}
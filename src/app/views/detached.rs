@@ -0,0 +1,189 @@
+use egui::{Id, ViewportBuilder, ViewportClass, ViewportCommand, ViewportId};
+use egui_notify::Toast;
+use graphannis::graph::NodeID;
+
+use crate::{
+    app::{
+        editors::document_editor::DocumentEditor, util::language::document_tokenizer,
+        views::Editor, MainView,
+    },
+    AnnatomicApp,
+};
+
+/// A document editor that is shown in its own native window, e.g. on a second
+/// monitor, in addition to the main view.
+pub(crate) struct DetachedDocument {
+    viewport_id: ViewportId,
+    node_id: NodeID,
+    /// Name of the document, once the editor has been created.
+    name: Option<String>,
+    /// `None` while the editor is created.
+    pub(crate) editor: Option<Box<dyn Editor>>,
+}
+
+impl DetachedDocument {
+    fn title(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{name} - annatomic"),
+            None => "Loading document - annatomic".to_string(),
+        }
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if let Some(editor) = &mut self.editor {
+            // Separate the IDs of the widgets from the ones of the main view
+            ui.push_id(self.viewport_id, |ui| editor.show(ui));
+        } else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Loading document");
+            });
+        }
+    }
+}
+
+fn job_title(node_id: NodeID) -> String {
+    format!("Opening document {node_id} in a new window")
+}
+
+/// Create the editor of the detached document in the background. An
+/// existing editor is shown until the new one replaces it.
+fn create_editor(app: &mut AnnatomicApp, node_id: NodeID) {
+    let Some(corpus) = &app.project.selected_corpus else {
+        return;
+    };
+    let title = job_title(node_id);
+    if app.jobs.has_active_job_with_title(&title) {
+        return;
+    }
+    let view_state = app
+        .detached_documents
+        .iter()
+        .find(|d| d.node_id == node_id)
+        .and_then(|d| d.editor.as_ref())
+        .and_then(|editor| editor.view_state());
+    let corpus_cache = app.project.corpus_cache.clone();
+    let location = corpus.location.clone();
+    let jobs = app.jobs.clone();
+    let manual_commit = app.settings.manual_commit;
+    let confirm_deletion = app.settings.confirm_deletion;
+    let token_tooltips = app.settings.token_tooltips;
+    let corpus_tokenizer = app.tokenizer_of_selected_corpus();
+    let default_tokenizer = app.settings.tokenizer.clone();
+    let layer_settings = app.layer_settings_of_selected_corpus();
+    let namespace = app.default_namespace_of_selected_corpus();
+    app.jobs.add_background(
+        &title,
+        move |_| {
+            let graph = corpus_cache.get(&location)?;
+            let mut document_editor = DocumentEditor::create_from_graph(node_id, graph, jobs)?;
+            document_editor.apply_layer_settings(&layer_settings);
+            document_editor.set_manual_commit(manual_commit);
+            document_editor.set_tokenizer(document_tokenizer(
+                corpus_tokenizer.as_ref(),
+                document_editor.language(),
+                &default_tokenizer,
+            ));
+            document_editor.set_default_namespace(namespace);
+            document_editor.set_confirm_deletion(confirm_deletion);
+            document_editor.set_token_tooltips(token_tooltips);
+            if let Some(view_state) = view_state {
+                document_editor.restore_view_state(view_state);
+            }
+            if let Err(e) = document_editor.load_linked_media(&location) {
+                log::warn!("Could not load linked media: {e:?}");
+            }
+            Ok(document_editor)
+        },
+        move |document_editor, app| {
+            let detached = app
+                .detached_documents
+                .iter_mut()
+                .find(|d| d.node_id == node_id);
+            // The window might have been closed in the meantime
+            if let Some(detached) = detached {
+                detached.name = document_editor.view_state().map(|s| s.node_name);
+                detached.editor = Some(Box::new(document_editor));
+            }
+        },
+    );
+}
+
+/// Open the document in an additional window. If the document is already
+/// shown in a window, this window is focused instead.
+pub(crate) fn open(ctx: &egui::Context, app: &mut AnnatomicApp, node_id: NodeID) {
+    if matches!(app.main_view, MainView::EditDocument { node_id: current } if current == node_id) {
+        app.notifier.add_toast(Toast::info(
+            "The document is already open in the main window.",
+        ));
+        return;
+    }
+    if let Some(detached) = app.detached_documents.iter().find(|d| d.node_id == node_id) {
+        ctx.send_viewport_cmd_to(detached.viewport_id, ViewportCommand::Focus);
+        return;
+    }
+    app.detached_documents.push(DetachedDocument {
+        viewport_id: ViewportId::from_hash_of(("detached-document", node_id)),
+        node_id,
+        name: None,
+        editor: None,
+    });
+    create_editor(app, node_id);
+}
+
+/// Create the editors of all windows again, e.g. after an undo. Windows with
+/// pending changes are not reloaded, so these changes are not lost.
+pub(crate) fn reload(app: &mut AnnatomicApp) {
+    let node_ids: Vec<NodeID> = app
+        .detached_documents
+        .iter()
+        .filter(|d| {
+            d.editor
+                .as_ref()
+                .is_some_and(|editor| !editor.has_pending_updates())
+        })
+        .map(|d| d.node_id)
+        .collect();
+    for node_id in node_ids {
+        create_editor(app, node_id);
+    }
+}
+
+/// Show the windows of all detached documents. If the platform does not
+/// support multiple native windows, they are shown inside the main window.
+pub(crate) fn show_windows(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    let mut closed = Vec::new();
+    for (idx, detached) in app.detached_documents.iter_mut().enumerate() {
+        let title = detached.title();
+        let builder = ViewportBuilder::default()
+            .with_title(&title)
+            .with_inner_size([900.0, 600.0]);
+        ctx.show_viewport_immediate(detached.viewport_id, builder, |ctx, class| {
+            if matches!(class, ViewportClass::Embedded) {
+                let mut open = true;
+                egui::Window::new(&title)
+                    .id(Id::new(detached.viewport_id))
+                    .open(&mut open)
+                    .default_size([600.0, 400.0])
+                    .show(ctx, |ui| detached.show(ui));
+                if !open {
+                    closed.push(idx);
+                }
+            } else {
+                if let Some(editor) = &mut detached.editor {
+                    editor.consume_shortcuts(ctx);
+                }
+                egui::CentralPanel::default().show(ctx, |ui| detached.show(ui));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    closed.push(idx);
+                }
+            }
+        });
+    }
+    for idx in closed.into_iter().rev() {
+        let mut detached = app.detached_documents.remove(idx);
+        if let Some(editor) = &mut detached.editor {
+            editor.apply_pending_updates_for_editor();
+        }
+    }
+}
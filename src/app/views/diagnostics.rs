@@ -0,0 +1,129 @@
+use egui::RichText;
+use egui_notify::Toast;
+use rfd::FileDialog;
+
+use crate::{
+    app::{
+        util::diagnostics::{environment_info, recent_logs, write_diagnostic_bundle},
+        MainView,
+    },
+    AnnatomicApp,
+};
+
+/// State of the window that creates a diagnostic bundle for bug reports.
+#[derive(Default, Clone)]
+pub(crate) struct DiagnosticsDialog {
+    pub(crate) open: bool,
+}
+
+/// A summary of the application state. Corpus data and annotations are not
+/// included, only the names and locations of the corpora.
+fn application_state(app: &AnnatomicApp) -> String {
+    let mut result = String::new();
+    result.push_str(&format!("Active profile: {}\n", app.active_profile));
+    result.push_str(&format!(
+        "Main view: {}\n",
+        match app.main_view {
+            MainView::Start => "Start".to_string(),
+            MainView::EditDocument { node_id } => format!("Document {node_id}"),
+        }
+    ));
+    result.push_str(&format!(
+        "Selected corpus: {}\n",
+        app.project
+            .selected_corpus
+            .as_ref()
+            .map(|c| c.name.as_str())
+            .unwrap_or("(none)")
+    ));
+    result.push_str(&format!("Read-only: {}\n", app.project.read_only));
+    result.push_str(&format!(
+        "Missing corpora: {}\n",
+        app.project.missing_corpora.join(", ")
+    ));
+    result.push_str(&format!("Pending changes: {}\n", app.has_pending_updates()));
+    let (undo_memory, undo_steps) = app.project.undo_memory_usage();
+    result.push_str(&format!(
+        "Undo history: {undo_steps} steps, {} MB\n",
+        undo_memory / 1_000_000
+    ));
+    result
+}
+
+fn create_bundle(app: &mut AnnatomicApp) {
+    let dlg = FileDialog::new()
+        .set_can_create_directories(true)
+        .set_file_name("annatomic-diagnostics.zip")
+        .add_filter("ZIP (*.zip)", &["zip"]);
+    if let Some(path) = dlg.save_file() {
+        let project = app.project_configuration().to_toml();
+        let result = project.and_then(|project| {
+            write_diagnostic_bundle(
+                &path,
+                &[
+                    ("environment.txt", environment_info()),
+                    ("state.txt", application_state(app)),
+                    ("project.toml", project),
+                    ("log.txt", recent_logs().join("\n")),
+                ],
+            )
+        });
+        match result {
+            Ok(()) => {
+                app.notifier.add_toast(Toast::info(format!(
+                    "Created diagnostic bundle {}",
+                    path.to_string_lossy()
+                )));
+                app.diagnostics.open = false;
+            }
+            Err(e) => app.notifier.report_error(e),
+        }
+    }
+}
+
+/// Show the window that explains the content of the diagnostic bundle
+/// before it is created.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.diagnostics.open {
+        return;
+    }
+    let mut open = app.diagnostics.open;
+    let mut create = false;
+    egui::Window::new("Create diagnostic bundle")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(
+                "The diagnostic bundle is a ZIP file you can attach to a bug report. It contains:",
+            );
+            for content in [
+                "the version of annatomic and your operating system,",
+                "the recent log messages,",
+                "the settings and profile of the application,",
+                "the names and locations of your corpora and their tags.",
+            ] {
+                ui.label(format!("• {content}"));
+            }
+            ui.label(
+                RichText::new(
+                    "Documents and annotations are not included. The file is only saved on \
+                     your computer and not sent anywhere. Please check its content before \
+                     sharing it.",
+                )
+                .strong(),
+            );
+            ui.horizontal(|ui| {
+                if ui.button("Create bundle…").clicked() {
+                    create = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    app.diagnostics.open = false;
+                }
+            });
+        });
+    if create {
+        create_bundle(app);
+    }
+    app.diagnostics.open &= open;
+}
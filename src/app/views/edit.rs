@@ -1,12 +1,47 @@
 use anyhow::Result;
-use egui::Ui;
+use egui::{Button, Ui};
 
-use crate::{app::MainView, AnnatomicApp};
+use crate::{
+    app::{MainView, NEXT_DOCUMENT_SHORTCUT, PREVIOUS_DOCUMENT_SHORTCUT},
+    AnnatomicApp,
+};
 
 pub(crate) fn show(ui: &mut Ui, app: &mut AnnatomicApp) -> Result<()> {
-    if ui.link("Go back to main view").clicked() {
-        app.change_view(MainView::Start);
-    }
+    let (previous, next) = app
+        .current_editor
+        .get()
+        .map(|editor| editor.get_neighbour_documents())
+        .unwrap_or_default();
+    ui.horizontal(|ui| {
+        if ui.link("Go back to main view").clicked() {
+            app.change_view(MainView::Start);
+        }
+        ui.separator();
+        let previous_button = ui
+            .add_enabled(
+                previous.is_some(),
+                Button::new(format!(
+                    "{} Previous document",
+                    egui_phosphor::regular::CARET_LEFT
+                )),
+            )
+            .on_hover_text(ui.ctx().format_shortcut(&PREVIOUS_DOCUMENT_SHORTCUT));
+        if let Some(previous) = previous.filter(|_| previous_button.clicked()) {
+            app.navigate_to_document(previous);
+        }
+        let next_button = ui
+            .add_enabled(
+                next.is_some(),
+                Button::new(format!(
+                    "Next document {}",
+                    egui_phosphor::regular::CARET_RIGHT
+                )),
+            )
+            .on_hover_text(ui.ctx().format_shortcut(&NEXT_DOCUMENT_SHORTCUT));
+        if let Some(next) = next.filter(|_| next_button.clicked()) {
+            app.navigate_to_document(next);
+        }
+    });
 
     if let Some(editor) = app.current_editor.get_mut() {
         editor.show(ui);
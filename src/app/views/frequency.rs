@@ -0,0 +1,222 @@
+use std::fs::File;
+
+use egui::{ComboBox, TextEdit, Ui, Widget};
+use egui_extras::{Column, TableBuilder};
+use graphannis::graph::AnnoKey;
+use rfd::FileDialog;
+
+use crate::{
+    app::{
+        util::frequency::{frequencies, FrequencyTable},
+        views::rename_annotation::key_label,
+    },
+    AnnatomicApp,
+};
+
+const JOB_TITLE: &str = "Computing frequencies";
+
+/// State of the window to compute frequency tables of annotation values.
+#[derive(Default, Clone)]
+pub(crate) struct FrequencyDialog {
+    pub(crate) open: bool,
+    available_keys: Option<Vec<AnnoKey>>,
+    first_key: Option<AnnoKey>,
+    second_key: Option<AnnoKey>,
+    /// AQL query to restrict the counted nodes.
+    subset_query: String,
+    table: Option<FrequencyTable>,
+    /// Column the table is sorted by, `None` is the count column.
+    sort_column: Option<usize>,
+    sort_ascending: bool,
+}
+
+fn compute(app: &mut AnnatomicApp) {
+    let dialog = &app.frequency;
+    let keys: Vec<AnnoKey> = dialog
+        .first_key
+        .iter()
+        .chain(dialog.second_key.iter())
+        .cloned()
+        .collect();
+    let subset_query = Some(dialog.subset_query.trim().to_string()).filter(|q| !q.is_empty());
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        let corpus_cache = app.project.corpus_cache.clone();
+        app.jobs.add(
+            JOB_TITLE,
+            move |job| {
+                job.allow_cancel();
+                job.update_message("Loading corpus if necessary");
+                let graph = corpus_cache.get(&selected_corpus.location)?;
+                let mut graph = graph.write();
+                if subset_query.is_some() {
+                    graph.ensure_loaded_all()?;
+                }
+                job.update_message("Counting annotation values");
+                frequencies(&graph, &keys, subset_query.as_deref(), &job)
+            },
+            |table, app| {
+                app.frequency.sort_column = None;
+                app.frequency.sort_ascending = false;
+                app.frequency.table = Some(table);
+            },
+        );
+    }
+}
+
+fn export_csv(app: &mut AnnatomicApp) {
+    let Some(table) = &app.frequency.table else {
+        return;
+    };
+    let dlg = FileDialog::new().add_filter("CSV (*.csv)", &["csv"]);
+    if let Some(path) = dlg.save_file() {
+        let result = File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| table.write_csv(file));
+        app.notifier.report_result(result);
+    }
+}
+
+fn key_selection(ui: &mut Ui, label: &str, keys: &[AnnoKey], selected: &mut Option<AnnoKey>) {
+    ComboBox::from_label(label)
+        .selected_text(selected.as_ref().map(key_label).unwrap_or_default())
+        .show_ui(ui, |ui| {
+            ui.selectable_value(selected, None, "");
+            for key in keys {
+                ui.selectable_value(selected, Some(key.clone()), key_label(key));
+            }
+        });
+}
+
+fn show_table(ui: &mut Ui, dialog: &mut FrequencyDialog) {
+    let Some(table) = &mut dialog.table else {
+        return;
+    };
+    ui.label(format!(
+        "{} combinations, {} nodes in total",
+        table.rows.len(),
+        table.total()
+    ));
+    let mut clicked_column = None;
+    let sort_marker = |column: Option<usize>| {
+        if dialog.sort_column == column {
+            if dialog.sort_ascending {
+                " ⏶"
+            } else {
+                " ⏷"
+            }
+        } else {
+            ""
+        }
+    };
+    TableBuilder::new(ui)
+        .striped(true)
+        .max_scroll_height(400.0)
+        .columns(
+            Column::auto().at_least(100.0).resizable(true),
+            table.keys.len(),
+        )
+        .column(Column::remainder())
+        .header(20.0, |mut header| {
+            for (idx, key) in table.keys.iter().enumerate() {
+                header.col(|ui| {
+                    if ui
+                        .button(format!("{}{}", key_label(key), sort_marker(Some(idx))))
+                        .clicked()
+                    {
+                        clicked_column = Some(Some(idx));
+                    }
+                });
+            }
+            header.col(|ui| {
+                if ui.button(format!("Count{}", sort_marker(None))).clicked() {
+                    clicked_column = Some(None);
+                }
+            });
+        })
+        .body(|body| {
+            body.rows(18.0, table.rows.len(), |mut row| {
+                let (values, count) = &table.rows[row.index()];
+                for value in values {
+                    row.col(|ui| {
+                        ui.label(value);
+                    });
+                }
+                row.col(|ui| {
+                    ui.label(count.to_string());
+                });
+            });
+        });
+    if let Some(column) = clicked_column {
+        if dialog.sort_column == column {
+            dialog.sort_ascending = !dialog.sort_ascending;
+        } else {
+            dialog.sort_column = column;
+            // Counts are most interesting in descending order, values in ascending
+            dialog.sort_ascending = column.is_some();
+        }
+        table.sort_by(dialog.sort_column, dialog.sort_ascending);
+    }
+}
+
+/// Show the frequency analysis if the window has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.frequency.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.frequency = FrequencyDialog::default();
+        return;
+    }
+    if app.frequency.available_keys.is_none() {
+        app.frequency.available_keys = Some(Vec::new());
+        app.project.load_annotation_keys(|keys, app| {
+            app.frequency.available_keys = Some(keys);
+        });
+    }
+
+    let mut open = app.frequency.open;
+    let mut start_computation = false;
+    let mut export = false;
+    egui::Window::new("Frequency analysis")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.frequency;
+            let keys = dialog.available_keys.clone().unwrap_or_default();
+            key_selection(ui, "Annotation", &keys, &mut dialog.first_key);
+            key_selection(
+                ui,
+                "Combined with (optional)",
+                &keys,
+                &mut dialog.second_key,
+            );
+            TextEdit::singleline(&mut dialog.subset_query)
+                .hint_text("Optional AQL query to count only the matched nodes")
+                .desired_width(350.0)
+                .ui(ui);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(dialog.first_key.is_some(), egui::Button::new("Count"))
+                    .clicked()
+                {
+                    start_computation = true;
+                }
+                if ui
+                    .add_enabled(dialog.table.is_some(), egui::Button::new("Export CSV…"))
+                    .clicked()
+                {
+                    export = true;
+                }
+            });
+            ui.separator();
+            show_table(ui, dialog);
+        });
+
+    if start_computation && !app.jobs.has_active_job_with_title(JOB_TITLE) {
+        compute(app);
+    }
+    if export {
+        export_csv(app);
+    }
+    app.frequency.open = open;
+}
@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Grid, RichText, ScrollArea};
+
+use crate::{
+    app::job_executor::{JobOutcome, JobPriority},
+    AnnatomicApp,
+};
+
+/// State of the window that lists the queued, running and recently finished
+/// jobs.
+#[derive(Default, Clone)]
+pub(crate) struct JobQueueDialog {
+    pub(crate) open: bool,
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 60 {
+        format!("{}:{:02} min", seconds / 60, seconds % 60)
+    } else {
+        format!("{:.1} s", duration.as_secs_f32())
+    }
+}
+
+fn priority_label(priority: JobPriority) -> &'static str {
+    match priority {
+        JobPriority::Interactive => "Edit",
+        JobPriority::Batch => "Batch",
+        JobPriority::Background => "Background",
+    }
+}
+
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.job_queue.open {
+        return;
+    }
+    let mut open = app.job_queue.open;
+    let active_jobs = app.jobs.active_jobs();
+    let finished_jobs = app.jobs.recently_finished();
+    egui::Window::new("Jobs")
+        .open(&mut open)
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                let (queued, running): (Vec<_>, Vec<_>) = active_jobs
+                    .iter()
+                    .partition(|job| job.running_for.is_none());
+
+                ui.heading("Running");
+                if running.is_empty() {
+                    ui.label("No job is running.");
+                }
+                Grid::new("running-jobs").striped(true).show(ui, |ui| {
+                    for job in running {
+                        ui.spinner();
+                        ui.label(&job.title)
+                            .on_hover_text(job.message.clone().unwrap_or_default());
+                        ui.label(priority_label(job.priority));
+                        ui.label(format_duration(job.running_for.unwrap_or_default()));
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                ui.heading("Queued");
+                if queued.is_empty() {
+                    ui.label("No job is waiting to be started.");
+                }
+                Grid::new("queued-jobs").striped(true).show(ui, |ui| {
+                    for job in queued {
+                        ui.label(egui_phosphor::regular::HOURGLASS);
+                        ui.label(&job.title);
+                        ui.label(priority_label(job.priority));
+                        ui.label(format!(
+                            "waiting {}",
+                            format_duration(job.queued_for.unwrap_or_default())
+                        ));
+                        if ui
+                            .button("Remove")
+                            .on_hover_text("Remove the job before it is started")
+                            .clicked()
+                        {
+                            app.jobs.remove_queued(&job.title);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                ui.heading("Recently finished");
+                if finished_jobs.is_empty() {
+                    ui.label("No job has finished yet.");
+                }
+                let now = Instant::now();
+                Grid::new("finished-jobs").striped(true).show(ui, |ui| {
+                    for job in finished_jobs.iter() {
+                        let (outcome, color) = match job.outcome {
+                            JobOutcome::Succeeded => ("finished", Color32::DARK_GREEN),
+                            JobOutcome::Failed => ("failed", Color32::RED),
+                            JobOutcome::Cancelled => ("cancelled", Color32::ORANGE),
+                        };
+                        ui.label(RichText::new(outcome).color(color));
+                        ui.label(&job.title);
+                        ui.label(format_duration(job.duration))
+                            .on_hover_text(format!(
+                                "Waited {} in the queue",
+                                format_duration(job.waited)
+                            ));
+                        ui.label(format!(
+                            "{} ago",
+                            format_duration(now.saturating_duration_since(job.finished_at))
+                        ));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    if !active_jobs.is_empty() {
+        // Update the durations while jobs are running
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+    app.job_queue.open &= open;
+}
@@ -0,0 +1,116 @@
+use std::fs::File;
+
+use egui::{ComboBox, DragValue, Grid, TextEdit, Widget};
+use egui_notify::Toast;
+use rfd::FileDialog;
+
+use crate::{
+    app::util::kwic::{export_kwic, KwicOptions},
+    AnnatomicApp,
+};
+
+/// State of the dialog to export the matches of an AQL query as a
+/// keyword-in-context concordance.
+#[derive(Default, Clone)]
+pub(crate) struct KwicExportDialog {
+    pub(crate) open: bool,
+    query: String,
+    options: KwicOptions,
+}
+
+fn export(app: &mut AnnatomicApp) {
+    let (extension, description) = if app.kwic_export.options.delimiter == b'\t' {
+        ("tsv", "TSV (*.tsv)")
+    } else {
+        ("csv", "CSV (*.csv)")
+    };
+    let dlg = FileDialog::new().add_filter(description, &[extension]);
+    if let (Some(path), Some(selected_corpus)) = (dlg.save_file(), &app.project.selected_corpus) {
+        let corpus_cache = app.project.corpus_cache.clone();
+        let location = selected_corpus.location.clone();
+        let query = app.kwic_export.query.clone();
+        let options = app.kwic_export.options.clone();
+        app.jobs.add(
+            "Exporting concordance",
+            move |job| {
+                job.update_message("Loading corpus if necessary");
+                let graph = corpus_cache.get(&location)?;
+                let mut graph = graph.write();
+                graph.ensure_loaded_all()?;
+                job.update_message("Executing query");
+                let count = export_kwic(&graph, &query, &options, File::create(&path)?)?;
+                Ok((count, path))
+            },
+            |(count, path), app| {
+                app.notifier.add_toast(Toast::info(format!(
+                    "Exported {count} matches to {}",
+                    path.display()
+                )));
+            },
+        );
+    }
+}
+
+/// Show the dialog to export a concordance if it has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.kwic_export.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.kwic_export.open = false;
+        return;
+    }
+
+    let mut open = app.kwic_export.open;
+    let mut start_export = false;
+    egui::Window::new("Export concordance (KWIC)")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.kwic_export;
+            ui.label("Each match of the AQL query is exported with its left and right context and the metadata of its document.");
+            Grid::new("kwic-export").num_columns(2).show(ui, |ui| {
+                ui.label("AQL query");
+                TextEdit::multiline(&mut dialog.query)
+                    .hint_text("tok=\"example\"")
+                    .desired_rows(2)
+                    .desired_width(300.0)
+                    .ui(ui);
+                ui.end_row();
+
+                ui.label("Context");
+                ui.add(
+                    DragValue::new(&mut dialog.options.context)
+                        .range(0..=100)
+                        .suffix(" token"),
+                );
+                ui.end_row();
+
+                ui.label("Format");
+                ComboBox::from_id_salt("kwic-format")
+                    .selected_text(if dialog.options.delimiter == b'\t' {
+                        "TSV"
+                    } else {
+                        "CSV"
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut dialog.options.delimiter, b',', "CSV");
+                        ui.selectable_value(&mut dialog.options.delimiter, b'\t', "TSV");
+                    });
+                ui.end_row();
+            });
+            if ui
+                .add_enabled(
+                    !dialog.query.trim().is_empty(),
+                    egui::Button::new("Export…"),
+                )
+                .clicked()
+            {
+                start_export = true;
+            }
+        });
+    if start_export {
+        export(app);
+    }
+    app.kwic_export.open = open;
+}
@@ -0,0 +1,146 @@
+use std::{collections::BTreeMap, fs::File, path::PathBuf};
+
+use egui::{Color32, ComboBox, RichText, ScrollArea};
+use graphannis::graph::AnnoKey;
+use rfd::FileDialog;
+
+use crate::{
+    app::{project::refactoring::parse_value_mapping, views::rename_annotation::key_label},
+    AnnatomicApp,
+};
+
+/// State of the dialog to map the values of an annotation with a CSV table.
+#[derive(Default, Clone)]
+pub(crate) struct ValueMappingDialog {
+    pub(crate) open: bool,
+    available_keys: Option<Vec<AnnoKey>>,
+    key: Option<AnnoKey>,
+    mapping: Option<(PathBuf, BTreeMap<String, String>)>,
+    /// Values without an entry in the mapping of the last applied mapping
+    pub(crate) unmapped: Option<BTreeMap<String, usize>>,
+}
+
+fn load_mapping(app: &mut AnnatomicApp) {
+    let dlg = FileDialog::new().add_filter("CSV (*.csv)", &["csv", "txt"]);
+    if let Some(path) = dlg.pick_file() {
+        let mapping = File::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(parse_value_mapping);
+        match mapping {
+            Ok(mapping) => app.value_mapping.mapping = Some((path, mapping)),
+            Err(e) => app.notifier.report_error(e),
+        }
+    }
+}
+
+/// Show the dialog to map annotation values if it has been opened and the
+/// values that were not mapped after it has been applied.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    show_unmapped_values(ctx, app);
+    if !app.value_mapping.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.value_mapping = ValueMappingDialog::default();
+        return;
+    }
+    if app.value_mapping.available_keys.is_none() {
+        app.value_mapping.available_keys = Some(Vec::new());
+        app.project.load_annotation_keys(|keys, app| {
+            app.value_mapping.available_keys = Some(keys);
+        });
+    }
+
+    let mut open = app.value_mapping.open;
+    let mut select_file = false;
+    let mut execute = false;
+    egui::Window::new("Map annotation values")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.value_mapping;
+            ComboBox::from_label("Annotation")
+                .selected_text(dialog.key.as_ref().map(key_label).unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    for key in dialog.available_keys.iter().flatten() {
+                        ui.selectable_value(&mut dialog.key, Some(key.clone()), key_label(key));
+                    }
+                });
+            ui.label("The mapping table is a CSV file with the old value in the first and the new value in the second column.");
+            ui.horizontal(|ui| {
+                if ui.button("Load mapping table…").clicked() {
+                    select_file = true;
+                }
+                if let Some((path, mapping)) = &dialog.mapping {
+                    ui.label(format!(
+                        "{} ({} entries)",
+                        path.file_name().unwrap_or_default().to_string_lossy(),
+                        mapping.len()
+                    ));
+                }
+            });
+            if ui
+                .add_enabled(
+                    dialog.key.is_some() && dialog.mapping.is_some(),
+                    egui::Button::new("Apply mapping"),
+                )
+                .clicked()
+            {
+                execute = true;
+            }
+        });
+
+    if select_file {
+        load_mapping(app);
+    }
+    if execute {
+        if let (Some(key), Some((_, mapping))) = (
+            app.value_mapping.key.clone(),
+            app.value_mapping.mapping.clone(),
+        ) {
+            app.apply_pending_updates();
+            app.project.map_annotation_values(key, mapping);
+            open = false;
+        }
+    }
+    if open {
+        app.value_mapping.open = true;
+    } else {
+        // Keep the report for the unmapped values, which is set when the job is finished
+        app.value_mapping = ValueMappingDialog {
+            unmapped: app.value_mapping.unmapped.take(),
+            ..Default::default()
+        };
+    }
+}
+
+fn show_unmapped_values(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    let mut open = app
+        .value_mapping
+        .unmapped
+        .as_ref()
+        .is_some_and(|unmapped| !unmapped.is_empty());
+    if let Some(unmapped) = &app.value_mapping.unmapped {
+        egui::Window::new("Unmapped values")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "{} value(s) had no entry in the mapping table and were not changed:",
+                        unmapped.len()
+                    ))
+                    .color(Color32::ORANGE),
+                );
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (value, count) in unmapped.iter() {
+                        ui.label(format!("\"{value}\" ({count}×)"));
+                    }
+                });
+            });
+    }
+    if !open {
+        app.value_mapping.unmapped = None;
+    }
+}
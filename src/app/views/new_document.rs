@@ -0,0 +1,227 @@
+use egui::{ComboBox, Grid, TextEdit, Ui, Widget};
+use graphannis::graph::NodeID;
+
+use crate::{
+    app::util::{
+        document_template::{annotation_lines, parse_annotation_lines, DocumentTemplate},
+        language::{document_tokenizer, Language, LANGUAGE_KEY},
+    },
+    AnnatomicApp,
+};
+
+/// The content of a template while it is edited. Annotations are edited as
+/// lines of the form `key=value`.
+#[derive(Default, Clone)]
+struct TemplateForm {
+    name: String,
+    metadata: String,
+    layers: String,
+    boilerplate: String,
+    spans: String,
+}
+
+impl TemplateForm {
+    fn from_template(name: &str, template: &DocumentTemplate) -> Self {
+        Self {
+            name: name.to_string(),
+            metadata: annotation_lines(&template.metadata),
+            layers: template.segmentation_layers.join(", "),
+            boilerplate: template.boilerplate.clone(),
+            spans: annotation_lines(&template.boilerplate_spans),
+        }
+    }
+
+    fn to_template(&self) -> anyhow::Result<DocumentTemplate> {
+        Ok(DocumentTemplate {
+            metadata: parse_annotation_lines(&self.metadata)?,
+            segmentation_layers: self
+                .layers
+                .split([',', ' '])
+                .filter(|l| !l.is_empty())
+                .map(str::to_string)
+                .collect(),
+            boilerplate: self.boilerplate.clone(),
+            boilerplate_spans: parse_annotation_lines(&self.spans)?,
+        })
+    }
+}
+
+/// State of the window that adds a new document to the selected corpus.
+#[derive(Default, Clone)]
+pub(crate) struct NewDocumentDialog {
+    pub(crate) open: bool,
+    /// The node selected in the corpus tree when the dialog was opened.
+    selected: Option<NodeID>,
+    name: String,
+    /// Name of the template the document is created from, if any.
+    template: Option<String>,
+    edited_template: Option<TemplateForm>,
+}
+
+impl NewDocumentDialog {
+    pub(crate) fn open_for(&mut self, selected: Option<NodeID>) {
+        self.open = true;
+        self.selected = selected;
+        self.name.clear();
+    }
+}
+
+fn template_selection(ui: &mut Ui, app: &mut AnnatomicApp) {
+    let dialog = &mut app.new_document;
+    if dialog
+        .template
+        .as_ref()
+        .is_some_and(|t| !app.project.document_templates.contains_key(t))
+    {
+        dialog.template = None;
+    }
+    ComboBox::from_label("Template")
+        .selected_text(dialog.template.as_deref().unwrap_or("(none)"))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut dialog.template, None, "(none)");
+            for name in app.project.document_templates.keys() {
+                ui.selectable_value(&mut dialog.template, Some(name.clone()), name);
+            }
+        });
+    ui.horizontal(|ui| {
+        if ui.button("New template").clicked() {
+            dialog.edited_template = Some(TemplateForm::default());
+        }
+        if let Some(name) = dialog.template.clone() {
+            if ui.button("Edit template").clicked() {
+                dialog.edited_template = app
+                    .project
+                    .document_templates
+                    .get(&name)
+                    .map(|t| TemplateForm::from_template(&name, t));
+            }
+            if ui.button("Delete template").clicked() {
+                app.project.document_templates.remove(&name);
+                dialog.template = None;
+            }
+        }
+    });
+}
+
+fn template_editor(ui: &mut Ui, app: &mut AnnatomicApp) {
+    let Some(form) = &mut app.new_document.edited_template else {
+        return;
+    };
+    ui.separator();
+    Grid::new("document-template")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Name");
+            TextEdit::singleline(&mut form.name).ui(ui);
+            ui.end_row();
+            ui.label("Metadata");
+            TextEdit::multiline(&mut form.metadata)
+                .hint_text("genre=letter")
+                .desired_rows(3)
+                .ui(ui);
+            ui.end_row();
+            ui.label("Segmentation layers");
+            TextEdit::singleline(&mut form.layers)
+                .hint_text("dipl, norm")
+                .ui(ui);
+            ui.end_row();
+            ui.label("Boilerplate text");
+            TextEdit::multiline(&mut form.boilerplate)
+                .desired_rows(2)
+                .ui(ui);
+            ui.end_row();
+            ui.label("Boilerplate spans");
+            TextEdit::multiline(&mut form.spans)
+                .hint_text("section=header")
+                .desired_rows(2)
+                .ui(ui);
+            ui.end_row();
+        });
+    let template = form.to_template();
+    if let Err(e) = &template {
+        ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+    }
+    let name = form.name.trim().to_string();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                template.is_ok() && !name.is_empty(),
+                egui::Button::new("Save template"),
+            )
+            .clicked()
+        {
+            if let Ok(template) = template {
+                app.project
+                    .document_templates
+                    .insert(name.clone(), template);
+                app.new_document.template = Some(name);
+                app.new_document.edited_template = None;
+            }
+        }
+        if ui.button("Cancel").clicked() {
+            app.new_document.edited_template = None;
+        }
+    });
+}
+
+fn create_document(app: &mut AnnatomicApp) {
+    let template = app
+        .new_document
+        .template
+        .as_ref()
+        .and_then(|name| app.project.document_templates.get(name))
+        .cloned()
+        .unwrap_or_default();
+    // A language given by the template determines the tokenizer of the
+    // boilerplate text
+    let language = template
+        .metadata
+        .get(LANGUAGE_KEY.name.as_str())
+        .and_then(|code| Language::from_code(code));
+    let tokenizer = document_tokenizer(
+        app.tokenizer_of_selected_corpus().as_ref(),
+        language,
+        &app.settings.tokenizer,
+    );
+    app.project.create_document(
+        app.new_document.selected,
+        app.new_document.name.clone(),
+        template,
+        tokenizer,
+    );
+    app.new_document.open = false;
+}
+
+/// Show the window to add a new document, optionally created from one of the
+/// document templates of the project.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.new_document.open {
+        return;
+    }
+    let mut open = app.new_document.open;
+    let mut create = false;
+    egui::Window::new("New document")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            TextEdit::singleline(&mut app.new_document.name)
+                .hint_text("Name of the document")
+                .ui(ui);
+            template_selection(ui, app);
+            template_editor(ui, app);
+            ui.separator();
+            if ui
+                .add_enabled(
+                    !app.new_document.name.trim().is_empty() && !app.project.read_only,
+                    egui::Button::new("Create document"),
+                )
+                .clicked()
+            {
+                create = true;
+            }
+        });
+    if create {
+        create_document(app);
+    }
+    app.new_document.open &= open;
+}
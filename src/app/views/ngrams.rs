@@ -0,0 +1,233 @@
+use egui::{ComboBox, DragValue, Grid, Ui};
+use egui_extras::{Column, TableBuilder};
+use graphannis::graph::AnnoKey;
+
+use crate::{
+    app::{
+        util::ngrams::{collocations, ngram_counts, value_sequences, Collocation},
+        views::rename_annotation::key_label,
+    },
+    AnnatomicApp,
+};
+
+const JOB_TITLE: &str = "Computing n-gram statistics";
+
+#[derive(Clone)]
+enum NgramResult {
+    Counts(Vec<(Vec<String>, usize)>),
+    Collocations(Vec<Collocation>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NgramMode {
+    Counts,
+    Collocations,
+}
+
+/// State of the window to compute n-gram counts and collocations.
+#[derive(Clone)]
+pub(crate) struct NgramDialog {
+    pub(crate) open: bool,
+    available_keys: Option<Vec<AnnoKey>>,
+    /// The annotation used as value of the token, the token value if `None`.
+    layer: Option<AnnoKey>,
+    mode: NgramMode,
+    n: usize,
+    min_count: usize,
+    result: Option<NgramResult>,
+}
+
+impl Default for NgramDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            available_keys: None,
+            layer: None,
+            mode: NgramMode::Counts,
+            n: 2,
+            min_count: 2,
+            result: None,
+        }
+    }
+}
+
+fn compute(app: &mut AnnatomicApp) {
+    let dialog = &app.ngrams;
+    let layer = dialog.layer.clone();
+    let mode = dialog.mode;
+    let n = dialog.n;
+    let min_count = dialog.min_count;
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        let corpus_cache = app.project.corpus_cache.clone();
+        app.jobs.add(
+            JOB_TITLE,
+            move |job| {
+                job.allow_cancel();
+                job.update_message("Loading corpus if necessary");
+                let graph = corpus_cache.get(&selected_corpus.location)?;
+                let mut graph = graph.write();
+                graph.ensure_loaded_all()?;
+                job.update_message("Collecting token values");
+                let sequences = value_sequences(&graph, layer.as_ref(), &job)?;
+                job.check_cancelled()?;
+                job.update_message("Counting");
+                let result = match mode {
+                    NgramMode::Counts => NgramResult::Counts(
+                        ngram_counts(&sequences, n)
+                            .into_iter()
+                            .filter(|(_, count)| *count >= min_count)
+                            .collect(),
+                    ),
+                    NgramMode::Collocations => {
+                        NgramResult::Collocations(collocations(&sequences, min_count))
+                    }
+                };
+                Ok(result)
+            },
+            |result, app| {
+                app.ngrams.result = Some(result);
+            },
+        );
+    }
+}
+
+fn show_result(ui: &mut Ui, result: &NgramResult) {
+    let table = TableBuilder::new(ui).striped(true).max_scroll_height(400.0);
+    match result {
+        NgramResult::Counts(counts) => {
+            table
+                .column(Column::auto().at_least(200.0).resizable(true))
+                .column(Column::remainder())
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        ui.strong("N-gram");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Count");
+                    });
+                })
+                .body(|body| {
+                    body.rows(18.0, counts.len(), |mut row| {
+                        let (ngram, count) = &counts[row.index()];
+                        row.col(|ui| {
+                            ui.label(ngram.join(" "));
+                        });
+                        row.col(|ui| {
+                            ui.label(count.to_string());
+                        });
+                    });
+                });
+        }
+        NgramResult::Collocations(collocations) => {
+            table
+                .column(Column::auto().at_least(200.0).resizable(true))
+                .columns(Column::auto().at_least(60.0), 2)
+                .column(Column::remainder())
+                .header(20.0, |mut header| {
+                    for title in ["Pair", "Count", "PMI", "Log-likelihood"] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(18.0, collocations.len(), |mut row| {
+                        let c = &collocations[row.index()];
+                        row.col(|ui| {
+                            ui.label(format!("{} {}", c.first, c.second));
+                        });
+                        row.col(|ui| {
+                            ui.label(c.count.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:.2}", c.pmi));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:.2}", c.log_likelihood));
+                        });
+                    });
+                });
+        }
+    }
+}
+
+/// Show the n-gram statistics if the window has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.ngrams.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.ngrams = NgramDialog::default();
+        return;
+    }
+    if app.ngrams.available_keys.is_none() {
+        app.ngrams.available_keys = Some(Vec::new());
+        app.project.load_annotation_keys(|keys, app| {
+            app.ngrams.available_keys = Some(keys);
+        });
+    }
+
+    let mut open = app.ngrams.open;
+    let mut start_computation = false;
+    egui::Window::new("N-grams and collocations")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.ngrams;
+            Grid::new("ngram-settings").num_columns(2).show(ui, |ui| {
+                ui.label("Values");
+                ComboBox::from_id_salt("ngram-layer")
+                    .selected_text(
+                        dialog
+                            .layer
+                            .as_ref()
+                            .map(key_label)
+                            .unwrap_or_else(|| "Token text".to_string()),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut dialog.layer, None, "Token text");
+                        for key in dialog.available_keys.iter().flatten() {
+                            ui.selectable_value(
+                                &mut dialog.layer,
+                                Some(key.clone()),
+                                key_label(key),
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Statistics");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut dialog.mode, NgramMode::Counts, "N-gram counts");
+                    ui.radio_value(
+                        &mut dialog.mode,
+                        NgramMode::Collocations,
+                        "Collocations (PMI, log-likelihood)",
+                    );
+                });
+                ui.end_row();
+
+                if dialog.mode == NgramMode::Counts {
+                    ui.label("N");
+                    ui.add(DragValue::new(&mut dialog.n).range(1..=10));
+                    ui.end_row();
+                }
+
+                ui.label("Minimal count");
+                ui.add(DragValue::new(&mut dialog.min_count).range(1..=1000));
+                ui.end_row();
+            });
+            if ui.button("Compute").clicked() {
+                start_computation = true;
+            }
+            if let Some(result) = &dialog.result {
+                ui.separator();
+                show_result(ui, result);
+            }
+        });
+
+    if start_computation && !app.jobs.has_active_job_with_title(JOB_TITLE) {
+        compute(app);
+    }
+    app.ngrams.open = open;
+}
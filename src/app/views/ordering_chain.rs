@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use egui::{CollapsingHeader, Frame, RichText, ScrollArea, Ui};
+use graphannis::graph::NodeID;
+
+use crate::{
+    app::{
+        util::ordering_chain::{ordering_chains, OrderingChains},
+        MainView,
+    },
+    AnnatomicApp,
+};
+
+/// State of the development window that draws the ordering components of the
+/// open document.
+#[derive(Default, Clone)]
+pub(crate) struct OrderingChainDialog {
+    pub(crate) open: bool,
+    /// The document the chains have been loaded for.
+    document: Option<NodeID>,
+    chains: Option<Vec<OrderingChains>>,
+}
+
+fn load_chains(app: &mut AnnatomicApp, document: NodeID) {
+    app.ordering_chain.document = Some(document);
+    app.ordering_chain.chains = None;
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        let corpus_cache = app.project.corpus_cache.clone();
+        app.jobs.add(
+            "Analyzing ordering components",
+            move |_job| {
+                let graph = corpus_cache.get(&selected_corpus.location)?;
+                let mut graph = graph.write();
+                graph.ensure_loaded_all()?;
+                ordering_chains(&graph, document)
+            },
+            |chains, app| {
+                app.ordering_chain.chains = Some(chains);
+            },
+        );
+    }
+}
+
+fn short_name(node_name: &str) -> &str {
+    node_name
+        .rsplit_once('#')
+        .map_or(node_name, |(_, name)| name)
+}
+
+fn show_chains(ui: &mut Ui, ordering: &OrderingChains) {
+    let problematic_nodes: HashSet<&str> = ordering
+        .issues
+        .iter()
+        .flat_map(|issue| issue.nodes())
+        .collect();
+    let error_color = ui.visuals().error_fg_color;
+    for issue in ordering.issues.iter() {
+        ui.colored_label(error_color, issue.to_string());
+    }
+    ui.horizontal_wrapped(|ui| {
+        for (idx, chain) in ordering.chains.iter().enumerate() {
+            if idx > 0 {
+                ui.label(RichText::new("⟂").color(error_color).strong())
+                    .on_hover_text("No ordering edge between these nodes");
+            }
+            for (node_idx, node_name) in chain.iter().enumerate() {
+                if node_idx > 0 {
+                    ui.label("→");
+                }
+                let mut frame = Frame::group(ui.style());
+                if problematic_nodes.contains(node_name.as_str()) {
+                    frame = frame.stroke((2.0, error_color));
+                }
+                frame
+                    .show(ui, |ui| {
+                        ui.label(short_name(node_name));
+                    })
+                    .response
+                    .on_hover_text(node_name);
+            }
+        }
+    });
+}
+
+/// Show the ordering chains of the open document if the window has been
+/// opened. This is only available in development mode.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.ordering_chain.open {
+        return;
+    }
+    let MainView::EditDocument { node_id } = app.main_view else {
+        app.ordering_chain = OrderingChainDialog::default();
+        return;
+    };
+    if app.ordering_chain.document != Some(node_id) {
+        load_chains(app, node_id);
+    }
+
+    let mut open = app.ordering_chain.open;
+    let mut reload = false;
+    egui::Window::new("Ordering chains")
+        .open(&mut open)
+        .default_width(600.0)
+        .show(ctx, |ui| {
+            if ui.button("Reload").clicked() {
+                reload = true;
+            }
+            ui.separator();
+            let Some(chains) = &app.ordering_chain.chains else {
+                return;
+            };
+            if chains.is_empty() {
+                ui.label("The document has no ordering components.");
+            }
+            ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                for ordering in chains {
+                    let name = if ordering.component.name.is_empty() {
+                        "(base token)"
+                    } else {
+                        ordering.component.name.as_str()
+                    };
+                    let title = format!(
+                        "{}/{name}: {} nodes, {} issues",
+                        ordering.component.layer,
+                        ordering.chains.iter().map(|c| c.len()).sum::<usize>(),
+                        ordering.issues.len()
+                    );
+                    CollapsingHeader::new(title)
+                        .default_open(!ordering.issues.is_empty())
+                        .show(ui, |ui| show_chains(ui, ordering));
+                }
+            });
+        });
+
+    if reload {
+        load_chains(app, node_id);
+    }
+    app.ordering_chain.open = open;
+}
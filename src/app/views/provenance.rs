@@ -0,0 +1,112 @@
+use egui::{Grid, ScrollArea, TextEdit, Widget};
+
+use crate::{
+    api::provenance::{changes_of_node, ProvenanceEntry},
+    AnnatomicApp,
+};
+
+/// State of the window that shows the recorded changes of a node.
+#[derive(Default, Clone)]
+pub(crate) struct ProvenanceDialog {
+    pub(crate) open: bool,
+    node_name: String,
+    entries: Option<Vec<ProvenanceEntry>>,
+    /// Load the entries for the node name in the next frame.
+    load_requested: bool,
+}
+
+impl ProvenanceDialog {
+    /// Open the window and show the changes of the given node.
+    pub(crate) fn open_for(&mut self, node_name: Option<String>) {
+        self.open = true;
+        if let Some(node_name) = node_name {
+            self.node_name = node_name;
+            self.entries = None;
+            self.load_requested = true;
+        }
+    }
+}
+
+fn load_entries(app: &mut AnnatomicApp, node_name: String) {
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        app.jobs.add(
+            "Loading provenance",
+            move |_job| changes_of_node(&selected_corpus.location, &node_name),
+            |entries, app| {
+                app.provenance.entries = Some(entries);
+            },
+        );
+    }
+}
+
+/// Show the provenance of a node if the window has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.provenance.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.provenance = ProvenanceDialog::default();
+        return;
+    }
+
+    let mut open = app.provenance.open;
+    let mut request_entries = false;
+    egui::Window::new("Provenance")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.provenance;
+            ui.horizontal(|ui| {
+                let response = TextEdit::singleline(&mut dialog.node_name)
+                    .hint_text("Node name")
+                    .desired_width(300.0)
+                    .ui(ui);
+                if response.changed() {
+                    dialog.entries = None;
+                }
+                if ui
+                    .add_enabled(
+                        !dialog.node_name.is_empty(),
+                        egui::Button::new("Show changes"),
+                    )
+                    .clicked()
+                {
+                    request_entries = true;
+                }
+            });
+            match &dialog.entries {
+                Some(entries) if entries.is_empty() => {
+                    ui.label("No changes have been recorded for this node.");
+                }
+                Some(entries) => {
+                    ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                        Grid::new("provenance-entries")
+                            .num_columns(3)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Time");
+                                ui.strong("Annotator");
+                                ui.strong("Change");
+                                ui.end_row();
+                                for entry in entries {
+                                    ui.label(&entry.timestamp);
+                                    ui.label(&entry.annotator);
+                                    ui.label(&entry.change);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                }
+                None => {}
+            }
+        });
+
+    if std::mem::take(&mut app.provenance.load_requested) {
+        request_entries = true;
+    }
+    if request_entries && !app.jobs.has_active_job_with_title("Loading provenance") {
+        app.provenance.entries = Some(Vec::new());
+        load_entries(app, app.provenance.node_name.clone());
+    }
+    app.provenance.open = open;
+}
@@ -0,0 +1,168 @@
+use egui::{Color32, ComboBox, RichText, TextEdit, Widget};
+use graphannis::graph::AnnoKey;
+
+use crate::{
+    app::project::refactoring::{preview_rename, RenamePreview},
+    AnnatomicApp,
+};
+
+/// State of the dialog to rename an annotation key in the whole corpus.
+#[derive(Default, Clone)]
+pub(crate) struct RenameAnnotationDialog {
+    pub(crate) open: bool,
+    available_keys: Option<Vec<AnnoKey>>,
+    old_key: Option<AnnoKey>,
+    new_ns: String,
+    new_name: String,
+    preview: Option<RenamePreview>,
+}
+
+impl RenameAnnotationDialog {
+    fn new_key(&self) -> AnnoKey {
+        AnnoKey {
+            ns: self.new_ns.trim().into(),
+            name: self.new_name.trim().into(),
+        }
+    }
+}
+
+pub(crate) fn key_label(key: &AnnoKey) -> String {
+    if key.ns.is_empty() {
+        key.name.to_string()
+    } else {
+        format!("{}:{}", key.ns, key.name)
+    }
+}
+
+/// Parse an annotation key given as `ns:name` or `name`.
+pub(crate) fn parse_key(key: &str) -> AnnoKey {
+    let key = key.trim();
+    match key.rsplit_once(':') {
+        Some((ns, name)) => AnnoKey {
+            ns: ns.into(),
+            name: name.into(),
+        },
+        None => AnnoKey {
+            ns: "".into(),
+            name: key.into(),
+        },
+    }
+}
+
+fn load_preview(app: &mut AnnatomicApp, old_key: AnnoKey, new_key: AnnoKey) {
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        let corpus_cache = app.project.corpus_cache.clone();
+        app.jobs.add(
+            "Counting affected annotations",
+            move |_job| {
+                let graph = corpus_cache.get(&selected_corpus.location)?;
+                let graph = graph.read();
+                preview_rename(&graph, &old_key, &new_key)
+            },
+            |preview, app| {
+                app.rename_annotation.preview = Some(preview);
+            },
+        );
+    }
+}
+
+/// Show the dialog to rename an annotation key if it has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.rename_annotation.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.rename_annotation = RenameAnnotationDialog::default();
+        return;
+    }
+    if app.rename_annotation.available_keys.is_none() {
+        app.rename_annotation.available_keys = Some(Vec::new());
+        app.project.load_annotation_keys(|keys, app| {
+            app.rename_annotation.available_keys = Some(keys);
+        });
+    }
+
+    let mut open = app.rename_annotation.open;
+    let mut request_preview = false;
+    let mut execute = false;
+    egui::Window::new("Rename annotation")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.rename_annotation;
+            let old_preview_input = (dialog.old_key.clone(), dialog.new_key());
+            ComboBox::from_label("Annotation to rename")
+                .selected_text(dialog.old_key.as_ref().map(key_label).unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    for key in dialog.available_keys.iter().flatten() {
+                        ui.selectable_value(&mut dialog.old_key, Some(key.clone()), key_label(key));
+                    }
+                });
+            ui.horizontal(|ui| {
+                TextEdit::singleline(&mut dialog.new_ns)
+                    .hint_text("New namespace")
+                    .desired_width(120.0)
+                    .ui(ui);
+                ui.label(":");
+                TextEdit::singleline(&mut dialog.new_name)
+                    .hint_text("New name")
+                    .desired_width(120.0)
+                    .ui(ui);
+            });
+            if old_preview_input != (dialog.old_key.clone(), dialog.new_key()) {
+                // The preview is not valid for the changed input
+                dialog.preview = None;
+            }
+            let valid_input = dialog
+                .old_key
+                .as_ref()
+                .is_some_and(|old_key| *old_key != dialog.new_key())
+                && !dialog.new_key().name.is_empty();
+
+            if let Some(preview) = &dialog.preview {
+                ui.label(format!("{} node(s) will be changed.", preview.affected));
+                if preview.overwritten > 0 {
+                    ui.label(
+                        RichText::new(format!(
+                            "{} node(s) already have an annotation with the new name, which will be overwritten.",
+                            preview.overwritten
+                        ))
+                        .color(Color32::ORANGE),
+                    );
+                }
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(valid_input, egui::Button::new("Count affected nodes"))
+                    .clicked()
+                {
+                    request_preview = true;
+                }
+                if ui
+                    .add_enabled(
+                        valid_input && dialog.preview.is_some(),
+                        egui::Button::new("Rename"),
+                    )
+                    .clicked()
+                {
+                    execute = true;
+                }
+            });
+        });
+
+    let dialog = app.rename_annotation.clone();
+    if let (Some(old_key), true) = (dialog.old_key.clone(), request_preview) {
+        load_preview(app, old_key, dialog.new_key());
+    }
+    if let (Some(old_key), true) = (dialog.old_key.clone(), execute) {
+        app.apply_pending_updates();
+        app.project.rename_annotation_key(old_key, dialog.new_key());
+        open = false;
+    }
+    if open {
+        app.rename_annotation.open = true;
+    } else {
+        app.rename_annotation = RenameAnnotationDialog::default();
+    }
+}
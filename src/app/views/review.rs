@@ -0,0 +1,237 @@
+use egui::{Grid, Key, KeyboardShortcut, Modifiers, ProgressBar, RichText, TextEdit, Widget};
+use graphannis::update::GraphUpdate;
+
+use crate::{
+    app::{
+        util::review::{
+            decision_events, review_queue, RejectAction, ReviewDecision, ReviewFilter, ReviewItem,
+        },
+        views::rename_annotation::key_label,
+    },
+    AnnatomicApp,
+};
+
+const JOB_TITLE: &str = "Collecting annotations to review";
+
+pub(crate) const ACCEPT_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::A);
+pub(crate) const REJECT_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::R);
+pub(crate) const SKIP_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::S);
+
+/// State of the window to accept or reject the annotations matching a filter
+/// one after another.
+#[derive(Default, Clone)]
+pub(crate) struct ReviewDialog {
+    pub(crate) open: bool,
+    /// Review the annotations of an annotator instead of a namespace.
+    by_annotator: bool,
+    namespace: String,
+    initials: String,
+    reject_action: RejectAction,
+    queue: Option<Vec<ReviewItem>>,
+    /// Index of the current item in the queue.
+    position: usize,
+    accepted: usize,
+    rejected: usize,
+}
+
+impl ReviewDialog {
+    /// True if there is an item that waits for a decision.
+    pub(crate) fn is_reviewing(&self) -> bool {
+        self.open && self.current_item().is_some()
+    }
+
+    fn current_item(&self) -> Option<&ReviewItem> {
+        self.queue.as_ref()?.get(self.position)
+    }
+
+    fn filter(&self, stamp_namespace: &str) -> ReviewFilter {
+        if self.by_annotator {
+            ReviewFilter::Annotator {
+                stamp_namespace: stamp_namespace.to_string(),
+                initials: self.initials.trim().to_string(),
+            }
+        } else {
+            ReviewFilter::Namespace(self.namespace.trim().to_string())
+        }
+    }
+}
+
+fn collect_queue(app: &mut AnnatomicApp) {
+    let filter = app.review.filter(&app.settings.stamp_namespace);
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        let corpus_cache = app.project.corpus_cache.clone();
+        app.jobs.add(
+            JOB_TITLE,
+            move |job| {
+                job.update_message("Loading corpus if necessary");
+                let graph = corpus_cache.get(&selected_corpus.location)?;
+                let mut graph = graph.write();
+                graph.ensure_loaded_all()?;
+                job.update_message("Collecting annotations");
+                review_queue(&graph, &filter)
+            },
+            |queue, app| {
+                app.review.queue = Some(queue);
+                app.review.position = 0;
+                app.review.accepted = 0;
+                app.review.rejected = 0;
+            },
+        );
+    }
+}
+
+fn decide(app: &mut AnnatomicApp, decision: Option<ReviewDecision>) {
+    let Some(item) = app.review.current_item().cloned() else {
+        return;
+    };
+    app.review.position += 1;
+    let Some(decision) = decision else {
+        // The item is skipped
+        return;
+    };
+    match decision {
+        ReviewDecision::Accept => app.review.accepted += 1,
+        ReviewDecision::Reject(_) => app.review.rejected += 1,
+    }
+    let mut update = GraphUpdate::new();
+    for event in decision_events(&item, decision) {
+        if let Err(e) = update.add_event(event) {
+            app.notifier.report_error(e.into());
+            return;
+        }
+    }
+    app.project.add_changeset(update);
+}
+
+/// Show the review window if it has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.review.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.review = ReviewDialog::default();
+        return;
+    }
+
+    let mut open = app.review.open;
+    let mut start = false;
+    let mut decision = None;
+    let mut skip = false;
+    let collecting = app.jobs.has_active_job_with_title(JOB_TITLE);
+    egui::Window::new("Review annotations")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.review;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut dialog.by_annotator, false, "Namespace");
+                ui.radio_value(&mut dialog.by_annotator, true, "Annotator initials");
+                if dialog.by_annotator {
+                    TextEdit::singleline(&mut dialog.initials)
+                        .hint_text("Initials")
+                        .desired_width(80.0)
+                        .ui(ui);
+                } else {
+                    TextEdit::singleline(&mut dialog.namespace)
+                        .hint_text("e.g. the namespace of a tagger")
+                        .desired_width(150.0)
+                        .ui(ui);
+                }
+                let has_filter = if dialog.by_annotator {
+                    !dialog.initials.trim().is_empty()
+                } else {
+                    !dialog.namespace.trim().is_empty()
+                };
+                if ui
+                    .add_enabled(has_filter && !collecting, egui::Button::new("Start review"))
+                    .clicked()
+                {
+                    start = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Rejected annotations are");
+                ui.radio_value(&mut dialog.reject_action, RejectAction::Delete, "deleted");
+                ui.radio_value(&mut dialog.reject_action, RejectAction::Flag, "flagged");
+            });
+            let Some(queue) = &dialog.queue else {
+                return;
+            };
+            ui.separator();
+            let total = queue.len().max(1);
+            ProgressBar::new(dialog.position as f32 / total as f32)
+                .text(format!(
+                    "{}/{} reviewed, {} accepted, {} rejected",
+                    dialog.position.min(queue.len()),
+                    queue.len(),
+                    dialog.accepted,
+                    dialog.rejected
+                ))
+                .ui(ui);
+            if let Some(item) = queue.get(dialog.position) {
+                Grid::new("review-item").num_columns(2).show(ui, |ui| {
+                    ui.label("Node");
+                    ui.label(&item.node_name);
+                    ui.end_row();
+                    ui.label("Annotation");
+                    ui.label(key_label(&item.key));
+                    ui.end_row();
+                    ui.label("Value");
+                    ui.label(RichText::new(&item.value).strong());
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(format!(
+                            "Accept ({})",
+                            ctx.format_shortcut(&ACCEPT_SHORTCUT)
+                        ))
+                        .clicked()
+                    {
+                        decision = Some(ReviewDecision::Accept);
+                    }
+                    if ui
+                        .button(format!(
+                            "Reject ({})",
+                            ctx.format_shortcut(&REJECT_SHORTCUT)
+                        ))
+                        .clicked()
+                    {
+                        decision = Some(ReviewDecision::Reject(dialog.reject_action));
+                    }
+                    if ui
+                        .button(format!("Skip ({})", ctx.format_shortcut(&SKIP_SHORTCUT)))
+                        .clicked()
+                    {
+                        skip = true;
+                    }
+                });
+            } else if queue.is_empty() {
+                ui.label("No annotations left to review.");
+            } else {
+                ui.label("All annotations have been reviewed.");
+            }
+        });
+
+    // Keyboard decisions are only possible if no text is edited
+    if app.review.is_reviewing() && ctx.memory(|m| m.focused().is_none()) {
+        ctx.input_mut(|i| {
+            if i.consume_shortcut(&ACCEPT_SHORTCUT) {
+                decision = Some(ReviewDecision::Accept);
+            } else if i.consume_shortcut(&REJECT_SHORTCUT) {
+                decision = Some(ReviewDecision::Reject(app.review.reject_action));
+            } else if i.consume_shortcut(&SKIP_SHORTCUT) {
+                skip = true;
+            }
+        });
+    }
+
+    if start && !collecting {
+        collect_queue(app);
+    }
+    if decision.is_some() || skip {
+        decide(app, decision);
+    }
+    app.review.open = open;
+}
@@ -0,0 +1,47 @@
+use egui::{Grid, RichText};
+
+use crate::{app::views::ShortcutEntry, AnnatomicApp};
+
+/// Group the entries by their context, keeping the order of the contexts.
+fn group_by_context(entries: &[ShortcutEntry]) -> Vec<(&'static str, Vec<&ShortcutEntry>)> {
+    let mut result: Vec<(&'static str, Vec<&ShortcutEntry>)> = Vec::new();
+    for e in entries {
+        if let Some((_, group)) = result.iter_mut().find(|(c, _)| *c == e.context) {
+            group.push(e);
+        } else {
+            result.push((e.context, vec![e]));
+        }
+    }
+    result
+}
+
+/// Show the list of all currently active keyboard shortcuts if the window
+/// has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.shortcuts_open {
+        return;
+    }
+    let entries = app.active_shortcuts();
+    let mut open = app.shortcuts_open;
+    egui::Window::new("Keyboard shortcuts")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            for (context, group) in group_by_context(&entries) {
+                ui.heading(context);
+                Grid::new(("shortcuts", context))
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for e in group {
+                            ui.label(RichText::new(ctx.format_shortcut(&e.shortcut)).monospace());
+                            ui.label(&e.description);
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(8.0);
+            }
+        });
+    app.shortcuts_open = open;
+}
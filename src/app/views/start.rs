@@ -1,18 +1,45 @@
-use std::{fs::File, io::BufReader};
+use crate::{
+    app::{
+        settings::tokenizer_selection,
+        util::{
+            corpus_groups::{group_corpora, pinned_first, tag_categories, CorpusGrouping},
+            health_check::HealthProblem,
+        },
+        MainView,
+    },
+    AnnatomicApp,
+};
+use std::collections::BTreeSet;
 
-use crate::{app::MainView, AnnatomicApp};
 use anyhow::Result;
-use egui::{Id, TextEdit, Ui, Widget};
+use egui::{CollapsingHeader, Color32, ComboBox, Id, RichText, ScrollArea, TextEdit, Ui, Widget};
 use egui_notify::Toast;
-use graphannis::model::AnnotationComponentType;
 
 use rfd::FileDialog;
 
 #[cfg(test)]
 mod tests;
 
+/// State of the window to export several corpora as one ZIP file.
+#[derive(Default, Clone)]
+pub(crate) struct BundleExportDialog {
+    selected: BTreeSet<String>,
+}
+
+/// State of the window to rename a corpus.
+#[derive(Default, Clone)]
+pub(crate) struct CorpusRenameDialog {
+    old_name: String,
+    new_name: String,
+    /// Also rename the top-level corpus node in the graph.
+    rename_nodes: bool,
+}
+
 pub(crate) fn show(ui: &mut Ui, app: &mut AnnatomicApp) -> Result<()> {
-    let corpora: Vec<_> = app.project.corpus_locations.keys().cloned().collect();
+    let corpora = pinned_first(
+        app.project.corpus_locations.keys(),
+        &app.project.pinned_corpora,
+    );
 
     ui.columns_const(|[c1, c2, c3, c4]| {
         if let Err(e) = corpus_selection(c1, app, &corpora) {
@@ -23,42 +50,403 @@ pub(crate) fn show(ui: &mut Ui, app: &mut AnnatomicApp) -> Result<()> {
         create_new_corpus(c4, app);
     });
     corpus_structure(ui, app);
+    import_report_window(ui.ctx(), app);
+    export_differences_window(ui.ctx(), app);
+    rename_corpus_window(ui.ctx(), app);
+    bundle_export_window(ui.ctx(), app, &corpora);
 
     Ok(())
 }
 
+fn bundle_export_window(ctx: &egui::Context, app: &mut AnnatomicApp, corpora: &[String]) {
+    let mut open = app.bundle_export.is_some();
+    let mut export = false;
+    if let Some(dialog) = &mut app.bundle_export {
+        egui::Window::new("Export several corpora")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("Select the corpora to export as one ZIP file:");
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for name in corpora {
+                        let mut selected = dialog.selected.contains(name);
+                        if ui.checkbox(&mut selected, name).changed() {
+                            if selected {
+                                dialog.selected.insert(name.clone());
+                            } else {
+                                dialog.selected.remove(name);
+                            }
+                        }
+                    }
+                });
+                if ui
+                    .add_enabled(
+                        !dialog.selected.is_empty(),
+                        egui::Button::new(format!("Export {} corpora…", dialog.selected.len())),
+                    )
+                    .clicked()
+                {
+                    export = true;
+                }
+            });
+    }
+    if export {
+        let dlg = FileDialog::new()
+            .set_can_create_directories(true)
+            .add_filter("Compressed GraphML for ANNIS (*.zip)", &["zip"]);
+        if let Some(path) = dlg.save_file() {
+            if let Some(dialog) = app.bundle_export.take() {
+                app.apply_pending_updates();
+                app.project
+                    .export_bundle(dialog.selected.into_iter().collect(), &path);
+            }
+        }
+    } else if !open {
+        app.bundle_export = None;
+    }
+}
+
+fn rename_corpus_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    let mut open = app.corpus_rename.is_some();
+    let mut rename = false;
+    let selected_corpus = app.project.selected_corpus.as_ref().map(|c| c.name.clone());
+    let read_only = app.project.read_only;
+    if let Some(dialog) = &mut app.corpus_rename {
+        let nodes_renamable =
+            !read_only && selected_corpus.as_deref() == Some(dialog.old_name.as_str());
+        if !nodes_renamable {
+            dialog.rename_nodes = false;
+        }
+        egui::Window::new(format!("Rename \"{}\"", dialog.old_name))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                TextEdit::singleline(&mut dialog.new_name)
+                    .hint_text("New corpus name")
+                    .ui(ui);
+                ui.add_enabled(
+                    nodes_renamable,
+                    egui::Checkbox::new(
+                        &mut dialog.rename_nodes,
+                        "Rename the top-level corpus node in the graph",
+                    ),
+                )
+                .on_hover_text("All node names starting with the old corpus name are changed")
+                .on_disabled_hover_text(
+                    "Only the nodes of the selected and editable corpus can be renamed",
+                );
+                if ui
+                    .add_enabled(
+                        !dialog.new_name.trim().is_empty() && dialog.new_name != dialog.old_name,
+                        egui::Button::new("Rename"),
+                    )
+                    .clicked()
+                {
+                    rename = true;
+                }
+            });
+    }
+    if rename {
+        if let Some(dialog) = app.corpus_rename.take() {
+            app.apply_pending_updates();
+            if let Err(e) =
+                app.project
+                    .rename_corpus(&dialog.old_name, &dialog.new_name, dialog.rename_nodes)
+            {
+                app.notifier.report_error(e);
+            }
+        }
+    } else if !open {
+        app.corpus_rename = None;
+    }
+}
+
+fn export_differences_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    let mut open = app.export_differences.is_some();
+    if let Some((location, differences)) = &app.export_differences {
+        egui::Window::new("Export verification")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "The exported file {} differs from the corpus:",
+                        location.to_string_lossy()
+                    ))
+                    .color(Color32::ORANGE),
+                );
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for difference in differences.iter() {
+                        ui.label(format!("{} {difference}", egui_phosphor::regular::WARNING));
+                    }
+                });
+            });
+    }
+    if !open {
+        app.export_differences = None;
+    }
+}
+
+fn import_report_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    let mut open = app.import_report.is_some();
+    if let Some(report) = &app.import_report {
+        egui::Window::new(format!("Imported \"{}\"", report.corpus_name))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} document(s) with {} token imported.",
+                    report.documents, report.token
+                ));
+                if report.issues.is_empty() {
+                    ui.label("No problems found.");
+                } else {
+                    ui.label(
+                        RichText::new(format!("{} problem(s) found:", report.issues.len()))
+                            .color(Color32::ORANGE),
+                    );
+                    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for issue in report.issues.iter() {
+                            ui.label(format!("{} {issue}", egui_phosphor::regular::WARNING));
+                        }
+                    });
+                }
+            });
+    }
+    if !open {
+        app.import_report = None;
+    }
+}
+
 fn corpus_selection(ui: &mut Ui, app: &mut AnnatomicApp, corpora: &[String]) -> Result<()> {
     ui.vertical_centered(|ui| {
         ui.heading("Select");
 
-        ui.horizontal_wrapped(|ui| {
-            for c in corpora {
-                let is_selected = app
-                    .project
-                    .selected_corpus
-                    .as_ref()
-                    .is_some_and(|selected_corpus| selected_corpus.name == *c);
-                let label = ui.selectable_label(is_selected, c);
-                label.context_menu(|ui| {
-                    if ui.button("Delete").clicked() {
-                        app.apply_pending_updates();
-                        app.project.scheduled_for_deletion = Some(c.clone());
+        ui.horizontal(|ui| {
+            TextEdit::singleline(&mut app.corpus_filter)
+                .hint_text("Search corpora and tags")
+                .desired_width(150.0)
+                .ui(ui);
+            let categories = tag_categories(&app.project.corpus_tags);
+            ComboBox::from_id_salt("corpus-grouping")
+                .selected_text(format!("Group by {}", app.corpus_grouping.label()))
+                .show_ui(ui, |ui| {
+                    let options = [CorpusGrouping::None, CorpusGrouping::Tag]
+                        .into_iter()
+                        .chain(categories.into_iter().map(CorpusGrouping::Category));
+                    for option in options {
+                        let label = option.label();
+                        ui.selectable_value(&mut app.corpus_grouping, option, label);
                     }
                 });
-                if label.clicked() {
+        });
+
+        let groups = group_corpora(
+            corpora,
+            &app.project.corpus_tags,
+            &app.corpus_grouping,
+            &app.corpus_filter,
+        );
+        if app.corpus_grouping == CorpusGrouping::None {
+            for corpora in groups.values() {
+                corpus_labels(ui, app, corpora);
+            }
+        } else {
+            for (group, corpora) in groups.iter() {
+                let title = if group.is_empty() {
+                    format!("Without {}", app.corpus_grouping.label().to_lowercase())
+                } else {
+                    group.clone()
+                };
+                CollapsingHeader::new(format!("{title} ({})", corpora.len()))
+                    .id_salt(("corpus-group", group))
+                    .default_open(true)
+                    .show(ui, |ui| corpus_labels(ui, app, corpora));
+            }
+        }
+    });
+    Ok(())
+}
+
+fn corpus_labels(ui: &mut Ui, app: &mut AnnatomicApp, corpora: &[String]) {
+    ui.horizontal_wrapped(|ui| {
+        for c in corpora {
+            let is_selected = app
+                .project
+                .selected_corpus
+                .as_ref()
+                .is_some_and(|selected_corpus| selected_corpus.name == *c);
+            if app.project.is_pinned(c) {
+                ui.label(RichText::new(egui_phosphor::regular::PUSH_PIN).weak())
+                    .on_hover_text("Pinned");
+            }
+            let label = ui.selectable_label(is_selected, c);
+            label.context_menu(|ui| {
+                if ui.button("Delete").clicked() {
                     app.apply_pending_updates();
-                    if is_selected {
-                        // Unselect the current corpus
-                        app.select_corpus(None);
-                    } else {
-                        // Select this corpus
-                        app.select_corpus(Some(c.clone()));
-                    }
+                    app.project.scheduled_for_deletion = Some(c.clone());
+                }
+                if ui.button("Rename…").clicked() {
+                    app.corpus_rename = Some(CorpusRenameDialog {
+                        old_name: c.clone(),
+                        new_name: c.clone(),
+                        rename_nodes: false,
+                    });
+                    ui.close_menu();
+                }
+                ui.separator();
+                corpus_pin_menu(ui, app, c);
+                ui.separator();
+                corpus_tag_menu(ui, app, c);
+                ui.separator();
+                corpus_tokenizer_menu(ui, app, c);
+                ui.separator();
+                corpus_namespace_menu(ui, app, c);
+            });
+            if label.clicked() {
+                app.apply_pending_updates();
+                if is_selected {
+                    // Unselect the current corpus
+                    app.select_corpus(None);
+                } else {
+                    // Select this corpus
+                    app.select_corpus(Some(c.clone()));
                 }
             }
+        }
+    });
+}
+
+/// Pin the corpus to the start of the corpus list and change the order of
+/// the pinned corpora.
+fn corpus_pin_menu(ui: &mut Ui, app: &mut AnnatomicApp, corpus: &str) {
+    let Some(idx) = app.project.pinned_corpora.iter().position(|c| c == corpus) else {
+        if ui.button("Pin to top").clicked() {
+            app.project.pin_corpus(corpus);
+            ui.close_menu();
+        }
+        return;
+    };
+    if ui.button("Unpin").clicked() {
+        app.project.unpin_corpus(corpus);
+        ui.close_menu();
+    }
+    if ui
+        .add_enabled(idx > 0, egui::Button::new("Move up"))
+        .clicked()
+    {
+        app.project.move_pinned_corpus(corpus, -1);
+    }
+    if ui
+        .add_enabled(
+            idx + 1 < app.project.pinned_corpora.len(),
+            egui::Button::new("Move down"),
+        )
+        .clicked()
+    {
+        app.project.move_pinned_corpus(corpus, 1);
+    }
+}
+
+/// Select a tokenizer for the corpus that is used instead of the default one.
+fn corpus_tokenizer_menu(ui: &mut Ui, app: &mut AnnatomicApp, corpus: &str) {
+    let mut tokenizer = app.project.corpus_tokenizers.get(corpus).cloned();
+    let mut use_default = tokenizer.is_none();
+    ui.checkbox(&mut use_default, "Use default tokenizer");
+    if use_default {
+        tokenizer = None;
+    } else {
+        let tokenizer = tokenizer.get_or_insert_with(|| app.settings.tokenizer.clone());
+        ui.horizontal(|ui| {
+            tokenizer_selection(ui, &format!("tokenizer-{corpus}"), tokenizer);
         });
+    }
+    if app.project.corpus_tokenizers.get(corpus) != tokenizer.as_ref() {
+        match tokenizer {
+            Some(tokenizer) => {
+                app.project
+                    .corpus_tokenizers
+                    .insert(corpus.to_string(), tokenizer);
+            }
+            None => {
+                app.project.corpus_tokenizers.remove(corpus);
+            }
+        }
+        app.apply_settings();
+    }
+}
+
+/// Set the namespace that is pre-filled for new annotations of the corpus.
+fn corpus_namespace_menu(ui: &mut Ui, app: &mut AnnatomicApp, corpus: &str) {
+    let mut namespace = app
+        .project
+        .corpus_namespaces
+        .get(corpus)
+        .cloned()
+        .unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.label("Default namespace");
+        TextEdit::singleline(&mut namespace)
+            .desired_width(120.0)
+            .hint_text("none")
+            .ui(ui)
+            .on_hover_text("Pre-filled namespace of new annotations and metadata entries");
     });
-    Ok(())
+    let namespace = namespace.trim();
+    if app
+        .project
+        .corpus_namespaces
+        .get(corpus)
+        .map_or("", |ns| ns)
+        != namespace
+    {
+        if namespace.is_empty() {
+            app.project.corpus_namespaces.remove(corpus);
+        } else {
+            app.project
+                .corpus_namespaces
+                .insert(corpus.to_string(), namespace.to_string());
+        }
+        app.apply_settings();
+    }
+}
+
+/// Show the tags of the corpus and allow to add new ones.
+fn corpus_tag_menu(ui: &mut Ui, app: &mut AnnatomicApp, corpus: &str) {
+    let tags = app
+        .project
+        .corpus_tags
+        .get(corpus)
+        .cloned()
+        .unwrap_or_default();
+    for tag in tags {
+        ui.horizontal(|ui| {
+            ui.label(&tag);
+            if ui
+                .small_button(egui_phosphor::regular::TRASH)
+                .on_hover_text("Remove tag")
+                .clicked()
+            {
+                app.project.remove_corpus_tag(corpus, &tag);
+            }
+        });
+    }
+    let edit_id = Id::new(("new-corpus-tag", corpus));
+    let mut new_tag = ui.data(|d| d.get_temp::<String>(edit_id).unwrap_or_default());
+    ui.horizontal(|ui| {
+        TextEdit::singleline(&mut new_tag)
+            .hint_text("Tag or category:value")
+            .desired_width(150.0)
+            .ui(ui);
+        if ui
+            .add_enabled(!new_tag.trim().is_empty(), egui::Button::new("Add tag"))
+            .clicked()
+        {
+            app.project.add_corpus_tag(corpus, &new_tag);
+            new_tag.clear();
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(edit_id, new_tag));
 }
 
 fn import_corpus(ui: &mut Ui, app: &mut AnnatomicApp) {
@@ -66,42 +454,11 @@ fn import_corpus(ui: &mut Ui, app: &mut AnnatomicApp) {
         ui.heading("Import");
         if ui.button("Import file...").clicked() {
             app.apply_pending_updates();
-            let dlg = FileDialog::new().add_filter("GraphML (*.graphml)", &["graphml"]);
+            let dlg = FileDialog::new()
+                .add_filter("GraphML (*.graphml)", &["graphml"])
+                .add_filter("Compressed GraphML, e.g. from ANNIS (*.zip)", &["zip"]);
             if let Some(path) = dlg.pick_file() {
-                let job_title = format!("Importing {}", path.to_string_lossy());
-                let parent_dir = app.project.corpus_storage_dir();
-                app.jobs.add(
-                    &job_title,
-                    move |job| {
-                        let corpus_name = if let Some(file_name) = path.file_stem() {
-                            file_name.to_string_lossy().to_string()
-                        } else {
-                            "UnknownCorpus".to_string()
-                        };
-                        let input_file = File::open(path)?;
-                        let input_file_buffered = BufReader::new(input_file);
-                        let (mut graph, _config_str) =
-                            graphannis_core::graph::serialization::graphml::import::<
-                                AnnotationComponentType,
-                                _,
-                                _,
-                            >(input_file_buffered, false, |status| {
-                                job.update_message(status);
-                            })?;
-
-                        let location = parent_dir?.join(uuid::Uuid::new_v4().to_string());
-                        std::fs::create_dir_all(&location)?;
-
-                        job.update_message("Persisting corpus");
-                        graph.persist_to(&location)?;
-
-                        Ok((corpus_name, location))
-                    },
-                    |(name, location), app| {
-                        app.project.corpus_locations.insert(name.clone(), location);
-                        app.select_corpus(Some(name));
-                    },
-                );
+                app.project.import_from_graphml(path, None);
             }
         }
     });
@@ -113,11 +470,21 @@ fn export_corpus(ui: &mut Ui, app: &mut AnnatomicApp) {
         if ui.button("Export file...").clicked() {
             let dlg = FileDialog::new()
                 .set_can_create_directories(true)
-                .add_filter("GraphML (*.graphml)", &["graphml"]);
+                .add_filter("GraphML (*.graphml)", &["graphml"])
+                .add_filter("Compressed GraphML for ANNIS (*.zip)", &["zip"]);
             if let Some(path) = dlg.save_file() {
-                app.project.export_to_graphml(&path);
+                app.project
+                    .export_to_graphml(&path, app.settings.verify_exports);
             }
         }
+        ui.checkbox(&mut app.settings.verify_exports, "Verify after export");
+        if ui
+            .button("Export several corpora…")
+            .on_hover_text("Export a ZIP file with a GraphML file for each corpus")
+            .clicked()
+        {
+            app.bundle_export = Some(BundleExportDialog::default());
+        }
     });
 }
 
@@ -156,14 +523,66 @@ fn corpus_structure(ui: &mut Ui, app: &mut AnnatomicApp) {
         .current_editor
         .get()
         .and_then(|editor| editor.get_selected_corpus_node());
-    if let Some(node_id) = selected_node_id {
-        if ui.link("Open selected in editor").clicked() {
-            app.change_view(MainView::EditDocument { node_id });
+    ui.horizontal(|ui| {
+        if let Some(node_id) = selected_node_id {
+            if ui.link("Open selected in editor").clicked() {
+                app.change_view(MainView::EditDocument { node_id });
+            }
+            if ui.link("Open selected in new window").clicked() {
+                super::detached::open(ui.ctx(), app, node_id);
+            }
         }
-    }
+        if app.current_editor.get().is_some()
+            && !app.project.read_only
+            && ui
+                .link("New document…")
+                .on_hover_text(
+                    "Add a document next to the selected one, optionally from a template",
+                )
+                .clicked()
+        {
+            app.new_document.open_for(selected_node_id);
+        }
+    });
+    health_warnings(ui, app);
     if let Some(editor) = app.current_editor.get_mut() {
         editor.show(ui);
     } else {
         ui.label("Select a corpus to edit it.");
     }
 }
+
+/// Show the problems found by the health check of the selected corpus and
+/// what the user can do about them.
+fn health_warnings(ui: &mut Ui, app: &mut AnnatomicApp) {
+    let Some(report) = &app.project.health_report else {
+        return;
+    };
+    if report.problems.is_empty() {
+        return;
+    }
+    let problems = report.problems.clone();
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        for problem in problems {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new(egui_phosphor::regular::WARNING).color(Color32::ORANGE));
+                ui.label(RichText::new(problem.to_string()).strong());
+            });
+            ui.label(problem.hint());
+            if matches!(problem, HealthProblem::ComponentNotLoadable { .. })
+                && ui.link("Show components").clicked()
+            {
+                app.component_browser.open = true;
+            }
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Check again").clicked() {
+                app.project.health_report = None;
+                app.project.check_health();
+            }
+            if ui.button("Dismiss").clicked() {
+                app.project.health_report = None;
+            }
+        });
+    });
+}
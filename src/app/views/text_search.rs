@@ -0,0 +1,332 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::File,
+    io::Write,
+};
+
+use anyhow::Result;
+use egui::{CollapsingHeader, Grid, Key, ScrollArea, TextEdit, Widget};
+use egui_notify::Toast;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::util::text_index::{TextIndex, TextSearchHit},
+    AnnatomicApp,
+};
+
+/// Maximum number of hits shown for a search.
+const MAX_HITS: usize = 500;
+/// Maximum number of queries kept in the history of each corpus.
+const MAX_HISTORY: usize = 20;
+const JOB_TITLE: &str = "Searching text";
+
+/// Named queries and recently executed queries of a corpus.
+#[derive(Default, Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct QueryCollection {
+    /// Saved queries by their name.
+    pub(crate) saved: BTreeMap<String, String>,
+    /// The most recent query is at the front.
+    pub(crate) history: VecDeque<String>,
+}
+
+impl QueryCollection {
+    pub(crate) fn add_to_history(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.history.retain(|q| q != query);
+        self.history.push_front(query.to_string());
+        self.history.truncate(MAX_HISTORY);
+    }
+
+    /// Write the number of matches of all saved queries and the queries of
+    /// the history as CSV.
+    pub(crate) fn write_match_counts<W: Write>(&self, index: &TextIndex, output: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(output);
+        writer.write_record(["name", "query", "matches"])?;
+        let named = self
+            .saved
+            .iter()
+            .map(|(name, q)| (name.as_str(), q.as_str()));
+        let unnamed = self
+            .history
+            .iter()
+            .filter(|q| !self.saved.values().any(|saved| saved == *q))
+            .map(|q| ("", q.as_str()));
+        for (name, query) in named.chain(unnamed) {
+            let count = index.search(query, usize::MAX).len();
+            writer.write_record([name, query, count.to_string().as_str()])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// State of the window to search the token text of all documents.
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct TextSearchDialog {
+    #[serde(skip)]
+    pub(crate) open: bool,
+    #[serde(skip)]
+    query: String,
+    #[serde(skip)]
+    hits: Option<Vec<TextSearchHit>>,
+    #[serde(skip)]
+    new_query_name: String,
+    /// Saved queries and history by the name of the corpus.
+    queries: BTreeMap<String, QueryCollection>,
+}
+
+impl TextSearchDialog {
+    /// The saved queries by the name of the corpus.
+    pub(crate) fn saved_queries(&self) -> BTreeMap<String, BTreeMap<String, String>> {
+        self.queries
+            .iter()
+            .filter(|(_, queries)| !queries.saved.is_empty())
+            .map(|(corpus_name, queries)| (corpus_name.clone(), queries.saved.clone()))
+            .collect()
+    }
+
+    /// Remove all saved queries and the history of all corpora.
+    pub(crate) fn clear_queries(&mut self) {
+        self.queries.clear();
+    }
+
+    /// Add saved queries for a corpus, existing queries with the same name
+    /// are replaced.
+    pub(crate) fn add_saved_queries(
+        &mut self,
+        corpus_name: &str,
+        queries: BTreeMap<String, String>,
+    ) {
+        self.queries
+            .entry(corpus_name.to_string())
+            .or_default()
+            .saved
+            .extend(queries);
+    }
+}
+
+fn search(app: &mut AnnatomicApp) {
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        let corpus_cache = app.project.corpus_cache.clone();
+        let query = app.text_search.query.clone();
+        app.text_search
+            .queries
+            .entry(selected_corpus.name.clone())
+            .or_default()
+            .add_to_history(&query);
+        app.jobs.add(
+            JOB_TITLE,
+            move |job| {
+                job.update_message("Creating search index if necessary");
+                let index = corpus_cache.text_index(&selected_corpus.location)?;
+                Ok(index.search(&query, MAX_HITS))
+            },
+            |hits, app| {
+                app.text_search.hits = Some(hits);
+            },
+        );
+    }
+}
+
+fn export_match_counts(app: &mut AnnatomicApp, queries: QueryCollection) {
+    let dlg = FileDialog::new().add_filter("CSV (*.csv)", &["csv"]);
+    if let (Some(path), Some(selected_corpus)) = (dlg.save_file(), &app.project.selected_corpus) {
+        let corpus_cache = app.project.corpus_cache.clone();
+        let location = selected_corpus.location.clone();
+        app.jobs.add(
+            "Exporting match counts",
+            move |job| {
+                job.update_message("Creating search index if necessary");
+                let index = corpus_cache.text_index(&location)?;
+                job.update_message("Counting matches");
+                queries.write_match_counts(&index, File::create(&path)?)?;
+                Ok(path)
+            },
+            |path, app| {
+                app.notifier.add_toast(Toast::info(format!(
+                    "Exported match counts to {}",
+                    path.display()
+                )));
+            },
+        );
+    }
+}
+
+/// Show the text search if the window has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.text_search.open {
+        return;
+    }
+    let Some(corpus_name) = app.project.selected_corpus.as_ref().map(|c| c.name.clone()) else {
+        app.text_search.open = false;
+        app.text_search.hits = None;
+        return;
+    };
+
+    let mut open = app.text_search.open;
+    let mut start_search = false;
+    let mut export_counts = false;
+    let mut selected_hit = None;
+    egui::Window::new("Search text")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.text_search;
+            ui.horizontal(|ui| {
+                let response = TextEdit::singleline(&mut dialog.query)
+                    .hint_text("Words to search for")
+                    .desired_width(300.0)
+                    .ui(ui);
+                if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    start_search = true;
+                }
+                if ui
+                    .add_enabled(!dialog.query.trim().is_empty(), egui::Button::new("Search"))
+                    .clicked()
+                {
+                    start_search = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                TextEdit::singleline(&mut dialog.new_query_name)
+                    .hint_text("Name")
+                    .desired_width(150.0)
+                    .ui(ui);
+                if ui
+                    .add_enabled(
+                        !dialog.query.trim().is_empty() && !dialog.new_query_name.trim().is_empty(),
+                        egui::Button::new("Save query"),
+                    )
+                    .clicked()
+                {
+                    let name = std::mem::take(&mut dialog.new_query_name);
+                    dialog
+                        .queries
+                        .entry(corpus_name.clone())
+                        .or_default()
+                        .saved
+                        .insert(name.trim().to_string(), dialog.query.trim().to_string());
+                }
+            });
+
+            let queries = dialog.queries.entry(corpus_name.clone()).or_default();
+            let mut run_query = None;
+            CollapsingHeader::new(format!("Saved queries ({})", queries.saved.len()))
+                .default_open(false)
+                .show(ui, |ui| {
+                    let mut deleted = None;
+                    Grid::new("saved-queries").num_columns(3).show(ui, |ui| {
+                        for (name, query) in queries.saved.iter() {
+                            if ui.link(name).on_hover_text("Search again").clicked() {
+                                run_query = Some(query.clone());
+                            }
+                            ui.label(query);
+                            if ui
+                                .small_button(egui_phosphor::regular::TRASH)
+                                .on_hover_text("Delete saved query")
+                                .clicked()
+                            {
+                                deleted = Some(name.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if let Some(name) = deleted {
+                        queries.saved.remove(&name);
+                    }
+                });
+            CollapsingHeader::new("History")
+                .default_open(false)
+                .show(ui, |ui| {
+                    for query in queries.history.iter() {
+                        if ui.link(query).on_hover_text("Search again").clicked() {
+                            run_query = Some(query.clone());
+                        }
+                    }
+                    if !queries.history.is_empty() && ui.button("Clear history").clicked() {
+                        queries.history.clear();
+                    }
+                });
+            if ui
+                .add_enabled(
+                    !queries.saved.is_empty() || !queries.history.is_empty(),
+                    egui::Button::new("Export match counts…"),
+                )
+                .on_hover_text("Save the number of matches of the saved and recent queries as CSV")
+                .clicked()
+            {
+                export_counts = true;
+            }
+            if let Some(query) = run_query {
+                dialog.query = query;
+                start_search = true;
+            }
+            ui.separator();
+
+            match &dialog.hits {
+                Some(hits) if hits.is_empty() => {
+                    ui.label("No matches found.");
+                }
+                Some(hits) => {
+                    ui.label(format!(
+                        "{} matches, click to open the document:",
+                        hits.len()
+                    ));
+                    ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                        Grid::new("text-search-hits")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for hit in hits {
+                                    ui.label(&hit.document_name);
+                                    if ui
+                                        .link(&hit.context)
+                                        .on_hover_text(format!(
+                                            "Token {}, relevance {:.0}%",
+                                            hit.token_index,
+                                            hit.score * 100.0
+                                        ))
+                                        .clicked()
+                                    {
+                                        selected_hit = Some((hit.document, hit.token_index));
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                }
+                None => {
+                    ui.label("Similar words are found as well, the best matches are shown first.");
+                }
+            }
+        });
+
+    if start_search
+        && !app.text_search.query.trim().is_empty()
+        && !app.jobs.has_active_job_with_title(JOB_TITLE)
+    {
+        search(app);
+    }
+    if export_counts {
+        let queries = app
+            .text_search
+            .queries
+            .get(&corpus_name)
+            .cloned()
+            .unwrap_or_default();
+        export_match_counts(app, queries);
+    }
+    if let Some((document, token_index)) = selected_hit {
+        app.navigate_to_token(document, token_index);
+    }
+    app.text_search.open = open;
+}
+
+#[cfg(test)]
+mod tests;
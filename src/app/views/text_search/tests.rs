@@ -0,0 +1,48 @@
+use graphannis::update::GraphUpdate;
+use pretty_assertions::assert_eq;
+
+use crate::app::util::{example_generator, text_index::TextIndex};
+
+use super::{QueryCollection, MAX_HISTORY};
+
+#[test]
+fn history_keeps_most_recent_queries() {
+    let mut queries = QueryCollection::default();
+    queries.add_to_history("example");
+    queries.add_to_history("  more ");
+    queries.add_to_history("");
+    queries.add_to_history("example");
+    assert_eq!(vec!["example", "more"], Vec::from(queries.history.clone()));
+
+    for i in 0..(MAX_HISTORY + 5) {
+        queries.add_to_history(&format!("query {i}"));
+    }
+    assert_eq!(MAX_HISTORY, queries.history.len());
+    assert_eq!(
+        Some(&format!("query {}", MAX_HISTORY + 4)),
+        queries.history.front()
+    );
+}
+
+#[test]
+fn export_match_counts() {
+    let mut updates = GraphUpdate::new();
+    example_generator::create_corpus_structure_simple(&mut updates);
+    example_generator::create_tokens(&mut updates, Some("root/doc1"));
+    let graph = example_generator::create_graph(&mut updates);
+    let index = TextIndex::build(&graph).unwrap();
+
+    let mut queries = QueryCollection::default();
+    queries
+        .saved
+        .insert("Question".to_string(), "?".to_string());
+    queries.add_to_history("?");
+    queries.add_to_history("unknownword");
+
+    let mut output = Vec::new();
+    queries.write_match_counts(&index, &mut output).unwrap();
+    assert_eq!(
+        "name,query,matches\nQuestion,?,1\n,unknownword,0\n",
+        String::from_utf8(output).unwrap()
+    );
+}
@@ -0,0 +1,219 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use egui::{CollapsingHeader, ScrollArea, TextEdit, Widget};
+use egui_phosphor::regular::{ARROWS_CLOCKWISE, FILE_TEXT, FOLDER, TRASH};
+use graphannis::graph::NodeID;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::util::virtual_collection::{matching_documents, CollectionDocument},
+    AnnatomicApp,
+};
+
+/// Saved AQL queries that are shown as folders of the documents with
+/// matches, so documents can be organized by phenomenon instead of the
+/// corpus structure.
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct VirtualCollectionsDialog {
+    #[serde(skip)]
+    pub(crate) open: bool,
+    #[serde(skip)]
+    new_name: String,
+    #[serde(skip)]
+    new_query: String,
+    /// The corpus the results have been queried for.
+    #[serde(skip)]
+    results_corpus: Option<String>,
+    /// Matching documents or the error message by the name of the collection.
+    #[serde(skip)]
+    results: BTreeMap<String, Result<Vec<CollectionDocument>, String>>,
+    #[serde(skip)]
+    loading: BTreeSet<String>,
+    /// The queries of the collections by their name and the name of the corpus.
+    collections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl VirtualCollectionsDialog {
+    /// The collections by the name of the corpus.
+    pub(crate) fn collections(&self) -> BTreeMap<String, BTreeMap<String, String>> {
+        self.collections
+            .iter()
+            .filter(|(_, collections)| !collections.is_empty())
+            .map(|(corpus_name, collections)| (corpus_name.clone(), collections.clone()))
+            .collect()
+    }
+
+    /// Add collections for a corpus, existing collections with the same name
+    /// are replaced.
+    pub(crate) fn add_collections(
+        &mut self,
+        corpus_name: &str,
+        collections: BTreeMap<String, String>,
+    ) {
+        self.collections
+            .entry(corpus_name.to_string())
+            .or_default()
+            .extend(collections);
+    }
+
+    pub(crate) fn clear_collections(&mut self) {
+        self.collections.clear();
+        self.results.clear();
+    }
+}
+
+fn query_collection(app: &mut AnnatomicApp, corpus_name: String, name: String, query: String) {
+    let Some(selected_corpus) = app.project.selected_corpus.clone() else {
+        return;
+    };
+    let corpus_cache = app.project.corpus_cache.clone();
+    app.virtual_collections.loading.insert(name.clone());
+    app.jobs.add(
+        &format!("Querying virtual collection \"{name}\""),
+        move |job| {
+            job.update_message("Loading corpus if necessary");
+            let graph = corpus_cache.get(&selected_corpus.location)?;
+            let mut graph = graph.write();
+            graph.ensure_loaded_all()?;
+            job.update_message("Executing query");
+            // Invalid queries are shown in the collection instead of a notification
+            let result = matching_documents(&graph, &query).map_err(|e| format!("{e:#}"));
+            Ok((name, result))
+        },
+        move |(name, result), app| {
+            let dialog = &mut app.virtual_collections;
+            dialog.loading.remove(&name);
+            if dialog.results_corpus.as_ref() == Some(&corpus_name) {
+                dialog.results.insert(name, result);
+            }
+        },
+    );
+}
+
+/// Show the virtual collections if the window has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.virtual_collections.open {
+        return;
+    }
+    let Some(corpus_name) = app.project.selected_corpus.as_ref().map(|c| c.name.clone()) else {
+        app.virtual_collections.open = false;
+        return;
+    };
+    let dialog = &mut app.virtual_collections;
+    if dialog.results_corpus.as_ref() != Some(&corpus_name) {
+        dialog.results.clear();
+        dialog.results_corpus = Some(corpus_name.clone());
+    }
+
+    let mut open = dialog.open;
+    let mut requested = Vec::new();
+    let mut selected_document: Option<(NodeID, usize)> = None;
+    egui::Window::new("Virtual collections")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                TextEdit::singleline(&mut dialog.new_name)
+                    .hint_text("Name")
+                    .desired_width(120.0)
+                    .ui(ui);
+                TextEdit::singleline(&mut dialog.new_query)
+                    .hint_text("AQL query, e.g. pos=\"VVFIN\"")
+                    .desired_width(250.0)
+                    .ui(ui);
+                if ui
+                    .add_enabled(
+                        !dialog.new_name.trim().is_empty() && !dialog.new_query.trim().is_empty(),
+                        egui::Button::new("Add collection"),
+                    )
+                    .clicked()
+                {
+                    let name = std::mem::take(&mut dialog.new_name).trim().to_string();
+                    let query = std::mem::take(&mut dialog.new_query).trim().to_string();
+                    dialog.results.remove(&name);
+                    dialog
+                        .collections
+                        .entry(corpus_name.clone())
+                        .or_default()
+                        .insert(name, query);
+                }
+            });
+            ui.separator();
+
+            let collections = dialog.collections.entry(corpus_name.clone()).or_default();
+            if collections.is_empty() {
+                ui.label("Add a query to show the documents with matches as a collection.");
+            }
+            let mut deleted = None;
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (name, query) in collections.iter() {
+                    CollapsingHeader::new(format!("{FOLDER} {name}"))
+                        .id_salt(name)
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(query).monospace());
+                                if ui
+                                    .small_button(ARROWS_CLOCKWISE)
+                                    .on_hover_text("Query the collection again")
+                                    .clicked()
+                                {
+                                    dialog.results.remove(name);
+                                }
+                                if ui
+                                    .small_button(TRASH)
+                                    .on_hover_text("Delete collection")
+                                    .clicked()
+                                {
+                                    deleted = Some(name.clone());
+                                }
+                            });
+                            match dialog.results.get(name) {
+                                None => {
+                                    if !dialog.loading.contains(name) {
+                                        requested.push((name.clone(), query.clone()));
+                                    }
+                                    ui.horizontal(|ui| {
+                                        ui.spinner();
+                                        ui.label("Querying…");
+                                    });
+                                }
+                                Some(Err(e)) => {
+                                    ui.label(format!("Invalid query: {e}"));
+                                }
+                                Some(Ok(documents)) if documents.is_empty() => {
+                                    ui.label("No matching documents");
+                                }
+                                Some(Ok(documents)) => {
+                                    for d in documents {
+                                        if ui
+                                            .link(format!("{FILE_TEXT} {}", d.document_name))
+                                            .on_hover_text(format!(
+                                                "{} match(es), click to open the first one",
+                                                d.number_of_matches
+                                            ))
+                                            .clicked()
+                                        {
+                                            selected_document = Some((d.document, d.first_match));
+                                        }
+                                    }
+                                }
+                            }
+                        });
+                }
+            });
+            if let Some(name) = deleted {
+                collections.remove(&name);
+                dialog.results.remove(&name);
+            }
+        });
+    app.virtual_collections.open = open;
+
+    for (name, query) in requested {
+        query_collection(app, corpus_name.clone(), name, query);
+    }
+    if let Some((document, token_index)) = selected_document {
+        app.navigate_to_token(document, token_index);
+    }
+}
@@ -0,0 +1,188 @@
+use egui::ComboBox;
+use egui_extras::{Column, TableBuilder};
+use egui_notify::Toast;
+
+use crate::{
+    app::util::whitespace::{
+        normalization_update, whitespace_changes, PunctuationSpacing, WhitespaceChange,
+    },
+    AnnatomicApp,
+};
+
+const JOB_TITLE: &str = "Checking whitespace";
+
+/// State of the window that finds inconsistent whitespace annotations and
+/// normalizes them.
+#[derive(Default, Clone)]
+pub(crate) struct WhitespaceDialog {
+    pub(crate) open: bool,
+    spacing: PunctuationSpacing,
+    /// The proposed changes and whether they are applied.
+    changes: Option<Vec<(WhitespaceChange, bool)>>,
+}
+
+fn scan(app: &mut AnnatomicApp) {
+    let spacing = app.whitespace.spacing;
+    if let Some(selected_corpus) = app.project.selected_corpus.clone() {
+        let corpus_cache = app.project.corpus_cache.clone();
+        app.jobs.add(
+            JOB_TITLE,
+            move |job| {
+                job.update_message("Loading corpus if necessary");
+                let graph = corpus_cache.get(&selected_corpus.location)?;
+                let mut graph = graph.write();
+                graph.ensure_loaded_all()?;
+                job.update_message("Checking whitespace of all token");
+                whitespace_changes(&graph, spacing)
+            },
+            |changes, app| {
+                app.whitespace.changes = Some(changes.into_iter().map(|c| (c, true)).collect());
+            },
+        );
+    }
+}
+
+/// Show whitespace with escaped line breaks and tabs.
+fn visible_whitespace(value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!("{value:?}"),
+        None => "(none)".to_string(),
+    }
+}
+
+/// Show the whitespace normalization window if it has been opened.
+pub(crate) fn show_window(ctx: &egui::Context, app: &mut AnnatomicApp) {
+    if !app.whitespace.open {
+        return;
+    }
+    if app.project.selected_corpus.is_none() {
+        app.whitespace = WhitespaceDialog::default();
+        return;
+    }
+
+    let mut open = app.whitespace.open;
+    let mut start = false;
+    let mut apply = false;
+    let scanning = app.jobs.has_active_job_with_title(JOB_TITLE);
+    egui::Window::new("Normalize whitespace")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut app.whitespace;
+            ui.horizontal(|ui| {
+                ComboBox::from_label("Punctuation")
+                    .selected_text(dialog.spacing.description())
+                    .show_ui(ui, |ui| {
+                        for spacing in PunctuationSpacing::ALL {
+                            ui.selectable_value(
+                                &mut dialog.spacing,
+                                spacing,
+                                spacing.description(),
+                            );
+                        }
+                    });
+                if ui
+                    .add_enabled(!scanning, egui::Button::new("Check corpus"))
+                    .clicked()
+                {
+                    start = true;
+                }
+            });
+            let Some(changes) = &mut dialog.changes else {
+                return;
+            };
+            ui.separator();
+            if changes.is_empty() {
+                ui.label("The whitespace of all token is consistent.");
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.label(format!("{} proposed changes", changes.len()));
+                if ui.button("Select all").clicked() {
+                    changes
+                        .iter_mut()
+                        .for_each(|(_, selected)| *selected = true);
+                }
+                if ui.button("Select none").clicked() {
+                    changes
+                        .iter_mut()
+                        .for_each(|(_, selected)| *selected = false);
+                }
+            });
+            TableBuilder::new(ui)
+                .striped(true)
+                .max_scroll_height(400.0)
+                .column(Column::auto())
+                .columns(Column::auto().at_least(80.0).resizable(true), 2)
+                .columns(Column::auto(), 3)
+                .header(20.0, |mut header| {
+                    for title in [
+                        "",
+                        "Token",
+                        "Annotation",
+                        "Current",
+                        "Normalized",
+                        "Problem",
+                    ] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(20.0, changes.len(), |mut row| {
+                        let (change, selected) = &mut changes[row.index()];
+                        row.col(|ui| {
+                            ui.checkbox(selected, "");
+                        });
+                        row.col(|ui| {
+                            ui.label(&change.node_name);
+                        });
+                        row.col(|ui| {
+                            ui.label(change.anno_name);
+                        });
+                        row.col(|ui| {
+                            ui.monospace(visible_whitespace(change.old_value.as_deref()));
+                        });
+                        row.col(|ui| {
+                            ui.monospace(visible_whitespace(Some(&change.new_value)));
+                        });
+                        row.col(|ui| {
+                            ui.label(change.problem.to_string());
+                        });
+                    });
+                });
+            let selected = changes.iter().filter(|(_, selected)| *selected).count();
+            if ui
+                .add_enabled(
+                    selected > 0,
+                    egui::Button::new(format!("Apply {selected} changes")),
+                )
+                .clicked()
+            {
+                apply = true;
+            }
+        });
+
+    if start && !scanning {
+        app.whitespace.changes = None;
+        scan(app);
+    }
+    if apply {
+        let changes = app.whitespace.changes.take().unwrap_or_default();
+        match normalization_update(changes.iter().filter(|(_, s)| *s).map(|(c, _)| c)) {
+            Ok(update) => {
+                let number_of_changes = update.len().unwrap_or_default();
+                app.apply_pending_updates();
+                app.project.add_changeset_then(update, move |app| {
+                    app.notifier.add_toast(Toast::info(format!(
+                        "Normalized {number_of_changes} whitespace annotation(s)."
+                    )));
+                    app.load_editor(true);
+                });
+            }
+            Err(e) => app.notifier.report_error(e),
+        }
+    }
+    app.whitespace.open = open;
+}
@@ -1,15 +1,28 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 use anyhow::{Context, Result};
-use egui::{Frame, Label, RichText, Sense, Widget, WidgetInfo};
+use egui::{
+    epaint::Galley, Color32, DragValue, Frame, Grid, Label, RichText, Sense, Shape, Stroke,
+    TextStyle, TextWrapMode, Vec2, Widget, WidgetInfo, WidgetText,
+};
 use graphannis::{
     graph::{AnnoKey, NodeID},
     AnnotationGraph,
 };
 use graphannis_core::graph::{ANNIS_NS, NODE_NAME_KEY};
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
 
-use super::util::{make_whitespace_visible, token_helper::TOKEN_KEY};
+use crate::api::token_helper::TOKEN_KEY;
+
+use super::util::{
+    make_whitespace_visible,
+    value_type::{format_date, format_number, number_value, parse_date, ValueType},
+};
 
 lazy_static! {
     static ref WITESPACE_BEFORE: Arc<AnnoKey> = Arc::from(AnnoKey {
@@ -53,6 +66,57 @@ impl Token {
     }
 }
 
+/// What is displayed in the small header of each token.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenHeader {
+    /// The start and end index of the covered base token.
+    #[default]
+    Index,
+    /// The full node name.
+    NodeName,
+    /// The part of the node name after the document name.
+    ShortId,
+    /// The covered base token index and the short ID.
+    IndexAndShortId,
+}
+
+impl TokenHeader {
+    pub const ALL: [TokenHeader; 4] = [
+        TokenHeader::Index,
+        TokenHeader::NodeName,
+        TokenHeader::ShortId,
+        TokenHeader::IndexAndShortId,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            TokenHeader::Index => "Token index",
+            TokenHeader::NodeName => "Node name",
+            TokenHeader::ShortId => "Short ID",
+            TokenHeader::IndexAndShortId => "Token index and short ID",
+        }
+    }
+
+    fn text(&self, token: &Token) -> String {
+        let token_range = if token.start == token.end {
+            token.start.to_string()
+        } else {
+            format!("{}-{}", token.start, token.end)
+        };
+        let short_id = token
+            .node_name
+            .rsplit_once('#')
+            .map(|(_, id)| id)
+            .unwrap_or(&token.node_name);
+        match self {
+            TokenHeader::Index => token_range,
+            TokenHeader::NodeName => token.node_name.clone(),
+            TokenHeader::ShortId => short_id.to_string(),
+            TokenHeader::IndexAndShortId => format!("{token_range} ({short_id})"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TokenEditor<'t> {
     token: &'t Token,
@@ -62,6 +126,15 @@ pub struct TokenEditor<'t> {
     value: String,
     whitespace_before: String,
     whitespace_after: String,
+    hidden_labels: Option<&'t BTreeSet<AnnoKey>>,
+    label_order: &'t [AnnoKey],
+    header: TokenHeader,
+    highlighted: bool,
+    layer_color: Option<Color32>,
+    opacity: f32,
+    max_labels: Option<usize>,
+    painted: bool,
+    details_tooltip: bool,
 }
 
 impl<'t> TokenEditor<'t> {
@@ -86,6 +159,15 @@ impl<'t> TokenEditor<'t> {
                 .get(&WITESPACE_AFTER)
                 .map(make_whitespace_visible)
                 .unwrap_or_default(),
+            hidden_labels: None,
+            label_order: &[],
+            header: TokenHeader::default(),
+            highlighted: false,
+            layer_color: None,
+            opacity: 1.0,
+            max_labels: None,
+            painted: false,
+            details_tooltip: false,
         }
     }
     pub fn with_min_width(token: &'t Token, selected: bool, min_width: Option<f32>) -> Self {
@@ -109,16 +191,261 @@ impl<'t> TokenEditor<'t> {
                 .get(&WITESPACE_AFTER)
                 .map(make_whitespace_visible)
                 .unwrap_or_default(),
+            hidden_labels: None,
+            label_order: &[],
+            header: TokenHeader::default(),
+            highlighted: false,
+            layer_color: None,
+            opacity: 1.0,
+            max_labels: None,
+            painted: false,
+            details_tooltip: false,
         }
     }
+
+    /// Show the labels with these annotation keys first, in the given order.
+    /// All other labels follow ordered by their namespace and name.
+    pub fn label_order(mut self, label_order: &'t [AnnoKey]) -> Self {
+        self.label_order = label_order;
+        self
+    }
+
+    /// Do not show the labels with the given annotation keys.
+    pub fn hide_labels(mut self, hidden_labels: &'t BTreeSet<AnnoKey>) -> Self {
+        self.hidden_labels = Some(hidden_labels);
+        self
+    }
+
+    /// Mark the token with a border, e.g. because it is at the current media
+    /// position.
+    pub fn highlight(mut self, highlighted: bool) -> Self {
+        self.highlighted = highlighted;
+        self
+    }
+
+    /// Mark the token with a colored bar at its left side to show to which
+    /// layer it belongs.
+    pub fn layer_color(mut self, color: Color32) -> Self {
+        self.layer_color = Some(color);
+        self
+    }
+
+    /// Draw the token with the given opacity, e.g. to make it less prominent
+    /// because it is filtered out. The token still uses the same space.
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Select what is displayed in the header of the token.
+    pub fn header(mut self, header: TokenHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Only show this many labels and a "+N more" indicator for the others,
+    /// which are listed when hovering the token. Selected token always show
+    /// all their labels.
+    pub fn max_labels(mut self, max_labels: Option<usize>) -> Self {
+        self.max_labels = max_labels;
+        self
+    }
+
+    /// Paint the frame and all texts of the token in one pass instead of
+    /// nesting a frame, layouts and a label widget for each line. This is
+    /// much cheaper for large documents, but the lines of the token are not
+    /// separate widgets anymore.
+    pub fn painted(mut self, painted: bool) -> Self {
+        self.painted = painted;
+        self
+    }
+
+    /// Show the whitespace before and after the value as visible characters,
+    /// which is the default. Disable it when the whitespace is displayed in
+    /// another way, e.g. as space between the token.
+    pub fn show_whitespace(mut self, show_whitespace: bool) -> Self {
+        if !show_whitespace {
+            self.whitespace_before.clear();
+            self.whitespace_after.clear();
+        }
+        self
+    }
+
+    /// Show the node name, the covered token and all annotations in a
+    /// tooltip when hovering the token.
+    pub fn details_tooltip(mut self, details_tooltip: bool) -> Self {
+        self.details_tooltip = details_tooltip;
+        self
+    }
+
+    /// The labels that are not hidden and not in the ANNIS namespace, sorted
+    /// by the label order.
+    fn visible_labels(&self) -> Vec<(String, &'t str)> {
+        let mut labels: Vec<_> = self
+            .token
+            .labels
+            .iter()
+            .filter(|(key, _)| {
+                key.ns != ANNIS_NS && !self.hidden_labels.is_some_and(|h| h.contains(*key))
+            })
+            .collect();
+        // The sort is stable, so the other labels keep their order
+        labels.sort_by_key(|(key, _)| label_rank(self.label_order, key));
+        labels
+            .into_iter()
+            .map(|(key, value)| {
+                let key_label = if key.ns.is_empty() {
+                    key.name.to_string()
+                } else {
+                    format!("{}:{}", key.ns, key.name)
+                };
+                (key_label, value.as_str())
+            })
+            .collect()
+    }
 }
 
-impl Widget for TokenEditor<'_> {
-    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+/// Position of the annotation key in the label order, keys that are not part
+/// of it are sorted after all others.
+pub(crate) fn label_rank(label_order: &[AnnoKey], key: &AnnoKey) -> usize {
+    label_order
+        .iter()
+        .position(|k| k == key)
+        .unwrap_or(label_order.len())
+}
+
+/// List the node name, the covered base token and all annotations of the
+/// token or span, including the ones in the ANNIS namespace.
+pub(crate) fn token_details(ui: &mut egui::Ui, token: &Token) {
+    ui.label(RichText::new(&token.node_name).strong());
+    let covered = if token.start == token.end {
+        format!("Covers token {}", token.start)
+    } else {
+        format!("Covers token {} to {}", token.start, token.end)
+    };
+    ui.label(RichText::new(covered).weak());
+    ui.separator();
+    Grid::new("token-details")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for (key, value) in token.labels.iter() {
+                if key == NODE_NAME_KEY.as_ref() {
+                    continue;
+                }
+                let key_label = if key.ns.is_empty() {
+                    key.name.to_string()
+                } else {
+                    format!("{}:{}", key.ns, key.name)
+                };
+                ui.label(RichText::new(key_label).weak());
+                ui.label(make_whitespace_visible(value));
+                ui.end_row();
+            }
+        });
+}
+
+/// Edit an annotation value with a widget that fits its type, e.g. a
+/// checkbox for boolean values. A value that does not match the type is
+/// replaced by the default value of the type. Values without a type are
+/// edited as text.
+pub(crate) fn value_editor(
+    ui: &mut egui::Ui,
+    value_type: Option<&ValueType>,
+    value: &mut String,
+) -> egui::Response {
+    let Some(value_type) = value_type else {
+        return ui.text_edit_singleline(value);
+    };
+    if value_type.validate(value).is_err() {
+        *value = value_type.default_value();
+    }
+    match value_type {
+        ValueType::Number { min, max, integer } => {
+            let mut number = number_value(value, *integer).unwrap_or_default();
+            let speed = match (min, max) {
+                _ if *integer => 1.0,
+                (Some(min), Some(max)) => (max - min) / 100.0,
+                _ => 0.1,
+            };
+            let response = ui.add(
+                DragValue::new(&mut number)
+                    .range(min.unwrap_or(f64::NEG_INFINITY)..=max.unwrap_or(f64::INFINITY))
+                    .speed(speed)
+                    .max_decimals(if *integer { 0 } else { 6 }),
+            );
+            if response.changed() {
+                *value = format_number(number, *integer);
+            }
+            response
+        }
+        ValueType::Boolean => {
+            let mut checked = value == "true";
+            let response = ui.checkbox(&mut checked, value.as_str());
+            if response.changed() {
+                *value = checked.to_string();
+            }
+            response
+        }
+        ValueType::Date => {
+            let Ok(date) = parse_date(value) else {
+                return ui.label(value.as_str());
+            };
+            let mut year = date.year();
+            let mut month = u8::from(date.month());
+            let mut day = date.day();
+            let response = ui
+                .horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 2.0;
+                    let mut response = ui.add(DragValue::new(&mut year).range(1..=9999));
+                    ui.label("-");
+                    response |= ui.add(DragValue::new(&mut month).range(1..=12).speed(0.1));
+                    ui.label("-");
+                    response |= ui.add(DragValue::new(&mut day).range(1..=31).speed(0.1));
+                    response
+                })
+                .inner;
+            if response.changed() {
+                // Use the last day of the month if the day does not exist in it
+                let date = Month::try_from(month).ok().and_then(|month| {
+                    (1..=day)
+                        .rev()
+                        .find_map(|day| Date::from_calendar_date(year, month, day).ok())
+                });
+                if let Some(date) = date {
+                    *value = format_date(date);
+                }
+            }
+            response
+        }
+    }
+}
+
+impl TokenEditor<'_> {
+    /// Number of labels that are shown, the others are only listed when
+    /// hovering the token.
+    fn shown_labels(&self, nr_labels: usize) -> usize {
+        match self.max_labels {
+            Some(max_labels) if !self.selected => max_labels.min(nr_labels),
+            _ => nr_labels,
+        }
+    }
+
+    fn frame(&self, ui: &egui::Ui) -> Frame {
         let mut g = Frame::group(ui.style());
         if self.selected {
             g.fill = ui.style().visuals.selection.bg_fill;
         }
+        if self.highlighted {
+            g.stroke = Stroke::new(2.0, ui.style().visuals.warn_fg_color);
+        }
+        g
+    }
+
+    fn show_token(self, ui: &mut egui::Ui) -> egui::Response {
+        let labels = self.visible_labels();
+        let shown_labels = self.shown_labels(labels.len());
+        let g = self.frame(ui);
         let group_response = g.show(ui, |ui| {
             if let Some(width) = self.width {
                 let width: f32 = width
@@ -135,12 +462,8 @@ impl Widget for TokenEditor<'_> {
             ui.vertical(|ui| {
                 // Add the token information as first line
                 ui.horizontal(|ui| {
-                    let token_range = if self.token.start == self.token.end {
-                        self.token.start.to_string()
-                    } else {
-                        format!("{}-{}", self.token.start, self.token.end)
-                    };
-                    ui.label(RichText::new(token_range).weak().small())
+                    let header = self.header.text(self.token);
+                    ui.label(RichText::new(header).weak().small())
                 });
                 if !self.value.is_empty()
                     || !self.whitespace_before.is_empty()
@@ -158,31 +481,161 @@ impl Widget for TokenEditor<'_> {
                     });
                 }
                 // Show all other labels
-                for (key, value) in self.token.labels.iter() {
-                    if key.ns != ANNIS_NS {
-                        let key_label = if key.ns.is_empty() {
-                            key.name.to_string()
-                        } else {
-                            format!("{}:{}", key.ns, key.name)
-                        };
-
-                        ui.horizontal(|ui| {
-                            Label::new(value)
-                                .wrap_mode(egui::TextWrapMode::Extend)
-                                .ui(ui);
-                            Label::new(RichText::new(key_label).weak().small_raised())
-                                .wrap_mode(egui::TextWrapMode::Extend)
-                                .ui(ui);
-                        });
-                    }
+                for (key_label, value) in labels.iter().take(shown_labels) {
+                    ui.horizontal(|ui| {
+                        Label::new(*value)
+                            .wrap_mode(egui::TextWrapMode::Extend)
+                            .ui(ui);
+                        Label::new(RichText::new(key_label).weak().small_raised())
+                            .wrap_mode(egui::TextWrapMode::Extend)
+                            .ui(ui);
+                    });
+                }
+                if shown_labels < labels.len() {
+                    ui.label(
+                        RichText::new(format!("+{} more", labels.len() - shown_labels))
+                            .weak()
+                            .small(),
+                    );
                 }
             });
         });
 
+        if let Some(color) = self.layer_color {
+            let rect = group_response.response.rect;
+            ui.painter().vline(
+                rect.left() + 1.5,
+                rect.y_range().shrink(2.0),
+                Stroke::new(3.0, color),
+            );
+        }
+
         let response = group_response
             .response
             .interact(Sense::click())
             .interact(Sense::hover());
+        self.finish_response(ui, response, &labels, shown_labels)
+    }
+
+    fn show_painted(self, ui: &mut egui::Ui) -> egui::Response {
+        let labels = self.visible_labels();
+        let shown_labels = self.shown_labels(labels.len());
+        let g = self.frame(ui);
+        let spacing = ui.spacing().item_spacing;
+        // Rows of a horizontal layout have at least this height
+        let row_height = ui.spacing().interact_size.y;
+        let layout = |text: RichText| {
+            WidgetText::from(text).into_galley(
+                ui,
+                Some(TextWrapMode::Extend),
+                f32::INFINITY,
+                TextStyle::Body,
+            )
+        };
+
+        // Layout each line of the token as a sequence of galleys
+        let mut lines: Vec<Vec<Arc<Galley>>> = Vec::with_capacity(shown_labels + 3);
+        lines.push(vec![layout(
+            RichText::new(self.header.text(self.token)).weak().small(),
+        )]);
+        if !self.value.is_empty()
+            || !self.whitespace_before.is_empty()
+            || !self.whitespace_after.is_empty()
+        {
+            let mut line = Vec::with_capacity(3);
+            if !self.whitespace_before.is_empty() {
+                line.push(layout(RichText::new(&self.whitespace_before).weak()));
+            }
+            line.push(layout(RichText::new(&self.value).strong()));
+            if !self.whitespace_after.is_empty() {
+                line.push(layout(RichText::new(&self.whitespace_after).weak()));
+            }
+            lines.push(line);
+        }
+        for (key_label, value) in labels.iter().take(shown_labels) {
+            lines.push(vec![
+                layout(RichText::new(*value)),
+                layout(RichText::new(key_label).weak().small_raised()),
+            ]);
+        }
+        if shown_labels < labels.len() {
+            lines.push(vec![layout(
+                RichText::new(format!("+{} more", labels.len() - shown_labels))
+                    .weak()
+                    .small(),
+            )]);
+        }
+        let line_sizes: Vec<Vec2> = lines
+            .iter()
+            .map(|line| {
+                let width = line.iter().map(|g| g.size().x).sum::<f32>()
+                    + spacing.x * (line.len().saturating_sub(1)) as f32;
+                let height = line.iter().map(|g| g.size().y).fold(row_height, f32::max);
+                Vec2::new(width, height)
+            })
+            .collect();
+
+        let margin = g.total_margin().sum();
+        let mut content_size = Vec2::new(
+            line_sizes.iter().map(|s| s.x).fold(0.0, f32::max),
+            line_sizes.iter().map(|s| s.y).sum::<f32>()
+                + spacing.y * (lines.len().saturating_sub(1)) as f32,
+        );
+        if let Some(width) = self.width {
+            content_size.x = width - margin.x;
+        } else if let Some(min_width) = self.min_width {
+            content_size.x = content_size.x.max(min_width);
+        }
+        let (rect, response) = ui.allocate_exact_size(content_size + margin, Sense::click());
+
+        if ui.is_rect_visible(rect) {
+            // Collect all shapes and add them to the painter at once
+            let content_rect = rect - g.total_margin();
+            let text_color = ui.visuals().text_color();
+            let mut shapes = Vec::with_capacity(lines.len() * 2 + 2);
+            shapes.push(g.paint(content_rect));
+            if let Some(color) = self.layer_color {
+                shapes.push(Shape::vline(
+                    rect.left() + 1.5,
+                    rect.y_range().shrink(2.0),
+                    Stroke::new(3.0, color),
+                ));
+            }
+            let mut y = content_rect.top();
+            for (line, size) in lines.into_iter().zip(line_sizes) {
+                let mut x = content_rect.left();
+                for galley in line {
+                    // Center the galleys of a line vertically
+                    let pos = egui::pos2(x, y + (size.y - galley.size().y) / 2.0);
+                    x += galley.size().x + spacing.x;
+                    shapes.push(Shape::galley(pos, galley, text_color));
+                }
+                y += size.y + spacing.y;
+            }
+            ui.painter().extend(shapes);
+        }
+
+        self.finish_response(ui, response, &labels, shown_labels)
+    }
+
+    /// Add the hover list of hidden labels, the accessibility information and
+    /// the cursor icon to the response of the token.
+    fn finish_response(
+        &self,
+        ui: &egui::Ui,
+        mut response: egui::Response,
+        labels: &[(String, &str)],
+        shown_labels: usize,
+    ) -> egui::Response {
+        if self.details_tooltip {
+            response = response.on_hover_ui(|ui| token_details(ui, self.token));
+        } else if shown_labels < labels.len() {
+            response = response.on_hover_ui(|ui| {
+                for (key_label, value) in labels.iter().skip(shown_labels) {
+                    ui.label(format!("{key_label}={value}"));
+                }
+            });
+        }
         let widget_label = if self.selected {
             format!(
                 "Selected token ranging from {} to {} ({})",
@@ -204,4 +657,27 @@ impl Widget for TokenEditor<'_> {
 
         response
     }
+
+    fn show(self, ui: &mut egui::Ui) -> egui::Response {
+        if self.painted {
+            self.show_painted(ui)
+        } else {
+            self.show_token(ui)
+        }
+    }
+}
+
+impl Widget for TokenEditor<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        if self.opacity < 1.0 {
+            let opacity = self.opacity;
+            ui.scope(|ui| {
+                ui.multiply_opacity(opacity);
+                self.show(ui)
+            })
+            .inner
+        } else {
+            self.show(ui)
+        }
+    }
 }
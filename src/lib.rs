@@ -1,4 +1,5 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+pub mod api;
 mod app;
-pub use app::{AnnatomicApp, AnnatomicArgs};
+pub use app::{util::diagnostics::init_logging, AnnatomicApp, AnnatomicArgs};
@@ -1,13 +1,13 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use annatomic::{AnnatomicApp, AnnatomicArgs};
+use annatomic::{init_logging, AnnatomicApp, AnnatomicArgs};
 use clap::Parser;
 
 fn main() -> eframe::Result {
     let args = AnnatomicArgs::parse();
 
-    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    init_logging(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -19,7 +19,8 @@ fn main() -> eframe::Result {
                 eframe::icon_data::from_png_bytes(&include_bytes!("../assets/icon-256.png")[..])
                     .expect("Failed to load icon"),
             ),
-        persist_window: true,
+        // Safe mode also resets the window position and size
+        persist_window: !args.safe_mode(),
         ..Default::default()
     };
 